@@ -0,0 +1,302 @@
+//! Benchmarks the execution of a representative block: transfers among a few funded accounts, in
+//! an ERC20 fee token, along with the declaration of a large Cairo 1 class. The state and the
+//! transactions are deterministic, and built from the feature contracts; no network access is
+//! required.
+//!
+//! Run using `cargo bench --features testing --bench blockifier_bench`; criterion reports the
+//! change relative to the previous run. To compare against a fixed baseline (e.g., the main
+//! branch), save it using `cargo bench --features testing --bench blockifier_bench --
+//! --save-baseline main`, and compare later runs to it by passing `--baseline main` instead. The
+//! reports are written to `target/criterion`.
+
+use std::collections::HashMap;
+
+use blockifier::abi::abi_utils::get_erc20_balance_var_addresses;
+use blockifier::block_context::BlockContext;
+use blockifier::blockifier::bouncer::BouncerConfig;
+use blockifier::blockifier::transaction_executor::TransactionExecutor;
+use blockifier::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use blockifier::selector;
+use blockifier::state::cached_state::{CachedState, ContractStorageKey, GlobalContractCache};
+use blockifier::state::errors::StateError;
+use blockifier::state::state_api::{StateReader, StateResult};
+use blockifier::test_utils::{
+    get_raw_contract_class, DictStateReader, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH, BALANCE,
+    ERC20_CONTRACT_PATH, MAX_FEE, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+    TEST_CONTRACT_CAIRO1_PATH, TEST_ERC20_CONTRACT_CLASS_HASH,
+};
+use blockifier::transaction::account_transaction::AccountTransaction;
+use blockifier::transaction::test_utils::account_invoke_tx;
+use blockifier::transaction::transaction_execution::Transaction;
+use blockifier::transaction::transactions::{DeclareTransaction, ExecutableTransaction};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{
+    Calldata, DeclareTransactionV2, Fee, TransactionHash, TransactionSignature,
+};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+const N_ACCOUNTS: usize = 4;
+const N_BLOCK_TRANSFERS: usize = 100;
+
+/// The initial state: `N_ACCOUNTS` instances of the Cairo 0 account (without validations), each
+/// holding `BALANCE` in the fee token, and the fee token itself.
+struct Fixture {
+    block_context: BlockContext,
+    account_addresses: Vec<ContractAddress>,
+    class_hash_to_class: HashMap<ClassHash, ContractClass>,
+    address_to_class_hash: HashMap<ContractAddress, ClassHash>,
+    storage_view: HashMap<ContractStorageKey, StarkFelt>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let block_context = BlockContext::create_for_account_testing();
+        let account_class_hash = class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH);
+        let erc20_class_hash = class_hash!(TEST_ERC20_CONTRACT_CLASS_HASH);
+        let class_hash_to_class = HashMap::from([
+            (account_class_hash, ContractClassV0::from_file(ACCOUNT_CONTRACT_CAIRO0_PATH).into()),
+            (erc20_class_hash, ContractClassV0::from_file(ERC20_CONTRACT_PATH).into()),
+        ]);
+
+        let fee_token_addresses = &block_context.fee_token_addresses;
+        let mut address_to_class_hash = HashMap::from([
+            (fee_token_addresses.eth_fee_token_address, erc20_class_hash),
+            (fee_token_addresses.strk_fee_token_address, erc20_class_hash),
+        ]);
+        let account_addresses: Vec<ContractAddress> = (0..N_ACCOUNTS)
+            .map(|i| contract_address!(format!("0x{:x}", 0x2001 + i).as_str()))
+            .collect();
+        let mut storage_view = HashMap::new();
+        for &account_address in &account_addresses {
+            address_to_class_hash.insert(account_address, account_class_hash);
+            let (balance_low_key, _) = get_erc20_balance_var_addresses(&account_address).unwrap();
+            storage_view.insert(
+                (fee_token_addresses.eth_fee_token_address, balance_low_key),
+                stark_felt!(BALANCE),
+            );
+        }
+
+        Self {
+            block_context,
+            account_addresses,
+            class_hash_to_class,
+            address_to_class_hash,
+            storage_view,
+        }
+    }
+
+    fn state_reader(&self) -> DictStateReader {
+        DictStateReader {
+            class_hash_to_class: self.class_hash_to_class.clone(),
+            address_to_class_hash: self.address_to_class_hash.clone(),
+            storage_view: self.storage_view.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns a reader that deserializes the classes on every read, as a reader backed by
+    /// storage would; the global class cache is what saves these reads.
+    fn raw_class_state_reader(&self) -> RawClassStateReader {
+        let raw_classes = HashMap::from([
+            (
+                class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH),
+                get_raw_contract_class(ACCOUNT_CONTRACT_CAIRO0_PATH),
+            ),
+            (
+                class_hash!(TEST_ERC20_CONTRACT_CLASS_HASH),
+                get_raw_contract_class(ERC20_CONTRACT_PATH),
+            ),
+        ]);
+        RawClassStateReader {
+            state_reader: DictStateReader {
+                class_hash_to_class: HashMap::new(),
+                ..self.state_reader()
+            },
+            raw_classes,
+        }
+    }
+
+    /// Returns transfers among the accounts, each sent by the account following the previous
+    /// sender.
+    fn transfers(&self, n_transfers: usize) -> Vec<AccountTransaction> {
+        let fee_token_address = self.block_context.fee_token_addresses.eth_fee_token_address;
+        let mut nonce_manager = NonceManager::default();
+        (0..n_transfers)
+            .map(|i| {
+                let sender_address = self.account_addresses[i % N_ACCOUNTS];
+                let recipient_address = self.account_addresses[(i + 1) % N_ACCOUNTS];
+                let execute_calldata = calldata![
+                    *fee_token_address.0.key(), // Contract address.
+                    selector!("transfer").0,    // EP selector.
+                    stark_felt!(3_u8),          // Calldata length.
+                    *recipient_address.0.key(), // Calldata: recipient.
+                    stark_felt!(1_u8),          // Calldata: amount (low).
+                    stark_felt!(0_u8)           // Calldata: amount (high).
+                ];
+                account_invoke_tx(
+                    execute_calldata,
+                    sender_address,
+                    &mut nonce_manager,
+                    Fee(MAX_FEE),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a declaration of the (large) Cairo 1 test contract.
+    fn cairo1_declare(&self, contract_class: ContractClassV1) -> AccountTransaction {
+        let declare_tx = DeclareTransactionV2 {
+            max_fee: Fee(MAX_FEE),
+            signature: TransactionSignature::default(),
+            nonce: Nonce::default(),
+            class_hash: class_hash!(TEST_CLASS_HASH),
+            compiled_class_hash: CompiledClassHash::default(),
+            sender_address: self.account_addresses[0],
+        };
+        AccountTransaction::Declare(
+            DeclareTransaction::new(
+                starknet_api::transaction::DeclareTransaction::V2(declare_tx),
+                TransactionHash::default(),
+                contract_class.into(),
+            )
+            .unwrap(),
+        )
+    }
+}
+
+/// A state reader holding the classes in their serialized form.
+struct RawClassStateReader {
+    state_reader: DictStateReader,
+    raw_classes: HashMap<ClassHash, String>,
+}
+
+impl StateReader for RawClassStateReader {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        self.state_reader.get_storage_at(contract_address, key)
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        self.state_reader.get_nonce_at(contract_address)
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        self.state_reader.get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        let raw_class =
+            self.raw_classes.get(class_hash).ok_or(StateError::UndeclaredClassHash(*class_hash))?;
+        Ok(ContractClassV0::try_from_json_string(raw_class)?.into())
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.state_reader.get_compiled_class_hash(class_hash)
+    }
+}
+
+fn transfer_benchmark(c: &mut Criterion) {
+    let fixture = Fixture::new();
+    let transfer = fixture.transfers(1).remove(0);
+
+    c.bench_function("transfer", |benchmark| {
+        benchmark.iter_batched(
+            || (CachedState::from(fixture.state_reader()), transfer.clone()),
+            |(mut state, transfer)| {
+                transfer.execute(&mut state, &fixture.block_context, true, true).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn block_benchmark(c: &mut Criterion) {
+    let fixture = Fixture::new();
+    let transfers: Vec<Transaction> = fixture
+        .transfers(N_BLOCK_TRANSFERS)
+        .into_iter()
+        .map(Transaction::AccountTransaction)
+        .collect();
+
+    c.bench_function("transfers_block", |benchmark| {
+        benchmark.iter_batched(
+            || {
+                TransactionExecutor::new(
+                    CachedState::from(fixture.state_reader()),
+                    fixture.block_context.clone(),
+                    BouncerConfig::max(),
+                    true,
+                )
+                .unwrap()
+            },
+            |mut executor| {
+                let results = executor.execute_txs(&transfers, true).unwrap();
+                assert!(results.iter().all(Result::is_ok));
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn declare_benchmark(c: &mut Criterion) {
+    let fixture = Fixture::new();
+    let declare = fixture.cairo1_declare(ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH));
+
+    c.bench_function("cairo1_declare", |benchmark| {
+        benchmark.iter_batched(
+            || (CachedState::from(fixture.state_reader()), declare.clone()),
+            |(mut state, declare)| {
+                declare.execute(&mut state, &fixture.block_context, true, true).unwrap()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// Executes a transfer with the global class cache empty (cold), and with it holding the classes
+/// of the transfer (warm).
+fn class_cache_benchmark(c: &mut Criterion) {
+    let fixture = Fixture::new();
+    let transfer = fixture.transfers(1).remove(0);
+    let warm_cache = GlobalContractCache::default();
+    let mut state = CachedState::new(fixture.raw_class_state_reader(), warm_cache.clone());
+    transfer.clone().execute(&mut state, &fixture.block_context, true, true).unwrap();
+    state.move_classes_to_global_cache();
+
+    let mut group = c.benchmark_group("class_cache");
+    for (name, global_contract_cache) in [("cold", None), ("warm", Some(&warm_cache))] {
+        group.bench_function(name, |benchmark| {
+            benchmark.iter_batched(
+                || {
+                    let global_contract_cache = global_contract_cache.cloned().unwrap_or_default();
+                    let state =
+                        CachedState::new(fixture.raw_class_state_reader(), global_contract_cache);
+                    (state, transfer.clone())
+                },
+                |(mut state, transfer)| {
+                    transfer.execute(&mut state, &fixture.block_context, true, true).unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    transfer_benchmark,
+    block_benchmark,
+    declare_benchmark,
+    class_cache_benchmark
+);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+//! Measures the time to resolve an entry point of a Cairo 1 class, by the number of entry points
+//! of the class; the lookup time should not grow with it.
+//!
+//! Run using `cargo bench --features testing --bench entry_point_lookup`.
+
+use blockifier::abi::abi_utils::selector_from_name;
+use blockifier::execution::contract_class::ContractClassV1;
+use blockifier::execution::entry_point::CallEntryPoint;
+use blockifier::test_utils::{
+    get_raw_contract_class, trivial_external_entry_point, TEST_CONTRACT_CAIRO1_PATH,
+};
+use cairo_lang_starknet::casm_contract_class::{CasmContractClass, CasmContractEntryPoint};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+
+/// Returns the test contract, with its external entry points replaced by the given number of
+/// entry points; returns calls to the new entry points as well.
+fn create_contract_class(n_entry_points: usize) -> (ContractClassV1, Vec<CallEntryPoint>) {
+    let mut casm_contract_class: CasmContractClass =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO1_PATH)).unwrap();
+    let names: Vec<String> = (0..n_entry_points).map(|i| format!("entry_point_{i}")).collect();
+    let calls = names
+        .iter()
+        .map(|name| CallEntryPoint {
+            entry_point_selector: selector_from_name(name),
+            ..trivial_external_entry_point()
+        })
+        .collect::<Vec<_>>();
+    casm_contract_class.entry_points_by_type.external = calls
+        .iter()
+        .map(|call| CasmContractEntryPoint {
+            selector: BigUint::from_bytes_be(call.entry_point_selector.0.bytes()),
+            offset: 0,
+            builtins: vec![],
+        })
+        .collect();
+
+    (casm_contract_class.try_into().unwrap(), calls)
+}
+
+/// Resolves the entry points of classes with different numbers of entry points, in turn.
+fn entry_point_lookup_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("entry_point_lookup");
+    for n_entry_points in [10, 100, 1000, 10000] {
+        let (contract_class, calls) = create_contract_class(n_entry_points);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n_entry_points),
+            &calls,
+            |benchmark, calls| {
+                let mut calls = calls.iter().cycle();
+                benchmark.iter(|| {
+                    let call = calls.next().unwrap();
+                    contract_class.get_entry_point(black_box(call)).unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, entry_point_lookup_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Measures the time to execute a storage-read-heavy call: the ERC20 `balanceOf` entry point,
+//! which normalizes the address of the balance and reads it from storage.
+//!
+//! Run using `cargo bench --features testing --bench storage_reads`.
+
+use std::collections::HashMap;
+
+use blockifier::execution::contract_class::{ContractClass, ContractClassV0};
+use blockifier::execution::entry_point::CallEntryPoint;
+use blockifier::selector;
+use blockifier::state::cached_state::CachedState;
+use blockifier::test_utils::{
+    DictStateReader, ERC20_CONTRACT_PATH, TEST_ERC20_CONTRACT_ADDRESS,
+    TEST_ERC20_CONTRACT_CLASS_HASH,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+const N_CALLS: u32 = 2000;
+
+/// Executes `N_CALLS` calls on a fresh state, each reading the balance of another account.
+fn storage_reads_benchmark(c: &mut Criterion) {
+    let erc20_class_hash = class_hash!(TEST_ERC20_CONTRACT_CLASS_HASH);
+    let erc20_address = contract_address!(TEST_ERC20_CONTRACT_ADDRESS);
+    let erc20_class: ContractClass = ContractClassV0::from_file(ERC20_CONTRACT_PATH).into();
+    let create_state = || {
+        CachedState::from(DictStateReader {
+            class_hash_to_class: HashMap::from([(erc20_class_hash, erc20_class.clone())]),
+            address_to_class_hash: HashMap::from([(erc20_address, erc20_class_hash)]),
+            ..Default::default()
+        })
+    };
+    let calls: Vec<CallEntryPoint> = (0..N_CALLS)
+        .map(|account| CallEntryPoint {
+            class_hash: Some(erc20_class_hash),
+            storage_address: erc20_address,
+            entry_point_selector: selector!("balanceOf"),
+            calldata: calldata![stark_felt!(account + 1)],
+            initial_gas: u64::MAX,
+            ..Default::default()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("storage_reads");
+    group.throughput(Throughput::Elements(N_CALLS.into()));
+    group.sample_size(10);
+    group.bench_function("balance_of", |benchmark| {
+        benchmark.iter_batched(
+            || (create_state(), calls.clone()),
+            |(mut state, calls)| {
+                for call in calls {
+                    call.execute_directly(&mut state).unwrap();
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, storage_reads_benchmark);
+criterion_main!(benches);
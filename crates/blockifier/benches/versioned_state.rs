@@ -0,0 +1,91 @@
+//! Compares the throughput of a read-heavy workload on the versioned state, using 1 and 8 worker
+//! threads; criterion reports it in reads per second.
+//!
+//! Run using `cargo bench --features testing --bench versioned_state`.
+
+use std::collections::HashMap;
+use std::thread;
+
+use blockifier::concurrency::versioned_state::VersionedState;
+use blockifier::state::cached_state::StateMaps;
+use blockifier::test_utils::DictStateReader;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{contract_address, patricia_key, stark_felt};
+
+const N_TXS: usize = 1000;
+const N_KEYS: u64 = 1024;
+const N_READS: usize = 1 << 20;
+// One in this many transactions writes to the cells it reads.
+const WRITING_TX_RATIO: usize = 10;
+
+fn storage_key(key: u64) -> StorageKey {
+    StorageKey(patricia_key!(key))
+}
+
+fn create_versioned_state(contract_address: ContractAddress) -> VersionedState<DictStateReader> {
+    let storage_view =
+        (0..N_KEYS).map(|key| ((contract_address, storage_key(key)), stark_felt!(key))).collect();
+    let versioned_state =
+        VersionedState::new(DictStateReader { storage_view, ..Default::default() });
+
+    for tx_index in (0..N_TXS).step_by(WRITING_TX_RATIO) {
+        let storage = (0..N_KEYS)
+            .step_by(WRITING_TX_RATIO)
+            .map(|key| ((contract_address, storage_key(key)), stark_felt!(key + 1)))
+            .collect();
+        versioned_state.apply_writes(
+            tx_index,
+            &StateMaps { storage, ..Default::default() },
+            &HashMap::new(),
+        );
+    }
+
+    versioned_state
+}
+
+/// Performs `N_READS` reads, split among the given number of workers.
+fn run_reads(
+    versioned_state: &VersionedState<DictStateReader>,
+    contract_address: ContractAddress,
+    n_workers: usize,
+) {
+    let n_reads_per_worker = N_READS / n_workers;
+    thread::scope(|scope| {
+        for worker in 0..n_workers {
+            scope.spawn(move || {
+                for i in 0..n_reads_per_worker {
+                    let read_index = worker * n_reads_per_worker + i;
+                    let key = storage_key(u64::try_from(read_index).unwrap() % N_KEYS);
+                    versioned_state
+                        .get_storage_at(read_index % N_TXS, contract_address, key)
+                        .unwrap();
+                }
+            });
+        }
+    });
+}
+
+fn versioned_state_benchmark(c: &mut Criterion) {
+    let contract_address = contract_address!("0x100");
+    let versioned_state = create_versioned_state(contract_address);
+
+    let mut group = c.benchmark_group("versioned_state_reads");
+    group.throughput(Throughput::Elements(N_READS.try_into().unwrap()));
+    group.sample_size(10);
+    for n_workers in [1, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(n_workers),
+            &n_workers,
+            |benchmark, &n_workers| {
+                benchmark.iter(|| run_reads(&versioned_state, contract_address, n_workers))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, versioned_state_benchmark);
+criterion_main!(benches);
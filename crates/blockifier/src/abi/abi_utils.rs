@@ -41,14 +41,201 @@ pub fn selector_from_name(entry_point_name: &str) -> EntryPointSelector {
     }
 }
 
+/// Returns the entry point selector of the given name, as `selector_from_name` does, hashing the
+/// name at compile time; the name must be a constant expression (e.g., a string literal).
+///
+/// The selector itself is constructed at runtime, as field elements have no const constructor.
+#[macro_export]
+macro_rules! selector {
+    ($entry_point_name:expr) => {{
+        const SELECTOR_BYTES: [u8; 32] =
+            $crate::abi::abi_utils::const_selector_bytes($entry_point_name);
+        $crate::abi::abi_utils::selector_from_bytes(SELECTOR_BYTES)
+    }};
+}
+
+/// The big-endian bytes of the selector of the given entry point name (see `selector!`).
+#[doc(hidden)]
+pub const fn const_selector_bytes(entry_point_name: &str) -> [u8; 32] {
+    let entry_point_name = entry_point_name.as_bytes();
+    if const_bytes_eq(entry_point_name, constants::DEFAULT_ENTRY_POINT_NAME.as_bytes())
+        || const_bytes_eq(entry_point_name, constants::DEFAULT_L1_ENTRY_POINT_NAME.as_bytes())
+    {
+        return u64_to_be_bytes(constants::DEFAULT_ENTRY_POINT_SELECTOR);
+    }
+
+    let mut hash = const_keccak256(entry_point_name);
+    // Truncate the hash to 250 bits, as `starknet_keccak` does.
+    hash[0] &= 3;
+    hash
+}
+
+#[doc(hidden)]
+pub fn selector_from_bytes(bytes: [u8; 32]) -> EntryPointSelector {
+    EntryPointSelector(StarkFelt::new(bytes).expect("A selector must be a field element."))
+}
+
+const fn const_bytes_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < lhs.len() {
+        if lhs[i] != rhs[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn u64_to_be_bytes(value: u64) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    let value_bytes = value.to_be_bytes();
+    let mut i = 0;
+    while i < value_bytes.len() {
+        bytes[32 - value_bytes.len() + i] = value_bytes[i];
+        i += 1;
+    }
+    bytes
+}
+
+/// The rate of Keccak-256, in bytes.
+const KECCAK256_RATE: usize = 136;
+const KECCAK_ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+/// The rotation offsets and the lane permutation of the rho and pi steps, along the pi cycle
+/// starting at lane 1.
+const KECCAK_RHO_OFFSETS: [u32; 24] =
+    [1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44];
+const KECCAK_PI_LANES: [usize; 24] =
+    [10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1];
+
+/// Keccak-256 (as used by Ethereum; i.e., with the original padding), as a const function; see
+/// `starknet_keccak` for the runtime version.
+const fn const_keccak256(data: &[u8]) -> [u8; 32] {
+    let mut state = [0u64; 25];
+    let mut i = 0;
+    while i < data.len() {
+        let position = i % KECCAK256_RATE;
+        state[position / 8] ^= (data[i] as u64) << (8 * (position % 8));
+        if position == KECCAK256_RATE - 1 {
+            state = keccak_f1600(state);
+        }
+        i += 1;
+    }
+
+    // Pad the last block.
+    let position = data.len() % KECCAK256_RATE;
+    state[position / 8] ^= 0x01 << (8 * (position % 8));
+    state[(KECCAK256_RATE - 1) / 8] ^= 0x80 << (8 * ((KECCAK256_RATE - 1) % 8));
+    state = keccak_f1600(state);
+
+    let mut hash = [0; 32];
+    let mut i = 0;
+    while i < hash.len() {
+        hash[i] = (state[i / 8] >> (8 * (i % 8))) as u8;
+        i += 1;
+    }
+    hash
+}
+
+const fn keccak_f1600(mut state: [u64; 25]) -> [u64; 25] {
+    let mut round = 0;
+    while round < KECCAK_ROUND_CONSTANTS.len() {
+        // Theta.
+        let mut column_parities = [0u64; 5];
+        let mut x = 0;
+        while x < 5 {
+            column_parities[x] =
+                state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+            x += 1;
+        }
+        let mut x = 0;
+        while x < 5 {
+            let d = column_parities[(x + 4) % 5] ^ column_parities[(x + 1) % 5].rotate_left(1);
+            let mut y = 0;
+            while y < 5 {
+                state[x + 5 * y] ^= d;
+                y += 1;
+            }
+            x += 1;
+        }
+
+        // Rho and pi.
+        let mut lane = state[1];
+        let mut i = 0;
+        while i < KECCAK_PI_LANES.len() {
+            let next_lane = state[KECCAK_PI_LANES[i]];
+            state[KECCAK_PI_LANES[i]] = lane.rotate_left(KECCAK_RHO_OFFSETS[i]);
+            lane = next_lane;
+            i += 1;
+        }
+
+        // Chi.
+        let mut y = 0;
+        while y < 5 {
+            let row = [
+                state[5 * y],
+                state[5 * y + 1],
+                state[5 * y + 2],
+                state[5 * y + 3],
+                state[5 * y + 4],
+            ];
+            let mut x = 0;
+            while x < 5 {
+                state[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+                x += 1;
+            }
+            y += 1;
+        }
+
+        // Iota.
+        state[0] ^= KECCAK_ROUND_CONSTANTS[round];
+        round += 1;
+    }
+    state
+}
+
 /// Returns the storage address of a StarkNet storage variable given its name and arguments.
 pub fn get_storage_var_address(
     storage_var_name: &str,
     args: &[StarkFelt],
 ) -> Result<StorageKey, StarknetApiError> {
-    let storage_var_name_hash = starknet_keccak(storage_var_name.as_bytes());
-    let storage_var_name_hash = felt_to_stark_felt(&storage_var_name_hash);
+    let storage_var_name_hash = felt_to_stark_felt(&starknet_keccak(storage_var_name.as_bytes()));
+    get_storage_var_address_by_name_hash(storage_var_name_hash, args)
+}
 
+/// Returns the storage address of a StarkNet storage variable given the hash of its name, and its
+/// arguments.
+fn get_storage_var_address_by_name_hash(
+    storage_var_name_hash: StarkFelt,
+    args: &[StarkFelt],
+) -> Result<StorageKey, StarknetApiError> {
     let storage_key_hash =
         args.iter().fold(storage_var_name_hash, |res, arg| pedersen_hash(&res, arg));
 
@@ -63,7 +250,11 @@ pub fn get_uint256_storage_var_addresses(
     storage_var_name: &str,
     args: &[StarkFelt],
 ) -> Result<(StorageKey, StorageKey), StarknetApiError> {
-    let low_key = get_storage_var_address(storage_var_name, args)?;
+    uint256_storage_keys(get_storage_var_address(storage_var_name, args)?)
+}
+
+/// Returns the storage keys of a Uint256 stored at the given (low) key.
+fn uint256_storage_keys(low_key: StorageKey) -> Result<(StorageKey, StorageKey), StarknetApiError> {
     // TODO(Dori, 1/7/2023): When a standard representation for large integers is set, there may
     //   be a better way to add 1 to the key.
     let high_key = StorageKey(PatriciaKey::try_from(StarkFelt::from(
@@ -75,5 +266,9 @@ pub fn get_uint256_storage_var_addresses(
 pub fn get_erc20_balance_var_addresses(
     contract_address: &ContractAddress,
 ) -> Result<(StorageKey, StorageKey), StarknetApiError> {
-    get_uint256_storage_var_addresses("ERC20_balances", &[*contract_address.0.key()])
+    let low_key = get_storage_var_address_by_name_hash(
+        *constants::ERC20_BALANCES_VAR_NAME_HASH,
+        &[*contract_address.0.key()],
+    )?;
+    uint256_storage_keys(low_key)
 }
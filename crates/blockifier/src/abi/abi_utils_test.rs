@@ -1,9 +1,11 @@
 use starknet_api::core::EntryPointSelector;
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
+use test_case::test_case;
 
-use crate::abi::abi_utils::selector_from_name;
+use crate::abi::abi_utils::{const_selector_bytes, selector_from_bytes, selector_from_name};
 use crate::abi::constants as abi_constants;
+use crate::selector;
 use crate::transaction::constants as transaction_constants;
 
 #[test]
@@ -35,3 +37,79 @@ fn test_selector_from_name() {
     let expected_empty_selector = EntryPointSelector(stark_felt!(expected_empty_selector));
     assert_eq!(selector_from_name(""), expected_empty_selector);
 }
+
+#[test]
+fn test_selector_macro() {
+    let expected_execute_selector = EntryPointSelector(stark_felt!(
+        "0x15d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad"
+    ));
+    assert_eq!(selector!("__execute__"), expected_execute_selector);
+    assert_eq!(selector!("__execute__"), selector_from_name("__execute__"));
+    assert_eq!(
+        selector!(transaction_constants::EXECUTE_ENTRY_POINT_NAME),
+        expected_execute_selector
+    );
+
+    // Default entry points are not hashed.
+    let expected_default_selector =
+        EntryPointSelector(stark_felt!(abi_constants::DEFAULT_ENTRY_POINT_SELECTOR));
+    assert_eq!(selector!(abi_constants::DEFAULT_ENTRY_POINT_NAME), expected_default_selector);
+    assert_eq!(selector!(abi_constants::DEFAULT_L1_ENTRY_POINT_NAME), expected_default_selector);
+}
+
+// Names of at least the Keccak rate (136 bytes) span several blocks.
+#[test_case(""; "empty")]
+#[test_case("transfer"; "short")]
+#[test_case(&"a".repeat(135); "one byte short of the rate")]
+#[test_case(&"a".repeat(136); "rate")]
+#[test_case(&"a".repeat(300); "several blocks")]
+fn test_const_selector_bytes(entry_point_name: &str) {
+    assert_eq!(
+        selector_from_bytes(const_selector_bytes(entry_point_name)),
+        selector_from_name(entry_point_name)
+    );
+}
+
+#[test_case(
+    selector!(abi_constants::CONSTRUCTOR_ENTRY_POINT_NAME),
+    "0x28ffe4ff0f226a9107253e17a904099aa4f63a02a5621de0576e5aa71bc5194";
+    "constructor"
+)]
+#[test_case(selector!(abi_constants::DEFAULT_ENTRY_POINT_NAME), "0x0"; "default")]
+#[test_case(
+    selector!(transaction_constants::EXECUTE_ENTRY_POINT_NAME),
+    "0x15d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad";
+    "execute"
+)]
+#[test_case(
+    selector!(transaction_constants::TRANSFER_ENTRY_POINT_NAME),
+    "0x83afd3f4caedc6eebf44246fe54e38c95e3179a5ec9ea81740eca5b482d12e";
+    "transfer"
+)]
+#[test_case(
+    selector!(transaction_constants::VALIDATE_ENTRY_POINT_NAME),
+    "0x162da33a4585851fe8d3af3c2a9c60b557814e221e0d4f30ff0b2189d9c7775";
+    "validate"
+)]
+#[test_case(
+    selector!(transaction_constants::VALIDATE_DECLARE_ENTRY_POINT_NAME),
+    "0x289da278a8dc833409cabfdad1581e8e7d40e42dcaed693fa4008dcdb4963b3";
+    "validate_declare"
+)]
+#[test_case(
+    selector!(transaction_constants::VALIDATE_DEPLOY_ENTRY_POINT_NAME),
+    "0x36fcbf06cd96843058359e1a75928beacfac10727dab22a3972f0af8aa92895";
+    "validate_deploy"
+)]
+fn test_entry_point_selectors(selector: EntryPointSelector, expected_selector: &str) {
+    assert_eq!(selector, EntryPointSelector(stark_felt!(expected_selector)));
+}
+
+#[test]
+fn test_felt_constants() {
+    assert_eq!(*abi_constants::VALID, stark_felt!("0x56414c4944"));
+    assert_eq!(
+        *abi_constants::ERC20_BALANCES_VAR_NAME_HASH,
+        stark_felt!("0x3a4e8ec16e258a799fe707996fd5d21d42b29adc1499a370edf7f809d8c458a")
+    );
+}
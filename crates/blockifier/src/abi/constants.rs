@@ -1,8 +1,26 @@
+use once_cell::sync::Lazy;
+use starknet_api::hash::StarkFelt;
+
+use crate::abi::abi_utils::starknet_keccak;
+use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::transaction::constants as transaction_constants;
+
 pub const CONSTRUCTOR_ENTRY_POINT_NAME: &str = "constructor";
 pub const DEFAULT_ENTRY_POINT_NAME: &str = "__default__";
 pub const DEFAULT_ENTRY_POINT_SELECTOR: u64 = 0;
 pub const DEFAULT_L1_ENTRY_POINT_NAME: &str = "__l1_default__";
 
+// The expected return value of a `validate` entry point: the short string `VALID`.
+pub static VALID: Lazy<StarkFelt> = Lazy::new(|| {
+    StarkFelt::try_from(transaction_constants::VALIDATE_RETDATA)
+        .expect("`VALID` must be a valid felt.")
+});
+
+// Storage variable name hashes.
+pub const ERC20_BALANCES_VAR_NAME: &str = "ERC20_balances";
+pub static ERC20_BALANCES_VAR_NAME_HASH: Lazy<StarkFelt> =
+    Lazy::new(|| felt_to_stark_felt(&starknet_keccak(ERC20_BALANCES_VAR_NAME.as_bytes())));
+
 // The version is considered 0 for L1-Handler transaction hash calculation purposes.
 pub const L1_HANDLER_VERSION: u64 = 0;
 
@@ -10,6 +28,11 @@ pub const L1_HANDLER_VERSION: u64 = 0;
 pub const L1_TO_L2_MSG_HEADER_SIZE: usize = 5;
 pub const L2_TO_L1_MSG_HEADER_SIZE: usize = 3;
 pub const CLASS_UPDATE_SIZE: usize = 1;
+// The bound of the storage keys in the OS (`ADDR_BOUND`): 2^251 - 256.
+pub static STORAGE_KEY_UPPER_BOUND: Lazy<StarkFelt> = Lazy::new(|| {
+    StarkFelt::try_from("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00")
+        .expect("The storage key bound must be a valid felt.")
+});
 
 // StarkNet solidity contract-related constants.
 pub const N_DEFAULT_TOPICS: usize = 1; // Events have one default topic.
@@ -28,6 +51,7 @@ pub const CONSUMED_MSG_TO_L2_ENCODED_DATA_SIZE: usize =
 // Transaction resource names.
 pub const MAX_STEPS_PER_TX: usize = 4_000_000;
 pub const GAS_USAGE: &str = "l1_gas_usage";
+pub const BLOB_GAS_USAGE: &str = "l1_blob_gas_usage";
 pub const N_STEPS_RESOURCE: &str = "n_steps";
 
 // Casm hash calculation-related constants.
@@ -54,6 +78,7 @@ pub const CALL_CONTRACT_GAS_COST: u64 = 10 * STEP_GAS_COST + ENTRY_POINT_GAS_COS
 pub const DEPLOY_GAS_COST: u64 = 200 * STEP_GAS_COST + ENTRY_POINT_GAS_COST;
 pub const EMIT_EVENT_GAS_COST: u64 = 10 * STEP_GAS_COST;
 pub const GET_BLOCK_HASH_GAS_COST: u64 = 50 * STEP_GAS_COST;
+pub const GET_CLASS_HASH_AT_GAS_COST: u64 = 50 * STEP_GAS_COST;
 pub const GET_EXECUTION_INFO_GAS_COST: u64 = 10 * STEP_GAS_COST;
 pub const KECCAK_GAS_COST: u64 = 0;
 pub const KECCAK_ROUND_COST_GAS_COST: u64 = 180000;
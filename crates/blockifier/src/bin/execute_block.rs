@@ -0,0 +1,230 @@
+//! Executes a block of transactions read from JSON files, and prints the outcome and the fee of
+//! each transaction, along with the state diff of the block, as JSON; a reference for integrating
+//! the `TransactionExecutor`. Exits with a nonzero code if any transaction is rejected, unless
+//! `--allow-rejections` is passed.
+//!
+//! Run using `cargo run -p blockifier --features testing --bin execute_block -- <state.json>
+//! <block_context.json> <txs.json> [--allow-rejections]`, where:
+//! * `state.json` holds the initial state: the classes, by class hash (each a compiled class file,
+//!   by its path relative to `state.json`, and its Cairo version), the compiled class hashes, and
+//!   the contracts, by address (each with its class hash, nonce and storage).
+//! * `block_context.json` holds a serialized `BlockContext`.
+//! * `txs.json` holds the transactions, in order: each a serialized `starknet_api` transaction,
+//!   with its hash, the class it declares (as in `state.json`), and the fee paid on L1 (for L1
+//!   handlers).
+//!
+//! See `resources/testing/execute_block` for an example.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use blockifier::block_context::BlockContext;
+use blockifier::blockifier::bouncer::BouncerConfig;
+use blockifier::blockifier::transaction_executor::TransactionExecutor;
+use blockifier::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use blockifier::state::cached_state::CachedState;
+use blockifier::test_utils::DictStateReader;
+use blockifier::transaction::transaction_execution::Transaction;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Fee, Transaction as StarknetApiTransaction, TransactionHash};
+
+const ALLOW_REJECTIONS_FLAG: &str = "--allow-rejections";
+
+/// A compiled class file.
+#[derive(Deserialize)]
+struct ClassFixture {
+    cairo_version: u8,
+    /// Relative to the fixture holding it.
+    path: PathBuf,
+}
+
+impl ClassFixture {
+    fn load(&self, fixture_dir: &Path) -> Result<ContractClass, Box<dyn Error>> {
+        let raw_contract_class = fs::read_to_string(fixture_dir.join(&self.path))?;
+        match self.cairo_version {
+            0 => Ok(ContractClassV0::try_from_json_string(&raw_contract_class)?.into()),
+            1 => Ok(ContractClassV1::try_from_json_string(&raw_contract_class)?.into()),
+            cairo_version => Err(format!("Unsupported Cairo version: {cairo_version}.").into()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ContractFixture {
+    class_hash: ClassHash,
+    #[serde(default)]
+    nonce: Nonce,
+    #[serde(default)]
+    storage: HashMap<StorageKey, StarkFelt>,
+}
+
+#[derive(Deserialize)]
+struct StateFixture {
+    classes: HashMap<ClassHash, ClassFixture>,
+    #[serde(default)]
+    compiled_class_hashes: HashMap<ClassHash, CompiledClassHash>,
+    contracts: HashMap<ContractAddress, ContractFixture>,
+}
+
+impl StateFixture {
+    fn into_state_reader(self, fixture_dir: &Path) -> Result<DictStateReader, Box<dyn Error>> {
+        let mut state_reader = DictStateReader {
+            class_hash_to_compiled_class_hash: self.compiled_class_hashes,
+            ..Default::default()
+        };
+        for (class_hash, class) in self.classes {
+            state_reader.class_hash_to_class.insert(class_hash, class.load(fixture_dir)?);
+        }
+        for (contract_address, contract) in self.contracts {
+            state_reader.address_to_class_hash.insert(contract_address, contract.class_hash);
+            state_reader.address_to_nonce.insert(contract_address, contract.nonce);
+            state_reader.storage_view.extend(
+                contract.storage.into_iter().map(|(key, value)| ((contract_address, key), value)),
+            );
+        }
+
+        Ok(state_reader)
+    }
+}
+
+#[derive(Deserialize)]
+struct TransactionFixture {
+    tx: StarknetApiTransaction,
+    #[serde(default)]
+    tx_hash: TransactionHash,
+    declared_class: Option<ClassFixture>,
+    paid_fee_on_l1: Option<Fee>,
+}
+
+impl TransactionFixture {
+    fn into_transaction(self, fixture_dir: &Path) -> Result<Transaction, Box<dyn Error>> {
+        let declared_class =
+            self.declared_class.map(|class| class.load(fixture_dir)).transpose()?;
+        Ok(Transaction::from_api(self.tx, self.tx_hash, declared_class, self.paid_fee_on_l1, None)?)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Succeeded,
+    Reverted,
+    Rejected,
+}
+
+#[derive(Serialize)]
+struct TransactionOutput {
+    tx_hash: TransactionHash,
+    outcome: Outcome,
+    actual_fee: Fee,
+    /// The revert reason, or the rejection error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let raw_json =
+        fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    Ok(serde_json::from_str(&raw_json).map_err(|error| format!("{}: {error}", path.display()))?)
+}
+
+fn fixture_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new(""))
+}
+
+/// Executes the block, and prints its output; returns the number of rejected transactions.
+fn execute_block(
+    state_path: &Path,
+    block_context_path: &Path,
+    txs_path: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    let state_reader =
+        read_json::<StateFixture>(state_path)?.into_state_reader(fixture_dir(state_path))?;
+    let block_context: BlockContext = read_json(block_context_path)?;
+    let txs = read_json::<Vec<TransactionFixture>>(txs_path)?
+        .into_iter()
+        .map(|tx| tx.into_transaction(fixture_dir(txs_path)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (charge_fee, enforce_fee) = (true, true);
+    let mut executor = TransactionExecutor::new(
+        CachedState::from(state_reader),
+        block_context,
+        BouncerConfig::max(),
+        enforce_fee,
+    )?;
+    let mut n_rejected_txs = 0;
+    let mut tx_outputs = Vec::with_capacity(txs.len());
+    for tx in txs {
+        let tx_hash = tx.tx_hash();
+        let tx_output = match executor.execute(tx, charge_fee) {
+            Ok((tx_execution_info, _)) => TransactionOutput {
+                tx_hash,
+                outcome: if tx_execution_info.is_reverted() {
+                    Outcome::Reverted
+                } else {
+                    Outcome::Succeeded
+                },
+                actual_fee: tx_execution_info.receipt.fee,
+                error: tx_execution_info.revert_error,
+            },
+            Err(error) => {
+                n_rejected_txs += 1;
+                TransactionOutput {
+                    tx_hash,
+                    outcome: Outcome::Rejected,
+                    actual_fee: Fee::default(),
+                    error: Some(error.to_string()),
+                }
+            }
+        };
+        tx_outputs.push(tx_output);
+    }
+    let (state_diff, _) = executor.finalize()?;
+
+    let output = serde_json::json!({
+        "txs": tx_outputs,
+        "state_diff": state_diff.to_py_compatible_json(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(n_rejected_txs)
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let allow_rejections = match args.iter().position(|arg| arg == ALLOW_REJECTIONS_FLAG) {
+        Some(index) => {
+            args.remove(index);
+            true
+        }
+        None => false,
+    };
+    let [state_path, block_context_path, txs_path] = args.as_slice() else {
+        eprintln!(
+            "Usage: execute_block <state.json> <block_context.json> <txs.json> \
+             [{ALLOW_REJECTIONS_FLAG}]"
+        );
+        return ExitCode::from(2);
+    };
+
+    match execute_block(Path::new(state_path), Path::new(block_context_path), Path::new(txs_path)) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) if allow_rejections => ExitCode::SUCCESS,
+        Ok(n_rejected_txs) => {
+            eprintln!("{n_rejected_txs} transaction(s) rejected.");
+            ExitCode::FAILURE
+        }
+        Err(error) => {
+            eprintln!("Failed to execute the block: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
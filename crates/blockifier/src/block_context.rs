@@ -1,10 +1,52 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
+use cairo_felt::Felt252;
+use cairo_vm::vm::runners::builtin_runner::{
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    OUTPUT_BUILTIN_NAME, POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
+};
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
-use starknet_api::core::{ChainId, ContractAddress};
+use starknet_api::core::{ContractAddress, EntryPointSelector};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::Calldata;
+use thiserror::Error;
 
-#[derive(Clone, Debug)]
+use crate::abi::abi_utils::selector_from_name;
+use crate::abi::constants::N_STEPS_RESOURCE;
+use crate::execution::cairo1_execution::PreludeOutOfGasCache;
+use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::platform::{Clock, StdClock};
+use crate::transaction::constants::TRANSFER_ENTRY_POINT_NAME;
+use crate::transaction::objects::FeeType;
+use crate::versioned_constants::{
+    StarknetVersion, VersionedConstants, VersionedConstantsError, VersionedConstantsOverrides,
+};
+
+#[cfg(test)]
+#[path = "block_context_test.rs"]
+pub mod test;
+
+// The Cairo resources charged by their weights; the segment arena builtin is charged by the steps
+// its usage takes.
+const WEIGHTED_VM_RESOURCES: [&str; 9] = [
+    N_STEPS_RESOURCE,
+    OUTPUT_BUILTIN_NAME,
+    HASH_BUILTIN_NAME,
+    RANGE_CHECK_BUILTIN_NAME,
+    SIGNATURE_BUILTIN_NAME,
+    BITWISE_BUILTIN_NAME,
+    EC_OP_BUILTIN_NAME,
+    KECCAK_BUILTIN_NAME,
+    POSEIDON_BUILTIN_NAME,
+];
+
+/// Serialized as the inputs of its [BlockContextBuilder]; deserialization performs the builder's
+/// validations.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "RawBlockContext", into = "RawBlockContext")]
 pub struct BlockContext {
     pub chain_id: ChainId,
     pub block_number: BlockNumber,
@@ -12,12 +54,639 @@ pub struct BlockContext {
 
     // Fee-related.
     pub sequencer_address: ContractAddress,
-    pub fee_token_address: ContractAddress,
-    pub vm_resource_fee_cost: Arc<HashMap<String, f64>>,
-    pub gas_price: u128, // In wei.
-
-    // Limits.
-    pub invoke_tx_max_n_steps: u32,
-    pub validate_max_n_steps: u32,
-    pub max_recursion_depth: usize,
+    pub fee_token_addresses: FeeTokenAddresses,
+    pub fee_transfer_interface: FeeTransferInterface,
+    pub gas_prices: GasPrices,
+    // If set, the state diff is published in blobs (paid in data gas), rather than in calldata.
+    pub use_kzg_da: bool,
+    // If set, the fee transfer is executed even when the actual fee is zero (as the OS does).
+    pub force_zero_fee_transfer: bool,
+    pub fee_charge_policy: FeeChargePolicy,
+    pub zero_sequencer_fee_policy: ZeroSequencerFeePolicy,
+
+    // The limits and gas costs of the block's Starknet version, possibly overridden.
+    pub starknet_version: StarknetVersion,
+    pub versioned_constants: Arc<VersionedConstants>,
+    // Wall-clock time limit of each execution stage (validation / execution); unlimited if unset.
+    pub max_execution_time: Option<Duration>,
+    pub validation_opt_outs: ValidationOptOuts,
+    pub concurrency_config: ConcurrencyConfig,
+    // The clock measuring execution time; not serialized (deserialized as the system clock).
+    pub clock: Arc<dyn Clock>,
+    // The runs of Cairo 1 calls that ran out of gas in their entry point prelude, shared by the
+    // executions of the block (and by the clones of the context); not serialized.
+    pub prelude_out_of_gas_cache: PreludeOutOfGasCache,
+}
+
+impl BlockContext {
+    pub fn fee_token_address(&self, fee_type: &FeeType) -> ContractAddress {
+        self.fee_token_addresses.get_by_fee_type(fee_type)
+    }
+
+    /// Returns the versioned constants for modification; they are copied if shared.
+    pub fn versioned_constants_mut(&mut self) -> &mut VersionedConstants {
+        Arc::make_mut(&mut self.versioned_constants)
+    }
+
+    /// Verifies the context is fit for executing a block; i.e., that its concurrency modes are
+    /// compiled in, that it has a timestamp, and, if fee is enforced, nonzero gas prices and a
+    /// sequencer to credit. Checks other than the first can be skipped by the context's opt-outs.
+    pub fn validate(&self, enforce_fee: bool) -> BlockContextResult<()> {
+        self.concurrency_config.verify_available()?;
+        let opt_outs = &self.validation_opt_outs;
+        if !opt_outs.allow_zero_block_timestamp && self.block_timestamp == BlockTimestamp(0) {
+            return Err(BlockContextError::ZeroBlockTimestamp);
+        }
+        if !enforce_fee {
+            return Ok(());
+        }
+
+        if !opt_outs.allow_zero_gas_prices {
+            self.gas_prices.verify_nonzero()?;
+        }
+        if !opt_outs.allow_zero_sequencer_address
+            && self.sequencer_address == ContractAddress::default()
+        {
+            return Err(BlockContextError::ZeroSequencerAddress);
+        }
+
+        Ok(())
+    }
+}
+
+/// Validations of a block context to skip; e.g., for devnets.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ValidationOptOuts {
+    /// E.g., for old blocks, which carry no STRK prices, or for devnets.
+    pub allow_zero_gas_prices: bool,
+    pub allow_zero_block_timestamp: bool,
+    pub allow_zero_sequencer_address: bool,
+}
+
+/// How the transaction executor executes batches of transactions (see
+/// `TransactionExecutor::execute_txs`).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    /// If unset, transactions are executed sequentially, and the other fields are ignored.
+    /// If set, fee transfers read the sequencer balance as zero, and the fees are added to it
+    /// later (on commit, or at the end of the block; see `TransactionExecutor`). Execution results
+    /// are unaffected, as a transaction that reads the balance otherwise is executed on its actual
+    /// value; however, the per-transaction state diffs of `TransactionExecutor::execute` omit it.
+    pub enabled: bool,
+    /// The number of threads executing each chunk.
+    pub n_workers: usize,
+    /// The number of transactions executed concurrently, as a single chunk.
+    pub chunk_size: usize,
+    /// Applies if `enabled` is unset. If set, the validations of each batch of transactions are
+    /// run first, in parallel (by `n_workers` threads), on the state preceding the batch; the
+    /// transactions are then executed sequentially, each reusing its validation unless it read a
+    /// value written by a preceding transaction (see `concurrency::validation_phase`).
+    pub parallel_validation: bool,
+}
+
+impl ConcurrencyConfig {
+    /// Verifies the configured modes are supported by this build; they require the `concurrency`
+    /// feature.
+    pub fn verify_available(&self) -> BlockContextResult<()> {
+        if cfg!(not(feature = "concurrency")) && (self.enabled || self.parallel_validation) {
+            return Err(BlockContextError::ConcurrencyUnavailable);
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> BlockContextResult<()> {
+        for (field_name, value) in [("n_workers", self.n_workers), ("chunk_size", self.chunk_size)]
+        {
+            if value == 0 {
+                return Err(BlockContextError::ZeroConcurrencyParameter { field_name });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        Self { enabled: false, n_workers: 1, chunk_size: 1, parallel_validation: false }
+    }
+}
+
+/// The chain a block belongs to; serialized as its name.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ChainId {
+    Mainnet,
+    Sepolia,
+    Other(String),
+}
+
+impl ChainId {
+    /// Returns the name of the chain; e.g., `SN_MAIN`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mainnet => "SN_MAIN",
+            Self::Sepolia => "SN_SEPOLIA",
+            Self::Other(name) => name,
+        }
+    }
+
+    /// Returns the chain id as seen by contracts (e.g., in the transaction info): the big-endian
+    /// encoding of the chain name's ASCII bytes.
+    pub fn to_felt(&self) -> StarkFelt {
+        felt_to_stark_felt(&self.to_felt252())
+    }
+
+    /// Returns the chain id felt in hex; e.g., `0x534e5f4d41494e` for `SN_MAIN`.
+    pub fn as_hex(&self) -> String {
+        format!("{:#x}", self.to_felt252().to_biguint())
+    }
+
+    fn to_felt252(&self) -> Felt252 {
+        Felt252::from_bytes_be(self.as_str().as_bytes())
+    }
+}
+
+impl From<String> for ChainId {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "SN_MAIN" => Self::Mainnet,
+            "SN_SEPOLIA" => Self::Sepolia,
+            _ => Self::Other(name),
+        }
+    }
+}
+
+impl From<ChainId> for String {
+    fn from(chain_id: ChainId) -> Self {
+        match chain_id {
+            ChainId::Other(name) => name,
+            _ => chain_id.as_str().to_string(),
+        }
+    }
+}
+
+impl From<starknet_api::core::ChainId> for ChainId {
+    fn from(chain_id: starknet_api::core::ChainId) -> Self {
+        chain_id.0.into()
+    }
+}
+
+/// How a transaction is charged if its sender cannot pay the actual fee when it is charged; i.e.,
+/// if the execution spent the balance the sender had beforehand.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FeeChargePolicy {
+    /// The transaction is rejected.
+    #[default]
+    Strict,
+    /// The sender's remaining balance is charged instead.
+    CapAtBalance,
+}
+
+/// How fees are charged when the sequencer address is zero (e.g., on networks that are
+/// bootstrapping); canonical fee tokens reject transfers to the zero address.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ZeroSequencerFeePolicy {
+    /// The fee is transferred to the zero address, as to any sequencer.
+    #[default]
+    Transfer,
+    /// The fee is debited from the sender, without crediting anyone.
+    Burn,
+    /// The fee is not charged.
+    Skip,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FeeTokenAddresses {
+    pub strk_fee_token_address: ContractAddress,
+    pub eth_fee_token_address: ContractAddress,
+}
+
+impl FeeTokenAddresses {
+    pub fn get_by_fee_type(&self, fee_type: &FeeType) -> ContractAddress {
+        match fee_type {
+            FeeType::Strk => self.strk_fee_token_address,
+            FeeType::Eth => self.eth_fee_token_address,
+        }
+    }
+}
+
+/// The entry point of the fee tokens that fees are paid by; appchains may use tokens whose transfer
+/// differs from the canonical `transfer(recipient, amount)`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct FeeTransferInterface {
+    pub selector_name: String,
+    pub calldata_layout: FeeTransferCalldataLayout,
+}
+
+impl FeeTransferInterface {
+    pub fn selector(&self) -> EntryPointSelector {
+        selector_from_name(&self.selector_name)
+    }
+
+    /// Verifies the selector name is a Cairo identifier.
+    fn validate(&self) -> BlockContextResult<()> {
+        let is_identifier = self
+            .selector_name
+            .chars()
+            .next()
+            .is_some_and(|first_char| first_char.is_ascii_alphabetic() || first_char == '_')
+            && self.selector_name.chars().all(|char| char.is_ascii_alphanumeric() || char == '_');
+        if !is_identifier {
+            return Err(BlockContextError::InvalidFeeTransferSelectorName {
+                selector_name: self.selector_name.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for FeeTransferInterface {
+    fn default() -> Self {
+        Self {
+            selector_name: TRANSFER_ENTRY_POINT_NAME.to_string(),
+            calldata_layout: FeeTransferCalldataLayout::default(),
+        }
+    }
+}
+
+/// The calldata of a fee transfer entry point, where the amount is a `Uint256` given by its
+/// (low, high) 128-bit limbs.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FeeTransferCalldataLayout {
+    /// `[recipient, amount_low, amount_high]`; e.g., of `transfer`.
+    #[default]
+    RecipientAmount,
+    /// `[sender, recipient, amount_low, amount_high]`; e.g., of `transferFrom`.
+    SenderRecipientAmount,
+}
+
+impl FeeTransferCalldataLayout {
+    pub fn calldata(
+        &self,
+        sender_address: ContractAddress,
+        recipient_address: ContractAddress,
+        (amount_low, amount_high): (StarkFelt, StarkFelt),
+    ) -> Calldata {
+        let recipient_and_amount = [*recipient_address.0.key(), amount_low, amount_high];
+        match self {
+            Self::RecipientAmount => Calldata(Arc::new(recipient_and_amount.to_vec())),
+            Self::SenderRecipientAmount => Calldata(Arc::new(
+                [[*sender_address.0.key()].as_slice(), &recipient_and_amount].concat(),
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GasPrices {
+    pub eth_l1_gas_price: u128,       // In wei.
+    pub strk_l1_gas_price: u128,      // In fri.
+    pub eth_l1_data_gas_price: u128,  // In wei.
+    pub strk_l1_data_gas_price: u128, // In fri.
+}
+
+impl GasPrices {
+    /// Creates the gas prices of a block; all prices must be nonzero, unless `allow_zero_prices` is
+    /// set (e.g., for old blocks, which carry no STRK prices, or for devnets).
+    pub fn new(
+        eth_l1_gas_price: u128,
+        strk_l1_gas_price: u128,
+        eth_l1_data_gas_price: u128,
+        strk_l1_data_gas_price: u128,
+        allow_zero_prices: bool,
+    ) -> BlockContextResult<Self> {
+        let gas_prices = Self {
+            eth_l1_gas_price,
+            strk_l1_gas_price,
+            eth_l1_data_gas_price,
+            strk_l1_data_gas_price,
+        };
+        if !allow_zero_prices {
+            gas_prices.verify_nonzero()?;
+        }
+
+        Ok(gas_prices)
+    }
+
+    fn verify_nonzero(&self) -> BlockContextResult<()> {
+        for (price_name, price) in [
+            ("eth_l1_gas_price", self.eth_l1_gas_price),
+            ("strk_l1_gas_price", self.strk_l1_gas_price),
+            ("eth_l1_data_gas_price", self.eth_l1_data_gas_price),
+            ("strk_l1_data_gas_price", self.strk_l1_data_gas_price),
+        ] {
+            if price == 0 {
+                return Err(BlockContextError::ZeroGasPrice { price_name });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_gas_price_by_fee_type(&self, fee_type: &FeeType) -> u128 {
+        match fee_type {
+            FeeType::Strk => self.strk_l1_gas_price,
+            FeeType::Eth => self.eth_l1_gas_price,
+        }
+    }
+
+    pub fn get_data_gas_price_by_fee_type(&self, fee_type: &FeeType) -> u128 {
+        match fee_type {
+            FeeType::Strk => self.strk_l1_data_gas_price,
+            FeeType::Eth => self.eth_l1_data_gas_price,
+        }
+    }
+
+    /// Returns whether all gas prices in the given fee token are zero; i.e., fee is disabled.
+    pub fn are_zero(&self, fee_type: &FeeType) -> bool {
+        self.get_gas_price_by_fee_type(fee_type) == 0
+            && self.get_data_gas_price_by_fee_type(fee_type) == 0
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BlockContextError {
+    #[error("Gas price {price_name} must be nonzero.")]
+    ZeroGasPrice { price_name: &'static str },
+    #[error("Block number must be positive.")]
+    ZeroBlockNumber,
+    #[error("Block timestamp must be positive.")]
+    ZeroBlockTimestamp,
+    #[error("Sequencer address must be nonzero when fee is enforced.")]
+    ZeroSequencerAddress,
+    #[error("Fee transfer selector name {selector_name:?} must be a Cairo identifier.")]
+    InvalidFeeTransferSelectorName { selector_name: String },
+    #[error("Concurrency parameter {field_name} must be positive.")]
+    ZeroConcurrencyParameter { field_name: &'static str },
+    #[error("Cairo resource {resource_name} must have a fee weight.")]
+    MissingVmResourceFeeCost { resource_name: &'static str },
+    #[error("Concurrency and parallel validation require the `concurrency` feature.")]
+    ConcurrencyUnavailable,
+    #[error(transparent)]
+    VersionedConstantsError(#[from] VersionedConstantsError),
+}
+
+pub type BlockContextResult<T> = Result<T, BlockContextError>;
+
+/// Builds a [BlockContext] from the block's required fields; the other fields take the defaults
+/// of the latest Starknet version, unless overridden.
+#[derive(Clone, Debug)]
+pub struct BlockContextBuilder {
+    block_context: BlockContext,
+    versioned_constants_overrides: VersionedConstantsOverrides,
+    vm_resource_fee_cost: Option<HashMap<String, f64>>,
+    require_positive_block_number: bool,
+}
+
+impl BlockContextBuilder {
+    pub fn new(
+        chain_id: ChainId,
+        block_number: BlockNumber,
+        block_timestamp: BlockTimestamp,
+        sequencer_address: ContractAddress,
+        gas_prices: GasPrices,
+        fee_token_addresses: FeeTokenAddresses,
+    ) -> Self {
+        let block_context = BlockContext {
+            chain_id,
+            block_number,
+            block_timestamp,
+            sequencer_address,
+            fee_token_addresses,
+            fee_transfer_interface: FeeTransferInterface::default(),
+            gas_prices,
+            use_kzg_da: false,
+            force_zero_fee_transfer: false,
+            fee_charge_policy: FeeChargePolicy::Strict,
+            zero_sequencer_fee_policy: ZeroSequencerFeePolicy::Transfer,
+            starknet_version: StarknetVersion::LATEST,
+            versioned_constants: Arc::clone(VersionedConstants::shared(StarknetVersion::LATEST)),
+            max_execution_time: None,
+            validation_opt_outs: ValidationOptOuts::default(),
+            concurrency_config: ConcurrencyConfig::default(),
+            clock: Arc::new(StdClock),
+            prelude_out_of_gas_cache: PreludeOutOfGasCache::default(),
+        };
+
+        Self {
+            block_context,
+            versioned_constants_overrides: VersionedConstantsOverrides::default(),
+            vm_resource_fee_cost: None,
+            require_positive_block_number: true,
+        }
+    }
+
+    pub fn starknet_version(mut self, starknet_version: StarknetVersion) -> Self {
+        self.block_context.starknet_version = starknet_version;
+        self
+    }
+
+    /// Allows zero gas prices; e.g., for old blocks, which carry no STRK prices, or for devnets.
+    pub fn allow_zero_gas_prices(mut self, allow_zero_gas_prices: bool) -> Self {
+        self.block_context.validation_opt_outs.allow_zero_gas_prices = allow_zero_gas_prices;
+        self
+    }
+
+    /// Sets the validations to skip; replaces the zero gas prices allowance.
+    pub fn validation_opt_outs(mut self, validation_opt_outs: ValidationOptOuts) -> Self {
+        self.block_context.validation_opt_outs = validation_opt_outs;
+        self
+    }
+
+    pub fn fee_transfer_interface(mut self, fee_transfer_interface: FeeTransferInterface) -> Self {
+        self.block_context.fee_transfer_interface = fee_transfer_interface;
+        self
+    }
+
+    pub fn concurrency_config(mut self, concurrency_config: ConcurrencyConfig) -> Self {
+        self.block_context.concurrency_config = concurrency_config;
+        self
+    }
+
+    /// The clock measuring execution time; the system clock by default.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.block_context.clock = clock;
+        self
+    }
+
+    /// Whether the block number must be positive; unset to build the genesis block.
+    pub fn require_positive_block_number(mut self, require_positive_block_number: bool) -> Self {
+        self.require_positive_block_number = require_positive_block_number;
+        self
+    }
+
+    /// Overrides the Cairo resource weights of the Starknet version.
+    pub fn vm_resource_fee_cost(mut self, vm_resource_fee_cost: HashMap<String, f64>) -> Self {
+        self.vm_resource_fee_cost = Some(vm_resource_fee_cost);
+        self
+    }
+
+    pub fn use_kzg_da(mut self, use_kzg_da: bool) -> Self {
+        self.block_context.use_kzg_da = use_kzg_da;
+        self
+    }
+
+    /// Overrides the step limits of the Starknet version.
+    pub fn max_n_steps(mut self, invoke_tx_max_n_steps: u32, validate_max_n_steps: u32) -> Self {
+        self.versioned_constants_overrides.invoke_tx_max_n_steps = Some(invoke_tx_max_n_steps);
+        self.versioned_constants_overrides.validate_max_n_steps = Some(validate_max_n_steps);
+        self
+    }
+
+    /// Overrides the max recursion depth of the Starknet version.
+    pub fn max_recursion_depth(mut self, max_recursion_depth: usize) -> Self {
+        self.versioned_constants_overrides.max_recursion_depth = Some(max_recursion_depth);
+        self
+    }
+
+    /// Overrides constants of the Starknet version; replaces previous overrides.
+    pub fn versioned_constants_overrides(
+        mut self,
+        versioned_constants_overrides: VersionedConstantsOverrides,
+    ) -> Self {
+        self.versioned_constants_overrides = versioned_constants_overrides;
+        self
+    }
+
+    pub fn build(self) -> BlockContextResult<BlockContext> {
+        let Self {
+            mut block_context,
+            versioned_constants_overrides,
+            vm_resource_fee_cost,
+            require_positive_block_number,
+        } = self;
+        block_context.versioned_constants = if versioned_constants_overrides.is_empty() {
+            Arc::clone(VersionedConstants::shared(block_context.starknet_version))
+        } else {
+            Arc::new(VersionedConstants::with_overrides(
+                block_context.starknet_version,
+                versioned_constants_overrides,
+            )?)
+        };
+        if let Some(vm_resource_fee_cost) = vm_resource_fee_cost {
+            block_context.versioned_constants_mut().vm_resource_fee_cost = vm_resource_fee_cost;
+        }
+        let vm_resource_fee_cost = &block_context.versioned_constants.vm_resource_fee_cost;
+        if let Some(resource_name) = WEIGHTED_VM_RESOURCES
+            .into_iter()
+            .find(|resource_name| !vm_resource_fee_cost.contains_key(*resource_name))
+        {
+            return Err(BlockContextError::MissingVmResourceFeeCost { resource_name });
+        }
+
+        if !block_context.validation_opt_outs.allow_zero_gas_prices {
+            block_context.gas_prices.verify_nonzero()?;
+        }
+        if require_positive_block_number && block_context.block_number == BlockNumber(0) {
+            return Err(BlockContextError::ZeroBlockNumber);
+        }
+        block_context.fee_transfer_interface.validate()?;
+        block_context.concurrency_config.validate()?;
+
+        Ok(block_context)
+    }
+}
+
+/// The serialized form of a [BlockContext]: the inputs of its builder, with human-readable values
+/// (hex addresses, decimal gas prices).
+#[derive(Deserialize, Serialize)]
+struct RawBlockContext {
+    chain_id: ChainId,
+    block_number: BlockNumber,
+    block_timestamp: BlockTimestamp,
+    sequencer_address: ContractAddress,
+    fee_token_addresses: FeeTokenAddresses,
+    gas_prices: GasPrices,
+    #[serde(default)]
+    fee_transfer_interface: FeeTransferInterface,
+    #[serde(default)]
+    use_kzg_da: bool,
+    #[serde(default)]
+    force_zero_fee_transfer: bool,
+    #[serde(default)]
+    fee_charge_policy: FeeChargePolicy,
+    #[serde(default)]
+    zero_sequencer_fee_policy: ZeroSequencerFeePolicy,
+    #[serde(default)]
+    starknet_version: StarknetVersion,
+    #[serde(default)]
+    versioned_constants_overrides: VersionedConstantsOverrides,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_execution_time: Option<Duration>,
+    // Validation relaxations; see the builder.
+    #[serde(default)]
+    validation_opt_outs: ValidationOptOuts,
+    #[serde(default = "require_positive_block_number_default")]
+    require_positive_block_number: bool,
+    #[serde(default)]
+    concurrency_config: ConcurrencyConfig,
+}
+
+fn require_positive_block_number_default() -> bool {
+    true
+}
+
+impl TryFrom<RawBlockContext> for BlockContext {
+    type Error = BlockContextError;
+
+    fn try_from(raw_block_context: RawBlockContext) -> BlockContextResult<Self> {
+        let mut block_context = BlockContextBuilder::new(
+            raw_block_context.chain_id,
+            raw_block_context.block_number,
+            raw_block_context.block_timestamp,
+            raw_block_context.sequencer_address,
+            raw_block_context.gas_prices,
+            raw_block_context.fee_token_addresses,
+        )
+        .starknet_version(raw_block_context.starknet_version)
+        .versioned_constants_overrides(raw_block_context.versioned_constants_overrides)
+        .use_kzg_da(raw_block_context.use_kzg_da)
+        .validation_opt_outs(raw_block_context.validation_opt_outs)
+        .require_positive_block_number(raw_block_context.require_positive_block_number)
+        .fee_transfer_interface(raw_block_context.fee_transfer_interface)
+        .concurrency_config(raw_block_context.concurrency_config)
+        .build()?;
+        block_context.force_zero_fee_transfer = raw_block_context.force_zero_fee_transfer;
+        block_context.fee_charge_policy = raw_block_context.fee_charge_policy;
+        block_context.zero_sequencer_fee_policy = raw_block_context.zero_sequencer_fee_policy;
+        block_context.max_execution_time = raw_block_context.max_execution_time;
+
+        Ok(block_context)
+    }
+}
+
+impl From<BlockContext> for RawBlockContext {
+    fn from(block_context: BlockContext) -> Self {
+        let versioned_constants_overrides = VersionedConstantsOverrides::from_diff(
+            VersionedConstants::get(block_context.starknet_version),
+            &block_context.versioned_constants,
+        );
+
+        Self {
+            validation_opt_outs: block_context.validation_opt_outs,
+            // Relaxed only where the context requires it.
+            require_positive_block_number: block_context.block_number != BlockNumber(0),
+            chain_id: block_context.chain_id,
+            block_number: block_context.block_number,
+            block_timestamp: block_context.block_timestamp,
+            sequencer_address: block_context.sequencer_address,
+            fee_token_addresses: block_context.fee_token_addresses,
+            gas_prices: block_context.gas_prices,
+            fee_transfer_interface: block_context.fee_transfer_interface,
+            use_kzg_da: block_context.use_kzg_da,
+            force_zero_fee_transfer: block_context.force_zero_fee_transfer,
+            fee_charge_policy: block_context.fee_charge_policy,
+            zero_sequencer_fee_policy: block_context.zero_sequencer_fee_policy,
+            starknet_version: block_context.starknet_version,
+            versioned_constants_overrides,
+            max_execution_time: block_context.max_execution_time,
+            concurrency_config: block_context.concurrency_config,
+        }
+    }
 }
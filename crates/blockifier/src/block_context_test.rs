@@ -0,0 +1,346 @@
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::builtin_runner::HASH_BUILTIN_NAME;
+use rstest::rstest;
+use serde_json::json;
+use starknet_api::block::{BlockNumber, BlockTimestamp};
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+use strum::IntoEnumIterator;
+
+use crate::abi::constants::N_STEPS_RESOURCE;
+use crate::block_context::{
+    BlockContext, BlockContextBuilder, BlockContextError, ChainId, ConcurrencyConfig,
+    FeeTransferInterface, GasPrices, ValidationOptOuts,
+};
+use crate::selector;
+use crate::test_utils::{
+    invoke_tx, test_fee_token_addresses, test_vm_resource_fee_cost, DEFAULT_GAS_PRICE, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS, TEST_SEQUENCER_ADDRESS,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::test_utils::create_state_with_trivial_validation_account;
+use crate::transaction::transactions::ExecutableTransaction;
+use crate::versioned_constants::{
+    StarknetVersion, VersionedConstants, VersionedConstantsError, VersionedConstantsOverrides,
+};
+
+#[rstest]
+#[case::nonzero_prices(1, false)]
+#[case::zero_strk_price(0, false)]
+#[case::zero_strk_price_allowed(0, true)]
+fn test_gas_prices_validation(#[case] strk_l1_gas_price: u128, #[case] allow_zero_prices: bool) {
+    let result = GasPrices::new(1, strk_l1_gas_price, 1, 1, allow_zero_prices);
+    if strk_l1_gas_price == 0 && !allow_zero_prices {
+        assert_matches!(
+            result.unwrap_err(),
+            BlockContextError::ZeroGasPrice { price_name: "strk_l1_gas_price" }
+        );
+    } else {
+        assert_eq!(result.unwrap().strk_l1_gas_price, strk_l1_gas_price);
+    }
+}
+
+fn builder(block_number: u64, gas_price: u128) -> BlockContextBuilder {
+    BlockContextBuilder::new(
+        ChainId::Other("SN_GOERLI".to_string()),
+        BlockNumber(block_number),
+        BlockTimestamp::default(),
+        contract_address!(TEST_SEQUENCER_ADDRESS),
+        GasPrices::new(gas_price, gas_price, gas_price, gas_price, true).unwrap(),
+        test_fee_token_addresses(),
+    )
+}
+
+#[test]
+fn test_builder_validation() {
+    assert_matches!(
+        builder(1, 0).build().unwrap_err(),
+        BlockContextError::ZeroGasPrice { price_name: "eth_l1_gas_price" }
+    );
+    assert_matches!(builder(0, 1).build().unwrap_err(), BlockContextError::ZeroBlockNumber);
+
+    let block_context = builder(0, 0)
+        .allow_zero_gas_prices(true)
+        .require_positive_block_number(false)
+        .build()
+        .unwrap();
+    assert_eq!(block_context.block_number, BlockNumber(0));
+
+    let block_context =
+        builder(1, 1).use_kzg_da(true).max_n_steps(10, 20).max_recursion_depth(5).build().unwrap();
+    assert!(block_context.use_kzg_da);
+    let versioned_constants = &block_context.versioned_constants;
+    assert_eq!(
+        (
+            versioned_constants.invoke_tx_max_n_steps,
+            versioned_constants.validate_max_n_steps,
+            versioned_constants.max_recursion_depth
+        ),
+        (10, 20, 5)
+    );
+
+    let invalid_overrides = VersionedConstantsOverrides {
+        json_overrides: Some(json!({"validate_max_n_steps": -1})),
+        ..Default::default()
+    };
+    assert_matches!(
+        builder(1, 1).versioned_constants_overrides(invalid_overrides).build().unwrap_err(),
+        BlockContextError::VersionedConstantsError(VersionedConstantsError::InvalidOverrides(_))
+    );
+}
+
+#[rstest]
+#[case::mainnet(BlockContext::create_for_account_testing())]
+#[case::devnet(BlockContext::create_for_devnet_testing())]
+#[case::fixture(BlockContext::create_from_fixture())]
+fn test_presets_are_executable(#[case] block_context: BlockContext) {
+    let state = &mut create_state_with_trivial_validation_account();
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
+        selector!("return_result").0,       // EP selector.
+        stark_felt!(1_u8),                  // Calldata length.
+        stark_felt!(2_u8)                   // Calldata: num.
+    ];
+    let tx = invoke_tx(
+        execute_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        Fee(MAX_FEE),
+        None,
+    );
+    let account_tx = AccountTransaction::Invoke(tx.into());
+
+    let tx_execution_info = account_tx.execute(state, &block_context, true, true).unwrap();
+    assert!(!tx_execution_info.is_reverted());
+    // Gas is free on the devnet.
+    let gas_prices_are_zero = block_context.gas_prices.eth_l1_gas_price == 0;
+    assert_eq!(tx_execution_info.receipt.fee == Fee(0), gas_prices_are_zero);
+}
+
+#[rstest]
+#[case::mainnet("SN_MAIN", ChainId::Mainnet, "0x534e5f4d41494e")]
+#[case::sepolia("SN_SEPOLIA", ChainId::Sepolia, "0x534e5f5345504f4c4941")]
+#[case::other("SN_GOERLI", ChainId::Other("SN_GOERLI".to_string()), "0x534e5f474f45524c49")]
+fn test_chain_id(#[case] name: &str, #[case] expected_chain_id: ChainId, #[case] hex: &str) {
+    let chain_id = ChainId::from(name.to_string());
+    assert_eq!(chain_id, expected_chain_id);
+    assert_eq!(chain_id.as_str(), name);
+    assert_eq!(chain_id.as_hex(), hex);
+    assert_eq!(chain_id.to_felt(), StarkFelt::try_from(hex).unwrap());
+}
+
+#[rstest]
+#[case::mainnet(BlockContext::create_for_testing())]
+#[case::devnet(BlockContext::create_for_devnet_testing())]
+#[case::fixture(BlockContext::create_from_fixture())]
+fn test_serde_round_trip(#[case] block_context: BlockContext) {
+    let serialized = serde_json::to_value(&block_context).unwrap();
+    let deserialized: BlockContext = serde_json::from_value(serialized.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+
+    assert_eq!(deserialized.chain_id, block_context.chain_id);
+    assert_eq!(deserialized.gas_prices, block_context.gas_prices);
+    assert_eq!(deserialized.fee_token_addresses, block_context.fee_token_addresses);
+    assert_eq!(
+        serde_json::to_value(&deserialized.versioned_constants).unwrap(),
+        serde_json::to_value(&block_context.versioned_constants).unwrap()
+    );
+}
+
+/// The Cairo resource weights are those of the Starknet version, unless overridden.
+#[test]
+fn test_vm_resource_fee_cost_by_version() {
+    for starknet_version in StarknetVersion::iter() {
+        let block_context = builder(1, 1).starknet_version(starknet_version).build().unwrap();
+        assert_eq!(
+            block_context.versioned_constants.vm_resource_fee_cost,
+            VersionedConstants::get(starknet_version).vm_resource_fee_cost
+        );
+    }
+
+    let block_context =
+        builder(1, 1).vm_resource_fee_cost(test_vm_resource_fee_cost()).build().unwrap();
+    assert_eq!(block_context.versioned_constants.vm_resource_fee_cost, test_vm_resource_fee_cost());
+}
+
+#[rstest]
+#[case::steps(N_STEPS_RESOURCE)]
+#[case::builtin(HASH_BUILTIN_NAME)]
+fn test_missing_vm_resource_fee_cost(#[case] resource_name: &str) {
+    let mut vm_resource_fee_cost = test_vm_resource_fee_cost();
+    vm_resource_fee_cost.remove(resource_name);
+    assert_matches!(
+        builder(1, 1).vm_resource_fee_cost(vm_resource_fee_cost).build().unwrap_err(),
+        BlockContextError::MissingVmResourceFeeCost { resource_name: missing_resource_name }
+        if missing_resource_name == resource_name
+    );
+}
+
+#[test]
+fn test_serde_representation() {
+    let block_context = BlockContext::create_from_fixture();
+    assert_eq!(block_context.chain_id, ChainId::Sepolia);
+    assert_eq!(block_context.versioned_constants.invoke_tx_max_n_steps, 1_000_000);
+    assert_eq!(
+        block_context.versioned_constants.validate_max_n_steps,
+        VersionedConstants::get(StarknetVersion::V0_13_3).validate_max_n_steps
+    );
+    assert_eq!(block_context.versioned_constants.vm_resource_fee_cost, test_vm_resource_fee_cost());
+
+    let serialized = serde_json::to_value(&block_context).unwrap();
+    assert_eq!(serialized["chain_id"], json!("SN_SEPOLIA"));
+    assert_eq!(serialized["sequencer_address"], json!(TEST_SEQUENCER_ADDRESS));
+    assert_eq!(serialized["gas_prices"]["eth_l1_gas_price"], json!(DEFAULT_GAS_PRICE));
+    assert_eq!(serialized["starknet_version"], json!("0.13.3"));
+    let overrides = &serialized["versioned_constants_overrides"];
+    assert_eq!(overrides["invoke_tx_max_n_steps"], json!(1_000_000));
+    assert_eq!(overrides["json_overrides"]["vm_resource_fee_cost"]["n_steps"], json!(1.0));
+}
+
+#[test]
+fn test_serde_validation() {
+    let mut serialized = serde_json::to_value(BlockContext::create_for_testing()).unwrap();
+    serialized["gas_prices"]["strk_l1_gas_price"] = json!(0);
+    let error = serde_json::from_value::<BlockContext>(serialized.clone()).unwrap_err();
+    assert_eq!(error.to_string(), "Gas price strk_l1_gas_price must be nonzero.");
+
+    serialized["validation_opt_outs"]["allow_zero_gas_prices"] = json!(true);
+    let block_context: BlockContext = serde_json::from_value(serialized.clone()).unwrap();
+    assert_eq!(block_context.gas_prices.strk_l1_gas_price, 0);
+
+    // A versioned constants patch may not remove a Cairo resource weight.
+    serialized["versioned_constants_overrides"]["json_overrides"] =
+        json!({"vm_resource_fee_cost": {"n_steps": null}});
+    let error = serde_json::from_value::<BlockContext>(serialized).unwrap_err();
+    assert_eq!(error.to_string(), "Cairo resource n_steps must have a fee weight.");
+}
+
+#[rstest]
+#[case::disabled(ConcurrencyConfig::default(), None)]
+#[case::enabled(ConcurrencyConfig { enabled: true, n_workers: 4, chunk_size: 10, ..Default::default() }, None)]
+#[case::zero_workers(
+    ConcurrencyConfig { enabled: true, n_workers: 0, chunk_size: 10, ..Default::default() },
+    Some("n_workers")
+)]
+#[case::zero_chunk_size(
+    ConcurrencyConfig { enabled: false, n_workers: 4, chunk_size: 0, ..Default::default() },
+    Some("chunk_size")
+)]
+fn test_concurrency_config_validation(
+    #[case] concurrency_config: ConcurrencyConfig,
+    #[case] expected_zero_field: Option<&str>,
+) {
+    let result = builder(1, 1).concurrency_config(concurrency_config).build();
+    match expected_zero_field {
+        None => assert_eq!(result.unwrap().concurrency_config, concurrency_config),
+        Some(expected_zero_field) => assert_matches!(
+            result.unwrap_err(),
+            BlockContextError::ZeroConcurrencyParameter { field_name }
+            if field_name == expected_zero_field
+        ),
+    }
+
+    // Deserialization performs the same validation; missing fields take the defaults.
+    let mut serialized = serde_json::to_value(BlockContext::create_for_testing()).unwrap();
+    serialized["concurrency_config"] = serde_json::to_value(concurrency_config).unwrap();
+    assert_eq!(
+        serde_json::from_value::<BlockContext>(serialized.clone()).is_ok(),
+        expected_zero_field.is_none()
+    );
+    serialized["concurrency_config"] = json!({"enabled": true});
+    assert_eq!(
+        serde_json::from_value::<BlockContext>(serialized).unwrap().concurrency_config,
+        ConcurrencyConfig { enabled: true, ..Default::default() }
+    );
+}
+
+#[rstest]
+#[case::canonical("transfer", true)]
+#[case::custom("transfer_legacy", true)]
+#[case::camel_case("transferFrom", true)]
+#[case::empty("", false)]
+#[case::leading_digit("2transfer", false)]
+#[case::whitespace("transfer legacy", false)]
+fn test_fee_transfer_interface_validation(#[case] selector_name: &str, #[case] is_valid: bool) {
+    let fee_transfer_interface =
+        FeeTransferInterface { selector_name: selector_name.to_string(), ..Default::default() };
+    let result = builder(1, 1).fee_transfer_interface(fee_transfer_interface.clone()).build();
+    if is_valid {
+        assert_eq!(result.unwrap().fee_transfer_interface, fee_transfer_interface);
+    } else {
+        assert_matches!(
+            result.unwrap_err(),
+            BlockContextError::InvalidFeeTransferSelectorName { selector_name: name }
+            if name == selector_name
+        );
+    }
+
+    // Deserialization performs the same validation; the default interface is the canonical one.
+    let mut serialized = serde_json::to_value(BlockContext::create_for_testing()).unwrap();
+    assert_eq!(
+        serialized["fee_transfer_interface"],
+        json!({"selector_name": "transfer", "calldata_layout": "RecipientAmount"})
+    );
+    serialized["fee_transfer_interface"] = serde_json::to_value(fee_transfer_interface).unwrap();
+    assert_eq!(serde_json::from_value::<BlockContext>(serialized).is_ok(), is_valid);
+}
+
+fn devnet_with_opt_outs(validation_opt_outs: ValidationOptOuts) -> BlockContext {
+    BlockContext { validation_opt_outs, ..BlockContext::create_for_devnet_testing() }
+}
+
+#[rstest]
+#[case::valid(BlockContext::create_for_testing(), true, None)]
+#[case::zero_timestamp(
+    BlockContext { block_timestamp: BlockTimestamp(0), ..BlockContext::create_for_testing() },
+    false,
+    Some(BlockContextError::ZeroBlockTimestamp)
+)]
+#[case::zero_prices_opted_out(BlockContext::create_for_devnet_testing(), true, None)]
+#[case::zero_prices_with_fee(
+    devnet_with_opt_outs(ValidationOptOuts {
+        allow_zero_block_timestamp: true,
+        allow_zero_sequencer_address: true,
+        ..Default::default()
+    }),
+    true,
+    Some(BlockContextError::ZeroGasPrice { price_name: "eth_l1_gas_price" })
+)]
+#[case::zero_prices_without_fee(
+    devnet_with_opt_outs(ValidationOptOuts {
+        allow_zero_block_timestamp: true,
+        ..Default::default()
+    }),
+    false,
+    None
+)]
+#[case::zero_sequencer_with_fee(
+    BlockContext {
+        sequencer_address: ContractAddress::default(),
+        ..BlockContext::create_for_testing()
+    },
+    true,
+    Some(BlockContextError::ZeroSequencerAddress)
+)]
+#[case::zero_sequencer_without_fee(
+    BlockContext {
+        sequencer_address: ContractAddress::default(),
+        ..BlockContext::create_for_testing()
+    },
+    false,
+    None
+)]
+fn test_validate(
+    #[case] block_context: BlockContext,
+    #[case] enforce_fee: bool,
+    #[case] expected_error: Option<BlockContextError>,
+) {
+    let result = block_context.validate(enforce_fee);
+    match expected_error {
+        None => result.unwrap(),
+        Some(expected_error) => {
+            assert_eq!(result.unwrap_err().to_string(), expected_error.to_string())
+        }
+    }
+}
@@ -2,28 +2,95 @@ use starknet_api::block::{BlockHash, BlockNumber};
 use starknet_api::core::ContractAddress;
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
+use thiserror::Error;
 
 use crate::abi::constants;
-use crate::state::state_api::State;
+use crate::block_context::BlockContext;
+use crate::state::cached_state::{
+    CachedState, CommitmentStateDiff, ContractClassMapping, StateMaps,
+};
+use crate::state::errors::StateError;
+use crate::state::state_api::{State, StateResult};
+use crate::state::witness_state_reader::WitnessStateReader;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
 
 #[cfg(test)]
 #[path = "block_execution_test.rs"]
 pub mod test;
 
+#[derive(Debug, Error)]
+pub enum BlockExecutionError {
+    #[error("Block {block_number:?} must be given the hash of block {old_block_number:?}.")]
+    MissingOldBlockHash { block_number: BlockNumber, old_block_number: BlockNumber },
+    #[error(
+        "Block {block_number:?} was given the hash of block {actual:?}; expected the hash of block \
+         {expected:?}."
+    )]
+    UnexpectedOldBlockNumber {
+        block_number: BlockNumber,
+        expected: Option<BlockNumber>,
+        actual: BlockNumber,
+    },
+}
+
+pub type BlockExecutionResult<T> = Result<T, BlockExecutionError>;
+
 // Block pre-processing.
 // Writes the hash of the (current_block_number - N) block under its block number in the dedicated
-// contract state, where N=STORED_BLOCK_HASH_BUFFER.
+// contract state, where N=STORED_BLOCK_HASH_BUFFER; to be called on the block-level state, before
+// the first transaction of the block.
+// The old block hash must be given iff it exists; i.e., iff the current block number is at least N.
 pub fn pre_process_block(
     state: &mut dyn State,
+    block_context: &BlockContext,
     old_block_number_and_hash: Option<(BlockNumber, BlockHash)>,
-) {
-    if let Some((block_number, block_hash)) = old_block_number_and_hash {
-        state.set_storage_at(
-            ContractAddress::try_from(StarkFelt::from(constants::BLOCK_HASH_CONTRACT_ADDRESS))
-                .expect("Failed to convert `BLOCK_HASH_CONTRACT_ADDRESS` to ContractAddress."),
-            StorageKey::try_from(StarkFelt::from(block_number.0))
-                .expect("Failed to convert BlockNumber to StorageKey."),
-            block_hash.0,
-        );
+) -> BlockExecutionResult<()> {
+    let block_number = block_context.block_number;
+    let expected_old_block_number =
+        block_number.0.checked_sub(constants::STORED_BLOCK_HASH_BUFFER).map(BlockNumber);
+    match (expected_old_block_number, old_block_number_and_hash) {
+        (None, None) => Ok(()),
+        (Some(old_block_number), None) => {
+            Err(BlockExecutionError::MissingOldBlockHash { block_number, old_block_number })
+        }
+        (expected, Some((old_block_number, _))) if expected != Some(old_block_number) => {
+            Err(BlockExecutionError::UnexpectedOldBlockNumber {
+                block_number,
+                expected,
+                actual: old_block_number,
+            })
+        }
+        (_, Some((old_block_number, old_block_hash))) => {
+            state.set_storage_at(
+                ContractAddress::try_from(StarkFelt::from(constants::BLOCK_HASH_CONTRACT_ADDRESS))
+                    .expect("Failed to convert `BLOCK_HASH_CONTRACT_ADDRESS` to ContractAddress."),
+                StorageKey::try_from(StarkFelt::from(old_block_number.0))
+                    .expect("Failed to convert BlockNumber to StorageKey."),
+                old_block_hash.0,
+            );
+            Ok(())
+        }
     }
 }
+
+/// Re-executes the given block transactions over a witness of the state they were executed on
+/// (see `CachedState::get_initial_reads`), and returns the resulting state diff.
+/// Fails on any read outside the witness, even if the transaction itself was reverted or rejected.
+pub fn replay_block(
+    txs: Vec<Transaction>,
+    witness: StateMaps,
+    class_hash_to_class: ContractClassMapping,
+    block_context: &BlockContext,
+) -> StateResult<CommitmentStateDiff> {
+    let mut state = CachedState::from(WitnessStateReader::new(witness, class_hash_to_class));
+    for tx in txs {
+        // Transactions rejected in the original execution are rejected on replay as well.
+        let _tx_execution_result = tx.execute(&mut state, block_context, true, true);
+        if let Some(entry) = state.state.first_missing_entry() {
+            return Err(StateError::MissingWitnessEntry { entry });
+        }
+    }
+
+    Ok(state.to_state_diff())
+}
@@ -1,24 +1,139 @@
+use assert_matches::assert_matches;
+use indexmap::IndexMap;
+use pretty_assertions::assert_eq;
 use starknet_api::block::{BlockHash, BlockNumber};
-use starknet_api::core::ContractAddress;
-use starknet_api::hash::StarkFelt;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
 
 use crate::abi::constants;
-use crate::block_execution::pre_process_block;
-use crate::state::state_api::StateReader;
-use crate::test_utils::create_test_state;
+use crate::block_context::BlockContext;
+use crate::block_execution::{pre_process_block, replay_block, BlockExecutionError};
+use crate::execution::entry_point::{CallEntryPoint, Retdata};
+use crate::retdata;
+use crate::selector;
+use crate::state::errors::StateError;
+use crate::state::state_api::State;
+use crate::state::witness_state_reader::WitnessEntry;
+use crate::test_utils::{
+    create_test_state, trivial_external_entry_point, NonceManager, CURRENT_BLOCK_NUMBER, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS,
+};
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_trivial_validation_account,
+};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
 
 #[test]
 fn test_pre_process_block() {
     let mut state = create_test_state();
+    let block_context = BlockContext::create_for_testing();
+    let old_block_number = CURRENT_BLOCK_NUMBER - constants::STORED_BLOCK_HASH_BUFFER;
+    let old_block_hash = stark_felt!(20_u8);
+    pre_process_block(
+        &mut state,
+        &block_context,
+        Some((BlockNumber(old_block_number), BlockHash(old_block_hash))),
+    )
+    .unwrap();
 
-    let block_number: u64 = 10;
-    let block_hash = StarkFelt::from(20u32);
-    pre_process_block(&mut state, Some((BlockNumber(block_number), BlockHash(block_hash))));
+    // The hash is written to the block hash contract, and read by the syscall.
+    let block_hash_contract_address =
+        ContractAddress::try_from(StarkFelt::from(constants::BLOCK_HASH_CONTRACT_ADDRESS)).unwrap();
+    let key = StorageKey::try_from(StarkFelt::from(old_block_number)).unwrap();
+    assert_eq!(
+        state.to_state_diff().storage_updates,
+        IndexMap::from([(block_hash_contract_address, IndexMap::from([(key, old_block_hash)]))])
+    );
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("test_get_block_hash"),
+        calldata: calldata![stark_felt!(old_block_number)],
+        ..trivial_external_entry_point()
+    };
+    assert_eq!(
+        entry_point_call.execute_directly(&mut state).unwrap().execution.retdata,
+        retdata![old_block_hash]
+    );
+}
+
+#[test]
+fn test_pre_process_block_validation() {
+    let mut state = create_test_state();
+    let block_context = |block_number| BlockContext {
+        block_number: BlockNumber(block_number),
+        ..BlockContext::create_for_testing()
+    };
+    let old_block_hash = BlockHash(stark_felt!(20_u8));
+
+    // The first blocks have no old block hash.
+    pre_process_block(&mut state, &block_context(5), None).unwrap();
+    assert_matches!(
+        pre_process_block(&mut state, &block_context(5), Some((BlockNumber(0), old_block_hash)))
+            .unwrap_err(),
+        BlockExecutionError::UnexpectedOldBlockNumber { expected: None, .. }
+    );
+
+    assert_matches!(
+        pre_process_block(&mut state, &block_context(15), None).unwrap_err(),
+        BlockExecutionError::MissingOldBlockHash { old_block_number: BlockNumber(5), .. }
+    );
+    assert_matches!(
+        pre_process_block(&mut state, &block_context(15), Some((BlockNumber(4), old_block_hash)))
+            .unwrap_err(),
+        BlockExecutionError::UnexpectedOldBlockNumber {
+            expected: Some(BlockNumber(5)),
+            actual: BlockNumber(4),
+            ..
+        }
+    );
+    assert!(state.to_state_diff().storage_updates.is_empty());
+}
+
+#[test]
+fn test_replay_block() {
+    let block_context = &BlockContext::create_for_account_testing();
+    let mut state = create_state_with_trivial_validation_account();
+    let class_hash_to_class = state.state.class_hash_to_class.clone();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let mut nonce_manager = NonceManager::default();
+    let txs: Vec<Transaction> = [(15_u8, 3_u8), (16_u8, 4_u8)]
+        .into_iter()
+        .map(|(address, value)| {
+            let execute_calldata = calldata![
+                *contract_address.0.key(),              // Contract address.
+                selector!("test_storage_read_write").0, // EP selector.
+                stark_felt!(2_u8),                      // Calldata length.
+                stark_felt!(address),                   // Calldata: address.
+                stark_felt!(value)                      // Calldata: value.
+            ];
+            Transaction::AccountTransaction(account_invoke_tx(
+                execute_calldata,
+                account_address,
+                &mut nonce_manager,
+                Fee(MAX_FEE),
+            ))
+        })
+        .collect();
+    for tx in txs.clone() {
+        assert!(!tx.execute(&mut state, block_context, true, true).unwrap().is_reverted());
+    }
+    let mut witness = state.get_initial_reads();
+
+    // Replaying over the witness yields the same diff.
+    let replayed_state_diff =
+        replay_block(txs.clone(), witness.clone(), class_hash_to_class.clone(), block_context);
+    assert_eq!(replayed_state_diff.unwrap(), state.to_state_diff());
 
-    let written_hash = state.get_storage_at(
-        ContractAddress::try_from(StarkFelt::from(constants::BLOCK_HASH_CONTRACT_ADDRESS)).unwrap(),
-        StorageKey::try_from(StarkFelt::from(block_number)).unwrap(),
+    // A missing entry is detected, although the read fails the execution (which is reverted).
+    let missing_storage_key = (contract_address, StorageKey(patricia_key!(15_u8)));
+    witness.storage.remove(&missing_storage_key).unwrap();
+    assert_matches!(
+        replay_block(txs, witness, class_hash_to_class, block_context),
+        Err(StateError::MissingWitnessEntry { entry: WitnessEntry::Storage(storage_key) })
+        if storage_key == missing_storage_key
     );
-    assert_eq!(written_hash.unwrap(), block_hash);
 }
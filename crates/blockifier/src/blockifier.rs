@@ -0,0 +1,4 @@
+pub mod bouncer;
+pub mod execution_artifact;
+pub mod metrics;
+pub mod transaction_executor;
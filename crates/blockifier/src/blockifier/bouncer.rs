@@ -0,0 +1,283 @@
+use cairo_vm::vm::runners::builtin_runner::{
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
+};
+use derive_more::AddAssign;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::abi::constants;
+use crate::block_context::BlockContext;
+use crate::fee::gas_usage::{get_message_segment_length, get_onchain_data_segment_length};
+use crate::state::cached_state::{StateChangesCount, StateChangesKeys};
+use crate::transaction::objects::{
+    ResourcesMapping, TransactionExecutionInfo, TransactionExecutionResult,
+};
+
+#[cfg(test)]
+#[path = "bouncer_test.rs"]
+mod test;
+
+/// Why a transaction cannot be added to the block; the dimension is a field of [BouncerWeights]
+/// (or a builtin name).
+#[derive(Debug, Error)]
+pub enum BouncerError {
+    /// The transaction may fit in another block.
+    #[error(
+        "Transaction cannot be added to the current block, block capacity of {dimension} reached."
+    )]
+    BlockFull { dimension: &'static str },
+    /// The transaction does not fit even in an empty block.
+    #[error("Transaction {dimension} weight {weight} exceeds the maximum block capacity {max}.")]
+    TxTooLarge { dimension: &'static str, weight: usize, max: usize },
+}
+
+pub type BouncerResult<T> = Result<T, BouncerError>;
+
+/// The usage of each builtin the proving layout has; the layout bounds the number of instances a
+/// block may use.
+#[derive(AddAssign, Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BuiltinCount {
+    pub bitwise: usize,
+    pub ecdsa: usize,
+    pub ec_op: usize,
+    pub keccak: usize,
+    pub pedersen: usize,
+    pub poseidon: usize,
+    pub range_check: usize,
+}
+
+impl BuiltinCount {
+    pub fn max() -> Self {
+        Self {
+            bitwise: usize::MAX,
+            ecdsa: usize::MAX,
+            ec_op: usize::MAX,
+            keccak: usize::MAX,
+            pedersen: usize::MAX,
+            poseidon: usize::MAX,
+            range_check: usize::MAX,
+        }
+    }
+
+    /// Returns the builtin usage in the given VM resources.
+    pub fn from_vm_resources(vm_resources: &ResourcesMapping) -> Self {
+        let count =
+            |builtin_name: &str| vm_resources.0.get(builtin_name).copied().unwrap_or_default();
+        Self {
+            bitwise: count(BITWISE_BUILTIN_NAME),
+            ecdsa: count(SIGNATURE_BUILTIN_NAME),
+            ec_op: count(EC_OP_BUILTIN_NAME),
+            keccak: count(KECCAK_BUILTIN_NAME),
+            pedersen: count(HASH_BUILTIN_NAME),
+            poseidon: count(POSEIDON_BUILTIN_NAME),
+            range_check: count(RANGE_CHECK_BUILTIN_NAME),
+        }
+    }
+
+    /// Returns the usage of each builtin, by its name.
+    fn dimensions(&self) -> [(&'static str, usize); 7] {
+        [
+            (BITWISE_BUILTIN_NAME, self.bitwise),
+            (SIGNATURE_BUILTIN_NAME, self.ecdsa),
+            (EC_OP_BUILTIN_NAME, self.ec_op),
+            (KECCAK_BUILTIN_NAME, self.keccak),
+            (HASH_BUILTIN_NAME, self.pedersen),
+            (POSEIDON_BUILTIN_NAME, self.poseidon),
+            (RANGE_CHECK_BUILTIN_NAME, self.range_check),
+        ]
+    }
+}
+
+/// The weights a transaction (or a block) is measured by, when deciding whether it fits in a
+/// block.
+#[derive(AddAssign, Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BouncerWeights {
+    // The L1 gas consumed directly; including the state diff publication, unless the block
+    // publishes it in blobs (see `BlockContext::use_kzg_da`).
+    pub gas: usize,
+    pub n_steps: usize,
+    pub builtin_count: BuiltinCount,
+    pub n_events: usize,
+    pub n_messages: usize,
+    pub message_segment_length: usize,
+    // The number of felts the state diff adds to the block's data availability segment.
+    pub state_diff_size: usize,
+}
+
+impl BouncerWeights {
+    /// Returns a weights instance with no practical limit, to be used as a block capacity.
+    pub fn max() -> Self {
+        Self {
+            gas: usize::MAX,
+            n_steps: usize::MAX,
+            builtin_count: BuiltinCount::max(),
+            n_events: usize::MAX,
+            n_messages: usize::MAX,
+            message_segment_length: usize::MAX,
+            state_diff_size: usize::MAX,
+        }
+    }
+
+    /// Returns the weights of an executed transaction, given the count of its state changes (see
+    /// `Bouncer::marginal_state_changes_count`).
+    /// `l1_handler_payload_size` should be [None] iff the transaction is not an L1 handler.
+    pub fn from_tx_execution(
+        tx_execution_info: &TransactionExecutionInfo,
+        tx_state_changes_count: StateChangesCount,
+        l1_handler_payload_size: Option<usize>,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<Self> {
+        let actual_resources = &tx_execution_info.actual_resources;
+
+        let mut l2_to_l1_payloads_length = vec![];
+        for call_info in tx_execution_info.non_optional_call_infos() {
+            l2_to_l1_payloads_length.extend(call_info.get_sorted_l2_to_l1_payloads_length()?);
+        }
+
+        Ok(Self {
+            gas: actual_resources.direct_gas_vector(block_context)?.l1_gas,
+            n_steps: actual_resources
+                .vm_resources
+                .0
+                .get(constants::N_STEPS_RESOURCE)
+                .copied()
+                .unwrap_or_default(),
+            builtin_count: BuiltinCount::from_vm_resources(&actual_resources.vm_resources),
+            n_events: tx_execution_info.n_events(),
+            n_messages: tx_execution_info.n_messages(),
+            message_segment_length: get_message_segment_length(
+                &l2_to_l1_payloads_length,
+                l1_handler_payload_size,
+            ),
+            state_diff_size: get_onchain_data_segment_length(tx_state_changes_count),
+        })
+    }
+
+    /// Returns true iff each of the weights is not greater than its counterpart in `other`.
+    pub fn fits_in(&self, other: &Self) -> bool {
+        self.dimensions().zip(other.dimensions()).all(|((_, weight), (_, max))| weight <= max)
+    }
+
+    /// Returns the weights by dimension, in a fixed order.
+    fn dimensions(&self) -> impl Iterator<Item = (&'static str, usize)> {
+        [
+            ("gas", self.gas),
+            ("n_steps", self.n_steps),
+            ("n_events", self.n_events),
+            ("n_messages", self.n_messages),
+            ("message_segment_length", self.message_segment_length),
+            ("state_diff_size", self.state_diff_size),
+        ]
+        .into_iter()
+        .chain(self.builtin_count.dimensions())
+    }
+}
+
+/// The capacity of a block, per dimension; loadable, e.g., from the sequencer configuration.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BouncerConfig {
+    pub block_max_capacity: BouncerWeights,
+}
+
+impl BouncerConfig {
+    pub fn max() -> Self {
+        Self { block_max_capacity: BouncerWeights::max() }
+    }
+}
+
+/// Keeps track of the weights accumulated in the block under construction, and decides whether a
+/// transaction still fits in it. A transaction added to the block is pending until it is committed
+/// (see `try_update`).
+#[derive(Clone, Debug)]
+pub struct Bouncer {
+    /// The weights of the committed transactions.
+    pub accumulated_weights: BouncerWeights,
+    /// The keys of the state changes of the committed transactions.
+    pub state_changes_keys: StateChangesKeys,
+    pub config: BouncerConfig,
+    // The weights and state changes of the pending transaction.
+    pending_tx: Option<(BouncerWeights, StateChangesKeys)>,
+}
+
+impl Bouncer {
+    pub fn new(config: BouncerConfig) -> Self {
+        Self {
+            accumulated_weights: BouncerWeights::default(),
+            state_changes_keys: StateChangesKeys::default(),
+            config,
+            pending_tx: None,
+        }
+    }
+
+    /// Returns the count of the given state changes of a transaction that are not already made in
+    /// the block; e.g., a contract modified by an earlier transaction adds no header to the state
+    /// diff of the block.
+    pub fn marginal_state_changes_count(
+        &self,
+        tx_state_changes_keys: &StateChangesKeys,
+    ) -> StateChangesCount {
+        tx_state_changes_keys.difference(&self.state_changes_keys).count()
+    }
+
+    /// Returns true iff a transaction of the given weights can be added to the block.
+    pub fn has_room(&self, tx_weights: BouncerWeights) -> bool {
+        self.full_dimension(tx_weights).is_none()
+    }
+
+    /// Returns the first dimension in which the block has no room for the given weights.
+    fn full_dimension(&self, tx_weights: BouncerWeights) -> Option<&'static str> {
+        self.accumulated_weights
+            .dimensions()
+            .zip(tx_weights.dimensions())
+            .zip(self.config.block_max_capacity.dimensions())
+            .find(|&(((_, accumulated), (_, weight)), (_, max))| {
+                accumulated.checked_add(weight).map_or(true, |total| total > max)
+            })
+            .map(|((_, (dimension, _)), _)| dimension)
+    }
+
+    /// Adds the weights and state changes of a transaction to the block, if it fits; the error
+    /// tells whether it may fit in another block. The transaction is pending until `commit` adds
+    /// it to the accumulated weights and state changes; `rollback` discards it, e.g., if the
+    /// transaction is rejected afterwards.
+    ///
+    /// Panics if another transaction is pending.
+    pub fn try_update(
+        &mut self,
+        tx_weights: BouncerWeights,
+        tx_state_changes_keys: &StateChangesKeys,
+    ) -> BouncerResult<()> {
+        assert!(
+            self.pending_tx.is_none(),
+            "The pending transaction must be committed or rolled back first."
+        );
+        let exceeded_dimension = tx_weights
+            .dimensions()
+            .zip(self.config.block_max_capacity.dimensions())
+            .find(|&((_, weight), (_, max))| weight > max);
+        if let Some(((dimension, weight), (_, max))) = exceeded_dimension {
+            return Err(BouncerError::TxTooLarge { dimension, weight, max });
+        }
+        if let Some(dimension) = self.full_dimension(tx_weights) {
+            return Err(BouncerError::BlockFull { dimension });
+        }
+
+        self.pending_tx = Some((tx_weights, tx_state_changes_keys.clone()));
+        Ok(())
+    }
+
+    /// Adds the pending transaction (if any) to the accumulated weights and state changes; to be
+    /// called once its state diff is committed to the block state.
+    pub fn commit(&mut self) {
+        if let Some((tx_weights, tx_state_changes_keys)) = self.pending_tx.take() {
+            self.accumulated_weights += tx_weights;
+            self.state_changes_keys.extend(&tx_state_changes_keys);
+        }
+    }
+
+    /// Discards the pending transaction (if any).
+    pub fn rollback(&mut self) {
+        self.pending_tx = None;
+    }
+}
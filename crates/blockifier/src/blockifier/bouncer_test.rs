@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use assert_matches::assert_matches;
+use serde_json::json;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::state::StorageKey;
+use starknet_api::{contract_address, patricia_key};
+
+use crate::blockifier::bouncer::{
+    Bouncer, BouncerConfig, BouncerError, BouncerWeights, BuiltinCount,
+};
+use crate::state::cached_state::{StateChangesCount, StateChangesKeys};
+
+fn weights(n_steps: usize, n_events: usize) -> BouncerWeights {
+    BouncerWeights { gas: 1, n_steps, n_events, state_diff_size: 4, ..Default::default() }
+}
+
+#[test]
+fn test_bouncer_try_update() {
+    let block_max_capacity =
+        BouncerWeights { n_steps: 100, n_events: 3, ..BouncerConfig::max().block_max_capacity };
+    let mut bouncer = Bouncer::new(BouncerConfig { block_max_capacity });
+
+    bouncer.try_update(weights(60, 1), &StateChangesKeys::default()).unwrap();
+    bouncer.commit();
+    bouncer.try_update(weights(40, 1), &StateChangesKeys::default()).unwrap();
+    bouncer.commit();
+    assert_eq!(
+        bouncer.accumulated_weights,
+        BouncerWeights {
+            gas: 2,
+            n_steps: 100,
+            n_events: 2,
+            state_diff_size: 8,
+            ..Default::default()
+        }
+    );
+
+    // A transaction that fits in an empty block, but not in the current one.
+    assert!(!bouncer.has_room(weights(1, 0)));
+    assert_matches!(
+        bouncer.try_update(weights(1, 0), &StateChangesKeys::default()),
+        Err(BouncerError::BlockFull { dimension: "n_steps" })
+    );
+
+    // A transaction that does not fit even in an empty block.
+    let too_big_weights = weights(0, 4);
+    assert_matches!(
+        bouncer.try_update(too_big_weights, &StateChangesKeys::default()),
+        Err(BouncerError::TxTooLarge { dimension: "n_events", weight: 4, max: 3 })
+    );
+
+    // Failed updates do not affect the accumulated weights.
+    assert_eq!(bouncer.accumulated_weights.n_steps, 100);
+    assert_eq!(bouncer.accumulated_weights.n_events, 2);
+}
+
+/// A transaction is counted in the block only once committed; e.g., a transaction rejected after
+/// it was weighed leaves the block as it was.
+#[test]
+fn test_bouncer_commit_and_rollback() {
+    let mut bouncer = Bouncer::new(BouncerConfig::max());
+    let first_tx_keys = storage_changes_keys(&[("0x1", 1)]);
+    bouncer.try_update(weights(60, 1), &first_tx_keys).unwrap();
+    bouncer.commit();
+    let (accumulated_weights, state_changes_keys) =
+        (bouncer.accumulated_weights, bouncer.state_changes_keys.clone());
+
+    let second_tx_keys = storage_changes_keys(&[("0x1", 2), ("0x2", 1)]);
+    bouncer.try_update(weights(40, 1), &second_tx_keys).unwrap();
+    // Weighed but not committed.
+    assert_eq!(bouncer.accumulated_weights, accumulated_weights);
+    assert_eq!(bouncer.state_changes_keys, state_changes_keys);
+    bouncer.rollback();
+    assert_eq!(bouncer.accumulated_weights, accumulated_weights);
+    assert_eq!(bouncer.state_changes_keys, state_changes_keys);
+    assert_eq!(
+        bouncer.marginal_state_changes_count(&second_tx_keys),
+        StateChangesCount { n_storage_updates: 2, n_modified_contracts: 1, ..Default::default() }
+    );
+
+    // The transaction may then be added again.
+    bouncer.try_update(weights(40, 1), &second_tx_keys).unwrap();
+    bouncer.commit();
+    let mut expected_state_changes_keys = first_tx_keys;
+    expected_state_changes_keys.extend(&second_tx_keys);
+    assert_eq!(bouncer.accumulated_weights.n_steps, 100);
+    assert_eq!(bouncer.state_changes_keys, expected_state_changes_keys);
+}
+
+#[test]
+fn test_bouncer_max_capacity_does_not_overflow() {
+    let mut bouncer = Bouncer::new(BouncerConfig::max());
+    bouncer.try_update(BouncerWeights::max(), &StateChangesKeys::default()).unwrap();
+    bouncer.commit();
+
+    assert_matches!(
+        bouncer.try_update(weights(1, 0), &StateChangesKeys::default()),
+        Err(BouncerError::BlockFull { dimension: "gas" })
+    );
+}
+
+/// Each builtin is bounded separately; e.g., by the block's capacity in the proving layout.
+#[test]
+fn test_bouncer_builtin_capacity() {
+    let block_max_capacity = BouncerWeights {
+        builtin_count: BuiltinCount { poseidon: 10, ..BuiltinCount::max() },
+        ..BouncerWeights::max()
+    };
+    let mut bouncer = Bouncer::new(BouncerConfig { block_max_capacity });
+    let tx_weights = |poseidon, pedersen| BouncerWeights {
+        builtin_count: BuiltinCount { poseidon, pedersen, ..Default::default() },
+        ..Default::default()
+    };
+
+    bouncer.try_update(tx_weights(6, 100), &StateChangesKeys::default()).unwrap();
+    bouncer.commit();
+    bouncer.try_update(tx_weights(0, 100), &StateChangesKeys::default()).unwrap();
+    bouncer.commit();
+    assert_matches!(
+        bouncer.try_update(tx_weights(5, 0), &StateChangesKeys::default()),
+        Err(BouncerError::BlockFull { dimension: "poseidon_builtin" })
+    );
+    assert_matches!(
+        bouncer.try_update(tx_weights(11, 0), &StateChangesKeys::default()),
+        Err(BouncerError::TxTooLarge { dimension: "poseidon_builtin", weight: 11, max: 10 })
+    );
+}
+
+#[test]
+fn test_bouncer_config_deserialization() {
+    let raw_config = json!({
+        "block_max_capacity": {
+            "gas": 2500000,
+            "n_steps": 2500000,
+            "builtin_count": {
+                "bitwise": 39062,
+                "ecdsa": 1220,
+                "ec_op": 2441,
+                "keccak": 1220,
+                "pedersen": 78125,
+                "poseidon": 78125,
+                "range_check": 156250
+            },
+            "n_events": 5000,
+            "n_messages": 500,
+            "message_segment_length": 3750,
+            "state_diff_size": 20000
+        }
+    });
+    let config: BouncerConfig = serde_json::from_value(raw_config.clone()).unwrap();
+    assert_eq!(config.block_max_capacity.builtin_count.range_check, 156250);
+    assert_eq!(config.block_max_capacity.state_diff_size, 20000);
+    assert_eq!(serde_json::to_value(config).unwrap(), raw_config);
+}
+
+fn storage_changes_keys(cells: &[(&str, u8)]) -> StateChangesKeys {
+    let storage_keys: HashSet<(ContractAddress, StorageKey)> = cells
+        .iter()
+        .map(|&(address, key)| (contract_address!(address), StorageKey(patricia_key!(key))))
+        .collect();
+    StateChangesKeys {
+        modified_contracts: storage_keys.iter().map(|(address, _)| *address).collect(),
+        storage_keys,
+        ..Default::default()
+    }
+}
+
+/// A transaction is only weighed by the state changes not already made in the block.
+#[test]
+fn test_marginal_state_changes_count() {
+    let mut bouncer = Bouncer::new(BouncerConfig::max());
+    let first_tx_keys = storage_changes_keys(&[("0x1", 1), ("0x1", 2)]);
+    assert_eq!(
+        bouncer.marginal_state_changes_count(&first_tx_keys),
+        StateChangesCount { n_storage_updates: 2, n_modified_contracts: 1, ..Default::default() }
+    );
+    bouncer.try_update(weights(0, 0), &first_tx_keys).unwrap();
+    bouncer.commit();
+
+    // One new cell in an already modified contract, and one in a new contract.
+    let second_tx_keys = storage_changes_keys(&[("0x1", 2), ("0x1", 3), ("0x2", 1)]);
+    assert_eq!(
+        bouncer.marginal_state_changes_count(&second_tx_keys),
+        StateChangesCount { n_storage_updates: 2, n_modified_contracts: 1, ..Default::default() }
+    );
+
+    // A failed update does not affect the block's state changes.
+    let block_max_capacity = BouncerWeights { n_steps: 0, ..BouncerWeights::max() };
+    bouncer.config = BouncerConfig { block_max_capacity };
+    assert_matches!(
+        bouncer.try_update(weights(1, 0), &second_tx_keys),
+        Err(BouncerError::TxTooLarge { dimension: "n_steps", .. })
+    );
+    assert_eq!(bouncer.state_changes_keys, first_tx_keys);
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use cairo_vm::types::errors::program_errors::ProgramError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::{Fee, Transaction as StarknetApiTransaction, TransactionHash};
+use thiserror::Error;
+
+use crate::block_context::BlockContext;
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::cached_state::{CachedState, ContractStorageKey, StateMaps};
+use crate::state::errors::StateError;
+use crate::state::state_api::StateReader;
+use crate::state::witness_state_reader::WitnessStateReader;
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[cfg(test)]
+#[path = "execution_artifact_test.rs"]
+mod test;
+
+#[derive(Debug, Error)]
+pub enum ExecutionArtifactError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error("Missing the raw class of class hash {0}.")]
+    MissingRawClass(ClassHash),
+    #[error(transparent)]
+    ProgramError(#[from] ProgramError),
+    #[error(transparent)]
+    StateError(#[from] StateError),
+    #[error(transparent)]
+    TransactionExecutionError(#[from] TransactionExecutionError),
+}
+
+pub type ExecutionArtifactResult<T> = Result<T, ExecutionArtifactError>;
+
+/// A contract class as compiled by the Cairo toolchain; unlike [ContractClass], serializable.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RawContractClass {
+    /// A compiled Cairo 0 class (JSON).
+    V0(serde_json::Value),
+    V1(CasmContractClass),
+}
+
+impl RawContractClass {
+    pub fn compile(&self) -> Result<ContractClass, ProgramError> {
+        match self {
+            Self::V0(raw_contract_class) => {
+                Ok(ContractClassV0::try_from_json_string(&raw_contract_class.to_string())?.into())
+            }
+            Self::V1(casm_contract_class) => {
+                Ok(ContractClassV1::try_from(casm_contract_class.clone())?.into())
+            }
+        }
+    }
+}
+
+/// Provides the raw classes of the classes used by executions, to embed in their artifacts.
+pub trait RawClassProvider: Send + Sync {
+    fn get_raw_class(&self, class_hash: &ClassHash) -> Option<RawContractClass>;
+}
+
+impl RawClassProvider for HashMap<ClassHash, RawContractClass> {
+    fn get_raw_class(&self, class_hash: &ClassHash) -> Option<RawContractClass> {
+        self.get(class_hash).cloned()
+    }
+}
+
+/// A self-contained record of a transaction execution, to reproduce it elsewhere (e.g., from a
+/// bug report): the transaction, its block context, the initial values of the state cells it read
+/// (see `CachedState::get_initial_reads`) and the classes it used.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExecutionArtifact {
+    pub block_context: BlockContext,
+    pub tx: StarknetApiTransaction,
+    pub tx_hash: TransactionHash,
+    pub paid_fee_on_l1: Option<Fee>,
+    pub deployed_contract_address: Option<ContractAddress>,
+    pub charge_fee: bool,
+    #[serde(serialize_with = "serialize_state_maps", deserialize_with = "deserialize_state_maps")]
+    pub initial_reads: StateMaps,
+    /// The classes read from the state.
+    pub classes: BTreeMap<ClassHash, RawContractClass>,
+    /// The class declared by the transaction, if it is a declaration.
+    pub declared_class: Option<RawContractClass>,
+}
+
+impl ExecutionArtifact {
+    /// Captures the execution of the given transaction through the given state (e.g., the
+    /// transactional state it was executed on), which must not have been used by any other
+    /// transaction.
+    ///
+    /// A re-declaration of a class already in the state is not reproduced, as the declared class
+    /// is not served by the replayed state.
+    pub fn capture<S: StateReader>(
+        tx: &Transaction,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        state: &CachedState<S>,
+        raw_class_provider: &dyn RawClassProvider,
+    ) -> ExecutionArtifactResult<Self> {
+        let get_raw_class = |class_hash: &ClassHash| {
+            raw_class_provider
+                .get_raw_class(class_hash)
+                .ok_or(ExecutionArtifactError::MissingRawClass(*class_hash))
+        };
+        let (tx_hash, paid_fee_on_l1, deployed_contract_address, declared_class_hash, api_tx) =
+            match tx {
+                Transaction::AccountTransaction(AccountTransaction::Declare(declare)) => (
+                    declare.tx_hash(),
+                    None,
+                    None,
+                    Some(declare.class_hash()),
+                    StarknetApiTransaction::Declare(declare.tx().clone()),
+                ),
+                Transaction::AccountTransaction(AccountTransaction::DeployAccount(
+                    deploy_account,
+                )) => (
+                    deploy_account.tx_hash,
+                    None,
+                    Some(deploy_account.contract_address),
+                    None,
+                    StarknetApiTransaction::DeployAccount(deploy_account.tx.clone()),
+                ),
+                Transaction::AccountTransaction(AccountTransaction::Invoke(invoke)) => (
+                    invoke.tx_hash,
+                    None,
+                    None,
+                    None,
+                    StarknetApiTransaction::Invoke(invoke.tx.clone()),
+                ),
+                Transaction::L1HandlerTransaction(l1_handler) => (
+                    l1_handler.tx_hash,
+                    Some(l1_handler.paid_fee_on_l1),
+                    None,
+                    None,
+                    StarknetApiTransaction::L1Handler(l1_handler.tx.clone()),
+                ),
+            };
+
+        let (_, class_hash_to_class) = state.get_writes();
+        let classes = class_hash_to_class
+            .keys()
+            .filter(|&class_hash| Some(*class_hash) != declared_class_hash)
+            .map(|class_hash| Ok((*class_hash, get_raw_class(class_hash)?)))
+            .collect::<ExecutionArtifactResult<_>>()?;
+        let declared_class = declared_class_hash.as_ref().map(get_raw_class).transpose()?;
+
+        Ok(Self {
+            block_context: block_context.clone(),
+            tx: api_tx,
+            tx_hash,
+            paid_fee_on_l1,
+            deployed_contract_address,
+            charge_fee,
+            initial_reads: state.get_initial_reads(),
+            classes,
+            declared_class,
+        })
+    }
+
+    pub fn dump(&self, path: &Path) -> ExecutionArtifactResult<()> {
+        Ok(fs::write(path, serde_json::to_string(self)?)?)
+    }
+
+    pub fn load(path: &Path) -> ExecutionArtifactResult<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Re-executes the transaction over its recorded initial reads (see `WitnessStateReader`).
+    /// Fails on any read outside them, even if the transaction itself was reverted or rejected.
+    pub fn replay(&self) -> ExecutionArtifactResult<TransactionExecutionInfo> {
+        let class_hash_to_class = self
+            .classes
+            .iter()
+            .map(|(class_hash, raw_class)| Ok((*class_hash, raw_class.compile()?)))
+            .collect::<Result<_, ProgramError>>()?;
+        let declared_class =
+            self.declared_class.as_ref().map(RawContractClass::compile).transpose()?;
+        let tx = Transaction::from_api(
+            self.tx.clone(),
+            self.tx_hash,
+            declared_class,
+            self.paid_fee_on_l1,
+            self.deployed_contract_address,
+        )?;
+
+        let mut state = CachedState::from(WitnessStateReader::new(
+            self.initial_reads.clone(),
+            class_hash_to_class,
+        ));
+        let tx_execution_result =
+            tx.execute(&mut state, &self.block_context, self.charge_fee, true);
+        if let Some(entry) = state.state.first_missing_entry() {
+            return Err(StateError::MissingWitnessEntry { entry }.into());
+        }
+
+        Ok(tx_execution_result?)
+    }
+}
+
+/// Replays the execution artifact dumped to the given path (see `ExecutionArtifact::replay`).
+pub fn replay_artifact(path: &Path) -> ExecutionArtifactResult<TransactionExecutionInfo> {
+    ExecutionArtifact::load(path)?.replay()
+}
+
+/// [StateMaps], with its entries sorted, for a deterministic serialization.
+#[derive(Deserialize, Serialize)]
+struct SerializableStateMaps {
+    nonces: Vec<(ContractAddress, Nonce)>,
+    class_hashes: Vec<(ContractAddress, ClassHash)>,
+    storage: Vec<(ContractStorageKey, StarkFelt)>,
+    compiled_class_hashes: Vec<(ClassHash, CompiledClassHash)>,
+}
+
+fn sorted_entries<K: Copy + Ord, V: Copy>(map: &HashMap<K, V>) -> Vec<(K, V)> {
+    let mut entries: Vec<(K, V)> = map.iter().map(|(key, value)| (*key, *value)).collect();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+fn serialize_state_maps<S: Serializer>(
+    state_maps: &StateMaps,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    SerializableStateMaps {
+        nonces: sorted_entries(&state_maps.nonces),
+        class_hashes: sorted_entries(&state_maps.class_hashes),
+        storage: sorted_entries(&state_maps.storage),
+        compiled_class_hashes: sorted_entries(&state_maps.compiled_class_hashes),
+    }
+    .serialize(serializer)
+}
+
+fn deserialize_state_maps<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<StateMaps, D::Error> {
+    let state_maps = SerializableStateMaps::deserialize(deserializer)?;
+    Ok(StateMaps {
+        nonces: state_maps.nonces.into_iter().collect(),
+        class_hashes: state_maps.class_hashes.into_iter().collect(),
+        storage: state_maps.storage.into_iter().collect(),
+        compiled_class_hashes: state_maps.compiled_class_hashes.into_iter().collect(),
+    })
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::block_context::BlockContext;
+use crate::blockifier::bouncer::BouncerConfig;
+use crate::blockifier::execution_artifact::{
+    replay_artifact, ExecutionArtifact, ExecutionArtifactError, RawContractClass,
+};
+use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::execution::contract_class::{ContractClass, ContractClassV1};
+use crate::selector;
+use crate::state::errors::StateError;
+use crate::state::witness_state_reader::WitnessEntry;
+use crate::test_utils::{
+    get_raw_contract_class, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH, ERC20_CONTRACT_PATH,
+    MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+    TEST_CONTRACT_CAIRO0_PATH, TEST_CONTRACT_CAIRO1_PATH, TEST_ERC20_CONTRACT_CLASS_HASH,
+};
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_trivial_validation_account,
+};
+use crate::transaction::transaction_execution::Transaction;
+
+/// The raw classes of the state of `create_state_with_trivial_validation_account`.
+fn raw_classes() -> HashMap<ClassHash, RawContractClass> {
+    [
+        (TEST_ACCOUNT_CONTRACT_CLASS_HASH, ACCOUNT_CONTRACT_CAIRO0_PATH),
+        (TEST_CLASS_HASH, TEST_CONTRACT_CAIRO0_PATH),
+        (TEST_ERC20_CONTRACT_CLASS_HASH, ERC20_CONTRACT_PATH),
+    ]
+    .into_iter()
+    .map(|(class_hash, contract_path)| {
+        let raw_class = serde_json::from_str(&get_raw_contract_class(contract_path)).unwrap();
+        (class_hash!(class_hash), RawContractClass::V0(raw_class))
+    })
+    .collect()
+}
+
+/// Executes a transfer of the fee token, capturing its artifact.
+fn capture_transfer() -> (TransactionExecutionInfo, ExecutionArtifact) {
+    let block_context = BlockContext::create_for_account_testing();
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let mut executor = TransactionExecutor::new(
+        create_state_with_trivial_validation_account(),
+        block_context,
+        BouncerConfig::max(),
+        true,
+    )
+    .unwrap()
+    .with_artifact_capture(Arc::new(raw_classes()));
+
+    let transfer_calldata = calldata![
+        *fee_token_address.0.key(), // Contract address.
+        selector!("transfer").0,    // EP selector.
+        stark_felt!(3_u8),          // Calldata length.
+        stark_felt!("0x123"),       // Calldata: recipient.
+        stark_felt!(10_u8),         // Calldata: amount (low).
+        stark_felt!(0_u8)           // Calldata: amount (high).
+    ];
+    let transfer = Transaction::AccountTransaction(account_invoke_tx(
+        transfer_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default(),
+        Fee(MAX_FEE),
+    ));
+    let (tx_execution_info, _) = executor.execute(transfer, true).unwrap();
+    let [artifact]: [ExecutionArtifact; 1] = executor.take_artifacts().try_into().unwrap();
+
+    (tx_execution_info, artifact)
+}
+
+#[test]
+fn test_capture_and_replay() {
+    let (tx_execution_info, artifact) = capture_transfer();
+    assert!(!tx_execution_info.is_reverted());
+    assert_eq!(artifact.classes.len(), 2);
+
+    let path = std::env::temp_dir().join("blockifier_transfer_execution_artifact.json");
+    artifact.dump(&path).unwrap();
+    let raw_artifact = fs::read_to_string(&path).unwrap();
+    assert!(!raw_artifact.contains(env!("CARGO_MANIFEST_DIR")));
+    assert_eq!(replay_artifact(&path).unwrap(), tx_execution_info);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_replay_with_missing_read() {
+    let (_, mut artifact) = capture_transfer();
+    // Any of the recorded reads is required by the replay.
+    let storage_cell =
+        *artifact.initial_reads.storage.keys().next().expect("The transfer must read storage.");
+    artifact.initial_reads.storage.remove(&storage_cell);
+
+    assert_matches!(
+        artifact.replay(),
+        Err(ExecutionArtifactError::StateError(StateError::MissingWitnessEntry {
+            entry: WitnessEntry::Storage(cell)
+        })) if cell == storage_cell
+    );
+}
+
+#[test]
+fn test_compile_raw_class() {
+    let casm_contract_class =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO1_PATH)).unwrap();
+    assert_eq!(
+        RawContractClass::V1(casm_contract_class).compile().unwrap(),
+        ContractClass::V1(ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH))
+    );
+}
@@ -0,0 +1,40 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// The outcome of a transaction given to the `TransactionExecutor`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionOutcome {
+    /// Executed successfully, and committed to the block.
+    Succeeded,
+    /// Reverted; its fee is charged, and it is committed to the block.
+    Reverted,
+    /// Not included in the block (e.g., failed validation, or does not fit in the block).
+    Rejected,
+}
+
+/// Why a block was closed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockCloseReason {
+    /// A transaction did not fit in the remaining capacity of the block.
+    Full,
+    /// Finalized by the caller, with capacity to spare.
+    Finalized,
+}
+
+/// Hooks for the metrics of the execution (e.g., counters and histograms), to be bridged by
+/// integrators to their metrics library; all hooks are no-ops by default.
+///
+/// The hooks are called on the executing thread, and should return quickly.
+pub trait ExecutionMetrics: Debug + Send + Sync {
+    /// Called once per transaction given to the `TransactionExecutor`, with its execution time and
+    /// the VM steps it consumed (zero if rejected).
+    fn tx_executed(&self, _outcome: TransactionOutcome, _duration: Duration, _n_steps: usize) {}
+
+    /// Called once per block, at `TransactionExecutor::finalize`, with the number of transactions
+    /// committed to it.
+    fn block_closed(&self, _reason: BlockCloseReason, _n_txs: usize) {}
+
+    /// Called when a transaction first uses a contract class: a hit if the class is cached (by the
+    /// block, or by the global contract cache), a miss if it is read from the state reader.
+    fn class_cache(&self, _hit: bool) {}
+}
@@ -0,0 +1,647 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+use thiserror::Error;
+use tracing::{info_span, Span};
+
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::abi::constants;
+use crate::block_context::{BlockContext, BlockContextError};
+use crate::block_execution::{pre_process_block, BlockExecutionError};
+use crate::blockifier::bouncer::{Bouncer, BouncerConfig, BouncerError, BouncerWeights};
+use crate::blockifier::execution_artifact::{ExecutionArtifact, RawClassProvider};
+use crate::blockifier::metrics::{BlockCloseReason, ExecutionMetrics, TransactionOutcome};
+use crate::concurrency::fee_utils::{
+    add_uint256, fill_sequencer_balance_reads, requires_sequential_execution,
+    sequencer_balance_cells,
+};
+#[cfg(feature = "concurrency")]
+use crate::concurrency::validation_phase::{is_validated_first, validate_txs};
+use crate::concurrency::validation_phase::{PrecomputedValidation, ValidationPhaseStats};
+#[cfg(feature = "concurrency")]
+use crate::concurrency::worker_executor::{CommittedChunk, WorkerExecutor};
+#[cfg(feature = "concurrency")]
+use crate::state::cached_state::MutRefState;
+use crate::state::cached_state::{
+    CachedState, CommitmentStateDiff, StateChangesKeys, TransactionalState,
+};
+use crate::state::errors::StateError;
+#[cfg(feature = "concurrency")]
+use crate::state::state_api::StateResult;
+use crate::state::state_api::{State, StateReader};
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[cfg(test)]
+#[path = "transaction_executor_test.rs"]
+mod test;
+
+#[derive(Debug, Error)]
+pub enum TransactionExecutorError {
+    #[error(transparent)]
+    BlockContextError(#[from] BlockContextError),
+    #[error(transparent)]
+    BlockExecutionError(#[from] BlockExecutionError),
+    #[error(transparent)]
+    BouncerError(#[from] BouncerError),
+    #[error(transparent)]
+    StateError(#[from] StateError),
+    #[error(transparent)]
+    TransactionExecutionError(#[from] TransactionExecutionError),
+}
+
+pub type TransactionExecutorResult<T> = Result<T, TransactionExecutorError>;
+
+/// Executes the transactions of a single block, one after the other, on a block-level state.
+///
+/// The execution is traced under a `block_execution` span, which lasts as long as the executor;
+/// each transaction is traced under a `transaction` span, holding its index in the block and its
+/// hash, and each call under an `entry_point` span (see `CallEntryPoint::execute`). Spans and
+/// events hold no calldata, and cost a filter check only when no subscriber is installed.
+pub struct TransactionExecutor<S: StateReader> {
+    pub block_context: BlockContext,
+    pub bouncer: Bouncer,
+    pub state: CachedState<S>,
+    /// The validations run by `execute_txs` in parallel validation mode (see
+    /// `ConcurrencyConfig::parallel_validation`).
+    pub validation_phase_stats: ValidationPhaseStats,
+    // Per fee token, the (low, high) amount credited to the sequencer and not yet added to its
+    // balance.
+    sequencer_balance_deltas: HashMap<ContractAddress, (StarkFelt, StarkFelt)>,
+    block_span: Span,
+    // The number of transactions given to the executor; i.e., the index of the next one.
+    n_txs: usize,
+    metrics: Option<Arc<dyn ExecutionMetrics>>,
+    // The number of transactions committed to the block.
+    n_committed_txs: usize,
+    // Whether a transaction was rejected for not fitting in the block.
+    is_block_full: bool,
+    // Set if the executions are captured (see `with_artifact_capture`).
+    raw_class_provider: Option<Arc<dyn RawClassProvider>>,
+    artifacts: Vec<ExecutionArtifact>,
+}
+
+impl<S: StateReader> TransactionExecutor<S> {
+    /// Creates an executor for the given block; fails if the block context is misconfigured (see
+    /// `BlockContext::validate`). `enforce_fee` is whether the block's transactions are charged.
+    pub fn new(
+        state: CachedState<S>,
+        block_context: BlockContext,
+        bouncer_config: BouncerConfig,
+        enforce_fee: bool,
+    ) -> TransactionExecutorResult<Self> {
+        log::debug!("Initializing Transaction Executor...");
+        block_context.validate(enforce_fee)?;
+        let bouncer = Bouncer::new(bouncer_config);
+        log::debug!("Initialized Transaction Executor.");
+        let block_span = info_span!("block_execution", block_number = block_context.block_number.0);
+        Ok(Self {
+            block_context,
+            bouncer,
+            state,
+            validation_phase_stats: ValidationPhaseStats::default(),
+            sequencer_balance_deltas: HashMap::new(),
+            block_span,
+            n_txs: 0,
+            metrics: None,
+            n_committed_txs: 0,
+            is_block_full: false,
+            raw_class_provider: None,
+            artifacts: Vec::new(),
+        })
+    }
+
+    /// Reports the execution to the given metrics (see `ExecutionMetrics`).
+    pub fn with_metrics(mut self, metrics: Arc<dyn ExecutionMetrics>) -> Self {
+        self.state.set_metrics(metrics.clone());
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Captures an artifact of each transaction execution (see `ExecutionArtifact::capture`), to be
+    /// taken by `take_artifacts`; the given provider supplies the raw classes it embeds.
+    /// Executions whose artifacts would not replay identically are not captured: in concurrency
+    /// mode, those not crediting the sequencer (see `execute`) and those of chunks; and those
+    /// reusing a validation of the parallel validation phase.
+    pub fn with_artifact_capture(mut self, raw_class_provider: Arc<dyn RawClassProvider>) -> Self {
+        self.raw_class_provider = Some(raw_class_provider);
+        self
+    }
+
+    /// Returns the artifacts captured since the previous call.
+    pub fn take_artifacts(&mut self) -> Vec<ExecutionArtifact> {
+        std::mem::take(&mut self.artifacts)
+    }
+
+    /// Writes the old block hash to the block state (see `block_execution::pre_process_block`);
+    /// to be called before the first transaction.
+    pub fn pre_process_block(
+        &mut self,
+        old_block_number_and_hash: Option<(BlockNumber, BlockHash)>,
+    ) -> TransactionExecutorResult<()> {
+        let _block_span = self.block_span.clone().entered();
+        Ok(pre_process_block(&mut self.state, &self.block_context, old_block_number_and_hash)?)
+    }
+
+    /// Executes the given transaction on the state maintained by the executor.
+    /// Returns the execution info, together with the state diff of the transaction.
+    /// Successful and reverted transactions are committed to the block state; rejected ones, and
+    /// ones that do not fit in the block, leave it unchanged.
+    ///
+    /// If concurrency is enabled (see `BlockContext::concurrency_config`), fee transfers do not
+    /// credit the sequencer, whose balance is accessed by every transaction; the fees are
+    /// accumulated instead, and added to its balance at `finalize`. The transaction state diffs
+    /// then omit the sequencer balance.
+    pub fn execute(
+        &mut self,
+        tx: Transaction,
+        charge_fee: bool,
+    ) -> TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)> {
+        let _tx_span = self.next_tx_span(&tx).entered();
+        let start = self.block_context.clock.now();
+        let result = self.execute_tx(tx, charge_fee);
+        self.record_tx_result(&result, self.elapsed_since(start));
+        result
+    }
+
+    fn execute_tx(
+        &mut self,
+        tx: Transaction,
+        charge_fee: bool,
+    ) -> TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)> {
+        let l1_handler_payload_size = l1_handler_payload_size(&tx)?;
+
+        if self.block_context.concurrency_config.enabled {
+            if let Some(result) =
+                self.execute_without_sequencer_credit(&tx, charge_fee, l1_handler_payload_size)
+            {
+                return result;
+            }
+            // The transaction depends on the actual sequencer balance.
+            self.add_sequencer_balance_deltas()?;
+        }
+
+        self.execute_on_block_state(tx, charge_fee, None, l1_handler_payload_size)
+    }
+
+    /// Executes the given transaction on the block state, applying the given validation (if any)
+    /// instead of running it again (see `AccountTransaction::execute_raw_with_precomputed_validation`).
+    fn execute_on_block_state(
+        &mut self,
+        tx: Transaction,
+        charge_fee: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
+        l1_handler_payload_size: Option<usize>,
+    ) -> TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)> {
+        let captured_tx = match (&self.raw_class_provider, &precomputed_validation) {
+            (Some(_), None) => Some(tx.clone()),
+            _ => None,
+        };
+        let mut transactional_state = CachedState::create_transactional(&mut self.state);
+        let validate = true;
+        let tx_execution_result = match (tx, precomputed_validation) {
+            (Transaction::AccountTransaction(account_tx), Some(validation)) => account_tx
+                .execute_raw_with_precomputed_validation(
+                    &mut transactional_state,
+                    &self.block_context,
+                    charge_fee,
+                    validation,
+                ),
+            (tx, _) => {
+                tx.execute_raw(&mut transactional_state, &self.block_context, charge_fee, validate)
+            }
+        };
+        if let (Some(tx), Some(raw_class_provider)) = (captured_tx, &self.raw_class_provider) {
+            match ExecutionArtifact::capture(
+                &tx,
+                &self.block_context,
+                charge_fee,
+                &transactional_state,
+                raw_class_provider.as_ref(),
+            ) {
+                Ok(artifact) => self.artifacts.push(artifact),
+                Err(error) => log::warn!("Failed to capture the execution artifact: {error}"),
+            }
+        }
+        let tx_execution_info = match tx_execution_result {
+            Ok(tx_execution_info) => tx_execution_info,
+            Err(error) => {
+                transactional_state.abort();
+                return Err(error.into());
+            }
+        };
+
+        commit_if_fits(
+            &mut self.bouncer,
+            &self.block_context,
+            transactional_state,
+            tx_execution_info,
+            l1_handler_payload_size,
+        )
+    }
+
+    /// Executes the given transaction in concurrency mode; i.e., the sequencer balance is read as
+    /// zero (without accessing the block state), and the fee transfer's writes to it are kept as
+    /// deltas. Returns [None], leaving the block state unchanged, if the transaction may depend on
+    /// the actual balance; it should then be executed normally.
+    fn execute_without_sequencer_credit(
+        &mut self,
+        tx: &Transaction,
+        charge_fee: bool,
+        l1_handler_payload_size: Option<usize>,
+    ) -> Option<TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>> {
+        let sequencer_balance_cells = sequencer_balance_cells(&self.block_context);
+        let zero_balances = sequencer_balance_cells
+            .iter()
+            .flat_map(|&(fee_token_address, low_key, high_key)| {
+                [(fee_token_address, low_key), (fee_token_address, high_key)]
+            })
+            .map(|cell| (cell, StarkFelt::default()))
+            .collect();
+
+        let mut transactional_state = CachedState::create_transactional(&mut self.state);
+        transactional_state.set_storage_initial_values(&zero_balances);
+        let validate = true;
+        let tx_execution_result = tx.clone().execute_raw(
+            &mut transactional_state,
+            &self.block_context,
+            charge_fee,
+            validate,
+        );
+        if requires_sequential_execution(tx, &self.block_context, &tx_execution_result) {
+            transactional_state.abort();
+            return None;
+        }
+        let mut tx_execution_info =
+            tx_execution_result.expect("A failed execution must be executed normally.");
+        let (mut writes, class_hash_to_class) = transactional_state.get_writes();
+        let mut tx_state_diff = transactional_state.to_state_diff();
+        transactional_state.abort();
+
+        // Only the fee transfer wrote to the (zero) sequencer balance; i.e., wrote the fee.
+        let mut accumulated_sequencer_balance_deltas = HashMap::new();
+        for (fee_token_address, low_key, high_key) in sequencer_balance_cells {
+            let low = writes.storage.remove(&(fee_token_address, low_key));
+            let high = writes.storage.remove(&(fee_token_address, high_key));
+            if low.is_none() && high.is_none() {
+                continue;
+            }
+            if let Some(storage_updates) = tx_state_diff.storage_updates.get_mut(&fee_token_address)
+            {
+                storage_updates.shift_remove(&low_key);
+                storage_updates.shift_remove(&high_key);
+                if storage_updates.is_empty() {
+                    tx_state_diff.storage_updates.shift_remove(&fee_token_address);
+                }
+            }
+            let delta = (low.unwrap_or_default(), high.unwrap_or_default());
+            // Fails before the transaction is committed, if the accumulated fees overflow.
+            let previous_delta =
+                self.sequencer_balance_deltas.get(&fee_token_address).copied().unwrap_or_default();
+            let accumulated_delta = match add_uint256(previous_delta, delta) {
+                Ok(accumulated_delta) => accumulated_delta,
+                Err(error) => return Some(Err(error.into())),
+            };
+            accumulated_sequencer_balance_deltas.insert(fee_token_address, accumulated_delta);
+
+            // Trace the balance as read in a normal execution.
+            let fee_transfer_call_info = tx_execution_info
+                .fee_transfer_call_info
+                .as_mut()
+                .expect("Fee transfer call info must be set.");
+            if fee_transfer_call_info.call.storage_address == fee_token_address {
+                let fill_result = self.sequencer_balance(fee_token_address).and_then(|balance| {
+                    fill_sequencer_balance_reads(fee_transfer_call_info, balance)
+                });
+                if let Err(error) = fill_result {
+                    return Some(Err(error.into()));
+                }
+            }
+        }
+
+        let tx_state_changes_keys = StateChangesKeys::from(&tx_state_diff);
+        let tx_weights = match BouncerWeights::from_tx_execution(
+            &tx_execution_info,
+            self.bouncer.marginal_state_changes_count(&tx_state_changes_keys),
+            l1_handler_payload_size,
+            &self.block_context,
+        ) {
+            Ok(tx_weights) => tx_weights,
+            Err(error) => return Some(Err(error.into())),
+        };
+        if let Err(error) = self.bouncer.try_update(tx_weights, &tx_state_changes_keys) {
+            return Some(Err(error.into()));
+        }
+
+        self.state.update_cache(&writes, class_hash_to_class);
+        self.sequencer_balance_deltas.extend(accumulated_sequencer_balance_deltas);
+        self.bouncer.commit();
+
+        Some(Ok((tx_execution_info, tx_state_diff)))
+    }
+
+    /// Returns the sequencer balance in the given fee token, including the fees not yet added to
+    /// it.
+    fn sequencer_balance(
+        &mut self,
+        fee_token_address: ContractAddress,
+    ) -> TransactionExecutionResult<(StarkFelt, StarkFelt)> {
+        let balance = self
+            .state
+            .get_fee_token_balance(&self.block_context.sequencer_address, &fee_token_address)?;
+        let delta =
+            self.sequencer_balance_deltas.get(&fee_token_address).copied().unwrap_or_default();
+
+        add_uint256(balance, delta)
+    }
+
+    /// Adds the accumulated fees to the sequencer balance, in the block state.
+    fn add_sequencer_balance_deltas(&mut self) -> TransactionExecutionResult<()> {
+        let (low_key, high_key) =
+            get_erc20_balance_var_addresses(&self.block_context.sequencer_address)?;
+        for fee_token_address in self.sequencer_balance_deltas.keys().copied().collect::<Vec<_>>() {
+            let (new_low, new_high) = self.sequencer_balance(fee_token_address)?;
+            self.state.set_storage_at(fee_token_address, low_key, new_low);
+            self.state.set_storage_at(fee_token_address, high_key, new_high);
+        }
+        self.sequencer_balance_deltas.clear();
+
+        Ok(())
+    }
+
+    /// Returns a span for the next transaction of the block, under the block span.
+    fn next_tx_span(&mut self, tx: &Transaction) -> Span {
+        let tx_index = self.n_txs;
+        self.n_txs += 1;
+        info_span!(parent: &self.block_span, "transaction", tx_index, tx_hash = %tx.tx_hash().0)
+    }
+
+    /// Returns the time passed since the given time of the block context clock.
+    fn elapsed_since(&self, start: Duration) -> Duration {
+        self.block_context.clock.now().saturating_sub(start)
+    }
+
+    /// Counts the result of a transaction given to the executor, and reports it to the metrics.
+    fn record_tx_result(
+        &mut self,
+        result: &TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>,
+        duration: Duration,
+    ) {
+        let (outcome, n_steps) = match result {
+            Ok((tx_execution_info, _)) => {
+                self.n_committed_txs += 1;
+                let outcome = if tx_execution_info.is_reverted() {
+                    TransactionOutcome::Reverted
+                } else {
+                    TransactionOutcome::Succeeded
+                };
+                let vm_resources = &tx_execution_info.actual_resources.vm_resources.0;
+                (
+                    outcome,
+                    vm_resources.get(constants::N_STEPS_RESOURCE).copied().unwrap_or_default(),
+                )
+            }
+            Err(error) => {
+                if matches!(
+                    error,
+                    TransactionExecutorError::BouncerError(BouncerError::BlockFull { .. })
+                ) {
+                    self.is_block_full = true;
+                }
+                (TransactionOutcome::Rejected, 0)
+            }
+        };
+        if let Some(metrics) = &self.metrics {
+            metrics.tx_executed(outcome, duration, n_steps);
+        }
+    }
+
+    /// Returns the state diff of the block, together with the weights accumulated by its
+    /// transactions.
+    pub fn finalize(mut self) -> TransactionExecutorResult<(CommitmentStateDiff, BouncerWeights)> {
+        let _block_span = self.block_span.clone().entered();
+        self.add_sequencer_balance_deltas()?;
+        if let Some(metrics) = &self.metrics {
+            let reason = if self.is_block_full {
+                BlockCloseReason::Full
+            } else {
+                BlockCloseReason::Finalized
+            };
+            metrics.block_closed(reason, self.n_committed_txs);
+        }
+        Ok((self.state.to_state_diff(), self.bouncer.accumulated_weights))
+    }
+}
+
+impl<S: StateReader + Send> TransactionExecutor<S> {
+    /// Executes the given transactions, in order, on the state maintained by the executor; each
+    /// result is that of `execute` without concurrency.
+    ///
+    /// If concurrency is enabled, the transactions are executed in chunks of the configured
+    /// size, each by the configured number of worker threads (see `WorkerExecutor`); unlike in
+    /// `execute`, the sequencer is credited per transaction. Otherwise, in parallel validation mode,
+    /// the transactions are validated in parallel first (see `execute_txs_after_validation`).
+    /// Fails only if the block state cannot be read, or if concurrency or parallel validation is
+    /// configured without the `concurrency` feature, which spawns the worker threads.
+    pub fn execute_txs(
+        &mut self,
+        txs: &[Transaction],
+        charge_fee: bool,
+    ) -> TransactionExecutorResult<
+        Vec<TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>>,
+    > {
+        let _block_span = self.block_span.clone().entered();
+        self.block_context.concurrency_config.verify_available()?;
+        #[cfg(feature = "concurrency")]
+        {
+            let concurrency_config = self.block_context.concurrency_config;
+            if concurrency_config.enabled {
+                return self.execute_txs_concurrently(txs, charge_fee);
+            }
+            if concurrency_config.parallel_validation {
+                return self.execute_txs_after_validation(
+                    txs,
+                    charge_fee,
+                    concurrency_config.n_workers,
+                );
+            }
+        }
+
+        Ok(txs.iter().map(|tx| self.execute(tx.clone(), charge_fee)).collect())
+    }
+
+    /// Executes the given transactions in chunks, each concurrently (see `execute_chunk`).
+    #[cfg(feature = "concurrency")]
+    fn execute_txs_concurrently(
+        &mut self,
+        txs: &[Transaction],
+        charge_fee: bool,
+    ) -> TransactionExecutorResult<
+        Vec<TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>>,
+    > {
+        let concurrency_config = self.block_context.concurrency_config;
+        // The workers read the sequencer balance from the block state.
+        self.add_sequencer_balance_deltas()?;
+        let mut results = Vec::with_capacity(txs.len());
+        while results.len() < txs.len() {
+            let chunk_end = txs.len().min(results.len() + concurrency_config.chunk_size);
+            let chunk = &txs[results.len()..chunk_end];
+            results.extend(self.execute_chunk(chunk, charge_fee, concurrency_config.n_workers)?);
+        }
+
+        Ok(results)
+    }
+
+    /// Validates the given transactions in parallel, on the block state, using the given number of
+    /// worker threads; then executes them sequentially, each as `execute` does. A transaction
+    /// reuses its validation, unless that failed or read a value since written by a preceding
+    /// transaction (e.g., the transaction of an account deployed within the batch); it is then
+    /// validated again.
+    #[cfg(feature = "concurrency")]
+    fn execute_txs_after_validation(
+        &mut self,
+        txs: &[Transaction],
+        charge_fee: bool,
+        n_workers: usize,
+    ) -> TransactionExecutorResult<
+        Vec<TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>>,
+    > {
+        let global_contract_cache = self.state.global_contract_cache();
+        let validations = validate_txs(
+            MutRefState::new(&mut self.state),
+            txs,
+            &self.block_context,
+            n_workers,
+            global_contract_cache,
+        );
+
+        let mut results = Vec::with_capacity(txs.len());
+        for (tx, validation) in txs.iter().zip(validations) {
+            let validation = match validation {
+                Some(validation) => {
+                    self.validation_phase_stats.n_precomputed += 1;
+                    validation.is_valid_on(&mut self.state)?.then_some(validation)
+                }
+                None => None,
+            };
+            if validation.is_none() && is_validated_first(tx) {
+                self.validation_phase_stats.n_rerun += 1;
+            }
+            let _tx_span = self.next_tx_span(tx).entered();
+            let start = self.block_context.clock.now();
+            let result = l1_handler_payload_size(tx).map_err(Into::into).and_then(
+                |l1_handler_payload_size| {
+                    self.execute_on_block_state(
+                        tx.clone(),
+                        charge_fee,
+                        validation,
+                        l1_handler_payload_size,
+                    )
+                },
+            );
+            self.record_tx_result(&result, self.elapsed_since(start));
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    /// Executes the chunk concurrently, and commits its transactions in order. Stops after the
+    /// first transaction that does not fit in the block, as the following ones were executed on
+    /// top of it; returns the results of the transactions up to it.
+    #[cfg(feature = "concurrency")]
+    fn execute_chunk(
+        &mut self,
+        chunk: &[Transaction],
+        charge_fee: bool,
+        n_workers: usize,
+    ) -> StateResult<Vec<TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)>>>
+    {
+        // The transactions are traced by the workers, by their index in the chunk.
+        let _chunk_span =
+            info_span!(parent: &self.block_span, "chunk", first_tx_index = self.n_txs).entered();
+        self.n_txs += chunk.len();
+        let start = self.block_context.clock.now();
+        let validate = true;
+        let global_contract_cache = self.state.global_contract_cache();
+        let worker_executor = WorkerExecutor::new(
+            MutRefState::new(&mut self.state),
+            chunk,
+            &self.block_context,
+            &mut self.bouncer,
+            charge_fee,
+            validate,
+            global_contract_cache,
+        );
+        let CommittedChunk { outputs, commit_error, .. } =
+            worker_executor.execute_and_commit(n_workers)?;
+
+        let mut results = Vec::with_capacity(chunk.len());
+        for output in outputs {
+            let mut transactional_state = CachedState::create_transactional(&mut self.state);
+            match output.apply_to(&mut transactional_state)? {
+                Ok(tx_execution_info) => {
+                    let tx_state_diff = transactional_state.to_state_diff();
+                    transactional_state.commit();
+                    results.push(Ok((tx_execution_info, tx_state_diff)));
+                }
+                // Failed transactions write nothing.
+                Err(error) => results.push(Err(error.into())),
+            }
+        }
+        results.extend(commit_error.map(Err));
+
+        // The transactions run concurrently; each is reported with an even share of the chunk
+        // execution time.
+        let tx_duration = self.elapsed_since(start) / results.len().max(1) as u32;
+        for result in &results {
+            self.record_tx_result(result, tx_duration);
+        }
+
+        Ok(results)
+    }
+}
+
+/// Returns the payload size of an L1 handler (see `L1HandlerTransaction::payload_size`); [None]
+/// for other transactions.
+pub fn l1_handler_payload_size(tx: &Transaction) -> TransactionExecutionResult<Option<usize>> {
+    match tx {
+        Transaction::AccountTransaction(_) => Ok(None),
+        Transaction::L1HandlerTransaction(l1_handler) => l1_handler.payload_size().map(Some),
+    }
+}
+
+/// Commits the executed transaction to the block state, unless it does not fit in the block.
+fn commit_if_fits<S: StateReader>(
+    bouncer: &mut Bouncer,
+    block_context: &BlockContext,
+    transactional_state: TransactionalState<'_, S>,
+    tx_execution_info: TransactionExecutionInfo,
+    l1_handler_payload_size: Option<usize>,
+) -> TransactionExecutorResult<(TransactionExecutionInfo, CommitmentStateDiff)> {
+    let tx_state_diff = transactional_state.to_state_diff();
+    let tx_state_changes_keys = StateChangesKeys::from(&tx_state_diff);
+    let tx_weights = BouncerWeights::from_tx_execution(
+        &tx_execution_info,
+        bouncer.marginal_state_changes_count(&tx_state_changes_keys),
+        l1_handler_payload_size,
+        block_context,
+    )?;
+    match bouncer.try_update(tx_weights, &tx_state_changes_keys) {
+        Ok(()) => {
+            transactional_state.commit();
+            bouncer.commit();
+            Ok((tx_execution_info, tx_state_diff))
+        }
+        Err(error) => {
+            transactional_state.abort();
+            Err(error.into())
+        }
+    }
+}
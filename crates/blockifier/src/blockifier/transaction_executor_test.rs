@@ -0,0 +1,973 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use assert_matches::assert_matches;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{
+    Calldata, DeclareTransactionV2, Fee, InvokeTransactionV1, TransactionHash,
+};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use crate::abi::abi_utils::{get_erc20_balance_var_addresses, selector_from_name};
+use crate::block_context::{BlockContext, BlockContextError, ConcurrencyConfig};
+#[cfg(feature = "concurrency")]
+use crate::block_context::{FeeTransferCalldataLayout, FeeTransferInterface};
+use crate::blockifier::bouncer::{BouncerConfig, BouncerError, BouncerWeights, BuiltinCount};
+use crate::blockifier::metrics::{BlockCloseReason, ExecutionMetrics, TransactionOutcome};
+use crate::blockifier::transaction_executor::{TransactionExecutor, TransactionExecutorError};
+#[cfg(feature = "concurrency")]
+use crate::concurrency::validation_phase::ValidationPhaseStats;
+use crate::execution::contract_class::ContractClassV1;
+use crate::fee::gas_usage::get_onchain_data_segment_length;
+use crate::selector;
+#[cfg(feature = "concurrency")]
+use crate::state::cached_state::{CachedState, CommitmentStateDiff};
+use crate::state::cached_state::{StateChangesCount, StateChangesKeys};
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::{
+    deploy_account_tx, invoke_tx, DictStateReader, NonceManager, BALANCE, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+    TEST_CONTRACT_ADDRESS, TEST_CONTRACT_CAIRO1_PATH, TEST_EMPTY_CONTRACT_CAIRO1_PATH,
+    TEST_EMPTY_CONTRACT_CLASS_HASH,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::errors::TransactionExecutionError;
+#[cfg(feature = "concurrency")]
+use crate::transaction::objects::TransactionExecutionInfo;
+#[cfg(feature = "concurrency")]
+use crate::transaction::test_utils::legacy_transfer_erc20_class;
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_trivial_validation_account, storage_write_tx,
+};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::{DeclareTransaction, L1HandlerTransaction};
+
+const PY_COMPATIBLE_BLOCK_FIXTURE_PATH: &str = "./resources/testing/py_compatible_block.json";
+
+fn create_executor(
+    bouncer_config: BouncerConfig,
+    concurrency_config: ConcurrencyConfig,
+) -> TransactionExecutor<DictStateReader> {
+    TransactionExecutor::new(
+        create_state_with_trivial_validation_account(),
+        BlockContext { concurrency_config, ..BlockContext::create_for_account_testing() },
+        bouncer_config,
+        true,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_execute_block() {
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+    let mut nonce_manager = NonceManager::default();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let test_contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+
+    let mut expected_weights = BouncerWeights::default();
+    let mut block_state_changes_keys = StateChangesKeys::default();
+    let mut execute_and_accumulate = |executor: &mut TransactionExecutor<DictStateReader>,
+                                      tx: Transaction| {
+        let (tx_execution_info, tx_state_diff) = executor.execute(tx, true).unwrap();
+        let tx_state_changes_keys = StateChangesKeys::from(&tx_state_diff);
+        expected_weights += BouncerWeights::from_tx_execution(
+            &tx_execution_info,
+            tx_state_changes_keys.difference(&block_state_changes_keys).count(),
+            None,
+            &executor.block_context,
+        )
+        .unwrap();
+        block_state_changes_keys.extend(&tx_state_changes_keys);
+        tx_execution_info
+    };
+
+    // Successful transaction.
+    let tx_execution_info = execute_and_accumulate(
+        &mut executor,
+        storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+    );
+    assert!(!tx_execution_info.is_reverted());
+
+    // Reverted transaction; its fee is still charged and its nonce is still consumed.
+    let tx_execution_info = execute_and_accumulate(
+        &mut executor,
+        storage_write_tx("write_and_revert", 2, account_address, &mut nonce_manager),
+    );
+    assert!(tx_execution_info.is_reverted());
+
+    // Rejected transaction (invalid nonce); does not affect the block.
+    let invalid_nonce_tx = Transaction::AccountTransaction(AccountTransaction::Invoke(
+        InvokeTransactionV1 {
+            nonce: Nonce(stark_felt!(7_u8)),
+            ..invoke_tx(calldata![], account_address, Fee(MAX_FEE), None)
+        }
+        .into(),
+    ));
+    assert_matches!(
+        executor.execute(invalid_nonce_tx, true),
+        Err(TransactionExecutorError::TransactionExecutionError(
+            TransactionExecutionError::InvalidNonce { .. }
+        ))
+    );
+
+    for key in [3, 4] {
+        let tx_execution_info = execute_and_accumulate(
+            &mut executor,
+            storage_write_tx("test_storage_read_write", key, account_address, &mut nonce_manager),
+        );
+        assert!(!tx_execution_info.is_reverted());
+    }
+
+    assert_eq!(executor.bouncer.accumulated_weights, expected_weights);
+    // Each accepted transaction emits a single (fee transfer) event.
+    assert_eq!(expected_weights.n_events, 4);
+
+    let (block_state_diff, block_weights) = executor.finalize().unwrap();
+    assert_eq!(block_weights, expected_weights);
+    // Contracts modified by several transactions (e.g., the fee token) are counted once.
+    assert_eq!(
+        block_weights.state_diff_size,
+        get_onchain_data_segment_length(StateChangesCount::from(&block_state_diff))
+    );
+    assert_eq!(
+        block_state_diff.address_to_nonce[&account_address],
+        nonce_manager.next(account_address)
+    );
+    // The storage write of the reverted transaction is not part of the block.
+    let written_keys: HashSet<StorageKey> =
+        block_state_diff.storage_updates[&test_contract_address].keys().copied().collect();
+    let expected_written_keys: HashSet<StorageKey> = [1_u8, 3, 4]
+        .into_iter()
+        .map(|key| StorageKey::try_from(stark_felt!(key)).unwrap())
+        .collect();
+    assert_eq!(written_keys, expected_written_keys);
+}
+
+#[test]
+fn test_block_full() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let mut nonce_manager = NonceManager::default();
+
+    // Measure the weights of a single transaction.
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+    executor
+        .execute(
+            storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+            true,
+        )
+        .unwrap();
+    let tx_weights = executor.bouncer.accumulated_weights;
+
+    // Only a single transaction fits in a block of this capacity.
+    let mut nonce_manager = NonceManager::default();
+    let block_max_capacity =
+        BouncerWeights { n_steps: tx_weights.n_steps + 1, ..BouncerWeights::max() };
+    let mut executor =
+        create_executor(BouncerConfig { block_max_capacity }, ConcurrencyConfig::default());
+    executor
+        .execute(
+            storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+            true,
+        )
+        .unwrap();
+    assert_matches!(
+        executor.execute(
+            storage_write_tx("test_storage_read_write", 2, account_address, &mut nonce_manager),
+            true
+        ),
+        Err(TransactionExecutorError::BouncerError(BouncerError::BlockFull {
+            dimension: "n_steps"
+        }))
+    );
+
+    // The transaction that did not fit was not applied.
+    assert_eq!(executor.state.get_nonce_at(account_address).unwrap(), Nonce(stark_felt!(1_u8)));
+    assert_eq!(executor.bouncer.accumulated_weights, tx_weights);
+}
+
+/// The state diff of a transaction is weighed by the state changes not already made in the block;
+/// a block whose data availability capacity is that of two transactions writing the same cell fits
+/// them, but not a third one writing another cell.
+#[test]
+fn test_block_full_on_state_diff_size() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let mut nonce_manager = NonceManager::default();
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+    for _ in 0..2 {
+        executor
+            .execute(
+                storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+                true,
+            )
+            .unwrap();
+    }
+    let block_weights = executor.bouncer.accumulated_weights;
+
+    let mut nonce_manager = NonceManager::default();
+    let block_max_capacity =
+        BouncerWeights { state_diff_size: block_weights.state_diff_size, ..BouncerWeights::max() };
+    let mut executor =
+        create_executor(BouncerConfig { block_max_capacity }, ConcurrencyConfig::default());
+    for _ in 0..2 {
+        executor
+            .execute(
+                storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+                true,
+            )
+            .unwrap();
+    }
+    assert_matches!(
+        executor.execute(
+            storage_write_tx("test_storage_read_write", 2, account_address, &mut nonce_manager),
+            true
+        ),
+        Err(TransactionExecutorError::BouncerError(BouncerError::BlockFull {
+            dimension: "state_diff_size"
+        }))
+    );
+    assert_eq!(executor.bouncer.accumulated_weights, block_weights);
+}
+
+/// Builtins are bounded separately from steps; a block fills on keccak instances while steps remain
+/// plentiful.
+#[test]
+fn test_block_full_on_keccak_builtin() {
+    let run_keccak_txs = |block_max_capacity: BouncerWeights, n_txs: usize| {
+        let mut executor =
+            create_executor(BouncerConfig { block_max_capacity }, ConcurrencyConfig::default());
+        // The keccak syscalls are made by an inner (Cairo 1) call of the account.
+        executor
+            .state
+            .set_contract_class(
+                &class_hash!(TEST_CLASS_HASH),
+                ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH).into(),
+            )
+            .unwrap();
+
+        let mut nonce_manager = NonceManager::default();
+        let results: Vec<_> = (0..n_txs)
+            .map(|_| {
+                let execute_calldata = calldata![
+                    *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+                    selector!("test_keccak").0,                        // EP selector.
+                    stark_felt!(0_u8)                                  // Calldata length.
+                ];
+                let tx = Transaction::AccountTransaction(account_invoke_tx(
+                    execute_calldata,
+                    contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+                    &mut nonce_manager,
+                    Fee(MAX_FEE),
+                ));
+                executor.execute(tx, true).map(|_| ())
+            })
+            .collect();
+        (results, executor.bouncer.accumulated_weights)
+    };
+
+    // Measure the weights of a single transaction.
+    let (results, tx_weights) = run_keccak_txs(BouncerWeights::max(), 1);
+    results[0].as_ref().unwrap();
+    assert!(tx_weights.builtin_count.keccak > 0);
+
+    let block_max_capacity = BouncerWeights {
+        builtin_count: BuiltinCount {
+            keccak: tx_weights.builtin_count.keccak,
+            ..BuiltinCount::max()
+        },
+        ..BouncerWeights::max()
+    };
+    let (results, block_weights) = run_keccak_txs(block_max_capacity, 2);
+    results[0].as_ref().unwrap();
+    assert_matches!(
+        &results[1],
+        Err(TransactionExecutorError::BouncerError(BouncerError::BlockFull {
+            dimension: "keccak_builtin"
+        }))
+    );
+    assert_eq!(block_weights, tx_weights);
+}
+
+/// A transaction that does not fit even in an empty block is rejected as such, and not applied.
+#[test]
+fn test_tx_too_large() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let block_max_capacity = BouncerWeights { n_steps: 1, ..BouncerWeights::max() };
+    let mut executor =
+        create_executor(BouncerConfig { block_max_capacity }, ConcurrencyConfig::default());
+    let tx = storage_write_tx(
+        "test_storage_read_write",
+        1,
+        account_address,
+        &mut NonceManager::default(),
+    );
+
+    assert_matches!(
+        executor.execute(tx, true),
+        Err(TransactionExecutorError::BouncerError(BouncerError::TxTooLarge {
+            dimension: "n_steps",
+            weight,
+            max: 1,
+        }))
+        if weight > 1
+    );
+    assert_eq!(executor.bouncer.accumulated_weights, BouncerWeights::default());
+    assert_eq!(executor.state.get_nonce_at(account_address).unwrap(), Nonce::default());
+    assert_eq!(
+        executor
+            .state
+            .get_storage_at(
+                contract_address!(TEST_CONTRACT_ADDRESS),
+                StorageKey(patricia_key!(1_u8))
+            )
+            .unwrap(),
+        StarkFelt::default()
+    );
+    let (block_state_diff, _) = executor.finalize().unwrap();
+    assert!(block_state_diff.storage_updates.is_empty());
+    assert!(block_state_diff.address_to_nonce.is_empty());
+}
+
+/// An L1 handler's calldata starts with the L1 sender address; one without it is rejected.
+#[test]
+fn test_l1_handler_without_sender_address() {
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+    let tx = Transaction::L1HandlerTransaction(L1HandlerTransaction {
+        tx: starknet_api::transaction::L1HandlerTransaction {
+            contract_address: contract_address!(TEST_CONTRACT_ADDRESS),
+            entry_point_selector: selector!("l1_handle"),
+            calldata: calldata![],
+            ..Default::default()
+        },
+        tx_hash: TransactionHash::default(),
+        paid_fee_on_l1: Fee(1),
+    });
+
+    assert_matches!(
+        executor.execute(tx, true),
+        Err(TransactionExecutorError::TransactionExecutionError(
+            TransactionExecutionError::MissingL1HandlerSenderAddress
+        ))
+    );
+    assert_eq!(executor.bouncer.accumulated_weights, BouncerWeights::default());
+}
+
+#[cfg(feature = "concurrency")]
+#[test]
+fn test_concurrency_mode() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let run_block = |enabled: bool| {
+        let concurrency_config = ConcurrencyConfig { enabled, ..Default::default() };
+        let mut executor = create_executor(BouncerConfig::max(), concurrency_config);
+        let mut nonce_manager = NonceManager::default();
+        let tx_outputs = [1, 2, 3].map(|key| {
+            executor
+                .execute(
+                    storage_write_tx(
+                        "test_storage_read_write",
+                        key,
+                        account_address,
+                        &mut nonce_manager,
+                    ),
+                    true,
+                )
+                .unwrap()
+        });
+        let (block_state_diff, _) = executor.finalize().unwrap();
+        (tx_outputs, block_state_diff)
+    };
+    let (tx_outputs, block_state_diff) = run_block(false);
+    let (concurrency_mode_tx_outputs, concurrency_mode_block_state_diff) = run_block(true);
+
+    // The sequencer is credited once, with the same final balance.
+    assert_eq!(concurrency_mode_block_state_diff, block_state_diff);
+
+    let block_context = BlockContext::create_for_account_testing();
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let (sequencer_balance_key_low, _) =
+        get_erc20_balance_var_addresses(&block_context.sequencer_address).unwrap();
+    for (
+        (tx_execution_info, tx_state_diff),
+        (concurrency_mode_tx_execution_info, concurrency_mode_tx_state_diff),
+    ) in tx_outputs.into_iter().zip(concurrency_mode_tx_outputs)
+    {
+        // The traces are the same, including the fee transfer reading the sequencer balance.
+        assert_eq!(concurrency_mode_tx_execution_info, tx_execution_info);
+        assert!(tx_state_diff.storage_updates[&fee_token_address]
+            .contains_key(&sequencer_balance_key_low));
+        assert!(!concurrency_mode_tx_state_diff.storage_updates[&fee_token_address]
+            .contains_key(&sequencer_balance_key_low));
+    }
+}
+
+/// Fee transfers through a non-default interface may not read the sequencer balance as
+/// `transfer` does; in concurrency mode, they are executed normally.
+#[cfg(feature = "concurrency")]
+#[test]
+fn test_concurrency_mode_with_fee_transfer_interface() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let run_block = |enabled: bool| {
+        let block_context = BlockContext {
+            concurrency_config: ConcurrencyConfig { enabled, ..Default::default() },
+            fee_transfer_interface: FeeTransferInterface {
+                selector_name: "transfer_legacy".to_string(),
+                calldata_layout: FeeTransferCalldataLayout::RecipientAmount,
+            },
+            ..BlockContext::create_for_account_testing()
+        };
+        let mut state = create_state_with_trivial_validation_account();
+        let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+        let legacy_token_class_hash = class_hash!("0x1e2c");
+        state.set_contract_class(&legacy_token_class_hash, legacy_transfer_erc20_class()).unwrap();
+        state.set_class_hash_at(fee_token_address, legacy_token_class_hash).unwrap();
+        let mut executor =
+            TransactionExecutor::new(state, block_context, BouncerConfig::max(), true).unwrap();
+
+        let mut nonce_manager = NonceManager::default();
+        let tx_outputs = [1, 2, 3].map(|key| {
+            executor
+                .execute(
+                    storage_write_tx(
+                        "test_storage_read_write",
+                        key,
+                        account_address,
+                        &mut nonce_manager,
+                    ),
+                    true,
+                )
+                .unwrap()
+        });
+        let (block_state_diff, _) = executor.finalize().unwrap();
+        (tx_outputs, block_state_diff)
+    };
+
+    let (tx_outputs, block_state_diff) = run_block(false);
+    let fee_transfer_call_info = tx_outputs[0].0.fee_transfer_call_info.as_ref().unwrap();
+    assert_eq!(fee_transfer_call_info.call.entry_point_selector, selector!("transfer_legacy"));
+    assert_eq!(run_block(true), (tx_outputs, block_state_diff));
+}
+
+#[cfg(feature = "concurrency")]
+#[test]
+fn test_execute_txs_concurrently() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let run_block = |concurrency_config: ConcurrencyConfig| {
+        let mut executor = create_executor(BouncerConfig::max(), concurrency_config);
+        let mut nonce_manager = NonceManager::default();
+        // Some transactions write the same cells, and all of them increment the account nonce.
+        let txs: Vec<_> = (0..10_u8)
+            .map(|i| {
+                storage_write_tx(
+                    "test_storage_read_write",
+                    i % 4,
+                    account_address,
+                    &mut nonce_manager,
+                )
+            })
+            .collect();
+        let tx_outputs: Vec<_> = executor
+            .execute_txs(&txs, true)
+            .unwrap()
+            .into_iter()
+            .map(|tx_output| tx_output.unwrap())
+            .collect();
+        let accumulated_weights = executor.bouncer.accumulated_weights;
+        let (block_state_diff, _) = executor.finalize().unwrap();
+        (tx_outputs, block_state_diff, accumulated_weights)
+    };
+
+    let sequential_outputs = run_block(ConcurrencyConfig::default());
+    let concurrent_outputs = run_block(ConcurrencyConfig {
+        enabled: true,
+        n_workers: 4,
+        chunk_size: 4,
+        ..Default::default()
+    });
+    assert_eq!(sequential_outputs.0.len(), 10);
+    // Unlike `execute`, the sequencer is credited per transaction; the outputs are identical.
+    assert_eq!(concurrent_outputs, sequential_outputs);
+}
+
+/// Without the `concurrency` feature, a block context configured for concurrency is not executed
+/// sequentially instead.
+/// Without the `concurrency` feature, a block context configured for concurrency is not executed
+/// sequentially instead.
+#[cfg(not(feature = "concurrency"))]
+#[test]
+fn test_concurrency_unavailable() {
+    for concurrency_config in [
+        ConcurrencyConfig { enabled: true, ..Default::default() },
+        ConcurrencyConfig { parallel_validation: true, ..Default::default() },
+    ] {
+        let block_context =
+            BlockContext { concurrency_config, ..BlockContext::create_for_account_testing() };
+        assert_matches!(
+            block_context.validate(true).unwrap_err(),
+            BlockContextError::ConcurrencyUnavailable
+        );
+
+        let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+        executor.block_context.concurrency_config = concurrency_config;
+        assert_matches!(
+            executor.execute_txs(&[], true).unwrap_err(),
+            TransactionExecutorError::BlockContextError(BlockContextError::ConcurrencyUnavailable)
+        );
+    }
+}
+
+#[test]
+fn test_block_context_validation() {
+    let create_executor = |block_context, enforce_fee| {
+        TransactionExecutor::new(
+            create_state_with_trivial_validation_account(),
+            block_context,
+            BouncerConfig::max(),
+            enforce_fee,
+        )
+    };
+    let zero_sequencer_block_context = BlockContext {
+        sequencer_address: ContractAddress::default(),
+        ..BlockContext::create_for_account_testing()
+    };
+
+    assert_matches!(
+        create_executor(zero_sequencer_block_context.clone(), true).err().unwrap(),
+        TransactionExecutorError::BlockContextError(BlockContextError::ZeroSequencerAddress)
+    );
+    create_executor(zero_sequencer_block_context, false).unwrap();
+    // The devnet opts out of all validations.
+    create_executor(BlockContext::create_for_devnet_testing(), true).unwrap();
+}
+
+#[cfg(feature = "concurrency")]
+/// Executes the given transactions using `execute_txs`, with or without a validation phase;
+/// returns their outputs, the block state diff and the validation phase statistics.
+fn execute_txs_with_validation_phase(
+    txs: &[Transaction],
+    parallel_validation: bool,
+    prepare_state: impl Fn(&mut CachedState<DictStateReader>),
+) -> (Vec<(TransactionExecutionInfo, CommitmentStateDiff)>, CommitmentStateDiff, ValidationPhaseStats)
+{
+    let concurrency_config =
+        ConcurrencyConfig { n_workers: 4, parallel_validation, ..Default::default() };
+    let mut executor = create_executor(BouncerConfig::max(), concurrency_config);
+    prepare_state(&mut executor.state);
+    let tx_outputs: Vec<_> = executor
+        .execute_txs(txs, true)
+        .unwrap()
+        .into_iter()
+        .map(|tx_output| tx_output.unwrap())
+        .collect();
+    let validation_phase_stats = executor.validation_phase_stats;
+    let (block_state_diff, _) = executor.finalize().unwrap();
+    (tx_outputs, block_state_diff, validation_phase_stats)
+}
+
+/// Validations that read no value written within the batch are run once, in the validation phase.
+#[cfg(feature = "concurrency")]
+#[test]
+fn test_parallel_validation() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let mut nonce_manager = NonceManager::default();
+    let txs: Vec<_> = (0..6_u8)
+        .map(|i| {
+            storage_write_tx("test_storage_read_write", i % 2, account_address, &mut nonce_manager)
+        })
+        .collect();
+
+    let (tx_outputs, block_state_diff, validation_phase_stats) =
+        execute_txs_with_validation_phase(&txs, false, |_| {});
+    assert_eq!(validation_phase_stats, ValidationPhaseStats::default());
+    let (
+        parallel_validation_tx_outputs,
+        parallel_validation_block_state_diff,
+        validation_phase_stats,
+    ) = execute_txs_with_validation_phase(&txs, true, |_| {});
+
+    assert_eq!(validation_phase_stats, ValidationPhaseStats { n_precomputed: 6, n_rerun: 0 });
+    assert_eq!(parallel_validation_tx_outputs, tx_outputs);
+    assert_eq!(parallel_validation_block_state_diff, block_state_diff);
+}
+
+/// A transaction sent by an account deployed earlier in the batch is validated again.
+#[cfg(feature = "concurrency")]
+#[test]
+fn test_parallel_validation_of_deployed_account() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let mut nonce_manager = NonceManager::default();
+    let deploy_account_tx = deploy_account_tx(
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        Fee(MAX_FEE),
+        None,
+        None,
+        &mut nonce_manager,
+    );
+    let deployed_account_address = deploy_account_tx.contract_address;
+    let entry_point_selector = selector!("test_storage_read_write");
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
+        entry_point_selector.0,             // EP selector.
+        stark_felt!(2_u8),                  // Calldata length.
+        stark_felt!(5_u8),                  // Calldata: address.
+        stark_felt!(99_u8)                  // Calldata: value.
+    ];
+    let txs = [
+        Transaction::AccountTransaction(AccountTransaction::DeployAccount(deploy_account_tx)),
+        Transaction::AccountTransaction(account_invoke_tx(
+            execute_calldata,
+            deployed_account_address,
+            &mut nonce_manager,
+            Fee(MAX_FEE),
+        )),
+        storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+    ];
+    // Fund the account to be deployed.
+    let fund_deployed_account = |state: &mut CachedState<DictStateReader>| {
+        let (balance_key, _) = get_erc20_balance_var_addresses(&deployed_account_address).unwrap();
+        let fee_token_address =
+            BlockContext::create_for_account_testing().fee_token_addresses.eth_fee_token_address;
+        state.set_storage_at(fee_token_address, balance_key, stark_felt!(BALANCE));
+    };
+
+    let (tx_outputs, block_state_diff, _) =
+        execute_txs_with_validation_phase(&txs, false, fund_deployed_account);
+    let (
+        parallel_validation_tx_outputs,
+        parallel_validation_block_state_diff,
+        validation_phase_stats,
+    ) = execute_txs_with_validation_phase(&txs, true, fund_deployed_account);
+
+    // The deployment is validated after its execution; the validation of the deployed account's
+    // transaction fails in the validation phase, and is run again on top of the deployment.
+    assert_eq!(validation_phase_stats, ValidationPhaseStats { n_precomputed: 1, n_rerun: 1 });
+    assert_eq!(parallel_validation_tx_outputs, tx_outputs);
+    assert_eq!(parallel_validation_block_state_diff, block_state_diff);
+}
+
+/// The Python-compatible layout of the execution infos and the state diff of a block holding a
+/// deployment, a declaration and a transfer is pinned by a fixture.
+#[test]
+fn test_py_compatible_json() {
+    let mut nonce_manager = NonceManager::default();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let block_context = BlockContext::create_for_account_testing();
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+
+    let deploy_account_tx = deploy_account_tx(
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        Fee(MAX_FEE),
+        None,
+        None,
+        &mut nonce_manager,
+    );
+    let deployed_account_address = deploy_account_tx.contract_address;
+    let declare_tx = DeclareTransaction::new(
+        starknet_api::transaction::DeclareTransaction::V2(DeclareTransactionV2 {
+            max_fee: Fee(MAX_FEE),
+            class_hash: class_hash!(TEST_EMPTY_CONTRACT_CLASS_HASH),
+            sender_address: account_address,
+            nonce: nonce_manager.next(account_address),
+            ..Default::default()
+        }),
+        TransactionHash::default(),
+        ContractClassV1::from_file(TEST_EMPTY_CONTRACT_CAIRO1_PATH).into(),
+    )
+    .unwrap();
+    let transfer_calldata = calldata![
+        *fee_token_address.0.key(),        // Contract address.
+        selector!("transfer").0,           // EP selector.
+        stark_felt!(3_u8),                 // Calldata length.
+        *deployed_account_address.0.key(), // Calldata: recipient.
+        stark_felt!(1000_u16),             // Calldata: amount (low).
+        stark_felt!(0_u8)                  // Calldata: amount (high).
+    ];
+    let txs = [
+        AccountTransaction::DeployAccount(deploy_account_tx),
+        AccountTransaction::Declare(declare_tx),
+        account_invoke_tx(transfer_calldata, account_address, &mut nonce_manager, Fee(MAX_FEE)),
+    ];
+
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+    // Fund the account to be deployed.
+    let (balance_key, _) = get_erc20_balance_var_addresses(&deployed_account_address).unwrap();
+    executor.state.set_storage_at(fee_token_address, balance_key, stark_felt!(BALANCE));
+    let tx_execution_infos: Vec<_> = txs
+        .into_iter()
+        .map(|tx| {
+            let (tx_execution_info, _) =
+                executor.execute(Transaction::AccountTransaction(tx), true).unwrap();
+            assert!(!tx_execution_info.is_reverted());
+            tx_execution_info.to_py_compatible_json(&block_context).unwrap()
+        })
+        .collect();
+    let (block_state_diff, _) = executor.finalize().unwrap();
+
+    let expected_block: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(PY_COMPATIBLE_BLOCK_FIXTURE_PATH).unwrap())
+            .unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "tx_execution_infos": tx_execution_infos,
+            "state_diff": block_state_diff.to_py_compatible_json(),
+        }),
+        expected_block
+    );
+}
+
+#[derive(Debug)]
+struct RecordedSpan {
+    name: &'static str,
+    parent: Option<usize>,
+    fields: HashMap<String, String>,
+    ref_count: usize,
+}
+
+/// Records the spans and the event messages of the execution, by the index of their (parent) span.
+#[derive(Debug, Default)]
+struct SpanRecorder {
+    spans: Mutex<Vec<RecordedSpan>>,
+    events: Mutex<Vec<(Option<usize>, String)>>,
+    // The entered spans, innermost last.
+    stack: Mutex<Vec<usize>>,
+}
+
+struct FieldRecorder<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldRecorder<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl SpanRecorder {
+    fn current_span(&self) -> Option<usize> {
+        self.stack.lock().unwrap().last().copied()
+    }
+
+    fn span_tree(&self) -> Vec<String> {
+        let spans = self.spans.lock().unwrap();
+        let mut tree = Vec::new();
+        let mut pending: Vec<(usize, usize)> =
+            (0..spans.len()).rev().filter(|&i| spans[i].parent.is_none()).map(|i| (i, 0)).collect();
+        while let Some((index, depth)) = pending.pop() {
+            tree.push(format!("{}{}", "  ".repeat(depth), spans[index].name));
+            let children = (0..spans.len()).rev().filter(|&i| spans[i].parent == Some(index));
+            pending.extend(children.map(|i| (i, depth + 1)));
+        }
+        tree
+    }
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let parent = match attributes.parent() {
+            Some(parent) => Some(parent.into_u64() as usize - 1),
+            None if attributes.is_contextual() => self.current_span(),
+            None => None,
+        };
+        let mut fields = HashMap::new();
+        attributes.record(&mut FieldRecorder(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(RecordedSpan {
+            name: attributes.metadata().name(),
+            parent,
+            fields,
+            ref_count: 1,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        values.record(&mut FieldRecorder(&mut spans[span.into_u64() as usize - 1].fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = HashMap::new();
+        event.record(&mut FieldRecorder(&mut fields));
+        self.events.lock().unwrap().push((self.current_span(), fields["message"].clone()));
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64() as usize - 1);
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        self.spans.lock().unwrap()[span.into_u64() as usize - 1].ref_count += 1;
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[span.into_u64() as usize - 1];
+        span.ref_count -= 1;
+        span.ref_count == 0
+    }
+}
+
+#[test]
+fn test_tracing_spans() {
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let recorder = Arc::new(SpanRecorder::default());
+    let (block_number, tx_hash) = tracing::subscriber::with_default(recorder.clone(), || {
+        let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default());
+        let tx = storage_write_tx(
+            "test_storage_read_write",
+            1,
+            account_address,
+            &mut NonceManager::default(),
+        );
+        let tx_hash = tx.tx_hash();
+        executor.execute(tx, true).unwrap();
+        let block_number = executor.block_context.block_number;
+        executor.finalize().unwrap();
+        (block_number, tx_hash)
+    });
+
+    let tree = recorder.span_tree();
+    assert_eq!(
+        tree,
+        vec![
+            "block_execution",
+            "  transaction",
+            "    entry_point",
+            "    entry_point",
+            "      entry_point",
+            "    entry_point",
+        ]
+    );
+    let spans = recorder.spans.lock().unwrap();
+    assert!(spans.iter().all(|span| span.ref_count == 0), "All spans must be closed.");
+    let (block_span, tx_span) = (&spans[0], &spans[1]);
+    assert_eq!(block_span.fields["block_number"], block_number.0.to_string());
+    assert_eq!(tx_span.fields["tx_index"], "0");
+    assert_eq!(tx_span.fields["tx_hash"], tx_hash.0.to_string());
+
+    // The calls of the transaction: validation, execution (calling the test contract), and fee
+    // transfer; each records its class hash and steps.
+    let selector = |entry_point_name| selector_from_name(entry_point_name).0.to_string();
+    let call_spans = &spans[2..];
+    assert_eq!(
+        call_spans.iter().map(|span| &span.fields["entry_point_selector"]).collect::<Vec<_>>(),
+        [
+            selector("__validate__"),
+            selector("__execute__"),
+            selector("test_storage_read_write"),
+            selector("transfer")
+        ]
+        .iter()
+        .collect::<Vec<_>>()
+    );
+    for span in call_spans {
+        assert!(span.fields.contains_key("class_hash"));
+        assert!(span.fields["n_steps"].parse::<usize>().unwrap() > 0);
+        assert!(!span.fields.contains_key("calldata"));
+    }
+    let (execute_span, inner_call_span) = (&call_spans[1], &call_spans[2]);
+    assert!(
+        execute_span.fields["n_steps"].parse::<usize>().unwrap()
+            > inner_call_span.fields["n_steps"].parse::<usize>().unwrap()
+    );
+
+    // The test contract reads and writes its storage.
+    let events = recorder.events.lock().unwrap();
+    let inner_call_span_index = 4;
+    let inner_call_events = |event_message: &str| {
+        events
+            .iter()
+            .filter(|(span, message)| {
+                *span == Some(inner_call_span_index) && message == event_message
+            })
+            .count()
+    };
+    assert_eq!(inner_call_events("Executing syscall."), 2);
+    // The storage is read through each layer of cache (of the transaction, and of the block).
+    assert!(inner_call_events("Storage cache miss.") > 0);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MetricsCall {
+    TxExecuted { outcome: TransactionOutcome, consumed_steps: bool },
+    BlockClosed { reason: BlockCloseReason, n_txs: usize },
+    ClassCache { hit: bool },
+}
+
+#[derive(Debug, Default)]
+struct RecordingMetrics {
+    calls: Mutex<Vec<MetricsCall>>,
+}
+
+impl ExecutionMetrics for RecordingMetrics {
+    fn tx_executed(&self, outcome: TransactionOutcome, _duration: Duration, n_steps: usize) {
+        let call = MetricsCall::TxExecuted { outcome, consumed_steps: n_steps > 0 };
+        self.calls.lock().unwrap().push(call);
+    }
+
+    fn block_closed(&self, reason: BlockCloseReason, n_txs: usize) {
+        self.calls.lock().unwrap().push(MetricsCall::BlockClosed { reason, n_txs });
+    }
+
+    fn class_cache(&self, hit: bool) {
+        self.calls.lock().unwrap().push(MetricsCall::ClassCache { hit });
+    }
+}
+
+#[test]
+fn test_execution_metrics() {
+    let metrics = Arc::new(RecordingMetrics::default());
+    let mut executor = create_executor(BouncerConfig::max(), ConcurrencyConfig::default())
+        .with_metrics(metrics.clone());
+    let mut nonce_manager = NonceManager::default();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+
+    let txs = [
+        storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+        storage_write_tx("write_and_revert", 2, account_address, &mut nonce_manager),
+        // Invalid nonce.
+        Transaction::AccountTransaction(AccountTransaction::Invoke(
+            InvokeTransactionV1 {
+                nonce: Nonce(stark_felt!(7_u8)),
+                ..invoke_tx(calldata![], account_address, Fee(MAX_FEE), None)
+            }
+            .into(),
+        )),
+    ];
+    for tx in txs {
+        let _result = executor.execute(tx, true);
+    }
+    executor.finalize().unwrap();
+
+    // The classes of the account, the test contract and the fee token are read by the first
+    // transaction, and cached for the next one; the rejected transaction fails before needing any.
+    let class_cache = |hit| vec![MetricsCall::ClassCache { hit }; 3];
+    let tx_executed = |outcome, consumed_steps| MetricsCall::TxExecuted { outcome, consumed_steps };
+    let expected_calls = [
+        class_cache(false),
+        vec![tx_executed(TransactionOutcome::Succeeded, true)],
+        class_cache(true),
+        vec![
+            tx_executed(TransactionOutcome::Reverted, true),
+            tx_executed(TransactionOutcome::Rejected, false),
+            MetricsCall::BlockClosed { reason: BlockCloseReason::Finalized, n_txs: 2 },
+        ],
+    ]
+    .concat();
+    assert_eq!(*metrics.calls.lock().unwrap(), expected_calls);
+}
@@ -0,0 +1,10 @@
+pub mod fee_utils;
+pub mod scheduler;
+pub mod validation_phase;
+pub mod versioned_state;
+// Spawns threads.
+#[cfg(feature = "concurrency")]
+pub mod worker_executor;
+
+/// The index of a transaction within the chunk of transactions executed concurrently.
+pub type TxIndex = usize;
@@ -0,0 +1,132 @@
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::block_context::{BlockContext, FeeTransferInterface};
+use crate::execution::entry_point::CallInfo;
+use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::transaction_execution::Transaction;
+
+#[cfg(test)]
+#[path = "fee_utils_test.rs"]
+pub mod test;
+
+// The indices of the storage reads of the recipient's balance (low, high) in the fee transfer;
+// the fee token's `transfer` reads the sender balance, then the recipient balance, and each balance
+// is read again when written. Fee transfers through other interfaces (see `FeeTransferInterface`)
+// are executed sequentially.
+const FEE_TRANSFER_N_STORAGE_READS: usize = 8;
+const FEE_TRANSFER_SEQUENCER_BALANCE_READ_INDICES: [(usize, usize); 2] = [(4, 5), (6, 7)];
+
+/// Returns the sequencer balance cells (fee token address, low key, high key) of all fee tokens.
+pub fn sequencer_balance_cells(
+    block_context: &BlockContext,
+) -> Vec<(ContractAddress, StorageKey, StorageKey)> {
+    let (low_key, high_key) = get_erc20_balance_var_addresses(&block_context.sequencer_address)
+        .expect("The sequencer address must be a valid contract address.");
+    let fee_token_addresses = &block_context.fee_token_addresses;
+    [fee_token_addresses.eth_fee_token_address, fee_token_addresses.strk_fee_token_address]
+        .into_iter()
+        .map(|fee_token_address| (fee_token_address, low_key, high_key))
+        .collect()
+}
+
+/// Returns whether the result of an execution in which the sequencer balance was read as zero may
+/// depend on that; i.e., the balance was not (only) accessed by the fee transfer, or the fee
+/// transfer did not read it as expected (see `fill_sequencer_balance_reads`).
+pub fn requires_sequential_execution(
+    tx: &Transaction,
+    block_context: &BlockContext,
+    result: &TransactionExecutionResult<TransactionExecutionInfo>,
+) -> bool {
+    if let Transaction::AccountTransaction(account_tx) = tx {
+        let sender_address = account_tx.get_account_transaction_context().sender_address;
+        if sender_address == block_context.sequencer_address {
+            return true;
+        }
+    }
+
+    let Ok(tx_execution_info) = result else {
+        return true;
+    };
+    // Reverted calls are not traced; they may have accessed the balance.
+    if tx_execution_info.is_reverted() {
+        return true;
+    }
+    let Some(fee_transfer_call_info) = &tx_execution_info.fee_transfer_call_info else {
+        return true;
+    };
+    if block_context.fee_transfer_interface != FeeTransferInterface::default()
+        || !has_expected_sequencer_balance_reads(fee_transfer_call_info)
+    {
+        return true;
+    }
+
+    let sequencer_balance_cells = sequencer_balance_cells(block_context);
+    let accesses_sequencer_balance = |call_info: &CallInfo| {
+        sequencer_balance_cells.iter().any(|&(fee_token_address, low_key, high_key)| {
+            call_info.call.storage_address == fee_token_address
+                && (call_info.accessed_storage_keys.contains(&low_key)
+                    || call_info.accessed_storage_keys.contains(&high_key))
+        })
+    };
+    [&tx_execution_info.validate_call_info, &tx_execution_info.execute_call_info]
+        .into_iter()
+        .flatten()
+        .any(|call_info| call_info.into_iter().any(accesses_sequencer_balance))
+}
+
+/// Returns whether the fee transfer read the sequencer balance as zero, where `transfer` reads it.
+fn has_expected_sequencer_balance_reads(fee_transfer_call_info: &CallInfo) -> bool {
+    let storage_read_values = &fee_transfer_call_info.storage_read_values;
+    storage_read_values.len() == FEE_TRANSFER_N_STORAGE_READS
+        && FEE_TRANSFER_SEQUENCER_BALANCE_READ_INDICES.iter().all(|&(low_index, high_index)| {
+            [storage_read_values[low_index], storage_read_values[high_index]]
+                == [StarkFelt::default(); 2]
+        })
+}
+
+/// Sets the sequencer balance read by the fee transfer, which was read as zero; fails if the fee
+/// transfer did not read it as expected (the execution should not have been committed without a
+/// sequential re-execution, see `requires_sequential_execution`).
+pub fn fill_sequencer_balance_reads(
+    fee_transfer_call_info: &mut CallInfo,
+    (balance_low, balance_high): (StarkFelt, StarkFelt),
+) -> TransactionExecutionResult<()> {
+    if !has_expected_sequencer_balance_reads(fee_transfer_call_info) {
+        return Err(TransactionFeeError::UnexpectedFeeTransferReads.into());
+    }
+    let storage_read_values = &mut fee_transfer_call_info.storage_read_values;
+    for (low_index, high_index) in FEE_TRANSFER_SEQUENCER_BALANCE_READ_INDICES {
+        storage_read_values[low_index] = balance_low;
+        storage_read_values[high_index] = balance_high;
+    }
+
+    Ok(())
+}
+
+/// Adds two uint256 values, given as (low, high) limbs; fails on overflow.
+pub fn add_uint256(
+    (lhs_low, lhs_high): (StarkFelt, StarkFelt),
+    (rhs_low, rhs_high): (StarkFelt, StarkFelt),
+) -> TransactionExecutionResult<(StarkFelt, StarkFelt)> {
+    let (low, carry) = felt_to_u128(&lhs_low)?.overflowing_add(felt_to_u128(&rhs_low)?);
+    let high = felt_to_u128(&lhs_high)?
+        .checked_add(felt_to_u128(&rhs_high)?)
+        .and_then(|high| high.checked_add(u128::from(carry)))
+        .ok_or(TransactionFeeError::BalanceOverflow)?;
+
+    Ok((StarkFelt::from(low), StarkFelt::from(high)))
+}
+
+/// Returns the value of a uint256 limb; fails if it does not fit in 128 bits.
+pub fn felt_to_u128(felt: &StarkFelt) -> TransactionExecutionResult<u128> {
+    let (high_bytes, low_bytes) = felt.bytes().split_at(16);
+    if high_bytes.iter().any(|byte| *byte != 0) {
+        return Err(TransactionFeeError::InvalidUint256Limb { limb: *felt }.into());
+    }
+
+    Ok(u128::from_be_bytes(low_bytes.try_into().expect("Slice must have 16 bytes.")))
+}
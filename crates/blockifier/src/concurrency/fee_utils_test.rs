@@ -0,0 +1,69 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+
+use crate::concurrency::fee_utils::{add_uint256, felt_to_u128, fill_sequencer_balance_reads};
+use crate::execution::entry_point::CallInfo;
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+
+#[test]
+fn test_fill_sequencer_balance_reads() {
+    // The sender balance reads, followed by the sequencer balance reads.
+    let mut storage_read_values = vec![stark_felt!(1_u8); 4];
+    storage_read_values.extend([StarkFelt::default(); 4]);
+    let mut fee_transfer_call_info = CallInfo { storage_read_values, ..Default::default() };
+
+    fill_sequencer_balance_reads(
+        &mut fee_transfer_call_info,
+        (stark_felt!(2_u8), stark_felt!(3_u8)),
+    )
+    .unwrap();
+    let filled_values = [2_u8, 3, 2, 3].map(|value| stark_felt!(value));
+    assert_eq!(fee_transfer_call_info.storage_read_values[4..], filled_values);
+
+    // The balance was already filled, i.e., not read as zero.
+    let error =
+        fill_sequencer_balance_reads(&mut fee_transfer_call_info, Default::default()).unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::TransactionFeeError(
+            TransactionFeeError::UnexpectedFeeTransferReads
+        )
+    );
+
+    // A fee transfer that does not read the balances as `transfer` does.
+    let mut fee_transfer_call_info =
+        CallInfo { storage_read_values: vec![StarkFelt::default(); 4], ..Default::default() };
+    assert!(fill_sequencer_balance_reads(&mut fee_transfer_call_info, Default::default()).is_err());
+}
+
+#[test]
+fn test_add_uint256() {
+    let max_limb = stark_felt!(u128::MAX);
+    assert_eq!(
+        add_uint256((max_limb, stark_felt!(1_u8)), (stark_felt!(2_u8), stark_felt!(3_u8))).unwrap(),
+        (stark_felt!(1_u8), stark_felt!(5_u8))
+    );
+
+    let error =
+        add_uint256((max_limb, max_limb), (stark_felt!(1_u8), StarkFelt::default())).unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::TransactionFeeError(TransactionFeeError::BalanceOverflow)
+    );
+}
+
+#[test]
+fn test_felt_to_u128() {
+    assert_eq!(felt_to_u128(&stark_felt!(u128::MAX)).unwrap(), u128::MAX);
+
+    // 2^128.
+    let limb = stark_felt!("0x100000000000000000000000000000000");
+    assert_matches!(
+        felt_to_u128(&limb).unwrap_err(),
+        TransactionExecutionError::TransactionFeeError(TransactionFeeError::InvalidUint256Limb {
+            limb: invalid_limb
+        }) if invalid_limb == limb
+    );
+}
@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::concurrency::TxIndex;
+
+#[cfg(test)]
+#[path = "scheduler_test.rs"]
+pub mod test;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Task {
+    ExecutionTask(TxIndex),
+    ValidationTask(TxIndex),
+    /// No task is currently available; the worker should ask again.
+    AskForTask,
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionStatus {
+    ReadyToExecute,
+    Executing,
+    Executed,
+    Aborting,
+    Committed,
+}
+
+/// Hands out the execution and validation tasks of a chunk to the worker threads, by transaction
+/// index, and orders the commit phase.
+///
+/// A transaction is executed once handed out, and validated once executed; whenever a transaction
+/// finishes an execution, the transactions following it are validated again, as their reads may
+/// be outdated. A transaction whose validation fails is aborted, and executed again.
+/// Transactions are committed in order, each once executed; the commit is the final validation,
+/// as all the preceding transactions are committed by then. The chunk is done once all its
+/// transactions are committed, or the scheduler is halted (e.g., when the block is full).
+#[derive(Debug)]
+pub struct Scheduler {
+    n_txs: usize,
+    // The index of the next transaction to try to execute.
+    execution_index: AtomicUsize,
+    // The index of the next transaction to try to validate.
+    validation_index: AtomicUsize,
+    // The index of the next transaction to commit; held by the worker in the commit phase.
+    commit_index: Mutex<TxIndex>,
+    tx_statuses: Vec<Mutex<TransactionStatus>>,
+    done_marker: AtomicBool,
+}
+
+impl Scheduler {
+    pub fn new(n_txs: usize) -> Self {
+        Self {
+            n_txs,
+            execution_index: AtomicUsize::new(0),
+            validation_index: AtomicUsize::new(n_txs),
+            commit_index: Mutex::new(0),
+            tx_statuses: (0..n_txs)
+                .map(|_| Mutex::new(TransactionStatus::ReadyToExecute))
+                .collect(),
+            done_marker: AtomicBool::new(n_txs == 0),
+        }
+    }
+
+    /// Returns the next task; validation tasks of lower transactions take precedence over
+    /// execution tasks.
+    pub fn next_task(&self) -> Task {
+        if self.done() {
+            return Task::Done;
+        }
+
+        let index_to_validate = self.validation_index.load(Ordering::Acquire);
+        let index_to_execute = self.execution_index.load(Ordering::Acquire);
+        if index_to_validate.min(index_to_execute) >= self.n_txs {
+            return Task::AskForTask;
+        }
+
+        if index_to_validate < index_to_execute {
+            if let Some(tx_index) = self.next_version_to_validate() {
+                return Task::ValidationTask(tx_index);
+            }
+        }
+        if let Some(tx_index) = self.next_version_to_execute() {
+            return Task::ExecutionTask(tx_index);
+        }
+
+        Task::AskForTask
+    }
+
+    /// Marks the execution of the transaction as done; the transaction, and those following it,
+    /// are to be validated.
+    pub fn finish_execution(&self, tx_index: TxIndex) {
+        self.set_executed_status(tx_index);
+        self.decrease_validation_index(tx_index);
+    }
+
+    /// Marks the re-execution of a transaction being committed as done; its writes may have
+    /// changed, so the transactions following it are to be validated again.
+    pub fn finish_execution_during_commit(&self, tx_index: TxIndex) {
+        self.decrease_validation_index(tx_index + 1);
+    }
+
+    /// Aborts the (executed) transaction, whose validation failed, unless it was already aborted
+    /// or committed. Returns whether it was aborted by this call.
+    pub fn try_validation_abort(&self, tx_index: TxIndex) -> bool {
+        let mut status = self.lock_tx_status(tx_index);
+        if *status == TransactionStatus::Executed {
+            *status = TransactionStatus::Aborting;
+            return true;
+        }
+
+        false
+    }
+
+    /// Marks the abort of the transaction as done; it is to be executed again, and the
+    /// transactions following it are to be validated again. Returns its execution task, if it was
+    /// already handed out once.
+    pub fn finish_abort(&self, tx_index: TxIndex) -> Task {
+        self.set_ready_status(tx_index);
+        self.decrease_validation_index(tx_index + 1);
+        if self.execution_index.load(Ordering::Acquire) > tx_index && self.try_incarnate(tx_index) {
+            return Task::ExecutionTask(tx_index);
+        }
+
+        Task::AskForTask
+    }
+
+    /// Enters the commit phase, unless another worker is in it.
+    pub fn try_enter_commit_phase(&self) -> Option<TransactionCommitter<'_>> {
+        match self.commit_index.try_lock() {
+            Ok(commit_index) => Some(TransactionCommitter { scheduler: self, commit_index }),
+            Err(TryLockError::WouldBlock) => None,
+            Err(TryLockError::Poisoned(error)) => panic!("Commit index is poisoned: {error}."),
+        }
+    }
+
+    /// Stops handing out tasks; no further transaction is committed.
+    pub fn halt(&self) {
+        self.done_marker.store(true, Ordering::Release);
+    }
+
+    pub fn done(&self) -> bool {
+        self.done_marker.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of committed transactions; these are the first ones of the chunk.
+    pub fn n_committed_txs(&self) -> usize {
+        *self.commit_index.lock().expect("Commit index is poisoned.")
+    }
+
+    pub fn tx_status(&self, tx_index: TxIndex) -> TransactionStatus {
+        *self.lock_tx_status(tx_index)
+    }
+
+    fn lock_tx_status(&self, tx_index: TxIndex) -> MutexGuard<'_, TransactionStatus> {
+        self.tx_statuses[tx_index].lock().expect("Transaction status is poisoned.")
+    }
+
+    fn next_version_to_execute(&self) -> Option<TxIndex> {
+        let tx_index = self.execution_index.fetch_add(1, Ordering::SeqCst);
+        if tx_index >= self.n_txs || !self.try_incarnate(tx_index) {
+            return None;
+        }
+
+        Some(tx_index)
+    }
+
+    fn next_version_to_validate(&self) -> Option<TxIndex> {
+        let tx_index = self.validation_index.fetch_add(1, Ordering::SeqCst);
+        if tx_index >= self.n_txs || self.tx_status(tx_index) != TransactionStatus::Executed {
+            return None;
+        }
+
+        Some(tx_index)
+    }
+
+    /// Starts an execution of the transaction, if it is ready to be executed.
+    fn try_incarnate(&self, tx_index: TxIndex) -> bool {
+        let mut status = self.lock_tx_status(tx_index);
+        if *status == TransactionStatus::ReadyToExecute {
+            *status = TransactionStatus::Executing;
+            return true;
+        }
+
+        false
+    }
+
+    fn set_executed_status(&self, tx_index: TxIndex) {
+        let mut status = self.lock_tx_status(tx_index);
+        assert_eq!(
+            *status,
+            TransactionStatus::Executing,
+            "Only executing transactions can finish an execution."
+        );
+        *status = TransactionStatus::Executed;
+    }
+
+    fn set_ready_status(&self, tx_index: TxIndex) {
+        let mut status = self.lock_tx_status(tx_index);
+        assert_eq!(
+            *status,
+            TransactionStatus::Aborting,
+            "Only aborting transactions can be made ready to execute."
+        );
+        *status = TransactionStatus::ReadyToExecute;
+    }
+
+    fn decrease_validation_index(&self, target_index: TxIndex) {
+        self.validation_index.fetch_min(target_index, Ordering::SeqCst);
+    }
+}
+
+/// The commit phase of a scheduler; at most one worker is in it at any time.
+pub struct TransactionCommitter<'a> {
+    scheduler: &'a Scheduler,
+    commit_index: MutexGuard<'a, TxIndex>,
+}
+
+impl TransactionCommitter<'_> {
+    /// Marks the next transaction as committed, if it is executed (and the scheduler was not
+    /// halted); returns its index. The caller commits it, or uncommits it and halts the scheduler.
+    pub fn try_commit(&mut self) -> Option<TxIndex> {
+        if self.scheduler.done() {
+            return None;
+        }
+
+        let tx_index = *self.commit_index;
+        let mut status = self.scheduler.lock_tx_status(tx_index);
+        if *status != TransactionStatus::Executed {
+            return None;
+        }
+        *status = TransactionStatus::Committed;
+        drop(status);
+
+        *self.commit_index += 1;
+        if *self.commit_index == self.scheduler.n_txs {
+            self.scheduler.halt();
+        }
+        Some(tx_index)
+    }
+
+    /// Reverts the last commit; e.g., the transaction does not fit in the block.
+    pub fn uncommit(&mut self) {
+        *self.commit_index -= 1;
+        let mut status = self.scheduler.lock_tx_status(*self.commit_index);
+        assert_eq!(
+            *status,
+            TransactionStatus::Committed,
+            "Only committed transactions can be uncommitted."
+        );
+        *status = TransactionStatus::Executed;
+    }
+
+    pub fn halt_scheduler(&self) {
+        self.scheduler.halt();
+    }
+}
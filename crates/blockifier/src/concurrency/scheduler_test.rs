@@ -0,0 +1,97 @@
+use pretty_assertions::assert_eq;
+
+use crate::concurrency::scheduler::{Scheduler, Task, TransactionStatus};
+
+#[test]
+fn test_next_task() {
+    let scheduler = Scheduler::new(2);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(0));
+    assert_eq!(scheduler.tx_status(0), TransactionStatus::Executing);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(1));
+    // All transactions are handed out, and none is executed yet.
+    assert_eq!(scheduler.next_task(), Task::AskForTask);
+
+    // An executed transaction is validated, along with those following it.
+    scheduler.finish_execution(1);
+    assert_eq!(scheduler.next_task(), Task::ValidationTask(1));
+    scheduler.finish_execution(0);
+    assert_eq!(scheduler.next_task(), Task::ValidationTask(0));
+    assert_eq!(scheduler.next_task(), Task::ValidationTask(1));
+    assert_eq!(scheduler.next_task(), Task::AskForTask);
+}
+
+#[test]
+fn test_validation_abort() {
+    let scheduler = Scheduler::new(3);
+    for tx_index in 0..3 {
+        assert_eq!(scheduler.next_task(), Task::ExecutionTask(tx_index));
+    }
+    for tx_index in 0..3 {
+        scheduler.finish_execution(tx_index);
+    }
+    assert_eq!(
+        [0, 1, 2, 3].map(|_| scheduler.next_task()),
+        [
+            Task::ValidationTask(0),
+            Task::ValidationTask(1),
+            Task::ValidationTask(2),
+            Task::AskForTask
+        ]
+    );
+
+    // A transaction is aborted once, and is then executed again.
+    assert!(scheduler.try_validation_abort(1));
+    assert!(!scheduler.try_validation_abort(1));
+    assert_eq!(scheduler.tx_status(1), TransactionStatus::Aborting);
+    assert_eq!(scheduler.finish_abort(1), Task::ExecutionTask(1));
+    assert_eq!(scheduler.tx_status(1), TransactionStatus::Executing);
+
+    // The transactions following it are validated again.
+    assert_eq!(scheduler.next_task(), Task::ValidationTask(2));
+    scheduler.finish_execution(1);
+    assert_eq!(
+        [scheduler.next_task(), scheduler.next_task()],
+        [Task::ValidationTask(1), Task::ValidationTask(2)]
+    );
+}
+
+#[test]
+fn test_commit_phase() {
+    let scheduler = Scheduler::new(3);
+    let mut tx_committer = scheduler.try_enter_commit_phase().unwrap();
+    // A single worker is in the commit phase.
+    assert!(scheduler.try_enter_commit_phase().is_none());
+    // Transactions are committed in order, once executed.
+    assert_eq!(tx_committer.try_commit(), None);
+    drop(tx_committer);
+
+    for tx_index in 0..3 {
+        assert_eq!(scheduler.next_task(), Task::ExecutionTask(tx_index));
+    }
+    scheduler.finish_execution(1);
+    let mut tx_committer = scheduler.try_enter_commit_phase().unwrap();
+    assert_eq!(tx_committer.try_commit(), None);
+    scheduler.finish_execution(0);
+    assert_eq!([tx_committer.try_commit(), tx_committer.try_commit()], [Some(0), Some(1)]);
+    assert_eq!(scheduler.tx_status(0), TransactionStatus::Committed);
+    // Committed transactions are not aborted.
+    assert!(!scheduler.try_validation_abort(0));
+
+    // E.g., transaction 1 does not fit in the block.
+    tx_committer.uncommit();
+    tx_committer.halt_scheduler();
+    assert_eq!(tx_committer.try_commit(), None);
+    drop(tx_committer);
+    assert_eq!(scheduler.n_committed_txs(), 1);
+    assert_eq!(scheduler.tx_status(1), TransactionStatus::Executed);
+    assert_eq!(scheduler.next_task(), Task::Done);
+}
+
+#[test]
+fn test_done_once_all_committed() {
+    let scheduler = Scheduler::new(1);
+    assert_eq!(scheduler.next_task(), Task::ExecutionTask(0));
+    scheduler.finish_execution(0);
+    assert_eq!(scheduler.try_enter_commit_phase().unwrap().try_commit(), Some(0));
+    assert_eq!(scheduler.next_task(), Task::Done);
+}
@@ -0,0 +1,195 @@
+#[cfg(feature = "concurrency")]
+use std::collections::HashSet;
+#[cfg(feature = "concurrency")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "concurrency")]
+use std::thread;
+
+#[cfg(feature = "concurrency")]
+use tracing::{info_span, Span};
+
+#[cfg(feature = "concurrency")]
+use crate::block_context::BlockContext;
+use crate::concurrency::versioned_state::StateReads;
+#[cfg(feature = "concurrency")]
+use crate::concurrency::versioned_state::{VersionedState, VersionedStateProxy};
+use crate::execution::entry_point::{CallInfo, ExecutionResources};
+#[cfg(feature = "concurrency")]
+use crate::state::cached_state::GlobalContractCache;
+use crate::state::cached_state::{CachedState, ContractClassMapping, StateMaps};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::transaction_execution::Transaction;
+
+#[cfg(all(test, feature = "concurrency"))]
+#[path = "validation_phase_test.rs"]
+pub mod test;
+
+/// The outcome of an account transaction's validation, run ahead of its execution (see
+/// `validate_txs`). Applying it is equivalent to running the validation again, as long as the
+/// values it read are unchanged.
+#[derive(Debug)]
+pub struct PrecomputedValidation {
+    pub reads: StateReads,
+    pub writes: StateMaps,
+    pub class_hash_to_class: ContractClassMapping,
+    pub validate_call_info: Option<CallInfo>,
+    pub resources: ExecutionResources,
+    pub remaining_gas: u64,
+}
+
+impl PrecomputedValidation {
+    /// Returns whether the values read by the validation are the ones seen through the given state.
+    pub fn is_valid_on(&self, state: &mut impl StateReader) -> StateResult<bool> {
+        let reads = &self.reads.values;
+        for (&(contract_address, key), value) in &reads.storage {
+            if state.get_storage_at(contract_address, key)? != *value {
+                return Ok(false);
+            }
+        }
+        for (&contract_address, nonce) in &reads.nonces {
+            if state.get_nonce_at(contract_address)? != *nonce {
+                return Ok(false);
+            }
+        }
+        for (&contract_address, class_hash) in &reads.class_hashes {
+            if state.get_class_hash_at(contract_address)? != *class_hash {
+                return Ok(false);
+            }
+        }
+        for (&class_hash, compiled_class_hash) in &reads.compiled_class_hashes {
+            if state.get_compiled_class_hash(class_hash)? != *compiled_class_hash {
+                return Ok(false);
+            }
+        }
+        for (class_hash, &was_declared) in &self.reads.declared_contracts {
+            let is_declared = match state.get_compiled_contract_class(class_hash) {
+                Ok(_) => true,
+                Err(StateError::UndeclaredClassHash(_)) => false,
+                Err(error) => return Err(error),
+            };
+            if is_declared != was_declared {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Applies the validation to the given state, as if it were run there, and returns its call
+    /// info. The validation is the first call of a transaction; the given resources and gas are set
+    /// to the ones left after it.
+    pub fn apply_to<S: StateReader>(
+        self,
+        state: &mut CachedState<S>,
+        resources: &mut ExecutionResources,
+        remaining_gas: &mut u64,
+    ) -> Option<CallInfo> {
+        state.update_cache(&self.writes, self.class_hash_to_class);
+        *resources = self.resources;
+        *remaining_gas = self.remaining_gas;
+        self.validate_call_info
+    }
+}
+
+/// Counts the validations of the account transactions executed after a validation phase (see
+/// `ConcurrencyConfig::parallel_validation`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ValidationPhaseStats {
+    /// The successful validations of the validation phase.
+    pub n_precomputed: usize,
+    /// The validations left to the execution, as the precomputed one failed, or read a value
+    /// written by a preceding transaction.
+    pub n_rerun: usize,
+}
+
+/// Returns whether the transaction's validation is its first call, and can thus be run ahead of
+/// its execution; `DeployAccount` transactions are validated after the deployment, and L1 handlers
+/// are not validated.
+pub fn is_validated_first(tx: &Transaction) -> bool {
+    matches!(
+        tx,
+        Transaction::AccountTransaction(
+            AccountTransaction::Declare(_) | AccountTransaction::Invoke(_)
+        )
+    )
+}
+
+/// Runs the validations of the given transactions (see `is_validated_first`) using the given
+/// number of worker threads. Every validation runs on the given state, regardless of the writes
+/// of the transactions preceding it, and records its reads. Returns the successful validations, by
+/// transaction; a failed validation is to be run again during the execution, which reproduces its
+/// error, unless a preceding transaction fixed it (e.g., deployed the account).
+#[cfg(feature = "concurrency")]
+pub fn validate_txs<S: StateReader + Send>(
+    state: S,
+    txs: &[Transaction],
+    block_context: &BlockContext,
+    n_workers: usize,
+    global_class_hash_to_class: GlobalContractCache,
+) -> Vec<Option<PrecomputedValidation>> {
+    let state = VersionedState::new(state);
+    let next_tx_index = AtomicUsize::new(0);
+    // The workers trace each validation under the caller's span.
+    let span = Span::current();
+    let validate_next_txs = || {
+        let mut validations = Vec::new();
+        loop {
+            let tx_index = next_tx_index.fetch_add(1, Ordering::Relaxed);
+            let Some(tx) = txs.get(tx_index) else {
+                return validations;
+            };
+            let Transaction::AccountTransaction(account_tx) = tx else {
+                continue;
+            };
+            if !is_validated_first(tx) {
+                continue;
+            }
+            let _validation_span = info_span!(
+                parent: &span,
+                "validation",
+                tx_index,
+                tx_hash = %account_tx.tx_hash().0
+            )
+            .entered();
+            // All validations see the initial state; i.e., the state preceding the first
+            // transaction.
+            let state_proxy = VersionedStateProxy::new(0, &state, HashSet::new());
+            let mut tx_state = CachedState::new(state_proxy, global_class_hash_to_class.clone());
+            let mut resources = ExecutionResources::default();
+            let mut remaining_gas = Transaction::initial_gas();
+            let Ok(validate_call_info) = account_tx.validate_tx(
+                &mut tx_state,
+                &mut resources,
+                &mut remaining_gas,
+                block_context,
+            ) else {
+                continue;
+            };
+
+            let (writes, class_hash_to_class) = tx_state.get_writes();
+            let validation = PrecomputedValidation {
+                reads: tx_state.state.reads,
+                writes,
+                class_hash_to_class,
+                validate_call_info,
+                resources,
+                remaining_gas,
+            };
+            validations.push((tx_index, validation));
+        }
+    };
+
+    let mut validations: Vec<Option<PrecomputedValidation>> = txs.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..n_workers).map(|_| scope.spawn(validate_next_txs)).collect();
+        for worker in workers {
+            for (tx_index, validation) in worker.join().expect("Validation worker panicked.") {
+                validations[tx_index] = Some(validation);
+            }
+        }
+    });
+
+    validations
+}
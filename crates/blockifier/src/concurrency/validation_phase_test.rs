@@ -0,0 +1,65 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::Fee;
+use starknet_api::{class_hash, contract_address, patricia_key};
+
+use crate::block_context::BlockContext;
+use crate::concurrency::validation_phase::validate_txs;
+use crate::state::cached_state::MutRefState;
+use crate::state::state_api::State;
+use crate::test_utils::{
+    deploy_account_tx, NonceManager, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS,
+    TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::test_utils::{
+    create_state_with_trivial_validation_account, storage_write_tx,
+};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[test]
+fn test_validate_txs() {
+    let block_context = BlockContext::create_for_account_testing();
+    let mut nonce_manager = NonceManager::default();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let txs = [
+        storage_write_tx("test_storage_read_write", 1, account_address, &mut nonce_manager),
+        // Validated after its execution.
+        Transaction::AccountTransaction(AccountTransaction::DeployAccount(deploy_account_tx(
+            TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+            Fee(MAX_FEE),
+            None,
+            None,
+            &mut nonce_manager,
+        ))),
+        // Sent by an undeployed account; the validation fails.
+        storage_write_tx(
+            "test_storage_read_write",
+            1,
+            contract_address!("0x999"),
+            &mut nonce_manager,
+        ),
+    ];
+
+    let mut state = create_state_with_trivial_validation_account();
+    let global_contract_cache = state.global_contract_cache();
+    let mut validations =
+        validate_txs(MutRefState::new(&mut state), &txs, &block_context, 2, global_contract_cache);
+    assert!(validations[1].is_none());
+    assert!(validations[2].is_none());
+    let validation = validations[0].take().unwrap();
+
+    // The validation is the one run by the execution.
+    let tx_execution_info = txs[0]
+        .clone()
+        .execute(&mut create_state_with_trivial_validation_account(), &block_context, true, true)
+        .unwrap();
+    assert_eq!(validation.validate_call_info, tx_execution_info.validate_call_info);
+    assert!(validation.is_valid_on(&mut state).unwrap());
+
+    // Replacing the account class invalidates it.
+    state.set_class_hash_at(account_address, class_hash!(TEST_CLASS_HASH)).unwrap();
+    assert!(!validation.is_valid_on(&mut state).unwrap());
+}
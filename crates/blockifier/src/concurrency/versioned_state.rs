@@ -0,0 +1,376 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard, RwLock};
+
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::concurrency::TxIndex;
+use crate::execution::contract_class::ContractClass;
+use crate::state::cached_state::{ContractClassMapping, ContractStorageKey, StateMaps};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+#[cfg(test)]
+#[path = "versioned_state_test.rs"]
+pub mod test;
+
+/// The number of shards each kind of state cells is split into; cells in different shards are
+/// accessed without contention.
+const N_SHARDS: usize = 16;
+
+/// Holds the values of some cells of a single kind: their initial values, and the values written to
+/// them by each transaction.
+#[derive(Debug)]
+struct VersionedStorageShard<K, V> {
+    // Cached values of the initial state.
+    initial_values: HashMap<K, V>,
+    writes: HashMap<K, BTreeMap<TxIndex, V>>,
+}
+
+impl<K, V> Default for VersionedStorageShard<K, V> {
+    fn default() -> Self {
+        Self { initial_values: HashMap::default(), writes: HashMap::default() }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> VersionedStorageShard<K, V> {
+    /// Returns the value of the cell as seen by the given transaction; i.e., the latest value
+    /// written by a preceding transaction, or the cached initial value.
+    fn read(&self, tx_index: TxIndex, key: &K) -> Option<V> {
+        self.writes
+            .get(key)
+            .and_then(|cell_writes| cell_writes.range(..tx_index).next_back())
+            .map(|(_, value)| value)
+            .or_else(|| self.initial_values.get(key))
+            .cloned()
+    }
+}
+
+/// Holds the values of a single kind of state cells, split into shards by key; each cell is
+/// accessed under the lock of its shard, so that the accesses to a single cell are linearizable.
+#[derive(Debug)]
+struct VersionedStorage<K, V> {
+    shards: Vec<RwLock<VersionedStorageShard<K, V>>>,
+}
+
+impl<K, V> Default for VersionedStorage<K, V> {
+    fn default() -> Self {
+        Self { shards: (0..N_SHARDS).map(|_| RwLock::default()).collect() }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> VersionedStorage<K, V> {
+    /// Returns the value of the cell as seen by the given transaction; if it was neither written by
+    /// a preceding transaction nor read before, its initial value is read (once) using the given
+    /// function.
+    fn read(
+        &self,
+        tx_index: TxIndex,
+        key: K,
+        read_initial_value: impl FnOnce() -> StateResult<V>,
+    ) -> StateResult<V> {
+        let shard = self.shard(&key);
+        if let Some(value) =
+            shard.read().expect("Versioned storage is poisoned.").read(tx_index, &key)
+        {
+            return Ok(value);
+        }
+
+        // Check again under the write lock; the cell may have been written (or read) meanwhile.
+        let mut shard = shard.write().expect("Versioned storage is poisoned.");
+        if let Some(value) = shard.read(tx_index, &key) {
+            return Ok(value);
+        }
+        let value = read_initial_value()?;
+        shard.initial_values.insert(key, value.clone());
+        Ok(value)
+    }
+
+    fn write(&self, tx_index: TxIndex, key: K, value: V) {
+        let mut shard = self.shard(&key).write().expect("Versioned storage is poisoned.");
+        shard.writes.entry(key).or_default().insert(tx_index, value);
+    }
+
+    fn delete_write(&self, tx_index: TxIndex, key: &K) {
+        let mut shard = self.shard(key).write().expect("Versioned storage is poisoned.");
+        if let Some(cell_writes) = shard.writes.get_mut(key) {
+            cell_writes.remove(&tx_index);
+        }
+    }
+
+    fn shard(&self, key: &K) -> &RwLock<VersionedStorageShard<K, V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        // The remainder is smaller than `N_SHARDS`, so the cast is lossless.
+        &self.shards[(hasher.finish() % N_SHARDS as u64) as usize]
+    }
+}
+
+/// The values read by a transaction from the versioned state; used to validate that the
+/// transaction's execution is still up to date.
+#[derive(Debug, Default)]
+pub struct StateReads {
+    pub values: StateMaps,
+    // Whether each requested contract class was declared.
+    pub declared_contracts: HashMap<ClassHash, bool>,
+}
+
+/// The state of a chunk of transactions that are executed concurrently.
+/// Holds the initial state, and the values written by each transaction (by its index). A
+/// transaction sees the latest values written by the transactions preceding it.
+/// The state is shared by the workers; each kind of cells is sharded (see `VersionedStorage`), and
+/// the initial state is only locked to read a cell for the first time.
+#[derive(Debug)]
+pub struct VersionedState<S: StateReader> {
+    initial_state: Mutex<S>,
+    storage: VersionedStorage<ContractStorageKey, StarkFelt>,
+    nonces: VersionedStorage<ContractAddress, Nonce>,
+    class_hashes: VersionedStorage<ContractAddress, ClassHash>,
+    compiled_class_hashes: VersionedStorage<ClassHash, CompiledClassHash>,
+    compiled_contract_classes: VersionedStorage<ClassHash, ContractClass>,
+}
+
+impl<S: StateReader> VersionedState<S> {
+    pub fn new(initial_state: S) -> Self {
+        Self {
+            initial_state: Mutex::new(initial_state),
+            storage: VersionedStorage::default(),
+            nonces: VersionedStorage::default(),
+            class_hashes: VersionedStorage::default(),
+            compiled_class_hashes: VersionedStorage::default(),
+            compiled_contract_classes: VersionedStorage::default(),
+        }
+    }
+
+    pub fn get_storage_at(
+        &self,
+        tx_index: TxIndex,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        self.storage.read(tx_index, (contract_address, key), || {
+            self.initial_state().get_storage_at(contract_address, key)
+        })
+    }
+
+    pub fn get_nonce_at(
+        &self,
+        tx_index: TxIndex,
+        contract_address: ContractAddress,
+    ) -> StateResult<Nonce> {
+        self.nonces.read(tx_index, contract_address, || {
+            self.initial_state().get_nonce_at(contract_address)
+        })
+    }
+
+    pub fn get_class_hash_at(
+        &self,
+        tx_index: TxIndex,
+        contract_address: ContractAddress,
+    ) -> StateResult<ClassHash> {
+        self.class_hashes.read(tx_index, contract_address, || {
+            self.initial_state().get_class_hash_at(contract_address)
+        })
+    }
+
+    pub fn get_compiled_class_hash(
+        &self,
+        tx_index: TxIndex,
+        class_hash: ClassHash,
+    ) -> StateResult<CompiledClassHash> {
+        self.compiled_class_hashes
+            .read(tx_index, class_hash, || self.initial_state().get_compiled_class_hash(class_hash))
+    }
+
+    pub fn get_compiled_contract_class(
+        &self,
+        tx_index: TxIndex,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        self.compiled_contract_classes.read(tx_index, *class_hash, || {
+            self.initial_state().get_compiled_contract_class(class_hash)
+        })
+    }
+
+    /// Returns whether the given values, read by the given transaction, are still the values it
+    /// sees.
+    pub fn validate_reads(&self, tx_index: TxIndex, reads: &StateReads) -> StateResult<bool> {
+        for (&(contract_address, key), value) in &reads.values.storage {
+            if self.get_storage_at(tx_index, contract_address, key)? != *value {
+                return Ok(false);
+            }
+        }
+
+        for (&contract_address, nonce) in &reads.values.nonces {
+            if self.get_nonce_at(tx_index, contract_address)? != *nonce {
+                return Ok(false);
+            }
+        }
+
+        for (&contract_address, class_hash) in &reads.values.class_hashes {
+            if self.get_class_hash_at(tx_index, contract_address)? != *class_hash {
+                return Ok(false);
+            }
+        }
+
+        for (&class_hash, compiled_class_hash) in &reads.values.compiled_class_hashes {
+            if self.get_compiled_class_hash(tx_index, class_hash)? != *compiled_class_hash {
+                return Ok(false);
+            }
+        }
+
+        for (class_hash, &was_declared) in &reads.declared_contracts {
+            let is_declared = match self.get_compiled_contract_class(tx_index, class_hash) {
+                Ok(_) => true,
+                Err(StateError::UndeclaredClassHash(_)) => false,
+                Err(error) => return Err(error),
+            };
+            if is_declared != was_declared {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Sets the given values as written by the given transaction.
+    pub fn apply_writes(
+        &self,
+        tx_index: TxIndex,
+        writes: &StateMaps,
+        class_hash_to_class: &ContractClassMapping,
+    ) {
+        for (&contract_storage_key, &value) in &writes.storage {
+            self.storage.write(tx_index, contract_storage_key, value);
+        }
+        for (&contract_address, &nonce) in &writes.nonces {
+            self.nonces.write(tx_index, contract_address, nonce);
+        }
+        for (&contract_address, &class_hash) in &writes.class_hashes {
+            self.class_hashes.write(tx_index, contract_address, class_hash);
+        }
+        for (&class_hash, &compiled_class_hash) in &writes.compiled_class_hashes {
+            self.compiled_class_hashes.write(tx_index, class_hash, compiled_class_hash);
+        }
+        for (&class_hash, contract_class) in class_hash_to_class {
+            self.compiled_contract_classes.write(tx_index, class_hash, contract_class.clone());
+        }
+    }
+
+    /// Removes the given values, previously written by the given transaction; e.g., before it is
+    /// re-executed.
+    pub fn delete_writes(
+        &self,
+        tx_index: TxIndex,
+        writes: &StateMaps,
+        class_hash_to_class: &ContractClassMapping,
+    ) {
+        for contract_storage_key in writes.storage.keys() {
+            self.storage.delete_write(tx_index, contract_storage_key);
+        }
+        for contract_address in writes.nonces.keys() {
+            self.nonces.delete_write(tx_index, contract_address);
+        }
+        for contract_address in writes.class_hashes.keys() {
+            self.class_hashes.delete_write(tx_index, contract_address);
+        }
+        for class_hash in writes.compiled_class_hashes.keys() {
+            self.compiled_class_hashes.delete_write(tx_index, class_hash);
+        }
+        for class_hash in class_hash_to_class.keys() {
+            self.compiled_contract_classes.delete_write(tx_index, class_hash);
+        }
+    }
+
+    pub fn into_initial_state(self) -> S {
+        self.initial_state.into_inner().expect("Initial state is poisoned.")
+    }
+
+    fn initial_state(&self) -> MutexGuard<'_, S> {
+        self.initial_state.lock().expect("Initial state is poisoned.")
+    }
+}
+
+/// Exposes the versioned state as seen by a single transaction, and records the transaction's
+/// reads.
+pub struct VersionedStateProxy<'a, S: StateReader> {
+    pub tx_index: TxIndex,
+    pub state: &'a VersionedState<S>,
+    pub reads: StateReads,
+    // The storage cells below are read as zero; their reads are recorded separately.
+    pub zero_reads: HashSet<ContractStorageKey>,
+    zero_read_storage_keys: HashSet<ContractStorageKey>,
+}
+
+impl<'a, S: StateReader> VersionedStateProxy<'a, S> {
+    pub fn new(
+        tx_index: TxIndex,
+        state: &'a VersionedState<S>,
+        zero_read_storage_keys: HashSet<ContractStorageKey>,
+    ) -> Self {
+        Self {
+            tx_index,
+            state,
+            reads: StateReads::default(),
+            zero_reads: HashSet::default(),
+            zero_read_storage_keys,
+        }
+    }
+}
+
+impl<S: StateReader> StateReader for VersionedStateProxy<'_, S> {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let contract_storage_key = (contract_address, key);
+        if self.zero_read_storage_keys.contains(&contract_storage_key) {
+            self.zero_reads.insert(contract_storage_key);
+            return Ok(StarkFelt::default());
+        }
+
+        let value = self.state.get_storage_at(self.tx_index, contract_address, key)?;
+        self.reads.values.storage.entry(contract_storage_key).or_insert(value);
+        Ok(value)
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let nonce = self.state.get_nonce_at(self.tx_index, contract_address)?;
+        self.reads.values.nonces.entry(contract_address).or_insert(nonce);
+        Ok(nonce)
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let class_hash = self.state.get_class_hash_at(self.tx_index, contract_address)?;
+        self.reads.values.class_hashes.entry(contract_address).or_insert(class_hash);
+        Ok(class_hash)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        let result = self.state.get_compiled_contract_class(self.tx_index, class_hash);
+        match &result {
+            Ok(_) => {
+                self.reads.declared_contracts.entry(*class_hash).or_insert(true);
+            }
+            Err(StateError::UndeclaredClassHash(_)) => {
+                self.reads.declared_contracts.entry(*class_hash).or_insert(false);
+            }
+            Err(_) => {}
+        }
+
+        result
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let compiled_class_hash = self.state.get_compiled_class_hash(self.tx_index, class_hash)?;
+        self.reads.values.compiled_class_hashes.entry(class_hash).or_insert(compiled_class_hash);
+        Ok(compiled_class_hash)
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::thread;
+
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::concurrency::versioned_state::{VersionedState, VersionedStateProxy};
+use crate::state::cached_state::StateMaps;
+use crate::state::state_api::StateReader;
+use crate::test_utils::{
+    get_test_contract_class, DictStateReader, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+};
+
+fn create_versioned_state() -> VersionedState<DictStateReader> {
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let initial_state = DictStateReader {
+        address_to_nonce: HashMap::from([(contract_address, Nonce(stark_felt!(1_u8)))]),
+        ..Default::default()
+    };
+    VersionedState::new(initial_state)
+}
+
+#[test]
+fn test_read_at_tx_index() {
+    let versioned_state = create_versioned_state();
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let writes = |nonce: u8| StateMaps {
+        nonces: HashMap::from([(contract_address, Nonce(stark_felt!(nonce)))]),
+        ..Default::default()
+    };
+    versioned_state.apply_writes(1, &writes(2), &HashMap::new());
+    versioned_state.apply_writes(3, &writes(4), &HashMap::new());
+
+    // Each transaction sees the latest write of a preceding transaction.
+    let nonce_at = |tx_index| {
+        VersionedStateProxy::new(tx_index, &versioned_state, HashSet::new())
+            .get_nonce_at(contract_address)
+            .unwrap()
+    };
+    assert_eq!(
+        [nonce_at(0), nonce_at(1), nonce_at(2), nonce_at(3), nonce_at(4)],
+        [1_u8, 1, 2, 2, 4].map(|nonce| Nonce(stark_felt!(nonce)))
+    );
+
+    // Deleted writes are no longer seen.
+    versioned_state.delete_writes(1, &writes(2), &HashMap::new());
+    assert_eq!(nonce_at(3), Nonce(stark_felt!(1_u8)));
+}
+
+#[test]
+fn test_validate_reads() {
+    let versioned_state = create_versioned_state();
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let class_hash = class_hash!(TEST_CLASS_HASH);
+
+    let mut proxy = VersionedStateProxy::new(2, &versioned_state, HashSet::new());
+    proxy.get_storage_at(contract_address, Default::default()).unwrap();
+    proxy.get_class_hash_at(contract_address).unwrap();
+    proxy.get_compiled_contract_class(&class_hash).unwrap_err();
+    let reads = proxy.reads;
+    assert!(versioned_state.validate_reads(2, &reads).unwrap());
+
+    // A write of a succeeding transaction does not invalidate the reads.
+    let class_hash_to_class = HashMap::from([(class_hash, get_test_contract_class())]);
+    versioned_state.apply_writes(2, &StateMaps::default(), &class_hash_to_class);
+    assert!(versioned_state.validate_reads(2, &reads).unwrap());
+
+    // A write of a preceding transaction does.
+    versioned_state.apply_writes(1, &StateMaps::default(), &class_hash_to_class);
+    assert!(!versioned_state.validate_reads(2, &reads).unwrap());
+}
+
+/// Concurrent accesses to the same cells are linearizable: each transaction reads back its own
+/// latest write, and never a write of a succeeding transaction.
+#[test]
+fn test_concurrent_read_your_own_writes() {
+    const N_TXS: u64 = 8;
+    const N_ROUNDS: u64 = 500;
+    const N_KEYS: u64 = 4;
+    let versioned_state = create_versioned_state();
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    // The value encodes the writing transaction (offset by one, as the initial value is zero).
+    let value = |tx_index: u64, round: u64| stark_felt!((tx_index + 1) * N_ROUNDS + round);
+
+    thread::scope(|scope| {
+        for tx_index in 0..N_TXS {
+            let versioned_state = &versioned_state;
+            scope.spawn(move || {
+                let tx = usize::try_from(tx_index).unwrap();
+                for round in 0..N_ROUNDS {
+                    let key = StorageKey(patricia_key!(round % N_KEYS));
+                    let writes = StateMaps {
+                        storage: HashMap::from([((contract_address, key), value(tx_index, round))]),
+                        ..Default::default()
+                    };
+                    versioned_state.apply_writes(tx, &writes, &HashMap::new());
+                    let own_value =
+                        versioned_state.get_storage_at(tx + 1, contract_address, key).unwrap();
+                    assert_eq!(own_value, value(tx_index, round));
+
+                    // With its write deleted, the transaction sees a preceding one (if any).
+                    versioned_state.delete_writes(tx, &writes, &HashMap::new());
+                    let seen_value =
+                        versioned_state.get_storage_at(tx + 1, contract_address, key).unwrap();
+                    assert!(seen_value < value(tx_index, 0));
+                }
+            });
+        }
+    });
+}
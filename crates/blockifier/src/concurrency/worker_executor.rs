@@ -0,0 +1,445 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+
+use starknet_api::core::ContractAddress;
+use starknet_api::hash::StarkFelt;
+use tracing::{info_span, Span};
+
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::block_context::BlockContext;
+use crate::blockifier::bouncer::{Bouncer, BouncerWeights};
+use crate::blockifier::transaction_executor::{
+    l1_handler_payload_size, TransactionExecutorError, TransactionExecutorResult,
+};
+use crate::concurrency::fee_utils::{
+    add_uint256, fill_sequencer_balance_reads, requires_sequential_execution,
+    sequencer_balance_cells,
+};
+use crate::concurrency::scheduler::{Scheduler, Task};
+use crate::concurrency::versioned_state::{StateReads, VersionedState, VersionedStateProxy};
+use crate::concurrency::TxIndex;
+use crate::state::cached_state::{
+    CachedState, ContractClassMapping, ContractStorageKey, GlobalContractCache, StateChangesKeys,
+    StateMaps,
+};
+use crate::state::state_api::{State, StateReader, StateResult};
+use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[cfg(test)]
+#[path = "worker_executor_test.rs"]
+pub mod test;
+
+/// The output of executing a transaction over the versioned state.
+#[derive(Debug)]
+pub struct ExecutionTaskOutput {
+    pub reads: StateReads,
+    pub writes: StateMaps,
+    pub class_hash_to_class: ContractClassMapping,
+    /// The sequencer balance cells read (as zero) by the transaction.
+    pub sequencer_balance_reads: HashSet<ContractStorageKey>,
+    /// Per fee token, the (low, high) amount added to the sequencer balance by the transaction.
+    /// These are not part of `writes`; the balance itself is updated when committing.
+    pub sequencer_balance_deltas: HashMap<ContractAddress, (StarkFelt, StarkFelt)>,
+    /// The keys of the state changes made by the transaction; used to compute its weights.
+    pub state_changes_keys: StateChangesKeys,
+    pub result: TransactionExecutionResult<TransactionExecutionInfo>,
+}
+
+impl ExecutionTaskOutput {
+    /// Applies the transaction to the given state, reproducing the accesses of a sequential
+    /// execution: reads first (caching initial values), then writes. Returns its execution result.
+    pub fn apply_to<T: StateReader>(
+        self,
+        state: &mut CachedState<T>,
+    ) -> StateResult<TransactionExecutionResult<TransactionExecutionInfo>> {
+        let reads = &self.reads.values;
+        for &(contract_address, key) in
+            reads.storage.keys().chain(self.sequencer_balance_reads.iter())
+        {
+            state.get_storage_at(contract_address, key)?;
+        }
+        for &contract_address in reads.nonces.keys() {
+            state.get_nonce_at(contract_address)?;
+        }
+        for &contract_address in reads.class_hashes.keys() {
+            state.get_class_hash_at(contract_address)?;
+        }
+        for &class_hash in reads.compiled_class_hashes.keys() {
+            state.get_compiled_class_hash(class_hash)?;
+        }
+
+        state.update_cache(&self.writes, self.class_hash_to_class);
+        Ok(self.result)
+    }
+}
+
+/// The outputs of a chunk executed concurrently, up to the first transaction that could not be
+/// committed.
+#[derive(Debug)]
+pub struct CommittedChunk<S> {
+    /// The outputs of the committed transactions, in order.
+    pub outputs: Vec<ExecutionTaskOutput>,
+    /// The error of the transaction following the committed ones, if it could not be committed
+    /// (e.g., it does not fit in the block); the transactions after it were discarded, as they
+    /// were executed on top of it.
+    pub commit_error: Option<TransactionExecutorError>,
+    /// The (untouched) initial state; applying the outputs to it (see
+    /// `ExecutionTaskOutput::apply_to`) reproduces a sequential execution.
+    pub initial_state: S,
+}
+
+/// Executes a chunk of transactions concurrently, and commits them with the same results as a
+/// sequential execution.
+///
+/// The workers execute transactions optimistically over a versioned state, recording their reads,
+/// and validate the reads against the writes of the transactions preceding them; a transaction
+/// whose reads are outdated is executed again (see `Scheduler`). Meanwhile, transactions are
+/// committed in order, by one worker at a time: a committed transaction is validated once more,
+/// on top of the committed ones; if still invalid, it is re-executed there, i.e., sequentially.
+/// Each committed transaction is added to the bouncer; the commit stops at the first one that
+/// does not fit in the block.
+///
+/// Since every transaction pays a fee to the sequencer, the sequencer balance is read as zero
+/// during the optimistic execution, and the fee transfer's write is kept as a delta; the actual
+/// balance is computed when committing. A transaction that otherwise accesses the sequencer
+/// balance is executed sequentially.
+pub struct WorkerExecutor<'a, S: StateReader> {
+    pub scheduler: Scheduler,
+    pub state: VersionedState<S>,
+    pub chunk: &'a [Transaction],
+    pub block_context: &'a BlockContext,
+    pub charge_fee: bool,
+    pub validate: bool,
+    bouncer: Mutex<&'a mut Bouncer>,
+    execution_outputs: Vec<Mutex<Option<ExecutionTaskOutput>>>,
+    // The error that halted the commit phase, if any.
+    commit_error: Mutex<Option<TransactionExecutorError>>,
+    global_class_hash_to_class: GlobalContractCache,
+    // The span current at creation; the workers trace each (re-)execution under it.
+    span: Span,
+}
+
+impl<'a, S: StateReader + Send> WorkerExecutor<'a, S> {
+    pub fn new(
+        state: S,
+        chunk: &'a [Transaction],
+        block_context: &'a BlockContext,
+        bouncer: &'a mut Bouncer,
+        charge_fee: bool,
+        validate: bool,
+        global_class_hash_to_class: GlobalContractCache,
+    ) -> Self {
+        Self {
+            scheduler: Scheduler::new(chunk.len()),
+            state: VersionedState::new(state),
+            chunk,
+            block_context,
+            charge_fee,
+            validate,
+            bouncer: Mutex::new(bouncer),
+            execution_outputs: chunk.iter().map(|_| Mutex::new(None)).collect(),
+            commit_error: Mutex::new(None),
+            global_class_hash_to_class,
+            span: Span::current(),
+        }
+    }
+
+    /// Executes the chunk using the given number of worker threads.
+    /// Returns the execution results of the committed transactions, the error of the transaction
+    /// that could not be committed, if any (see `CommittedChunk::commit_error`), and the state
+    /// resulting from applying the committed transactions to the initial state; all are identical
+    /// to those of a sequential execution.
+    #[allow(clippy::type_complexity)]
+    pub fn run(
+        self,
+        n_workers: usize,
+    ) -> StateResult<(
+        Vec<TransactionExecutionResult<TransactionExecutionInfo>>,
+        Option<TransactionExecutorError>,
+        CachedState<S>,
+    )> {
+        let global_class_hash_to_class = self.global_class_hash_to_class.clone();
+        let CommittedChunk { outputs, commit_error, initial_state } =
+            self.execute_and_commit(n_workers)?;
+
+        let mut state = CachedState::new(initial_state, global_class_hash_to_class);
+        let results = outputs
+            .into_iter()
+            .map(|output| output.apply_to(&mut state))
+            .collect::<StateResult<_>>()?;
+
+        Ok((results, commit_error, state))
+    }
+
+    /// Executes the chunk using the given number of worker threads, and commits it.
+    /// Fails only if the state cannot be read.
+    pub fn execute_and_commit(self, n_workers: usize) -> StateResult<CommittedChunk<S>> {
+        thread::scope(|scope| {
+            for _ in 0..n_workers {
+                scope.spawn(|| self.run_tasks());
+            }
+        });
+
+        let n_committed_txs = self.scheduler.n_committed_txs();
+        let commit_error = self.commit_error.into_inner().expect("Commit error is poisoned.");
+        if let Some(TransactionExecutorError::StateError(error)) = commit_error {
+            return Err(error);
+        }
+        let outputs = self
+            .execution_outputs
+            .into_iter()
+            .take(n_committed_txs)
+            .map(|output| {
+                output
+                    .into_inner()
+                    .expect("Execution output is poisoned.")
+                    .expect("Committed transactions must be executed.")
+            })
+            .collect();
+        let initial_state = self.state.into_initial_state();
+
+        Ok(CommittedChunk { outputs, commit_error, initial_state })
+    }
+
+    fn run_tasks(&self) {
+        let mut task = Task::AskForTask;
+        loop {
+            self.commit_while_possible();
+            task = match task {
+                Task::ExecutionTask(tx_index) => {
+                    self.execute(tx_index);
+                    Task::AskForTask
+                }
+                Task::ValidationTask(tx_index) => self.validate(tx_index),
+                Task::AskForTask => {
+                    let next_task = self.scheduler.next_task();
+                    if next_task == Task::AskForTask {
+                        thread::yield_now();
+                    }
+                    next_task
+                }
+                Task::Done => break,
+            };
+        }
+    }
+
+    /// Executes the transaction optimistically, and publishes its writes.
+    fn execute(&self, tx_index: TxIndex) {
+        let output = self.execute_tx(tx_index, self.sequencer_balance_keys());
+        self.publish_output(tx_index, output);
+        self.scheduler.finish_execution(tx_index);
+    }
+
+    /// Replaces the writes of the transaction's previous execution (if any) with the given ones.
+    fn publish_output(&self, tx_index: TxIndex, output: ExecutionTaskOutput) {
+        let mut execution_output =
+            self.execution_outputs[tx_index].lock().expect("Execution output is poisoned.");
+        if let Some(previous_output) = execution_output.take() {
+            self.state.delete_writes(
+                tx_index,
+                &previous_output.writes,
+                &previous_output.class_hash_to_class,
+            );
+        }
+        self.state.apply_writes(tx_index, &output.writes, &output.class_hash_to_class);
+        *execution_output = Some(output);
+    }
+
+    /// Aborts the transaction if its reads are outdated; returns the next task of the worker.
+    fn validate(&self, tx_index: TxIndex) -> Task {
+        if !self.is_valid(tx_index) && self.scheduler.try_validation_abort(tx_index) {
+            return self.scheduler.finish_abort(tx_index);
+        }
+
+        Task::AskForTask
+    }
+
+    /// Returns whether the reads of the transaction's last execution are the values it sees.
+    fn is_valid(&self, tx_index: TxIndex) -> bool {
+        let execution_output =
+            self.execution_outputs[tx_index].lock().expect("Execution output is poisoned.");
+        let reads = &execution_output.as_ref().expect("Transaction must be executed.").reads;
+        // A failure to read the state invalidates the execution; re-executing reproduces it.
+        self.state.validate_reads(tx_index, reads).unwrap_or(false)
+    }
+
+    /// Executes the transaction over the versioned state, as seen by it; the given storage cells
+    /// are read as zero.
+    fn execute_tx(
+        &self,
+        tx_index: TxIndex,
+        zero_read_storage_keys: HashSet<ContractStorageKey>,
+    ) -> ExecutionTaskOutput {
+        let tx = self.chunk[tx_index].clone();
+        let _tx_span =
+            info_span!(parent: &self.span, "transaction", tx_index, tx_hash = %tx.tx_hash().0)
+                .entered();
+        let versioned_state_proxy =
+            VersionedStateProxy::new(tx_index, &self.state, zero_read_storage_keys.clone());
+        let mut tx_state =
+            CachedState::new(versioned_state_proxy, self.global_class_hash_to_class.clone());
+        let result = tx.execute(&mut tx_state, self.block_context, self.charge_fee, self.validate);
+
+        let state_changes_keys = StateChangesKeys::from(&tx_state.to_state_diff());
+        let (mut writes, class_hash_to_class) = tx_state.get_writes();
+        let mut sequencer_balance_deltas = HashMap::new();
+        for (fee_token_address, low_key, high_key) in sequencer_balance_cells(self.block_context) {
+            if !zero_read_storage_keys.contains(&(fee_token_address, low_key)) {
+                // The balance was actually read; its writes are the actual balance.
+                continue;
+            }
+            let low = writes.storage.remove(&(fee_token_address, low_key));
+            let high = writes.storage.remove(&(fee_token_address, high_key));
+            if low.is_some() || high.is_some() {
+                sequencer_balance_deltas
+                    .insert(fee_token_address, (low.unwrap_or_default(), high.unwrap_or_default()));
+            }
+        }
+        let VersionedStateProxy { reads, zero_reads, .. } = tx_state.state;
+
+        ExecutionTaskOutput {
+            reads,
+            writes,
+            class_hash_to_class,
+            sequencer_balance_reads: zero_reads,
+            sequencer_balance_deltas,
+            state_changes_keys,
+            result,
+        }
+    }
+
+    /// Commits the executed transactions that follow the committed ones, in order, unless another
+    /// worker is doing so. Halts the scheduler on the first one that cannot be committed.
+    fn commit_while_possible(&self) {
+        let Some(mut tx_committer) = self.scheduler.try_enter_commit_phase() else {
+            return;
+        };
+        while let Some(tx_index) = tx_committer.try_commit() {
+            if let Err(error) = self.commit_tx(tx_index) {
+                tx_committer.uncommit();
+                tx_committer.halt_scheduler();
+                *self.commit_error.lock().expect("Commit error is poisoned.") = Some(error);
+            }
+        }
+    }
+
+    /// Commits the transaction, following the committed ones; fails if it does not fit in the
+    /// block.
+    fn commit_tx(&self, tx_index: TxIndex) -> TransactionExecutorResult<()> {
+        let mut execution_output =
+            self.execution_outputs[tx_index].lock().expect("Execution output is poisoned.");
+        let output = execution_output.as_mut().expect("Transaction must be executed.");
+        let is_valid = self.state.validate_reads(tx_index, &output.reads).unwrap_or(false);
+
+        // All preceding transactions are committed; a re-execution is a sequential execution. The
+        // sequencer balance is still read as zero, unless the result may depend on it.
+        if !is_valid {
+            self.reexecute_during_commit(tx_index, output, self.sequencer_balance_keys());
+        }
+        if self.requires_sequential_execution(tx_index, output) {
+            self.reexecute_during_commit(tx_index, output, HashSet::new());
+        }
+
+        let mut bouncer = self.bouncer.lock().expect("Bouncer is poisoned.");
+        if let Ok(tx_execution_info) = &output.result {
+            let tx_weights = BouncerWeights::from_tx_execution(
+                tx_execution_info,
+                bouncer.marginal_state_changes_count(&output.state_changes_keys),
+                l1_handler_payload_size(&self.chunk[tx_index])?,
+                self.block_context,
+            )?;
+            bouncer.try_update(tx_weights, &output.state_changes_keys)?;
+        }
+
+        // The transaction is counted in the block only once it is committed.
+        match self.add_fee_to_sequencer_balance(tx_index, output) {
+            Ok(()) => {
+                bouncer.commit();
+                Ok(())
+            }
+            Err(error) => {
+                bouncer.rollback();
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Re-executes the transaction being committed, and replaces its output.
+    fn reexecute_during_commit(
+        &self,
+        tx_index: TxIndex,
+        output: &mut ExecutionTaskOutput,
+        zero_read_storage_keys: HashSet<ContractStorageKey>,
+    ) {
+        let reexecution_output = self.execute_tx(tx_index, zero_read_storage_keys);
+        self.state.delete_writes(tx_index, &output.writes, &output.class_hash_to_class);
+        self.state.apply_writes(
+            tx_index,
+            &reexecution_output.writes,
+            &reexecution_output.class_hash_to_class,
+        );
+        *output = reexecution_output;
+        self.scheduler.finish_execution_during_commit(tx_index);
+    }
+
+    /// Writes the sequencer balance after the given transaction's fee transfer, and fills in the
+    /// balance read by the fee transfer (which was read as zero).
+    fn add_fee_to_sequencer_balance(
+        &self,
+        tx_index: TxIndex,
+        output: &mut ExecutionTaskOutput,
+    ) -> TransactionExecutionResult<()> {
+        let state = &self.state;
+        let (low_key, high_key) =
+            get_erc20_balance_var_addresses(&self.block_context.sequencer_address)?;
+        for (&fee_token_address, &fee) in &output.sequencer_balance_deltas {
+            let sequencer_balance = (
+                state.get_storage_at(tx_index, fee_token_address, low_key)?,
+                state.get_storage_at(tx_index, fee_token_address, high_key)?,
+            );
+            let (new_low, new_high) = add_uint256(sequencer_balance, fee)?;
+            output.writes.storage.insert((fee_token_address, low_key), new_low);
+            output.writes.storage.insert((fee_token_address, high_key), new_high);
+
+            if let Ok(TransactionExecutionInfo {
+                fee_transfer_call_info: Some(fee_transfer_call_info),
+                ..
+            }) = &mut output.result
+            {
+                if fee_transfer_call_info.call.storage_address == fee_token_address {
+                    fill_sequencer_balance_reads(fee_transfer_call_info, sequencer_balance)?;
+                }
+            }
+        }
+        state.apply_writes(tx_index, &output.writes, &HashMap::new());
+
+        Ok(())
+    }
+
+    /// Returns whether the result of the optimistic execution may depend on the sequencer balance
+    /// being read as zero; i.e., the balance was not (only) accessed by a fee transfer.
+    fn requires_sequential_execution(
+        &self,
+        tx_index: TxIndex,
+        output: &ExecutionTaskOutput,
+    ) -> bool {
+        !output.sequencer_balance_reads.is_empty()
+            && requires_sequential_execution(
+                &self.chunk[tx_index],
+                self.block_context,
+                &output.result,
+            )
+    }
+
+    fn sequencer_balance_keys(&self) -> HashSet<ContractStorageKey> {
+        sequencer_balance_cells(self.block_context)
+            .into_iter()
+            .flat_map(|(fee_token_address, low_key, high_key)| {
+                [(fee_token_address, low_key), (fee_token_address, high_key)]
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::block_context::BlockContext;
+use crate::blockifier::bouncer::{Bouncer, BouncerConfig, BouncerError, BouncerWeights};
+use crate::blockifier::transaction_executor::TransactionExecutorError;
+use crate::concurrency::worker_executor::{CommittedChunk, WorkerExecutor};
+use crate::execution::contract_class::ContractClassV0;
+use crate::selector;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::State;
+use crate::test_utils::{
+    DictStateReader, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH, BALANCE, ERC20_CONTRACT_PATH,
+    MAX_FEE, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_ERC20_CONTRACT_CLASS_HASH,
+};
+use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::test_utils::account_invoke_tx;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+const N_ACCOUNTS: usize = 4;
+const N_TRANSFERS: usize = 20;
+const SEQUENCER_INITIAL_BALANCE: u128 = 17;
+
+fn account_addresses() -> Vec<ContractAddress> {
+    (0..N_ACCOUNTS).map(|i| contract_address!(format!("0x{:x}", 0x2001 + i).as_str())).collect()
+}
+
+fn create_transfers_test_state(block_context: &BlockContext) -> DictStateReader {
+    let account_class_hash = class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH);
+    let erc20_class_hash = class_hash!(TEST_ERC20_CONTRACT_CLASS_HASH);
+    let class_hash_to_class = HashMap::from([
+        (account_class_hash, ContractClassV0::from_file(ACCOUNT_CONTRACT_CAIRO0_PATH).into()),
+        (erc20_class_hash, ContractClassV0::from_file(ERC20_CONTRACT_PATH).into()),
+    ]);
+
+    let fee_token_addresses = &block_context.fee_token_addresses;
+    let erc20_addresses =
+        [fee_token_addresses.eth_fee_token_address, fee_token_addresses.strk_fee_token_address];
+    let mut address_to_class_hash: HashMap<_, _> =
+        erc20_addresses.iter().map(|&address| (address, erc20_class_hash)).collect();
+    let mut storage_view = HashMap::new();
+    for account_address in account_addresses() {
+        address_to_class_hash.insert(account_address, account_class_hash);
+        let (balance_low_key, _) = get_erc20_balance_var_addresses(&account_address).unwrap();
+        storage_view.insert((erc20_addresses[0], balance_low_key), stark_felt!(BALANCE));
+    }
+    let (sequencer_balance_low_key, _) =
+        get_erc20_balance_var_addresses(&block_context.sequencer_address).unwrap();
+    storage_view.insert(
+        (erc20_addresses[0], sequencer_balance_low_key),
+        stark_felt!(SEQUENCER_INITIAL_BALANCE),
+    );
+
+    DictStateReader {
+        address_to_class_hash,
+        class_hash_to_class,
+        storage_view,
+        ..Default::default()
+    }
+}
+
+/// Creates transfers among the accounts; if `pay_sequencer` is set, one of them is to the sequencer.
+fn create_transfers(
+    block_context: &BlockContext,
+    n_transfers: usize,
+    pay_sequencer: bool,
+) -> Vec<Transaction> {
+    let account_addresses = account_addresses();
+    let mut nonce_manager = NonceManager::default();
+    (0..n_transfers)
+        .map(|i| {
+            let sender_address = account_addresses[i % N_ACCOUNTS];
+            let recipient_address = if pay_sequencer && i == n_transfers / 2 {
+                block_context.sequencer_address
+            } else {
+                account_addresses[(i + 1) % N_ACCOUNTS]
+            };
+            let execute_calldata = calldata![
+                *block_context.fee_token_addresses.eth_fee_token_address.0.key(), // Contract address.
+                selector!("transfer").0,                                          // EP selector.
+                stark_felt!(3_u8),                          // Calldata length.
+                *recipient_address.0.key(),                 // Calldata: recipient.
+                stark_felt!(u64::try_from(i + 1).unwrap()), // Calldata: amount (low).
+                stark_felt!(0_u8)                           // Calldata: amount (high).
+            ];
+            Transaction::AccountTransaction(account_invoke_tx(
+                execute_calldata,
+                sender_address,
+                &mut nonce_manager,
+                Fee(MAX_FEE),
+            ))
+        })
+        .collect()
+}
+
+fn comparable_results(
+    results: Vec<TransactionExecutionResult<TransactionExecutionInfo>>,
+) -> Vec<Result<TransactionExecutionInfo, String>> {
+    results.into_iter().map(|result| result.map_err(|error| error.to_string())).collect()
+}
+
+/// Executes the chunk sequentially, and then concurrently (using the given number of workers) the
+/// given number of times; asserts the results and the resulting states are always identical.
+fn assert_concurrent_execution_matches_sequential(
+    block_context: &BlockContext,
+    chunk: &[Transaction],
+    n_workers: usize,
+    n_runs: usize,
+) {
+    let mut sequential_state = CachedState::from(create_transfers_test_state(block_context));
+    let sequential_results: Vec<_> = chunk
+        .iter()
+        .map(|tx| tx.clone().execute(&mut sequential_state, block_context, true, true))
+        .collect();
+    assert!(sequential_results.iter().all(|result| !result.as_ref().unwrap().is_reverted()));
+    let sequential_results = comparable_results(sequential_results);
+    let (sequential_writes, _) = sequential_state.get_writes();
+
+    for _ in 0..n_runs {
+        let mut bouncer = Bouncer::new(BouncerConfig::max());
+        let worker_executor = WorkerExecutor::new(
+            create_transfers_test_state(block_context),
+            chunk,
+            block_context,
+            &mut bouncer,
+            true,
+            true,
+            Default::default(),
+        );
+        let (concurrent_results, commit_error, concurrent_state) =
+            worker_executor.run(n_workers).unwrap();
+
+        assert_matches!(commit_error, None);
+        assert_eq!(comparable_results(concurrent_results), sequential_results);
+        assert_eq!(concurrent_state.to_state_diff(), sequential_state.to_state_diff());
+        let (concurrent_writes, _) = concurrent_state.get_writes();
+        assert_eq!(concurrent_writes, sequential_writes);
+    }
+}
+
+#[rstest]
+#[case::transfers_among_accounts(false)]
+// The transfer to the sequencer reads its balance, and is executed sequentially.
+#[case::transfer_to_sequencer(true)]
+fn test_concurrent_transfers_match_sequential_execution(#[case] pay_sequencer: bool) {
+    let block_context = BlockContext::create_for_account_testing();
+    let chunk = create_transfers(&block_context, N_TRANSFERS, pay_sequencer);
+    assert_concurrent_execution_matches_sequential(&block_context, &chunk, 4, 1);
+}
+
+/// The committed results do not depend on the interleaving of the workers.
+// A stress test; run with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn test_conflicting_transfers_are_deterministic() {
+    let block_context = BlockContext::create_for_account_testing();
+    let chunk = create_transfers(&block_context, 200, false);
+    assert_concurrent_execution_matches_sequential(&block_context, &chunk, 8, 50);
+}
+
+/// The fee transfers do not conflict through the sequencer balance; it is read as zero by all of
+/// them, and its actual value, the sum of the fees, is written when committing.
+#[test]
+fn test_sequencer_balance_is_virtual() {
+    let block_context = BlockContext::create_for_account_testing();
+    let chunk = create_transfers(&block_context, N_TRANSFERS, false);
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let (low_key, high_key) =
+        get_erc20_balance_var_addresses(&block_context.sequencer_address).unwrap();
+
+    let mut bouncer = Bouncer::new(BouncerConfig::max());
+    let CommittedChunk { outputs, .. } = WorkerExecutor::new(
+        create_transfers_test_state(&block_context),
+        &chunk,
+        &block_context,
+        &mut bouncer,
+        true,
+        true,
+        Default::default(),
+    )
+    .execute_and_commit(4)
+    .unwrap();
+
+    assert_eq!(outputs.len(), N_TRANSFERS);
+    let mut expected_balance = SEQUENCER_INITIAL_BALANCE;
+    for output in outputs {
+        let Fee(actual_fee) = output.result.as_ref().unwrap().receipt.collected_fee;
+        expected_balance += actual_fee;
+        assert!(actual_fee > 0);
+        assert!(output.sequencer_balance_reads.contains(&(fee_token_address, low_key)));
+        assert!(!output.reads.values.storage.contains_key(&(fee_token_address, low_key)));
+        assert_eq!(
+            output.sequencer_balance_deltas[&fee_token_address],
+            (stark_felt!(actual_fee), stark_felt!(0_u8))
+        );
+        // Each commit writes the balance after the transaction's fee transfer.
+        assert_eq!(
+            output.writes.storage[&(fee_token_address, low_key)],
+            stark_felt!(expected_balance)
+        );
+        assert_eq!(output.writes.storage[&(fee_token_address, high_key)], stark_felt!(0_u8));
+    }
+}
+
+/// The commit stops at the first transaction that does not fit in the block; the following ones
+/// are discarded.
+#[test]
+fn test_commit_halts_when_block_is_full() {
+    let block_context = BlockContext::create_for_account_testing();
+    let chunk = create_transfers(&block_context, N_TRANSFERS, false);
+
+    // Measure the weights of the first transactions.
+    let n_fitting_txs = N_TRANSFERS / 2;
+    let mut bouncer = Bouncer::new(BouncerConfig::max());
+    WorkerExecutor::new(
+        create_transfers_test_state(&block_context),
+        &chunk[..n_fitting_txs],
+        &block_context,
+        &mut bouncer,
+        true,
+        true,
+        Default::default(),
+    )
+    .execute_and_commit(4)
+    .unwrap();
+
+    let mut bouncer = Bouncer::new(BouncerConfig {
+        block_max_capacity: BouncerWeights {
+            n_steps: bouncer.accumulated_weights.n_steps,
+            ..BouncerWeights::max()
+        },
+    });
+    let worker_executor = WorkerExecutor::new(
+        create_transfers_test_state(&block_context),
+        &chunk,
+        &block_context,
+        &mut bouncer,
+        true,
+        true,
+        Default::default(),
+    );
+    let (results, commit_error, _) = worker_executor.run(4).unwrap();
+
+    assert_eq!(results.len(), n_fitting_txs);
+    assert_matches!(
+        commit_error,
+        Some(TransactionExecutorError::BouncerError(BouncerError::BlockFull {
+            dimension: "n_steps"
+        }))
+    );
+}
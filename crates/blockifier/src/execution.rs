@@ -9,3 +9,4 @@ pub mod errors;
 pub mod execution_utils;
 pub mod hint_code;
 pub mod syscalls;
+pub mod trace_diff;
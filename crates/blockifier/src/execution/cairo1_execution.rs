@@ -1,13 +1,17 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
 use cairo_felt::Felt252;
 use cairo_vm::serde::deserialize_program::BuiltinName;
 use cairo_vm::types::relocatable::{MaybeRelocatable, Relocatable};
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
 use cairo_vm::vm::runners::builtin_runner::SEGMENT_ARENA_BUILTIN_NAME;
 use cairo_vm::vm::runners::cairo_runner::{
-    CairoArg, CairoRunner, ExecutionResources as VmExecutionResources,
+    CairoArg, CairoRunner, ExecutionResources as VmExecutionResources, ResourceTracker,
 };
 use cairo_vm::vm::vm_core::VirtualMachine;
 use num_traits::ToPrimitive;
+use starknet_api::core::ClassHash;
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
 
@@ -20,10 +24,10 @@ use crate::execution::errors::{
     EntryPointExecutionError, PostExecutionError, PreExecutionError, VirtualMachineExecutionError,
 };
 use crate::execution::execution_utils::{
-    read_execution_retdata, stark_felt_to_felt, write_maybe_relocatable, write_stark_felt, Args,
-    ReadOnlySegments,
+    get_n_memory_cells, read_execution_retdata, stark_felt_to_felt, write_maybe_relocatable,
+    write_stark_felt, Args, ReadOnlySegments,
 };
-use crate::execution::syscalls::hint_processor::SyscallHintProcessor;
+use crate::execution::syscalls::hint_processor::{SyscallHintProcessor, OUT_OF_GAS_ERROR};
 use crate::state::state_api::State;
 
 // TODO(spapini): Try to refactor this file into a StarknetRunner struct.
@@ -38,6 +42,35 @@ pub struct VmExecutionContext<'a> {
     pub program_extra_data_length: usize,
 }
 
+/// The resources of calls that run out of gas in the prelude of their entry point (see
+/// `EntryPointV1::required_gas`), by class hash and entry point PC. All such calls run the same
+/// code, regardless of their calldata and state, and differ only in the memory of their calldata;
+/// the first one is recorded, and replayed by the following ones without running the VM. Clones
+/// share the recorded runs.
+#[derive(Clone, Debug, Default)]
+pub struct PreludeOutOfGasCache(Arc<Mutex<HashMap<(ClassHash, usize), PreludeOutOfGasRun>>>);
+
+impl PreludeOutOfGasCache {
+    pub fn get(&self, class_hash: ClassHash, entry_point_pc: usize) -> Option<PreludeOutOfGasRun> {
+        self.lock().get(&(class_hash, entry_point_pc)).cloned()
+    }
+
+    pub fn record(&self, class_hash: ClassHash, entry_point_pc: usize, run: PreludeOutOfGasRun) {
+        self.lock().insert((class_hash, entry_point_pc), run);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(ClassHash, usize), PreludeOutOfGasRun>> {
+        self.0.lock().expect("Prelude out-of-gas cache is poisoned.")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PreludeOutOfGasRun {
+    pub vm_resources: VmExecutionResources,
+    /// Excluding the calldata, whose length is that of the replaying call.
+    pub n_memory_cells: usize,
+}
+
 pub struct CallResult {
     pub failed: bool,
     pub retdata: Retdata,
@@ -52,6 +85,26 @@ pub fn execute_entry_point_call(
     resources: &mut ExecutionResources,
     context: &mut EntryPointExecutionContext,
 ) -> EntryPointExecutionResult<CallInfo> {
+    let entry_point = contract_class.get_entry_point(&call)?;
+    let entry_point_pc = entry_point.pc();
+    let runs_out_of_gas_in_prelude =
+        entry_point.required_gas.is_some_and(|required_gas| call.initial_gas < required_gas);
+    // The class hash of a call that runs out of gas in the prelude, under which its run is cached;
+    // it is set when the call is executed (see `CallEntryPoint::execute`).
+    let prelude_out_of_gas_class_hash = call.class_hash.filter(|_| runs_out_of_gas_in_prelude);
+    if let Some(class_hash) = prelude_out_of_gas_class_hash {
+        let prelude_out_of_gas_cache = &context.block_context.prelude_out_of_gas_cache;
+        if let Some(run) = prelude_out_of_gas_cache.get(class_hash, entry_point_pc) {
+            let calldata_length = call.calldata.0.len();
+            if let Some(error) = replay_prelude_out_of_gas(run, calldata_length, resources, context)
+            {
+                return Err(error);
+            }
+        }
+    }
+
+    // The calldata is excluded from the memory of a recorded run (see `PreludeOutOfGasRun`).
+    let previous_n_memory_cells = context.n_memory_cells + call.calldata.0.len();
     let VmExecutionContext {
         mut runner,
         mut vm,
@@ -82,7 +135,10 @@ pub fn execute_entry_point_call(
         entry_point,
         args,
         program_segment_size,
-    )?;
+    )
+    .map_err(|error| {
+        EntryPointExecutionError::from_vm_error(error, syscall_handler.syscall_error.take())
+    })?;
 
     let call_info = finalize_execution(
         vm,
@@ -93,6 +149,19 @@ pub fn execute_entry_point_call(
         program_extra_data_length,
     )?;
     if call_info.execution.failed {
+        if let Some(class_hash) = prelude_out_of_gas_class_hash {
+            if call_info.execution.retdata.0 == out_of_gas_error_data() {
+                let run = PreludeOutOfGasRun {
+                    vm_resources: call_info.vm_resources,
+                    n_memory_cells: context.n_memory_cells - previous_n_memory_cells,
+                };
+                context.block_context.prelude_out_of_gas_cache.record(
+                    class_hash,
+                    entry_point_pc,
+                    run,
+                );
+            }
+        }
         return Err(EntryPointExecutionError::ExecutionFailed {
             error_data: call_info.execution.retdata.0,
         });
@@ -101,6 +170,34 @@ pub fn execute_entry_point_call(
     Ok(call_info)
 }
 
+/// Applies a call that runs out of gas in its entry point's prelude, given the recorded run of such
+/// a call and the length of its own calldata, without running the VM; returns the failure of the
+/// call, as the VM run would. Returns [None] if the call has too few steps left to run the
+/// prelude; it is then to be run, and fail on the step limit.
+fn replay_prelude_out_of_gas(
+    run: PreludeOutOfGasRun,
+    calldata_length: usize,
+    resources: &mut ExecutionResources,
+    context: &mut EntryPointExecutionContext,
+) -> Option<EntryPointExecutionError> {
+    if let Some(n_steps) = context.vm_run_resources.get_n_steps() {
+        if n_steps < run.vm_resources.n_steps {
+            return None;
+        }
+        context.subtract_steps(run.vm_resources.n_steps);
+    }
+    resources.vm_resources += &run.vm_resources;
+    if let Err(error) = context.add_memory_cells(run.n_memory_cells + calldata_length) {
+        return Some(error.into());
+    }
+
+    Some(EntryPointExecutionError::ExecutionFailed { error_data: out_of_gas_error_data() })
+}
+
+fn out_of_gas_error_data() -> Vec<StarkFelt> {
+    vec![StarkFelt::try_from(OUT_OF_GAS_ERROR).expect("The out-of-gas error must be a felt.")]
+}
+
 pub fn initialize_execution_context<'a>(
     call: CallEntryPoint,
     contract_class: &'a ContractClassV1,
@@ -287,9 +384,10 @@ pub fn finalize_execution(
         .map_err(VirtualMachineError::TracerError)?
         .filter_unused_builtins();
     syscall_handler.resources.vm_resources += &vm_resources_without_inner_calls;
+    syscall_handler.context.add_memory_cells(get_n_memory_cells(&vm))?;
 
     let full_call_vm_resources = &syscall_handler.resources.vm_resources - &previous_vm_resources;
-    Ok(CallInfo {
+    let mut call_info = CallInfo {
         call: syscall_handler.call,
         execution: CallExecution {
             retdata: call_result.retdata,
@@ -300,9 +398,14 @@ pub fn finalize_execution(
         },
         vm_resources: full_call_vm_resources.filter_unused_builtins(),
         inner_calls: syscall_handler.inner_calls,
+        gas_metered: true,
         storage_read_values: syscall_handler.read_values,
         accessed_storage_keys: syscall_handler.accessed_keys,
-    })
+        syscall_counter: syscall_handler.syscall_counter,
+    };
+    call_info.shrink_to_fit();
+
+    Ok(call_info)
 }
 
 fn get_call_result(
@@ -320,31 +423,29 @@ fn get_call_result(
     } else if *failure_flag == MaybeRelocatable::from(1) {
         true
     } else {
-        return Err(PostExecutionError::MalformedReturnData {
-            error_message: "Failure flag expected to be either 0 or 1.".to_string(),
-        });
+        return Err(PostExecutionError::InvalidFailureFlag { failure_flag: failure_flag.clone() });
     };
 
     let retdata_size = retdata_end.sub(retdata_start)?;
     // TODO(spapini): Validate implicits.
 
-    let gas = &return_result[0];
-    let MaybeRelocatable::Int(gas) = gas else {
-        return Err(PostExecutionError::MalformedReturnData {
-            error_message: "Error extracting return data.".to_string(),
+    let initial_gas = syscall_handler.call.initial_gas;
+    let MaybeRelocatable::Int(remaining_gas) = &return_result[0] else {
+        return Err(PostExecutionError::MalformedRemainingGas {
+            remaining_gas: return_result[0].clone(),
         });
     };
-    let gas = gas.to_u64().ok_or(PostExecutionError::MalformedReturnData {
-        error_message: format!("Unexpected remaining gas: {gas}."),
-    })?;
-
-    if gas > syscall_handler.call.initial_gas {
-        return Err(PostExecutionError::MalformedReturnData {
-            error_message: format!("Unexpected remaining gas: {gas}."),
-        });
-    }
+    let gas = match remaining_gas.to_u64() {
+        Some(gas) if gas <= initial_gas => gas,
+        _ => {
+            return Err(PostExecutionError::UnexpectedRemainingGas {
+                remaining_gas: remaining_gas.clone(),
+                initial_gas,
+            });
+        }
+    };
 
-    let gas_consumed = syscall_handler.call.initial_gas - gas;
+    let gas_consumed = initial_gas - gas;
     Ok(CallResult {
         failed,
         retdata: read_execution_retdata(vm, retdata_size, retdata_start)?,
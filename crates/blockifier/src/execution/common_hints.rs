@@ -1,8 +1,7 @@
 use std::collections::HashMap;
-use std::ops::Shl;
 use std::rc::Rc;
 
-use cairo_felt::{Felt252, PRIME_STR};
+use cairo_felt::Felt252;
 use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::{
     BuiltinHintProcessor, HintFunc,
 };
@@ -13,18 +12,37 @@ use cairo_vm::hint_processor::hint_processor_definition::HintReference;
 use cairo_vm::serde::deserialize_program::ApTracking;
 use cairo_vm::types::exec_scope::ExecutionScopes;
 use cairo_vm::vm::errors::hint_errors::HintError;
-use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
 use cairo_vm::vm::runners::cairo_runner::RunResources;
 use cairo_vm::vm::vm_core::VirtualMachine;
-use num_bigint::BigUint;
-use num_traits::{Num, One, Zero};
+use num_traits::{One, Zero};
+use once_cell::sync::Lazy;
 
+use crate::execution::execution_utils::felt_lt_pow2;
 use crate::execution::hint_code::{
     NORMALIZE_ADDRESS_SET_IS_250_HINT, NORMALIZE_ADDRESS_SET_IS_SMALL_HINT,
 };
 
 pub type HintExecutionResult = Result<(), HintError>;
 
+/// A hint function, as defined by the `HintProcessor`.
+pub type HintFunction = fn(
+    &mut VirtualMachine,
+    &mut ExecutionScopes,
+    &HashMap<String, HintReference>,
+    &ApTracking,
+    &HashMap<String, Felt252>,
+) -> HintExecutionResult;
+
+/// The hints the builtin hint processor is extended with, by code.
+pub const COMMON_HINTS: [(&str, HintFunction); 2] = [
+    (NORMALIZE_ADDRESS_SET_IS_SMALL_HINT, normalize_address_set_is_small),
+    (NORMALIZE_ADDRESS_SET_IS_250_HINT, normalize_address_set_is_250),
+];
+
+static POW2_250: Lazy<Felt252> = Lazy::new(|| Felt252::one() << 250_u32);
+static POW2_251: Lazy<Felt252> = Lazy::new(|| Felt252::one() << 251_u32);
+static HALF_PRIME: Lazy<Felt252> = Lazy::new(|| Felt252::from(Felt252::prime() >> 1_u32));
+
 /// Must comply with the API of a hint function, as defined by the `HintProcessor`.
 pub fn normalize_address_set_is_small(
     vm: &mut VirtualMachine,
@@ -34,19 +52,13 @@ pub fn normalize_address_set_is_small(
     constants: &HashMap<String, Felt252>,
 ) -> HintExecutionResult {
     const ADDR_BOUND: &str = "starkware.starknet.common.storage.ADDR_BOUND";
-    let addr_bound = &constants
-        .get(ADDR_BOUND)
-        .ok_or_else(|| HintError::MissingConstant("ADDR_BOUND".into()))?
-        .to_biguint();
-    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?.to_biguint();
-    let prime = BigUint::from_str_radix(&PRIME_STR[2..], 16)
-        .map_err(|_| VirtualMachineError::CouldntParsePrime(PRIME_STR.into()))?;
+    let addr_bound =
+        constants.get(ADDR_BOUND).ok_or_else(|| HintError::MissingConstant("ADDR_BOUND".into()))?;
+    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
 
-    if !(addr_bound > &BigUint::from(1u8).shl(250)
-        && addr_bound <= &BigUint::from(1u8).shl(251)
-        && prime > (BigUint::from(2u8) * BigUint::from(1u8).shl(250))
-        && prime < (2u8 * addr_bound))
-    {
+    // 2 * 2**250 < PRIME holds for the StarkNet prime; PRIME < 2 * ADDR_BOUND holds iff
+    // ADDR_BOUND > (PRIME - 1) / 2, as the prime is odd.
+    if !(*addr_bound > *POW2_250 && *addr_bound <= *POW2_251 && *addr_bound > *HALF_PRIME) {
         return Err(HintError::AssertionFailed(
             format!(
                 "assert (2**250 < {addr_bound} <= 2**251) and (2 * 2**250 < PRIME) and \
@@ -57,7 +69,7 @@ pub fn normalize_address_set_is_small(
         ));
     }
 
-    let is_small = if addr < *addr_bound { Felt252::one() } else { Felt252::zero() };
+    let is_small = if *addr < *addr_bound { Felt252::one() } else { Felt252::zero() };
     insert_value_from_var_name("is_small", is_small, vm, ids_data, ap_tracking)
 }
 
@@ -71,22 +83,17 @@ pub fn normalize_address_set_is_250(
 ) -> HintExecutionResult {
     let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
 
-    let is_250 =
-        if *addr < (Felt252::one() << (250_u32)) { Felt252::one() } else { Felt252::zero() };
+    let is_250 = if felt_lt_pow2(&addr, 250) { Felt252::one() } else { Felt252::zero() };
     insert_value_from_var_name("is_250", is_250, vm, ids_data, ap_tracking)
 }
 
 /// Extend the builtin hint processor with common hints.
 pub fn extended_builtin_hint_processor() -> BuiltinHintProcessor {
-    let extra_hints: HashMap<String, Rc<HintFunc>> = HashMap::from([
-        (
-            NORMALIZE_ADDRESS_SET_IS_SMALL_HINT.to_string(),
-            Rc::new(HintFunc(Box::new(normalize_address_set_is_small))),
-        ),
-        (
-            NORMALIZE_ADDRESS_SET_IS_250_HINT.to_string(),
-            Rc::new(HintFunc(Box::new(normalize_address_set_is_250))),
-        ),
-    ]);
+    let extra_hints: HashMap<String, Rc<HintFunc>> = COMMON_HINTS
+        .iter()
+        .map(|&(hint_code, hint_function)| {
+            (hint_code.to_string(), Rc::new(HintFunc(Box::new(hint_function))))
+        })
+        .collect();
     BuiltinHintProcessor::new(extra_hints, RunResources::default())
 }
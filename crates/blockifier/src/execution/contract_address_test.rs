@@ -3,9 +3,9 @@ use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::transaction::{Calldata, ContractAddressSalt};
 use starknet_api::{calldata, class_hash, stark_felt};
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::execution::entry_point::{CallEntryPoint, CallExecution, Retdata};
 use crate::retdata;
+use crate::selector;
 use crate::state::cached_state::CachedState;
 use crate::test_utils::{
     deprecated_create_test_state, trivial_external_entry_point, DictStateReader, TEST_CLASS_HASH,
@@ -26,7 +26,7 @@ fn test_calculate_contract_address() {
     ) {
         let entry_point_call = CallEntryPoint {
             calldata,
-            entry_point_selector: selector_from_name("test_contract_address"),
+            entry_point_selector: selector!("test_contract_address"),
             ..trivial_external_entry_point()
         };
         let contract_address =
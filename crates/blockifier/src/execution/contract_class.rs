@@ -4,7 +4,8 @@ use std::sync::Arc;
 
 use cairo_felt::Felt252;
 use cairo_lang_casm;
-use cairo_lang_casm::hints::Hint;
+use cairo_lang_casm::hints::{CoreHint, CoreHintBase, Hint};
+use cairo_lang_casm::operand::{CellRef, Register, ResOperand};
 use cairo_lang_starknet::casm_contract_class::{CasmContractClass, CasmContractEntryPoint};
 use cairo_vm::serde::deserialize_program::{
     ApTracking, FlowTrackingData, HintParams, ReferenceManager,
@@ -14,6 +15,7 @@ use cairo_vm::types::program::Program;
 use cairo_vm::types::relocatable::MaybeRelocatable;
 use cairo_vm::vm::runners::builtin_runner::{HASH_BUILTIN_NAME, POSEIDON_BUILTIN_NAME};
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
+use num_traits::ToPrimitive;
 use serde::de::Error as DeserializationError;
 use serde::{Deserialize, Deserializer};
 use starknet_api::core::EntryPointSelector;
@@ -22,10 +24,14 @@ use starknet_api::deprecated_contract_class::{
     Program as DeprecatedProgram,
 };
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants::{self, CONSTRUCTOR_ENTRY_POINT_NAME};
 use crate::execution::errors::PreExecutionError;
 use crate::execution::execution_utils::{felt_to_stark_felt, sn_api_to_cairo_vm_program};
+use crate::selector;
+
+#[cfg(test)]
+#[path = "contract_class_test.rs"]
+pub mod test;
 
 /// Represents a runnable StarkNet contract class (meaning, the program is runnable by the VM).
 /// We wrap the actual class in an Arc to avoid cloning the program when cloning the class.
@@ -38,6 +44,15 @@ pub enum ContractClass {
 }
 
 impl ContractClass {
+    pub fn bytecode_length(&self) -> usize {
+        match self {
+            ContractClass::V0(class) => class.bytecode_length(),
+            ContractClass::V1(class) => class.bytecode_length(),
+        }
+    }
+
+    /// Returns the selector of the constructor; [None] if the class has no constructor entry
+    /// point (e.g., a deprecated class without the constructor entry point type).
     pub fn constructor_selector(&self) -> Option<EntryPointSelector> {
         match self {
             ContractClass::V0(class) => class.constructor_selector(),
@@ -53,6 +68,25 @@ impl ContractClass {
     }
 }
 
+/// The figures of a class that its declaration requires; a declaration given these, instead of
+/// the class itself, leaves the compiled class to be read from the state upon its first
+/// invocation (e.g., when replaying blocks, where declared classes may never be invoked).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ClassInfo {
+    pub cairo_version: u64,
+    pub bytecode_length: usize,
+}
+
+impl From<&ContractClass> for ClassInfo {
+    fn from(contract_class: &ContractClass) -> Self {
+        let cairo_version = match contract_class {
+            ContractClass::V0(_) => 0,
+            ContractClass::V1(_) => 1,
+        };
+        Self { cairo_version, bytecode_length: contract_class.bytecode_length() }
+    }
+}
+
 // V0.
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
 pub struct ContractClassV0(pub Arc<ContractClassV0Inner>);
@@ -66,7 +100,7 @@ impl Deref for ContractClassV0 {
 
 impl ContractClassV0 {
     fn constructor_selector(&self) -> Option<EntryPointSelector> {
-        Some(self.entry_points_by_type[&EntryPointType::Constructor].first()?.selector)
+        Some(self.entry_points_by_type.get(&EntryPointType::Constructor)?.first()?.selector)
     }
 
     fn n_entry_points(&self) -> usize {
@@ -105,21 +139,47 @@ impl ContractClassV0 {
     }
 }
 
-#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct ContractClassV0Inner {
-    #[serde(deserialize_with = "deserialize_program")]
     pub program: Program,
     pub entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
+    // Built from the entry points; a class with modified entry points is rebuilt using `new`.
+    pub(crate) entry_point_index: EntryPointIndex,
+}
+
+impl ContractClassV0Inner {
+    pub fn new(
+        program: Program,
+        entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
+    ) -> Self {
+        let entry_point_index =
+            EntryPointIndex::new(&entry_points_by_type, |entry_point| entry_point.selector);
+        Self { program, entry_points_by_type, entry_point_index }
+    }
+}
+
+impl<'de> Deserialize<'de> for ContractClassV0Inner {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawContractClassV0 {
+            #[serde(deserialize_with = "deserialize_program")]
+            program: Program,
+            entry_points_by_type: HashMap<EntryPointType, Vec<EntryPoint>>,
+        }
+
+        let raw_contract_class = RawContractClassV0::deserialize(deserializer)?;
+        Ok(Self::new(raw_contract_class.program, raw_contract_class.entry_points_by_type))
+    }
 }
 
 impl TryFrom<DeprecatedContractClass> for ContractClassV0 {
     type Error = ProgramError;
 
     fn try_from(class: DeprecatedContractClass) -> Result<Self, Self::Error> {
-        Ok(Self(Arc::new(ContractClassV0Inner {
-            program: sn_api_to_cairo_vm_program(class.program)?,
-            entry_points_by_type: class.entry_points_by_type,
-        })))
+        Ok(Self(Arc::new(ContractClassV0Inner::new(
+            sn_api_to_cairo_vm_program(class.program)?,
+            class.entry_points_by_type,
+        ))))
     }
 }
 
@@ -136,7 +196,7 @@ impl Deref for ContractClassV1 {
 
 impl ContractClassV1 {
     fn constructor_selector(&self) -> Option<EntryPointSelector> {
-        Some(self.0.entry_points_by_type[&EntryPointType::Constructor].first()?.selector)
+        Some(self.0.entry_points_by_type.get(&EntryPointType::Constructor)?.first()?.selector)
     }
 
     pub fn bytecode_length(&self) -> usize {
@@ -148,24 +208,15 @@ impl ContractClassV1 {
         call: &super::entry_point::CallEntryPoint,
     ) -> Result<EntryPointV1, PreExecutionError> {
         if call.entry_point_type == EntryPointType::Constructor
-            && call.entry_point_selector != selector_from_name(CONSTRUCTOR_ENTRY_POINT_NAME)
+            && call.entry_point_selector != selector!(CONSTRUCTOR_ENTRY_POINT_NAME)
         {
             return Err(PreExecutionError::InvalidConstructorEntryPointName);
         }
 
         let entry_points_of_same_type = &self.0.entry_points_by_type[&call.entry_point_type];
-        let filtered_entry_points: Vec<_> = entry_points_of_same_type
-            .iter()
-            .filter(|ep| ep.selector == call.entry_point_selector)
-            .collect();
-
-        match &filtered_entry_points[..] {
-            [] => Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector)),
-            [entry_point] => Ok((*entry_point).clone()),
-            _ => Err(PreExecutionError::DuplicatedEntryPointSelector {
-                selector: call.entry_point_selector,
-                typ: call.entry_point_type,
-            }),
+        match self.entry_point_index.position(call.entry_point_type, call.entry_point_selector)? {
+            Some(position) => Ok(entry_points_of_same_type[position].clone()),
+            None => Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector)),
         }
     }
 
@@ -199,7 +250,9 @@ impl ContractClassV1 {
 pub struct ContractClassV1Inner {
     pub program: Program,
     pub entry_points_by_type: HashMap<EntryPointType, Vec<EntryPointV1>>,
-    pub hints: HashMap<String, Hint>,
+    pub hints: HashMap<String, Arc<Hint>>,
+    // Built from the entry points, when the class is loaded.
+    pub(crate) entry_point_index: EntryPointIndex,
 }
 
 #[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
@@ -207,6 +260,9 @@ pub struct EntryPointV1 {
     pub selector: EntryPointSelector,
     pub offset: EntryPointOffset,
     pub builtins: Vec<String>,
+    /// The gas checked by the entry point's prelude, if it starts with a gas check; a call given
+    /// less gas runs out of gas there, before running any of the entry point's code.
+    pub required_gas: Option<u64>,
 }
 
 impl EntryPointV1 {
@@ -233,11 +289,11 @@ impl TryFrom<CasmContractClass> for ContractClassV1 {
         }
 
         // Collect a sting to hint map so that the hint processor can fetch the correct [Hint]
-        // for each instruction.
-        let mut string_to_hint: HashMap<String, Hint> = HashMap::new();
+        // for each instruction; the hints are shared, as they are handed to the VM on every run.
+        let mut string_to_hint: HashMap<String, Arc<Hint>> = HashMap::new();
         for (_, hint_list) in class.hints.iter() {
             for hint in hint_list.iter() {
-                string_to_hint.insert(serde_json::to_string(hint)?, hint.clone());
+                string_to_hint.insert(serde_json::to_string(hint)?, Arc::new(hint.clone()));
             }
         }
 
@@ -259,28 +315,82 @@ impl TryFrom<CasmContractClass> for ContractClassV1 {
             instruction_locations,
         )?;
 
+        let hints_by_pc: HashMap<usize, &[Hint]> =
+            class.hints.iter().map(|(pc, hint_list)| (*pc, hint_list.as_slice())).collect();
         let mut entry_points_by_type = HashMap::new();
         entry_points_by_type.insert(
             EntryPointType::Constructor,
-            convert_entry_points_v1(class.entry_points_by_type.constructor)?,
+            convert_entry_points_v1(class.entry_points_by_type.constructor, &hints_by_pc)?,
         );
         entry_points_by_type.insert(
             EntryPointType::External,
-            convert_entry_points_v1(class.entry_points_by_type.external)?,
+            convert_entry_points_v1(class.entry_points_by_type.external, &hints_by_pc)?,
         );
         entry_points_by_type.insert(
             EntryPointType::L1Handler,
-            convert_entry_points_v1(class.entry_points_by_type.l1_handler)?,
+            convert_entry_points_v1(class.entry_points_by_type.l1_handler, &hints_by_pc)?,
         );
 
+        let entry_point_index =
+            EntryPointIndex::new(&entry_points_by_type, |entry_point| entry_point.selector);
         Ok(Self(Arc::new(ContractClassV1Inner {
             program,
             entry_points_by_type,
             hints: string_to_hint,
+            entry_point_index,
         })))
     }
 }
 
+/// The positions of the entry points of a class in its entry point lists, by type and selector;
+/// built when the class is loaded, so that an entry point is resolved in constant time.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EntryPointIndex(HashMap<(EntryPointType, EntryPointSelector), EntryPointPosition>);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum EntryPointPosition {
+    Unique(usize),
+    Duplicated,
+}
+
+impl EntryPointIndex {
+    fn new<E>(
+        entry_points_by_type: &HashMap<EntryPointType, Vec<E>>,
+        selector: impl Fn(&E) -> EntryPointSelector,
+    ) -> Self {
+        let mut positions = HashMap::new();
+        for (&entry_point_type, entry_points) in entry_points_by_type {
+            for (position, entry_point) in entry_points.iter().enumerate() {
+                positions
+                    .entry((entry_point_type, selector(entry_point)))
+                    .and_modify(|position| *position = EntryPointPosition::Duplicated)
+                    .or_insert(EntryPointPosition::Unique(position));
+            }
+        }
+
+        Self(positions)
+    }
+
+    /// Returns the position of the entry point of the given type and selector, if there is one;
+    /// a selector appearing more than once is an error.
+    pub fn position(
+        &self,
+        entry_point_type: EntryPointType,
+        selector: EntryPointSelector,
+    ) -> Result<Option<usize>, PreExecutionError> {
+        match self.0.get(&(entry_point_type, selector)) {
+            None => Ok(None),
+            Some(EntryPointPosition::Unique(position)) => Ok(Some(*position)),
+            Some(EntryPointPosition::Duplicated) => {
+                Err(PreExecutionError::DuplicatedEntryPointSelector {
+                    selector,
+                    typ: entry_point_type,
+                })
+            }
+        }
+    }
+}
+
 // V0 utilities.
 
 /// Converts the program type from SN API into a Cairo VM-compatible type.
@@ -308,6 +418,7 @@ fn hint_to_hint_params(hint: &cairo_lang_casm::hints::Hint) -> Result<HintParams
 
 fn convert_entry_points_v1(
     external: Vec<CasmContractEntryPoint>,
+    hints_by_pc: &HashMap<usize, &[Hint]>,
 ) -> Result<Vec<EntryPointV1>, ProgramError> {
     external
         .into_iter()
@@ -318,7 +429,26 @@ fn convert_entry_points_v1(
                 )),
                 offset: EntryPointOffset(ep.offset),
                 builtins: ep.builtins.into_iter().map(|builtin| builtin + "_builtin").collect(),
+                required_gas: hints_by_pc
+                    .get(&ep.offset)
+                    .and_then(|hints| prelude_gas_check(hints)),
             })
         })
         .collect()
 }
+
+/// Returns the gas checked by the given hints of an entry point's first instruction, if they are
+/// those of a compiled entry point's prelude; i.e., a check that the cost of the entry point
+/// wrapper is at most the gas argument (the sixth cell below the frame pointer, followed by the
+/// system pointer and the calldata).
+fn prelude_gas_check(hints: &[Hint]) -> Option<u64> {
+    let gas_argument = ResOperand::Deref(CellRef { register: Register::FP, offset: -6 });
+    match hints {
+        [Hint::Core(CoreHintBase::Core(CoreHint::TestLessThanOrEqual {
+            lhs: ResOperand::Immediate(cost),
+            rhs,
+            ..
+        }))] if *rhs == gas_argument => cost.value.to_u64(),
+        _ => None,
+    }
+}
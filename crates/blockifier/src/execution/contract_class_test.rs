@@ -0,0 +1,129 @@
+use assert_matches::assert_matches;
+use cairo_lang_starknet::casm_contract_class::CasmContractClass;
+use num_bigint::BigUint;
+use pretty_assertions::assert_eq;
+use starknet_api::core::EntryPointSelector;
+use starknet_api::deprecated_contract_class::EntryPointType;
+
+use crate::abi::constants::CONSTRUCTOR_ENTRY_POINT_NAME;
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::execution::deprecated_execution::resolve_entry_point_pc;
+use crate::execution::entry_point::CallEntryPoint;
+use crate::execution::errors::PreExecutionError;
+use crate::selector;
+use crate::test_utils::{
+    get_raw_contract_class, trivial_external_entry_point, TEST_CONTRACT_CAIRO0_PATH,
+    TEST_CONTRACT_CAIRO1_PATH,
+};
+
+fn external_call(entry_point_selector: EntryPointSelector) -> CallEntryPoint {
+    CallEntryPoint { entry_point_selector, ..trivial_external_entry_point() }
+}
+
+/// Returns the Cairo 0 test contract, with the given entry points prepended to its external ones.
+fn cairo0_test_contract(extra_entry_points: &[serde_json::Value]) -> ContractClassV0 {
+    let mut raw_contract_class: serde_json::Value =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO0_PATH)).unwrap();
+    let external_entry_points =
+        raw_contract_class["entry_points_by_type"]["EXTERNAL"].as_array_mut().unwrap();
+    external_entry_points.splice(0..0, extra_entry_points.iter().cloned());
+    ContractClassV0::try_from_json_string(&raw_contract_class.to_string()).unwrap()
+}
+
+#[test]
+fn test_entry_points_are_indexed() {
+    let cairo0_contract_class = cairo0_test_contract(&[]);
+    for entry_point in &cairo0_contract_class.entry_points_by_type[&EntryPointType::External] {
+        assert_eq!(
+            resolve_entry_point_pc(&external_call(entry_point.selector), &cairo0_contract_class)
+                .unwrap(),
+            entry_point.offset.0
+        );
+    }
+
+    let cairo1_contract_class = ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH);
+    for entry_point in &cairo1_contract_class.entry_points_by_type[&EntryPointType::External] {
+        assert_eq!(
+            &cairo1_contract_class.get_entry_point(&external_call(entry_point.selector)).unwrap(),
+            entry_point
+        );
+    }
+
+    let missing_selector = selector!("missing_entry_point");
+    assert_matches!(
+        cairo1_contract_class.get_entry_point(&external_call(missing_selector)),
+        Err(PreExecutionError::EntryPointNotFound(selector)) if selector == missing_selector
+    );
+    assert_matches!(
+        resolve_entry_point_pc(&external_call(missing_selector), &cairo0_contract_class),
+        Err(PreExecutionError::EntryPointNotFound(selector)) if selector == missing_selector
+    );
+}
+
+#[test]
+fn test_default_entry_point() {
+    let default_offset = 0;
+    let contract_class = cairo0_test_contract(&[serde_json::json!({
+        "offset": default_offset,
+        "selector": "0x0",
+    })]);
+
+    let missing_selector = selector!("missing_entry_point");
+    assert_eq!(
+        resolve_entry_point_pc(&external_call(missing_selector), &contract_class).unwrap(),
+        default_offset
+    );
+}
+
+#[test]
+fn test_duplicated_selector() {
+    let selector = selector!("duplicated_entry_point");
+    let is_duplicated_selector_error = |error: PreExecutionError| {
+        assert_matches!(
+            error,
+            PreExecutionError::DuplicatedEntryPointSelector { selector: error_selector, typ }
+                if error_selector == selector && typ == EntryPointType::External
+        )
+    };
+
+    let entry_point = serde_json::json!({ "offset": 0, "selector": selector.0.to_string() });
+    let cairo0_contract_class = cairo0_test_contract(&[entry_point.clone(), entry_point]);
+    is_duplicated_selector_error(
+        resolve_entry_point_pc(&external_call(selector), &cairo0_contract_class).unwrap_err(),
+    );
+
+    let mut casm_contract_class: CasmContractClass =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO1_PATH)).unwrap();
+    let mut entry_point = casm_contract_class.entry_points_by_type.external[0].clone();
+    entry_point.selector = BigUint::from_bytes_be(selector.0.bytes());
+    casm_contract_class.entry_points_by_type.external.extend([entry_point.clone(), entry_point]);
+    let cairo1_contract_class = ContractClassV1::try_from(casm_contract_class).unwrap();
+    is_duplicated_selector_error(
+        cairo1_contract_class.get_entry_point(&external_call(selector)).unwrap_err(),
+    );
+
+    // Other selectors are unaffected.
+    let selector = selector!("test_storage_read_write");
+    assert_eq!(
+        cairo1_contract_class.get_entry_point(&external_call(selector)).unwrap().selector,
+        selector
+    );
+}
+
+#[test]
+fn test_constructor_selector() {
+    let contract_class = ContractClass::V0(cairo0_test_contract(&[]));
+    assert_eq!(
+        contract_class.constructor_selector(),
+        Some(selector!(CONSTRUCTOR_ENTRY_POINT_NAME))
+    );
+
+    // A class without the constructor entry point type has no constructor.
+    let mut raw_contract_class: serde_json::Value =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO0_PATH)).unwrap();
+    raw_contract_class["entry_points_by_type"].as_object_mut().unwrap().remove("CONSTRUCTOR");
+    let contract_class = ContractClass::V0(
+        ContractClassV0::try_from_json_string(&raw_contract_class.to_string()).unwrap(),
+    );
+    assert_eq!(contract_class.constructor_selector(), None);
+}
@@ -8,7 +8,6 @@ use starknet_api::core::EntryPointSelector;
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkHash;
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants::{CONSTRUCTOR_ENTRY_POINT_NAME, DEFAULT_ENTRY_POINT_SELECTOR};
 use crate::execution::contract_class::ContractClassV0;
 use crate::execution::deprecated_syscalls::hint_processor::DeprecatedSyscallHintProcessor;
@@ -17,11 +16,13 @@ use crate::execution::entry_point::{
     ExecutionResources,
 };
 use crate::execution::errors::{
-    PostExecutionError, PreExecutionError, VirtualMachineExecutionError,
+    EntryPointExecutionError, PostExecutionError, PreExecutionError, SecurityValidationError,
+    VirtualMachineExecutionError,
 };
 use crate::execution::execution_utils::{
-    read_execution_retdata, stark_felt_to_felt, Args, ReadOnlySegments,
+    get_n_memory_cells, read_execution_retdata, stark_felt_to_felt, Args, ReadOnlySegments,
 };
+use crate::selector;
 use crate::state::state_api::State;
 
 pub struct VmExecutionContext<'a> {
@@ -60,7 +61,11 @@ pub fn execute_entry_point_call(
     let previous_vm_resources = syscall_handler.resources.vm_resources.clone();
 
     // Execute.
-    run_entry_point(&mut vm, &mut runner, &mut syscall_handler, entry_point_pc, args)?;
+    run_entry_point(&mut vm, &mut runner, &mut syscall_handler, entry_point_pc, args).map_err(
+        |error| {
+            EntryPointExecutionError::from_vm_error(error, syscall_handler.syscall_error.take())
+        },
+    )?;
 
     Ok(finalize_execution(
         vm,
@@ -112,47 +117,32 @@ pub fn resolve_entry_point_pc(
     contract_class: &ContractClassV0,
 ) -> Result<usize, PreExecutionError> {
     if call.entry_point_type == EntryPointType::Constructor
-        && call.entry_point_selector != selector_from_name(CONSTRUCTOR_ENTRY_POINT_NAME)
+        && call.entry_point_selector != selector!(CONSTRUCTOR_ENTRY_POINT_NAME)
     {
         return Err(PreExecutionError::InvalidConstructorEntryPointName);
     }
 
     let entry_points_of_same_type = &contract_class.entry_points_by_type[&call.entry_point_type];
-    let filtered_entry_points: Vec<_> = entry_points_of_same_type
-        .iter()
-        .filter(|ep| ep.selector == call.entry_point_selector)
-        .collect();
+    let position = contract_class
+        .entry_point_index
+        .position(call.entry_point_type, call.entry_point_selector)?;
+    if let Some(position) = position {
+        return Ok(entry_points_of_same_type[position].offset.0);
+    }
 
     // Returns the default entrypoint if the given selector is missing.
-    if filtered_entry_points.is_empty() {
-        match entry_points_of_same_type.get(0) {
-            Some(entry_point) => {
-                if entry_point.selector
-                    == EntryPointSelector(StarkHash::from(DEFAULT_ENTRY_POINT_SELECTOR))
-                {
-                    return Ok(entry_point.offset.0);
-                } else {
-                    return Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector));
-                }
-            }
-            None => {
-                return Err(PreExecutionError::NoEntryPointOfTypeFound(call.entry_point_type));
+    match entry_points_of_same_type.get(0) {
+        Some(entry_point) => {
+            if entry_point.selector
+                == EntryPointSelector(StarkHash::from(DEFAULT_ENTRY_POINT_SELECTOR))
+            {
+                Ok(entry_point.offset.0)
+            } else {
+                Err(PreExecutionError::EntryPointNotFound(call.entry_point_selector))
             }
         }
+        None => Err(PreExecutionError::NoEntryPointOfTypeFound(call.entry_point_type)),
     }
-
-    if filtered_entry_points.len() > 1 {
-        return Err(PreExecutionError::DuplicatedEntryPointSelector {
-            selector: call.entry_point_selector,
-            typ: call.entry_point_type,
-        });
-    }
-
-    // Filtered entry points contain exactly one element.
-    let entry_point = filtered_entry_points
-        .get(0)
-        .expect("The number of entry points with the given selector is exactly one.");
-    Ok(entry_point.offset.0)
 }
 
 pub fn prepare_call_arguments(
@@ -242,9 +232,10 @@ pub fn finalize_execution(
         .map_err(VirtualMachineError::TracerError)?
         .filter_unused_builtins();
     syscall_handler.resources.vm_resources += &vm_resources_without_inner_calls;
+    syscall_handler.context.add_memory_cells(get_n_memory_cells(&vm))?;
 
     let full_call_vm_resources = &syscall_handler.resources.vm_resources - &previous_vm_resources;
-    Ok(CallInfo {
+    let mut call_info = CallInfo {
         call,
         execution: CallExecution {
             retdata: read_execution_retdata(&vm, retdata_size, &retdata_ptr)?,
@@ -255,9 +246,14 @@ pub fn finalize_execution(
         },
         vm_resources: full_call_vm_resources.filter_unused_builtins(),
         inner_calls: syscall_handler.inner_calls,
+        gas_metered: false,
         storage_read_values: syscall_handler.read_values,
         accessed_storage_keys: syscall_handler.accessed_keys,
-    })
+        syscall_counter: syscall_handler.syscall_counter,
+    };
+    call_info.shrink_to_fit();
+
+    Ok(call_info)
 }
 
 pub fn validate_run(
@@ -275,18 +271,14 @@ pub fn validate_run(
     // Subtract one to get to the first implicit arg segment (the syscall pointer).
     let implicit_args_start = (current_builtin_ptr - 1)?;
     if (implicit_args_start + implicit_args.len())? != implicit_args_end {
-        return Err(PostExecutionError::SecurityValidationError(
-            "Implicit arguments' segments".to_string(),
-        ));
+        return Err(SecurityValidationError::ImplicitArgumentsSegments.into());
     }
 
     // Validate syscall segment start.
     let syscall_start_ptr = implicit_args.first().expect("Implicit args must not be empty.");
     let syscall_start_ptr = Relocatable::try_from(syscall_start_ptr)?;
     if syscall_start_ptr.offset != 0 {
-        return Err(PostExecutionError::SecurityValidationError(
-            "Syscall segment start".to_string(),
-        ));
+        return Err(SecurityValidationError::SyscallSegmentStart.into());
     }
 
     // Validate syscall segment size.
@@ -295,15 +287,13 @@ pub fn validate_run(
         .get_segment_used_size(syscall_start_ptr.segment_index as usize)
         .expect("Segments must contain the syscall segment.");
     if (syscall_start_ptr + syscall_used_size)? != syscall_end_ptr {
-        return Err(PostExecutionError::SecurityValidationError(
-            "Syscall segment size".to_string(),
-        ));
+        return Err(SecurityValidationError::SyscallSegmentSize.into());
     }
 
     // Validate syscall segment end.
-    syscall_handler.verify_syscall_ptr(syscall_end_ptr).map_err(|_| {
-        PostExecutionError::SecurityValidationError("Syscall segment end".to_string())
-    })?;
+    syscall_handler
+        .verify_syscall_ptr(syscall_end_ptr)
+        .map_err(|error| SecurityValidationError::SyscallSegmentEnd(Box::new(error)))?;
 
     syscall_handler.read_only_segments.validate(vm)
 }
@@ -1,7 +1,15 @@
 use std::collections::{HashMap, HashSet};
 
+use assert_matches::assert_matches;
+use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData;
+use cairo_vm::hint_processor::hint_processor_definition::HintProcessorLogic;
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::exec_scope::ExecutionScopes;
+use cairo_vm::types::relocatable::Relocatable;
+use cairo_vm::vm::errors::hint_errors::HintError;
 use cairo_vm::vm::runners::builtin_runner::RANGE_CHECK_BUILTIN_NAME;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
+use cairo_vm::vm::vm_core::VirtualMachine;
 use pretty_assertions::assert_eq;
 use starknet_api::core::{calculate_contract_address, ClassHash, ContractAddress, PatriciaKey};
 use starknet_api::hash::{StarkFelt, StarkHash};
@@ -10,15 +18,27 @@ use starknet_api::transaction::{Calldata, ContractAddressSalt};
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
 use test_case::test_case;
 
-use crate::abi::abi_utils::selector_from_name;
-use crate::execution::entry_point::{CallEntryPoint, CallExecution, CallInfo, CallType, Retdata};
+use crate::abi::abi_utils::get_storage_var_address;
+use crate::block_context::BlockContext;
+use crate::execution::deprecated_syscalls::hint_processor::{
+    DeprecatedHint, DeprecatedSyscallHintProcessor,
+};
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::execution::entry_point::{
+    CallEntryPoint, CallExecution, CallInfo, CallType, EntryPointExecutionContext,
+    ExecutionResources, Retdata,
+};
+use crate::execution::errors::ExecutionErrorCategory;
+use crate::execution::hint_code::NORMALIZE_ADDRESS_SET_IS_250_HINT;
 use crate::retdata;
+use crate::selector;
 use crate::state::state_api::StateReader;
 use crate::test_utils::{
-    deprecated_create_deploy_test_state, deprecated_create_test_state,
-    trivial_external_entry_point, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
-    TEST_EMPTY_CONTRACT_CLASS_HASH,
+    assert_constructor_semantics, deprecated_create_deploy_test_state,
+    deprecated_create_test_state, trivial_external_entry_point, TEST_CLASS_HASH,
+    TEST_CONTRACT_ADDRESS, TEST_EMPTY_CONTRACT_CLASS_HASH,
 };
+use crate::transaction::objects::AccountTransactionContext;
 
 #[test]
 fn test_storage_read_write() {
@@ -28,7 +48,7 @@ fn test_storage_read_write() {
     let calldata = calldata![key, value];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_storage_read_write"),
+        entry_point_selector: selector!("test_storage_read_write"),
         ..trivial_external_entry_point()
     };
     let storage_address = entry_point_call.storage_address;
@@ -42,10 +62,26 @@ fn test_storage_read_write() {
     assert_eq!(value_from_state, value);
 }
 
+/// Keys at or above the bound of the OS fail the write.
+#[test_case("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00"; "bound")]
+#[test_case("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"; "max_key")]
+fn test_storage_write_out_of_range_key(key: &str) {
+    let mut state = deprecated_create_test_state();
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata![stark_felt!(key), stark_felt!(18_u8)],
+        entry_point_selector: selector!("test_storage_read_write"),
+        ..trivial_external_entry_point()
+    };
+
+    let error = entry_point_call.execute_directly(&mut state).unwrap_err();
+    assert_eq!(error.category(), ExecutionErrorCategory::Reverted);
+    assert!(error.to_string().contains("Storage key out of range."), "{error}");
+}
+
 #[test]
 fn test_library_call() {
     let mut state = deprecated_create_test_state();
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let calldata = calldata![
         stark_felt!(TEST_CLASS_HASH), // Class hash.
         inner_entry_point_selector.0, // Function selector.
@@ -54,7 +90,7 @@ fn test_library_call() {
         stark_felt!(91_u16)           // Calldata: value.
     ];
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_library_call"),
+        entry_point_selector: selector!("test_library_call"),
         calldata,
         class_hash: Some(class_hash!(TEST_CLASS_HASH)),
         ..trivial_external_entry_point()
@@ -69,8 +105,8 @@ fn test_library_call() {
 fn test_nested_library_call() {
     let mut state = deprecated_create_test_state();
     let (key, value) = (255_u64, 44_u64);
-    let outer_entry_point_selector = selector_from_name("test_library_call");
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let outer_entry_point_selector = selector!("test_library_call");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let main_entry_point_calldata = calldata![
         stark_felt!(TEST_CLASS_HASH), // Class hash.
         outer_entry_point_selector.0, // Library call function selector.
@@ -82,7 +118,7 @@ fn test_nested_library_call() {
 
     // Create expected call info tree.
     let main_entry_point = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_nested_library_call"),
+        entry_point_selector: selector!("test_nested_library_call"),
         calldata: main_entry_point_calldata,
         class_hash: Some(class_hash!(TEST_CLASS_HASH)),
         ..trivial_external_entry_point()
@@ -121,6 +157,10 @@ fn test_nested_library_call() {
         vm_resources: storage_entry_point_vm_resources.clone(),
         storage_read_values: vec![stark_felt!(0_u8), stark_felt!(value + 1)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key + 1))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 1),
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+        ]),
         ..Default::default()
     };
     let mut library_call_vm_resources = VmExecutionResources {
@@ -134,6 +174,7 @@ fn test_nested_library_call() {
         execution: CallExecution::from_retdata(retdata![stark_felt!(value + 1)]),
         vm_resources: library_call_vm_resources.clone(),
         inner_calls: vec![nested_storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 1)]),
         ..Default::default()
     };
     let storage_call_info = CallInfo {
@@ -142,6 +183,10 @@ fn test_nested_library_call() {
         vm_resources: storage_entry_point_vm_resources.clone(),
         storage_read_values: vec![stark_felt!(0_u8), stark_felt!(value)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 1),
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+        ]),
         ..Default::default()
     };
 
@@ -153,6 +198,7 @@ fn test_nested_library_call() {
         execution: CallExecution::from_retdata(retdata![stark_felt!(0_u8)]),
         vm_resources: main_call_vm_resources,
         inner_calls: vec![library_call_info, storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 2)]),
         ..Default::default()
     };
 
@@ -162,8 +208,8 @@ fn test_nested_library_call() {
 #[test]
 fn test_call_contract() {
     let mut state = deprecated_create_test_state();
-    let outer_entry_point_selector = selector_from_name("test_call_contract");
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let outer_entry_point_selector = selector!("test_call_contract");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let calldata = calldata![
         stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
         inner_entry_point_selector.0,       // Function selector.
@@ -190,7 +236,7 @@ fn test_replace_class() {
     let calldata = calldata![stark_felt!(1234_u16)];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_replace_class"),
+        entry_point_selector: selector!("test_replace_class"),
         ..trivial_external_entry_point()
     };
     let error = entry_point_call.execute_directly(&mut state).unwrap_err().to_string();
@@ -203,7 +249,7 @@ fn test_replace_class() {
     assert_eq!(state.get_class_hash_at(contract_address).unwrap(), old_class_hash);
     let entry_point_call = CallEntryPoint {
         calldata: calldata![new_class_hash.0],
-        entry_point_selector: selector_from_name("test_replace_class"),
+        entry_point_selector: selector!("test_replace_class"),
         ..trivial_external_entry_point()
     };
     entry_point_call.execute_directly(&mut state).unwrap();
@@ -290,6 +336,20 @@ fn test_replace_class() {
         "The deploy_from_zero field in the deploy system call must be 0 or 1.",
     ));
     "With constructor: Negative flow: illegal value for deploy_from_zero")]
+#[test_case(
+    class_hash!(TEST_CLASS_HASH),
+    calldata![
+        stark_felt!(TEST_CLASS_HASH), // Class hash.
+        ContractAddressSalt::default().0, // Contract_address_salt.
+        stark_felt!(1_u8), // Calldata length.
+        stark_felt!(1_u8), // Calldata: address.
+        stark_felt!(0_u8) // deploy_from_zero.
+    ],
+    calldata![
+        stark_felt!(1_u8) // Calldata: address.
+    ],
+    Some("An ASSERT_EQ instruction failed");
+    "With constructor: Negative flow: failing constructor")]
 fn test_deploy(
     class_hash: ClassHash,
     calldata: Calldata,
@@ -298,14 +358,16 @@ fn test_deploy(
 ) {
     let mut state = deprecated_create_deploy_test_state();
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_deploy"),
+        entry_point_selector: selector!("test_deploy"),
         calldata,
         ..trivial_external_entry_point()
     };
 
     if let Some(expected_error) = expected_error {
-        let error = entry_point_call.execute_directly(&mut state).unwrap_err().to_string();
-        assert!(error.contains(expected_error));
+        let error = entry_point_call.execute_directly(&mut state).unwrap_err();
+        assert!(error.to_string().contains(expected_error), "{error}");
+        // The deploying call fails; its transaction is reverted, rather than rejected.
+        assert_eq!(error.category(), ExecutionErrorCategory::Reverted);
         return;
     }
 
@@ -317,9 +379,94 @@ fn test_deploy(
         contract_address!(TEST_CONTRACT_ADDRESS),
     )
     .unwrap();
+    let call_info = entry_point_call.execute_directly(&mut state).unwrap();
     assert_eq!(
-        entry_point_call.execute_directly(&mut state).unwrap().execution,
+        call_info.execution,
         CallExecution::from_retdata(retdata![*contract_address.0.key()])
     );
+    assert_constructor_semantics(
+        &call_info.inner_calls[0],
+        contract_address,
+        contract_address!(TEST_CONTRACT_ADDRESS),
+        class_hash,
+    );
     assert_eq!(state.get_class_hash_at(contract_address).unwrap(), class_hash);
 }
+
+#[test]
+fn test_hint_dispatch() {
+    let mut state = deprecated_create_test_state();
+    let mut resources = ExecutionResources::default();
+    let mut context = EntryPointExecutionContext::new_invoke(
+        &BlockContext::create_for_testing(),
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let mut syscall_handler = DeprecatedSyscallHintProcessor::new(
+        &mut state,
+        &mut resources,
+        &mut context,
+        Relocatable::from((0, 0)),
+        contract_address,
+        contract_address,
+    );
+    let compile_hint = |hint_code: &str| {
+        syscall_handler
+            .compile_hint(hint_code, &ApTracking::default(), &HashMap::new(), &[])
+            .unwrap()
+    };
+    // Returns the kind of the compiled hint, along with its code.
+    let compiled_hint_kind =
+        |hint_code: &str| match compile_hint(hint_code).downcast_ref::<DeprecatedHint>().unwrap() {
+            DeprecatedHint::Syscall(hint) => ("syscall", hint.code.clone()),
+            DeprecatedHint::Common(_, hint) => ("common", hint.code.clone()),
+            DeprecatedHint::Builtin(hint_data) => {
+                ("builtin", hint_data.downcast_ref::<HintProcessorData>().unwrap().code.clone())
+            }
+        };
+
+    // Syscall and common hints are resolved when compiled; the others are left to the builtin
+    // hint processor.
+    let custom_hint = "ids.x = custom_hint()";
+    for (hint_code, expected_kind) in [
+        (
+            "syscall_handler.storage_write(segments=segments, syscall_ptr=ids.syscall_ptr)",
+            "syscall",
+        ),
+        (NORMALIZE_ADDRESS_SET_IS_250_HINT, "common"),
+        ("memory[ap] = segments.add()", "builtin"),
+        (custom_hint, "builtin"),
+    ] {
+        assert_eq!(compiled_hint_kind(hint_code), (expected_kind, hint_code.to_string()));
+    }
+
+    // Unknown hints are rejected by the builtin hint processor, as before.
+    let custom_hint_data = compile_hint(custom_hint);
+    assert_matches!(
+        syscall_handler.execute_hint(
+            &mut VirtualMachine::new(false),
+            &mut ExecutionScopes::new(),
+            &custom_hint_data,
+            &HashMap::new(),
+        ),
+        Err(HintError::UnknownHint(hint_code)) if hint_code.as_ref() == custom_hint
+    );
+
+    // A run dispatching all kinds: the storage variable address is normalized by the common
+    // hints, and written and read by syscalls.
+    let mut state = deprecated_create_test_state();
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("test_storage_var"),
+        ..trivial_external_entry_point()
+    };
+    assert_eq!(
+        entry_point_call.execute_directly(&mut state).unwrap().execution,
+        CallExecution::default()
+    );
+    let storage_var_key = get_storage_var_address("number_map", &[stark_felt!(1_u8)]).unwrap();
+    assert_eq!(
+        state.get_storage_at(contract_address, storage_var_key).unwrap(),
+        stark_felt!(39_u8)
+    );
+}
@@ -25,7 +25,9 @@ use starknet_api::StarknetApiError;
 use thiserror::Error;
 
 use crate::abi::constants;
-use crate::execution::common_hints::{extended_builtin_hint_processor, HintExecutionResult};
+use crate::execution::common_hints::{
+    extended_builtin_hint_processor, HintExecutionResult, HintFunction, COMMON_HINTS,
+};
 use crate::execution::deprecated_syscalls::{
     call_contract, delegate_call, delegate_l1_handler, deploy, emit_event, get_block_number,
     get_block_timestamp, get_caller_address, get_contract_address, get_sequencer_address,
@@ -38,7 +40,7 @@ use crate::execution::entry_point::{
     CallEntryPoint, CallInfo, CallType, EntryPointExecutionContext, ExecutionResources,
     OrderedEvent, OrderedL2ToL1Message,
 };
-use crate::execution::errors::EntryPointExecutionError;
+use crate::execution::errors::{EntryPointExecutionError, SyscallHintError};
 use crate::execution::execution_utils::{
     felt_range_from_ptr, stark_felt_from_ptr, stark_felt_to_felt, ReadOnlySegment, ReadOnlySegments,
 };
@@ -58,6 +60,8 @@ pub enum DeprecatedSyscallExecutionError {
     InvalidSyscallInput { input: StarkFelt, info: String },
     #[error("Invalid syscall selector: {0:?}.")]
     InvalidDeprecatedSyscallSelector(StarkFelt),
+    #[error("Exceeded the maximal number of {limit_name} per transaction.")]
+    TransactionLimitExceeded { limit_name: &'static str },
     #[error(transparent)]
     MathError(#[from] cairo_vm::types::errors::math_errors::MathError),
     #[error(transparent)]
@@ -68,14 +72,10 @@ pub enum DeprecatedSyscallExecutionError {
     StateError(#[from] StateError),
     #[error(transparent)]
     VirtualMachineError(#[from] VirtualMachineError),
-}
-
-// Needed for custom hint implementations (in our case, syscall hints) which must comply with the
-// cairo-rs API.
-impl From<DeprecatedSyscallExecutionError> for HintError {
-    fn from(error: DeprecatedSyscallExecutionError) -> Self {
-        HintError::CustomHint(error.to_string().into())
-    }
+    /// An error of the VM API (e.g., of reading the hint's operands); crosses the hint boundary as
+    /// is.
+    #[error(transparent)]
+    HintError(#[from] HintError),
 }
 
 /// Executes StarkNet syscalls (stateful protocol hints) during the execution of an entry point
@@ -101,6 +101,10 @@ pub struct DeprecatedSyscallHintProcessor<'a> {
     // Additional information gathered during execution.
     pub read_values: Vec<StarkFelt>,
     pub accessed_keys: HashSet<StorageKey>,
+    /// The syscalls invoked by the current execution (not including inner calls).
+    pub syscall_counter: SyscallCounter,
+    /// The error of the syscall which failed the run, if any (see `SyscallHintError`).
+    pub syscall_error: Option<SyscallHintError>,
 
     // Additional fields.
     // Invariant: must only contain allowed hints.
@@ -132,6 +136,8 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
             syscall_ptr: initial_syscall_ptr,
             read_values: vec![],
             accessed_keys: HashSet::new(),
+            syscall_counter: SyscallCounter::new(),
+            syscall_error: None,
             builtin_hint_processor: extended_builtin_hint_processor(),
             tx_signature_start_ptr: None,
             tx_info_start_ptr: None,
@@ -156,11 +162,18 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         vm: &mut VirtualMachine,
         ids_data: &HashMap<String, HintReference>,
         ap_tracking: &ApTracking,
-    ) -> HintExecutionResult {
+    ) -> DeprecatedSyscallResult<()> {
         let initial_syscall_ptr = get_ptr_from_var_name("syscall_ptr", vm, ids_data, ap_tracking)?;
         self.verify_syscall_ptr(initial_syscall_ptr)?;
 
-        let selector = DeprecatedSyscallSelector::try_from(self.read_next_syscall_selector(vm)?)?;
+        let raw_selector = self.read_next_syscall_selector(vm)?;
+        let selector = DeprecatedSyscallSelector::try_from(raw_selector)?;
+        if !self.context.block_context.versioned_constants.is_syscall_allowed(selector) {
+            return Err(DeprecatedSyscallExecutionError::InvalidDeprecatedSyscallSelector(
+                raw_selector,
+            ));
+        }
+        tracing::debug!(?selector, "Executing syscall.");
         self.increment_syscall_count(&selector);
 
         match selector {
@@ -198,8 +211,21 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
             DeprecatedSyscallSelector::StorageWrite => self.execute_syscall(vm, storage_write),
             _ => Err(HintError::UnknownHint(
                 format!("Unsupported syscall selector {selector:?}.").into(),
-            )),
+            )
+            .into()),
+        }
+    }
+
+    /// Returns the error of a syscall as a hint error, to cross the hint boundary; keeps the
+    /// syscall error in `syscall_error`, as the hint error only holds its message.
+    fn stash_syscall_error(&mut self, error: DeprecatedSyscallExecutionError) -> HintError {
+        if let DeprecatedSyscallExecutionError::HintError(error) = error {
+            return error;
         }
+
+        let hint_error = HintError::CustomHint(error.to_string().into());
+        self.syscall_error = Some(error.into());
+        hint_error
     }
 
     pub fn get_or_allocate_tx_signature_segment(
@@ -234,7 +260,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         &mut self,
         vm: &mut VirtualMachine,
         execute_callback: ExecuteCallback,
-    ) -> HintExecutionResult
+    ) -> DeprecatedSyscallResult<()>
     where
         Request: SyscallRequest,
         Response: SyscallResponse,
@@ -264,6 +290,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
     fn increment_syscall_count(&mut self, selector: &DeprecatedSyscallSelector) {
         let syscall_count = self.resources.syscall_counter.entry(*selector).or_default();
         *syscall_count += 1;
+        *self.syscall_counter.entry(*selector).or_default() += 1;
     }
 
     fn allocate_tx_signature_segment(
@@ -292,7 +319,7 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
             tx_signature_length.into(),
             tx_signature_start_ptr.into(),
             stark_felt_to_felt(account_tx_context.transaction_hash.0).into(),
-            Felt252::from_bytes_be(self.context.block_context.chain_id.0.as_bytes()).into(),
+            stark_felt_to_felt(self.context.block_context.chain_id.to_felt()).into(),
             stark_felt_to_felt(account_tx_context.nonce.0).into(),
         ];
 
@@ -316,6 +343,14 @@ impl<'a> DeprecatedSyscallHintProcessor<'a> {
         key: StorageKey,
         value: StarkFelt,
     ) -> DeprecatedSyscallResult<StorageWriteResponse> {
+        let storage_key = (self.storage_address, key);
+        if self.context.exceeds_max_n_storage_writes(&storage_key) {
+            return Err(DeprecatedSyscallExecutionError::TransactionLimitExceeded {
+                limit_name: "storage writes",
+            });
+        }
+        self.context.written_storage_keys.insert(storage_key);
+
         self.accessed_keys.insert(key);
         self.state.set_storage_at(self.storage_address, key, value);
 
@@ -341,6 +376,16 @@ impl ResourceTracker for DeprecatedSyscallHintProcessor<'_> {
     }
 }
 
+/// A hint of a Cairo 0 program, resolved when the hints of the program are compiled (before the
+/// run); executing it requires no lookup by its code.
+pub(crate) enum DeprecatedHint {
+    Syscall(HintProcessorData),
+    Common(HintFunction, HintProcessorData),
+    /// Executed by the (extended) builtin hint processor, by its code; holds the hint in the form
+    /// the processor expects.
+    Builtin(Box<dyn Any>),
+}
+
 impl HintProcessorLogic for DeprecatedSyscallHintProcessor<'_> {
     fn execute_hint(
         &mut self,
@@ -349,12 +394,53 @@ impl HintProcessorLogic for DeprecatedSyscallHintProcessor<'_> {
         hint_data: &Box<dyn Any>,
         constants: &HashMap<String, Felt252>,
     ) -> HintExecutionResult {
-        let hint = hint_data.downcast_ref::<HintProcessorData>().ok_or(HintError::WrongHintData)?;
-        if hint_code::SYSCALL_HINTS.contains(hint.code.as_str()) {
-            return self.execute_next_syscall(vm, &hint.ids_data, &hint.ap_tracking);
+        if self.context.is_deadline_exceeded() {
+            return Err(HintError::CustomHint(
+                EntryPointExecutionError::ExecutionDeadlineExceeded.to_string().into(),
+            ));
         }
+        match hint_data.downcast_ref::<DeprecatedHint>().ok_or(HintError::WrongHintData)? {
+            DeprecatedHint::Syscall(hint) => self
+                .execute_next_syscall(vm, &hint.ids_data, &hint.ap_tracking)
+                .map_err(|error| self.stash_syscall_error(error)),
+            DeprecatedHint::Common(hint_function, hint) => {
+                hint_function(vm, exec_scopes, &hint.ids_data, &hint.ap_tracking, constants)
+            }
+            DeprecatedHint::Builtin(hint_data) => {
+                self.builtin_hint_processor.execute_hint(vm, exec_scopes, hint_data, constants)
+            }
+        }
+    }
 
-        self.builtin_hint_processor.execute_hint(vm, exec_scopes, hint_data, constants)
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        let hint_data = self.builtin_hint_processor.compile_hint(
+            hint_code,
+            ap_tracking_data,
+            reference_ids,
+            references,
+        )?;
+        let into_hint = |hint_data: Box<dyn Any>| {
+            *hint_data
+                .downcast::<HintProcessorData>()
+                .expect("The builtin hint processor compiles hints into `HintProcessorData`.")
+        };
+        let hint = if hint_code::SYSCALL_HINTS.contains(hint_code) {
+            DeprecatedHint::Syscall(into_hint(hint_data))
+        } else if let Some(&(_, hint_function)) =
+            COMMON_HINTS.iter().find(|(common_hint_code, _)| *common_hint_code == hint_code)
+        {
+            DeprecatedHint::Common(hint_function, into_hint(hint_data))
+        } else {
+            DeprecatedHint::Builtin(hint_data)
+        };
+
+        Ok(Box::new(hint))
     }
 }
 
@@ -1,7 +1,7 @@
 use cairo_felt::Felt252;
 use cairo_vm::types::relocatable::Relocatable;
 use cairo_vm::vm::vm_core::VirtualMachine;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::{
     calculate_contract_address, ClassHash, ContractAddress, EntryPointSelector,
@@ -23,8 +23,8 @@ use crate::execution::entry_point::{
     CallEntryPoint, CallType, ConstructorContext, MessageToL1, OrderedEvent, OrderedL2ToL1Message,
 };
 use crate::execution::execution_utils::{
-    execute_deployment, stark_felt_from_ptr, write_maybe_relocatable, write_stark_felt,
-    ReadOnlySegment,
+    execute_deployment, is_valid_storage_key, stark_felt_from_ptr, write_maybe_relocatable,
+    write_stark_felt, ReadOnlySegment,
 };
 
 #[cfg(test)]
@@ -35,7 +35,9 @@ pub mod hint_processor;
 pub type DeprecatedSyscallResult<T> = Result<T, DeprecatedSyscallExecutionError>;
 pub type WriteResponseResult = DeprecatedSyscallResult<()>;
 
-#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq)]
+#[derive(
+    Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
 pub enum DeprecatedSyscallSelector {
     CallContract,
     DelegateCall,
@@ -46,6 +48,7 @@ pub enum DeprecatedSyscallSelector {
     GetBlockNumber,
     GetBlockTimestamp,
     GetCallerAddress,
+    GetClassHashAt,
     GetContractAddress,
     GetExecutionInfo,
     GetSequencerAddress,
@@ -82,6 +85,7 @@ impl TryFrom<StarkFelt> for DeprecatedSyscallSelector {
             b"GetBlockNumber" => Ok(Self::GetBlockNumber),
             b"GetBlockTimestamp" => Ok(Self::GetBlockTimestamp),
             b"GetCallerAddress" => Ok(Self::GetCallerAddress),
+            b"GetClassHashAt" => Ok(Self::GetClassHashAt),
             b"GetContractAddress" => Ok(Self::GetContractAddress),
             b"GetExecutionInfo" => Ok(Self::GetExecutionInfo),
             b"GetSequencerAddress" => Ok(Self::GetSequencerAddress),
@@ -351,6 +355,11 @@ pub fn emit_event(
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<EmitEventResponse> {
     let execution_context = &mut syscall_handler.context;
+    if execution_context.reached_max_n_events() {
+        return Err(DeprecatedSyscallExecutionError::TransactionLimitExceeded {
+            limit_name: "events",
+        });
+    }
     let ordered_event =
         OrderedEvent { order: execution_context.n_emitted_events, event: request.content };
     syscall_handler.events.push(ordered_event);
@@ -456,9 +465,7 @@ pub fn get_sequencer_address(
     _vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<GetSequencerAddressResponse> {
-    Ok(GetSequencerAddressResponse {
-        address: syscall_handler.context.block_context.sequencer_address,
-    })
+    Ok(GetSequencerAddressResponse { address: syscall_handler.context.sequencer_address() })
 }
 
 // GetTxInfo syscall.
@@ -625,6 +632,11 @@ pub fn send_message_to_l1(
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<SendMessageToL1Response> {
     let execution_context = &mut syscall_handler.context;
+    if execution_context.reached_max_n_l2_to_l1_messages() {
+        return Err(DeprecatedSyscallExecutionError::TransactionLimitExceeded {
+            limit_name: "L2-to-L1 messages",
+        });
+    }
     let ordered_message_to_l1 = OrderedL2ToL1Message {
         order: execution_context.n_sent_messages_to_l1,
         message: request.message,
@@ -669,6 +681,12 @@ pub fn storage_read(
     _vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<StorageReadResponse> {
+    if !is_valid_storage_key(request.address) {
+        return Err(DeprecatedSyscallExecutionError::InvalidSyscallInput {
+            input: *request.address.0.key(),
+            info: String::from("Storage key out of range."),
+        });
+    }
     syscall_handler.get_contract_storage_at(request.address)
 }
 
@@ -698,6 +716,12 @@ pub fn storage_write(
     _vm: &mut VirtualMachine,
     syscall_handler: &mut DeprecatedSyscallHintProcessor<'_>,
 ) -> DeprecatedSyscallResult<StorageWriteResponse> {
+    if !is_valid_storage_key(request.address) {
+        return Err(DeprecatedSyscallExecutionError::InvalidSyscallInput {
+            input: *request.address.0.key(),
+            info: String::from("Storage key out of range."),
+        });
+    }
     // Read the value before the write operation in order to log it in the list of read·
     // values. This is needed to correctly build the `DictAccess` entry corresponding to·
     // `storage_write` syscall in the OS.
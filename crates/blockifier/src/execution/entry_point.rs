@@ -1,23 +1,33 @@
 use std::cmp::min;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem::size_of;
+use std::time::Duration;
 
 use cairo_vm::vm::runners::cairo_runner::{
     ExecutionResources as VmExecutionResources, ResourceTracker, RunResources,
 };
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
-    Calldata, EthAddress, EventContent, Fee, L2ToL1Payload, TransactionVersion,
+    Calldata, EthAddress, EventContent, L2ToL1Payload, TransactionVersion,
 };
+use tracing::{debug_span, field};
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants;
 use crate::block_context::BlockContext;
 use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
-use crate::execution::errors::{EntryPointExecutionError, PreExecutionError};
-use crate::execution::execution_utils::execute_entry_point_call;
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::execution::errors::{
+    format_revert_reason, EntryPointExecutionError, PostExecutionError, PreExecutionError,
+};
+use crate::execution::execution_utils::{execute_entry_point_call, felt_to_decimal_string};
+use crate::selector;
+use crate::state::cached_state::ContractStorageKey;
 use crate::state::state_api::State;
 use crate::transaction::errors::TransactionExecutionError;
 use crate::transaction::objects::{AccountTransactionContext, TransactionExecutionResult};
@@ -32,14 +42,14 @@ pub const FAULTY_CLASS_HASH: &str =
 pub type EntryPointExecutionResult<T> = Result<T, EntryPointExecutionError>;
 
 /// Represents a the type of the call (used for debugging).
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CallType {
     #[default]
     Call = 0,
     Delegate = 1,
 }
 /// Represents a call to an entry point of a StarkNet contract.
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallEntryPoint {
     // The class hash is not given if it can be deduced from the storage address.
     pub class_hash: Option<ClassHash>,
@@ -81,12 +91,22 @@ pub struct EntryPointExecutionContext {
     pub n_emitted_events: usize,
     /// Used for tracking L2-to-L1 messages order during the current execution.
     pub n_sent_messages_to_l1: usize,
+    /// The storage cells written during the current execution; used for limiting their number.
+    pub written_storage_keys: HashSet<ContractStorageKey>,
+    /// The Cairo VM memory cells (including memory holes) of the calls of the current execution;
+    /// used for limiting their number.
+    pub n_memory_cells: usize,
     /// Used to track error stack for call chain.
     pub error_stack: Vec<(ContractAddress, String)>,
+    /// Execution is aborted once the block context clock passes this time.
+    pub deadline: Option<Duration>,
 
     current_recursion_depth: usize,
     // Maximum depth is limited by the stack size, which is configured at `.cargo/config.toml`.
     max_recursion_depth: usize,
+    // L1 handler entry points may only be called while executing an L1 handler transaction.
+    is_l1_handler: bool,
+    is_validate: bool,
 }
 impl EntryPointExecutionContext {
     pub fn new(
@@ -98,10 +118,17 @@ impl EntryPointExecutionContext {
             vm_run_resources: RunResources::new(max_n_steps),
             n_emitted_events: 0,
             n_sent_messages_to_l1: 0,
+            written_storage_keys: HashSet::new(),
+            n_memory_cells: 0,
             error_stack: vec![],
+            deadline: block_context
+                .max_execution_time
+                .map(|max_execution_time| block_context.clock.now() + max_execution_time),
             account_tx_context,
             current_recursion_depth: 0,
-            max_recursion_depth: block_context.max_recursion_depth,
+            max_recursion_depth: block_context.versioned_constants.max_recursion_depth,
+            is_l1_handler: false,
+            is_validate: false,
             block_context,
         }
     }
@@ -110,22 +137,32 @@ impl EntryPointExecutionContext {
         block_context: &BlockContext,
         account_tx_context: &AccountTransactionContext,
     ) -> Self {
-        Self::new(
-            block_context.clone(),
-            account_tx_context.clone(),
-            block_context.validate_max_n_steps as usize,
-        )
+        Self {
+            is_validate: true,
+            ..Self::new(
+                block_context.clone(),
+                account_tx_context.clone(),
+                block_context.versioned_constants.validate_max_n_steps as usize,
+            )
+        }
     }
 
     pub fn new_invoke(
         block_context: &BlockContext,
         account_tx_context: &AccountTransactionContext,
-    ) -> Self {
-        Self::new(
+    ) -> TransactionExecutionResult<Self> {
+        Ok(Self::new(
             block_context.clone(),
             account_tx_context.clone(),
-            Self::max_invoke_steps(block_context, account_tx_context),
-        )
+            Self::max_invoke_steps(block_context, account_tx_context)?,
+        ))
+    }
+
+    pub fn new_l1_handler(
+        block_context: &BlockContext,
+        account_tx_context: &AccountTransactionContext,
+    ) -> TransactionExecutionResult<Self> {
+        Ok(Self { is_l1_handler: true, ..Self::new_invoke(block_context, account_tx_context)? })
     }
 
     /// Returns the maximum number of cairo steps allowed, given the max fee and gas price.
@@ -133,21 +170,37 @@ impl EntryPointExecutionContext {
     pub fn max_invoke_steps(
         block_context: &BlockContext,
         account_tx_context: &AccountTransactionContext,
-    ) -> usize {
-        if account_tx_context.max_fee == Fee(0) {
-            min(constants::MAX_STEPS_PER_TX, block_context.invoke_tx_max_n_steps as usize)
-        } else {
-            let gas_per_step = block_context
-                .vm_resource_fee_cost
-                .get(constants::N_STEPS_RESOURCE)
-                .unwrap_or_else(|| {
-                    panic!("{} must appear in `vm_resource_fee_cost`.", constants::N_STEPS_RESOURCE)
-                });
-            let max_gas = account_tx_context.max_fee.0 / block_context.gas_price;
-            ((max_gas as f64 / gas_per_step).floor() as usize)
-                .min(constants::MAX_STEPS_PER_TX)
-                .min(block_context.invoke_tx_max_n_steps as usize)
+    ) -> TransactionExecutionResult<usize> {
+        let gas_price =
+            block_context.gas_prices.get_gas_price_by_fee_type(&account_tx_context.fee_type());
+        // A zero gas price (e.g., on devnets) cannot bound the number of steps.
+        if !account_tx_context.enforce_fee(block_context) || gas_price == 0 {
+            return Ok(min(
+                constants::MAX_STEPS_PER_TX,
+                block_context.versioned_constants.invoke_tx_max_n_steps as usize,
+            ));
         }
+
+        let gas_per_step = block_context
+            .versioned_constants
+            .vm_resource_fee_cost
+            .get(constants::N_STEPS_RESOURCE)
+            .ok_or(TransactionExecutionError::CairoResourcesNotContainedInFeeCosts)?;
+        let max_gas = account_tx_context.max_fee.0 / gas_price;
+        Ok(((max_gas as f64 / gas_per_step).floor() as usize)
+            .min(constants::MAX_STEPS_PER_TX)
+            .min(block_context.versioned_constants.invoke_tx_max_n_steps as usize))
+    }
+
+    /// Returns the sequencer address exposed to contracts; zero during validation, which must not
+    /// depend on the sequencer. Also zero, by the block context, on networks without a sequencer
+    /// (see `ZeroSequencerFeePolicy`).
+    pub fn sequencer_address(&self) -> ContractAddress {
+        if self.is_validate {
+            return ContractAddress::default();
+        }
+
+        self.block_context.sequencer_address
     }
 
     /// Subtracts the given number of steps from the currently available run resources.
@@ -160,36 +213,90 @@ impl EntryPointExecutionContext {
         self.vm_run_resources = RunResources::new(current_n_steps - steps_to_subtract);
     }
 
-    /// Combines individual errors into a single stack trace string, with contract addresses printed
-    /// alongside their respective trace.
-    pub fn error_trace(&self) -> String {
-        self.error_stack
-            .iter()
-            .rev()
-            .map(|(contract_address, trace_string)| {
-                format!(
-                    "Error in the called contract ({}):\n{}",
-                    contract_address.0.key(),
-                    trace_string
-                )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+    /// Returns whether the execution already emitted the maximal number of events of the version.
+    pub fn reached_max_n_events(&self) -> bool {
+        is_limit_reached(self.n_emitted_events, self.block_context.versioned_constants.max_n_events)
+    }
+
+    /// Returns whether the execution already sent the maximal number of L2-to-L1 messages of the
+    /// version.
+    pub fn reached_max_n_l2_to_l1_messages(&self) -> bool {
+        is_limit_reached(
+            self.n_sent_messages_to_l1,
+            self.block_context.versioned_constants.max_n_l2_to_l1_messages,
+        )
+    }
+
+    /// Returns whether writing the given storage cell exceeds the maximal number of distinct cells
+    /// the version allows the execution to write; i.e., the cell is new, and the maximum was
+    /// reached.
+    pub fn exceeds_max_n_storage_writes(&self, storage_key: &ContractStorageKey) -> bool {
+        !self.written_storage_keys.contains(storage_key)
+            && is_limit_reached(
+                self.written_storage_keys.len(),
+                self.block_context.versioned_constants.max_n_storage_writes,
+            )
+    }
+
+    /// Adds the memory cells of a call to the ones of the execution; fails if they exceed the
+    /// maximum of the version.
+    pub fn add_memory_cells(&mut self, n_memory_cells: usize) -> Result<(), PostExecutionError> {
+        self.n_memory_cells += n_memory_cells;
+        match self.block_context.versioned_constants.max_n_memory_cells {
+            Some(max_n_memory_cells) if self.n_memory_cells > max_n_memory_cells => {
+                Err(PostExecutionError::MemoryLimitExceeded {
+                    n_memory_cells: self.n_memory_cells,
+                    max_n_memory_cells,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the execution deadline has passed.
+    pub fn is_deadline_exceeded(&self) -> bool {
+        self.deadline.is_some_and(|deadline| self.block_context.clock.now() >= deadline)
+    }
+
+    /// Returns the revert reason of the execution, which failed with the given error (see
+    /// `format_revert_reason`).
+    pub fn revert_reason(&self, error: &dyn fmt::Display) -> String {
+        format_revert_reason(&self.error_stack, error)
     }
 }
 
+// Whether the given count reached the (optional) limit.
+fn is_limit_reached(count: usize, limit: Option<usize>) -> bool {
+    limit.is_some_and(|limit| count >= limit)
+}
+
 impl CallEntryPoint {
+    /// Executes the call under an `entry_point` span, which records the class hash once resolved,
+    /// and the steps of the call (including its inner calls) once executed.
     pub fn execute(
         mut self,
         state: &mut dyn State,
         resources: &mut ExecutionResources,
         context: &mut EntryPointExecutionContext,
     ) -> EntryPointExecutionResult<CallInfo> {
+        let span = debug_span!(
+            "entry_point",
+            storage_address = %self.storage_address.0.key(),
+            entry_point_selector = %self.entry_point_selector.0,
+            entry_point_type = ?self.entry_point_type,
+            class_hash = field::Empty,
+            n_steps = field::Empty,
+        );
+        let _span = span.enter();
         context.current_recursion_depth += 1;
         if context.current_recursion_depth > context.max_recursion_depth {
             return Err(EntryPointExecutionError::RecursionDepthExceeded);
         }
 
+        if self.entry_point_type == EntryPointType::L1Handler && !context.is_l1_handler {
+            return Err(PreExecutionError::InvalidL1HandlerCall(self.entry_point_selector).into());
+        }
+
         // Validate contract is deployed.
         let storage_address = self.storage_address;
         let storage_class_hash = state.get_class_hash_at(self.storage_address)?;
@@ -212,11 +319,23 @@ impl CallEntryPoint {
         }
         // Add class hash to the call, that will appear in the output (call info).
         self.class_hash = Some(class_hash);
+        span.record("class_hash", field::display(class_hash.0));
         let contract_class = state.get_compiled_contract_class(&class_hash)?;
 
+        let initial_n_steps = resources.vm_resources.n_steps;
         let result = execute_entry_point_call(self, contract_class, state, resources, context)
             .map_err(|error| {
+                if context.is_deadline_exceeded() {
+                    // The execution was aborted, not failed.
+                    return EntryPointExecutionError::ExecutionDeadlineExceeded;
+                }
+
                 match error {
+                    // A Cairo 1 failure is a frame of the stack trace, with its panic data.
+                    EntryPointExecutionError::ExecutionFailed { .. } => {
+                        context.error_stack.push((storage_address, error.to_string()));
+                        error
+                    }
                     // On VM error, pack the stack trace into the propagated error.
                     EntryPointExecutionError::VirtualMachineExecutionError(error) => {
                         context.error_stack.push((storage_address, error.try_to_vm_trace()));
@@ -226,20 +345,21 @@ impl CallEntryPoint {
                         // with the stringified trace of all errors below
                         // it.
                         EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace {
-                            trace: context.error_trace(),
+                            trace: context.revert_reason(&error),
                             source: error,
                         }
                     }
                     other_error => other_error,
                 }
             });
+        span.record("n_steps", resources.vm_resources.n_steps.saturating_sub(initial_n_steps));
 
         context.current_recursion_depth -= 1;
         result
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Retdata(pub Vec<StarkFelt>);
 
 #[macro_export]
@@ -249,24 +369,39 @@ macro_rules! retdata {
     };
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct OrderedEvent {
     pub order: usize,
     pub event: EventContent,
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct MessageToL1 {
     pub to_address: EthAddress,
     pub payload: L2ToL1Payload,
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct OrderedL2ToL1Message {
     pub order: usize,
     pub message: MessageToL1,
 }
-#[derive(Debug, Default, Eq, PartialEq)]
+
+/// An event, tagged with the address of the contract which emitted it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventWithAddress {
+    pub from_address: ContractAddress,
+    pub content: EventContent,
+}
+
+/// An L2 to L1 message, tagged with the address of the contract which sent it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MessageToL1WithAddress {
+    pub from_address: ContractAddress,
+    pub message: MessageToL1,
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallExecution {
     pub retdata: Retdata,
     pub events: Vec<OrderedEvent>,
@@ -275,19 +410,152 @@ pub struct CallExecution {
     pub gas_consumed: u64,
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CallInfo {
     pub call: CallEntryPoint,
     pub execution: CallExecution,
+    #[serde(with = "VmExecutionResourcesDef")]
     pub vm_resources: VmExecutionResources,
     pub inner_calls: Vec<CallInfo>,
+    // Whether the call ran Cairo 1 code, which reports its consumed (Sierra) gas; Cairo 0 calls
+    // are not gas-metered.
+    pub gas_metered: bool,
 
     // Additional information gathered during execution.
     pub storage_read_values: Vec<StarkFelt>,
     pub accessed_storage_keys: HashSet<StorageKey>,
+    // The syscalls invoked by the call itself (not by its inner calls); the OS resources they
+    // require are charged by the transaction.
+    pub syscall_counter: SyscallCounter,
 }
 
 impl CallInfo {
+    /// Releases the spare capacity of the collections gathered during the call, which grow as the
+    /// call runs; the info outlives the call, as part of the trace of the block.
+    /// Inner calls are expected to be shrunk already (when they are built).
+    pub fn shrink_to_fit(&mut self) {
+        self.execution.retdata.0.shrink_to_fit();
+        self.execution.events.shrink_to_fit();
+        self.execution.l2_to_l1_messages.shrink_to_fit();
+        self.inner_calls.shrink_to_fit();
+        self.storage_read_values.shrink_to_fit();
+        self.accessed_storage_keys.shrink_to_fit();
+        self.syscall_counter.shrink_to_fit();
+    }
+
+    /// Returns the (approximate) number of heap bytes held by the call info, including its inner
+    /// calls; hash tables are estimated by their capacity.
+    pub fn approx_heap_size(&self) -> usize {
+        fn vec_size<T>(vec: &Vec<T>) -> usize {
+            vec.capacity() * size_of::<T>()
+        }
+        // Entries, plus a control byte each.
+        fn table_size<T>(capacity: usize) -> usize {
+            capacity * (size_of::<T>() + 1)
+        }
+
+        // The calldata is held by an `Arc`, along with its (two) reference counters.
+        let calldata_size =
+            size_of::<Vec<StarkFelt>>() + 2 * size_of::<usize>() + vec_size(&self.call.calldata.0);
+        let events_size = vec_size(&self.execution.events)
+            + self
+                .execution
+                .events
+                .iter()
+                .map(|ordered_event| {
+                    let event = &ordered_event.event;
+                    vec_size(&event.keys) + vec_size(&event.data.0)
+                })
+                .sum::<usize>();
+        let messages_size = vec_size(&self.execution.l2_to_l1_messages)
+            + self
+                .execution
+                .l2_to_l1_messages
+                .iter()
+                .map(|ordered_message| vec_size(&ordered_message.message.payload.0))
+                .sum::<usize>();
+        let builtin_counter = &self.vm_resources.builtin_instance_counter;
+        let vm_resources_size = table_size::<(String, usize)>(builtin_counter.capacity())
+            + builtin_counter.keys().map(String::capacity).sum::<usize>();
+        let inner_calls_size = vec_size(&self.inner_calls)
+            + self.inner_calls.iter().map(CallInfo::approx_heap_size).sum::<usize>();
+
+        calldata_size
+            + vec_size(&self.execution.retdata.0)
+            + events_size
+            + messages_size
+            + vm_resources_size
+            + inner_calls_size
+            + vec_size(&self.storage_read_values)
+            + table_size::<StorageKey>(self.accessed_storage_keys.capacity())
+            + table_size::<(DeprecatedSyscallSelector, usize)>(self.syscall_counter.capacity())
+    }
+
+    /// Returns the call info in the layout of the Python `CallInfo` (as reported by
+    /// `native_blockifier`), with felts as decimal strings.
+    pub fn to_py_compatible_json(&self) -> serde_json::Value {
+        let call = &self.call;
+        let execution = &self.execution;
+        let felts = |felts: &[StarkFelt]| -> Vec<String> {
+            felts.iter().map(felt_to_decimal_string).collect()
+        };
+        let mut accessed_storage_keys: Vec<_> =
+            self.accessed_storage_keys.iter().map(|storage_key| *storage_key.0.key()).collect();
+        accessed_storage_keys.sort();
+        let mut builtin_instance_counter: Vec<_> =
+            self.vm_resources.builtin_instance_counter.iter().collect();
+        builtin_instance_counter.sort();
+
+        serde_json::json!({
+            "caller_address": felt_to_decimal_string(call.caller_address.0.key()),
+            "contract_address": felt_to_decimal_string(call.storage_address.0.key()),
+            "class_hash": call.class_hash.map(|class_hash| felt_to_decimal_string(&class_hash.0)),
+            "entry_point_selector": felt_to_decimal_string(&call.entry_point_selector.0),
+            "entry_point_type": call.entry_point_type as u8,
+            "calldata": felts(&call.calldata.0),
+            "call_type": call.call_type as u8,
+            "gas_consumed": execution.gas_consumed,
+            "failure_flag": felt_to_decimal_string(&StarkFelt::from(u8::from(execution.failed))),
+            "retdata": felts(&execution.retdata.0),
+            "execution_resources": {
+                "n_steps": self.vm_resources.n_steps,
+                "builtin_instance_counter": serde_json::Map::from_iter(
+                    builtin_instance_counter
+                        .into_iter()
+                        .map(|(builtin_name, &count)| (builtin_name.clone(), count.into()))
+                ),
+                "n_memory_holes": self.vm_resources.n_memory_holes,
+            },
+            "events": execution.events.iter().map(|ordered_event| serde_json::json!({
+                "order": ordered_event.order,
+                "keys": ordered_event
+                    .event
+                    .keys
+                    .iter()
+                    .map(|key| felt_to_decimal_string(&key.0))
+                    .collect::<Vec<_>>(),
+                "data": felts(&ordered_event.event.data.0),
+            })).collect::<Vec<_>>(),
+            "l2_to_l1_messages": execution.l2_to_l1_messages.iter().map(|ordered_message| {
+                let to_address = ordered_message.message.to_address.0.as_bytes();
+                serde_json::json!({
+                    "order": ordered_message.order,
+                    "to_address": BigUint::from_bytes_be(to_address).to_string(),
+                    "payload": felts(&ordered_message.message.payload.0),
+                })
+            }).collect::<Vec<_>>(),
+            "internal_calls": self
+                .inner_calls
+                .iter()
+                .map(CallInfo::to_py_compatible_json)
+                .collect::<Vec<_>>(),
+            "storage_read_values": felts(&self.storage_read_values),
+            "accessed_storage_keys": felts(&accessed_storage_keys),
+            "code_address":
+                call.code_address.map(|address| felt_to_decimal_string(address.0.key())),
+        })
+    }
+
     /// Returns the set of class hashes that were executed during this call execution.
     // TODO: Add unit test for this method
     pub fn get_executed_class_hashes(&self) -> HashSet<ClassHash> {
@@ -301,6 +569,72 @@ impl CallInfo {
         class_hashes
     }
 
+    /// Returns the L2 gas consumed by the call, including its inner calls: the Sierra gas of
+    /// gas-metered calls, and the gas equivalent of the steps (`STEP_GAS_COST` each) of the others.
+    pub fn l2_gas_consumed(&self) -> u64 {
+        if self.gas_metered {
+            // The gas of gas-metered inner calls is included in the consumed gas of the caller.
+            return self.execution.gas_consumed
+                + self
+                    .inner_calls
+                    .iter()
+                    .filter(|inner_call| !inner_call.gas_metered)
+                    .map(CallInfo::l2_gas_consumed)
+                    .sum::<u64>();
+        }
+
+        let n_inner_calls_steps: usize =
+            self.inner_calls.iter().map(|inner_call| inner_call.vm_resources.n_steps).sum();
+        // Saturates on call infos whose resources do not include those of their inner calls (e.g.,
+        // deserialized ones), rather than underflowing.
+        let n_steps = self.vm_resources.n_steps.saturating_sub(n_inner_calls_steps);
+        n_steps as u64 * constants::STEP_GAS_COST
+            + self.inner_calls.iter().map(CallInfo::l2_gas_consumed).sum::<u64>()
+    }
+
+    /// Iterates over the calls which did not fail, as `into_iter`; a failed call is skipped along
+    /// with its inner calls, as their effects (e.g., events) are discarded.
+    pub fn iter_non_failed(&self) -> CallInfoIter<'_> {
+        CallInfoIter { call_infos: vec![self], skip_failed: true }
+    }
+
+    /// Returns the events emitted by the calls which did not fail, in the order of emission.
+    pub fn get_sorted_events(&self) -> Vec<EventWithAddress> {
+        let mut ordered_events: Vec<(usize, EventWithAddress)> = self
+            .iter_non_failed()
+            .flat_map(|call| {
+                call.execution.events.iter().map(|ordered_event| {
+                    let event = EventWithAddress {
+                        from_address: call.call.storage_address,
+                        content: ordered_event.event.clone(),
+                    };
+                    (ordered_event.order, event)
+                })
+            })
+            .collect();
+        ordered_events.sort_by_key(|(order, _)| *order);
+        ordered_events.into_iter().map(|(_, event)| event).collect()
+    }
+
+    /// Returns the L2 to L1 messages sent by the calls which did not fail, in the order they were
+    /// sent.
+    pub fn get_sorted_messages(&self) -> Vec<MessageToL1WithAddress> {
+        let mut ordered_messages: Vec<(usize, MessageToL1WithAddress)> = self
+            .iter_non_failed()
+            .flat_map(|call| {
+                call.execution.l2_to_l1_messages.iter().map(|ordered_message| {
+                    let message = MessageToL1WithAddress {
+                        from_address: call.call.storage_address,
+                        message: ordered_message.message.clone(),
+                    };
+                    (ordered_message.order, message)
+                })
+            })
+            .collect();
+        ordered_messages.sort_by_key(|(order, _)| *order);
+        ordered_messages.into_iter().map(|(_, message)| message).collect()
+    }
+
     /// Returns a list of StarkNet L2ToL1Payload length collected during the execution, sorted
     /// by the order in which they were sent.
     pub fn get_sorted_l2_to_l1_payloads_length(&self) -> TransactionExecutionResult<Vec<usize>> {
@@ -311,8 +645,7 @@ impl CallInfo {
             for ordered_message_content in &call.execution.l2_to_l1_messages {
                 let message_order = ordered_message_content.order;
                 if message_order >= n_messages {
-                    return Err(TransactionExecutionError::InvalidOrder {
-                        object: "L2-to-L1 message".to_string(),
+                    return Err(TransactionExecutionError::InvalidL2ToL1MessageOrder {
                         order: message_order,
                         max_order: n_messages,
                     });
@@ -329,25 +662,41 @@ impl CallInfo {
                     acc.push(value);
                     Ok(acc)
                 }
-                None => Err(TransactionExecutionError::UnexpectedHoles {
-                    object: "L2-to-L1 message".to_string(),
-                    order: i,
-                }),
+                None => {
+                    Err(TransactionExecutionError::UnexpectedL2ToL1MessageOrderHole { order: i })
+                }
             },
         )
     }
 }
 
+/// (De)serializes the VM resources of a call; the VM's resources struct does not implement serde's
+/// traits itself.
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "VmExecutionResources")]
+struct VmExecutionResourcesDef {
+    n_steps: usize,
+    n_memory_holes: usize,
+    builtin_instance_counter: HashMap<String, usize>,
+}
+
 pub struct CallInfoIter<'a> {
     call_infos: Vec<&'a CallInfo>,
+    // Whether to skip failed calls, along with their inner calls.
+    skip_failed: bool,
 }
 
 impl<'a> Iterator for CallInfoIter<'a> {
     type Item = &'a CallInfo;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(call_info) = self.call_infos.pop() else {
-            return None;
+        let call_info = loop {
+            let Some(call_info) = self.call_infos.pop() else {
+                return None;
+            };
+            if !(self.skip_failed && call_info.execution.failed) {
+                break call_info;
+            }
         };
 
         // Push order is right to left.
@@ -361,7 +710,7 @@ impl<'a> IntoIterator for &'a CallInfo {
     type IntoIter = CallInfoIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        CallInfoIter { call_infos: vec![self] }
+        CallInfoIter { call_infos: vec![self], skip_failed: false }
     }
 }
 
@@ -402,10 +751,7 @@ pub fn handle_empty_constructor(
 ) -> EntryPointExecutionResult<CallInfo> {
     // Validate no calldata.
     if !calldata.0.is_empty() {
-        return Err(EntryPointExecutionError::InvalidExecutionInput {
-            input_descriptor: "constructor_calldata".to_string(),
-            info: "Cannot pass calldata to a contract with no constructor.".to_string(),
-        });
+        return Err(EntryPointExecutionError::UnexpectedConstructorCalldata);
     }
 
     let empty_constructor_call_info = CallInfo {
@@ -413,7 +759,7 @@ pub fn handle_empty_constructor(
             class_hash: Some(ctor_context.class_hash),
             code_address: ctor_context.code_address,
             entry_point_type: EntryPointType::Constructor,
-            entry_point_selector: selector_from_name(constants::CONSTRUCTOR_ENTRY_POINT_NAME),
+            entry_point_selector: selector!(constants::CONSTRUCTOR_ENTRY_POINT_NAME),
             calldata: Calldata::default(),
             storage_address: ctor_context.storage_address,
             caller_address: ctor_context.caller_address,
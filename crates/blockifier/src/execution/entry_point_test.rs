@@ -1,24 +1,37 @@
 use std::collections::HashSet;
 
+use assert_matches::assert_matches;
 use cairo_vm::serde::deserialize_program::BuiltinName;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
 use num_bigint::BigInt;
 use pretty_assertions::assert_eq;
-use starknet_api::core::{EntryPointSelector, PatriciaKey};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
-use starknet_api::transaction::Calldata;
-use starknet_api::{calldata, patricia_key, stark_felt};
+use starknet_api::transaction::{Calldata, EventContent, EventKey, Fee};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
 
 use crate::abi::abi_utils::{get_storage_var_address, selector_from_name};
-use crate::execution::entry_point::{CallEntryPoint, CallExecution, CallInfo, Retdata};
-use crate::execution::errors::EntryPointExecutionError;
+use crate::abi::constants::{N_STEPS_RESOURCE, STEP_GAS_COST};
+use crate::block_context::BlockContext;
+use crate::execution::entry_point::{
+    handle_empty_constructor, CallEntryPoint, CallExecution, CallInfo, ConstructorContext,
+    EntryPointExecutionContext, ExecutionResources, OrderedEvent, Retdata,
+};
+use crate::execution::errors::{
+    EntryPointExecutionError, PostExecutionError, PreExecutionError, SecurityValidationError,
+};
 use crate::retdata;
+use crate::selector;
 use crate::state::cached_state::CachedState;
 use crate::test_utils::{
     create_test_state, deprecated_create_test_state, pad_address_to_64,
     trivial_external_entry_point, trivial_external_entry_point_security_test, DictStateReader,
-    SECURITY_TEST_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS_2,
+    MAX_FEE, SECURITY_TEST_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS_2,
 };
+use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::objects::AccountTransactionContext;
 
 #[test]
 fn test_call_info_iteration() {
@@ -53,11 +66,81 @@ fn test_call_info_iteration() {
     }
 }
 
+#[test]
+fn test_l2_gas_consumed() {
+    // A Cairo 1 call (consuming 1000 gas, including its Cairo 1 inner call), which calls a Cairo 0
+    // contract (2 steps of its own), which in turn calls a Cairo 1 contract (consuming 30 gas).
+    let steps = |n_steps| VmExecutionResources { n_steps, ..Default::default() };
+    let gas_metered_call = |gas_consumed, inner_calls| CallInfo {
+        execution: CallExecution { gas_consumed, ..Default::default() },
+        inner_calls,
+        gas_metered: true,
+        ..Default::default()
+    };
+    let cairo0_call = CallInfo {
+        vm_resources: steps(2 + 3),
+        inner_calls: vec![CallInfo { vm_resources: steps(3), ..gas_metered_call(30, vec![]) }],
+        ..Default::default()
+    };
+    let root = gas_metered_call(1000, vec![gas_metered_call(200, vec![]), cairo0_call]);
+
+    assert_eq!(root.l2_gas_consumed(), 1000 + 2 * STEP_GAS_COST + 30);
+
+    // A call whose resources do not cover those of its inner calls has no steps of its own.
+    let inconsistent_call = CallInfo {
+        vm_resources: steps(1),
+        inner_calls: vec![CallInfo { vm_resources: steps(3), ..gas_metered_call(30, vec![]) }],
+        ..Default::default()
+    };
+    assert_eq!(inconsistent_call.l2_gas_consumed(), 30);
+}
+
+#[test]
+fn test_call_info_shrink_to_fit() {
+    // Builds a trace of 10k calls (a root calling 99 contracts, each calling 100 others), whose
+    // collections are grown as during execution; each call reads and writes a few storage cells
+    // and emits an event.
+    fn synthetic_call_info(inner_calls: impl Iterator<Item = CallInfo>, shrink: bool) -> CallInfo {
+        let mut call_info = CallInfo::default();
+        for inner_call in inner_calls {
+            call_info.inner_calls.push(inner_call);
+        }
+        for i in 0..3_u8 {
+            call_info.storage_read_values.push(stark_felt!(i));
+            call_info.accessed_storage_keys.insert(StorageKey(patricia_key!(i)));
+        }
+        let mut event = EventContent::default();
+        event.keys.push(EventKey(stark_felt!(1_u8)));
+        event.data.0.push(stark_felt!(2_u8));
+        call_info.execution.events.push(OrderedEvent { order: 0, event });
+        if shrink {
+            call_info.shrink_to_fit();
+        }
+        call_info
+    }
+    let synthetic_trace = |shrink| {
+        let inner_calls = (0..99).map(|_| {
+            let leaves = (0..100).map(|_| synthetic_call_info(std::iter::empty(), shrink));
+            synthetic_call_info(leaves, shrink)
+        });
+        synthetic_call_info(inner_calls, shrink)
+    };
+
+    let trace = synthetic_trace(false);
+    let shrunk_trace = synthetic_trace(true);
+    assert_eq!(shrunk_trace.into_iter().count(), 10000);
+    assert_eq!(shrunk_trace, trace);
+
+    // Over a fifth of the trace is spare capacity, mostly that of the inner calls.
+    let (size, shrunk_size) = (trace.approx_heap_size(), shrunk_trace.approx_heap_size());
+    assert!(shrunk_size * 5 < size * 4, "Shrunk trace size {shrunk_size} vs. {size}.");
+}
+
 #[test]
 fn test_entry_point_without_arg() {
     let mut state = deprecated_create_test_state();
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("without_arg"),
+        entry_point_selector: selector!("without_arg"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -72,7 +155,7 @@ fn test_entry_point_with_arg() {
     let calldata = calldata![stark_felt!(25_u8)];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("with_arg"),
+        entry_point_selector: selector!("with_arg"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -87,7 +170,7 @@ fn test_long_retdata() {
     let calldata = calldata![];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_long_retdata"),
+        entry_point_selector: selector!("test_long_retdata"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -108,7 +191,7 @@ fn test_entry_point_with_builtin() {
     let calldata = calldata![stark_felt!(47_u8), stark_felt!(31_u8)];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("bitwise_and"),
+        entry_point_selector: selector!("bitwise_and"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -123,7 +206,7 @@ fn test_entry_point_with_hint() {
     let calldata = calldata![stark_felt!(81_u8)];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("sqrt"),
+        entry_point_selector: selector!("sqrt"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -138,7 +221,7 @@ fn test_entry_point_with_return_value() {
     let calldata = calldata![stark_felt!(23_u8)];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("return_result"),
+        entry_point_selector: selector!("return_result"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -160,11 +243,31 @@ fn test_entry_point_not_found_in_contract() {
     );
 }
 
+#[test]
+fn test_l1_handler_entry_point_outside_l1_handler() {
+    let mut state = create_test_state();
+    let entry_point_selector = selector!("l1_handle");
+    let entry_point_call = CallEntryPoint {
+        entry_point_type: EntryPointType::L1Handler,
+        entry_point_selector,
+        calldata: calldata![stark_felt!(0x123_u16), stark_felt!(7_u8)],
+        ..trivial_external_entry_point()
+    };
+    // Executed without an L1 handler transaction.
+    assert_matches!(
+        entry_point_call.execute_directly(&mut state).unwrap_err(),
+        EntryPointExecutionError::PreExecutionError(PreExecutionError::InvalidL1HandlerCall(
+            selector
+        ))
+        if selector == entry_point_selector
+    );
+}
+
 #[test]
 fn test_storage_var() {
     let mut state = deprecated_create_test_state();
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_storage_var"),
+        entry_point_selector: selector!("test_storage_var"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -173,6 +276,45 @@ fn test_storage_var() {
     );
 }
 
+/// The memory of the calls of an execution, including memory holes, is limited; a recursion,
+/// for one, allocates a frame per level.
+#[test]
+fn test_max_n_memory_cells() {
+    let mut state = deprecated_create_test_state();
+    let depth = 100;
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("recurse"),
+        calldata: calldata![stark_felt!(depth as u8)],
+        ..trivial_external_entry_point()
+    };
+    // Returns the memory cells of the execution, given its limit.
+    let mut execute = |max_n_memory_cells: Option<usize>| {
+        let mut block_context = BlockContext::create_for_testing();
+        block_context.versioned_constants_mut().max_n_memory_cells = max_n_memory_cells;
+        let mut context = EntryPointExecutionContext::new_invoke(
+            &block_context,
+            &AccountTransactionContext::default(),
+        )
+        .unwrap();
+        entry_point_call
+            .clone()
+            .execute(&mut state, &mut ExecutionResources::default(), &mut context)
+            .map(|_| context.n_memory_cells)
+    };
+
+    let n_memory_cells = execute(None).unwrap();
+    assert!(n_memory_cells > 2 * depth);
+    assert_eq!(execute(Some(n_memory_cells)).unwrap(), n_memory_cells);
+    assert_matches!(
+        execute(Some(n_memory_cells - 1)).unwrap_err(),
+        EntryPointExecutionError::PostExecutionError(PostExecutionError::MemoryLimitExceeded {
+            n_memory_cells: exceeding_n_memory_cells,
+            max_n_memory_cells,
+        })
+        if exceeding_n_memory_cells == n_memory_cells && max_n_memory_cells == n_memory_cells - 1
+    );
+}
+
 /// Runs test scenarios that could fail the OS run and therefore must be caught in the Blockifier.
 fn run_security_test(
     expected_error: &str,
@@ -449,6 +591,54 @@ fn test_post_run_validation_security_failure() {
     );
 }
 
+#[test]
+fn test_empty_constructor_with_calldata() {
+    let ctor_context = ConstructorContext {
+        class_hash: ClassHash::default(),
+        code_address: None,
+        storage_address: contract_address!(TEST_CONTRACT_ADDRESS),
+        caller_address: ContractAddress::default(),
+    };
+    assert_matches!(
+        handle_empty_constructor(ctor_context, calldata![stark_felt!(1_u8)], 0),
+        Err(EntryPointExecutionError::UnexpectedConstructorCalldata)
+    );
+}
+
+#[test]
+fn test_post_run_validation_security_failure_variants() {
+    let mut state = deprecated_create_test_state();
+    let mut run_security_test = |entry_point_name: &str| {
+        let entry_point_call = CallEntryPoint {
+            entry_point_selector: selector_from_name(entry_point_name),
+            ..trivial_external_entry_point_security_test()
+        };
+        match entry_point_call.execute_directly(&mut state) {
+            Err(EntryPointExecutionError::PostExecutionError(
+                PostExecutionError::SecurityValidationError(error),
+            )) => error,
+            result => panic!("Unexpected result of '{entry_point_name}': {result:?}."),
+        }
+    };
+
+    assert_matches!(
+        run_security_test("test_bad_builtin_stop_ptr"),
+        SecurityValidationError::RunnerError(_)
+    );
+    assert_matches!(
+        run_security_test("test_access_after_syscall_stop_ptr"),
+        SecurityValidationError::SyscallSegmentSize
+    );
+    assert_matches!(
+        run_security_test("test_bad_syscall_stop_ptr"),
+        SecurityValidationError::SyscallSegmentEnd(_)
+    );
+    assert_matches!(
+        run_security_test("test_out_of_bounds_write_to_signature_segment"),
+        SecurityValidationError::ReadOnlySegments
+    );
+}
+
 // Tests correct update of the fields: `storage_read_values` and `accessed_storage_keys`.
 // Note read values also contain the reads performed right before a write operation.
 #[test]
@@ -457,7 +647,7 @@ fn test_storage_related_members() {
 
     // Test storage variable.
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_storage_var"),
+        entry_point_selector: selector!("test_storage_var"),
         ..trivial_external_entry_point()
     };
     let actual_call_info = entry_point_call.execute_directly(&mut state).unwrap();
@@ -473,7 +663,7 @@ fn test_storage_related_members() {
     let calldata = calldata![key, value];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_storage_read_write"),
+        entry_point_selector: selector!("test_storage_read_write"),
         ..trivial_external_entry_point()
     };
     let actual_call_info = entry_point_call.execute_directly(&mut state).unwrap();
@@ -490,7 +680,7 @@ fn test_cairo1_entry_point_segment_arena() {
     let calldata = calldata![];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("segment_arena_builtin"),
+        entry_point_selector: selector!("segment_arena_builtin"),
         ..trivial_external_entry_point()
     };
 
@@ -508,8 +698,8 @@ fn test_cairo1_entry_point_segment_arena() {
 fn test_stack_trace() {
     let mut state = deprecated_create_test_state();
     // Nest 3 calls: test_call_contract -> test_call_contract -> assert_0_is_1.
-    let outer_entry_point_selector = selector_from_name("test_call_contract");
-    let inner_entry_point_selector = selector_from_name("foo");
+    let outer_entry_point_selector = selector!("test_call_contract");
+    let inner_entry_point_selector = selector!("foo");
     let calldata = calldata![
         stark_felt!(TEST_CONTRACT_ADDRESS_2), // Contract address.
         outer_entry_point_selector.0,         // Calling test_call_contract again.
@@ -556,3 +746,16 @@ Unknown location (pc=0:62)
         other_error => panic!("Unexpected error type: {other_error:?}"),
     }
 }
+
+#[test]
+fn test_max_invoke_steps_without_step_weight() {
+    let mut block_context = BlockContext::create_for_account_testing();
+    block_context.versioned_constants_mut().vm_resource_fee_cost.remove(N_STEPS_RESOURCE);
+    let account_tx_context =
+        AccountTransactionContext { max_fee: Fee(MAX_FEE), ..Default::default() };
+
+    assert_matches!(
+        EntryPointExecutionContext::max_invoke_steps(&block_context, &account_tx_context),
+        Err(TransactionExecutionError::CairoResourcesNotContainedInFeeCosts)
+    );
+}
@@ -1,17 +1,27 @@
+use cairo_felt::Felt252;
 use cairo_vm::types::errors::math_errors::MathError;
+use cairo_vm::types::relocatable::MaybeRelocatable;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::errors::hint_errors::HintError;
 use cairo_vm::vm::errors::memory_errors::MemoryError;
 use cairo_vm::vm::errors::runner_errors::RunnerError;
 use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
-use num_bigint::{BigInt, TryFromBigIntError};
+use std::fmt;
+
+use num_bigint::{BigInt, BigUint, TryFromBigIntError};
 use starknet_api::core::{ContractAddress, EntryPointSelector};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
 use thiserror::Error;
 
-use crate::execution::execution_utils::felts_as_str;
+use crate::execution::deprecated_syscalls::hint_processor::DeprecatedSyscallExecutionError;
+use crate::execution::syscalls::hint_processor::{SyscallExecutionError, ENTRYPOINT_FAILED_ERROR};
 use crate::state::errors::StateError;
 
+#[cfg(test)]
+#[path = "errors_test.rs"]
+pub mod test;
+
 // TODO(AlonH, 21/12/2022): Implement Display for all types that appear in errors.
 
 #[derive(Debug, Error)]
@@ -26,6 +36,8 @@ pub enum PreExecutionError {
     InvalidBuiltin(String),
     #[error("The constructor entry point must be named 'constructor'.")]
     InvalidConstructorEntryPointName,
+    #[error("L1 handler entry point {0:?} can only be called by an L1 handler transaction.")]
+    InvalidL1HandlerCall(EntryPointSelector),
     #[error(transparent)]
     MathError(#[from] MathError),
     #[error(transparent)]
@@ -50,23 +62,102 @@ impl From<RunnerError> for PreExecutionError {
 
 #[derive(Debug, Error)]
 pub enum PostExecutionError {
+    #[error("Malformed return data: the failure flag must be 0 or 1; got {failure_flag:?}.")]
+    InvalidFailureFlag { failure_flag: MaybeRelocatable },
+    #[error("Malformed return data: the remaining gas must be an integer; got {remaining_gas:?}.")]
+    MalformedRemainingGas { remaining_gas: MaybeRelocatable },
     #[error(transparent)]
     MathError(#[from] cairo_vm::types::errors::math_errors::MathError),
     #[error(transparent)]
     MemoryError(#[from] MemoryError),
+    #[error(
+        "Exceeded the maximal number of memory cells per transaction: {n_memory_cells} > \
+         {max_n_memory_cells}."
+    )]
+    MemoryLimitExceeded { n_memory_cells: usize, max_n_memory_cells: usize },
     #[error(transparent)]
     RetdataSizeTooBig(#[from] TryFromBigIntError<BigInt>),
     #[error("Validation failed: {0}.")]
-    SecurityValidationError(String),
+    SecurityValidationError(#[from] SecurityValidationError),
+    #[error(
+        "Malformed return data: the remaining gas ({remaining_gas}) exceeds the initial gas \
+         ({initial_gas})."
+    )]
+    UnexpectedRemainingGas { remaining_gas: Felt252, initial_gas: u64 },
     #[error(transparent)]
     VirtualMachineError(#[from] VirtualMachineError),
-    #[error("Malformed return data : {error_message}.")]
-    MalformedReturnData { error_message: String },
 }
 
 impl From<RunnerError> for PostExecutionError {
     fn from(error: RunnerError) -> Self {
-        Self::SecurityValidationError(error.to_string())
+        SecurityValidationError::RunnerError(Box::new(error)).into()
+    }
+}
+
+/// A failed security check of the final state of a Cairo 0 run (see `validate_run`).
+#[derive(Debug, Error)]
+pub enum SecurityValidationError {
+    #[error("Implicit arguments' segments")]
+    ImplicitArgumentsSegments,
+    #[error("Read-only segments")]
+    ReadOnlySegments,
+    /// E.g., an invalid stop pointer of a builtin.
+    #[error(transparent)]
+    RunnerError(Box<RunnerError>),
+    #[error("Syscall segment end")]
+    SyscallSegmentEnd(#[source] Box<DeprecatedSyscallExecutionError>),
+    #[error("Syscall segment size")]
+    SyscallSegmentSize,
+    #[error("Syscall segment start")]
+    SyscallSegmentStart,
+}
+
+/// The prefixes of the errors of failed inner calls; these have frames of their own in the revert
+/// reason (a VM error trace of a Cairo 0 call, or the panic data of a Cairo 1 call).
+const INNER_CALL_ERROR_PREFIXES: [&str; 2] =
+    ["Error in the called contract", "Execution failed. Failure reason:"];
+
+/// How a failed execution affects its transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExecutionErrorCategory {
+    /// A failure of the executed code (e.g., a failed assertion); the transaction is reverted.
+    Reverted,
+    /// A failure beyond the control of the executed code (e.g., a failed state read, or a corrupted
+    /// class); the transaction is rejected.
+    Rejected,
+}
+
+/// The error of a syscall (i.e., of blockifier code) raised inside a hint, which failed the run.
+///
+/// The VM only carries the message of errors raised by hints (see `HintError::CustomHint`); hence,
+/// the hint processor keeps the error itself, for `EntryPointExecutionError::from_vm_error` to
+/// recover.
+#[derive(Debug, Error)]
+pub enum SyscallHintError {
+    #[error(transparent)]
+    DeprecatedSyscallExecutionError(#[from] DeprecatedSyscallExecutionError),
+    #[error(transparent)]
+    SyscallExecutionError(#[from] SyscallExecutionError),
+}
+
+impl SyscallHintError {
+    /// Returns the execution error behind the syscall error, if it rejects the transaction.
+    fn into_rejection(self) -> Option<EntryPointExecutionError> {
+        let error = match self {
+            Self::DeprecatedSyscallExecutionError(
+                DeprecatedSyscallExecutionError::InnerCallExecutionError(error),
+            )
+            | Self::SyscallExecutionError(SyscallExecutionError::InnerCallExecutionError(
+                error,
+            )) => error,
+            Self::DeprecatedSyscallExecutionError(DeprecatedSyscallExecutionError::StateError(
+                error,
+            ))
+            | Self::SyscallExecutionError(SyscallExecutionError::StateError(error)) => error.into(),
+            _ => return None,
+        };
+
+        (error.category() == ExecutionErrorCategory::Rejected).then_some(error)
     }
 }
 
@@ -79,6 +170,30 @@ pub enum VirtualMachineExecutionError {
 }
 
 impl VirtualMachineExecutionError {
+    pub fn category(&self) -> ExecutionErrorCategory {
+        let vm_error = match self {
+            Self::CairoRunError(CairoRunError::VmException(exception)) => &exception.inner_exc,
+            Self::CairoRunError(CairoRunError::VirtualMachine(error))
+            | Self::VirtualMachineError(error) => error,
+            Self::CairoRunError(_) => return ExecutionErrorCategory::Reverted,
+        };
+        match vm_error {
+            // Hints referencing identifiers the program lacks; i.e., a corrupted class.
+            VirtualMachineError::Hint(hint_error) => match hint_error.as_ref() {
+                (
+                    _,
+                    HintError::UnknownIdentifier(_)
+                    | HintError::UnknownIdentifierInternal
+                    | HintError::IdentifierNotInteger(_)
+                    | HintError::WrongIdentifierTypeInternal(_)
+                    | HintError::WrongHintData,
+                ) => ExecutionErrorCategory::Rejected,
+                _ => ExecutionErrorCategory::Reverted,
+            },
+            _ => ExecutionErrorCategory::Reverted,
+        }
+    }
+
     /// Unwrap inner VM exception and return it as a string. If this is a call_contract exception,
     /// the inner error (inner call errors) will not appear in the string.
     pub fn try_to_vm_trace(&self) -> String {
@@ -91,9 +206,15 @@ impl VirtualMachineExecutionError {
                 // to append inner representation.
                 // Otherwise, add the inner representation. Prefer using the error attribute as the
                 // description of the error; if it is unavailable, use the inner exception string.
-                let outer_call_prefix = "Got an exception while executing a hint: Custom Hint \
-                                         Error: Error in the called contract";
-                if inner_exc_string.starts_with(outer_call_prefix) {
+                let hint_error_prefix =
+                    "Got an exception while executing a hint: Custom Hint Error: ";
+                let is_inner_call_error =
+                    inner_exc_string.strip_prefix(hint_error_prefix).is_some_and(|hint_error| {
+                        INNER_CALL_ERROR_PREFIXES
+                            .iter()
+                            .any(|prefix| hint_error.starts_with(prefix))
+                    });
+                if is_inner_call_error {
                     trace_string += "Got an exception while executing a hint.";
                 } else if let Some(error_attribute) = &exception.error_attr_value {
                     trace_string += error_attribute;
@@ -122,10 +243,10 @@ impl VirtualMachineExecutionError {
 
 #[derive(Debug, Error)]
 pub enum EntryPointExecutionError {
-    #[error("Execution failed. Failure reason: {:?}.", felts_as_str(.error_data))]
+    #[error("Execution deadline exceeded.")]
+    ExecutionDeadlineExceeded,
+    #[error("Execution failed. Failure reason: {}.", format_panic_data(.error_data))]
     ExecutionFailed { error_data: Vec<StarkFelt> },
-    #[error("Invalid input: {input_descriptor}; {info}")]
-    InvalidExecutionInput { input_descriptor: String, info: String },
     #[error(transparent)]
     PostExecutionError(#[from] PostExecutionError),
     #[error(transparent)]
@@ -134,6 +255,11 @@ pub enum EntryPointExecutionError {
     RecursionDepthExceeded,
     #[error(transparent)]
     StateError(#[from] StateError),
+    #[error(
+        "Invalid input: constructor_calldata; Cannot pass calldata to a contract with no \
+         constructor."
+    )]
+    UnexpectedConstructorCalldata,
     /// Gathers all errors from running the Cairo VM, excluding hints.
     #[error(transparent)]
     VirtualMachineExecutionError(#[from] VirtualMachineExecutionError),
@@ -144,3 +270,109 @@ pub enum EntryPointExecutionError {
         source: VirtualMachineExecutionError,
     },
 }
+
+impl EntryPointExecutionError {
+    /// Converts the error of a VM run, given the error of the syscall which failed it, if any (see
+    /// `SyscallHintError`). A syscall error which rejects the transaction surfaces as itself;
+    /// e.g., a failed state read, as a `StateError`. Otherwise, the VM error is kept, as its trace
+    /// is part of the revert reason.
+    pub fn from_vm_error(
+        error: VirtualMachineExecutionError,
+        syscall_error: Option<SyscallHintError>,
+    ) -> Self {
+        syscall_error
+            .and_then(SyscallHintError::into_rejection)
+            .unwrap_or(Self::VirtualMachineExecutionError(error))
+    }
+
+    pub fn category(&self) -> ExecutionErrorCategory {
+        match self {
+            Self::ExecutionDeadlineExceeded => ExecutionErrorCategory::Rejected,
+            Self::PreExecutionError(PreExecutionError::ProgramError(_)) => {
+                ExecutionErrorCategory::Rejected
+            }
+            Self::PreExecutionError(PreExecutionError::StateError(error))
+            | Self::StateError(error) => state_error_category(error),
+            Self::VirtualMachineExecutionError(error)
+            | Self::VirtualMachineExecutionErrorWithTrace { source: error, .. } => error.category(),
+            _ => ExecutionErrorCategory::Reverted,
+        }
+    }
+}
+
+/// State errors not caused by the executed code (e.g., by a deployment to a taken address) reject
+/// the transaction.
+fn state_error_category(error: &StateError) -> ExecutionErrorCategory {
+    match error {
+        StateError::MissingWitnessEntry { .. }
+        | StateError::ProgramError(_)
+        | StateError::StateReadError(_) => ExecutionErrorCategory::Rejected,
+        _ => ExecutionErrorCategory::Reverted,
+    }
+}
+
+/// Formats the panic data of a failed Cairo 1 call as the Starknet OS does: each felt in hex,
+/// followed by its text if all of its bytes are printable ASCII (e.g.,
+/// `0x4f7574206f6620676173 ('Out of gas')`); several felts are parenthesized. The trailing
+/// `ENTRYPOINT_FAILED` errors are dropped, as the frames of the revert reason show the failed inner
+/// calls.
+pub fn format_panic_data(panic_data: &[StarkFelt]) -> String {
+    let entrypoint_failed = StarkFelt::try_from(ENTRYPOINT_FAILED_ERROR)
+        .expect("The entry point failure error must be a felt.");
+    let n_propagated =
+        panic_data.iter().rev().take_while(|&&felt| felt == entrypoint_failed).count();
+    let panic_data = match n_propagated {
+        n_propagated if n_propagated < panic_data.len() => {
+            &panic_data[..panic_data.len() - n_propagated]
+        }
+        _ => panic_data,
+    };
+
+    let formatted_felts: Vec<String> = panic_data
+        .iter()
+        .map(|felt| {
+            let hex = format!("{:#x}", BigUint::from_bytes_be(felt.bytes()));
+            match as_printable_short_string(felt) {
+                Some(text) => format!("{hex} ('{text}')"),
+                None => hex,
+            }
+        })
+        .collect();
+    match formatted_felts.as_slice() {
+        [formatted_felt] => formatted_felt.clone(),
+        _ => format!("({})", formatted_felts.join(", ")),
+    }
+}
+
+/// Formats the revert reason of a failed execution as the Starknet OS (and the feeder gateway)
+/// does: a frame per failed call, from the outermost inwards, each naming the address of its
+/// contract. The error stack holds the frames from the innermost call outwards; if it is empty
+/// (the execution failed outside of the VM), the reason is the given error.
+pub fn format_revert_reason(
+    error_stack: &[(ContractAddress, String)],
+    error: &dyn fmt::Display,
+) -> String {
+    if error_stack.is_empty() {
+        return error.to_string();
+    }
+
+    error_stack
+        .iter()
+        .rev()
+        .map(|(contract_address, frame)| {
+            format!("Error in the called contract ({}):\n{frame}", contract_address.0.key())
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Decodes a felt as a (Cairo) short string, if it is one of printable ASCII characters.
+fn as_printable_short_string(felt: &StarkFelt) -> Option<String> {
+    let bytes: Vec<u8> = felt.bytes().iter().copied().skip_while(|&byte| byte == 0).collect();
+    let is_printable = |byte: &u8| byte.is_ascii_graphic() || *byte == b' ';
+    if bytes.is_empty() || !bytes.iter().all(is_printable) {
+        return None;
+    }
+
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::Calldata;
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::abi::constants;
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::execution::errors::{
+    format_panic_data, format_revert_reason, EntryPointExecutionError, ExecutionErrorCategory,
+};
+use crate::execution::syscalls::hint_processor::{ENTRYPOINT_FAILED_ERROR, OUT_OF_GAS_ERROR};
+use crate::selector;
+use crate::state::cached_state::CachedState;
+use crate::state::errors::StateError;
+use crate::test_utils::{
+    get_raw_contract_class, trivial_external_entry_point, DictStateReader, FailingStorageReader,
+    CURRENT_BLOCK_NUMBER, ERC20_CONTRACT_PATH, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+    TEST_CONTRACT_CAIRO0_PATH, TEST_CONTRACT_CAIRO1_PATH,
+};
+
+fn short_string(text: &str) -> StarkFelt {
+    let mut bytes = [0; 32];
+    bytes[32 - text.len()..].copy_from_slice(text.as_bytes());
+    StarkFelt::new(bytes).unwrap()
+}
+
+#[rstest]
+#[case::short_string(vec![stark_felt!(OUT_OF_GAS_ERROR)], "0x4f7574206f6620676173 ('Out of gas')")]
+#[case::not_printable(vec![stark_felt!("0x4f75740a")], "0x4f75740a")]
+#[case::zero(vec![stark_felt!(0_u8)], "0x0")]
+#[case::several(
+    vec![short_string("argent/multicall-failed"), stark_felt!(2_u8)],
+    "(0x617267656e742f6d756c746963616c6c2d6661696c6564 ('argent/multicall-failed'), 0x2)"
+)]
+#[case::propagated(
+    vec![
+        short_string("u256_sub Overflow"),
+        stark_felt!(ENTRYPOINT_FAILED_ERROR),
+        stark_felt!(ENTRYPOINT_FAILED_ERROR),
+    ],
+    "0x753235365f737562204f766572666c6f77 ('u256_sub Overflow')"
+)]
+#[case::only_propagated(
+    vec![stark_felt!(ENTRYPOINT_FAILED_ERROR)],
+    "0x454e545259504f494e545f4641494c4544 ('ENTRYPOINT_FAILED')"
+)]
+#[case::empty(vec![], "()")]
+fn test_format_panic_data(#[case] panic_data: Vec<StarkFelt>, #[case] expected: &str) {
+    assert_eq!(format_panic_data(&panic_data), expected);
+}
+
+#[test]
+fn test_format_revert_reason() {
+    // Frames are stacked from the innermost call outwards.
+    let error_stack = vec![
+        (contract_address!("0x200"), "Execution failed. Failure reason: 0x1.".to_string()),
+        (contract_address!("0x100"), "Error at pc=0:7:\nGot an exception.".to_string()),
+    ];
+    assert_eq!(
+        format_revert_reason(&error_stack, &"Ignored."),
+        "Error in the called contract \
+         (0x0000000000000000000000000000000000000000000000000000000000000100):
+Error at pc=0:7:
+Got an exception.
+Error in the called contract (0x0000000000000000000000000000000000000000000000000000000000000200):
+Execution failed. Failure reason: 0x1."
+    );
+    assert_eq!(format_revert_reason(&[], &"Failed outside the VM."), "Failed outside the VM.");
+}
+
+/// A state with the given class deployed at the test contract address.
+fn test_contract_state_reader(contract_class: ContractClass) -> DictStateReader {
+    DictStateReader {
+        class_hash_to_class: HashMap::from([(class_hash!(TEST_CLASS_HASH), contract_class)]),
+        address_to_class_hash: HashMap::from([(
+            contract_address!(TEST_CONTRACT_ADDRESS),
+            class_hash!(TEST_CLASS_HASH),
+        )]),
+        ..Default::default()
+    }
+}
+
+fn storage_write_call(entry_point_name: &str) -> CallEntryPoint {
+    CallEntryPoint {
+        entry_point_selector: selector_from_name(entry_point_name),
+        calldata: calldata![stark_felt!(1_u8), stark_felt!(99_u8)],
+        ..trivial_external_entry_point()
+    }
+}
+
+#[test]
+fn test_assertion_failure_is_reverted() {
+    let mut state = CachedState::from(test_contract_state_reader(
+        ContractClassV0::from_file(TEST_CONTRACT_CAIRO0_PATH).into(),
+    ));
+
+    let error = storage_write_call("write_and_revert").execute_directly(&mut state).unwrap_err();
+    assert_matches!(error, EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace { .. });
+    assert_eq!(error.category(), ExecutionErrorCategory::Reverted);
+}
+
+#[test]
+fn test_corrupted_ids_reference_is_rejected() {
+    // Drops the references of the syscall hints (i.e., `ids.syscall_ptr`).
+    let mut raw_contract_class: serde_json::Value =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO0_PATH)).unwrap();
+    for hints in raw_contract_class["program"]["hints"].as_object_mut().unwrap().values_mut() {
+        for hint in hints.as_array_mut().unwrap() {
+            if hint["code"].as_str().unwrap().starts_with("syscall_handler.") {
+                hint["flow_tracking_data"]["reference_ids"] = serde_json::json!({});
+            }
+        }
+    }
+    let contract_class =
+        ContractClassV0::try_from_json_string(&raw_contract_class.to_string()).unwrap();
+    let mut state = CachedState::from(test_contract_state_reader(contract_class.into()));
+
+    let error =
+        storage_write_call("test_storage_read_write").execute_directly(&mut state).unwrap_err();
+    assert_matches!(error, EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace { .. });
+    assert!(error.to_string().contains("Unknown identifier syscall_ptr"), "{error}");
+    assert_eq!(error.category(), ExecutionErrorCategory::Rejected);
+}
+
+/// A failed storage read surfaces as is, rather than as the message of a VM error.
+#[rstest]
+#[case::cairo0(
+    ContractClassV0::from_file(ERC20_CONTRACT_PATH).into(),
+    CallEntryPoint {
+        entry_point_selector: selector!("balanceOf"),
+        calldata: calldata![stark_felt!(1_u8)],
+        ..trivial_external_entry_point()
+    },
+    contract_address!(TEST_CONTRACT_ADDRESS)
+)]
+#[case::cairo1(
+    ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH).into(),
+    CallEntryPoint {
+        entry_point_selector: selector!("test_get_block_hash"),
+        calldata: calldata![stark_felt!(CURRENT_BLOCK_NUMBER - 10)],
+        ..trivial_external_entry_point()
+    },
+    contract_address!(constants::BLOCK_HASH_CONTRACT_ADDRESS)
+)]
+fn test_state_error_in_syscall(
+    #[case] contract_class: ContractClass,
+    #[case] call: CallEntryPoint,
+    #[case] failing_contract_address: ContractAddress,
+) {
+    let mut state = CachedState::from(FailingStorageReader {
+        state_reader: test_contract_state_reader(contract_class),
+        failing_contract_address,
+    });
+
+    let error = call.execute_directly(&mut state).unwrap_err();
+    assert_matches!(error, EntryPointExecutionError::StateError(StateError::StateReadError(_)));
+    assert_eq!(error.category(), ExecutionErrorCategory::Rejected);
+}
@@ -1,7 +1,6 @@
 use std::collections::HashMap;
 
 use cairo_felt::Felt252;
-use cairo_lang_runner::short_string::as_cairo_short_string;
 use cairo_vm::serde::deserialize_program::{
     deserialize_array_of_bigint_hex, Attribute, HintParams, Identifier, ReferenceManager,
 };
@@ -16,14 +15,16 @@ use num_bigint::BigUint;
 use starknet_api::core::ClassHash;
 use starknet_api::deprecated_contract_class::Program as DeprecatedProgram;
 use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
 use starknet_api::transaction::Calldata;
 
+use crate::abi::constants;
 use crate::execution::contract_class::ContractClass;
 use crate::execution::entry_point::{
     execute_constructor_entry_point, CallEntryPoint, CallInfo, ConstructorContext,
     EntryPointExecutionContext, EntryPointExecutionResult, ExecutionResources, Retdata,
 };
-use crate::execution::errors::PostExecutionError;
+use crate::execution::errors::{PostExecutionError, SecurityValidationError};
 use crate::execution::{cairo1_execution, deprecated_execution};
 use crate::state::errors::StateError;
 use crate::state::state_api::State;
@@ -39,8 +40,43 @@ pub fn stark_felt_to_felt(stark_felt: StarkFelt) -> Felt252 {
 }
 
 pub fn felt_to_stark_felt(felt: &Felt252) -> StarkFelt {
-    let biguint = format!("{:#x}", felt.to_biguint());
-    StarkFelt::try_from(biguint.as_str()).expect("Felt252 must be in StarkFelt's range.")
+    StarkFelt::new(felt.to_be_bytes()).expect("Felt252 must be in StarkFelt's range.")
+}
+
+/// Returns whether the felt is smaller than 2**n.
+pub fn felt_lt_pow2(felt: &Felt252, n: u32) -> bool {
+    felt.bits() <= u64::from(n)
+}
+
+/// Returns whether the storage key is below the bound of the storage keys in the OS; the storage
+/// syscalls fail on keys at or above it.
+pub fn is_valid_storage_key(key: StorageKey) -> bool {
+    *key.0.key() < *constants::STORAGE_KEY_UPPER_BOUND
+}
+
+/// Splits the felt into its 128 least significant bits and the rest (i.e., `felt >> 128`).
+pub fn felt_low_high_128(felt: &Felt252) -> (u128, Felt252) {
+    let [digit_0, digit_1, digit_2, digit_3] = felt.to_le_digits();
+    let low = u128::from(digit_0) | (u128::from(digit_1) << 64);
+    let high = u128::from(digit_2) | (u128::from(digit_3) << 64);
+    (low, Felt252::from(high))
+}
+
+/// Returns the felt as a decimal string; the form in which felts are reported to Python.
+pub fn felt_to_decimal_string(felt: &StarkFelt) -> String {
+    BigUint::from_bytes_be(felt.bytes()).to_string()
+}
+
+/// Returns a JSON object keyed by the decimal strings of the given felts; entries are inserted by
+/// key, for a deterministic output regardless of the map's ordering.
+pub fn felt_keyed_json_object(
+    entries: impl IntoIterator<Item = (StarkFelt, serde_json::Value)>,
+) -> serde_json::Value {
+    let mut entries: Vec<_> = entries.into_iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+    serde_json::Value::Object(
+        entries.into_iter().map(|(key, value)| (felt_to_decimal_string(&key), value)).collect(),
+    )
 }
 
 /// Executes a specific call to a contract entry point and returns its output.
@@ -194,9 +230,7 @@ impl ReadOnlySegments {
                 .get_segment_used_size(segment.start_ptr.segment_index as usize)
                 .expect("Segments must contain the allocated read-only segment.");
             if segment.length != used_size {
-                return Err(PostExecutionError::SecurityValidationError(
-                    "Read-only segments".to_string(),
-                ));
+                return Err(SecurityValidationError::ReadOnlySegments.into());
             }
         }
 
@@ -212,6 +246,11 @@ impl ReadOnlySegments {
     }
 }
 
+/// Returns the number of memory cells of the given (finished) run, including memory holes.
+pub fn get_n_memory_cells(vm: &VirtualMachine) -> usize {
+    (0..).map_while(|segment_index| vm.get_segment_used_size(segment_index)).sum()
+}
+
 /// Instantiates the given class and assigns it an address.
 /// Returns the call info of the deployed class' constructor execution.
 pub fn execute_deployment(
@@ -269,13 +308,3 @@ pub fn write_maybe_relocatable<T: Into<MaybeRelocatable>>(
     *ptr = (*ptr + 1)?;
     Ok(())
 }
-
-pub fn felts_as_str(felts: &[StarkFelt]) -> String {
-    felts
-        .iter()
-        .map(|felt| {
-            as_cairo_short_string(&stark_felt_to_felt(*felt)).unwrap_or_else(|| felt.to_string())
-        })
-        .collect::<Vec<_>>()
-        .join(", ")
-}
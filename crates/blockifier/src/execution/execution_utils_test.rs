@@ -6,7 +6,9 @@ use num_traits::{One, Zero};
 use pretty_assertions::assert_eq;
 use starknet_api::hash::StarkFelt;
 
-use crate::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use crate::execution::execution_utils::{
+    felt_low_high_128, felt_lt_pow2, felt_to_stark_felt, stark_felt_to_felt,
+};
 
 fn starkfelt_to_felt_pairs() -> Vec<(StarkFelt, Felt252)> {
     // The STARK prime is 2 ^ 251 + 17 * 2 ^ 192 + 1.
@@ -45,3 +47,66 @@ fn test_felt_to_stark_felt() {
         assert_eq!(felt_to_stark_felt(&felt), equivalent_stark_felt);
     }
 }
+
+/// Returns felts at and around the powers of two, along with (deterministic) pseudo-random felts.
+fn sample_felts() -> Vec<Felt252> {
+    // Zero and PRIME - 1.
+    let mut felts = vec![Felt252::zero(), Felt252::zero() - Felt252::one()];
+    for n in 0..252_u32 {
+        let power_of_two = Felt252::one() << n;
+        felts.extend([
+            power_of_two.clone() - 1_usize,
+            power_of_two.clone(),
+            power_of_two + 1_usize,
+        ]);
+    }
+
+    // A xorshift generator.
+    let mut state = 0x2545f4914f6cdd1d_u64;
+    let mut next_digit = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        u32::try_from(state >> 32).unwrap()
+    };
+    for n_digits in 1..=8 {
+        felts.extend(
+            (0..100).map(|_| {
+                Felt252::from(BigUint::new((0..n_digits).map(|_| next_digit()).collect()))
+            }),
+        );
+    }
+
+    felts
+}
+
+#[test]
+fn test_felt_lt_pow2() {
+    for felt in sample_felts() {
+        for n in 0..=252_u32 {
+            assert_eq!(
+                felt_lt_pow2(&felt, n),
+                felt.to_biguint() < BigUint::one() << n,
+                "felt: {felt}, n: {n}."
+            );
+        }
+    }
+}
+
+#[test]
+fn test_felt_low_high_128() {
+    for felt in sample_felts() {
+        let (low, high) = felt_low_high_128(&felt);
+        let value = felt.to_biguint();
+        assert_eq!(BigUint::from(low), &value & BigUint::from(u128::MAX));
+        assert_eq!(high.to_biguint(), value >> 128_u32);
+    }
+}
+
+#[test]
+fn test_felt_to_stark_felt_matches_hex_representation() {
+    for felt in sample_felts() {
+        let hex = format!("{:#x}", felt.to_biguint());
+        assert_eq!(felt_to_stark_felt(&felt), StarkFelt::try_from(hex.as_str()).unwrap());
+    }
+}
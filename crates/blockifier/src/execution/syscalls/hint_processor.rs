@@ -1,5 +1,6 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use cairo_felt::Felt252;
 use cairo_lang_casm::hints::{Hint, StarknetHint};
@@ -30,7 +31,7 @@ use crate::execution::entry_point::{
     CallEntryPoint, CallInfo, CallType, EntryPointExecutionContext, ExecutionResources,
     OrderedEvent, OrderedL2ToL1Message,
 };
-use crate::execution::errors::EntryPointExecutionError;
+use crate::execution::errors::{EntryPointExecutionError, SyscallHintError};
 use crate::execution::execution_utils::{
     felt_range_from_ptr, felt_to_stark_felt, stark_felt_from_ptr, stark_felt_to_felt,
     write_maybe_relocatable, ReadOnlySegment, ReadOnlySegments,
@@ -39,10 +40,10 @@ use crate::execution::syscalls::secp::{
     secp256k1_add, secp256k1_get_point_from_x, secp256k1_get_xy, secp256k1_mul, secp256k1_new,
 };
 use crate::execution::syscalls::{
-    call_contract, deploy, emit_event, get_block_hash, get_execution_info, keccak, library_call,
-    library_call_l1_handler, replace_class, send_message_to_l1, storage_read, storage_write,
-    StorageReadResponse, StorageWriteResponse, SyscallRequest, SyscallRequestWrapper,
-    SyscallResponse, SyscallResponseWrapper, SyscallResult, SyscallSelector,
+    call_contract, deploy, emit_event, get_block_hash, get_class_hash_at, get_execution_info,
+    keccak, library_call, library_call_l1_handler, replace_class, send_message_to_l1, storage_read,
+    storage_write, StorageReadResponse, StorageWriteResponse, SyscallRequest,
+    SyscallRequestWrapper, SyscallResponse, SyscallResponseWrapper, SyscallResult, SyscallSelector,
 };
 use crate::state::errors::StateError;
 use crate::state::state_api::State;
@@ -76,14 +77,10 @@ pub enum SyscallExecutionError {
     VirtualMachineError(#[from] VirtualMachineError),
     #[error("Syscall error.")]
     SyscallError { error_data: Vec<StarkFelt> },
-}
-
-// Needed for custom hint implementations (in our case, syscall hints) which must comply with the
-// cairo-rs API.
-impl From<SyscallExecutionError> for HintError {
-    fn from(error: SyscallExecutionError) -> Self {
-        HintError::CustomHint(error.to_string().into())
-    }
+    /// An error of the VM API (e.g., of reading the hint's operands); crosses the hint boundary as
+    /// is.
+    #[error(transparent)]
+    HintError(#[from] HintError),
 }
 
 /// Error codes returned by Cairo 1.0 code.
@@ -91,6 +88,9 @@ impl From<SyscallExecutionError> for HintError {
 // "Out of gas";
 pub const OUT_OF_GAS_ERROR: &str =
     "0x000000000000000000000000000000000000000000004f7574206f6620676173";
+// "ENTRYPOINT_FAILED"; appended to the panic data of a failed inner call, as it is propagated.
+pub const ENTRYPOINT_FAILED_ERROR: &str =
+    "0x000000000000000000000000000000454e545259504f494e545f4641494c4544";
 // "Block number out of range";
 pub const BLOCK_NUMBER_OUT_OF_RANGE_ERROR: &str =
     "0x00000000000000426c6f636b206e756d626572206f7574206f662072616e6765";
@@ -100,6 +100,18 @@ pub const INVALID_INPUT_LENGTH_ERROR: &str =
 // "Invalid argument";
 pub const INVALID_ARGUMENT: &str =
     "0x00000000000000000000000000000000496e76616c696420617267756d656e74";
+// "Invalid storage key";
+pub const INVALID_STORAGE_KEY_ERROR: &str =
+    "0x00000000000000000000000000496e76616c69642073746f72616765206b6579";
+// "Too many events";
+pub const TOO_MANY_EVENTS_ERROR: &str =
+    "0x0000000000000000000000000000000000546f6f206d616e79206576656e7473";
+// "Too many L2 to L1 messages";
+pub const TOO_MANY_L2_TO_L1_MESSAGES_ERROR: &str =
+    "0x000000000000546f6f206d616e79204c3220746f204c31206d65737361676573";
+// "Too many storage writes";
+pub const TOO_MANY_STORAGE_WRITES_ERROR: &str =
+    "0x000000000000000000546f6f206d616e792073746f7261676520777269746573";
 
 /// Executes StarkNet syscalls (stateful protocol hints) during the execution of an entry point
 /// call.
@@ -123,12 +135,17 @@ pub struct SyscallHintProcessor<'a> {
     // Additional information gathered during execution.
     pub read_values: Vec<StarkFelt>,
     pub accessed_keys: HashSet<StorageKey>,
+    /// The syscalls invoked by the current execution (not including inner calls).
+    pub syscall_counter: SyscallCounter,
 
     // Secp256k1 points.
     pub secp256k1_points: Vec<ark_secp256k1::Affine>,
 
+    /// The error of the syscall which failed the run, if any (see `SyscallHintError`).
+    pub syscall_error: Option<SyscallHintError>,
+
     // Additional fields.
-    hints: &'a HashMap<String, Hint>,
+    hints: &'a HashMap<String, Arc<Hint>>,
     // Transaction info. and signature segments; allocated on-demand.
     execution_info_ptr: Option<Relocatable>,
 }
@@ -140,7 +157,7 @@ impl<'a> SyscallHintProcessor<'a> {
         context: &'a mut EntryPointExecutionContext,
         initial_syscall_ptr: Relocatable,
         call: CallEntryPoint,
-        hints: &'a HashMap<String, Hint>,
+        hints: &'a HashMap<String, Arc<Hint>>,
         read_only_segments: ReadOnlySegments,
     ) -> Self {
         SyscallHintProcessor {
@@ -155,9 +172,11 @@ impl<'a> SyscallHintProcessor<'a> {
             syscall_ptr: initial_syscall_ptr,
             read_values: vec![],
             accessed_keys: HashSet::new(),
+            syscall_counter: SyscallCounter::new(),
             hints,
             execution_info_ptr: None,
             secp256k1_points: vec![],
+            syscall_error: None,
         }
     }
 
@@ -190,16 +209,23 @@ impl<'a> SyscallHintProcessor<'a> {
         &mut self,
         vm: &mut VirtualMachine,
         hint: &StarknetHint,
-    ) -> HintExecutionResult {
+    ) -> SyscallResult<()> {
         let StarknetHint::SystemCall { system: syscall } = hint else {
             return Err(HintError::CustomHint(
                 "Test functions are unsupported on starknet.".into(),
-            ));
+            )
+            .into());
         };
         let initial_syscall_ptr = get_ptr_from_res_operand_unchecked(vm, syscall);
         self.verify_syscall_ptr(initial_syscall_ptr)?;
 
-        let selector = SyscallSelector::try_from(self.read_next_syscall_selector(vm)?)?;
+        let raw_selector = self.read_next_syscall_selector(vm)?;
+        let selector = SyscallSelector::try_from(raw_selector)
+            .map_err(|_| SyscallExecutionError::InvalidSyscallSelector(raw_selector))?;
+        if !self.context.block_context.versioned_constants.is_syscall_allowed(selector) {
+            return Err(SyscallExecutionError::InvalidSyscallSelector(raw_selector));
+        }
+        tracing::debug!(?selector, "Executing syscall.");
 
         // Keccak resource usage depends on the input length, so we increment the syscall count
         // in the syscall execution callback.
@@ -218,6 +244,9 @@ impl<'a> SyscallHintProcessor<'a> {
             SyscallSelector::GetBlockHash => {
                 self.execute_syscall(vm, get_block_hash, constants::GET_BLOCK_HASH_GAS_COST)
             }
+            SyscallSelector::GetClassHashAt => {
+                self.execute_syscall(vm, get_class_hash_at, constants::GET_CLASS_HASH_AT_GAS_COST)
+            }
             SyscallSelector::GetExecutionInfo => {
                 self.execute_syscall(vm, get_execution_info, constants::GET_EXECUTION_INFO_GAS_COST)
             }
@@ -259,10 +288,23 @@ impl<'a> SyscallHintProcessor<'a> {
             }
             _ => Err(HintError::UnknownHint(
                 format!("Unsupported syscall selector {selector:?}.").into(),
-            )),
+            )
+            .into()),
         }
     }
 
+    /// Returns the error of a syscall as a hint error, to cross the hint boundary; keeps the
+    /// syscall error in `syscall_error`, as the hint error only holds its message.
+    fn stash_syscall_error(&mut self, error: SyscallExecutionError) -> HintError {
+        if let SyscallExecutionError::HintError(error) = error {
+            return error;
+        }
+
+        let hint_error = HintError::CustomHint(error.to_string().into());
+        self.syscall_error = Some(error.into());
+        hint_error
+    }
+
     pub fn get_or_allocate_execution_info_segment(
         &mut self,
         vm: &mut VirtualMachine,
@@ -282,7 +324,7 @@ impl<'a> SyscallHintProcessor<'a> {
         vm: &mut VirtualMachine,
         execute_callback: ExecuteCallback,
         base_gas_cost: u64,
-    ) -> HintExecutionResult
+    ) -> SyscallResult<()>
     where
         Request: SyscallRequest + std::fmt::Debug,
         Response: SyscallResponse + std::fmt::Debug,
@@ -317,7 +359,7 @@ impl<'a> SyscallHintProcessor<'a> {
             Err(SyscallExecutionError::SyscallError { error_data: data }) => {
                 SyscallResponseWrapper::Failure { gas_counter: remaining_gas, error_data: data }
             }
-            Err(error) => return Err(error.into()),
+            Err(error) => return Err(error),
         };
 
         response.write(vm, &mut self.syscall_ptr)?;
@@ -334,6 +376,7 @@ impl<'a> SyscallHintProcessor<'a> {
     pub fn increment_syscall_count_by(&mut self, selector: &SyscallSelector, n: usize) {
         let syscall_count = self.resources.syscall_counter.entry(*selector).or_default();
         *syscall_count += n;
+        *self.syscall_counter.entry(*selector).or_default() += n;
     }
 
     fn increment_syscall_count(&mut self, selector: &SyscallSelector) {
@@ -368,7 +411,7 @@ impl<'a> SyscallHintProcessor<'a> {
         let block_info: Vec<MaybeRelocatable> = vec![
             Felt252::from(block_context.block_number.0).into(),
             Felt252::from(block_context.block_timestamp.0).into(),
-            stark_felt_to_felt(*block_context.sequencer_address.0.key()).into(),
+            stark_felt_to_felt(*self.context.sequencer_address().0.key()).into(),
         ];
         let block_info_segment_start_ptr = self.read_only_segments.allocate(vm, &block_info)?;
 
@@ -399,7 +442,7 @@ impl<'a> SyscallHintProcessor<'a> {
             tx_signature_start_ptr.into(),
             tx_signature_end_ptr.into(),
             stark_felt_to_felt(account_tx_context.transaction_hash.0).into(),
-            Felt252::from_bytes_be(self.context.block_context.chain_id.0.as_bytes()).into(),
+            stark_felt_to_felt(self.context.block_context.chain_id.to_felt()).into(),
             stark_felt_to_felt(account_tx_context.nonce.0).into(),
         ];
 
@@ -423,6 +466,12 @@ impl<'a> SyscallHintProcessor<'a> {
         key: StorageKey,
         value: StarkFelt,
     ) -> SyscallResult<StorageWriteResponse> {
+        let storage_key = (self.storage_address(), key);
+        if self.context.exceeds_max_n_storage_writes(&storage_key) {
+            return Err(syscall_error(TOO_MANY_STORAGE_WRITES_ERROR));
+        }
+        self.context.written_storage_keys.insert(storage_key);
+
         self.accessed_keys.insert(key);
         self.state.set_storage_at(self.storage_address(), key, value);
 
@@ -495,10 +544,17 @@ impl HintProcessorLogic for SyscallHintProcessor<'_> {
         hint_data: &Box<dyn Any>,
         _constants: &HashMap<String, Felt252>,
     ) -> HintExecutionResult {
-        let hint = hint_data.downcast_ref::<Hint>().ok_or(HintError::WrongHintData)?;
-        match hint {
+        if self.context.is_deadline_exceeded() {
+            return Err(HintError::CustomHint(
+                EntryPointExecutionError::ExecutionDeadlineExceeded.to_string().into(),
+            ));
+        }
+        let hint = hint_data.downcast_ref::<Arc<Hint>>().ok_or(HintError::WrongHintData)?;
+        match hint.as_ref() {
             Hint::Core(hint) => execute_core_hint_base(vm, exec_scopes, hint),
-            Hint::Starknet(hint) => self.execute_next_syscall(vm, hint),
+            Hint::Starknet(hint) => {
+                self.execute_next_syscall(vm, hint).map_err(|error| self.stash_syscall_error(error))
+            }
         }
     }
 
@@ -510,7 +566,7 @@ impl HintProcessorLogic for SyscallHintProcessor<'_> {
         _reference_ids: &HashMap<String, usize>,
         _references: &[HintReference],
     ) -> Result<Box<dyn Any>, VirtualMachineError> {
-        Ok(Box::new(self.hints[hint_code].clone()))
+        Ok(Box::new(Arc::clone(&self.hints[hint_code])))
     }
 }
 
@@ -628,3 +684,10 @@ pub fn write_segment(
 
     Ok(())
 }
+
+/// Returns a failure of the syscall with the given (short string) error, which the calling
+/// contract may handle.
+pub fn syscall_error(error: &str) -> SyscallExecutionError {
+    let error = StarkFelt::try_from(error).expect("Syscall errors must be valid felts.");
+    SyscallExecutionError::SyscallError { error_data: vec![error] }
+}
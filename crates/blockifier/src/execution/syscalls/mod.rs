@@ -25,10 +25,14 @@ use crate::execution::entry_point::{
     CallEntryPoint, CallType, ConstructorContext, MessageToL1, OrderedEvent, OrderedL2ToL1Message,
 };
 use crate::execution::execution_utils::{
-    execute_deployment, felt_from_ptr, felt_to_stark_felt, stark_felt_from_ptr, stark_felt_to_felt,
-    write_felt, write_maybe_relocatable, write_stark_felt, ReadOnlySegment,
+    execute_deployment, felt_from_ptr, felt_to_stark_felt, is_valid_storage_key,
+    stark_felt_from_ptr, stark_felt_to_felt, write_felt, write_maybe_relocatable, write_stark_felt,
+    ReadOnlySegment,
+};
+use crate::execution::syscalls::hint_processor::{
+    syscall_error, INVALID_INPUT_LENGTH_ERROR, INVALID_STORAGE_KEY_ERROR, OUT_OF_GAS_ERROR,
+    TOO_MANY_EVENTS_ERROR, TOO_MANY_L2_TO_L1_MESSAGES_ERROR,
 };
-use crate::execution::syscalls::hint_processor::{INVALID_INPUT_LENGTH_ERROR, OUT_OF_GAS_ERROR};
 use crate::transaction::transaction_utils::update_remaining_gas;
 
 pub mod hint_processor;
@@ -288,6 +292,9 @@ pub fn emit_event(
     _remaining_gas: &mut u64,
 ) -> SyscallResult<EmitEventResponse> {
     let execution_context = &mut syscall_handler.context;
+    if execution_context.reached_max_n_events() {
+        return Err(syscall_error(TOO_MANY_EVENTS_ERROR));
+    }
     let ordered_event =
         OrderedEvent { order: execution_context.n_emitted_events, event: request.content };
     syscall_handler.events.push(ordered_event);
@@ -358,6 +365,43 @@ pub fn get_block_hash(
     Ok(GetBlockHashResponse { block_hash })
 }
 
+// GetClassHashAt syscall.
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GetClassHashAtRequest {
+    pub contract_address: ContractAddress,
+}
+
+impl SyscallRequest for GetClassHashAtRequest {
+    fn read(vm: &VirtualMachine, ptr: &mut Relocatable) -> SyscallResult<GetClassHashAtRequest> {
+        let contract_address = ContractAddress::try_from(stark_felt_from_ptr(vm, ptr)?)?;
+        Ok(GetClassHashAtRequest { contract_address })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GetClassHashAtResponse {
+    pub class_hash: ClassHash,
+}
+
+impl SyscallResponse for GetClassHashAtResponse {
+    fn write(self, vm: &mut VirtualMachine, ptr: &mut Relocatable) -> WriteResponseResult {
+        write_stark_felt(vm, ptr, self.class_hash.0)?;
+        Ok(())
+    }
+}
+
+/// Returns the class hash of the contract at the given address; zero, if it is not deployed.
+pub fn get_class_hash_at(
+    request: GetClassHashAtRequest,
+    _vm: &mut VirtualMachine,
+    syscall_handler: &mut SyscallHintProcessor<'_>,
+    _remaining_gas: &mut u64,
+) -> SyscallResult<GetClassHashAtResponse> {
+    let class_hash = syscall_handler.state.get_class_hash_at(request.contract_address)?;
+    Ok(GetClassHashAtResponse { class_hash })
+}
+
 // GetExecutionInfo syscall.
 
 type GetExecutionInfoRequest = EmptyRequest;
@@ -512,6 +556,9 @@ pub fn send_message_to_l1(
     _remaining_gas: &mut u64,
 ) -> SyscallResult<SendMessageToL1Response> {
     let execution_context = &mut syscall_handler.context;
+    if execution_context.reached_max_n_l2_to_l1_messages() {
+        return Err(syscall_error(TOO_MANY_L2_TO_L1_MESSAGES_ERROR));
+    }
     let ordered_message_to_l1 = OrderedL2ToL1Message {
         order: execution_context.n_sent_messages_to_l1,
         message: request.message,
@@ -560,6 +607,9 @@ pub fn storage_read(
     syscall_handler: &mut SyscallHintProcessor<'_>,
     _remaining_gas: &mut u64,
 ) -> SyscallResult<StorageReadResponse> {
+    if !is_valid_storage_key(request.address) {
+        return Err(syscall_error(INVALID_STORAGE_KEY_ERROR));
+    }
     syscall_handler.get_contract_storage_at(request.address)
 }
 
@@ -592,6 +642,9 @@ pub fn storage_write(
     syscall_handler: &mut SyscallHintProcessor<'_>,
     _remaining_gas: &mut u64,
 ) -> SyscallResult<StorageWriteResponse> {
+    if !is_valid_storage_key(request.address) {
+        return Err(syscall_error(INVALID_STORAGE_KEY_ERROR));
+    }
     syscall_handler.set_contract_storage_at(request.address, request.value)
 }
 
@@ -640,10 +693,8 @@ pub fn keccak(
 
     if remainder != 0 {
         return Err(SyscallExecutionError::SyscallError {
-            error_data: vec![
-                StarkFelt::try_from(INVALID_INPUT_LENGTH_ERROR)
-                    .map_err(SyscallExecutionError::from)?,
-            ],
+            error_data: vec![StarkFelt::try_from(INVALID_INPUT_LENGTH_ERROR)
+                .map_err(SyscallExecutionError::from)?],
         });
     }
 
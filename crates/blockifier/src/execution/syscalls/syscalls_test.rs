@@ -1,8 +1,17 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use cairo_felt::Felt252;
+use cairo_lang_casm::hints::{Hint, StarknetHint};
+use cairo_lang_casm::operand::{CellRef, Register, ResOperand};
+use cairo_vm::hint_processor::hint_processor_definition::HintProcessorLogic;
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::relocatable::MaybeRelocatable;
 use cairo_vm::vm::runners::builtin_runner::RANGE_CHECK_BUILTIN_NAME;
-use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
+use cairo_vm::vm::runners::cairo_runner::{
+    ExecutionResources as VmExecutionResources, ResourceTracker,
+};
 use itertools::concat;
 use pretty_assertions::assert_eq;
 use starknet_api::core::{calculate_contract_address, ClassHash, ContractAddress, PatriciaKey};
@@ -10,28 +19,41 @@ use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
     Calldata, ContractAddressSalt, EthAddress, EventContent, EventData, EventKey, L2ToL1Payload,
+    TransactionHash, TransactionSignature,
 };
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
 use test_case::test_case;
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::abi::constants;
-use crate::execution::contract_class::ContractClassV0;
+use crate::block_context::{BlockContext, ChainId};
+use crate::execution::cairo1_execution::PreludeOutOfGasRun;
+use crate::execution::cairo1_execution::{initialize_execution_context, VmExecutionContext};
+use crate::execution::contract_class::{ContractClass, ContractClassV0};
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
 use crate::execution::entry_point::{
-    CallEntryPoint, CallExecution, CallInfo, CallType, MessageToL1, OrderedEvent,
-    OrderedL2ToL1Message, Retdata,
+    CallEntryPoint, CallExecution, CallInfo, CallType, EntryPointExecutionContext,
+    ExecutionResources, MessageToL1, OrderedEvent, OrderedL2ToL1Message, Retdata,
+};
+use crate::execution::errors::{EntryPointExecutionError, ExecutionErrorCategory};
+use crate::execution::execution_utils::{
+    felt_range_from_ptr, felt_to_stark_felt, stark_felt_to_felt,
 };
-use crate::execution::errors::EntryPointExecutionError;
 use crate::execution::syscalls::hint_processor::{
-    BLOCK_NUMBER_OUT_OF_RANGE_ERROR, OUT_OF_GAS_ERROR,
+    SyscallExecutionError, BLOCK_NUMBER_OUT_OF_RANGE_ERROR, INVALID_STORAGE_KEY_ERROR,
+    OUT_OF_GAS_ERROR, TOO_MANY_L2_TO_L1_MESSAGES_ERROR, TOO_MANY_STORAGE_WRITES_ERROR,
 };
+use crate::execution::syscalls::SyscallResult;
 use crate::retdata;
+use crate::selector;
 use crate::state::state_api::{State, StateReader};
 use crate::test_utils::{
-    create_deploy_test_state, create_test_state, trivial_external_entry_point,
-    CURRENT_BLOCK_NUMBER, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS, TEST_EMPTY_CONTRACT_CAIRO0_PATH,
-    TEST_EMPTY_CONTRACT_CLASS_HASH,
+    assert_constructor_semantics, create_deploy_test_state, create_test_state,
+    trivial_external_entry_point, CURRENT_BLOCK_NUMBER, CURRENT_BLOCK_TIMESTAMP, TEST_CLASS_HASH,
+    TEST_CONTRACT_ADDRESS, TEST_EMPTY_CONTRACT_CAIRO0_PATH, TEST_EMPTY_CONTRACT_CLASS_HASH,
+    TEST_SEQUENCER_ADDRESS,
 };
+use crate::transaction::objects::AccountTransactionContext;
+use crate::versioned_constants::{StarknetVersion, VersionedConstants};
 
 pub const REQUIRED_GAS_STORAGE_READ_WRITE_TEST: u64 = 34650;
 pub const REQUIRED_GAS_CALL_CONTRACT_TEST: u64 = 128080;
@@ -46,7 +68,7 @@ fn test_storage_read_write() {
     let calldata = calldata![key, value];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_storage_read_write"),
+        entry_point_selector: selector!("test_storage_read_write"),
         ..trivial_external_entry_point()
     };
     let storage_address = entry_point_call.storage_address;
@@ -64,12 +86,28 @@ fn test_storage_read_write() {
     assert_eq!(value_from_state, value);
 }
 
+/// Keys at or above the bound of the OS fail the write, recoverably.
+#[test_case("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00"; "bound")]
+#[test_case("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"; "max_key")]
+fn test_storage_write_out_of_range_key(key: &str) {
+    let mut state = create_test_state();
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata![stark_felt!(key), stark_felt!(18_u8)],
+        entry_point_selector: selector!("test_storage_read_write"),
+        ..trivial_external_entry_point()
+    };
+
+    let error = entry_point_call.execute_directly(&mut state).unwrap_err();
+    assert_matches!(error, EntryPointExecutionError::ExecutionFailed{ error_data }
+        if error_data == vec![stark_felt!(INVALID_STORAGE_KEY_ERROR)]);
+}
+
 #[test]
 fn test_call_contract() {
     let mut state = create_test_state();
 
-    let outer_entry_point_selector = selector_from_name("test_call_contract");
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let outer_entry_point_selector = selector!("test_call_contract");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let calldata = calldata![
         stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
         inner_entry_point_selector.0,       // Function selector.
@@ -108,7 +146,7 @@ fn test_emit_event() {
         .into(),
     );
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_emit_event"),
+        entry_point_selector: selector!("test_emit_event"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -141,7 +179,7 @@ fn test_get_block_hash() {
     // Positive flow.
     let calldata = calldata![block_number];
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_get_block_hash"),
+        entry_point_selector: selector!("test_get_block_hash"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -156,7 +194,7 @@ fn test_get_block_hash() {
     let block_number = stark_felt!(requested_block_number);
     let calldata = calldata![block_number];
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_get_block_hash"),
+        entry_point_selector: selector!("test_get_block_hash"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -165,13 +203,139 @@ fn test_get_block_hash() {
         if error_data == vec![stark_felt!(BLOCK_NUMBER_OUT_OF_RANGE_ERROR)]);
 }
 
+/// Before 0.13.1, `get_block_hash` fails as an unknown syscall.
+#[test_case(StarknetVersion::V0_13_0, false; "0.13.0")]
+#[test_case(StarknetVersion::V0_13_1, true; "0.13.1")]
+fn test_get_block_hash_by_version(starknet_version: StarknetVersion, is_available: bool) {
+    let mut state = create_test_state();
+    let block_number = stark_felt!(CURRENT_BLOCK_NUMBER - constants::STORED_BLOCK_HASH_BUFFER);
+    let block_context = BlockContext {
+        versioned_constants: VersionedConstants::shared(starknet_version).clone(),
+        ..BlockContext::create_for_testing()
+    };
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("test_get_block_hash"),
+        calldata: calldata![block_number],
+        ..trivial_external_entry_point()
+    };
+
+    let result = entry_point_call.execute_directly_given_block_context(&mut state, &block_context);
+    if is_available {
+        result.unwrap();
+    } else {
+        // The selector is the short string `GetBlockHash`.
+        let expected_error = SyscallExecutionError::InvalidSyscallSelector(stark_felt!(
+            "0x476574426c6f636b48617368"
+        ));
+        assert!(result.unwrap_err().to_string().contains(&expected_error.to_string()));
+    }
+}
+
+/// Executes a single syscall, given its selector (as a short string) and request, through the hint
+/// processor; returns the `response_size` felts of its response (following the remaining gas and
+/// the failure flag). For syscalls no entry point of the test contract invokes.
+fn execute_syscall_directly(
+    state: &mut dyn State,
+    block_context: &BlockContext,
+    selector: &str,
+    request: &[StarkFelt],
+    response_size: usize,
+) -> SyscallResult<Vec<StarkFelt>> {
+    let Ok(ContractClass::V1(contract_class)) =
+        state.get_compiled_contract_class(&class_hash!(TEST_CLASS_HASH))
+    else {
+        panic!("The test class must be a Cairo 1 class.");
+    };
+    let mut resources = ExecutionResources::default();
+    let mut context = EntryPointExecutionContext::new_invoke(
+        block_context,
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    let VmExecutionContext { mut vm, mut syscall_handler, initial_syscall_ptr, .. } =
+        initialize_execution_context(
+            CallEntryPoint {
+                entry_point_selector: selector!("test_storage_read_write"),
+                ..trivial_external_entry_point()
+            },
+            &contract_class,
+            state,
+            &mut resources,
+            &mut context,
+        )
+        .unwrap();
+
+    // The syscall pointer is read from `[fp]`.
+    vm.insert_value(vm.get_fp(), initial_syscall_ptr).unwrap();
+    let raw_selector = felt_to_stark_felt(&Felt252::from_bytes_be(selector.as_bytes()));
+    let syscall: Vec<MaybeRelocatable> =
+        concat(vec![vec![raw_selector, stark_felt!(constants::INITIAL_GAS_COST)], request.into()])
+            .into_iter()
+            .map(|felt| stark_felt_to_felt(felt).into())
+            .collect();
+    let response_ptr = vm.load_data(initial_syscall_ptr, &syscall).unwrap();
+    let hint = StarknetHint::SystemCall {
+        system: ResOperand::Deref(CellRef { register: Register::FP, offset: 0 }),
+    };
+    syscall_handler.execute_next_syscall(&mut vm, &hint)?;
+
+    let response = felt_range_from_ptr(&vm, response_ptr, 2 + response_size).unwrap();
+    assert_eq!(response[1], stark_felt!(0_u8), "The syscall failed: {response:?}.");
+    Ok(response[2..].to_vec())
+}
+
+/// Undeployed addresses have the zero class hash.
+#[test_case(TEST_CONTRACT_ADDRESS, TEST_CLASS_HASH; "deployed")]
+#[test_case("0x999", "0x0"; "undeployed")]
+fn test_get_class_hash_at(contract_address: &str, expected_class_hash: &str) {
+    let mut state = create_test_state();
+    let class_hash = execute_syscall_directly(
+        &mut state,
+        &BlockContext::create_for_testing(),
+        "GetClassHashAt",
+        &[stark_felt!(contract_address)],
+        1,
+    )
+    .unwrap();
+    assert_eq!(class_hash, vec![stark_felt!(expected_class_hash)]);
+}
+
+/// Before 0.13.3, `get_class_hash_at` fails as an unknown syscall.
+#[test_case(StarknetVersion::V0_13_2, false; "0.13.2")]
+#[test_case(StarknetVersion::V0_13_3, true; "0.13.3")]
+fn test_get_class_hash_at_by_version(starknet_version: StarknetVersion, is_available: bool) {
+    let mut state = create_test_state();
+    let block_context = BlockContext {
+        versioned_constants: VersionedConstants::shared(starknet_version).clone(),
+        ..BlockContext::create_for_testing()
+    };
+
+    let result = execute_syscall_directly(
+        &mut state,
+        &block_context,
+        "GetClassHashAt",
+        &[stark_felt!(TEST_CONTRACT_ADDRESS)],
+        1,
+    );
+    if is_available {
+        result.unwrap();
+    } else {
+        // The selector is the short string `GetClassHashAt`.
+        assert_matches!(
+            result,
+            Err(SyscallExecutionError::InvalidSyscallSelector(selector))
+            if selector == stark_felt!("0x476574436c617373486173684174")
+        );
+    }
+}
+
 #[test]
 fn test_keccak() {
     let mut state = create_test_state();
 
     let calldata = Calldata(vec![].into());
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_keccak"),
+        entry_point_selector: selector!("test_keccak"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -203,7 +367,7 @@ fn test_get_execution_info() {
         stark_felt!(1810_u16)  // Entry point selector.
     ];
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_get_execution_info"),
+        entry_point_selector: selector!("test_get_execution_info"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -212,11 +376,104 @@ fn test_get_execution_info() {
     entry_point_call.execute_directly(&mut state).unwrap_err();
 }
 
+/// The chain id in the transaction info is the encoding of the configured chain's name.
+#[test_case(ChainId::Mainnet, "0x534e5f4d41494e"; "Mainnet")]
+#[test_case(ChainId::Sepolia, "0x534e5f5345504f4c4941"; "Sepolia")]
+fn test_get_execution_info_chain_id(chain_id: ChainId, expected_chain_id: &str) {
+    let mut state = create_test_state();
+    let block_context = BlockContext { chain_id, ..BlockContext::create_for_testing() };
+    assert_eq!(block_context.chain_id.as_hex(), expected_chain_id);
+    // The contract expects the transaction hash as the signature.
+    let transaction_hash = TransactionHash(stark_felt!(1991_u16));
+    let account_tx_context = AccountTransactionContext {
+        transaction_hash,
+        signature: TransactionSignature(vec![transaction_hash.0]),
+        ..Default::default()
+    };
+
+    let mut execute_with_expected_chain_id = |expected_chain_id: StarkFelt| {
+        let selector = selector!("test_get_execution_info");
+        let calldata = calldata![
+            // Expected block info.
+            stark_felt!(CURRENT_BLOCK_NUMBER),    // Block number.
+            stark_felt!(CURRENT_BLOCK_TIMESTAMP), // Block timestamp.
+            stark_felt!(TEST_SEQUENCER_ADDRESS),  // Sequencer address.
+            // Expected transaction info.
+            stark_felt!(0_u8), // Transaction version.
+            stark_felt!(0_u8), // Account address.
+            stark_felt!(0_u8), // Max fee.
+            expected_chain_id, // Chain ID.
+            stark_felt!(0_u8), // Nonce.
+            // Expected call info.
+            stark_felt!(0_u8),                  // Caller address.
+            stark_felt!(TEST_CONTRACT_ADDRESS), // Storage address.
+            selector.0                          // Entry point selector.
+        ];
+        let entry_point_call = CallEntryPoint {
+            entry_point_selector: selector,
+            calldata,
+            ..trivial_external_entry_point()
+        };
+        let mut context =
+            EntryPointExecutionContext::new_invoke(&block_context, &account_tx_context).unwrap();
+        entry_point_call.execute(&mut state, &mut ExecutionResources::default(), &mut context)
+    };
+
+    execute_with_expected_chain_id(stark_felt!(expected_chain_id)).unwrap();
+    // The contract sees a different chain id than another chain's.
+    execute_with_expected_chain_id(ChainId::Other("SN_GOERLI".to_string()).to_felt()).unwrap_err();
+}
+
+/// Validation sees a zero sequencer address, regardless of the block's.
+#[test_case(true, "0x0"; "validate")]
+#[test_case(false, TEST_SEQUENCER_ADDRESS; "execute")]
+fn test_get_execution_info_sequencer_address(is_validate: bool, expected_sequencer_address: &str) {
+    let mut state = create_test_state();
+    let block_context = BlockContext::create_for_testing();
+    // The contract expects the transaction hash as the signature.
+    let transaction_hash = TransactionHash(stark_felt!(1991_u16));
+    let account_tx_context = AccountTransactionContext {
+        transaction_hash,
+        signature: TransactionSignature(vec![transaction_hash.0]),
+        ..Default::default()
+    };
+
+    let selector = selector!("test_get_execution_info");
+    let calldata = calldata![
+        // Expected block info.
+        stark_felt!(CURRENT_BLOCK_NUMBER),       // Block number.
+        stark_felt!(CURRENT_BLOCK_TIMESTAMP),    // Block timestamp.
+        stark_felt!(expected_sequencer_address), // Sequencer address.
+        // Expected transaction info.
+        stark_felt!(0_u8),                // Transaction version.
+        stark_felt!(0_u8),                // Account address.
+        stark_felt!(0_u8),                // Max fee.
+        block_context.chain_id.to_felt(), // Chain ID.
+        stark_felt!(0_u8),                // Nonce.
+        // Expected call info.
+        stark_felt!(0_u8),                  // Caller address.
+        stark_felt!(TEST_CONTRACT_ADDRESS), // Storage address.
+        selector.0                          // Entry point selector.
+    ];
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector,
+        calldata,
+        ..trivial_external_entry_point()
+    };
+    let mut context = if is_validate {
+        EntryPointExecutionContext::new_validate(&block_context, &account_tx_context)
+    } else {
+        EntryPointExecutionContext::new_invoke(&block_context, &account_tx_context).unwrap()
+    };
+
+    entry_point_call.execute(&mut state, &mut ExecutionResources::default(), &mut context).unwrap();
+}
+
 #[test]
 fn test_library_call() {
     let mut state = create_test_state();
 
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let calldata = calldata![
         stark_felt!(TEST_CLASS_HASH), // Class hash.
         inner_entry_point_selector.0, // Function selector.
@@ -225,7 +482,7 @@ fn test_library_call() {
         stark_felt!(91_u8)            // Calldata: value.
     ];
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_library_call"),
+        entry_point_selector: selector!("test_library_call"),
         calldata,
         class_hash: Some(class_hash!(TEST_CLASS_HASH)),
         ..trivial_external_entry_point()
@@ -246,8 +503,8 @@ fn test_nested_library_call() {
     let mut state = create_test_state();
 
     let (key, value) = (255_u64, 44_u64);
-    let outer_entry_point_selector = selector_from_name("test_library_call");
-    let inner_entry_point_selector = selector_from_name("test_storage_read_write");
+    let outer_entry_point_selector = selector!("test_library_call");
+    let inner_entry_point_selector = selector!("test_storage_read_write");
     let main_entry_point_calldata = calldata![
         stark_felt!(TEST_CLASS_HASH), // Class hash.
         outer_entry_point_selector.0, // Library call function selector.
@@ -258,7 +515,7 @@ fn test_nested_library_call() {
 
     // Create expected call info tree.
     let main_entry_point = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_nested_library_call"),
+        entry_point_selector: selector!("test_nested_library_call"),
         calldata: main_entry_point_calldata,
         class_hash: Some(class_hash!(TEST_CLASS_HASH)),
         initial_gas: 9999906600,
@@ -308,6 +565,11 @@ fn test_nested_library_call() {
         vm_resources: storage_entry_point_vm_resources.clone(),
         storage_read_values: vec![stark_felt!(value + 1)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key + 1))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 1),
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+        ]),
+        gas_metered: true,
         ..Default::default()
     };
     let library_call_vm_resources = VmExecutionResources {
@@ -324,6 +586,8 @@ fn test_nested_library_call() {
         },
         vm_resources: library_call_vm_resources,
         inner_calls: vec![nested_storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 1)]),
+        gas_metered: true,
         ..Default::default()
     };
     let storage_call_info = CallInfo {
@@ -336,6 +600,11 @@ fn test_nested_library_call() {
         vm_resources: storage_entry_point_vm_resources,
         storage_read_values: vec![stark_felt!(value)],
         accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(key))]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 1),
+            (DeprecatedSyscallSelector::StorageWrite, 1),
+        ]),
+        gas_metered: true,
         ..Default::default()
     };
 
@@ -353,6 +622,8 @@ fn test_nested_library_call() {
         },
         vm_resources: main_call_vm_resources,
         inner_calls: vec![library_call_info, storage_call_info],
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::LibraryCall, 2)]),
+        gas_metered: true,
         ..Default::default()
     };
 
@@ -368,7 +639,7 @@ fn test_replace_class() {
     // Replace with undeclared class hash.
     let entry_point_call = CallEntryPoint {
         calldata: calldata![stark_felt!(1234_u16)],
-        entry_point_selector: selector_from_name("test_replace_class"),
+        entry_point_selector: selector!("test_replace_class"),
         ..trivial_external_entry_point()
     };
     let error = entry_point_call.execute_directly(&mut state).unwrap_err().to_string();
@@ -381,7 +652,7 @@ fn test_replace_class() {
 
     let entry_point_call = CallEntryPoint {
         calldata: calldata![v0_class_hash.0],
-        entry_point_selector: selector_from_name("test_replace_class"),
+        entry_point_selector: selector!("test_replace_class"),
         ..trivial_external_entry_point()
     };
     let error = entry_point_call.execute_directly(&mut state).unwrap_err().to_string();
@@ -394,7 +665,7 @@ fn test_replace_class() {
     assert_eq!(state.get_class_hash_at(contract_address).unwrap(), old_class_hash);
     let entry_point_call = CallEntryPoint {
         calldata: calldata![new_class_hash.0],
-        entry_point_selector: selector_from_name("test_replace_class"),
+        entry_point_selector: selector!("test_replace_class"),
         ..trivial_external_entry_point()
     };
     assert_eq!(
@@ -410,7 +681,7 @@ fn test_secp256k1() {
 
     let calldata = Calldata(vec![].into());
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_secp256k1"),
+        entry_point_selector: selector!("test_secp256k1"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -431,7 +702,7 @@ fn test_send_message_to_l1() {
         concat(vec![vec![to_address, stark_felt!(payload.len() as u64)], payload.clone()]).into(),
     );
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_send_message_to_l1"),
+        entry_point_selector: selector!("test_send_message_to_l1"),
         calldata,
         ..trivial_external_entry_point()
     };
@@ -449,6 +720,70 @@ fn test_send_message_to_l1() {
     );
 }
 
+/// The limit applies to the messages of the whole transaction; versions predating it are unlimited.
+#[test_case(StarknetVersion::LATEST, false; "latest")]
+#[test_case(StarknetVersion::V0_13_0, true; "0.13.0")]
+fn test_send_message_to_l1_limit(starknet_version: StarknetVersion, is_allowed: bool) {
+    let mut state = create_test_state();
+    let block_context = BlockContext {
+        versioned_constants: VersionedConstants::shared(starknet_version).clone(),
+        ..BlockContext::create_for_testing()
+    };
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("test_send_message_to_l1"),
+        calldata: calldata![stark_felt!(1234_u16), stark_felt!(0_u8)],
+        ..trivial_external_entry_point()
+    };
+
+    let mut context = EntryPointExecutionContext::new_invoke(
+        &block_context,
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    // The preceding calls of the transaction sent the maximal number of messages.
+    context.n_sent_messages_to_l1 = VersionedConstants::latest().max_n_l2_to_l1_messages.unwrap();
+    let result =
+        entry_point_call.execute(&mut state, &mut ExecutionResources::default(), &mut context);
+    if is_allowed {
+        assert_eq!(result.unwrap().execution.l2_to_l1_messages.len(), 1);
+    } else {
+        assert_matches!(result.unwrap_err(), EntryPointExecutionError::ExecutionFailed{ error_data }
+            if error_data == vec![stark_felt!(TOO_MANY_L2_TO_L1_MESSAGES_ERROR)]);
+    }
+}
+
+/// Only writes to new cells count towards the limit.
+#[test_case(1234, true; "written cell")]
+#[test_case(1235, false; "new cell")]
+fn test_storage_write_limit(key: u16, is_allowed: bool) {
+    let mut state = create_test_state();
+    let mut block_context = BlockContext::create_for_testing();
+    block_context.versioned_constants_mut().max_n_storage_writes = Some(1);
+    let entry_point_call = CallEntryPoint {
+        entry_point_selector: selector!("test_storage_read_write"),
+        calldata: calldata![stark_felt!(key), stark_felt!(18_u8)],
+        ..trivial_external_entry_point()
+    };
+
+    let mut context = EntryPointExecutionContext::new_invoke(
+        &block_context,
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    context.written_storage_keys.insert((
+        contract_address!(TEST_CONTRACT_ADDRESS),
+        StorageKey::try_from(stark_felt!(1234_u16)).unwrap(),
+    ));
+    let result =
+        entry_point_call.execute(&mut state, &mut ExecutionResources::default(), &mut context);
+    if is_allowed {
+        result.unwrap();
+    } else {
+        assert_matches!(result.unwrap_err(), EntryPointExecutionError::ExecutionFailed{ error_data }
+            if error_data == vec![stark_felt!(TOO_MANY_STORAGE_WRITES_ERROR)]);
+    }
+}
+
 #[test_case(
     class_hash!(TEST_EMPTY_CONTRACT_CLASS_HASH),
     calldata![
@@ -509,6 +844,20 @@ fn test_send_message_to_l1() {
     ],
     Some("is unavailable for deployment.");
     "With constructor: Negative flow: deploy to the same address")]
+#[test_case(
+    class_hash!(TEST_CLASS_HASH),
+    calldata![
+        stark_felt!(TEST_CLASS_HASH),     // Class hash.
+        ContractAddressSalt::default().0, // Contract_address_salt.
+        stark_felt!(1_u8),                // Calldata length.
+        stark_felt!(1_u8),                // Calldata: arg1.
+        stark_felt!(0_u8)                 // deploy_from_zero.
+    ],
+    calldata![
+        stark_felt!(1_u8)                 // Calldata: arg1.
+    ],
+    Some("Input too short for arguments");
+    "With constructor: Negative flow: failing constructor")]
 fn test_deploy(
     class_hash: ClassHash,
     calldata: Calldata,
@@ -517,14 +866,16 @@ fn test_deploy(
 ) {
     let mut state = create_deploy_test_state();
     let entry_point_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("test_deploy"),
+        entry_point_selector: selector!("test_deploy"),
         calldata,
         ..trivial_external_entry_point()
     };
 
     if let Some(expected_error) = expected_error {
-        let error = entry_point_call.execute_directly(&mut state).unwrap_err().to_string();
-        assert!(error.contains(expected_error));
+        let error = entry_point_call.execute_directly(&mut state).unwrap_err();
+        assert!(error.to_string().contains(expected_error), "{error}");
+        // The deploying call fails; its transaction is reverted, rather than rejected.
+        assert_eq!(error.category(), ExecutionErrorCategory::Reverted);
         return;
     }
 
@@ -537,7 +888,12 @@ fn test_deploy(
     )
     .unwrap();
     let deploy_call = &entry_point_call.execute_directly(&mut state).unwrap().inner_calls[0];
-    assert_eq!(deploy_call.call.storage_address, contract_address);
+    assert_constructor_semantics(
+        deploy_call,
+        contract_address,
+        contract_address!(TEST_CONTRACT_ADDRESS),
+        class_hash,
+    );
     let mut retdata = retdata![];
     let gas_consumed = if constructor_calldata.0.is_empty() {
         0
@@ -561,7 +917,7 @@ fn test_out_of_gas() {
     let calldata = calldata![key, value];
     let entry_point_call = CallEntryPoint {
         calldata,
-        entry_point_selector: selector_from_name("test_storage_read_write"),
+        entry_point_selector: selector!("test_storage_read_write"),
         initial_gas: REQUIRED_GAS_STORAGE_READ_WRITE_TEST - 1,
         ..trivial_external_entry_point()
     };
@@ -569,3 +925,118 @@ fn test_out_of_gas() {
     assert_matches!(error, EntryPointExecutionError::ExecutionFailed{ error_data }
         if error_data == vec![stark_felt!(OUT_OF_GAS_ERROR)]);
 }
+
+/// A call given less gas than its entry point's prelude checks fails there; once such a failure is
+/// recorded in the block context, it is replayed without running the VM, with the same error and
+/// resources, for any calldata.
+#[test]
+fn test_out_of_gas_in_prelude() {
+    let mut state = create_test_state();
+    let calldata = calldata![stark_felt!(1234_u16), stark_felt!(18_u8)];
+    let entry_point_call = CallEntryPoint {
+        calldata: calldata.clone(),
+        entry_point_selector: selector!("test_storage_read_write"),
+        initial_gas: 1,
+        ..trivial_external_entry_point()
+    };
+    let class_hash = class_hash!(TEST_CLASS_HASH);
+    let ContractClass::V1(contract_class) = state.get_compiled_contract_class(&class_hash).unwrap()
+    else {
+        panic!("The test class must be a Cairo 1 class.");
+    };
+    let entry_point_pc = contract_class.get_entry_point(&entry_point_call).unwrap().pc();
+    let block_context = BlockContext::create_for_testing();
+    let prelude_out_of_gas_cache = block_context.prelude_out_of_gas_cache.clone();
+
+    // Returns the error data, the resources, the number of steps and the memory cells of the call,
+    // with the given calldata.
+    let mut execute = |block_context: &BlockContext, calldata: &Calldata| {
+        let mut resources = ExecutionResources::default();
+        let mut context = EntryPointExecutionContext::new_invoke(
+            block_context,
+            &AccountTransactionContext::default(),
+        )
+        .unwrap();
+        let initial_n_steps = context.vm_run_resources.get_n_steps().unwrap();
+        let call = CallEntryPoint { calldata: calldata.clone(), ..entry_point_call.clone() };
+        let error = call.execute(&mut state, &mut resources, &mut context).unwrap_err();
+        let EntryPointExecutionError::ExecutionFailed { error_data } = error else {
+            panic!("Unexpected error: {error}.");
+        };
+        let n_steps = initial_n_steps - context.vm_run_resources.get_n_steps().unwrap();
+        (error_data, resources, n_steps, context.n_memory_cells)
+    };
+
+    // The first call runs the prelude.
+    assert_eq!(prelude_out_of_gas_cache.get(class_hash, entry_point_pc), None);
+    let vm_run_output = execute(&block_context, &calldata);
+    let run = prelude_out_of_gas_cache.get(class_hash, entry_point_pc).unwrap();
+    let vm_resources = &run.vm_resources;
+    assert_eq!(vm_run_output.0, vec![stark_felt!(OUT_OF_GAS_ERROR)]);
+    assert_eq!(&vm_run_output.1.vm_resources, vm_resources);
+    assert_eq!(vm_run_output.2, vm_resources.n_steps);
+    assert_eq!(vm_resources.builtin_instance_counter[RANGE_CHECK_BUILTIN_NAME], 1);
+    assert!(vm_resources.n_steps > 0);
+    assert_eq!(vm_run_output.3, run.n_memory_cells + calldata.0.len());
+    assert!(run.n_memory_cells > 0);
+
+    // The following ones replay it; with their own calldata, as a VM run would (with an empty
+    // cache).
+    assert_eq!(execute(&block_context, &calldata), vm_run_output);
+    let longer_calldata = Calldata([calldata.0.as_slice(), &[stark_felt!(7_u8)]].concat().into());
+    let replay_output = execute(&block_context, &longer_calldata);
+    assert_eq!(replay_output, execute(&BlockContext::create_for_testing(), &longer_calldata));
+    assert_eq!(replay_output.3, vm_run_output.3 + 1);
+    let recorded_vm_resources =
+        VmExecutionResources { n_steps: vm_resources.n_steps + 1, ..vm_resources.clone() };
+    prelude_out_of_gas_cache.record(
+        class_hash,
+        entry_point_pc,
+        PreludeOutOfGasRun { vm_resources: recorded_vm_resources.clone(), ..run.clone() },
+    );
+    let (error_data, resources, n_steps, _) = execute(&block_context, &calldata);
+    assert_eq!(error_data, vm_run_output.0);
+    assert_eq!(resources.vm_resources, recorded_vm_resources);
+    assert_eq!(n_steps, vm_resources.n_steps + 1);
+}
+
+#[test]
+fn test_loaded_class_is_shared() {
+    let mut state = create_test_state();
+    let class_hash = class_hash!(TEST_CLASS_HASH);
+    let get_class = |state: &mut dyn State| match state.get_compiled_contract_class(&class_hash) {
+        Ok(ContractClass::V1(contract_class)) => contract_class,
+        _ => panic!("The test class must be a Cairo 1 class."),
+    };
+
+    // Loading a class again does not copy it.
+    let contract_class = get_class(&mut state);
+    assert!(Arc::ptr_eq(&contract_class.0, &get_class(&mut state).0));
+
+    // The hints are handed to the VM (on every run) without being copied.
+    let call = CallEntryPoint {
+        entry_point_selector: selector!("test_keccak"),
+        ..trivial_external_entry_point()
+    };
+    let mut resources = ExecutionResources::default();
+    let mut context = EntryPointExecutionContext::new_invoke(
+        &BlockContext::create_for_testing(),
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    let execution_context = initialize_execution_context(
+        call,
+        &contract_class,
+        &mut state,
+        &mut resources,
+        &mut context,
+    )
+    .unwrap();
+    for (hint_code, hint) in &contract_class.hints {
+        let hint_data = execution_context
+            .syscall_handler
+            .compile_hint(hint_code, &ApTracking::default(), &HashMap::new(), &[])
+            .unwrap();
+        assert!(Arc::ptr_eq(hint_data.downcast_ref::<Arc<Hint>>().unwrap(), hint));
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::execution::entry_point::CallInfo;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+#[cfg(test)]
+#[path = "trace_diff_test.rs"]
+pub mod test;
+
+/// The fields holding resources; these follow from the executed code (e.g., they change with the
+/// compiler or the OS), and are ignored under `TraceDiffOptions::ignore_resources`. The receipt
+/// holds the fee and gas charged for the resources.
+const RESOURCE_FIELDS: [&str; 3] = ["vm_resources", "actual_resources", "receipt"];
+/// Sets, serialized in an arbitrary order.
+const UNORDERED_FIELDS: [&str; 1] = ["accessed_storage_keys"];
+const EVENTS_FIELD: &str = "events";
+const EVENT_ORDER_FIELD: &str = "order";
+
+/// The tolerances of a trace comparison.
+#[derive(Clone, Debug)]
+pub struct TraceDiffOptions {
+    /// The comparison stops after this many divergences.
+    pub max_divergences: usize,
+    pub ignore_resources: bool,
+    /// Compares the events of each call as a multiset, regardless of their order.
+    pub ignore_event_order: bool,
+}
+
+impl Default for TraceDiffOptions {
+    fn default() -> Self {
+        Self { max_divergences: 10, ignore_resources: false, ignore_event_order: false }
+    }
+}
+
+/// A difference between two traces, at the given path (e.g.,
+/// `inner_calls[2].execution.events[0].event.data[3]`); a side is [None] if the path is missing
+/// from it (e.g., one call has fewer inner calls).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceDivergence {
+    pub path: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+impl fmt::Display for TraceDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let display_side = |side: &Option<Value>| match side {
+            Some(value) => value.to_string(),
+            None => "<missing>".to_string(),
+        };
+        write!(f, "{}: {} != {}", self.path, display_side(&self.left), display_side(&self.right))
+    }
+}
+
+impl CallInfo {
+    /// Walks both call trees in parallel, and returns their first divergences; the fields of each
+    /// struct are visited in alphabetical order.
+    pub fn diff(&self, other: &CallInfo, options: &TraceDiffOptions) -> Vec<TraceDivergence> {
+        diff_traces(self, other, options)
+    }
+}
+
+impl TransactionExecutionInfo {
+    /// As `CallInfo::diff`, over all the call infos and results of the transactions.
+    pub fn diff(
+        &self,
+        other: &TransactionExecutionInfo,
+        options: &TraceDiffOptions,
+    ) -> Vec<TraceDivergence> {
+        diff_traces(self, other, options)
+    }
+}
+
+/// Compares the traces (or any serializable values) in their JSON form, whose paths match the
+/// field names.
+pub(crate) fn diff_traces<T: Serialize>(
+    left: &T,
+    right: &T,
+    options: &TraceDiffOptions,
+) -> Vec<TraceDivergence> {
+    let to_value = |trace: &T| serde_json::to_value(trace).expect("Traces serialize to JSON.");
+    let mut divergences = Vec::new();
+    diff_values(String::new(), &to_value(left), &to_value(right), options, &mut divergences);
+    divergences.truncate(options.max_divergences);
+    divergences
+}
+
+fn diff_values(
+    path: String,
+    left: &Value,
+    right: &Value,
+    options: &TraceDiffOptions,
+    divergences: &mut Vec<TraceDivergence>,
+) {
+    if divergences.len() >= options.max_divergences || left == right {
+        return;
+    }
+
+    match (left, right) {
+        (Value::Object(left_fields), Value::Object(right_fields)) => {
+            let field_path = |field: &str| {
+                if path.is_empty() {
+                    field.to_string()
+                } else {
+                    format!("{path}.{field}")
+                }
+            };
+            for (field, left_value) in left_fields {
+                if options.ignore_resources && RESOURCE_FIELDS.contains(&field.as_str()) {
+                    continue;
+                }
+                let Some(right_value) = right_fields.get(field) else {
+                    push_divergence(field_path(field), Some(left_value), None, divergences);
+                    continue;
+                };
+                match normalize_field(field, left_value, right_value, options) {
+                    Some((left_value, right_value)) => diff_values(
+                        field_path(field),
+                        &left_value,
+                        &right_value,
+                        options,
+                        divergences,
+                    ),
+                    None => diff_values(
+                        field_path(field),
+                        left_value,
+                        right_value,
+                        options,
+                        divergences,
+                    ),
+                }
+            }
+            // Maps (e.g., of resources) may have keys on one side only.
+            for (field, right_value) in right_fields {
+                if !left_fields.contains_key(field) {
+                    push_divergence(field_path(field), None, Some(right_value), divergences);
+                }
+            }
+        }
+        (Value::Array(left_items), Value::Array(right_items)) => {
+            for index in 0..left_items.len().max(right_items.len()) {
+                let item_path = format!("{path}[{index}]");
+                match (left_items.get(index), right_items.get(index)) {
+                    (Some(left_item), Some(right_item)) => {
+                        diff_values(item_path, left_item, right_item, options, divergences)
+                    }
+                    (left_item, right_item) => {
+                        push_divergence(item_path, left_item, right_item, divergences)
+                    }
+                }
+                if divergences.len() >= options.max_divergences {
+                    return;
+                }
+            }
+        }
+        _ => push_divergence(path, Some(left), Some(right), divergences),
+    }
+}
+
+/// Brings the values of a field to a comparable form, if they need one: sorts sets, and, if the
+/// event order is ignored, sorts the events and drops their order numbers.
+fn normalize_field(
+    field: &str,
+    left: &Value,
+    right: &Value,
+    options: &TraceDiffOptions,
+) -> Option<(Value, Value)> {
+    let drop_field = if UNORDERED_FIELDS.contains(&field) {
+        None
+    } else if options.ignore_event_order && field == EVENTS_FIELD {
+        Some(EVENT_ORDER_FIELD)
+    } else {
+        return None;
+    };
+    let sort_items = |value: &Value| match value {
+        Value::Array(items) => {
+            let mut items = items.clone();
+            if let Some(drop_field) = drop_field {
+                for item in &mut items {
+                    if let Value::Object(fields) = item {
+                        fields.remove(drop_field);
+                    }
+                }
+            }
+            items.sort_by_cached_key(Value::to_string);
+            Value::Array(items)
+        }
+        value => value.clone(),
+    };
+
+    Some((sort_items(left), sort_items(right)))
+}
+
+fn push_divergence(
+    path: String,
+    left: Option<&Value>,
+    right: Option<&Value>,
+    divergences: &mut Vec<TraceDivergence>,
+) {
+    divergences.push(TraceDivergence { path, left: left.cloned(), right: right.cloned() });
+}
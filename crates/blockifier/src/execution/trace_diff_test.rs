@@ -0,0 +1,119 @@
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::{EventContent, EventData, EventKey};
+
+use crate::execution::entry_point::{CallExecution, CallInfo, OrderedEvent};
+use crate::execution::trace_diff::{TraceDiffOptions, TraceDivergence};
+use crate::transaction::objects::TransactionExecutionInfo;
+
+fn event(order: usize, data: &[u8]) -> OrderedEvent {
+    OrderedEvent {
+        order,
+        event: EventContent {
+            keys: vec![EventKey(stark_felt!(1_u8))],
+            data: EventData(data.iter().map(|&felt| stark_felt!(felt)).collect()),
+        },
+    }
+}
+
+/// A call with two inner calls; the second emits the given events.
+fn call_info(n_steps: usize, events: Vec<OrderedEvent>) -> CallInfo {
+    let inner_call = CallInfo {
+        execution: CallExecution { events, ..Default::default() },
+        ..Default::default()
+    };
+    CallInfo {
+        inner_calls: vec![CallInfo::default(), inner_call],
+        vm_resources: VmExecutionResources { n_steps, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn divergence(path: &str, left: serde_json::Value, right: serde_json::Value) -> TraceDivergence {
+    TraceDivergence { path: path.to_string(), left: Some(left), right: Some(right) }
+}
+
+#[test]
+fn test_call_info_diff() {
+    let left = call_info(100, vec![event(0, &[1, 2]), event(1, &[3])]);
+    let right = call_info(101, vec![event(0, &[1, 5]), event(1, &[3])]);
+    let event_divergence =
+        divergence("inner_calls[1].execution.events[0].event.data[1]", json!("0x2"), json!("0x5"));
+
+    assert_eq!(left.diff(&left, &TraceDiffOptions::default()), vec![]);
+    assert_eq!(
+        left.diff(&right, &TraceDiffOptions::default()),
+        vec![event_divergence.clone(), divergence("vm_resources.n_steps", json!(100), json!(101))]
+    );
+    let ignore_resources = TraceDiffOptions { ignore_resources: true, ..Default::default() };
+    assert_eq!(left.diff(&right, &ignore_resources), vec![event_divergence.clone()]);
+    let max_one = TraceDiffOptions { max_divergences: 1, ..Default::default() };
+    assert_eq!(left.diff(&right, &max_one), vec![event_divergence]);
+
+    // An extra inner call.
+    let mut right = call_info(100, vec![event(0, &[1, 2]), event(1, &[3])]);
+    right.inner_calls.push(CallInfo::default());
+    assert_eq!(
+        left.diff(&right, &TraceDiffOptions::default()),
+        vec![TraceDivergence {
+            path: "inner_calls[2]".to_string(),
+            left: None,
+            right: Some(serde_json::to_value(CallInfo::default()).unwrap()),
+        }]
+    );
+}
+
+#[test]
+fn test_call_info_diff_event_order() {
+    let left = call_info(100, vec![event(0, &[1]), event(1, &[2])]);
+    let right = call_info(100, vec![event(0, &[2]), event(1, &[1])]);
+
+    assert_eq!(
+        left.diff(&right, &TraceDiffOptions::default()),
+        vec![
+            divergence(
+                "inner_calls[1].execution.events[0].event.data[0]",
+                json!("0x1"),
+                json!("0x2")
+            ),
+            divergence(
+                "inner_calls[1].execution.events[1].event.data[0]",
+                json!("0x2"),
+                json!("0x1")
+            ),
+        ]
+    );
+    let ignore_event_order = TraceDiffOptions { ignore_event_order: true, ..Default::default() };
+    assert_eq!(left.diff(&right, &ignore_event_order), vec![]);
+}
+
+#[test]
+fn test_tx_execution_info_diff() {
+    let tx_execution_info = |call_info, n_event_keys| {
+        let mut tx_execution_info =
+            TransactionExecutionInfo { execute_call_info: Some(call_info), ..Default::default() };
+        tx_execution_info.actual_resources.n_event_keys = n_event_keys;
+        tx_execution_info
+    };
+    let left = tx_execution_info(call_info(100, vec![event(0, &[1, 2])]), 10);
+    let right = tx_execution_info(call_info(101, vec![event(0, &[1, 5])]), 11);
+
+    let event_divergence = divergence(
+        "execute_call_info.inner_calls[1].execution.events[0].event.data[1]",
+        json!("0x2"),
+        json!("0x5"),
+    );
+    assert_eq!(
+        left.diff(&right, &TraceDiffOptions::default()),
+        vec![
+            divergence("actual_resources.n_event_keys", json!(10), json!(11)),
+            event_divergence.clone(),
+            divergence("execute_call_info.vm_resources.n_steps", json!(100), json!(101)),
+        ]
+    );
+    let ignore_resources = TraceDiffOptions { ignore_resources: true, ..Default::default() };
+    assert_eq!(left.diff(&right, &ignore_resources), vec![event_divergence]);
+}
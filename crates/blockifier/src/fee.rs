@@ -1,5 +1,9 @@
 pub mod eth_gas_constants;
 pub mod fee_utils;
 pub mod gas_usage;
-pub mod os_resources;
 pub mod os_usage;
+pub mod transaction_resources;
+
+#[cfg(test)]
+#[path = "fee/fee_regression_test.rs"]
+mod fee_regression_test;
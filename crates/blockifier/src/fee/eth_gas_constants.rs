@@ -9,6 +9,8 @@ pub const GAS_PER_COLD_STORAGE_ACCESS: usize = 2100;
 pub const GAS_PER_NONZERO_TO_INT_STORAGE_SET: usize = 2900;
 pub const GAS_PER_COUNTER_DECREASE: usize =
     GAS_PER_COLD_STORAGE_ACCESS + GAS_PER_NONZERO_TO_INT_STORAGE_SET;
+// EIP-4844: a blob of 4096 field elements costs 2^17 (blob) gas.
+pub const DATA_GAS_PER_FIELD_ELEMENT: usize = (1 << 17) / 4096;
 pub const GAS_PER_LOG: usize = 375;
 pub const GAS_PER_LOG_TOPIC: usize = 375;
 pub const GAS_PER_LOG_DATA_BYTE: usize = 8;
@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee, TransactionHash, TransactionVersion};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::block_context::BlockContext;
+use crate::blockifier::bouncer::BouncerConfig;
+use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::execution::contract_class::ContractClassV1;
+use crate::execution::trace_diff::{diff_traces, TraceDiffOptions};
+use crate::selector;
+use crate::state::state_api::State;
+use crate::test_utils::transaction_builders::{DeclareTxArgs, DeployAccountTxArgs, InvokeTxArgs};
+use crate::test_utils::{
+    create_test_init_data, TestInitData, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+    TEST_CONTRACT_CAIRO1_PATH,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::L1HandlerTransaction;
+use crate::versioned_constants::StarknetVersion;
+
+const FEE_REGRESSIONS_DIR: &str = "resources/testing/fee_regressions";
+const FIX_COMMAND: &str = "FIX_FEE_REGRESSIONS=1 cargo test -p blockifier fee_regression";
+const RECIPIENT_ADDRESS: &str = "0x777";
+const TRANSFER_AMOUNT: u128 = 1000;
+
+/// A (well-known) sequence of transactions, executed in order on the test init data; the
+/// resources and fee of each are pinned by a golden file, per Starknet version.
+#[derive(Clone, Copy, Debug)]
+enum Scenario {
+    Transfer,
+    ApproveAndTransferFrom,
+    DeclareV1,
+    DeployAccount,
+    RevertedTransfer,
+    L1Handler,
+}
+
+impl Scenario {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Transfer => "transfer",
+            Self::ApproveAndTransferFrom => "approve_and_transfer_from",
+            Self::DeclareV1 => "declare_v1",
+            Self::DeployAccount => "deploy_account",
+            Self::RevertedTransfer => "reverted_transfer",
+            Self::L1Handler => "l1_handler",
+        }
+    }
+
+    /// Returns the transactions of the scenario; prepares the state for them (e.g., funds a
+    /// deployed account).
+    fn txs(&self, test_init_data: &mut TestInitData) -> Vec<Transaction> {
+        let account_address = test_init_data.cairo0_account_address;
+        let fee_token_address =
+            test_init_data.block_context.fee_token_addresses.eth_fee_token_address;
+        let (balance_low, _) = test_init_data.balance;
+        let mut erc20_invoke_tx = |entry_point_name: &str, args: &[StarkFelt]| {
+            let tx = test_init_data.invoke_tx(InvokeTxArgs {
+                sender_address: account_address,
+                calldata: erc20_execute_calldata(fee_token_address, entry_point_name, args),
+                ..Default::default()
+            });
+            Transaction::AccountTransaction(tx)
+        };
+        let recipient = stark_felt!(RECIPIENT_ADDRESS);
+        let amount = stark_felt!(TRANSFER_AMOUNT);
+
+        match self {
+            Self::Transfer => {
+                vec![erc20_invoke_tx("transfer", &[recipient, amount, stark_felt!(0_u8)])]
+            }
+            Self::ApproveAndTransferFrom => {
+                // The account approves itself as the spender.
+                let account = *account_address.0.key();
+                vec![
+                    erc20_invoke_tx("approve", &[account, amount, stark_felt!(0_u8)]),
+                    erc20_invoke_tx(
+                        "transferFrom",
+                        &[account, recipient, amount, stark_felt!(0_u8)],
+                    ),
+                ]
+            }
+            Self::RevertedTransfer => {
+                // Exceeds the balance of the account.
+                let amount = stark_felt!(balance_low + 1);
+                vec![erc20_invoke_tx("transfer", &[recipient, amount, stark_felt!(0_u8)])]
+            }
+            Self::DeclareV1 => {
+                let tx = test_init_data.declare_tx(DeclareTxArgs {
+                    sender_address: account_address,
+                    ..Default::default()
+                });
+                vec![Transaction::AccountTransaction(tx)]
+            }
+            Self::DeployAccount => {
+                let tx = test_init_data.deploy_account_tx(DeployAccountTxArgs {
+                    class_hash: test_init_data.cairo0_account_class_hash,
+                    ..Default::default()
+                });
+                test_init_data.fund(tx.contract_address);
+                vec![Transaction::AccountTransaction(AccountTransaction::DeployAccount(tx))]
+            }
+            Self::L1Handler => {
+                let (class_hash, contract_address) =
+                    (class_hash!(TEST_CLASS_HASH), contract_address!(TEST_CONTRACT_ADDRESS));
+                let state = &mut test_init_data.state;
+                state
+                    .set_contract_class(
+                        &class_hash,
+                        ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH).into(),
+                    )
+                    .unwrap();
+                state.set_class_hash_at(contract_address, class_hash).unwrap();
+                vec![Transaction::L1HandlerTransaction(L1HandlerTransaction {
+                    tx: starknet_api::transaction::L1HandlerTransaction {
+                        version: TransactionVersion::default(),
+                        nonce: Nonce::default(),
+                        contract_address,
+                        entry_point_selector: selector!("l1_handle"),
+                        calldata: calldata![stark_felt!(0x123_u16), stark_felt!(7_u8)],
+                    },
+                    tx_hash: TransactionHash::default(),
+                    paid_fee_on_l1: Fee(1),
+                })]
+            }
+        }
+    }
+}
+
+/// The calldata of the (Cairo 0) account's `__execute__`, calling the given ERC20 entry point.
+fn erc20_execute_calldata(
+    fee_token_address: ContractAddress,
+    entry_point_name: &str,
+    args: &[StarkFelt],
+) -> Calldata {
+    let selector = selector_from_name(entry_point_name);
+    let mut calldata =
+        vec![*fee_token_address.0.key(), selector.0, StarkFelt::from(args.len() as u64)];
+    calldata.extend_from_slice(args);
+    Calldata(calldata.into())
+}
+
+/// The pinned part of an execution: the fee and gas charged, and the resources they follow from.
+fn pinned_fields(tx_execution_info: &TransactionExecutionInfo) -> serde_json::Value {
+    serde_json::json!({
+        "reverted": tx_execution_info.is_reverted(),
+        "receipt": tx_execution_info.receipt,
+        "actual_resources": tx_execution_info.actual_resources,
+    })
+}
+
+/// Executes the scenario under the constants of the given version, and compares the pinned fields
+/// of its transactions to the golden file; rewrites the golden file if `FIX_FEE_REGRESSIONS` is
+/// set.
+#[rstest]
+fn test_fee_regression(
+    #[values(
+        Scenario::Transfer,
+        Scenario::ApproveAndTransferFrom,
+        Scenario::DeclareV1,
+        Scenario::DeployAccount,
+        Scenario::RevertedTransfer,
+        Scenario::L1Handler
+    )]
+    scenario: Scenario,
+    #[values(StarknetVersion::V0_13_0, StarknetVersion::LATEST)] starknet_version: StarknetVersion,
+) {
+    let mut test_init_data = create_test_init_data(
+        BlockContext::create_for_account_testing_with_version(starknet_version),
+    );
+    let txs = scenario.txs(&mut test_init_data);
+    let TestInitData { state, block_context, .. } = test_init_data;
+    let mut executor =
+        TransactionExecutor::new(state, block_context, BouncerConfig::max(), true).unwrap();
+    let actual = serde_json::Value::Array(
+        txs.into_iter().map(|tx| pinned_fields(&executor.execute(tx, true).unwrap().0)).collect(),
+    );
+    assert_eq!(
+        actual.as_array().unwrap().iter().any(|tx| tx["reverted"] == true),
+        matches!(scenario, Scenario::RevertedTransfer)
+    );
+
+    let golden_file_path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        FEE_REGRESSIONS_DIR,
+        starknet_version.as_str(),
+        &format!("{}.json", scenario.name()),
+    ]
+    .iter()
+    .collect();
+    if std::env::var("FIX_FEE_REGRESSIONS").is_ok() {
+        fs::create_dir_all(golden_file_path.parent().unwrap()).unwrap();
+        fs::write(&golden_file_path, serde_json::to_string_pretty(&actual).unwrap() + "\n")
+            .unwrap();
+    }
+    let raw_expected = fs::read_to_string(&golden_file_path).unwrap_or_else(|error| {
+        panic!("{}: {error}. To create it, run: {FIX_COMMAND}.", golden_file_path.display())
+    });
+    let expected: serde_json::Value = serde_json::from_str(&raw_expected).unwrap();
+
+    let divergences = diff_traces(
+        &actual,
+        &expected,
+        &TraceDiffOptions { max_divergences: usize::MAX, ..Default::default() },
+    );
+    assert!(
+        divergences.is_empty(),
+        "The resources of {scenario:?} on {} diverge from {} (actual != expected):\n{}\nTo fix \
+         the golden file, run: {FIX_COMMAND}.",
+        starknet_version.as_str(),
+        golden_file_path.display(),
+        divergences
+            .iter()
+            .map(|divergence| format!("  {divergence}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+}
@@ -5,12 +5,31 @@ use cairo_vm::vm::runners::builtin_runner::{
     BITWISE_BUILTIN_NAME, HASH_BUILTIN_NAME, POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME,
     SIGNATURE_BUILTIN_NAME,
 };
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee, TransactionVersion};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
 
 use crate::abi::constants;
-use crate::block_context::BlockContext;
-use crate::fee::fee_utils::calculate_l1_gas_by_vm_usage;
-use crate::transaction::errors::TransactionExecutionError;
-use crate::transaction::objects::ResourcesMapping;
+use crate::block_context::{
+    BlockContext, FeeTransferCalldataLayout, FeeTransferInterface, GasPrices,
+};
+use crate::execution::entry_point::{CallExecution, CallInfo, Retdata};
+use crate::fee::fee_utils::{
+    calculate_l1_gas_by_vm_usage, fee_transfer_call, get_fee_by_gas_vector,
+    verify_fee_transfer_retdata,
+};
+use crate::fee::transaction_resources::TransactionResources;
+use crate::retdata;
+use crate::selector;
+use crate::test_utils::TEST_ACCOUNT_CONTRACT_ADDRESS;
+use crate::transaction::constants::{FELT_FALSE, FELT_TRUE};
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+use crate::transaction::objects::{
+    AccountTransactionContext, FeeType, GasVector, ResourcesMapping,
+};
+use crate::utils::Rounding;
 
 fn get_vm_resource_usage() -> ResourcesMapping {
     ResourcesMapping(HashMap::from([
@@ -32,7 +51,7 @@ fn test_calculate_l1_gas_by_vm_usage() {
     // Verify calculation - in our case, n_steps is the heaviest resource.
     let l1_gas_by_vm_usage = vm_resource_usage.0.get(constants::N_STEPS_RESOURCE).unwrap();
     assert_eq!(
-        *l1_gas_by_vm_usage as f64,
+        GasVector { l1_gas: *l1_gas_by_vm_usage, l1_data_gas: 0 },
         calculate_l1_gas_by_vm_usage(&block_context, &vm_resource_usage).unwrap()
     );
 
@@ -44,3 +63,170 @@ fn test_calculate_l1_gas_by_vm_usage() {
         calculate_l1_gas_by_vm_usage(&block_context, &invalid_vm_resource_usage).unwrap_err();
     assert_matches!(error, TransactionExecutionError::CairoResourcesNotContainedInFeeCosts);
 }
+
+#[rstest]
+// 1000 * 0.0025 = 2.5 vs. 50 * 0.08 = 4.
+#[case::poseidon_dominates(1000, 50, 4)]
+// 10001 * 0.0025 = 25.0025 (rounded up) vs. 10 * 0.08 = 0.8.
+#[case::steps_dominate(10001, 10, 26)]
+fn test_calculate_l1_gas_by_vm_usage_fractional_weights(
+    #[case] n_steps: usize,
+    #[case] n_poseidon: usize,
+    #[case] expected_l1_gas: usize,
+) {
+    let mut block_context = BlockContext::create_for_account_testing();
+    block_context.versioned_constants_mut().vm_resource_fee_cost = HashMap::from([
+        (constants::N_STEPS_RESOURCE.to_string(), 0.0025),
+        (POSEIDON_BUILTIN_NAME.to_string(), 0.08),
+    ]);
+    let vm_resource_usage = ResourcesMapping(HashMap::from([
+        (constants::N_STEPS_RESOURCE.to_string(), n_steps),
+        (POSEIDON_BUILTIN_NAME.to_string(), n_poseidon),
+    ]));
+
+    assert_eq!(
+        calculate_l1_gas_by_vm_usage(&block_context, &vm_resource_usage).unwrap(),
+        GasVector { l1_gas: expected_l1_gas, l1_data_gas: 0 }
+    );
+
+    // A builtin missing from the weights table is not silently ignored.
+    let mut vm_resource_usage = vm_resource_usage;
+    vm_resource_usage.0.insert(HASH_BUILTIN_NAME.to_string(), 1);
+    assert_matches!(
+        calculate_l1_gas_by_vm_usage(&block_context, &vm_resource_usage).unwrap_err(),
+        TransactionExecutionError::CairoResourcesNotContainedInFeeCosts
+    );
+}
+
+/// Transactions of different versions in the same block pay by the prices of their fee tokens.
+#[test]
+fn test_fee_by_version() {
+    let block_context = BlockContext {
+        gas_prices: GasPrices::new(2, 3, 5, 7, false).unwrap(),
+        ..BlockContext::create_for_account_testing()
+    };
+    let gas_vector = GasVector { l1_gas: 100, l1_data_gas: 10 };
+
+    for (version, expected_fee) in [(1_u8, 100 * 2 + 10 * 5), (3_u8, 100 * 3 + 10 * 7)] {
+        let account_tx_context = AccountTransactionContext {
+            version: TransactionVersion(stark_felt!(version)),
+            ..Default::default()
+        };
+        assert_eq!(
+            get_fee_by_gas_vector(&block_context, gas_vector, &account_tx_context.fee_type())
+                .unwrap(),
+            Fee(expected_fee)
+        );
+    }
+}
+
+/// The fee is paid by the sender, through the fee token's `transfer`, with the recipient and the
+/// amount's (low, high) limbs as calldata.
+#[rstest]
+#[case::small_fee(Fee(7))]
+#[case::max_fee(Fee(u128::MAX))]
+fn test_fee_transfer_call(#[case] fee: Fee) {
+    let block_context = BlockContext::create_for_account_testing();
+    let sender_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let account_tx_context = AccountTransactionContext { sender_address, ..Default::default() };
+
+    let call = fee_transfer_call(&block_context, &account_tx_context, fee);
+    assert_eq!(call.entry_point_selector, selector!("transfer"));
+    assert_eq!(call.storage_address, block_context.fee_token_addresses.eth_fee_token_address);
+    assert_eq!(call.caller_address, sender_address);
+    assert_eq!(
+        call.calldata,
+        calldata![*block_context.sequencer_address.0.key(), stark_felt!(fee.0), stark_felt!(0_u8)]
+    );
+}
+
+/// The fee transfer follows the configured interface of the fee token.
+#[test]
+fn test_fee_transfer_call_by_interface() {
+    let block_context = BlockContext {
+        fee_transfer_interface: FeeTransferInterface {
+            selector_name: "transferFrom".to_string(),
+            calldata_layout: FeeTransferCalldataLayout::SenderRecipientAmount,
+        },
+        ..BlockContext::create_for_account_testing()
+    };
+    let sender_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let account_tx_context = AccountTransactionContext { sender_address, ..Default::default() };
+
+    let call = fee_transfer_call(&block_context, &account_tx_context, Fee(7));
+    assert_eq!(call.entry_point_selector, selector!("transferFrom"));
+    assert_eq!(
+        call.calldata,
+        calldata![
+            *sender_address.0.key(),
+            *block_context.sequencer_address.0.key(),
+            stark_felt!(7_u8),
+            stark_felt!(0_u8)
+        ]
+    );
+}
+
+#[rstest]
+#[case::success(retdata![stark_felt!(FELT_TRUE)], true)]
+#[case::false_return(retdata![stark_felt!(FELT_FALSE)], false)]
+#[case::no_return(retdata![], false)]
+#[case::extra_return(
+    retdata![stark_felt!(FELT_TRUE), stark_felt!(FELT_TRUE)],
+    false
+)]
+fn test_verify_fee_transfer_retdata(#[case] retdata: Retdata, #[case] is_success: bool) {
+    let call_info =
+        CallInfo { execution: CallExecution::from_retdata(retdata), ..Default::default() };
+    let result = verify_fee_transfer_retdata(&call_info);
+    if is_success {
+        result.unwrap();
+    } else {
+        assert_matches!(
+            result,
+            Err(TransactionExecutionError::TransactionFeeError(
+                TransactionFeeError::FeeTransferFailed { .. }
+            ))
+        );
+    }
+}
+
+/// Fees of Cairo steps whose L1 gas lands on a .5 boundary, by each rounding mode; the gas price is
+/// 100 wei.
+#[rstest]
+// 100 * 0.005 = 0.5.
+#[case::half(100, 0.005, Rounding::Ceil, 100)]
+#[case::half_floored(100, 0.005, Rounding::Floor, 0)]
+// 2500 * 0.005 = 12.5.
+#[case::twelve_and_a_half(2500, 0.005, Rounding::Ceil, 1300)]
+#[case::twelve_and_a_half_floored(2500, 0.005, Rounding::Floor, 1200)]
+// 100 * 0.07 = 7 (7.000000000000001 in floating point).
+#[case::exact(100, 0.07, Rounding::Ceil, 700)]
+#[case::exact_floored(100, 0.07, Rounding::Floor, 700)]
+fn test_fee_rounding(
+    #[case] n_steps: usize,
+    #[case] step_weight: f64,
+    #[case] gas_rounding: Rounding,
+    #[case] expected_fee: u128,
+) {
+    let mut block_context = BlockContext {
+        gas_prices: GasPrices::new(100, 100, 1, 1, false).unwrap(),
+        ..BlockContext::create_for_account_testing()
+    };
+    let versioned_constants = block_context.versioned_constants_mut();
+    versioned_constants.vm_resource_fee_cost =
+        HashMap::from([(constants::N_STEPS_RESOURCE.to_string(), step_weight)]);
+    versioned_constants.gas_rounding = gas_rounding;
+    let tx_resources = TransactionResources {
+        vm_resources: ResourcesMapping(HashMap::from([(
+            constants::N_STEPS_RESOURCE.to_string(),
+            n_steps,
+        )])),
+        ..Default::default()
+    };
+
+    let gas_vector = tx_resources.to_gas_vector(&block_context).unwrap();
+    assert_eq!(
+        get_fee_by_gas_vector(&block_context, gas_vector, &FeeType::Eth).unwrap(),
+        Fee(expected_fee)
+    );
+}
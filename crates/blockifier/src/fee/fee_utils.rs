@@ -1,59 +1,138 @@
 use std::collections::HashSet;
 
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::Fee;
 
 use crate::abi::constants;
 use crate::block_context::BlockContext;
-use crate::transaction::errors::TransactionExecutionError;
-use crate::transaction::objects::{ResourcesMapping, TransactionExecutionResult};
+use crate::execution::entry_point::{CallEntryPoint, CallInfo, CallType};
+use crate::transaction::constants::FELT_TRUE;
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+use crate::transaction::objects::{
+    AccountTransactionContext, FeeType, GasVector, ResourcesMapping, TransactionExecutionResult,
+};
+use crate::utils::{u64_from_ratio, Rounding};
 
 #[cfg(test)]
 #[path = "fee_test.rs"]
 pub mod test;
 
-pub fn extract_l1_gas_and_vm_usage(resources: &ResourcesMapping) -> (usize, ResourcesMapping) {
-    let mut vm_resource_usage = resources.0.clone();
-    let l1_gas_usage = vm_resource_usage
-        .remove(constants::GAS_USAGE)
-        .expect("`ResourcesMapping` does not have the key `l1_gas_usage`.");
+// Fractional gas weights (e.g., the L1 gas of a Cairo step) are decimals with at most this many
+// fractional digits; scaling them to integers makes the weighted amounts exact, so that they are
+// rounded only once.
+const GAS_WEIGHT_SCALE: u64 = 1_000_000;
 
-    (l1_gas_usage, ResourcesMapping(vm_resource_usage))
+/// Returns the L1 gas of the given amount of a resource with the given (fractional) weight, rounded
+/// by the given mode. All conversions of resources to gas by fractional weights go through here.
+pub fn get_weighted_gas(
+    amount: usize,
+    weight: f64,
+    rounding: Rounding,
+) -> TransactionExecutionResult<usize> {
+    let scaled_weight = (weight * GAS_WEIGHT_SCALE as f64).round() as u64;
+    let scaled_gas =
+        (amount as u64).checked_mul(scaled_weight).ok_or(TransactionFeeError::GasAmountOverflow)?;
+
+    Ok(u64_from_ratio(scaled_gas, GAS_WEIGHT_SCALE, rounding) as usize)
 }
 
 /// Calculates the L1 gas consumed when submitting the underlying Cairo program to SHARP.
 /// I.e., returns the heaviest Cairo resource weight (in terms of L1 gas), as the size of
 /// a proof is determined similarly - by the (normalized) largest segment.
+/// The weights are fractional (e.g., a fraction of a gas unit per step), so the result is
+/// rounded, by the rounding mode of the block.
 pub fn calculate_l1_gas_by_vm_usage(
     block_context: &BlockContext,
     vm_resource_usage: &ResourcesMapping,
-) -> TransactionExecutionResult<f64> {
-    let vm_resource_fee_costs = &block_context.vm_resource_fee_cost;
+) -> TransactionExecutionResult<GasVector> {
+    let vm_resource_fee_costs = &block_context.versioned_constants.vm_resource_fee_cost;
     let vm_resource_names = HashSet::<&String>::from_iter(vm_resource_usage.0.keys());
     if !vm_resource_names.is_subset(&HashSet::from_iter(vm_resource_fee_costs.keys())) {
         return Err(TransactionExecutionError::CairoResourcesNotContainedInFeeCosts);
     };
 
-    // Convert Cairo usage to L1 gas usage.
-    let vm_l1_gas_usage = vm_resource_fee_costs
-        .iter()
-        .map(|(key, resource_val)| {
-            (*resource_val) * vm_resource_usage.0.get(key).cloned().unwrap_or_default() as f64
-        })
-        .fold(f64::NAN, f64::max);
+    // Convert Cairo usage to L1 gas usage; as rounding is monotonic, rounding each resource's gas
+    // before taking the maximum is the same as rounding the maximum.
+    let mut vm_l1_gas_usage = 0;
+    for (key, weight) in vm_resource_fee_costs.iter() {
+        let amount = vm_resource_usage.0.get(key).copied().unwrap_or_default();
+        vm_l1_gas_usage = vm_l1_gas_usage.max(get_weighted_gas(
+            amount,
+            *weight,
+            block_context.versioned_constants.gas_rounding,
+        )?);
+    }
 
-    Ok(vm_l1_gas_usage)
+    Ok(GasVector { l1_gas: vm_l1_gas_usage, l1_data_gas: 0 })
 }
 
-/// Calculates the fee that should be charged, given execution resources.
-/// We add the l1_gas_usage (which may include, for example, the direct cost of L2-to-L1 messages)
-/// to the gas consumed by Cairo VM resource and multiply by the L1 gas price.
-pub fn calculate_tx_fee(
-    resources: &ResourcesMapping,
+/// Returns the fee of the given gas vector, priced by the L1 gas and data gas prices of the given
+/// fee type; as the gas amounts are already rounded up, no further rounding is needed.
+/// All fees are computed by this function (or by `get_saturated_fee_by_gas_vector`); a fee that
+/// does not fit in 128 bits is an error.
+pub fn get_fee_by_gas_vector(
     block_context: &BlockContext,
+    gas_vector: GasVector,
+    fee_type: &FeeType,
 ) -> TransactionExecutionResult<Fee> {
-    let (l1_gas_usage, vm_resources) = extract_l1_gas_and_vm_usage(resources);
-    let l1_gas_by_vm_usage = calculate_l1_gas_by_vm_usage(block_context, &vm_resources)?;
-    let total_l1_gas_usage = l1_gas_usage as f64 + l1_gas_by_vm_usage;
+    Ok(gas_vector
+        .cost(&block_context.gas_prices, fee_type)
+        .ok_or(TransactionFeeError::FeeOverflow)?)
+}
+
+/// Same as `get_fee_by_gas_vector`, but saturates at the maximal fee on overflow. An overflowing fee
+/// exceeds any max fee; to be used only for fees that are bounded by the max fee before they are
+/// charged.
+pub fn get_saturated_fee_by_gas_vector(
+    block_context: &BlockContext,
+    gas_vector: GasVector,
+    fee_type: &FeeType,
+) -> Fee {
+    get_fee_by_gas_vector(block_context, gas_vector, fee_type).unwrap_or(Fee(u128::MAX))
+}
+
+/// Returns the call that pays the given fee to the sequencer: the fee token's transfer entry point
+/// (by default, `transfer(recipient, amount)`; see `FeeTransferInterface`), called by the sender.
+pub fn fee_transfer_call(
+    block_context: &BlockContext,
+    account_tx_context: &AccountTransactionContext,
+    actual_fee: Fee,
+) -> CallEntryPoint {
+    // The least significant 128 bits of the amount transferred.
+    let lsb_amount = StarkFelt::from(actual_fee.0);
+    // The most significant 128 bits of the amount transferred; zero, as fees are 128-bit integers.
+    let msb_amount = StarkFelt::from(0_u8);
+
+    let fee_transfer_interface = &block_context.fee_transfer_interface;
+
+    CallEntryPoint {
+        class_hash: None,
+        code_address: None,
+        entry_point_type: EntryPointType::External,
+        entry_point_selector: fee_transfer_interface.selector(),
+        calldata: fee_transfer_interface.calldata_layout.calldata(
+            account_tx_context.sender_address,
+            block_context.sequencer_address,
+            (lsb_amount, msb_amount),
+        ),
+        storage_address: block_context.fee_token_address(&account_tx_context.fee_type()),
+        caller_address: account_tx_context.sender_address,
+        call_type: CallType::Call,
+        // The fee-token contract is a Cairo 0 contract, hence the initial gas is irrelevant.
+        initial_gas: constants::INITIAL_GAS_COST,
+    }
+}
+
+/// Checks that the fee transfer returned `TRUE`; a token may signal a failed transfer by returning
+/// `FALSE`, rather than by failing.
+pub fn verify_fee_transfer_retdata(
+    fee_transfer_call_info: &CallInfo,
+) -> TransactionExecutionResult<()> {
+    let retdata = &fee_transfer_call_info.execution.retdata;
+    if retdata.0 != [StarkFelt::from(FELT_TRUE)] {
+        return Err(TransactionFeeError::FeeTransferFailed { retdata: retdata.clone() }.into());
+    }
 
-    Ok(Fee(total_l1_gas_usage.ceil() as u128 * block_context.gas_price))
+    Ok(())
 }
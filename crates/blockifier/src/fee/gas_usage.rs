@@ -4,12 +4,13 @@ use starknet_api::transaction::Fee;
 
 use crate::abi::constants;
 use crate::block_context::BlockContext;
+use crate::execution::entry_point::MessageToL1;
 use crate::fee::eth_gas_constants;
-use crate::fee::fee_utils::calculate_tx_fee;
-use crate::fee::os_resources::OS_RESOURCES;
+use crate::fee::fee_utils::{get_fee_by_gas_vector, get_weighted_gas};
+use crate::fee::transaction_resources::TransactionResources;
 use crate::state::cached_state::StateChangesCount;
 use crate::transaction::account_transaction::AccountTransaction;
-use crate::transaction::objects::{ResourcesMapping, TransactionExecutionResult};
+use crate::transaction::objects::{GasVector, ResourcesMapping, TransactionExecutionResult};
 
 #[cfg(test)]
 #[path = "gas_usage_test.rs"]
@@ -18,19 +19,63 @@ pub mod test;
 /// Returns an estimation of the L1 gas amount that will be used (by StarkNet's update state and
 /// the verifier) following the addition of a transaction with the given parameters to a batch;
 /// e.g., a message from L2 to L1 is followed by a storage write operation in StarkNet L1 contract
-/// which requires gas.
+/// which requires gas. The state diff is assumed to be published in calldata.
 pub fn calculate_tx_gas_usage(
     l2_to_l1_payloads_length: &[usize],
     state_changes_count: StateChangesCount,
     l1_handler_payload_size: Option<usize>,
 ) -> usize {
+    calculate_tx_gas_vector(
+        l2_to_l1_payloads_length,
+        state_changes_count,
+        l1_handler_payload_size,
+        false,
+    )
+    .l1_gas
+}
+
+/// Returns an estimation of the L1 gas and data gas amounts that will be used following the
+/// addition of a transaction with the given parameters to a batch (see `calculate_tx_gas_usage`);
+/// if `use_kzg_da` is set, the state diff is published in blobs, and paid for in data gas.
+pub fn calculate_tx_gas_vector(
+    l2_to_l1_payloads_length: &[usize],
+    state_changes_count: StateChangesCount,
+    l1_handler_payload_size: Option<usize>,
+    use_kzg_da: bool,
+) -> GasVector {
+    let messages_gas_cost =
+        get_messages_gas_cost(l2_to_l1_payloads_length, l1_handler_payload_size);
+
+    // Calculate the effect of the transaction on the output data availability segment.
+    let da_gas_cost = get_da_gas_cost(state_changes_count, use_kzg_da);
+
+    GasVector {
+        l1_gas: messages_gas_cost.l1_gas + da_gas_cost.l1_gas,
+        l1_data_gas: da_gas_cost.l1_data_gas,
+    }
+}
+
+/// Returns the L1 gas consumed by the given L2-to-L1 messages, and by the L1-to-L2 message consumed
+/// by an L1 handler with the given payload size (if any).
+pub fn message_l1_gas(
+    l2_to_l1_messages: &[MessageToL1],
+    l1_handler_payload_size: Option<usize>,
+) -> GasVector {
+    let l2_to_l1_payloads_length: Vec<usize> =
+        l2_to_l1_messages.iter().map(|message| message.payload.0.len()).collect();
+    get_messages_gas_cost(&l2_to_l1_payloads_length, l1_handler_payload_size)
+}
+
+/// Returns the L1 gas consumed by messages with the given payload lengths (see `message_l1_gas`):
+/// their output, their storage counters in the StarkNet L1 contract, and their log emissions.
+pub fn get_messages_gas_cost(
+    l2_to_l1_payloads_length: &[usize],
+    l1_handler_payload_size: Option<usize>,
+) -> GasVector {
     // Calculate the addition of the transaction to the output messages segment.
     let residual_message_segment_length =
         get_message_segment_length(l2_to_l1_payloads_length, l1_handler_payload_size);
 
-    // Calculate the effect of the transaction on the output data availability segment.
-    let residual_onchain_data_segment_length = get_onchain_data_segment_length(state_changes_count);
-
     let n_l2_to_l1_messages = l2_to_l1_payloads_length.len();
     let n_l1_to_l2_messages = usize::from(l1_handler_payload_size.is_some());
 
@@ -46,11 +91,73 @@ pub fn calculate_tx_gas_usage(
     + get_consumed_message_to_l2_emissions_cost(l1_handler_payload_size)
     + get_log_message_to_l1_emissions_cost(l2_to_l1_payloads_length);
 
-    let sharp_gas_usage = residual_message_segment_length
-        * eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD
-        + residual_onchain_data_segment_length * eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD;
+    let sharp_gas_usage =
+        residual_message_segment_length * eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD;
+
+    GasVector { l1_gas: starknet_gas_usage + sharp_gas_usage, l1_data_gas: 0 }
+}
+
+/// Returns the L1 gas charged for emitted events with the given total numbers of keys and data
+/// felts.
+pub fn get_events_gas_cost(
+    n_event_keys: usize,
+    n_event_data_felts: usize,
+    block_context: &BlockContext,
+) -> GasVector {
+    let versioned_constants = &block_context.versioned_constants;
+    GasVector {
+        l1_gas: n_event_keys * versioned_constants.event_key_gas_cost
+            + n_event_data_felts * versioned_constants.event_data_gas_cost,
+        l1_data_gas: 0,
+    }
+}
+
+/// Returns the L1 gas charged for the calldata and signature of a transaction with the given
+/// lengths.
+pub fn get_tx_data_gas_cost(
+    calldata_length: usize,
+    signature_length: usize,
+    block_context: &BlockContext,
+) -> GasVector {
+    GasVector {
+        l1_gas: (calldata_length + signature_length)
+            * block_context.versioned_constants.tx_data_felt_gas_cost,
+        l1_data_gas: 0,
+    }
+}
+
+/// Returns the L1 gas charged for the given amount of consumed L2 gas (rounded up).
+pub fn get_l2_gas_cost(
+    l2_gas: usize,
+    block_context: &BlockContext,
+) -> TransactionExecutionResult<GasVector> {
+    let versioned_constants = &block_context.versioned_constants;
+    Ok(GasVector {
+        l1_gas: get_weighted_gas(
+            l2_gas,
+            versioned_constants.l2_gas_cost,
+            versioned_constants.gas_rounding,
+        )?,
+        l1_data_gas: 0,
+    })
+}
 
-    starknet_gas_usage + sharp_gas_usage
+/// Returns the cost of publishing the state diff of a transaction with the given state changes;
+/// in data gas if it is published in blobs, and in L1 gas otherwise.
+pub fn get_da_gas_cost(state_changes_count: StateChangesCount, use_kzg_da: bool) -> GasVector {
+    let onchain_data_segment_length = get_onchain_data_segment_length(state_changes_count);
+    if use_kzg_da {
+        GasVector {
+            l1_gas: 0,
+            l1_data_gas: onchain_data_segment_length
+                * eth_gas_constants::DATA_GAS_PER_FIELD_ELEMENT,
+        }
+    } else {
+        GasVector {
+            l1_gas: onchain_data_segment_length * eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD,
+            l1_data_gas: 0,
+        }
+    }
 }
 
 /// Returns the number of felts added to the output data availability segment as a result of adding
@@ -135,50 +242,48 @@ fn get_event_emission_cost(n_topics: usize, data_length: usize) -> usize {
         + data_length * eth_gas_constants::GAS_PER_LOG_DATA_WORD
 }
 
-/// Return an estimated lower bound for the fee on an account transaction.
+/// Return an estimated lower bound for the fee on an account transaction: that of its OS overhead,
+/// calldata and signature, and the state changes every such transaction makes.
 pub fn estimate_minimal_fee(
     block_context: &BlockContext,
     tx: &AccountTransaction,
 ) -> TransactionExecutionResult<Fee> {
     // TODO(Dori, 1/8/2023): Give names to the constant VM step estimates and regression-test them.
-    let os_steps_for_type = OS_RESOURCES
-        .execute_txs_inner()
-        .get(&tx.tx_type())
-        .expect("`OS_RESOURCES` must contain all transaction types.")
+    let os_steps_for_type = block_context
+        .versioned_constants
+        .os_resources
+        .os_resources_for_tx(tx.tx_type(), tx.calldata_length(), block_context.use_kzg_da)
         .n_steps;
-    let gas_for_type: usize = match tx {
+    let state_changes_by_type = match tx {
         // We consider the following state changes: sender balance update (storage update) + nonce
         // increment (contract modification) (we exclude the sequencer balance update and the ERC20
         // contract modification since it occurs for every tx).
-        AccountTransaction::Declare(_) => get_onchain_data_segment_length(StateChangesCount {
+        AccountTransaction::Declare(_) | AccountTransaction::Invoke(_) => StateChangesCount {
             n_storage_updates: 1,
             n_class_hash_updates: 0,
             n_compiled_class_hash_updates: 0,
             n_modified_contracts: 1,
-        }),
-        AccountTransaction::Invoke(_) => get_onchain_data_segment_length(StateChangesCount {
+        },
+        // DeployAccount also updates the address -> class hash mapping.
+        AccountTransaction::DeployAccount(_) => StateChangesCount {
             n_storage_updates: 1,
-            n_class_hash_updates: 0,
+            n_class_hash_updates: 1,
             n_compiled_class_hash_updates: 0,
             n_modified_contracts: 1,
-        }),
-        // DeployAccount also updates the address -> class hash mapping.
-        AccountTransaction::DeployAccount(_) => {
-            get_onchain_data_segment_length(StateChangesCount {
-                n_storage_updates: 1,
-                n_class_hash_updates: 1,
-                n_compiled_class_hash_updates: 0,
-                n_modified_contracts: 1,
-            })
-        }
+        },
     };
-    let resources = ResourcesMapping(HashMap::from([
-        (
-            constants::GAS_USAGE.to_string(),
-            gas_for_type * eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD,
-        ),
-        (constants::N_STEPS_RESOURCE.to_string(), os_steps_for_type),
-    ]));
-
-    calculate_tx_fee(&resources, block_context)
+    let tx_resources = TransactionResources {
+        vm_resources: ResourcesMapping(HashMap::from([(
+            constants::N_STEPS_RESOURCE.to_string(),
+            os_steps_for_type,
+        )])),
+        calldata_length: tx.calldata_length(),
+        signature_length: tx.signature_length(),
+        state_changes_count: state_changes_by_type,
+        ..Default::default()
+    };
+
+    let gas_vector = tx_resources.to_gas_vector(block_context)?;
+    let fee_type = tx.get_account_transaction_context().fee_type();
+    get_fee_by_gas_vector(block_context, gas_vector, &fee_type)
 }
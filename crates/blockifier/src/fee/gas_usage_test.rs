@@ -1,10 +1,19 @@
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::L2ToL1Payload;
+
+use crate::execution::entry_point::MessageToL1;
 use crate::fee::eth_gas_constants;
 use crate::fee::gas_usage::{
-    calculate_tx_gas_usage, get_consumed_message_to_l2_emissions_cost,
-    get_log_message_to_l1_emissions_cost, get_message_segment_length,
-    get_onchain_data_segment_length,
+    calculate_tx_gas_usage, calculate_tx_gas_vector, get_consumed_message_to_l2_emissions_cost,
+    get_da_gas_cost, get_log_message_to_l1_emissions_cost, get_message_segment_length,
+    get_onchain_data_segment_length, message_l1_gas,
 };
 use crate::state::cached_state::StateChangesCount;
+use crate::transaction::objects::GasVector;
 
 /// This test goes over five cases. In each case, we calculate the gas usage given the parameters.
 /// We then perform the same calculation manually, each time using only the relevant parameters.
@@ -114,3 +123,83 @@ fn test_calculate_tx_gas_usage_basic() {
 
     assert_eq!(gas_usage, expected_gas_usage);
 }
+
+/// A transfer-like state diff: the sender and the fee token are modified, with three storage
+/// updates (sender balance, recipient balance, sequencer balance); 10 felts in total.
+fn transfer_state_changes_count() -> StateChangesCount {
+    StateChangesCount {
+        n_storage_updates: 3,
+        n_class_hash_updates: 0,
+        n_compiled_class_hash_updates: 0,
+        n_modified_contracts: 2,
+    }
+}
+
+#[rstest]
+#[case::calldata_da(false, GasVector { l1_gas: 10 * 612, l1_data_gas: 0 })]
+#[case::kzg_da(true, GasVector { l1_gas: 0, l1_data_gas: 10 * 32 })]
+fn test_get_da_gas_cost(#[case] use_kzg_da: bool, #[case] expected_gas_vector: GasVector) {
+    assert_eq!(get_da_gas_cost(transfer_state_changes_count(), use_kzg_da), expected_gas_vector);
+}
+
+/// When the state diff is published in blobs, only the DA cost moves from L1 gas to data gas;
+/// messages are still paid for in L1 gas.
+#[test]
+fn test_calculate_tx_gas_vector_kzg_da() {
+    let l2_to_l1_payloads_length = [0, 1, 2];
+    let l1_handler_payload_size = Some(4);
+    let calldata_da_gas_vector = calculate_tx_gas_vector(
+        &l2_to_l1_payloads_length,
+        transfer_state_changes_count(),
+        l1_handler_payload_size,
+        false,
+    );
+    let kzg_da_gas_vector = calculate_tx_gas_vector(
+        &l2_to_l1_payloads_length,
+        transfer_state_changes_count(),
+        l1_handler_payload_size,
+        true,
+    );
+
+    assert_eq!(
+        calldata_da_gas_vector.l1_gas,
+        calculate_tx_gas_usage(
+            &l2_to_l1_payloads_length,
+            transfer_state_changes_count(),
+            l1_handler_payload_size
+        )
+    );
+    assert_eq!(calldata_da_gas_vector.l1_data_gas, 0);
+    assert_eq!(
+        kzg_da_gas_vector,
+        GasVector { l1_gas: calldata_da_gas_vector.l1_gas - 10 * 612, l1_data_gas: 10 * 32 }
+    );
+}
+
+fn two_word_message() -> MessageToL1 {
+    MessageToL1 {
+        payload: L2ToL1Payload(vec![stark_felt!(1_u8), stark_felt!(2_u8)]),
+        ..Default::default()
+    }
+}
+
+#[rstest]
+#[case::no_messages(vec![], None, 0)]
+// Output: (3 + 2) words * (512 + 612);
+// storage: 20000 (new message counter);
+// log: 375 + (2 + 1) topics * 375 + (2 + 2) words * 256.
+#[case::one_two_word_message(vec![two_word_message()], None, 5 * 1124 + 20000 + 2524)]
+// Output: (5 + 4) words * (512 + 612);
+// storage: 5000 (the consumed message counter is decreased, rather than newly set);
+// log: 375 + (3 + 1) topics * 375 + (3 + 4) words * 256.
+#[case::l1_handler(vec![], Some(4), 9 * 1124 + 5000 + 3667)]
+fn test_message_l1_gas(
+    #[case] l2_to_l1_messages: Vec<MessageToL1>,
+    #[case] l1_handler_payload_size: Option<usize>,
+    #[case] expected_l1_gas: usize,
+) {
+    assert_eq!(
+        message_l1_gas(&l2_to_l1_messages, l1_handler_payload_size),
+        GasVector { l1_gas: expected_l1_gas, l1_data_gas: 0 }
+    );
+}
@@ -1,56 +1,140 @@
 use std::collections::HashMap;
 
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+use strum::IntoEnumIterator;
 
 use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
 use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
-use crate::fee::os_resources::OS_RESOURCES;
 use crate::transaction::errors::TransactionExecutionError;
 use crate::transaction::transaction_types::TransactionType;
+use crate::versioned_constants::{VersionedConstantsError, VersionedConstantsResult};
 
 #[cfg(test)]
 #[path = "os_usage_test.rs"]
 pub mod test;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct OsResources {
+    // Extra execution resources in the OS of a transaction that publishes its state diff in blobs.
+    #[serde(serialize_with = "VmExecutionResourcesDef::serialize")]
+    compute_os_kzg_commitment_info: VmExecutionResources,
     // Mapping from every syscall to its execution resources in the OS (e.g., amount of Cairo
     // steps).
+    #[serde(serialize_with = "serialize_resources_map")]
     execute_syscalls: HashMap<DeprecatedSyscallSelector, VmExecutionResources>,
     // Mapping from every transaction to its extra execution resources in the OS,
     // i.e., resources that don't count during the execution itself.
-    execute_txs_inner: HashMap<TransactionType, VmExecutionResources>,
+    execute_txs_inner: HashMap<TransactionType, TxOsResources>,
+}
+
+/// The extra execution resources of a transaction in the OS; a constant part, and a part that is
+/// linear in the transaction calldata length.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxOsResources {
+    #[serde(serialize_with = "VmExecutionResourcesDef::serialize")]
+    constant: VmExecutionResources,
+    #[serde(serialize_with = "VmExecutionResourcesDef::serialize")]
+    calldata_factor: VmExecutionResources,
+}
+
+/// Serializes Cairo VM execution resources in the form they are deserialized from.
+#[derive(Serialize)]
+#[serde(remote = "VmExecutionResources")]
+struct VmExecutionResourcesDef {
+    n_steps: usize,
+    n_memory_holes: usize,
+    builtin_instance_counter: HashMap<String, usize>,
+}
+
+struct SerializableVmExecutionResources<'a>(&'a VmExecutionResources);
+
+impl Serialize for SerializableVmExecutionResources<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VmExecutionResourcesDef::serialize(self.0, serializer)
+    }
+}
+
+fn serialize_resources_map<S: Serializer>(
+    resources_map: &HashMap<DeprecatedSyscallSelector, VmExecutionResources>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        resources_map
+            .iter()
+            .map(|(selector, resources)| (selector, SerializableVmExecutionResources(resources))),
+    )
 }
 
 impl OsResources {
-    pub fn execute_txs_inner(&self) -> &HashMap<TransactionType, VmExecutionResources> {
-        &self.execute_txs_inner
+    /// Verifies that every syscall and transaction type has OS resources; e.g., after overrides,
+    /// which may remove some.
+    pub fn verify_complete(&self) -> VersionedConstantsResult<()> {
+        if let Some(selector) = DeprecatedSyscallSelector::iter()
+            .find(|selector| !self.execute_syscalls.contains_key(selector))
+        {
+            return Err(VersionedConstantsError::MissingSyscallOsResources { selector });
+        }
+        if let Some(tx_type) =
+            TransactionType::iter().find(|tx_type| !self.execute_txs_inner.contains_key(tx_type))
+        {
+            return Err(VersionedConstantsError::MissingTxOsResources { tx_type });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the extra execution resources in the OS of a transaction of the given type and
+    /// calldata length; i.e., the resources of the StarkNet OS function
+    /// `execute_transactions_inner`, including the fee transfer performed in the end of every
+    /// transaction.
+    pub fn os_resources_for_tx(
+        &self,
+        tx_type: TransactionType,
+        calldata_length: usize,
+        use_kzg_da: bool,
+    ) -> VmExecutionResources {
+        let TxOsResources { constant, calldata_factor } = self
+            .execute_txs_inner
+            .get(&tx_type)
+            .expect("The OS resources must contain all transaction types.");
+        let mut os_resources = constant + &(calldata_factor * calldata_length);
+        if use_kzg_da {
+            os_resources += &self.compute_os_kzg_commitment_info;
+        }
+
+        os_resources
+    }
+
+    /// Returns the extra execution resources in the OS of the given syscalls; i.e., the resources
+    /// of the StarkNet OS function `execute_syscalls`.
+    pub fn os_resources_for_syscalls(
+        &self,
+        syscall_counter: &SyscallCounter,
+    ) -> VmExecutionResources {
+        let mut os_resources = VmExecutionResources::default();
+        for (syscall_selector, count) in syscall_counter {
+            let syscall_resources =
+                self.execute_syscalls.get(syscall_selector).unwrap_or_else(|| {
+                    panic!("OS resources of syscall '{syscall_selector:?}' are unknown.")
+                });
+            os_resources += &(syscall_resources * *count);
+        }
+
+        os_resources
     }
 }
 
-/// Calculates the additional resources needed for the OS to run the given syscalls;
-/// i.e., the resources of the StarkNet OS function `execute_syscalls`.
+/// Calculates the additional resources needed for the OS to run the given transaction, with the
+/// given syscalls.
 pub fn get_additional_os_resources(
-    syscall_counter: SyscallCounter,
+    os_resources: &OsResources,
+    syscall_counter: &SyscallCounter,
     tx_type: TransactionType,
+    calldata_length: usize,
+    use_kzg_da: bool,
 ) -> Result<VmExecutionResources, TransactionExecutionError> {
-    let mut os_additional_vm_resources = VmExecutionResources::default();
-    for (syscall_selector, count) in syscall_counter {
-        let syscall_resources =
-            OS_RESOURCES.execute_syscalls.get(&syscall_selector).unwrap_or_else(|| {
-                panic!("OS resources of syscall '{syscall_selector:?}' are unknown.")
-            });
-        os_additional_vm_resources += &(syscall_resources * count);
-    }
-
-    // Calculates the additional resources needed for the OS to run the given transaction;
-    // i.e., the resources of the StarkNet OS function `execute_transactions_inner`.
-    // Also adds the resources needed for the fee transfer execution, performed in the end·
-    // of every transaction.
-    let os_resources = OS_RESOURCES
-        .execute_txs_inner
-        .get(&tx_type)
-        .expect("`OS_RESOURCES` must contain all transaction types.");
-    Ok(&os_additional_vm_resources + os_resources)
+    let os_additional_vm_resources = os_resources.os_resources_for_syscalls(syscall_counter);
+    Ok(&os_additional_vm_resources
+        + &os_resources.os_resources_for_tx(tx_type, calldata_length, use_kzg_da))
 }
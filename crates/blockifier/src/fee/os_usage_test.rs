@@ -1,19 +1,40 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use cairo_vm::vm::runners::builtin_runner;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::stark_felt;
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, contract_address, patricia_key};
 use strum::IntoEnumIterator;
 
+use crate::abi::constants;
+use crate::block_context::BlockContext;
 use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
-use crate::fee::os_usage::OS_RESOURCES;
+use crate::selector;
+use crate::test_utils::{
+    NonceManager, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS,
+};
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_trivial_validation_account,
+};
 use crate::transaction::transaction_types::TransactionType;
+use crate::transaction::transactions::ExecutableTransaction;
+use crate::versioned_constants::{StarknetVersion, VersionedConstants};
 
 #[test]
 fn test_resources_entries() {
-    for tx_type in TransactionType::iter() {
-        assert!(OS_RESOURCES.execute_txs_inner.get(&tx_type).is_some());
-    }
-    for syscall_selector in DeprecatedSyscallSelector::iter() {
-        assert!(OS_RESOURCES.execute_syscalls.get(&syscall_selector).is_some());
+    for version in StarknetVersion::iter() {
+        let os_resources = &VersionedConstants::get(version).os_resources;
+        for tx_type in TransactionType::iter() {
+            assert!(os_resources.execute_txs_inner.get(&tx_type).is_some());
+        }
+        for syscall_selector in DeprecatedSyscallSelector::iter() {
+            assert!(os_resources.execute_syscalls.get(&syscall_selector).is_some());
+        }
     }
 }
 
@@ -30,11 +51,137 @@ fn test_resource_name_consistency() {
         builtin_runner::POSEIDON_BUILTIN_NAME,
         builtin_runner::SEGMENT_ARENA_BUILTIN_NAME,
     ]);
-    for resources in
-        OS_RESOURCES.execute_syscalls.values().chain(OS_RESOURCES.execute_txs_inner.values())
+    let os_resources = &VersionedConstants::latest().os_resources;
+    for resources in [&os_resources.compute_os_kzg_commitment_info]
+        .into_iter()
+        .chain(os_resources.execute_syscalls.values())
+        .chain(os_resources.execute_txs_inner.values().flat_map(|tx_os_resources| {
+            [&tx_os_resources.constant, &tx_os_resources.calldata_factor]
+        }))
     {
         for builtin_name in resources.builtin_instance_counter.keys() {
             assert!(known_builtin_names.contains(builtin_name.as_str()));
         }
     }
 }
+
+#[rstest]
+#[case::one_storage_write(1)]
+#[case::three_storage_writes(3)]
+fn test_os_resources_for_syscalls(#[case] n_storage_writes: usize) {
+    let syscall_counter = HashMap::from([
+        (DeprecatedSyscallSelector::StorageRead, 1),
+        (DeprecatedSyscallSelector::StorageWrite, n_storage_writes),
+    ]);
+    // 87 steps per read, 89 per write.
+    assert_eq!(
+        VersionedConstants::latest()
+            .os_resources
+            .os_resources_for_syscalls(&syscall_counter)
+            .n_steps,
+        87 + 89 * n_storage_writes
+    );
+}
+
+/// Executes the same invoke under the current OS resources, and under (hypothetical) ones that
+/// charge 100 more constant steps, and 8 more steps per calldata felt, for an invoke.
+#[test]
+fn test_os_resources_for_tx_versions() {
+    let block_context = BlockContext::create_for_account_testing();
+    let mut other_block_context = block_context.clone();
+    let invoke_os_resources = other_block_context
+        .versioned_constants_mut()
+        .os_resources
+        .execute_txs_inner
+        .get_mut(&TransactionType::InvokeFunction)
+        .unwrap();
+    invoke_os_resources.constant.n_steps += 100;
+    invoke_os_resources.calldata_factor.n_steps += 8;
+
+    let execute_calldata = calldata![
+        *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+        selector!("return_result").0,                      // EP selector.
+        stark_felt!(1_u8),                                 // Calldata length.
+        stark_felt!(2_u8)                                  // Calldata: num.
+    ];
+    let calldata_length = execute_calldata.0.len();
+    let n_steps_by_os_resources = [block_context, other_block_context].map(|block_context| {
+        let account_tx = account_invoke_tx(
+            execute_calldata.clone(),
+            contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            &mut NonceManager::default(),
+            Fee(MAX_FEE),
+        );
+        let state = &mut create_state_with_trivial_validation_account();
+        let tx_execution_info = account_tx.execute(state, &block_context, true, true).unwrap();
+        tx_execution_info.actual_resources.vm_resources.0[constants::N_STEPS_RESOURCE]
+    });
+
+    assert_eq!(n_steps_by_os_resources[1], n_steps_by_os_resources[0] + 100 + 8 * calldata_length);
+}
+
+/// Invokes `recursive_syscall` with depths 5 and 0: the former invokes exactly 5 more
+/// `call_contract` syscalls, and is charged their OS resources on top of its own VM resources.
+#[test]
+fn test_syscall_os_resources_are_charged() {
+    let block_context = BlockContext::create_for_account_testing();
+    let test_contract_address = *contract_address!(TEST_CONTRACT_ADDRESS).0.key();
+    let recursive_syscall_selector = selector!("recursive_syscall").0;
+    let execute_recursive_syscall = |depth: u8| {
+        let execute_calldata = calldata![
+            test_contract_address,      // Contract address.
+            recursive_syscall_selector, // EP selector.
+            stark_felt!(3_u8),          // Calldata length.
+            test_contract_address,      // Calldata: contract address.
+            recursive_syscall_selector, // Calldata: function selector.
+            stark_felt!(depth)          // Calldata: depth.
+        ];
+        let account_tx = account_invoke_tx(
+            execute_calldata,
+            contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            &mut NonceManager::default(),
+            Fee(MAX_FEE),
+        );
+        let state = &mut create_state_with_trivial_validation_account();
+        account_tx.execute(state, &block_context, true, true).unwrap()
+    };
+
+    let [deep_execution_info, shallow_execution_info] = [5, 0].map(execute_recursive_syscall);
+    let n_call_contract_syscalls = |tx_execution_info: &TransactionExecutionInfo| {
+        tx_execution_info
+            .execute_call_info
+            .iter()
+            .flat_map(|call_info| call_info.into_iter())
+            .filter_map(|call_info| {
+                call_info.syscall_counter.get(&DeprecatedSyscallSelector::CallContract)
+            })
+            .sum::<usize>()
+    };
+    // The account's `__execute__` invokes one more `call_contract`.
+    assert_eq!(n_call_contract_syscalls(&deep_execution_info), 5 + 1);
+    assert_eq!(n_call_contract_syscalls(&shallow_execution_info), 1);
+
+    let n_vm_steps = |tx_execution_info: &TransactionExecutionInfo| {
+        [
+            &tx_execution_info.validate_call_info,
+            &tx_execution_info.execute_call_info,
+            &tx_execution_info.fee_transfer_call_info,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|call_info| call_info.vm_resources.n_steps + call_info.vm_resources.n_memory_holes)
+        .sum::<usize>()
+    };
+    let n_charged_steps = |tx_execution_info: &TransactionExecutionInfo| {
+        tx_execution_info.actual_resources.vm_resources.0[constants::N_STEPS_RESOURCE]
+    };
+    let call_contract_os_steps = VersionedConstants::latest()
+        .os_resources
+        .os_resources_for_syscalls(&HashMap::from([(DeprecatedSyscallSelector::CallContract, 5)]))
+        .n_steps;
+    assert_eq!(
+        n_charged_steps(&deep_execution_info) - n_charged_steps(&shallow_execution_info),
+        n_vm_steps(&deep_execution_info) - n_vm_steps(&shallow_execution_info)
+            + call_contract_os_steps
+    );
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::abi::constants;
+use crate::block_context::BlockContext;
+use crate::fee::fee_utils::calculate_l1_gas_by_vm_usage;
+use crate::fee::gas_usage::{
+    calculate_tx_gas_vector, get_da_gas_cost, get_events_gas_cost, get_l2_gas_cost,
+    get_tx_data_gas_cost,
+};
+use crate::state::cached_state::StateChangesCount;
+use crate::transaction::errors::TransactionFeeError;
+use crate::transaction::objects::{GasVector, ResourcesMapping, TransactionExecutionResult};
+
+#[cfg(test)]
+#[path = "transaction_resources_test.rs"]
+pub mod test;
+
+/// The resources a transaction is charged for; its fee is obtained by converting them to a gas
+/// vector (`to_gas_vector`), and pricing it (`fee_utils::get_fee_by_gas_vector`).
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionResources {
+    /// Cairo VM resources (steps, including memory holes, and builtins), including the OS
+    /// overhead and the steps of reverted executions.
+    pub vm_resources: ResourcesMapping,
+    /// Of the constructor, for `DeployAccount`; zero for `Declare`.
+    pub calldata_length: usize,
+    pub signature_length: usize,
+    pub state_changes_count: StateChangesCount,
+    /// Sorted by the order in which the messages were sent.
+    pub l2_to_l1_payloads_length: Vec<usize>,
+    /// [None] unless the transaction is an L1 handler.
+    pub l1_handler_payload_size: Option<usize>,
+    pub n_event_keys: usize,
+    pub n_event_data_felts: usize,
+    /// The L2 gas consumed by the calls of the transaction (see `CallInfo::l2_gas_consumed`).
+    pub l2_gas: usize,
+}
+
+impl TransactionResources {
+    /// Returns the gas consumed directly (i.e., not by the Cairo VM): by the calldata and signature,
+    /// messages, events, and the state diff publication.
+    pub fn direct_gas_vector(
+        &self,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<GasVector> {
+        let messages_and_da_gas = calculate_tx_gas_vector(
+            &self.l2_to_l1_payloads_length,
+            self.state_changes_count,
+            self.l1_handler_payload_size,
+            block_context.use_kzg_da,
+        );
+        let events_gas =
+            get_events_gas_cost(self.n_event_keys, self.n_event_data_felts, block_context);
+        let tx_data_gas =
+            get_tx_data_gas_cost(self.calldata_length, self.signature_length, block_context);
+
+        Ok(messages_and_da_gas
+            .checked_add(events_gas)
+            .and_then(|gas_vector| gas_vector.checked_add(tx_data_gas))
+            .ok_or(TransactionFeeError::GasAmountOverflow)?)
+    }
+
+    /// Returns the gas charged for the resources other than the Cairo VM resources: the direct gas
+    /// usage, and the consumed L2 gas.
+    fn non_vm_gas_vector(
+        &self,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<GasVector> {
+        Ok(self
+            .direct_gas_vector(block_context)?
+            .checked_add(get_l2_gas_cost(self.l2_gas, block_context)?)
+            .ok_or(TransactionFeeError::GasAmountOverflow)?)
+    }
+
+    /// Returns the gas consumed by publishing the state diff.
+    pub fn da_gas_vector(&self, block_context: &BlockContext) -> GasVector {
+        get_da_gas_cost(self.state_changes_count, block_context.use_kzg_da)
+    }
+
+    /// Returns the gas vector the transaction is charged for: the direct gas usage and the consumed
+    /// L2 gas, along with the (rounded up) L1 gas attributed to the Cairo VM resources by their
+    /// weights.
+    pub fn to_gas_vector(
+        &self,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<GasVector> {
+        let non_vm_gas_vector = self.non_vm_gas_vector(block_context)?;
+        let l1_gas_by_vm_usage = calculate_l1_gas_by_vm_usage(block_context, &self.vm_resources)?;
+
+        Ok(non_vm_gas_vector
+            .checked_add(l1_gas_by_vm_usage)
+            .ok_or(TransactionFeeError::GasAmountOverflow)?)
+    }
+
+    /// Returns the resources in the flat form reported to the Python side: the VM resources, along
+    /// with the gas charged for the other resources.
+    pub fn to_resources_mapping(
+        &self,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<ResourcesMapping> {
+        let non_vm_gas_vector = self.non_vm_gas_vector(block_context)?;
+        let mut resources = HashMap::from([
+            (constants::GAS_USAGE.to_string(), non_vm_gas_vector.l1_gas),
+            (constants::BLOB_GAS_USAGE.to_string(), non_vm_gas_vector.l1_data_gas),
+        ]);
+        resources.extend(self.vm_resources.0.clone());
+
+        Ok(ResourcesMapping(resources))
+    }
+}
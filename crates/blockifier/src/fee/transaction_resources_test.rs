@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use cairo_vm::vm::runners::builtin_runner::HASH_BUILTIN_NAME;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::stark_felt;
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, contract_address, patricia_key};
+
+use crate::abi::constants;
+use crate::block_context::BlockContext;
+use crate::fee::fee_utils::{calculate_l1_gas_by_vm_usage, get_fee_by_gas_vector};
+use crate::fee::gas_usage::get_l2_gas_cost;
+use crate::fee::transaction_resources::TransactionResources;
+use crate::selector;
+use crate::state::cached_state::StateChangesCount;
+use crate::test_utils::{
+    NonceManager, DEFAULT_DATA_GAS_PRICE, DEFAULT_GAS_PRICE, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS,
+};
+use crate::transaction::objects::{FeeType, GasVector, ResourcesMapping};
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_cairo1_account,
+    create_state_with_trivial_validation_account,
+};
+use crate::transaction::transactions::ExecutableTransaction;
+use crate::versioned_constants::StarknetVersion;
+
+/// A fully worked example:
+/// VM: max(1001 steps * 0.005, 10 Pedersen * 0.16) = 5.005 L1 gas.
+/// Message (2-word payload): (3 + 2) words * (512 + 612) + 20000 + 2524 (log) = 28144 L1 gas.
+/// Events (2 keys, 3 data felts): 2 * 200 + 3 * 100 = 700 L1 gas.
+/// Calldata and signature (4 + 1 felts): 5 * 20 = 100 L1 gas.
+/// DA (2 modified contracts, 3 storage updates): 10 felts, * 612 L1 gas or * 32 data gas.
+/// L2 gas: 100050 * 0.0001 = 10.005 L1 gas, rounded up to 11.
+#[rstest]
+#[case::calldata_da(false, GasVector { l1_gas: 35081, l1_data_gas: 0 })]
+#[case::kzg_da(true, GasVector { l1_gas: 28961, l1_data_gas: 320 })]
+fn test_to_gas_vector(#[case] use_kzg_da: bool, #[case] expected_gas_vector: GasVector) {
+    let mut block_context = BlockContext { use_kzg_da, ..BlockContext::create_for_testing() };
+    let versioned_constants = block_context.versioned_constants_mut();
+    versioned_constants.vm_resource_fee_cost = HashMap::from([
+        (constants::N_STEPS_RESOURCE.to_string(), 0.005),
+        (HASH_BUILTIN_NAME.to_string(), 0.16),
+    ]);
+    versioned_constants.event_key_gas_cost = 200;
+    versioned_constants.event_data_gas_cost = 100;
+    versioned_constants.tx_data_felt_gas_cost = 20;
+    versioned_constants.l2_gas_cost = 0.0001;
+    let tx_resources = TransactionResources {
+        vm_resources: ResourcesMapping(HashMap::from([
+            (constants::N_STEPS_RESOURCE.to_string(), 1001),
+            (HASH_BUILTIN_NAME.to_string(), 10),
+        ])),
+        calldata_length: 4,
+        signature_length: 1,
+        state_changes_count: StateChangesCount {
+            n_storage_updates: 3,
+            n_class_hash_updates: 0,
+            n_compiled_class_hash_updates: 0,
+            n_modified_contracts: 2,
+        },
+        l2_to_l1_payloads_length: vec![2],
+        l1_handler_payload_size: None,
+        n_event_keys: 2,
+        n_event_data_felts: 3,
+        l2_gas: 100050,
+    };
+
+    // 35064 + 11 + 5.005 and 28944 + 11 + 5.005, rounded up.
+    let gas_vector = tx_resources.to_gas_vector(&block_context).unwrap();
+    assert_eq!(gas_vector, expected_gas_vector);
+    assert_eq!(
+        get_fee_by_gas_vector(&block_context, gas_vector, &FeeType::Eth).unwrap(),
+        Fee(gas_vector.l1_gas as u128 * DEFAULT_GAS_PRICE
+            + gas_vector.l1_data_gas as u128 * DEFAULT_DATA_GAS_PRICE)
+    );
+    // The reported resources carry the same gas, along with the VM resources.
+    let resources = tx_resources.to_resources_mapping(&block_context).unwrap();
+    let l1_gas_by_vm_usage =
+        calculate_l1_gas_by_vm_usage(&block_context, &tx_resources.vm_resources).unwrap();
+    assert_eq!(resources.0[constants::GAS_USAGE] + l1_gas_by_vm_usage.l1_gas, gas_vector.l1_gas);
+    assert_eq!(resources.0[constants::BLOB_GAS_USAGE], gas_vector.l1_data_gas);
+}
+
+#[test]
+fn test_charged_fee_matches_gas_vector() {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let account_tx = account_invoke_tx(
+        calldata![
+            *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+            selector!("return_result").0,                      // EP selector.
+            stark_felt!(1_u8),                                 // Calldata length.
+            stark_felt!(2_u8)                                  // Calldata: num.
+        ],
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default(),
+        Fee(MAX_FEE),
+    );
+    let tx_execution_info = account_tx.execute(state, block_context, true, true).unwrap();
+
+    assert_eq!(
+        tx_execution_info.receipt.gas,
+        tx_execution_info.actual_resources.to_gas_vector(block_context).unwrap()
+    );
+    assert_eq!(
+        tx_execution_info.receipt.fee,
+        get_fee_by_gas_vector(block_context, tx_execution_info.receipt.gas, &FeeType::Eth).unwrap()
+    );
+}
+
+/// Once L2 gas is priced (as of 0.13.3), a compute-heavy invoke pays for it, on top of its VM
+/// resources; the constants of 0.13.2 differ from those of 0.13.3 in nothing else that is charged.
+#[test]
+fn test_l2_gas_fee_component() {
+    let legacy_block_context =
+        BlockContext::create_for_account_testing_with_version(StarknetVersion::V0_13_2);
+    let l2_gas_pricing_block_context =
+        BlockContext::create_for_account_testing_with_version(StarknetVersion::V0_13_3);
+    let execute_recursion = |block_context: &BlockContext| {
+        let state = &mut create_state_with_cairo1_account();
+        let account_tx = account_invoke_tx(
+            calldata![
+                *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+                selector!("recurse").0,                            // EP selector.
+                stark_felt!(1_u8),                                 // Calldata length.
+                stark_felt!(1000_u16)                              // Calldata: depth.
+            ],
+            contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            &mut NonceManager::default(),
+            Fee(MAX_FEE),
+        );
+        account_tx.execute(state, block_context, true, true).unwrap()
+    };
+    let legacy_execution_info = execute_recursion(&legacy_block_context);
+    let l2_gas_pricing_execution_info = execute_recursion(&l2_gas_pricing_block_context);
+    assert!(!l2_gas_pricing_execution_info.is_reverted());
+
+    // The consumed resources do not depend on the pricing; the (Cairo 0) recursion alone
+    // consumes the gas equivalent of more than a thousand steps.
+    let tx_resources = &l2_gas_pricing_execution_info.actual_resources;
+    assert_eq!(tx_resources, &legacy_execution_info.actual_resources);
+    assert!(tx_resources.l2_gas > 1000 * constants::STEP_GAS_COST as usize);
+
+    assert_eq!(
+        l2_gas_pricing_execution_info.receipt.gas.l1_gas - legacy_execution_info.receipt.gas.l1_gas,
+        get_l2_gas_cost(tx_resources.l2_gas, &l2_gas_pricing_block_context).unwrap().l1_gas
+    );
+    assert!(l2_gas_pricing_execution_info.receipt.fee > legacy_execution_info.receipt.fee);
+}
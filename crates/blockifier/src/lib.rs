@@ -1,10 +1,14 @@
 pub mod abi;
 pub mod block_context;
 pub mod block_execution;
+pub mod blockifier;
+pub mod concurrency;
 pub mod execution;
 pub mod fee;
+pub mod platform;
 pub mod state;
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
 pub mod transaction;
 pub mod utils;
+pub mod versioned_constants;
@@ -0,0 +1,25 @@
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+/// The origin of [StdClock].
+static STD_CLOCK_ORIGIN: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// A monotonic clock, used by the execution to measure time (e.g., to enforce execution deadlines;
+/// see `BlockContext::max_execution_time`). Abstracts the system clock, for environments lacking
+/// one; the clock is only read through the block context (see `BlockContext::clock`).
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the time passed since a fixed, arbitrary origin.
+    fn now(&self) -> Duration;
+}
+
+/// The system clock (see [Instant]); the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdClock;
+
+impl Clock for StdClock {
+    fn now(&self) -> Duration {
+        STD_CLOCK_ORIGIN.elapsed()
+    }
+}
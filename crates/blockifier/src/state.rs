@@ -1,3 +1,6 @@
 pub mod cached_state;
 pub mod errors;
+#[cfg(feature = "papyrus-state-reader")]
+pub mod papyrus_state_reader;
 pub mod state_api;
+pub mod witness_state_reader;
@@ -4,12 +4,15 @@ use std::sync::{Arc, Mutex, MutexGuard};
 use cached::{Cached, SizedCache};
 use derive_more::IntoIterator;
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
 
 use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::blockifier::metrics::ExecutionMetrics;
 use crate::execution::contract_class::ContractClass;
+use crate::execution::execution_utils::{felt_keyed_json_object, felt_to_decimal_string};
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader, StateResult};
 use crate::utils::subtract_mappings;
@@ -32,6 +35,11 @@ pub struct CachedState<S: StateReader> {
     class_hash_to_class: ContractClassMapping,
     // Invariant: managed by CachedState.
     global_class_hash_to_class: GlobalContractCache,
+    // Notified of the contract class cache hits and misses; see `set_metrics`.
+    metrics: Option<Arc<dyn ExecutionMetrics>>,
+    // Whether the state was created by `create_transactional`; its class cache misses are
+    // reported by the underlying state.
+    is_transactional: bool,
 }
 
 impl<S: StateReader> CachedState<S> {
@@ -41,6 +49,8 @@ impl<S: StateReader> CachedState<S> {
             cache: StateCache::default(),
             class_hash_to_class: HashMap::default(),
             global_class_hash_to_class,
+            metrics: None,
+            is_transactional: false,
         }
     }
 
@@ -49,12 +59,38 @@ impl<S: StateReader> CachedState<S> {
     /// will either all happen (will be committed) or none of them (will be discarded).
     pub fn create_transactional(state: &mut CachedState<S>) -> TransactionalState<'_, S> {
         let global_class_hash_to_class = state.global_class_hash_to_class.clone();
-        CachedState::new(MutRefState::new(state), global_class_hash_to_class)
+        let metrics = state.metrics.clone();
+        CachedState {
+            metrics,
+            is_transactional: true,
+            ..CachedState::new(MutRefState::new(state), global_class_hash_to_class)
+        }
+    }
+
+    /// Reports the contract class cache hits and misses to the given metrics (see
+    /// `ExecutionMetrics::class_cache`), from this state and its transactional states. A class
+    /// first used by a transactional state is a hit if found in the global cache, or in this
+    /// state; a miss if read from the underlying state.
+    pub fn set_metrics(&mut self, metrics: Arc<dyn ExecutionMetrics>) {
+        self.metrics = Some(metrics);
+    }
+
+    fn report_class_cache(&self, hit: bool) {
+        if let Some(metrics) = &self.metrics {
+            metrics.class_cache(hit);
+        }
+    }
+
+    /// Returns a handle to the global contract cache used by this state.
+    pub fn global_contract_cache(&self) -> GlobalContractCache {
+        self.global_class_hash_to_class.clone()
     }
 
     /// Returns the storage changes done through this state.
     /// For each contract instance (address) we have three attributes: (class hash, nonce, storage
     /// root); the state updates correspond to them.
+    /// As in the OS, each modified contract is counted once (for its header), regardless of the
+    /// number of its modified attributes and storage cells, and each storage cell is counted once.
     pub fn get_actual_state_changes_for_fee_charge(
         &mut self,
         fee_token_address: ContractAddress,
@@ -135,6 +171,47 @@ impl<S: StateReader> CachedState<S> {
         Ok(())
     }
 
+    /// Returns the values written through this state, and the contract classes declared through
+    /// it.
+    pub fn get_writes(&self) -> (StateMaps, ContractClassMapping) {
+        let writes = StateMaps {
+            nonces: self.cache.nonce_writes.clone(),
+            class_hashes: self.cache.class_hash_writes.clone(),
+            storage: self.cache.storage_writes.clone(),
+            compiled_class_hashes: self.cache.compiled_class_hash_writes.clone(),
+        };
+
+        (writes, self.class_hash_to_class.clone())
+    }
+
+    /// Returns the initial values of the cells read through this state; i.e., a witness of the
+    /// underlying state, sufficient to re-execute the same transactions.
+    pub fn get_initial_reads(&self) -> StateMaps {
+        StateMaps {
+            nonces: self.cache.nonce_initial_values.clone(),
+            class_hashes: self.cache.class_hash_initial_values.clone(),
+            storage: self.cache.storage_initial_values.clone(),
+            compiled_class_hashes: self.cache.compiled_class_hash_initial_values.clone(),
+        }
+    }
+
+    /// Updates the cache with the given writes, as if they were written through this state.
+    pub fn update_cache(&mut self, writes: &StateMaps, class_hash_to_class: ContractClassMapping) {
+        self.cache.nonce_writes.extend(&writes.nonces);
+        self.cache.class_hash_writes.extend(&writes.class_hashes);
+        self.cache.storage_writes.extend(&writes.storage);
+        self.cache.compiled_class_hash_writes.extend(&writes.compiled_class_hashes);
+        self.class_hash_to_class.extend(class_hash_to_class);
+    }
+
+    /// Sets the given values as the initial values of the storage cells, as if they were read from
+    /// the underlying state (which is not accessed).
+    pub fn set_storage_initial_values(&mut self, values: &HashMap<ContractStorageKey, StarkFelt>) {
+        for (&(contract_address, key), &value) in values {
+            self.cache.set_storage_initial_value(contract_address, key, value);
+        }
+    }
+
     /// Drains contract-class cache collected during execution and updates the global cache.
     pub fn move_classes_to_global_cache(&mut self) {
         let contract_class_updates: Vec<_> = self.class_hash_to_class.drain().collect();
@@ -167,6 +244,11 @@ impl<S: StateReader> StateReader for CachedState<S> {
         key: StorageKey,
     ) -> StateResult<StarkFelt> {
         if self.cache.get_storage_at(contract_address, key).is_none() {
+            tracing::debug!(
+                contract_address = %contract_address.0.key(),
+                key = %key.0.key(),
+                "Storage cache miss."
+            );
             let storage_value = self.state.get_storage_at(contract_address, key)?;
             self.cache.set_storage_initial_value(contract_address, key, storage_value);
         }
@@ -179,6 +261,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
 
     fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
         if self.cache.get_nonce_at(contract_address).is_none() {
+            tracing::debug!(contract_address = %contract_address.0.key(), "Nonce cache miss.");
             let nonce = self.state.get_nonce_at(contract_address)?;
             self.cache.set_nonce_initial_value(contract_address, nonce);
         }
@@ -192,6 +275,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
 
     fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
         if self.cache.get_class_hash_at(contract_address).is_none() {
+            tracing::debug!(contract_address = %contract_address.0.key(), "Class hash cache miss.");
             let class_hash = self.state.get_class_hash_at(contract_address)?;
             self.cache.set_class_hash_initial_value(contract_address, class_hash);
         }
@@ -207,14 +291,24 @@ impl<S: StateReader> StateReader for CachedState<S> {
         &mut self,
         class_hash: &ClassHash,
     ) -> StateResult<ContractClass> {
-        if !self.class_hash_to_class.contains_key(class_hash) {
+        if self.class_hash_to_class.contains_key(class_hash) {
+            // A transactional state reuses its classes silently.
+            if !self.is_transactional {
+                self.report_class_cache(true);
+            }
+        } else {
             let contract_class = self.global_class_hash_to_class().cache_get(class_hash).cloned();
 
             match contract_class {
                 Some(contract_class_from_global_cache) => {
+                    self.report_class_cache(true);
                     self.class_hash_to_class.insert(*class_hash, contract_class_from_global_cache);
                 }
                 None => {
+                    tracing::debug!(class_hash = %class_hash.0, "Contract class cache miss.");
+                    if !self.is_transactional {
+                        self.report_class_cache(false);
+                    }
                     let contract_class_from_db =
                         self.state.get_compiled_contract_class(class_hash)?;
                     self.class_hash_to_class.insert(*class_hash, contract_class_from_db);
@@ -233,6 +327,7 @@ impl<S: StateReader> StateReader for CachedState<S> {
 
     fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
         if self.cache.get_compiled_class_hash(class_hash).is_none() {
+            tracing::debug!(class_hash = %class_hash.0, "Compiled class hash cache miss.");
             let compiled_class_hash = self.state.get_compiled_class_hash(class_hash)?;
             self.cache.set_compiled_class_hash_initial_value(class_hash, compiled_class_hash);
         }
@@ -324,6 +419,8 @@ impl Default for CachedState<crate::test_utils::DictStateReader> {
             cache: Default::default(),
             class_hash_to_class: Default::default(),
             global_class_hash_to_class: Default::default(),
+            metrics: None,
+            is_transactional: false,
         }
     }
 }
@@ -350,6 +447,15 @@ impl From<StorageView> for IndexMap<ContractAddress, IndexMap<StorageKey, StarkF
     }
 }
 
+/// Holds a value per state cell; e.g., the values read or written by a transaction.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StateMaps {
+    pub nonces: HashMap<ContractAddress, Nonce>,
+    pub class_hashes: HashMap<ContractAddress, ClassHash>,
+    pub storage: HashMap<ContractStorageKey, StarkFelt>,
+    pub compiled_class_hashes: HashMap<ClassHash, CompiledClassHash>,
+}
+
 /// Caches read and write requests.
 /// The tracked changes are needed for block state commitment.
 
@@ -591,6 +697,44 @@ pub struct CommitmentStateDiff {
     pub class_hash_to_compiled_class_hash: IndexMap<ClassHash, CompiledClassHash>,
 }
 
+impl CommitmentStateDiff {
+    /// Returns the state diff in the layout of the Python `StateDiff` (as reported by
+    /// `native_blockifier`): nested maps, with felts as decimal strings.
+    pub fn to_py_compatible_json(&self) -> serde_json::Value {
+        let felt_value = |felt: &StarkFelt| serde_json::Value::from(felt_to_decimal_string(felt));
+
+        serde_json::json!({
+            "address_to_class_hash": felt_keyed_json_object(
+                self.address_to_class_hash
+                    .iter()
+                    .map(|(address, class_hash)| (*address.0.key(), felt_value(&class_hash.0)))
+            ),
+            "address_to_nonce": felt_keyed_json_object(
+                self.address_to_nonce
+                    .iter()
+                    .map(|(address, nonce)| (*address.0.key(), felt_value(&nonce.0)))
+            ),
+            "storage_updates": felt_keyed_json_object(
+                self.storage_updates.iter().map(|(address, storage_updates)| {
+                    let storage_updates = felt_keyed_json_object(
+                        storage_updates
+                            .iter()
+                            .map(|(key, value)| (*key.0.key(), felt_value(value))),
+                    );
+                    (*address.0.key(), storage_updates)
+                })
+            ),
+            "class_hash_to_compiled_class_hash": felt_keyed_json_object(
+                self.class_hash_to_compiled_class_hash
+                    .iter()
+                    .map(|(class_hash, compiled_class_hash)| {
+                        (class_hash.0, felt_value(&compiled_class_hash.0))
+                    })
+            ),
+        })
+    }
+}
+
 /// Holds the state changes.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct StateChanges {
@@ -619,7 +763,7 @@ impl StateChanges {
 }
 
 /// Holds the number of state changes.
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct StateChangesCount {
     pub n_storage_updates: usize,
     pub n_class_hash_updates: usize,
@@ -638,6 +782,82 @@ impl From<&StateChanges> for StateChangesCount {
     }
 }
 
+impl From<&CommitmentStateDiff> for StateChangesCount {
+    fn from(state_diff: &CommitmentStateDiff) -> Self {
+        StateChangesKeys::from(state_diff).count()
+    }
+}
+
+/// Holds the keys of the state changes (what was modified, regardless of the new values); used to
+/// count only the changes of a transaction that were not already made earlier in the block.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StateChangesKeys {
+    pub modified_contracts: HashSet<ContractAddress>,
+    pub storage_keys: HashSet<(ContractAddress, StorageKey)>,
+    pub class_hash_keys: HashSet<ContractAddress>,
+    pub compiled_class_hash_keys: HashSet<ClassHash>,
+}
+
+impl StateChangesKeys {
+    /// Returns the keys of `self` that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            modified_contracts: &self.modified_contracts - &other.modified_contracts,
+            storage_keys: &self.storage_keys - &other.storage_keys,
+            class_hash_keys: &self.class_hash_keys - &other.class_hash_keys,
+            compiled_class_hash_keys: &self.compiled_class_hash_keys
+                - &other.compiled_class_hash_keys,
+        }
+    }
+
+    /// Adds the keys of `other` to `self`.
+    pub fn extend(&mut self, other: &Self) {
+        self.modified_contracts.extend(&other.modified_contracts);
+        self.storage_keys.extend(&other.storage_keys);
+        self.class_hash_keys.extend(&other.class_hash_keys);
+        self.compiled_class_hash_keys.extend(&other.compiled_class_hash_keys);
+    }
+
+    pub fn count(&self) -> StateChangesCount {
+        StateChangesCount {
+            n_storage_updates: self.storage_keys.len(),
+            n_class_hash_updates: self.class_hash_keys.len(),
+            n_compiled_class_hash_updates: self.compiled_class_hash_keys.len(),
+            n_modified_contracts: self.modified_contracts.len(),
+        }
+    }
+}
+
+impl From<&CommitmentStateDiff> for StateChangesKeys {
+    fn from(state_diff: &CommitmentStateDiff) -> Self {
+        let modified_contracts = state_diff
+            .address_to_class_hash
+            .keys()
+            .chain(state_diff.address_to_nonce.keys())
+            .chain(state_diff.storage_updates.keys())
+            .copied()
+            .collect();
+        let storage_keys = state_diff
+            .storage_updates
+            .iter()
+            .flat_map(|(address, storage_updates)| {
+                storage_updates.keys().map(|key| (*address, *key))
+            })
+            .collect();
+
+        Self {
+            modified_contracts,
+            storage_keys,
+            class_hash_keys: state_diff.address_to_class_hash.keys().copied().collect(),
+            compiled_class_hash_keys: state_diff
+                .class_hash_to_compiled_class_hash
+                .keys()
+                .copied()
+                .collect(),
+        }
+    }
+}
+
 // Note: `ContractClassLRUCache` key-value types must align with `ContractClassMapping`.
 type ContractClassLRUCache = SizedCache<ClassHash, ContractClass>;
 #[derive(Debug, Clone, derive_more::Deref, derive_more::DerefMut)]
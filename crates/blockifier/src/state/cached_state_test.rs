@@ -3,12 +3,14 @@ use std::collections::HashMap;
 use assert_matches::assert_matches;
 use indexmap::indexmap;
 use pretty_assertions::assert_eq;
+use rstest::rstest;
 use starknet_api::core::PatriciaKey;
 use starknet_api::hash::StarkHash;
 use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
 
 use super::*;
 use crate::block_context::BlockContext;
+use crate::fee::gas_usage::get_onchain_data_segment_length;
 use crate::test_utils::{
     deprecated_create_test_state, get_test_contract_class, DictStateReader, TEST_CLASS_HASH,
     TEST_EMPTY_CONTRACT_CLASS_HASH,
@@ -279,7 +281,7 @@ fn create_state_changes_for_test<S: StateReader>(state: &mut CachedState<S>) ->
     // Return the resulting state changes.
     state
         .get_actual_state_changes_for_fee_charge(
-            block_context.fee_token_address,
+            block_context.fee_token_addresses.eth_fee_token_address,
             Some(contract_address),
         )
         .unwrap()
@@ -300,6 +302,47 @@ fn count_actual_state_changes() {
     );
 }
 
+/// Writes to 3 storage cells, of a single contract or of 3 different ones; each modified contract
+/// adds its header (2 words) to the state diff once, and each cell adds 2 words.
+#[rstest]
+#[case::one_contract(["0x100", "0x100", "0x100"], 1)]
+#[case::three_contracts(["0x100", "0x101", "0x102"], 3)]
+fn test_modified_contract_header_count(
+    #[case] contract_addresses: [&str; 3],
+    #[case] expected_n_modified_contracts: usize,
+) {
+    let mut state: CachedState<DictStateReader> = CachedState::default();
+    for (key, contract_address) in contract_addresses.into_iter().enumerate() {
+        let key = StorageKey(patricia_key!(key as u8 + 1));
+        state.set_storage_at(contract_address!(contract_address), key, stark_felt!(7_u8));
+        // Writing the same cell again is not an additional change.
+        state.set_storage_at(contract_address!(contract_address), key, stark_felt!(8_u8));
+    }
+    let block_context = BlockContext::create_for_testing();
+    let state_changes = state
+        .get_actual_state_changes_for_fee_charge(
+            block_context.fee_token_addresses.eth_fee_token_address,
+            None,
+        )
+        .unwrap();
+
+    let state_changes_count = StateChangesCount::from(&state_changes);
+    assert_eq!(
+        state_changes_count,
+        StateChangesCount {
+            n_storage_updates: 3,
+            n_modified_contracts: expected_n_modified_contracts,
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        get_onchain_data_segment_length(state_changes_count),
+        2 * expected_n_modified_contracts + 2 * 3
+    );
+    // The same count is obtained from the state diff.
+    assert_eq!(StateChangesCount::from(&state.to_state_diff()), state_changes_count);
+}
+
 #[test]
 fn test_state_changes_merge() {
     // Create a transactional state containing the `create_state_changes_for_test` logic, get the
@@ -317,7 +360,10 @@ fn test_state_changes_merge() {
     // state returns null state changes and that merging null state changes with non-null state
     // changes results in the non-null state changes, no matter the order.
     let state_changes2 = transactional_state
-        .get_actual_state_changes_for_fee_charge(block_context.fee_token_address, None)
+        .get_actual_state_changes_for_fee_charge(
+            block_context.fee_token_addresses.eth_fee_token_address,
+            None,
+        )
         .unwrap();
     assert_eq!(state_changes2, StateChanges::default());
     assert_eq!(
@@ -343,7 +389,10 @@ fn test_state_changes_merge() {
     transactional_state.increment_nonce(contract_address).unwrap();
     // Get the new state changes and then commit the transactional state.
     let state_changes3 = transactional_state
-        .get_actual_state_changes_for_fee_charge(block_context.fee_token_address, None)
+        .get_actual_state_changes_for_fee_charge(
+            block_context.fee_token_addresses.eth_fee_token_address,
+            None,
+        )
         .unwrap();
     transactional_state.commit();
 
@@ -351,7 +400,10 @@ fn test_state_changes_merge() {
     // states. We expect the state_changes to match the merged state_changes of the transactional
     // states, but only when done in the right order.
     let state_changes_final = state
-        .get_actual_state_changes_for_fee_charge(block_context.fee_token_address, None)
+        .get_actual_state_changes_for_fee_charge(
+            block_context.fee_token_addresses.eth_fee_token_address,
+            None,
+        )
         .unwrap();
     assert_eq!(
         StateChanges::merge(vec![
@@ -3,8 +3,12 @@ use starknet_api::core::{ClassHash, ContractAddress};
 use starknet_api::StarknetApiError;
 use thiserror::Error;
 
+use crate::state::witness_state_reader::WitnessEntry;
+
 #[derive(Debug, Error)]
 pub enum StateError {
+    #[error("Missing witness entry: {entry:?}.")]
+    MissingWitnessEntry { entry: WitnessEntry },
     #[error("Cannot deploy contract at address 0.")]
     OutOfRangeContractAddress,
     #[error(transparent)]
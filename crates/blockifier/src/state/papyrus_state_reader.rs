@@ -0,0 +1,144 @@
+use papyrus_storage::compiled_class::CasmStorageReader;
+use papyrus_storage::db::RO;
+use papyrus_storage::state::StateStorageReader;
+use papyrus_storage::{StorageError, StorageReader, StorageTxn};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::{StateNumber, StorageKey};
+
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+// The test storage is a `papyrus_storage` testing utility.
+#[cfg(all(test, feature = "testing"))]
+#[path = "papyrus_state_reader_test.rs"]
+mod test;
+
+/// A state reader over papyrus storage, at the given state number; e.g., to execute the block
+/// following it. Classes declared after the state number are undeclared.
+#[derive(Clone)]
+pub struct PapyrusStateReader {
+    storage_reader: StorageReader,
+    state_number: StateNumber,
+}
+
+impl PapyrusStateReader {
+    pub fn new(storage_reader: StorageReader, state_number: StateNumber) -> Self {
+        Self { storage_reader, state_number }
+    }
+
+    /// Returns a reader of the state at the beginning of the given block.
+    pub fn at_block(storage_reader: StorageReader, block_number: BlockNumber) -> Self {
+        Self::new(storage_reader, StateNumber::right_before_block(block_number))
+    }
+
+    fn txn(&self) -> StateResult<StorageTxn<'_, RO>> {
+        self.storage_reader.begin_ro_txn().map_err(storage_error)
+    }
+
+    /// Returns the block in which the given Cairo 1 class was declared, if it was declared up to
+    /// the state number.
+    fn cairo1_class_declaration_block(
+        &self,
+        txn: &StorageTxn<'_, RO>,
+        class_hash: &ClassHash,
+    ) -> StateResult<Option<BlockNumber>> {
+        let block_number = txn
+            .get_state_reader()
+            .and_then(|state_reader| state_reader.get_class_definition_block_number(class_hash))
+            .map_err(storage_error)?;
+        Ok(block_number.filter(|block_number| self.state_number.is_after(*block_number)))
+    }
+}
+
+impl StateReader for PapyrusStateReader {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        self.txn()?
+            .get_state_reader()
+            .and_then(|state_reader| {
+                state_reader.get_storage_at(self.state_number, &contract_address, &key)
+            })
+            .map_err(storage_error)
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        let nonce = self
+            .txn()?
+            .get_state_reader()
+            .and_then(|state_reader| {
+                state_reader.get_nonce_at(self.state_number, &contract_address)
+            })
+            .map_err(storage_error)?;
+        Ok(nonce.unwrap_or_default())
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        let class_hash = self
+            .txn()?
+            .get_state_reader()
+            .and_then(|state_reader| {
+                state_reader.get_class_hash_at(self.state_number, &contract_address)
+            })
+            .map_err(storage_error)?;
+        Ok(class_hash.unwrap_or_default())
+    }
+
+    /// Returns the compiled (Casm) class of a declared Cairo 1 class, or a declared deprecated
+    /// class.
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        let txn = self.txn()?;
+        if self.cairo1_class_declaration_block(&txn, class_hash)?.is_some() {
+            let casm_contract_class =
+                txn.get_casm(class_hash).map_err(storage_error)?.ok_or_else(|| {
+                    StateError::StateReadError(format!(
+                        "Missing the compiled class of the declared class {class_hash:?}"
+                    ))
+                })?;
+            return Ok(ContractClassV1::try_from(casm_contract_class)?.into());
+        }
+
+        let deprecated_contract_class = txn
+            .get_state_reader()
+            .and_then(|state_reader| {
+                state_reader.get_deprecated_class_definition_at(self.state_number, class_hash)
+            })
+            .map_err(storage_error)?;
+        match deprecated_contract_class {
+            Some(deprecated_contract_class) => {
+                Ok(ContractClassV0::try_from(deprecated_contract_class)?.into())
+            }
+            None => Err(StateError::UndeclaredClassHash(*class_hash)),
+        }
+    }
+
+    /// Returns the compiled class hash of a declared Cairo 1 class, from the state diff declaring
+    /// it; the default value otherwise.
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        let txn = self.txn()?;
+        let Some(block_number) = self.cairo1_class_declaration_block(&txn, &class_hash)? else {
+            return Ok(CompiledClassHash::default());
+        };
+        let state_diff = txn.get_state_diff(block_number).map_err(storage_error)?;
+        let compiled_class_hash = state_diff
+            .and_then(|state_diff| state_diff.declared_classes.get(&class_hash).copied())
+            .ok_or_else(|| {
+                StateError::StateReadError(format!(
+                    "Missing the compiled class hash of the declared class {class_hash:?}"
+                ))
+            })?;
+        Ok(compiled_class_hash)
+    }
+}
+
+fn storage_error(error: StorageError) -> StateError {
+    StateError::StateReadError(error.to_string())
+}
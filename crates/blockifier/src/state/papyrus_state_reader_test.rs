@@ -0,0 +1,165 @@
+use assert_matches::assert_matches;
+use indexmap::IndexMap;
+use papyrus_storage::compiled_class::CasmStorageWriter;
+use papyrus_storage::state::StateStorageWriter;
+use papyrus_storage::test_utils::get_test_storage;
+use papyrus_storage::StorageWriter;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::{
+    ContractClass as SierraContractClass, StateDiff, StateNumber, StorageKey,
+};
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::state::errors::StateError;
+use crate::state::papyrus_state_reader::PapyrusStateReader;
+use crate::state::state_api::StateReader;
+use crate::test_utils::{
+    get_deprecated_contract_class, get_raw_contract_class, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+    TEST_CONTRACT_CAIRO0_PATH, TEST_CONTRACT_CAIRO1_PATH,
+};
+
+const CAIRO1_CLASS_HASH: &str = "0x1110";
+const CAIRO1_COMPILED_CLASS_HASH: &str = "0x1111";
+const STORAGE_KEY: &str = "0x1234";
+const STORAGE_VALUE: u8 = 18;
+const NONCE: u8 = 3;
+
+/// Writes two blocks to the given storage: the first deploys a contract of a deprecated class
+/// (declaring it implicitly), and the second declares a Cairo 1 class.
+fn write_blocks(storage_writer: &mut StorageWriter) {
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let deprecated_class_hash = class_hash!(TEST_CLASS_HASH);
+    let first_state_diff = StateDiff {
+        deployed_contracts: IndexMap::from([(contract_address, deprecated_class_hash)]),
+        storage_diffs: IndexMap::from([(
+            contract_address,
+            IndexMap::from([(StorageKey(patricia_key!(STORAGE_KEY)), stark_felt!(STORAGE_VALUE))]),
+        )]),
+        nonces: IndexMap::from([(contract_address, Nonce(stark_felt!(NONCE)))]),
+        ..Default::default()
+    };
+    let deployed_contract_class_definitions = IndexMap::from([(
+        deprecated_class_hash,
+        get_deprecated_contract_class(TEST_CONTRACT_CAIRO0_PATH),
+    )]);
+
+    let cairo1_class_hash = class_hash!(CAIRO1_CLASS_HASH);
+    let second_state_diff = StateDiff {
+        declared_classes: IndexMap::from([(
+            cairo1_class_hash,
+            (
+                CompiledClassHash(stark_felt!(CAIRO1_COMPILED_CLASS_HASH)),
+                SierraContractClass::default(),
+            ),
+        )]),
+        ..Default::default()
+    };
+    let casm_contract_class =
+        serde_json::from_str(&get_raw_contract_class(TEST_CONTRACT_CAIRO1_PATH)).unwrap();
+
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(0), first_state_diff, deployed_contract_class_definitions)
+        .unwrap()
+        .append_state_diff(BlockNumber(1), second_state_diff, IndexMap::new())
+        .unwrap()
+        .append_casm(&cairo1_class_hash, &casm_contract_class)
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+#[test]
+fn test_state_reads() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut storage_writer);
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let mut state_reader = PapyrusStateReader::at_block(storage_reader.clone(), BlockNumber(1));
+
+    assert_eq!(
+        state_reader
+            .get_storage_at(contract_address, StorageKey(patricia_key!(STORAGE_KEY)))
+            .unwrap(),
+        stark_felt!(STORAGE_VALUE)
+    );
+    assert_eq!(state_reader.get_nonce_at(contract_address).unwrap(), Nonce(stark_felt!(NONCE)));
+    assert_eq!(
+        state_reader.get_class_hash_at(contract_address).unwrap(),
+        class_hash!(TEST_CLASS_HASH)
+    );
+
+    // Undeployed contracts have default values.
+    let undeployed_contract_address = contract_address!("0x999");
+    assert_eq!(
+        state_reader
+            .get_storage_at(undeployed_contract_address, StorageKey(patricia_key!(STORAGE_KEY)))
+            .unwrap(),
+        StarkFelt::default()
+    );
+    assert_eq!(state_reader.get_nonce_at(undeployed_contract_address).unwrap(), Nonce::default());
+    assert_eq!(
+        state_reader.get_class_hash_at(undeployed_contract_address).unwrap(),
+        ClassHash::default()
+    );
+
+    // The contract is not deployed before the first block.
+    let mut state_reader = PapyrusStateReader::at_block(storage_reader, BlockNumber(0));
+    assert_eq!(state_reader.get_class_hash_at(contract_address).unwrap(), ClassHash::default());
+}
+
+#[test]
+fn test_class_reads() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut storage_writer);
+    let deprecated_class_hash = class_hash!(TEST_CLASS_HASH);
+    let cairo1_class_hash = class_hash!(CAIRO1_CLASS_HASH);
+    let expected_deprecated_class = ContractClass::V0(
+        ContractClassV0::try_from(get_deprecated_contract_class(TEST_CONTRACT_CAIRO0_PATH))
+            .unwrap(),
+    );
+
+    // After both blocks, both classes are declared.
+    let mut state_reader = PapyrusStateReader::new(
+        storage_reader.clone(),
+        StateNumber::right_after_block(BlockNumber(1)),
+    );
+    assert_eq!(
+        state_reader.get_compiled_contract_class(&deprecated_class_hash).unwrap(),
+        expected_deprecated_class
+    );
+    assert_eq!(
+        state_reader.get_compiled_contract_class(&cairo1_class_hash).unwrap(),
+        ContractClass::V1(ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH))
+    );
+    assert_eq!(
+        state_reader.get_compiled_class_hash(cairo1_class_hash).unwrap(),
+        CompiledClassHash(stark_felt!(CAIRO1_COMPILED_CLASS_HASH))
+    );
+
+    // The Cairo 1 class, declared in the second block, is undeclared at its beginning.
+    let mut state_reader = PapyrusStateReader::at_block(storage_reader.clone(), BlockNumber(1));
+    assert_eq!(
+        state_reader.get_compiled_contract_class(&deprecated_class_hash).unwrap(),
+        expected_deprecated_class
+    );
+    assert_matches!(
+        state_reader.get_compiled_contract_class(&cairo1_class_hash),
+        Err(StateError::UndeclaredClassHash(class_hash)) if class_hash == cairo1_class_hash
+    );
+    assert_eq!(
+        state_reader.get_compiled_class_hash(cairo1_class_hash).unwrap(),
+        CompiledClassHash::default()
+    );
+
+    // Neither class is declared before the first block.
+    let mut state_reader = PapyrusStateReader::at_block(storage_reader, BlockNumber(0));
+    assert_matches!(
+        state_reader.get_compiled_contract_class(&deprecated_class_hash),
+        Err(StateError::UndeclaredClassHash(class_hash)) if class_hash == deprecated_class_hash
+    );
+}
@@ -3,7 +3,6 @@ use starknet_api::hash::StarkFelt;
 use starknet_api::state::StorageKey;
 
 use crate::abi::abi_utils::get_erc20_balance_var_addresses;
-use crate::block_context::BlockContext;
 use crate::execution::contract_class::ContractClass;
 use crate::state::cached_state::CommitmentStateDiff;
 use crate::state::errors::StateError;
@@ -39,17 +38,18 @@ pub trait StateReader {
     /// Returns the compiled class hash of the given class hash.
     fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash>;
 
-    /// Returns the storage value representing the balance (in fee token) at the given address.
+    /// Returns the storage value representing the balance (in the given fee token) at the given
+    /// address.
     // TODO(Dori, 1/7/2023): When a standard representation for large integers is set, change the
     //    return type to that.
     fn get_fee_token_balance(
         &mut self,
-        block_context: &BlockContext,
         contract_address: &ContractAddress,
+        fee_token_address: &ContractAddress,
     ) -> Result<(StarkFelt, StarkFelt), StateError> {
         let (low_key, high_key) = get_erc20_balance_var_addresses(contract_address)?;
-        let low = self.get_storage_at(block_context.fee_token_address, low_key)?;
-        let high = self.get_storage_at(block_context.fee_token_address, high_key)?;
+        let low = self.get_storage_at(*fee_token_address, low_key)?;
+        let high = self.get_storage_at(*fee_token_address, high_key)?;
 
         Ok((low, high))
     }
@@ -0,0 +1,87 @@
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+
+use crate::execution::contract_class::ContractClass;
+use crate::state::cached_state::{ContractClassMapping, ContractStorageKey, StateMaps};
+use crate::state::errors::StateError;
+use crate::state::state_api::{StateReader, StateResult};
+
+/// A state cell that may be recorded in a witness.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WitnessEntry {
+    ClassHash(ContractAddress),
+    CompiledClassHash(ClassHash),
+    Nonce(ContractAddress),
+    Storage(ContractStorageKey),
+}
+
+/// A state reader serving only the given witness; i.e., the initial reads of a previous
+/// execution. Reading any other cell is an error, rather than a default value.
+/// Contract classes are content-addressed, and are served from a separate mapping.
+#[derive(Debug, Default)]
+pub struct WitnessStateReader {
+    witness: StateMaps,
+    class_hash_to_class: ContractClassMapping,
+    // Kept, since the error itself may be swallowed by the execution (e.g., by a revert).
+    first_missing_entry: Option<WitnessEntry>,
+}
+
+impl WitnessStateReader {
+    pub fn new(witness: StateMaps, class_hash_to_class: ContractClassMapping) -> Self {
+        Self { witness, class_hash_to_class, first_missing_entry: None }
+    }
+
+    /// Returns the first entry read outside the witness, if any.
+    pub fn first_missing_entry(&self) -> Option<WitnessEntry> {
+        self.first_missing_entry
+    }
+
+    fn missing_entry(&mut self, entry: WitnessEntry) -> StateError {
+        self.first_missing_entry.get_or_insert(entry);
+        StateError::MissingWitnessEntry { entry }
+    }
+}
+
+impl StateReader for WitnessStateReader {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        let contract_storage_key = (contract_address, key);
+        match self.witness.storage.get(&contract_storage_key) {
+            Some(value) => Ok(*value),
+            None => Err(self.missing_entry(WitnessEntry::Storage(contract_storage_key))),
+        }
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        match self.witness.nonces.get(&contract_address) {
+            Some(nonce) => Ok(*nonce),
+            None => Err(self.missing_entry(WitnessEntry::Nonce(contract_address))),
+        }
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        match self.witness.class_hashes.get(&contract_address) {
+            Some(class_hash) => Ok(*class_hash),
+            None => Err(self.missing_entry(WitnessEntry::ClassHash(contract_address))),
+        }
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        let contract_class = self.class_hash_to_class.get(class_hash).cloned();
+        contract_class.ok_or(StateError::UndeclaredClassHash(*class_hash))
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        match self.witness.compiled_class_hashes.get(&class_hash) {
+            Some(compiled_class_hash) => Ok(*compiled_class_hash),
+            None => Err(self.missing_entry(WitnessEntry::CompiledClassHash(class_hash))),
+        }
+    }
+}
@@ -1,18 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Arc;
 
 use cairo_felt::Felt252;
 use cairo_vm::vm::runners::builtin_runner::{
-    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, OUTPUT_BUILTIN_NAME,
-    POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    OUTPUT_BUILTIN_NAME, POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SIGNATURE_BUILTIN_NAME,
 };
 use num_traits::{One, Zero};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::{
-    calculate_contract_address, ChainId, ClassHash, CompiledClassHash, ContractAddress,
-    EntryPointSelector, Nonce, PatriciaKey,
+    calculate_contract_address, ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector,
+    Nonce, PatriciaKey,
 };
 use starknet_api::deprecated_contract_class::{
     ContractClass as DeprecatedContractClass, EntryPointType,
@@ -25,20 +24,41 @@ use starknet_api::transaction::{
 };
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
 
-use crate::abi::abi_utils::get_storage_var_address;
+use crate::abi::abi_utils::{get_erc20_balance_var_addresses, get_storage_var_address};
 use crate::abi::constants;
-use crate::block_context::BlockContext;
+use crate::block_context::{
+    BlockContext, BlockContextBuilder, ChainId, FeeTokenAddresses, GasPrices, ValidationOptOuts,
+};
 use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
 use crate::execution::entry_point::{
-    CallEntryPoint, CallExecution, CallInfo, CallType, EntryPointExecutionContext,
-    EntryPointExecutionResult, ExecutionResources, Retdata,
+    CallEntryPoint, CallExecution, CallInfo, CallType, ConstructorContext,
+    EntryPointExecutionContext, EntryPointExecutionResult, ExecutionResources, Retdata,
+};
+use crate::execution::execution_utils::{
+    execute_deployment, felt_to_stark_felt, stark_felt_to_felt,
 };
-use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::selector;
 use crate::state::cached_state::{CachedState, ContractClassMapping, ContractStorageKey};
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader, StateResult};
+use crate::test_utils::transaction_builders::{DeclareTxArgs, DeployAccountTxArgs, InvokeTxArgs};
+use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::objects::AccountTransactionContext;
 use crate::transaction::transactions::DeployAccountTransaction;
+use crate::versioned_constants::StarknetVersion;
+
+/// Random generators of transaction fields, biased towards edge values, and the shrinking of
+/// failing invokes; for fuzzing the transaction pipeline (see the bounded fuzz test).
+pub mod fuzzing;
+/// Builders of ready-to-execute account transactions, with per-field overrides of defaults (e.g.,
+/// `InvokeTxArgs { calldata, ..Default::default() }.build(state)`); the version-specific fields are
+/// populated by the version, an unset nonce is read from the state, and an unset hash is a
+/// placeholder.
+pub mod transaction_builders;
+
+#[cfg(test)]
+#[path = "test_utils_test.rs"]
+pub mod test;
 
 // Addresses.
 pub const TEST_CONTRACT_ADDRESS: &str = "0x100";
@@ -46,8 +66,10 @@ pub const TEST_CONTRACT_ADDRESS_2: &str = "0x200";
 pub const SECURITY_TEST_CONTRACT_ADDRESS: &str = "0x300";
 pub const TEST_ACCOUNT_CONTRACT_ADDRESS: &str = "0x101";
 pub const TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS: &str = "0x102";
+pub const TEST_CAIRO1_ACCOUNT_CONTRACT_ADDRESS: &str = "0x103";
 pub const TEST_SEQUENCER_ADDRESS: &str = "0x1000";
 pub const TEST_ERC20_CONTRACT_ADDRESS: &str = "0x1001";
+pub const TEST_ERC20_STRK_CONTRACT_ADDRESS: &str = "0x1002";
 
 // Class hashes.
 pub const TEST_CLASS_HASH: &str = "0x110";
@@ -55,6 +77,7 @@ pub const TEST_ACCOUNT_CONTRACT_CLASS_HASH: &str = "0x111";
 pub const TEST_EMPTY_CONTRACT_CLASS_HASH: &str = "0x112";
 pub const TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH: &str = "0x113";
 pub const SECURITY_TEST_CLASS_HASH: &str = "0x114";
+pub const TEST_CAIRO1_ACCOUNT_CONTRACT_CLASS_HASH: &str = "0x115";
 // TODO(Adi, 15/01/2023): Remove and compute the class hash corresponding to the ERC20 contract in
 // starkgate once we use the real ERC20 contract.
 pub const TEST_ERC20_CONTRACT_CLASS_HASH: &str = "0x1010";
@@ -78,6 +101,7 @@ pub const TEST_FAULTY_ACCOUNT_CONTRACT_CAIRO0_PATH: &str =
     "./feature_contracts/cairo0/compiled/account_faulty_compiled.json";
 pub const ERC20_CONTRACT_PATH: &str =
     "./ERC20_without_some_syscalls/ERC20/erc20_contract_without_some_syscalls_compiled.json";
+pub const BLOCK_CONTEXT_FIXTURE_PATH: &str = "./resources/testing/block_context.json";
 
 // Storage keys.
 pub fn test_erc20_sequencer_balance_key() -> StorageKey {
@@ -99,9 +123,13 @@ pub const MAX_FEE: u128 = 1000000 * 100000000000; // 1000000 * min_gas_price.
 pub const BALANCE: u128 = 10 * MAX_FEE;
 
 pub const DEFAULT_GAS_PRICE: u128 = 100 * u128::pow(10, 9); // Given in units of wei.
+pub const DEFAULT_STRK_GAS_PRICE: u128 = 100 * u128::pow(10, 9); // Given in units of fri.
+pub const DEFAULT_DATA_GAS_PRICE: u128 = u128::pow(10, 9); // Given in units of wei.
+pub const DEFAULT_STRK_DATA_GAS_PRICE: u128 = u128::pow(10, 9); // Given in units of fri.
 
 // The block number of the BlockContext being used for testing.
 pub const CURRENT_BLOCK_NUMBER: u64 = 2000;
+pub const CURRENT_BLOCK_TIMESTAMP: u64 = 1072023;
 
 /// A simple implementation of `StateReader` using `HashMap`s as storage.
 #[derive(Debug, Default)]
@@ -156,12 +184,68 @@ impl StateReader for DictStateReader {
     }
 }
 
+/// A state reader failing the storage reads of the given contract, as a faulty storage would; other
+/// reads go to the inner reader.
+#[derive(Debug)]
+pub struct FailingStorageReader {
+    pub state_reader: DictStateReader,
+    pub failing_contract_address: ContractAddress,
+}
+
+impl StateReader for FailingStorageReader {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        if contract_address == self.failing_contract_address {
+            return Err(StateError::StateReadError(format!("Storage of {contract_address:?}")));
+        }
+        self.state_reader.get_storage_at(contract_address, key)
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        self.state_reader.get_nonce_at(contract_address)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        self.state_reader.get_compiled_contract_class(class_hash)
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        self.state_reader.get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.state_reader.get_compiled_class_hash(class_hash)
+    }
+}
+
 #[derive(Default)]
 pub struct NonceManager {
     next_nonce: HashMap<ContractAddress, Felt252>,
 }
 
 impl NonceManager {
+    /// Returns a manager that continues from the current nonces of the given accounts in the
+    /// state; e.g., after transactions that read their nonces from it.
+    pub fn from_state(
+        state: &mut dyn StateReader,
+        account_addresses: &[ContractAddress],
+    ) -> NonceManager {
+        let next_nonce = account_addresses
+            .iter()
+            .map(|&account_address| {
+                let nonce = state.get_nonce_at(account_address).unwrap();
+                (account_address, stark_felt_to_felt(nonce.0))
+            })
+            .collect();
+        NonceManager { next_nonce }
+    }
+
     pub fn next(&mut self, account_address: ContractAddress) -> Nonce {
         let zero = Felt252::zero();
         let next_felt252 = self.next_nonce.get(&account_address).unwrap_or(&zero);
@@ -322,43 +406,112 @@ pub fn create_deploy_test_state() -> CachedState<DictStateReader> {
 impl CallEntryPoint {
     // Executes the call directly, without account context.
     pub fn execute_directly(self, state: &mut dyn State) -> EntryPointExecutionResult<CallInfo> {
-        let block_context = BlockContext::create_for_testing();
+        self.execute_directly_given_block_context(state, &BlockContext::create_for_testing())
+    }
+
+    pub fn execute_directly_given_block_context(
+        self,
+        state: &mut dyn State,
+        block_context: &BlockContext,
+    ) -> EntryPointExecutionResult<CallInfo> {
         let mut context = EntryPointExecutionContext::new_invoke(
-            &block_context,
+            block_context,
             &AccountTransactionContext::default(),
-        );
+        )
+        .unwrap();
         self.execute(state, &mut ExecutionResources::default(), &mut context)
     }
 }
 
+pub fn test_fee_token_addresses() -> FeeTokenAddresses {
+    FeeTokenAddresses {
+        eth_fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS),
+        strk_fee_token_address: contract_address!(TEST_ERC20_STRK_CONTRACT_ADDRESS),
+    }
+}
+
+/// Unit weights for all Cairo resources.
+pub fn test_vm_resource_fee_cost() -> HashMap<String, f64> {
+    HashMap::from([
+        (constants::N_STEPS_RESOURCE.to_string(), 1_f64),
+        (HASH_BUILTIN_NAME.to_string(), 1_f64),
+        (RANGE_CHECK_BUILTIN_NAME.to_string(), 1_f64),
+        (SIGNATURE_BUILTIN_NAME.to_string(), 1_f64),
+        (BITWISE_BUILTIN_NAME.to_string(), 1_f64),
+        (POSEIDON_BUILTIN_NAME.to_string(), 1_f64),
+        (OUTPUT_BUILTIN_NAME.to_string(), 1_f64),
+        (EC_OP_BUILTIN_NAME.to_string(), 1_f64),
+        (KECCAK_BUILTIN_NAME.to_string(), 1_f64),
+    ])
+}
+
 impl BlockContext {
+    /// A mainnet-like block context: a positive block number and nonzero gas prices.
     pub fn create_for_testing() -> BlockContext {
-        BlockContext {
-            chain_id: ChainId("SN_GOERLI".to_string()),
-            block_number: BlockNumber(CURRENT_BLOCK_NUMBER),
-            block_timestamp: BlockTimestamp::default(),
-            sequencer_address: contract_address!(TEST_SEQUENCER_ADDRESS),
-            fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS),
-            vm_resource_fee_cost: Default::default(),
-            gas_price: DEFAULT_GAS_PRICE,
-            invoke_tx_max_n_steps: 1_000_000,
-            validate_max_n_steps: 1_000_000,
-            max_recursion_depth: 50,
-        }
+        Self::create_for_testing_with_version(StarknetVersion::LATEST)
+    }
+
+    /// As `create_for_testing`, with the constants of the given Starknet version.
+    pub fn create_for_testing_with_version(starknet_version: StarknetVersion) -> BlockContext {
+        BlockContextBuilder::new(
+            ChainId::Other("SN_GOERLI".to_string()),
+            BlockNumber(CURRENT_BLOCK_NUMBER),
+            BlockTimestamp(CURRENT_BLOCK_TIMESTAMP),
+            contract_address!(TEST_SEQUENCER_ADDRESS),
+            GasPrices::new(
+                DEFAULT_GAS_PRICE,
+                DEFAULT_STRK_GAS_PRICE,
+                DEFAULT_DATA_GAS_PRICE,
+                DEFAULT_STRK_DATA_GAS_PRICE,
+                false,
+            )
+            .unwrap(),
+            test_fee_token_addresses(),
+        )
+        .starknet_version(starknet_version)
+        .max_n_steps(1_000_000, 1_000_000)
+        .build()
+        .unwrap()
+    }
+
+    /// A devnet-like block context: the genesis block, with zero gas prices (i.e., fee disabled) and
+    /// no timestamp.
+    pub fn create_for_devnet_testing() -> BlockContext {
+        BlockContextBuilder::new(
+            ChainId::Other("SN_DEVNET".to_string()),
+            BlockNumber(0),
+            BlockTimestamp::default(),
+            contract_address!(TEST_SEQUENCER_ADDRESS),
+            GasPrices::new(0, 0, 0, 0, true).unwrap(),
+            test_fee_token_addresses(),
+        )
+        .validation_opt_outs(ValidationOptOuts {
+            allow_zero_gas_prices: true,
+            allow_zero_block_timestamp: true,
+            allow_zero_sequencer_address: true,
+        })
+        .require_positive_block_number(false)
+        .vm_resource_fee_cost(test_vm_resource_fee_cost())
+        .build()
+        .unwrap()
     }
 
     pub fn create_for_account_testing() -> BlockContext {
-        let vm_resource_fee_cost = Arc::new(HashMap::from([
-            (constants::N_STEPS_RESOURCE.to_string(), 1_f64),
-            (HASH_BUILTIN_NAME.to_string(), 1_f64),
-            (RANGE_CHECK_BUILTIN_NAME.to_string(), 1_f64),
-            (SIGNATURE_BUILTIN_NAME.to_string(), 1_f64),
-            (BITWISE_BUILTIN_NAME.to_string(), 1_f64),
-            (POSEIDON_BUILTIN_NAME.to_string(), 1_f64),
-            (OUTPUT_BUILTIN_NAME.to_string(), 1_f64),
-            (EC_OP_BUILTIN_NAME.to_string(), 1_f64),
-        ]));
-        BlockContext { vm_resource_fee_cost, ..BlockContext::create_for_testing() }
+        Self::create_for_account_testing_with_version(StarknetVersion::LATEST)
+    }
+
+    /// As `create_for_account_testing`, with the constants of the given Starknet version.
+    pub fn create_for_account_testing_with_version(
+        starknet_version: StarknetVersion,
+    ) -> BlockContext {
+        let mut block_context = BlockContext::create_for_testing_with_version(starknet_version);
+        block_context.versioned_constants_mut().vm_resource_fee_cost = test_vm_resource_fee_cost();
+        block_context
+    }
+
+    /// The block context of the JSON fixture; equivalent to the account testing one, on Sepolia.
+    pub fn create_from_fixture() -> BlockContext {
+        serde_json::from_str(&get_raw_contract_class(BLOCK_CONTEXT_FIXTURE_PATH)).unwrap()
     }
 }
 
@@ -431,6 +584,198 @@ pub fn declare_tx(
     }
 }
 
+// Test init data.
+
+/// A funded state for transaction tests: the Cairo 0 and Cairo 1 account classes, each declared
+/// and deployed once, and the ERC20 class, deployed at the block's fee token addresses. Both
+/// accounts hold `balance` in both fee tokens; these are initial values of the state, rather than
+/// writes.
+pub struct TestInitData {
+    pub state: CachedState<DictStateReader>,
+    pub block_context: BlockContext,
+    pub cairo0_account_address: ContractAddress,
+    pub cairo1_account_address: ContractAddress,
+    pub cairo0_account_class_hash: ClassHash,
+    pub cairo1_account_class_hash: ClassHash,
+    pub erc20_class_hash: ClassHash,
+    /// The (low, high) balance of each account, in each fee token.
+    pub balance: (u128, u128),
+    /// Used by the transaction builders for transactions without an explicit nonce.
+    pub nonce_manager: NonceManager,
+}
+
+pub fn create_test_init_data(block_context: BlockContext) -> TestInitData {
+    create_test_init_data_with_balance(block_context, (BALANCE, 0))
+}
+
+pub fn create_test_init_data_with_balance(
+    block_context: BlockContext,
+    balance: (u128, u128),
+) -> TestInitData {
+    let cairo0_account_class_hash = class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH);
+    let cairo1_account_class_hash = class_hash!(TEST_CAIRO1_ACCOUNT_CONTRACT_CLASS_HASH);
+    let erc20_class_hash = class_hash!(TEST_ERC20_CONTRACT_CLASS_HASH);
+    let class_hash_to_class = HashMap::from([
+        (
+            cairo0_account_class_hash,
+            ContractClassV0::from_file(ACCOUNT_CONTRACT_CAIRO0_PATH).into(),
+        ),
+        (
+            cairo1_account_class_hash,
+            ContractClassV1::from_file(ACCOUNT_CONTRACT_CAIRO1_PATH).into(),
+        ),
+        (erc20_class_hash, ContractClassV0::from_file(ERC20_CONTRACT_PATH).into()),
+    ]);
+
+    let cairo0_account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let cairo1_account_address = contract_address!(TEST_CAIRO1_ACCOUNT_CONTRACT_ADDRESS);
+    let fee_token_addresses = &block_context.fee_token_addresses;
+    let address_to_class_hash = HashMap::from([
+        (cairo0_account_address, cairo0_account_class_hash),
+        (cairo1_account_address, cairo1_account_class_hash),
+        (fee_token_addresses.eth_fee_token_address, erc20_class_hash),
+        (fee_token_addresses.strk_fee_token_address, erc20_class_hash),
+    ]);
+    let storage_view = [cairo0_account_address, cairo1_account_address]
+        .into_iter()
+        .flat_map(|account_address| {
+            fee_token_balance_cells(&block_context, account_address, balance)
+        })
+        .collect();
+
+    TestInitData {
+        state: CachedState::from(DictStateReader {
+            address_to_class_hash,
+            class_hash_to_class,
+            storage_view,
+            ..Default::default()
+        }),
+        block_context,
+        cairo0_account_address,
+        cairo1_account_address,
+        cairo0_account_class_hash,
+        cairo1_account_class_hash,
+        erc20_class_hash,
+        balance,
+        nonce_manager: NonceManager::default(),
+    }
+}
+
+// The storage cells holding the given (low, high) balance of the account, in each fee token.
+fn fee_token_balance_cells(
+    block_context: &BlockContext,
+    account_address: ContractAddress,
+    (balance_low, balance_high): (u128, u128),
+) -> Vec<(ContractStorageKey, StarkFelt)> {
+    let (low_key, high_key) = get_erc20_balance_var_addresses(&account_address).unwrap();
+    let fee_token_addresses = &block_context.fee_token_addresses;
+    [fee_token_addresses.eth_fee_token_address, fee_token_addresses.strk_fee_token_address]
+        .into_iter()
+        .flat_map(|fee_token_address| {
+            [
+                ((fee_token_address, low_key), stark_felt!(balance_low)),
+                ((fee_token_address, high_key), stark_felt!(balance_high)),
+            ]
+        })
+        .collect()
+}
+
+/// Sets the balance of the account to the given amount, in both fee tokens. Both limbs are written;
+/// a high limb set earlier is cleared.
+pub fn fund_account(
+    state: &mut dyn State,
+    account_address: ContractAddress,
+    amount: u128,
+    block_context: &BlockContext,
+) {
+    for ((fee_token_address, key), value) in
+        fee_token_balance_cells(block_context, account_address, (amount, 0))
+    {
+        state.set_storage_at(fee_token_address, key, value);
+    }
+}
+
+/// Deploys an instance of the given (declared) class, as a deploy syscall of the zero address
+/// would: the class hash is set at the computed address, and the constructor (if any) runs on the
+/// state. Returns the address of the instance; panics if the deployment fails.
+pub fn deploy_contract(
+    state: &mut dyn State,
+    class_hash: ClassHash,
+    salt: ContractAddressSalt,
+    constructor_calldata: Calldata,
+) -> ContractAddress {
+    let deployer_address = ContractAddress::default();
+    let contract_address =
+        calculate_contract_address(salt, class_hash, &constructor_calldata, deployer_address)
+            .unwrap();
+    let ctor_context = ConstructorContext {
+        class_hash,
+        code_address: None,
+        storage_address: contract_address,
+        caller_address: deployer_address,
+    };
+    let mut context = EntryPointExecutionContext::new_invoke(
+        &BlockContext::create_for_testing(),
+        &AccountTransactionContext::default(),
+    )
+    .unwrap();
+    execute_deployment(
+        state,
+        &mut ExecutionResources::default(),
+        &mut context,
+        ctor_context,
+        constructor_calldata,
+        constants::INITIAL_GAS_COST,
+    )
+    .expect("Failed to deploy the contract.");
+
+    contract_address
+}
+
+/// Asserts the given call is the constructor call of a deployment of the given class by the given
+/// deployer; it is recorded whether the class has a constructor or not.
+pub fn assert_constructor_semantics(
+    call_info: &CallInfo,
+    contract_address: ContractAddress,
+    deployer_address: ContractAddress,
+    class_hash: ClassHash,
+) {
+    let call = &call_info.call;
+    assert_eq!(call.storage_address, contract_address);
+    assert_eq!(call.caller_address, deployer_address);
+    assert_eq!(call.class_hash, Some(class_hash));
+    assert_eq!(call.entry_point_type, EntryPointType::Constructor);
+    assert_eq!(call.entry_point_selector, selector!(constants::CONSTRUCTOR_ENTRY_POINT_NAME));
+}
+
+impl TestInitData {
+    pub fn invoke_tx(&mut self, args: InvokeTxArgs) -> AccountTransaction {
+        let nonce = args.nonce.unwrap_or_else(|| self.nonce_manager.next(args.sender_address));
+        InvokeTxArgs { nonce: Some(nonce), ..args }.build(&mut self.state)
+    }
+
+    pub fn declare_tx(&mut self, args: DeclareTxArgs) -> AccountTransaction {
+        let nonce = args.nonce.unwrap_or_else(|| self.nonce_manager.next(args.sender_address));
+        DeclareTxArgs { nonce: Some(nonce), ..args }.build(&mut self.state)
+    }
+
+    /// Returns the transaction, rather than an `AccountTransaction`, to expose the address of the
+    /// deployed account; e.g., to fund it (see `fund`).
+    pub fn deploy_account_tx(&mut self, args: DeployAccountTxArgs) -> DeployAccountTransaction {
+        let nonce = args.nonce.unwrap_or_else(|| self.nonce_manager.next(args.contract_address()));
+        DeployAccountTxArgs { nonce: Some(nonce), ..args }.build_deploy_account(&mut self.state)
+    }
+
+    /// Gives the account the init balance, in both fee tokens.
+    pub fn fund(&mut self, account_address: ContractAddress) {
+        for ((fee_token_address, key), value) in
+            fee_token_balance_cells(&self.block_context, account_address, self.balance)
+        {
+            self.state.set_storage_at(fee_token_address, key, value);
+        }
+    }
+}
+
 // Contract loaders.
 
 impl ContractClassV0 {
@@ -0,0 +1,169 @@
+use cairo_felt::Felt252;
+use num_traits::Zero;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use starknet_api::core::Nonce;
+use starknet_api::hash::StarkFelt;
+use starknet_api::stark_felt;
+use starknet_api::transaction::{Calldata, Fee, TransactionSignature, TransactionVersion};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::execution::execution_utils::felt_to_stark_felt;
+use crate::test_utils::transaction_builders::InvokeTxArgs;
+use crate::test_utils::{BALANCE, MAX_FEE, TEST_CONTRACT_ADDRESS};
+
+#[cfg(test)]
+#[path = "fuzzing_test.rs"]
+mod test;
+
+/// The entry points of the Cairo 0 test contract that generated invokes call.
+const TEST_CONTRACT_ENTRY_POINTS: [&str; 6] = [
+    "return_result",
+    "test_storage_read_write",
+    "write_and_revert",
+    "test_emit_event",
+    "test_call_contract",
+    "recurse",
+];
+const MAX_CALLDATA_LENGTH: usize = 8;
+// Short, as the test contract entry points take few arguments.
+const MAX_INNER_CALLDATA_LENGTH: usize = 3;
+const MAX_SIGNATURE_LENGTH: usize = 4;
+/// The generated felts near the prime are `P - k`, for `1 <= k <= MAX_DISTANCE_FROM_PRIME`.
+const MAX_DISTANCE_FROM_PRIME: u8 = 16;
+
+/// A felt, biased towards edge values: small numbers, and felts near the field prime.
+pub fn arbitrary_felt(rng: &mut impl Rng) -> StarkFelt {
+    match rng.gen_range(0..4) {
+        0 => StarkFelt::from(rng.gen_range(0_u8..4)),
+        1 => felt_near_prime(rng),
+        2 => StarkFelt::from(rng.gen::<u128>()),
+        _ => {
+            // Below 2^251, hence below the prime.
+            let mut bytes: [u8; 32] = rng.gen();
+            bytes[0] &= 0x07;
+            StarkFelt::new(bytes).expect("The bytes must be below the prime.")
+        }
+    }
+}
+
+/// `P - k` for a small `k`; e.g., `-1`.
+pub fn felt_near_prime(rng: &mut impl Rng) -> StarkFelt {
+    let distance = Felt252::from(rng.gen_range(1..=MAX_DISTANCE_FROM_PRIME));
+    felt_to_stark_felt(&(Felt252::zero() - distance))
+}
+
+pub fn arbitrary_felts(rng: &mut impl Rng, max_length: usize) -> Vec<StarkFelt> {
+    let length = rng.gen_range(0..=max_length);
+    (0..length).map(|_| arbitrary_felt(rng)).collect()
+}
+
+/// The calldata of an account's `__execute__`: half of the time, a call of an entry point of the
+/// test contract, whose calldata length may not match its calldata; otherwise, arbitrary felts.
+pub fn arbitrary_execute_calldata(rng: &mut impl Rng) -> Calldata {
+    if rng.gen_bool(0.5) {
+        return Calldata(arbitrary_felts(rng, MAX_CALLDATA_LENGTH).into());
+    }
+
+    let entry_point_name =
+        TEST_CONTRACT_ENTRY_POINTS.choose(rng).expect("The entry points must be non-empty.");
+    let inner_calldata = arbitrary_felts(rng, MAX_INNER_CALLDATA_LENGTH);
+    let inner_calldata_length = if rng.gen_bool(0.9) {
+        StarkFelt::from(inner_calldata.len() as u64)
+    } else {
+        arbitrary_felt(rng)
+    };
+    let mut calldata = vec![
+        stark_felt!(TEST_CONTRACT_ADDRESS),
+        selector_from_name(entry_point_name).0,
+        inner_calldata_length,
+    ];
+    calldata.extend(inner_calldata);
+
+    Calldata(calldata.into())
+}
+
+pub fn arbitrary_signature(rng: &mut impl Rng) -> TransactionSignature {
+    TransactionSignature(arbitrary_felts(rng, MAX_SIGNATURE_LENGTH))
+}
+
+/// A max fee around the edges of the account balance (see `BALANCE`).
+pub fn arbitrary_max_fee(rng: &mut impl Rng) -> Fee {
+    let max_fees = [0, 1, MAX_FEE, BALANCE, BALANCE + 1, u128::MAX];
+    match max_fees.choose(rng) {
+        Some(&max_fee) if rng.gen_bool(0.8) => Fee(max_fee),
+        _ => Fee(rng.gen_range(0..=BALANCE)),
+    }
+}
+
+/// An invoke of the test account (see `InvokeTxArgs::default`), of version 0 or 1.
+pub fn arbitrary_invoke_args(rng: &mut impl Rng) -> InvokeTxArgs {
+    let nonce = if rng.gen_bool(0.8) { None } else { Some(Nonce(arbitrary_felt(rng))) };
+    InvokeTxArgs {
+        version: TransactionVersion(StarkFelt::from(rng.gen_range(0_u8..=1))),
+        calldata: arbitrary_execute_calldata(rng),
+        max_fee: arbitrary_max_fee(rng),
+        signature: arbitrary_signature(rng),
+        nonce,
+        ..Default::default()
+    }
+}
+
+/// Returns simpler variants of the given invoke: each drops a calldata or a signature felt, zeroes
+/// a calldata felt, or resets a field to its default.
+pub fn shrink_invoke_args(invoke_args: &InvokeTxArgs) -> Vec<InvokeTxArgs> {
+    let default_args = InvokeTxArgs::default();
+    let mut shrunk_args = Vec::new();
+    let with_calldata = |calldata: Vec<StarkFelt>| InvokeTxArgs {
+        calldata: Calldata(calldata.into()),
+        ..invoke_args.clone()
+    };
+
+    let calldata = &invoke_args.calldata.0;
+    for index in 0..calldata.len() {
+        let mut shorter_calldata = calldata.to_vec();
+        shorter_calldata.remove(index);
+        shrunk_args.push(with_calldata(shorter_calldata));
+    }
+    for index in 0..calldata.len() {
+        if calldata[index] != StarkFelt::default() {
+            let mut zeroed_calldata = calldata.to_vec();
+            zeroed_calldata[index] = StarkFelt::default();
+            shrunk_args.push(with_calldata(zeroed_calldata));
+        }
+    }
+    let signature = &invoke_args.signature.0;
+    for index in 0..signature.len() {
+        let mut shorter_signature = signature.clone();
+        shorter_signature.remove(index);
+        shrunk_args.push(InvokeTxArgs {
+            signature: TransactionSignature(shorter_signature),
+            ..invoke_args.clone()
+        });
+    }
+    if invoke_args.max_fee != default_args.max_fee {
+        shrunk_args.push(InvokeTxArgs { max_fee: default_args.max_fee, ..invoke_args.clone() });
+    }
+    if invoke_args.nonce.is_some() {
+        shrunk_args.push(InvokeTxArgs { nonce: None, ..invoke_args.clone() });
+    }
+    if invoke_args.version != default_args.version {
+        shrunk_args.push(InvokeTxArgs { version: default_args.version, ..invoke_args.clone() });
+    }
+
+    shrunk_args
+}
+
+/// Shrinks the given failing value while it fails; returns a value none of whose shrunk variants
+/// fail.
+pub fn minimize<T>(
+    mut value: T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    mut fails: impl FnMut(&T) -> bool,
+) -> T {
+    while let Some(simpler_value) = shrink(&value).into_iter().find(|value| fails(value)) {
+        value = simpler_value;
+    }
+
+    value
+}
@@ -0,0 +1,88 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use cairo_felt::Felt252;
+use num_traits::Zero;
+use pretty_assertions::assert_eq;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::{Calldata, TransactionSignature};
+
+use crate::block_context::BlockContext;
+use crate::execution::execution_utils::stark_felt_to_felt;
+use crate::state::cached_state::CachedState;
+use crate::test_utils::fuzzing::{
+    arbitrary_invoke_args, felt_near_prime, minimize, shrink_invoke_args,
+};
+use crate::test_utils::transaction_builders::InvokeTxArgs;
+use crate::test_utils::DictStateReader;
+use crate::transaction::test_utils::create_state_with_trivial_validation_account;
+use crate::transaction::transactions::ExecutableTransaction;
+
+const SEED: u64 = 0;
+const N_CASES: usize = 300;
+
+/// Executes the invoke on top of the given state, which is left unchanged; returns whether the
+/// execution panicked, regardless of its result.
+fn execution_panics(
+    state: &mut CachedState<DictStateReader>,
+    block_context: &BlockContext,
+    invoke_args: &InvokeTxArgs,
+) -> bool {
+    let mut transactional_state = CachedState::create_transactional(state);
+    let execution = panic::catch_unwind(AssertUnwindSafe(|| {
+        let tx = invoke_args.clone().build(&mut transactional_state);
+        let (charge_fee, validate) = (true, true);
+        let _result = tx.execute(&mut transactional_state, block_context, charge_fee, validate);
+    }));
+    transactional_state.abort();
+
+    execution.is_err()
+}
+
+/// Executes generated invokes against a funded account; each may succeed, revert, or be rejected,
+/// but must not panic.
+#[test]
+fn test_invoke_fuzzing() {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for _ in 0..N_CASES {
+        let invoke_args = arbitrary_invoke_args(&mut rng);
+        if execution_panics(state, block_context, &invoke_args) {
+            let minimal_invoke_args = minimize(invoke_args, shrink_invoke_args, |invoke_args| {
+                execution_panics(state, block_context, invoke_args)
+            });
+            panic!("Executing the invoke panicked; minimal failing case: {minimal_invoke_args:?}.");
+        }
+    }
+}
+
+#[test]
+fn test_felt_near_prime() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for _ in 0..100 {
+        let distance = Felt252::zero() - stark_felt_to_felt(felt_near_prime(&mut rng));
+        assert!(distance >= Felt252::from(1_u8) && distance <= Felt252::from(16_u8));
+    }
+}
+
+/// A failure that depends on a single calldata felt shrinks to the invoke with only that felt.
+#[test]
+fn test_minimize() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let special_felt = felt_near_prime(&mut rng);
+    let mut invoke_args = arbitrary_invoke_args(&mut rng);
+    let mut calldata = invoke_args.calldata.0.to_vec();
+    calldata.push(special_felt);
+    invoke_args.calldata = Calldata(calldata.into());
+    invoke_args.signature = TransactionSignature(vec![StarkFelt::from(1_u8)]);
+
+    let minimal_invoke_args = minimize(invoke_args, shrink_invoke_args, |invoke_args| {
+        invoke_args.calldata.0.contains(&special_felt)
+    });
+    let expected_invoke_args =
+        InvokeTxArgs { calldata: Calldata(vec![special_felt].into()), ..Default::default() };
+    assert_eq!(format!("{minimal_invoke_args:?}"), format!("{expected_invoke_args:?}"));
+}
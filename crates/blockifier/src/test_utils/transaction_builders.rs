@@ -0,0 +1,248 @@
+use starknet_api::core::{
+    calculate_contract_address, ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey,
+};
+use starknet_api::hash::{pedersen_hash_array, StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    Calldata, ContractAddressSalt, DeclareTransactionV0V1, DeclareTransactionV2, Fee,
+    InvokeTransactionV0, InvokeTransactionV1, TransactionHash, TransactionSignature,
+    TransactionVersion,
+};
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::execution::contract_class::{ContractClass, ContractClassV0};
+use crate::state::state_api::StateReader;
+use crate::test_utils::{
+    MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+    TEST_CONTRACT_CAIRO0_PATH,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::constants::EXECUTE_ENTRY_POINT_NAME;
+use crate::transaction::transactions::{
+    DeclareTransaction, DeployAccountTransaction, InvokeTransaction,
+};
+
+#[cfg(test)]
+#[path = "transaction_builders_test.rs"]
+mod test;
+
+/// The fields of an invoke; by default, a V1 call of the Cairo 0 account with no calldata.
+#[derive(Clone, Debug)]
+pub struct InvokeTxArgs {
+    /// 0 or 1; a V0 invoke has no nonce, and calls `__execute__` of the sender directly.
+    pub version: TransactionVersion,
+    pub sender_address: ContractAddress,
+    pub calldata: Calldata,
+    pub max_fee: Fee,
+    pub signature: TransactionSignature,
+    /// Read from the state, if unset.
+    pub nonce: Option<Nonce>,
+    /// A placeholder, if unset.
+    pub tx_hash: Option<TransactionHash>,
+}
+
+impl Default for InvokeTxArgs {
+    fn default() -> Self {
+        Self {
+            version: TransactionVersion(stark_felt!(1_u8)),
+            sender_address: contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            calldata: Calldata::default(),
+            max_fee: Fee(MAX_FEE),
+            signature: TransactionSignature::default(),
+            nonce: None,
+            tx_hash: None,
+        }
+    }
+}
+
+impl InvokeTxArgs {
+    /// Panics on an unsupported version.
+    pub fn build(self, state: &mut dyn StateReader) -> AccountTransaction {
+        let nonce = resolve_nonce(state, self.sender_address, self.nonce);
+        let tx_hash = self
+            .tx_hash
+            .unwrap_or_else(|| placeholder_tx_hash(self.version, self.sender_address, nonce));
+        let tx = match self.version.0 {
+            version if version == stark_felt!(0_u8) => InvokeTransactionV0 {
+                max_fee: self.max_fee,
+                signature: self.signature,
+                contract_address: self.sender_address,
+                entry_point_selector: selector_from_name(EXECUTE_ENTRY_POINT_NAME),
+                calldata: self.calldata,
+            }
+            .into(),
+            version if version == stark_felt!(1_u8) => InvokeTransactionV1 {
+                max_fee: self.max_fee,
+                signature: self.signature,
+                nonce,
+                sender_address: self.sender_address,
+                calldata: self.calldata,
+            }
+            .into(),
+            version => panic!("Unsupported invoke version: {version}."),
+        };
+
+        AccountTransaction::Invoke(InvokeTransaction { tx, tx_hash })
+    }
+}
+
+/// The fields of a declare; by default, a V1 declaration of the Cairo 0 test contract, by the
+/// Cairo 0 account.
+#[derive(Clone, Debug)]
+pub struct DeclareTxArgs {
+    /// 0 or 1 for a Cairo 0 class, 2 for a Cairo 1 class.
+    pub version: TransactionVersion,
+    pub sender_address: ContractAddress,
+    pub class_hash: ClassHash,
+    /// Used by V2 only.
+    pub compiled_class_hash: CompiledClassHash,
+    pub contract_class: ContractClass,
+    pub max_fee: Fee,
+    pub signature: TransactionSignature,
+    /// Read from the state, if unset.
+    pub nonce: Option<Nonce>,
+    /// A placeholder, if unset.
+    pub tx_hash: Option<TransactionHash>,
+}
+
+impl Default for DeclareTxArgs {
+    fn default() -> Self {
+        Self {
+            version: TransactionVersion(stark_felt!(1_u8)),
+            sender_address: contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            class_hash: class_hash!(TEST_CLASS_HASH),
+            compiled_class_hash: CompiledClassHash::default(),
+            contract_class: ContractClassV0::from_file(TEST_CONTRACT_CAIRO0_PATH).into(),
+            max_fee: Fee(MAX_FEE),
+            signature: TransactionSignature::default(),
+            nonce: None,
+            tx_hash: None,
+        }
+    }
+}
+
+impl DeclareTxArgs {
+    /// Panics on an unsupported version, or on a version that does not match the Cairo version of
+    /// the class.
+    pub fn build(self, state: &mut dyn StateReader) -> AccountTransaction {
+        let nonce = resolve_nonce(state, self.sender_address, self.nonce);
+        let tx_hash = self
+            .tx_hash
+            .unwrap_or_else(|| placeholder_tx_hash(self.version, self.sender_address, nonce));
+        let declare_v0_v1 = || DeclareTransactionV0V1 {
+            max_fee: self.max_fee,
+            signature: self.signature.clone(),
+            nonce,
+            class_hash: self.class_hash,
+            sender_address: self.sender_address,
+        };
+        let tx = match self.version.0 {
+            version if version == stark_felt!(0_u8) => {
+                starknet_api::transaction::DeclareTransaction::V0(declare_v0_v1())
+            }
+            version if version == stark_felt!(1_u8) => {
+                starknet_api::transaction::DeclareTransaction::V1(declare_v0_v1())
+            }
+            version if version == stark_felt!(2_u8) => {
+                starknet_api::transaction::DeclareTransaction::V2(DeclareTransactionV2 {
+                    max_fee: self.max_fee,
+                    signature: self.signature.clone(),
+                    nonce,
+                    class_hash: self.class_hash,
+                    compiled_class_hash: self.compiled_class_hash,
+                    sender_address: self.sender_address,
+                })
+            }
+            version => panic!("Unsupported declare version: {version}."),
+        };
+
+        AccountTransaction::Declare(
+            DeclareTransaction::new(tx, tx_hash, self.contract_class).unwrap(),
+        )
+    }
+}
+
+/// The fields of a deploy account; by default, of a new instance of the Cairo 0 account.
+#[derive(Clone, Debug)]
+pub struct DeployAccountTxArgs {
+    pub version: TransactionVersion,
+    pub class_hash: ClassHash,
+    pub constructor_calldata: Calldata,
+    pub contract_address_salt: ContractAddressSalt,
+    pub max_fee: Fee,
+    pub signature: TransactionSignature,
+    /// Read from the state (at the deployed address), if unset.
+    pub nonce: Option<Nonce>,
+    /// A placeholder, if unset.
+    pub tx_hash: Option<TransactionHash>,
+}
+
+impl Default for DeployAccountTxArgs {
+    fn default() -> Self {
+        Self {
+            version: TransactionVersion(stark_felt!(1_u8)),
+            class_hash: class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH),
+            constructor_calldata: Calldata::default(),
+            contract_address_salt: ContractAddressSalt::default(),
+            max_fee: Fee(MAX_FEE),
+            signature: TransactionSignature::default(),
+            nonce: None,
+            tx_hash: None,
+        }
+    }
+}
+
+impl DeployAccountTxArgs {
+    /// The address of the deployed account; e.g., to fund it before the deployment.
+    pub fn contract_address(&self) -> ContractAddress {
+        calculate_contract_address(
+            self.contract_address_salt,
+            self.class_hash,
+            &self.constructor_calldata,
+            ContractAddress::default(),
+        )
+        .unwrap()
+    }
+
+    pub fn build(self, state: &mut dyn StateReader) -> AccountTransaction {
+        AccountTransaction::DeployAccount(self.build_deploy_account(state))
+    }
+
+    /// As `build`, but returns the transaction itself, which exposes the deployed address.
+    pub fn build_deploy_account(self, state: &mut dyn StateReader) -> DeployAccountTransaction {
+        let contract_address = self.contract_address();
+        let nonce = resolve_nonce(state, contract_address, self.nonce);
+        let tx_hash = self
+            .tx_hash
+            .unwrap_or_else(|| placeholder_tx_hash(self.version, contract_address, nonce));
+        let tx = starknet_api::transaction::DeployAccountTransaction {
+            max_fee: self.max_fee,
+            version: self.version,
+            signature: self.signature,
+            nonce,
+            class_hash: self.class_hash,
+            contract_address_salt: self.contract_address_salt,
+            constructor_calldata: self.constructor_calldata,
+        };
+
+        DeployAccountTransaction { tx, tx_hash, contract_address }
+    }
+}
+
+/// A hash that tells apart the transactions of a test (by their version, sender and nonce); not the
+/// Starknet hash of the transaction, as accounts under test do not verify signatures on it.
+pub fn placeholder_tx_hash(
+    version: TransactionVersion,
+    sender_address: ContractAddress,
+    nonce: Nonce,
+) -> TransactionHash {
+    TransactionHash(pedersen_hash_array(&[version.0, *sender_address.0.key(), nonce.0]))
+}
+
+fn resolve_nonce(
+    state: &mut dyn StateReader,
+    account_address: ContractAddress,
+    nonce: Option<Nonce>,
+) -> Nonce {
+    nonce.unwrap_or_else(|| state.get_nonce_at(account_address).unwrap())
+}
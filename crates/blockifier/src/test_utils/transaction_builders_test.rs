@@ -0,0 +1,119 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    Calldata, DeclareTransactionV0V1, Fee, InvokeTransactionV0, InvokeTransactionV1,
+    TransactionSignature, TransactionVersion,
+};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::State;
+use crate::test_utils::transaction_builders::{
+    placeholder_tx_hash, DeclareTxArgs, DeployAccountTxArgs, InvokeTxArgs,
+};
+use crate::test_utils::{create_test_state, DictStateReader, TEST_ACCOUNT_CONTRACT_ADDRESS};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::constants::EXECUTE_ENTRY_POINT_NAME;
+
+fn invoke_args(version: u8) -> InvokeTxArgs {
+    InvokeTxArgs {
+        version: TransactionVersion(stark_felt!(version)),
+        calldata: calldata![stark_felt!(7_u8)],
+        max_fee: Fee(17),
+        signature: TransactionSignature(vec![stark_felt!(3_u8)]),
+        ..Default::default()
+    }
+}
+
+/// Invokes of different versions built from the same args differ only in their version-specific
+/// fields.
+#[test]
+fn test_invoke_versions() {
+    let state = &mut create_test_state();
+    let sender_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let AccountTransaction::Invoke(invoke_v0) = invoke_args(0).build(state) else { panic!() };
+    let AccountTransaction::Invoke(invoke_v1) = invoke_args(1).build(state) else { panic!() };
+
+    assert_eq!(
+        invoke_v0.tx,
+        InvokeTransactionV0 {
+            max_fee: Fee(17),
+            signature: TransactionSignature(vec![stark_felt!(3_u8)]),
+            contract_address: sender_address,
+            entry_point_selector: selector_from_name(EXECUTE_ENTRY_POINT_NAME),
+            calldata: calldata![stark_felt!(7_u8)],
+        }
+        .into()
+    );
+    assert_eq!(
+        invoke_v1.tx,
+        InvokeTransactionV1 {
+            max_fee: Fee(17),
+            signature: TransactionSignature(vec![stark_felt!(3_u8)]),
+            nonce: Nonce::default(),
+            sender_address,
+            calldata: calldata![stark_felt!(7_u8)],
+        }
+        .into()
+    );
+    assert_ne!(invoke_v0.tx_hash, invoke_v1.tx_hash);
+}
+
+#[test]
+fn test_declare_versions() {
+    let state = &mut create_test_state();
+    let declare_args = |version: u8| DeclareTxArgs {
+        version: TransactionVersion(stark_felt!(version)),
+        max_fee: Fee(17),
+        ..Default::default()
+    };
+    let AccountTransaction::Declare(declare_v0) = declare_args(0).build(state) else { panic!() };
+    let AccountTransaction::Declare(declare_v1) = declare_args(1).build(state) else { panic!() };
+
+    let expected_tx = DeclareTransactionV0V1 {
+        max_fee: Fee(17),
+        class_hash: DeclareTxArgs::default().class_hash,
+        sender_address: contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        ..Default::default()
+    };
+    assert_eq!(
+        declare_v0.tx(),
+        &starknet_api::transaction::DeclareTransaction::V0(expected_tx.clone())
+    );
+    assert_eq!(declare_v1.tx(), &starknet_api::transaction::DeclareTransaction::V1(expected_tx));
+}
+
+/// An unset nonce is read from the state; an unset hash is a placeholder.
+#[test]
+fn test_nonce_and_hash() {
+    let state = &mut create_test_state();
+    let sender_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    state.increment_nonce(sender_address).unwrap();
+    state.increment_nonce(sender_address).unwrap();
+
+    let invoke_nonce = |invoke_args: InvokeTxArgs, state: &mut CachedState<DictStateReader>| {
+        let AccountTransaction::Invoke(invoke) = invoke_args.build(state) else { panic!() };
+        let starknet_api::transaction::InvokeTransaction::V1(invoke_v1) = invoke.tx else {
+            panic!()
+        };
+        (invoke_v1.nonce, invoke.tx_hash)
+    };
+    let (nonce, tx_hash) = invoke_nonce(InvokeTxArgs::default(), state);
+    assert_eq!(nonce, Nonce(stark_felt!(2_u8)));
+    assert_eq!(
+        tx_hash,
+        placeholder_tx_hash(TransactionVersion(stark_felt!(1_u8)), sender_address, nonce)
+    );
+    let explicit_nonce = Nonce(stark_felt!(5_u8));
+    let (nonce, _) =
+        invoke_nonce(InvokeTxArgs { nonce: Some(explicit_nonce), ..Default::default() }, state);
+    assert_eq!(nonce, explicit_nonce);
+
+    let deploy_account_args = DeployAccountTxArgs::default();
+    let contract_address = deploy_account_args.contract_address();
+    let deploy_account = deploy_account_args.build_deploy_account(state);
+    assert_eq!(deploy_account.contract_address, contract_address);
+    assert_eq!(deploy_account.tx.nonce, Nonce::default());
+}
@@ -0,0 +1,65 @@
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, ContractAddressSalt};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::{get_erc20_balance_var_addresses, get_storage_var_address};
+use crate::block_context::BlockContext;
+use crate::state::state_api::{State, StateReader};
+use crate::test_utils::{
+    create_test_state, deploy_contract, fund_account, TEST_CLASS_HASH,
+    TEST_EMPTY_CONTRACT_CLASS_HASH,
+};
+
+#[test]
+fn test_fund_account() {
+    let state = &mut create_test_state();
+    let block_context = BlockContext::create_for_account_testing();
+    let account_address = contract_address!("0x777");
+    let (low_key, high_key) = get_erc20_balance_var_addresses(&account_address).unwrap();
+    let fee_token_addresses = &block_context.fee_token_addresses;
+    let fee_token_addresses =
+        [fee_token_addresses.eth_fee_token_address, fee_token_addresses.strk_fee_token_address];
+
+    // A balance of 2^128 or more has a high limb, which is cleared by the funding; the largest
+    // amount fits in the low limb.
+    for fee_token_address in fee_token_addresses {
+        state.set_storage_at(fee_token_address, high_key, stark_felt!(1_u8));
+    }
+    fund_account(state, account_address, u128::MAX, &block_context);
+    for fee_token_address in fee_token_addresses {
+        assert_eq!(
+            state.get_storage_at(fee_token_address, low_key).unwrap(),
+            stark_felt!(u128::MAX)
+        );
+        assert_eq!(state.get_storage_at(fee_token_address, high_key).unwrap(), stark_felt!(0_u8));
+        assert_eq!(
+            state.get_fee_token_balance(&account_address, &fee_token_address).unwrap(),
+            (stark_felt!(u128::MAX), stark_felt!(0_u8))
+        );
+    }
+}
+
+#[test]
+fn test_deploy_contract() {
+    let state = &mut create_test_state();
+
+    // The constructor writes the sum of its arguments.
+    let class_hash = class_hash!(TEST_CLASS_HASH);
+    let contract_address = deploy_contract(
+        state,
+        class_hash,
+        ContractAddressSalt::default(),
+        calldata![stark_felt!(3_u8), stark_felt!(4_u8)],
+    );
+    assert_eq!(state.get_class_hash_at(contract_address).unwrap(), class_hash);
+    let storage_key = get_storage_var_address("my_storage_var", &[]).unwrap();
+    assert_eq!(state.get_storage_at(contract_address, storage_key).unwrap(), stark_felt!(7_u8));
+
+    // A class with no constructor.
+    let class_hash = class_hash!(TEST_EMPTY_CONTRACT_CLASS_HASH);
+    let contract_address =
+        deploy_contract(state, class_hash, ContractAddressSalt::default(), calldata![]);
+    assert_eq!(state.get_class_hash_at(contract_address).unwrap(), class_hash);
+}
@@ -4,6 +4,7 @@ pub mod errors;
 pub mod objects;
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
+pub mod trace;
 pub mod transaction_execution;
 pub mod transaction_types;
 pub mod transaction_utils;
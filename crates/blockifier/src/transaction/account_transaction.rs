@@ -3,37 +3,46 @@ use std::cmp::min;
 use cairo_vm::vm::runners::cairo_runner::ResourceTracker;
 use itertools::concat;
 use starknet_api::calldata;
-use starknet_api::core::{ContractAddress, EntryPointSelector, Nonce};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::StarkFelt;
-use starknet_api::transaction::{Calldata, Fee, TransactionVersion};
+use starknet_api::transaction::{Calldata, Fee, TransactionHash, TransactionVersion};
 
-use crate::abi::abi_utils::selector_from_name;
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
 use crate::abi::constants as abi_constants;
-use crate::block_context::BlockContext;
+use crate::block_context::{BlockContext, FeeChargePolicy, ZeroSequencerFeePolicy};
+use crate::concurrency::fee_utils::felt_to_u128;
+use crate::concurrency::validation_phase::PrecomputedValidation;
 use crate::execution::contract_class::ContractClass;
 use crate::execution::entry_point::{
     CallEntryPoint, CallInfo, CallType, EntryPointExecutionContext, ExecutionResources, Retdata,
 };
-use crate::fee::fee_utils::calculate_tx_fee;
+use crate::execution::errors::{EntryPointExecutionError, ExecutionErrorCategory};
+use crate::fee::fee_utils::{
+    fee_transfer_call, get_saturated_fee_by_gas_vector, verify_fee_transfer_retdata,
+};
 use crate::fee::gas_usage::estimate_minimal_fee;
-use crate::fee::os_resources::OS_RESOURCES;
+use crate::fee::transaction_resources::TransactionResources;
 use crate::retdata;
+use crate::selector;
 use crate::state::cached_state::{
     CachedState, StateChanges, StateChangesCount, TransactionalState,
 };
 use crate::state::state_api::{State, StateReader};
-use crate::transaction::constants;
-use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::constants::{
+    VALIDATE_DECLARE_ENTRY_POINT_NAME, VALIDATE_DEPLOY_ENTRY_POINT_NAME, VALIDATE_ENTRY_POINT_NAME,
+};
+use crate::transaction::errors::{
+    RestrictedEntryPointKind, TransactionExecutionError, TransactionFeeError,
+};
 use crate::transaction::objects::{
-    AccountTransactionContext, ResourcesMapping, TransactionExecutionInfo,
-    TransactionExecutionResult,
+    AccountTransactionContext, GasVector, TransactionExecutionInfo, TransactionExecutionResult,
+    TransactionReceipt,
 };
 use crate::transaction::transaction_execution::Transaction;
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transaction_utils::{
-    calculate_l1_gas_usage, calculate_tx_resources, update_remaining_gas,
-    verify_no_calls_to_other_contracts,
+    calculate_tx_resources, update_remaining_gas, verify_no_calls_to_other_contracts,
 };
 use crate::transaction::transactions::{
     DeclareTransaction, DeployAccountTransaction, Executable, ExecutableTransaction,
@@ -45,7 +54,7 @@ use crate::transaction::transactions::{
 mod test;
 
 /// Represents a paid StarkNet transaction.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum AccountTransaction {
     Declare(DeclareTransaction),
     DeployAccount(DeployAccountTransaction),
@@ -99,6 +108,14 @@ impl AccountTransaction {
         }
     }
 
+    pub fn tx_hash(&self) -> TransactionHash {
+        match self {
+            AccountTransaction::Declare(tx) => tx.tx_hash(),
+            AccountTransaction::DeployAccount(tx) => tx.tx_hash,
+            AccountTransaction::Invoke(tx) => tx.tx_hash,
+        }
+    }
+
     pub fn get_address_of_deploy(&self) -> Option<ContractAddress> {
         match self {
             AccountTransaction::DeployAccount(deploy_tx) => Some(deploy_tx.contract_address),
@@ -114,13 +131,31 @@ impl AccountTransaction {
         }
     }
 
+    /// Returns the length of the calldata of the transaction; that of the constructor, for
+    /// `DeployAccount`.
+    pub fn calldata_length(&self) -> usize {
+        match self {
+            // A `Declare` transaction has no calldata.
+            Self::Declare(_) => 0,
+            Self::DeployAccount(tx) => tx.constructor_calldata().0.len(),
+            Self::Invoke(tx) => tx.calldata().0.len(),
+        }
+    }
+
+    pub fn signature_length(&self) -> usize {
+        match self {
+            Self::Declare(tx) => tx.tx().signature().0.len(),
+            Self::DeployAccount(tx) => tx.signature().0.len(),
+            Self::Invoke(tx) => tx.tx.signature().0.len(),
+        }
+    }
+
     fn validate_entry_point_selector(&self) -> EntryPointSelector {
-        let validate_entry_point_name = match self {
-            Self::Declare(_) => constants::VALIDATE_DECLARE_ENTRY_POINT_NAME,
-            Self::DeployAccount(_) => constants::VALIDATE_DEPLOY_ENTRY_POINT_NAME,
-            Self::Invoke(_) => constants::VALIDATE_ENTRY_POINT_NAME,
-        };
-        selector_from_name(validate_entry_point_name)
+        match self {
+            Self::Declare(_) => selector!(VALIDATE_DECLARE_ENTRY_POINT_NAME),
+            Self::DeployAccount(_) => selector!(VALIDATE_DEPLOY_ENTRY_POINT_NAME),
+            Self::Invoke(_) => selector!(VALIDATE_ENTRY_POINT_NAME),
+        }
     }
 
     // Calldata for validation contains transaction fields that cannot be obtained by calling
@@ -140,7 +175,7 @@ impl AccountTransaction {
         }
     }
 
-    fn get_account_transaction_context(&self) -> AccountTransactionContext {
+    pub fn get_account_transaction_context(&self) -> AccountTransactionContext {
         match self {
             Self::Declare(tx) => {
                 let sn_api_tx = &tx.tx();
@@ -217,6 +252,46 @@ impl AccountTransaction {
         }
     }
 
+    /// Checks the signature and calldata lengths against the block limits.
+    fn verify_tx_lengths(
+        &self,
+        account_tx_context: &AccountTransactionContext,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<()> {
+        let signature_length = account_tx_context.signature.0.len();
+        if signature_length > block_context.versioned_constants.max_signature_length {
+            return Err(TransactionExecutionError::SignatureTooLong {
+                length: signature_length,
+                max_length: block_context.versioned_constants.max_signature_length,
+            });
+        }
+
+        let calldata_length = self.calldata_length();
+        if calldata_length > block_context.versioned_constants.max_calldata_length {
+            return Err(TransactionExecutionError::CalldataTooLong {
+                length: calldata_length,
+                max_length: block_context.versioned_constants.max_calldata_length,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the sender account is deployed, so that it can validate and pay for the
+    /// transaction.
+    fn verify_sender_deployed(
+        state: &mut dyn State,
+        sender_address: ContractAddress,
+    ) -> TransactionExecutionResult<()> {
+        if state.get_class_hash_at(sender_address)? == ClassHash::default() {
+            return Err(TransactionExecutionError::UninitializedSenderAccount {
+                address: sender_address,
+            });
+        }
+
+        Ok(())
+    }
+
     fn handle_nonce(
         account_tx_context: &AccountTransactionContext,
         state: &mut dyn State,
@@ -239,22 +314,32 @@ impl AccountTransaction {
         Ok(state.increment_nonce(address)?)
     }
 
-    fn handle_validate_tx(
+    /// Runs the validation, unless the given precomputed one is still valid; it is then applied
+    /// instead.
+    fn handle_validate_tx<S: StateReader>(
         &self,
-        state: &mut dyn State,
+        state: &mut TransactionalState<'_, S>,
         resources: &mut ExecutionResources,
         remaining_gas: &mut u64,
         block_context: &BlockContext,
         validate: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
     ) -> TransactionExecutionResult<Option<CallInfo>> {
-        if validate {
-            self.validate_tx(state, resources, remaining_gas, block_context)
-        } else {
-            Ok(None)
+        if !validate {
+            return Ok(None);
+        }
+
+        if let Some(validation) = precomputed_validation {
+            if validation.is_valid_on(state)? {
+                return Ok(validation.apply_to(state, resources, remaining_gas));
+            }
         }
+        self.validate_tx(state, resources, remaining_gas, block_context)
     }
 
-    fn validate_tx(
+    /// Runs the `validate` entry point of the sender account; returns [None] for V0 transactions,
+    /// which are not validated.
+    pub fn validate_tx(
         &self,
         state: &mut dyn State,
         resources: &mut ExecutionResources,
@@ -281,12 +366,16 @@ impl AccountTransaction {
             initial_gas: *remaining_gas,
         };
 
-        let validate_call_info = validate_call
-            .execute(state, resources, &mut context)
-            .map_err(TransactionExecutionError::ValidateTransactionError)?;
+        let validate_call_info =
+            validate_call.execute(state, resources, &mut context).map_err(|error| match error {
+                EntryPointExecutionError::ExecutionDeadlineExceeded => {
+                    TransactionExecutionError::ExecutionDeadlineExceeded
+                }
+                error => TransactionExecutionError::ValidateTransactionError(error),
+            })?;
         verify_no_calls_to_other_contracts(
             &validate_call_info,
-            String::from(constants::VALIDATE_ENTRY_POINT_NAME),
+            RestrictedEntryPointKind::Validate,
         )?;
 
         // Validate return data.
@@ -295,7 +384,7 @@ impl AccountTransaction {
         if let ContractClass::V1(_) = contract_class {
             // The account contract class is a Cairo 1.0 contract; the `validate` entry point should
             // return `VALID`.
-            let expected_retdata = retdata![StarkFelt::try_from(constants::VALIDATE_RETDATA)?];
+            let expected_retdata = retdata![*abi_constants::VALID];
             if validate_call_info.execution.retdata != expected_retdata {
                 return Err(TransactionExecutionError::InvalidValidateReturnData {
                     actual: validate_call_info.execution.retdata,
@@ -308,8 +397,8 @@ impl AccountTransaction {
         Ok(Some(validate_call_info))
     }
 
-    fn enforce_fee(&self) -> bool {
-        self.max_fee() != Fee(0)
+    fn enforce_fee(&self, block_context: &BlockContext) -> bool {
+        self.get_account_transaction_context().enforce_fee(block_context)
     }
 
     /// Checks that the account's balance covers max fee.
@@ -321,52 +410,136 @@ impl AccountTransaction {
         let account_tx_context = self.get_account_transaction_context();
 
         // Check fee balance.
-        if self.enforce_fee() {
+        if self.enforce_fee(block_context) {
             // Check max fee is at least the estimated constant overhead.
             let minimal_fee = estimate_minimal_fee(block_context, self)?;
             if minimal_fee > account_tx_context.max_fee {
-                return Err(TransactionExecutionError::MaxFeeTooLow {
+                return Err(TransactionFeeError::MaxFeeTooLow {
                     min_fee: minimal_fee,
                     max_fee: account_tx_context.max_fee,
-                });
+                }
+                .into());
             }
 
-            let (balance_low, balance_high) =
-                state.get_fee_token_balance(block_context, &account_tx_context.sender_address)?;
+            let (balance_low, balance_high) = state.get_fee_token_balance(
+                &account_tx_context.sender_address,
+                &block_context.fee_token_address(&account_tx_context.fee_type()),
+            )?;
             // TODO(Dori, 1/7/2023): If and when Fees can be more than 128 bit integers, this check
             //   should be updated.
             if balance_high == StarkFelt::from(0_u8)
                 && balance_low < StarkFelt::from(account_tx_context.max_fee.0)
             {
-                return Err(TransactionExecutionError::MaxFeeExceedsBalance {
+                return Err(TransactionFeeError::MaxFeeExceedsBalance {
                     max_fee: account_tx_context.max_fee,
                     balance_low,
                     balance_high,
-                });
+                }
+                .into());
             }
         }
 
         Ok(())
     }
 
+    /// Charges the given fee; returns the fee transfer call info (if any), and the collected fee.
     fn handle_fee(
         &self,
         state: &mut dyn State,
         block_context: &BlockContext,
         actual_fee: Fee,
         charge_fee: bool,
-    ) -> TransactionExecutionResult<Option<CallInfo>> {
-        if !charge_fee || actual_fee == Fee(0) {
+    ) -> TransactionExecutionResult<(Option<CallInfo>, Fee)> {
+        if !charge_fee {
             // Fee charging is not enforced in some transaction simulations and tests.
-            return Ok(None);
+            return Ok((None, Fee(0)));
+        }
+        if actual_fee == Fee(0) && !block_context.force_zero_fee_transfer {
+            // Nothing to transfer (e.g., on chains with a zero gas price).
+            return Ok((None, Fee(0)));
+        }
+        let zero_sequencer_fee_policy =
+            if block_context.sequencer_address == ContractAddress::default() {
+                block_context.zero_sequencer_fee_policy
+            } else {
+                ZeroSequencerFeePolicy::Transfer
+            };
+        if zero_sequencer_fee_policy == ZeroSequencerFeePolicy::Skip {
+            return Ok((None, Fee(0)));
         }
 
         // Charge fee.
         let account_tx_context = self.get_account_transaction_context();
+        // At this point, the sender is deployed for all transaction types.
+        Self::verify_sender_deployed(state, account_tx_context.sender_address)?;
+        let collected_fee =
+            Self::fee_to_collect(state, block_context, &account_tx_context, actual_fee)?;
+        if zero_sequencer_fee_policy == ZeroSequencerFeePolicy::Burn {
+            Self::burn_fee(state, block_context, &account_tx_context, collected_fee)?;
+            return Ok((None, collected_fee));
+        }
         let fee_transfer_call_info =
-            Self::execute_fee_transfer(state, block_context, account_tx_context, actual_fee)?;
+            Self::execute_fee_transfer(state, block_context, account_tx_context, collected_fee)?;
+
+        Ok((Some(fee_transfer_call_info), collected_fee))
+    }
+
+    /// Debits the given fee from the sender's balance, without crediting anyone; the fee must not
+    /// exceed the balance (see `fee_to_collect`).
+    fn burn_fee(
+        state: &mut dyn State,
+        block_context: &BlockContext,
+        account_tx_context: &AccountTransactionContext,
+        fee: Fee,
+    ) -> TransactionExecutionResult<()> {
+        let max_fee = account_tx_context.max_fee;
+        if fee > max_fee {
+            return Err(TransactionFeeError::FeeTransferError { max_fee, actual_fee: fee }.into());
+        }
 
-        Ok(Some(fee_transfer_call_info))
+        let sender_address = account_tx_context.sender_address;
+        let fee_token_address = block_context.fee_token_address(&account_tx_context.fee_type());
+        let (balance_low, balance_high) =
+            state.get_fee_token_balance(&sender_address, &fee_token_address)?;
+        let (low_key, high_key) = get_erc20_balance_var_addresses(&sender_address)?;
+        let (new_balance_low, borrow) = felt_to_u128(&balance_low)?.overflowing_sub(fee.0);
+        state.set_storage_at(fee_token_address, low_key, StarkFelt::from(new_balance_low));
+        if borrow {
+            let new_balance_high = felt_to_u128(&balance_high)? - 1;
+            state.set_storage_at(fee_token_address, high_key, StarkFelt::from(new_balance_high));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fee to transfer: the actual fee, if the sender can still pay it; the balance was
+    /// checked against max fee before running the transaction, but it may have been spent since
+    /// (e.g., by the constructor of a `DeployAccount` transaction, which pays from the deployed
+    /// address). Otherwise, the transaction is rejected or charged the remaining balance, by the
+    /// fee charge policy.
+    fn fee_to_collect(
+        state: &mut dyn State,
+        block_context: &BlockContext,
+        account_tx_context: &AccountTransactionContext,
+        actual_fee: Fee,
+    ) -> TransactionExecutionResult<Fee> {
+        let (balance_low, balance_high) = state.get_fee_token_balance(
+            &account_tx_context.sender_address,
+            &block_context.fee_token_address(&account_tx_context.fee_type()),
+        )?;
+        if balance_high == StarkFelt::from(0_u8) && balance_low < StarkFelt::from(actual_fee.0) {
+            return match block_context.fee_charge_policy {
+                FeeChargePolicy::Strict => Err(TransactionFeeError::ActualFeeExceedsBalance {
+                    actual_fee,
+                    balance_low,
+                    balance_high,
+                }
+                .into()),
+                FeeChargePolicy::CapAtBalance => Ok(Fee(felt_to_u128(&balance_low)?)),
+            };
+        }
+
+        Ok(actual_fee)
     }
 
     fn execute_fee_transfer(
@@ -377,36 +550,18 @@ impl AccountTransaction {
     ) -> TransactionExecutionResult<CallInfo> {
         let max_fee = account_tx_context.max_fee;
         if actual_fee > max_fee {
-            return Err(TransactionExecutionError::FeeTransferError { max_fee, actual_fee });
+            return Err(TransactionFeeError::FeeTransferError { max_fee, actual_fee }.into());
         }
 
-        // The least significant 128 bits of the amount transferred.
-        let lsb_amount = StarkFelt::from(actual_fee.0);
-        // The most significant 128 bits of the amount transferred.
-        let msb_amount = StarkFelt::from(0_u8);
-
-        let storage_address = block_context.fee_token_address;
-        let fee_transfer_call = CallEntryPoint {
-            class_hash: None,
-            code_address: None,
-            entry_point_type: EntryPointType::External,
-            entry_point_selector: selector_from_name(constants::TRANSFER_ENTRY_POINT_NAME),
-            calldata: calldata![
-                *block_context.sequencer_address.0.key(), // Recipient.
-                lsb_amount,
-                msb_amount
-            ],
-            storage_address,
-            caller_address: account_tx_context.sender_address,
-            call_type: CallType::Call,
-            // The fee-token contract is a Cairo 0 contract, hence the initial gas is irrelevant.
-            initial_gas: abi_constants::INITIAL_GAS_COST,
-        };
-
+        let fee_transfer_call = fee_transfer_call(block_context, &account_tx_context, actual_fee);
         let mut context =
-            EntryPointExecutionContext::new_invoke(block_context, &account_tx_context);
+            EntryPointExecutionContext::new_invoke(block_context, &account_tx_context)?;
 
-        Ok(fee_transfer_call.execute(state, &mut ExecutionResources::default(), &mut context)?)
+        let fee_transfer_call_info =
+            fee_transfer_call.execute(state, &mut ExecutionResources::default(), &mut context)?;
+        verify_fee_transfer_retdata(&fee_transfer_call_info)?;
+
+        Ok(fee_transfer_call_info)
     }
 
     fn run_execute<S: State>(
@@ -434,11 +589,18 @@ impl AccountTransaction {
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
         let execute_call_info =
             self.run_execute(state, resources, &mut execution_context, remaining_gas)?;
-        let validate_call_info =
-            self.handle_validate_tx(state, resources, remaining_gas, block_context, validate)?;
+        let validate_call_info = self.handle_validate_tx(
+            state,
+            resources,
+            remaining_gas,
+            block_context,
+            validate,
+            None,
+        )?;
         Ok(ValidateExecuteCallInfo::new_accepted(validate_call_info, execute_call_info))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_not_revertible<S: StateReader>(
         &self,
         state: &mut TransactionalState<'_, S>,
@@ -447,14 +609,22 @@ impl AccountTransaction {
         block_context: &BlockContext,
         mut execution_context: EntryPointExecutionContext,
         validate: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
-        let validate_call_info =
-            self.handle_validate_tx(state, resources, remaining_gas, block_context, validate)?;
+        let validate_call_info = self.handle_validate_tx(
+            state,
+            resources,
+            remaining_gas,
+            block_context,
+            validate,
+            precomputed_validation,
+        )?;
         let execute_call_info =
             self.run_execute(state, resources, &mut execution_context, remaining_gas)?;
         Ok(ValidateExecuteCallInfo::new_accepted(validate_call_info, execute_call_info))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_revertible<S: StateReader>(
         &self,
         state: &mut TransactionalState<'_, S>,
@@ -463,11 +633,19 @@ impl AccountTransaction {
         block_context: &BlockContext,
         mut execution_context: EntryPointExecutionContext,
         validate: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
+        signature_length_for_fee: usize,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
         let account_tx_context = self.get_account_transaction_context();
         // Run the validation, and if execution later fails, only keep the validation diff.
-        let validate_call_info =
-            self.handle_validate_tx(state, resources, remaining_gas, block_context, validate)?;
+        let validate_call_info = self.handle_validate_tx(
+            state,
+            resources,
+            remaining_gas,
+            block_context,
+            validate,
+            precomputed_validation,
+        )?;
         let validate_steps = if validate {
             validate_call_info
                 .as_ref()
@@ -477,10 +655,10 @@ impl AccountTransaction {
         } else {
             0
         };
-        let overhead_steps = OS_RESOURCES
-            .execute_txs_inner()
-            .get(&self.tx_type())
-            .expect("`OS_RESOURCES` must contain all transaction types.")
+        let overhead_steps = block_context
+            .versioned_constants
+            .os_resources
+            .os_resources_for_tx(self.tx_type(), self.calldata_length(), block_context.use_kzg_da)
             .n_steps;
 
         // Subtract the actual steps used for validate_tx and estimated steps required for fee
@@ -494,7 +672,7 @@ impl AccountTransaction {
         // Save the state changes resulting from running `validate_tx`, to be used later for
         // resource and fee calculation.
         let validate_state_changes = state.get_actual_state_changes_for_fee_charge(
-            block_context.fee_token_address,
+            block_context.fee_token_address(&account_tx_context.fee_type()),
             Some(account_tx_context.sender_address),
         )?;
 
@@ -516,7 +694,7 @@ impl AccountTransaction {
                 // transactional state. If max_fee is insufficient, revert the `run_execute` part.
                 let execute_state_changes = execution_state
                     .get_actual_state_changes_for_fee_charge(
-                        block_context.fee_token_address,
+                        block_context.fee_token_address(&account_tx_context.fee_type()),
                         Some(account_tx_context.sender_address),
                     )?;
                 // Fee is determined by the sum of `validate` and `execute` state changes.
@@ -525,7 +703,7 @@ impl AccountTransaction {
                 let state_changes =
                     StateChanges::merge(vec![validate_state_changes, execute_state_changes]);
 
-                let (actual_fee, _) = self.calculate_actual_fee_and_resources(
+                let (actual_fee, ..) = self.calculate_actual_fee_and_resources(
                     StateChangesCount::from(&state_changes),
                     &execute_call_info,
                     &validate_call_info,
@@ -533,6 +711,7 @@ impl AccountTransaction {
                     block_context,
                     false,
                     0,
+                    signature_length_for_fee,
                 )?;
 
                 if actual_fee > self.max_fee() {
@@ -561,7 +740,21 @@ impl AccountTransaction {
                 execution_state.commit();
                 Ok(ValidateExecuteCallInfo::new_accepted(validate_call_info, execute_call_info))
             }
-            Err(_) => {
+            Err(TransactionExecutionError::ExecutionError(
+                EntryPointExecutionError::ExecutionDeadlineExceeded,
+            )) => {
+                // Aborted rather than failed; reject the transaction.
+                execution_state.abort();
+                Err(TransactionExecutionError::ExecutionDeadlineExceeded)
+            }
+            Err(TransactionExecutionError::ExecutionError(error))
+                if error.category() == ExecutionErrorCategory::Rejected =>
+            {
+                // Failed beyond the control of the transaction (e.g., on a state read); reject it.
+                execution_state.abort();
+                Err(TransactionExecutionError::ExecutionError(error))
+            }
+            Err(error) => {
                 // Error during execution. Revert.
                 execution_state.abort();
                 let remaining_steps = execution_context
@@ -570,9 +763,15 @@ impl AccountTransaction {
                     .expect("The number of steps must be initialized.");
                 let reverted_steps = allotted_steps - remaining_steps;
 
+                let revert_reason = match &error {
+                    TransactionExecutionError::ExecutionError(error) => {
+                        execution_context.revert_reason(error)
+                    }
+                    error => execution_context.revert_reason(error),
+                };
                 Ok(ValidateExecuteCallInfo::new_reverted(
                     validate_call_info,
-                    execution_context.error_trace(),
+                    revert_reason,
                     reverted_steps,
                     false,
                 ))
@@ -581,6 +780,7 @@ impl AccountTransaction {
     }
 
     /// Runs validation and execution.
+    #[allow(clippy::too_many_arguments)]
     fn run_or_revert<S: StateReader>(
         &self,
         state: &mut TransactionalState<'_, S>,
@@ -588,11 +788,13 @@ impl AccountTransaction {
         remaining_gas: &mut u64,
         block_context: &BlockContext,
         validate: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
+        signature_length_for_fee: usize,
     ) -> TransactionExecutionResult<ValidateExecuteCallInfo> {
         let account_tx_context = self.get_account_transaction_context();
         let is_v0 = account_tx_context.is_v0();
         let execution_context =
-            EntryPointExecutionContext::new_invoke(block_context, &account_tx_context);
+            EntryPointExecutionContext::new_invoke(block_context, &account_tx_context)?;
 
         // Handle `DeployAccount` transactions separately, due to different order of things.
         if matches!(self, Self::DeployAccount(_)) {
@@ -616,6 +818,7 @@ impl AccountTransaction {
                 block_context,
                 execution_context,
                 validate,
+                precomputed_validation,
             );
         }
 
@@ -626,6 +829,8 @@ impl AccountTransaction {
             block_context,
             execution_context,
             validate,
+            precomputed_validation,
+            signature_length_for_fee,
         )
     }
 
@@ -639,30 +844,49 @@ impl AccountTransaction {
         block_context: &BlockContext,
         is_reverted: bool,
         n_reverted_steps: usize,
-    ) -> TransactionExecutionResult<(Fee, ResourcesMapping)> {
+        signature_length_for_fee: usize,
+    ) -> TransactionExecutionResult<(Fee, TransactionResources, GasVector)> {
         let account_tx_context = self.get_account_transaction_context();
 
         let non_optional_call_infos = vec![validate_call_info.as_ref(), execute_call_info.as_ref()]
             .into_iter()
             .flatten()
             .collect::<Vec<&CallInfo>>();
-        let l1_gas_usage =
-            calculate_l1_gas_usage(&non_optional_call_infos, state_changes_count, None)?;
-        let mut actual_resources =
-            calculate_tx_resources(execution_resources, l1_gas_usage, self.tx_type())?;
-
-        // Add reverted steps to actual_resources' n_steps for correct fee charge.
-        *actual_resources.0.get_mut(&abi_constants::N_STEPS_RESOURCE.to_string()).unwrap() +=
-            n_reverted_steps;
+        let mut tx_resources = calculate_tx_resources(
+            &non_optional_call_infos,
+            execution_resources,
+            self.tx_type(),
+            self.calldata_length(),
+            signature_length_for_fee,
+            state_changes_count,
+            None,
+            block_context,
+        )?;
 
-        let mut actual_fee = calculate_tx_fee(&actual_resources, block_context)?;
+        // Add reverted steps to the VM resources' n_steps for correct fee charge.
+        *tx_resources
+            .vm_resources
+            .0
+            .get_mut(&abi_constants::N_STEPS_RESOURCE.to_string())
+            .unwrap() += n_reverted_steps;
+
+        let gas_vector = tx_resources.to_gas_vector(block_context)?;
+        // The actual fee is compared with max_fee before it is charged (the execution is reverted,
+        // or the transaction rejected, if it exceeds it), so it may saturate.
+        let mut actual_fee = get_saturated_fee_by_gas_vector(
+            block_context,
+            gas_vector,
+            &account_tx_context.fee_type(),
+        );
 
-        if is_reverted || account_tx_context.max_fee == Fee(0) {
+        if !account_tx_context.enforce_fee(block_context) {
+            actual_fee = Fee(0);
+        } else if is_reverted {
             // We cannot charge more than max_fee for reverted txs.
             actual_fee = min(actual_fee, account_tx_context.max_fee);
         }
 
-        Ok((actual_fee, actual_resources))
+        Ok((actual_fee, tx_resources, gas_vector))
     }
 }
 
@@ -673,9 +897,82 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
         block_context: &BlockContext,
         charge_fee: bool,
         validate: bool,
+    ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        self.execute_raw_with_validation(state, block_context, charge_fee, validate, None, None)
+    }
+}
+
+impl AccountTransaction {
+    /// Executes the transaction as `execute_raw` does, with validation; the given validation
+    /// (precomputed on another state, see `concurrency::validation_phase`) is applied instead of
+    /// running it again, unless a value it read is different in the given state.
+    pub fn execute_raw_with_precomputed_validation<S: StateReader>(
+        self,
+        state: &mut TransactionalState<'_, S>,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        precomputed_validation: PrecomputedValidation,
+    ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        let validate = true;
+        self.execute_raw_with_validation(
+            state,
+            block_context,
+            charge_fee,
+            validate,
+            Some(precomputed_validation),
+            None,
+        )
+    }
+
+    /// Executes the transaction as `execute` does, for a fee estimate. Estimated transactions
+    /// typically carry an empty or placeholder signature, and are executed without validation
+    /// (`validate` is false); their signature is then charged for as being of length
+    /// `signature_length_for_fee`, if given, so that the estimate matches the fee of the signed
+    /// transaction, but for the cost of its (skipped) `__validate__` run. With validation, the
+    /// signature is the one validated, and it is charged for by its own length.
+    pub fn execute_with_signature_length_for_fee<S: StateReader>(
+        self,
+        state: &mut CachedState<S>,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        validate: bool,
+        signature_length_for_fee: Option<usize>,
+    ) -> TransactionExecutionResult<TransactionExecutionInfo> {
+        let mut transactional_state = CachedState::create_transactional(state);
+        let execution_result = self.execute_raw_with_validation(
+            &mut transactional_state,
+            block_context,
+            charge_fee,
+            validate,
+            None,
+            signature_length_for_fee,
+        );
+        match execution_result {
+            Ok(_) => transactional_state.commit(),
+            Err(_) => transactional_state.abort(),
+        }
+
+        execution_result
+    }
+
+    fn execute_raw_with_validation<S: StateReader>(
+        self,
+        state: &mut TransactionalState<'_, S>,
+        block_context: &BlockContext,
+        charge_fee: bool,
+        validate: bool,
+        precomputed_validation: Option<PrecomputedValidation>,
+        signature_length_for_fee: Option<usize>,
     ) -> TransactionExecutionResult<TransactionExecutionInfo> {
         let account_tx_context = self.get_account_transaction_context();
         self.verify_tx_version(account_tx_context.version)?;
+        self.verify_tx_lengths(&account_tx_context, block_context)?;
+        // A `DeployAccount` transaction deploys its own sender, and a version 0 `Declare` (used
+        // for bootstrapping, see `verify_tx_version`) is neither validated nor paid for by it.
+        let is_bootstrap_declare = matches!(self, Self::Declare(_)) && account_tx_context.is_v0();
+        if !matches!(self, Self::DeployAccount(_)) && !is_bootstrap_declare {
+            Self::verify_sender_deployed(state, account_tx_context.sender_address)?;
+        }
 
         let mut resources = ExecutionResources::default();
         let mut remaining_gas = Transaction::initial_gas();
@@ -686,6 +983,11 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
         }
         // Handle nonce.
         Self::handle_nonce(&account_tx_context, state)?;
+        // Without validation, the signature may be a placeholder for one of the given length.
+        let signature_length_for_fee = match signature_length_for_fee {
+            Some(signature_length) if !validate => signature_length,
+            _ => self.signature_length(),
+        };
 
         // Run validation and execution.
         let ValidateExecuteCallInfo {
@@ -694,15 +996,22 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
             revert_error,
             n_reverted_steps,
             charge_max_fee_for_reverted,
-        } =
-            self.run_or_revert(state, &mut resources, &mut remaining_gas, block_context, validate)?;
+        } = self.run_or_revert(
+            state,
+            &mut resources,
+            &mut remaining_gas,
+            block_context,
+            validate,
+            precomputed_validation,
+            signature_length_for_fee,
+        )?;
 
         let is_reverted = revert_error.is_some();
         let state_changes = state.get_actual_state_changes_for_fee_charge(
-            block_context.fee_token_address,
+            block_context.fee_token_address(&account_tx_context.fee_type()),
             Some(account_tx_context.sender_address),
         )?;
-        let (mut actual_fee, actual_resources) = self.calculate_actual_fee_and_resources(
+        let (mut actual_fee, tx_resources, gas_vector) = self.calculate_actual_fee_and_resources(
             StateChangesCount::from(&state_changes),
             &execute_call_info,
             &validate_call_info,
@@ -710,21 +1019,27 @@ impl<S: StateReader> ExecutableTransaction<S> for AccountTransaction {
             block_context,
             is_reverted,
             n_reverted_steps,
+            signature_length_for_fee,
         )?;
 
         // Charge max fee when a transaction reverts due to insufficient max fee.
         if charge_max_fee_for_reverted && is_reverted {
             actual_fee = account_tx_context.max_fee;
         }
-        let fee_transfer_call_info =
+        let (fee_transfer_call_info, collected_fee) =
             self.handle_fee(state, block_context, actual_fee, charge_fee)?;
 
         let tx_execution_info = TransactionExecutionInfo {
             validate_call_info,
             execute_call_info,
             fee_transfer_call_info,
-            actual_fee,
-            actual_resources,
+            receipt: TransactionReceipt {
+                fee: actual_fee,
+                collected_fee,
+                gas: gas_vector,
+                da_gas: tx_resources.da_gas_vector(block_context),
+            },
+            actual_resources: tx_resources,
             revert_error,
         };
         Ok(tx_execution_info)
@@ -1,35 +1,60 @@
 use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
+use assert_matches::assert_matches;
 use cairo_vm::vm::runners::cairo_runner::ResourceTracker;
+use indexmap::IndexMap;
 use rstest::{fixture, rstest};
 use starknet_api::core::{
-    calculate_contract_address, ClassHash, ContractAddress, Nonce, PatriciaKey,
+    calculate_contract_address, ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey,
 };
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
-    Calldata, ContractAddressSalt, DeclareTransactionV0V1, DeclareTransactionV2, Fee,
-    TransactionHash,
+    Calldata, ContractAddressSalt, DeclareTransactionV2, Fee, InvokeTransactionV1,
+    TransactionExecutionStatus, TransactionHash, TransactionSignature,
 };
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
 
-use crate::abi::abi_utils::{get_storage_var_address, selector_from_name};
-use crate::block_context::BlockContext;
+use crate::abi::abi_utils::{
+    get_erc20_balance_var_addresses, get_storage_var_address, selector_from_name,
+};
+use crate::abi::constants as abi_constants;
+use crate::block_context::{
+    BlockContext, FeeChargePolicy, FeeTransferCalldataLayout, FeeTransferInterface, GasPrices,
+    ZeroSequencerFeePolicy,
+};
 use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
 use crate::execution::entry_point::EntryPointExecutionContext;
+use crate::execution::errors::{EntryPointExecutionError, PreExecutionError};
+use crate::fee::fee_utils::get_fee_by_gas_vector;
+use crate::platform::Clock;
+use crate::selector;
 use crate::state::cached_state::CachedState;
-use crate::state::state_api::{State, StateReader};
+use crate::state::errors::StateError;
+use crate::state::state_api::{State, StateReader, StateResult};
+use crate::test_utils::transaction_builders::{DeclareTxArgs, DeployAccountTxArgs, InvokeTxArgs};
 use crate::test_utils::{
-    declare_tx, deploy_account_tx, DictStateReader, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH,
-    BALANCE, ERC20_CONTRACT_PATH, MAX_FEE, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
-    TEST_CONTRACT_ADDRESS, TEST_CONTRACT_CAIRO0_PATH, TEST_ERC20_CONTRACT_CLASS_HASH,
+    create_test_init_data_with_balance, deploy_account_tx, deploy_contract, fund_account,
+    invoke_tx, pad_address_to_64, DictStateReader, FailingStorageReader, NonceManager,
+    ACCOUNT_CONTRACT_CAIRO0_PATH, BALANCE, CURRENT_BLOCK_NUMBER, ERC20_CONTRACT_PATH, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+    TEST_CAIRO1_ACCOUNT_CONTRACT_ADDRESS, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+    TEST_CONTRACT_CAIRO1_PATH, TEST_ERC20_CONTRACT_CLASS_HASH,
     TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS,
 };
 use crate::transaction::account_transaction::AccountTransaction;
-use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::constants::TRANSFER_ENTRY_POINT_NAME;
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+use crate::transaction::objects::{FeeType, GasVector, TransactionExecutionInfo};
 use crate::transaction::test_utils::{
     account_invoke_tx, create_account_tx_for_validate_test,
-    create_state_with_falliable_validation_account, run_invoke_tx, INVALID,
+    create_state_with_falliable_validation_account, create_state_with_trivial_validation_account,
+    legacy_transfer_erc20_class, run_invoke_tx, INVALID,
 };
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transactions::{DeclareTransaction, ExecutableTransaction};
@@ -62,7 +87,7 @@ fn create_state(block_context: BlockContext) -> CachedState<DictStateReader> {
         (test_erc20_class_hash, ContractClassV0::from_file(ERC20_CONTRACT_PATH).into()),
     ]);
     // Deploy the erc20 contract.
-    let test_erc20_address = block_context.fee_token_address;
+    let test_erc20_address = block_context.fee_token_addresses.eth_fee_token_address;
     let address_to_class_hash = HashMap::from([(test_erc20_address, test_erc20_class_hash)]);
 
     CachedState::from(DictStateReader {
@@ -78,79 +103,43 @@ fn create_test_init_data(
     block_context: BlockContext,
     #[from(create_state)] mut state: CachedState<DictStateReader>,
 ) -> TestInitData {
-    let mut nonce_manager = NonceManager::default();
-
-    // Deploy an account contract.
-    let deploy_account_tx = deploy_account_tx(
-        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
-        max_fee,
-        None,
-        None,
-        &mut nonce_manager,
-    );
-    let account_address = deploy_account_tx.contract_address;
-
-    // Update the balance of the about-to-be deployed account contract in the erc20 contract, so it
-    // can pay for the transaction execution.
-    let deployed_account_balance_key =
-        get_storage_var_address("ERC20_balances", &[*account_address.0.key()]).unwrap();
-    state.set_storage_at(
-        block_context.fee_token_address,
-        deployed_account_balance_key,
-        stark_felt!(BALANCE),
-    );
-
-    let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
+    // Deploy an account contract, funded to pay for its transactions.
+    let deploy_account_args = DeployAccountTxArgs { max_fee, ..Default::default() };
+    let account_address = deploy_account_args.contract_address();
+    fund_account(&mut state, account_address, BALANCE, &block_context);
+    let account_tx = deploy_account_args.build(&mut state);
     account_tx.execute(&mut state, &block_context, true, true).unwrap();
 
-    // Declare a contract.
-    let contract_class = ContractClassV0::from_file(TEST_CONTRACT_CAIRO0_PATH).into();
-    let declare_tx = declare_tx(TEST_CLASS_HASH, account_address, max_fee, None);
-    let account_tx = AccountTransaction::Declare(
-        DeclareTransaction::new(
-            starknet_api::transaction::DeclareTransaction::V1(DeclareTransactionV0V1 {
-                nonce: nonce_manager.next(account_address),
-                ..declare_tx
-            }),
-            TransactionHash::default(),
-            contract_class,
-        )
-        .unwrap(),
-    );
+    // Declare a contract, and deploy it using syscall deploy.
+    let account_tx =
+        DeclareTxArgs { sender_address: account_address, max_fee, ..Default::default() }
+            .build(&mut state);
+    account_tx.execute(&mut state, &block_context, true, true).unwrap();
+    let constructor_calldata = calldata![stark_felt!(1_u8), stark_felt!(1_u8)];
+    let calldata = calldata![
+        *account_address.0.key(),         // Contract address.
+        selector!("deploy_contract").0,   // EP selector.
+        stark_felt!(5_u8),                // Calldata length.
+        stark_felt!(TEST_CLASS_HASH),     // Calldata: class_hash.
+        ContractAddressSalt::default().0, // Contract_address_salt.
+        stark_felt!(2_u8),                // Constructor calldata length.
+        constructor_calldata.0[0],        // Constructor calldata: address.
+        constructor_calldata.0[1]         // Constructor calldata: value.
+    ];
+    let account_tx =
+        InvokeTxArgs { sender_address: account_address, calldata, max_fee, ..Default::default() }
+            .build(&mut state);
     account_tx.execute(&mut state, &block_context, true, true).unwrap();
-
-    // Deploy a contract using syscall deploy.
-    let entry_point_selector = selector_from_name("deploy_contract");
-    let salt = ContractAddressSalt::default();
-    let class_hash = class_hash!(TEST_CLASS_HASH);
-    run_invoke_tx(
-        calldata![
-            *account_address.0.key(), // Contract address.
-            entry_point_selector.0,   // EP selector.
-            stark_felt!(5_u8),        // Calldata length.
-            class_hash.0,             // Calldata: class_hash.
-            salt.0,                   // Contract_address_salt.
-            stark_felt!(2_u8),        // Constructor calldata length.
-            stark_felt!(1_u8),        // Constructor calldata: address.
-            stark_felt!(1_u8)         // Constructor calldata: value.
-        ],
-        &mut state,
-        account_address,
-        &block_context,
-        &mut nonce_manager,
-        max_fee,
-    )
-    .unwrap();
-
-    // Calculate the newly deployed contract address
     let contract_address = calculate_contract_address(
         ContractAddressSalt::default(),
-        class_hash,
-        &calldata![stark_felt!(1_u8), stark_felt!(1_u8)],
+        class_hash!(TEST_CLASS_HASH),
+        &constructor_calldata,
         account_address,
     )
     .unwrap();
 
+    let nonce_manager = NonceManager::from_state(&mut state, &[account_address]);
+
     TestInitData { state, account_address, contract_address, nonce_manager, block_context }
 }
 
@@ -171,12 +160,105 @@ fn test_fee_enforcement(
         );
 
         let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
-        let enforce_fee = account_tx.enforce_fee();
+        let enforce_fee = account_tx.enforce_fee(&block_context);
         let result = account_tx.execute(&mut state, &block_context, true, true);
         assert_eq!(result.is_err(), enforce_fee);
     }
 }
 
+/// At pathological gas prices even the minimal fee overflows; the transaction is rejected, rather
+/// than charged a wrapped-around fee.
+#[rstest]
+fn test_fee_overflow(
+    block_context: BlockContext,
+    #[from(create_state)] mut state: CachedState<DictStateReader>,
+) {
+    let gas_price = u128::MAX / 2;
+    let block_context = BlockContext {
+        gas_prices: GasPrices::new(gas_price, gas_price, gas_price, gas_price, false).unwrap(),
+        ..block_context
+    };
+    let deploy_account_tx = deploy_account_tx(
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        Fee(MAX_FEE),
+        None,
+        None,
+        &mut NonceManager::default(),
+    );
+
+    let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
+    let error = account_tx.execute(&mut state, &block_context, true, true).unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::TransactionFeeError(TransactionFeeError::FeeOverflow)
+    );
+}
+
+/// With fee disabled (all gas prices are zero), the transactions of an unfunded account go
+/// through, at a zero fee, and still report their resources.
+#[rstest]
+fn test_fee_disabled_lifecycle() {
+    let block_context = BlockContext {
+        gas_prices: GasPrices::new(0, 0, 0, 0, true).unwrap(),
+        ..BlockContext::create_for_account_testing()
+    };
+    let mut init_data = create_test_init_data_with_balance(block_context, (0, 0));
+    let max_fee = Fee(MAX_FEE);
+    let assert_fee_disabled = |tx_execution_info: &TransactionExecutionInfo| {
+        assert!(!tx_execution_info.is_reverted());
+        assert_eq!(tx_execution_info.receipt.fee, Fee(0));
+        assert!(tx_execution_info.fee_transfer_call_info.is_none());
+        assert!(
+            tx_execution_info.actual_resources.vm_resources.0[abi_constants::N_STEPS_RESOURCE] > 0
+        );
+    };
+
+    // Deploy an account, without funding it.
+    let deploy_account_tx =
+        init_data.deploy_account_tx(DeployAccountTxArgs { max_fee, ..Default::default() });
+    let account_address = deploy_account_tx.contract_address;
+    let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
+    let (state, block_context) = (&mut init_data.state, &init_data.block_context);
+    assert!(!account_tx.enforce_fee(block_context));
+    assert_fee_disabled(&account_tx.execute(state, block_context, true, true).unwrap());
+    assert_eq!(
+        state.get_class_hash_at(account_address).unwrap(),
+        class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH)
+    );
+
+    // Declare a contract.
+    let account_tx = init_data.declare_tx(DeclareTxArgs {
+        sender_address: account_address,
+        max_fee,
+        ..Default::default()
+    });
+    let (state, block_context) = (&mut init_data.state, &init_data.block_context);
+    assert_fee_disabled(&account_tx.execute(state, block_context, true, true).unwrap());
+    assert!(state.get_compiled_contract_class(&class_hash!(TEST_CLASS_HASH)).is_ok());
+
+    // Deploy it, using the deploy syscall.
+    let tx_execution_info = run_invoke_tx(
+        calldata![
+            *account_address.0.key(),         // Contract address.
+            selector!("deploy_contract").0,   // EP selector.
+            stark_felt!(5_u8),                // Calldata length.
+            stark_felt!(TEST_CLASS_HASH),     // Calldata: class_hash.
+            ContractAddressSalt::default().0, // Contract_address_salt.
+            stark_felt!(2_u8),                // Constructor calldata length.
+            stark_felt!(1_u8),                // Constructor calldata: address.
+            stark_felt!(1_u8)                 // Constructor calldata: value.
+        ],
+        state,
+        account_address,
+        block_context,
+        &mut init_data.nonce_manager,
+        max_fee,
+    )
+    .unwrap();
+    assert_fee_disabled(&tx_execution_info);
+    assert_eq!(state.get_nonce_at(account_address).unwrap(), Nonce(stark_felt!(3_u8)));
+}
+
 #[rstest]
 fn test_account_flow_test(max_fee: Fee, #[from(create_test_init_data)] init_data: TestInitData) {
     let TestInitData {
@@ -188,7 +270,7 @@ fn test_account_flow_test(max_fee: Fee, #[from(create_test_init_data)] init_data
     } = init_data;
 
     // Invoke a function from the newly deployed contract.
-    let entry_point_selector = selector_from_name("return_result");
+    let entry_point_selector = selector!("return_result");
     run_invoke_tx(
         calldata![
             *contract_address.0.key(), // Contract address.
@@ -220,7 +302,7 @@ fn test_infinite_recursion(
     mut block_context: BlockContext,
 ) {
     // Limit the number of execution steps (so we quickly hit the limit).
-    block_context.invoke_tx_max_n_steps = 4000;
+    block_context.versioned_constants_mut().invoke_tx_max_n_steps = 4600;
 
     let TestInitData {
         mut state,
@@ -296,22 +378,16 @@ fn test_revert_invoke(
     );
     let deployed_account_address = deploy_account_tx.contract_address;
 
-    // Update the balance of the about-to-be deployed account contract in the erc20 contract, so it
-    // can pay for the transaction execution.
-    let deployed_account_balance_key =
-        get_storage_var_address("ERC20_balances", &[*deployed_account_address.0.key()]).unwrap();
-    state.set_storage_at(
-        block_context.fee_token_address,
-        deployed_account_balance_key,
-        stark_felt!(BALANCE),
-    );
+    // Fund the about-to-be deployed account contract, so it can pay for the transaction
+    // execution.
+    fund_account(&mut state, deployed_account_address, BALANCE, &block_context);
 
     let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
     let deploy_execution_info = account_tx.execute(&mut state, &block_context, true, true).unwrap();
 
     // Invoke a function from the newly deployed contract, that changes the state.
     let storage_key = stark_felt!(9_u8);
-    let entry_point_selector = selector_from_name("write_and_revert");
+    let entry_point_selector = selector!("write_and_revert");
     let tx_execution_info = run_invoke_tx(
         calldata![
             *deployed_account_address.0.key(), // Contract address.
@@ -335,9 +411,14 @@ fn test_revert_invoke(
     assert!(tx_execution_info.revert_error.is_some());
 
     // Check that the nonce was increased and the fee was deducted.
-    let total_deducted_fee = deploy_execution_info.actual_fee.0 + tx_execution_info.actual_fee.0;
+    let total_deducted_fee = deploy_execution_info.receipt.fee.0 + tx_execution_info.receipt.fee.0;
     assert_eq!(
-        state.get_fee_token_balance(&block_context, &deployed_account_address).unwrap(),
+        state
+            .get_fee_token_balance(
+                &deployed_account_address,
+                &block_context.fee_token_addresses.eth_fee_token_address
+            )
+            .unwrap(),
         (stark_felt!(BALANCE - total_deducted_fee), stark_felt!(0_u8))
     );
     assert_eq!(
@@ -364,8 +445,12 @@ fn test_fail_deploy_account(block_context: BlockContext) {
 
     let deployed_account_address =
         ContractAddress::try_from(stark_felt!(TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS)).unwrap();
-    let initial_balance =
-        state.get_fee_token_balance(&block_context, &deployed_account_address).unwrap();
+    let initial_balance = state
+        .get_fee_token_balance(
+            &deployed_account_address,
+            &block_context.fee_token_addresses.eth_fee_token_address,
+        )
+        .unwrap();
 
     // Create and execute (failing) deploy account transaction.
     let deploy_account_tx = create_account_tx_for_validate_test(
@@ -380,7 +465,12 @@ fn test_fail_deploy_account(block_context: BlockContext) {
     // Assert nonce and balance are unchanged, and that no contract was deployed at the address.
     assert_eq!(state.get_nonce_at(deployed_account_address).unwrap(), Nonce(stark_felt!(0_u8)));
     assert_eq!(
-        state.get_fee_token_balance(&block_context, &deployed_account_address).unwrap(),
+        state
+            .get_fee_token_balance(
+                &deployed_account_address,
+                &block_context.fee_token_addresses.eth_fee_token_address
+            )
+            .unwrap(),
         initial_balance
     );
     assert_eq!(state.get_class_hash_at(deploy_address).unwrap(), ClassHash::default());
@@ -393,7 +483,12 @@ fn test_fail_declare(max_fee: Fee, #[from(create_test_init_data)] init_data: Tes
         init_data;
     let class_hash = class_hash!(0xdeadeadeaf72_u128);
     let contract_class = ContractClass::V1(ContractClassV1::default());
-    let initial_balance = state.get_fee_token_balance(&block_context, &account_address).unwrap();
+    let initial_balance = state
+        .get_fee_token_balance(
+            &account_address,
+            &block_context.fee_token_addresses.eth_fee_token_address,
+        )
+        .unwrap();
     let next_nonce = nonce_manager.next(account_address);
 
     // Cannot fail executing a declare tx unless it's V2 or above, and already declared.
@@ -421,7 +516,12 @@ fn test_fail_declare(max_fee: Fee, #[from(create_test_init_data)] init_data: Tes
     declare_account_tx.execute(&mut state, &block_context, true, true).unwrap_err();
     assert_eq!(state.get_nonce_at(account_address).unwrap(), next_nonce);
     assert_eq!(
-        state.get_fee_token_balance(&block_context, &account_address).unwrap(),
+        state
+            .get_fee_token_balance(
+                &account_address,
+                &block_context.fee_token_addresses.eth_fee_token_address
+            )
+            .unwrap(),
         initial_balance
     );
 }
@@ -463,7 +563,7 @@ fn test_reverted_reach_steps_limit(
     #[from(create_state)] state: CachedState<DictStateReader>,
 ) {
     // Limit the number of execution steps (so we quickly hit the limit).
-    block_context.invoke_tx_max_n_steps = 5000;
+    block_context.versioned_constants_mut().invoke_tx_max_n_steps = 5000;
 
     let TestInitData {
         mut state,
@@ -484,8 +584,8 @@ fn test_reverted_reach_steps_limit(
         "recurse",
         0,
     );
-    let n_steps_0 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_0 = result.actual_fee.0;
+    let n_steps_0 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_0 = result.receipt.fee.0;
     // Ensure the transaction was not reverted.
     assert!(!result.is_reverted());
 
@@ -500,8 +600,8 @@ fn test_reverted_reach_steps_limit(
         "recurse",
         1,
     );
-    let n_steps_1 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_1 = result.actual_fee.0;
+    let n_steps_1 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_1 = result.receipt.fee.0;
     // Ensure the transaction was not reverted.
     assert!(!result.is_reverted());
 
@@ -512,7 +612,7 @@ fn test_reverted_reach_steps_limit(
     // Calculate a recursion depth where the transaction will surely fail (not a minimal depth, as
     // base costs are neglected here).
     let steps_diff = n_steps_1 - n_steps_0;
-    let fail_depth = block_context.invoke_tx_max_n_steps / (steps_diff as u32);
+    let fail_depth = block_context.versioned_constants.invoke_tx_max_n_steps / (steps_diff as u32);
 
     // Invoke the `recurse` function with `fail_depth` iterations. This call should fail.
     let result: TransactionExecutionInfo = run_recursive_function(
@@ -525,8 +625,8 @@ fn test_reverted_reach_steps_limit(
         "recurse",
         fail_depth,
     );
-    let n_steps_fail = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_fail: u128 = result.actual_fee.0;
+    let n_steps_fail = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_fail: u128 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -546,8 +646,8 @@ fn test_reverted_reach_steps_limit(
         "recurse",
         fail_depth + 1,
     );
-    let n_steps_fail_next = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_fail_next: u128 = result.actual_fee.0;
+    let n_steps_fail_next = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_fail_next: u128 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -584,8 +684,8 @@ fn test_n_reverted_steps(
         "recursive_fail",
         0,
     );
-    let n_steps_0 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_0 = result.actual_fee.0;
+    let n_steps_0 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_0 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -600,8 +700,8 @@ fn test_n_reverted_steps(
         "recursive_fail",
         1,
     );
-    let n_steps_1 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_1 = result.actual_fee.0;
+    let n_steps_1 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_1 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -616,8 +716,8 @@ fn test_n_reverted_steps(
         "recursive_fail",
         2,
     );
-    let n_steps_2 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_2 = result.actual_fee.0;
+    let n_steps_2 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_2 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -642,8 +742,8 @@ fn test_n_reverted_steps(
         "recursive_fail",
         100,
     );
-    let n_steps_100 = result.actual_resources.0.get("n_steps").unwrap();
-    let actual_fee_100 = result.actual_fee.0;
+    let n_steps_100 = result.actual_resources.vm_resources.0.get("n_steps").unwrap();
+    let actual_fee_100 = result.receipt.fee.0;
     // Ensure the transaction was reverted.
     assert!(result.is_reverted());
 
@@ -652,6 +752,67 @@ fn test_n_reverted_steps(
     assert!(actual_fee_100 - actual_fee_0 == 100 * single_call_fee_delta);
 }
 
+#[rstest]
+/// Tests that a reverted transaction pays for the steps executed before the failure point, on top
+/// of the validation and the OS overhead, and never more than its max_fee.
+fn test_reverted_tx_fee_by_failure_point(
+    max_fee: Fee,
+    block_context: BlockContext,
+    #[from(create_state)] state: CachedState<DictStateReader>,
+) {
+    let TestInitData {
+        mut state,
+        account_address,
+        contract_address,
+        mut nonce_manager,
+        block_context,
+    } = create_test_init_data(max_fee, block_context, state);
+    let mut run_recursive_fail = |max_fee: Fee, depth: u32| {
+        run_recursive_function(
+            &mut state,
+            &block_context,
+            max_fee,
+            &contract_address,
+            &account_address,
+            &mut nonce_manager,
+            "recursive_fail",
+            depth,
+        )
+    };
+
+    // Revert immediately, and after a long loop.
+    let immediate_revert = run_recursive_fail(max_fee, 0);
+    let late_revert = run_recursive_fail(max_fee, 500);
+    assert!(immediate_revert.is_reverted() && late_revert.is_reverted());
+
+    // The transactions differ only by their reverted steps, which are reported in the actual
+    // resources (and hence counted by the bouncer), and charged for (steps are the heaviest
+    // resource, of weight 1).
+    let n_steps = |tx_execution_info: &TransactionExecutionInfo| {
+        tx_execution_info.actual_resources.vm_resources.0[abi_constants::N_STEPS_RESOURCE]
+    };
+    let n_extra_reverted_steps = n_steps(&late_revert) - n_steps(&immediate_revert);
+    assert!(n_extra_reverted_steps > 500);
+    assert_eq!(
+        late_revert.receipt.fee.0 - immediate_revert.receipt.fee.0,
+        n_extra_reverted_steps as u128 * block_context.gas_prices.eth_l1_gas_price
+    );
+
+    // A max_fee below the actual cost bounds the charged fee.
+    let low_max_fee = Fee((immediate_revert.receipt.fee.0 + late_revert.receipt.fee.0) / 2);
+    let capped_revert = run_recursive_fail(low_max_fee, 500);
+    assert!(capped_revert.is_reverted());
+    assert!(capped_revert.receipt.fee <= low_max_fee);
+    assert!(capped_revert.receipt.fee > immediate_revert.receipt.fee);
+    // The consumed resources are reported in full, regardless of the cap.
+    assert_eq!(capped_revert.actual_resources, late_revert.actual_resources);
+    assert_eq!(capped_revert.receipt.gas, late_revert.receipt.gas);
+    assert!(
+        get_fee_by_gas_vector(&block_context, capped_revert.receipt.gas, &FeeType::Eth).unwrap()
+            > capped_revert.receipt.fee
+    );
+}
+
 #[rstest]
 /// Tests that steps are correctly limited based on max_fee.
 fn test_max_fee_to_max_steps_conversion(
@@ -665,12 +826,12 @@ fn test_max_fee_to_max_steps_conversion(
         mut nonce_manager,
         block_context,
     } = create_test_init_data(Fee(MAX_FEE), block_context, state);
-    let actual_fee = 657500000000000;
+    let actual_fee = 730400000000000;
     let execute_calldata = calldata![
-        *contract_address.0.key(),        // Contract address.
-        selector_from_name("with_arg").0, // EP selector.
-        stark_felt!(1_u8),                // Calldata length.
-        stark_felt!(25_u8)                // Calldata: arg.
+        *contract_address.0.key(), // Contract address.
+        selector!("with_arg").0,   // EP selector.
+        stark_felt!(1_u8),         // Calldata length.
+        stark_felt!(25_u8)         // Calldata: arg.
     ];
 
     // First invocation of `with_arg` gets the exact pre-calculated actual fee as max_fee.
@@ -683,10 +844,11 @@ fn test_max_fee_to_max_steps_conversion(
     let execution_context1 = EntryPointExecutionContext::new_invoke(
         &block_context,
         &account_tx1.get_account_transaction_context(),
-    );
+    )
+    .unwrap();
     let max_steps_limit1 = execution_context1.vm_run_resources.get_n_steps();
     let tx_execution_info1 = account_tx1.execute(&mut state, &block_context, true, true).unwrap();
-    let n_steps1 = tx_execution_info1.actual_resources.0.get("n_steps").unwrap();
+    let n_steps1 = tx_execution_info1.actual_resources.vm_resources.0.get("n_steps").unwrap();
 
     // Second invocation of `with_arg` gets twice the pre-calculated actual fee as max_fee.
     let account_tx2 = account_invoke_tx(
@@ -698,15 +860,16 @@ fn test_max_fee_to_max_steps_conversion(
     let execution_context2 = EntryPointExecutionContext::new_invoke(
         &block_context,
         &account_tx2.get_account_transaction_context(),
-    );
+    )
+    .unwrap();
     let max_steps_limit2 = execution_context2.vm_run_resources.get_n_steps();
     let tx_execution_info2 = account_tx2.execute(&mut state, &block_context, true, true).unwrap();
-    let n_steps2 = tx_execution_info2.actual_resources.0.get("n_steps").unwrap();
+    let n_steps2 = tx_execution_info2.actual_resources.vm_resources.0.get("n_steps").unwrap();
 
     // Test that steps limit doubles as max_fee doubles, but actual consumed steps and fee remains.
     assert!(max_steps_limit2.unwrap() == 2 * max_steps_limit1.unwrap());
-    assert!(tx_execution_info1.actual_fee.0 == tx_execution_info2.actual_fee.0);
-    assert!(actual_fee == tx_execution_info2.actual_fee.0);
+    assert!(tx_execution_info1.receipt.fee.0 == tx_execution_info2.receipt.fee.0);
+    assert!(actual_fee == tx_execution_info2.receipt.fee.0);
     assert!(n_steps1 == n_steps2);
 }
 
@@ -737,7 +900,7 @@ fn test_insufficient_max_fee_reverts(
         1,
     );
     assert!(!tx_execution_info1.is_reverted());
-    let actual_fee_depth1 = tx_execution_info1.actual_fee;
+    let actual_fee_depth1 = tx_execution_info1.receipt.fee;
 
     // Invoke the `recurse` function with depth of 2 and the actual fee of depth 1 as max_fee.
     // This call should fail due to insufficient max fee (steps bound based on max_fee is not so
@@ -753,10 +916,10 @@ fn test_insufficient_max_fee_reverts(
         2,
     );
     assert!(tx_execution_info2.is_reverted());
-    assert!(tx_execution_info2.actual_fee == actual_fee_depth1);
+    assert!(tx_execution_info2.receipt.fee == actual_fee_depth1);
     assert!(tx_execution_info2.revert_error.unwrap().starts_with("Insufficient max fee"));
 
-    // Invoke the `recurse` function with depth of 800 and the actual fee of depth 1 as max_fee.
+    // Invoke the `recurse` function with depth of 1500 and the actual fee of depth 1 as max_fee.
     // This call should fail due to no remaining steps (execution steps based on max_fee are bounded
     // well enough to catch this mid-execution).
     let tx_execution_info3: TransactionExecutionInfo = run_recursive_function(
@@ -767,11 +930,943 @@ fn test_insufficient_max_fee_reverts(
         &account_address,
         &mut nonce_manager,
         "recurse",
-        800,
+        1500,
     );
     assert!(tx_execution_info3.is_reverted());
-    assert!(tx_execution_info3.actual_fee == actual_fee_depth1);
-    assert!(
-        tx_execution_info3.revert_error.unwrap().contains("RunResources has no remaining steps.")
+    assert!(tx_execution_info3.receipt.fee == actual_fee_depth1);
+    assert!(tx_execution_info3
+        .revert_error
+        .unwrap()
+        .contains("RunResources has no remaining steps."));
+}
+
+#[rstest]
+/// Tests that an invoke calling a contract that is not deployed is reverted, and that the revert
+/// reason is exposed both by the execution info and by its serialized form.
+fn test_revert_on_call_to_undeployed_contract(
+    max_fee: Fee,
+    #[from(create_test_init_data)] init_data: TestInitData,
+) {
+    let TestInitData { mut state, account_address, mut nonce_manager, block_context, .. } =
+        init_data;
+
+    let undeployed_contract_address = stark_felt!("0x999");
+    let tx_execution_info = run_invoke_tx(
+        calldata![
+            undeployed_contract_address, // Contract address.
+            selector!("transfer").0,     // EP selector.
+            stark_felt!(0_u8)            // Calldata length.
+        ],
+        &mut state,
+        account_address,
+        &block_context,
+        &mut nonce_manager,
+        max_fee,
+    )
+    .unwrap();
+
+    assert!(tx_execution_info.is_reverted());
+    assert_eq!(tx_execution_info.execution_status(), TransactionExecutionStatus::Reverted);
+    let revert_error = tx_execution_info.revert_error.clone().unwrap();
+    let expected_revert_error = format!(
+        "Error in the called contract ({}):
+Error at pc=0:7:
+Got an exception while executing a hint: Custom Hint Error: Requested contract address \
+         ContractAddress(PatriciaKey(StarkFelt(\"{}\"))) is not deployed.
+Cairo traceback (most recent call last):
+Unknown location (pc=0:151)
+Unknown location (pc=0:125)
+",
+        account_address.0.key(),
+        pad_address_to_64("0x999"),
+    );
+    assert_eq!(revert_error, expected_revert_error);
+    let serialized = serde_json::to_value(&tx_execution_info).unwrap();
+    assert_eq!(serialized["revert_error"], serde_json::Value::String(revert_error));
+}
+
+const REVERT_REASONS_DIR: &str = "./resources/testing/revert_reasons";
+
+/// Tests the revert reasons of failed Cairo 1 calls, in the format of the Starknet OS (see
+/// `format_revert_reason`): a frame per failed call, and the decoded panic data.
+#[rstest]
+#[case::cairo0_account_cairo1_panic(
+    TEST_ACCOUNT_CONTRACT_ADDRESS,
+    false,
+    "cairo0_account_cairo1_panic"
+)]
+#[case::cairo0_account_nested_cairo1_panic(
+    TEST_ACCOUNT_CONTRACT_ADDRESS,
+    true,
+    "cairo0_account_nested_cairo1_panic"
+)]
+#[case::cairo1_account_cairo1_panic(
+    TEST_CAIRO1_ACCOUNT_CONTRACT_ADDRESS,
+    false,
+    "cairo1_account_cairo1_panic"
+)]
+fn test_cairo1_revert_reason(
+    #[case] account_address: &str,
+    #[case] nested: bool,
+    #[case] revert_reason_name: &str,
+) {
+    let mut init_data = create_test_init_data_with_balance(
+        BlockContext::create_for_account_testing(),
+        (BALANCE, 0),
+    );
+    let class_hash = class_hash!(TEST_CLASS_HASH);
+    let state = &mut init_data.state;
+    state
+        .set_contract_class(
+            &class_hash,
+            ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH).into(),
+        )
+        .unwrap();
+    let deploy = |state: &mut CachedState<DictStateReader>, salt: u8| {
+        let constructor_calldata = calldata![stark_felt!(1_u8), stark_felt!(1_u8)];
+        deploy_contract(
+            state,
+            class_hash,
+            ContractAddressSalt(stark_felt!(salt)),
+            constructor_calldata,
+        )
+    };
+    let (contract_address, inner_contract_address) = (deploy(state, 0), deploy(state, 1));
+
+    // Fails as the block hash is not yet available.
+    let recent_block_number = stark_felt!(CURRENT_BLOCK_NUMBER);
+    let calldata = if nested {
+        calldata![
+            *contract_address.0.key(),
+            selector!("test_call_contract").0,
+            stark_felt!(4_u8),                  // Calldata length.
+            *inner_contract_address.0.key(),    // Calldata: contract address.
+            selector!("test_get_block_hash").0, // Calldata: EP selector.
+            stark_felt!(1_u8),                  // Calldata: calldata length.
+            recent_block_number                 // Calldata: block number.
+        ]
+    } else {
+        calldata![
+            *contract_address.0.key(),
+            selector!("test_get_block_hash").0,
+            stark_felt!(1_u8), // Calldata length.
+            recent_block_number
+        ]
+    };
+    let account_tx = init_data.invoke_tx(InvokeTxArgs {
+        sender_address: contract_address!(account_address),
+        calldata,
+        ..Default::default()
+    });
+    let tx_execution_info =
+        account_tx.execute(&mut init_data.state, &init_data.block_context, true, true).unwrap();
+
+    let expected_revert_reason =
+        fs::read_to_string(format!("{REVERT_REASONS_DIR}/{revert_reason_name}.txt")).unwrap();
+    assert_eq!(tx_execution_info.revert_error.unwrap(), expected_revert_reason);
+}
+
+/// Tests that on a zero gas price chain, the fee transfer is skipped unless forced, and that the
+/// state diff contains only the changes made by the transaction itself.
+#[rstest]
+#[case::skip_zero_fee_transfer(false)]
+#[case::force_zero_fee_transfer(true)]
+fn test_zero_fee_transfer(
+    #[case] force_zero_fee_transfer: bool,
+    max_fee: Fee,
+    #[from(create_test_init_data)] init_data: TestInitData,
+) {
+    let TestInitData {
+        mut state,
+        account_address,
+        contract_address,
+        mut nonce_manager,
+        block_context,
+    } = init_data;
+    let block_context = BlockContext {
+        gas_prices: GasPrices {
+            eth_l1_gas_price: 0,
+            strk_l1_gas_price: 0,
+            eth_l1_data_gas_price: 0,
+            strk_l1_data_gas_price: 0,
+        },
+        force_zero_fee_transfer,
+        ..block_context
+    };
+
+    let (key, value) = (stark_felt!(15_u8), stark_felt!(7_u8));
+    let account_tx = account_invoke_tx(
+        calldata![
+            *contract_address.0.key(),              // Contract address.
+            selector!("test_storage_read_write").0, // EP selector.
+            stark_felt!(2_u8),                      // Calldata length.
+            key,                                    // Calldata: address.
+            value                                   // Calldata: value.
+        ],
+        account_address,
+        &mut nonce_manager,
+        max_fee,
+    );
+    let mut transactional_state = CachedState::create_transactional(&mut state);
+    let tx_execution_info =
+        account_tx.execute(&mut transactional_state, &block_context, true, true).unwrap();
+
+    assert_eq!(tx_execution_info.receipt.fee, Fee(0));
+    assert_eq!(tx_execution_info.fee_transfer_call_info.is_some(), force_zero_fee_transfer);
+    // A transfer of zero tokens does not change any balance.
+    assert_eq!(
+        transactional_state.to_state_diff().storage_updates,
+        IndexMap::from([(
+            contract_address,
+            IndexMap::from([(StorageKey(patricia_key!(key)), value)])
+        )])
+    );
+}
+
+/// Tests that the validation stage is bounded by `validate_max_n_steps`, and that exceeding it
+/// rejects the transaction, while the execution stage is bounded only by the invoke step limit.
+#[rstest]
+fn test_validate_max_n_steps(max_fee: Fee, #[from(create_test_init_data)] init_data: TestInitData) {
+    let TestInitData {
+        mut state,
+        account_address,
+        contract_address,
+        mut nonce_manager,
+        block_context,
+    } = init_data;
+    let execute_calldata = calldata![
+        *contract_address.0.key(),    // Contract address.
+        selector!("return_result").0, // EP selector.
+        stark_felt!(1_u8),            // Calldata length.
+        stark_felt!(2_u8)             // Calldata: num.
+    ];
+    let nonce = nonce_manager.next(account_address);
+    // Runs the same transaction over the same state, with the given validation step limit.
+    let mut run_with_validate_max_n_steps = |validate_max_n_steps: u32| {
+        let mut block_context = block_context.clone();
+        block_context.versioned_constants_mut().validate_max_n_steps = validate_max_n_steps;
+        let tx = invoke_tx(execute_calldata.clone(), account_address, max_fee, None);
+        let account_tx = AccountTransaction::Invoke(InvokeTransactionV1 { nonce, ..tx }.into());
+        account_tx.execute(
+            &mut CachedState::create_transactional(&mut state),
+            &block_context,
+            true,
+            true,
+        )
+    };
+
+    let tx_execution_info =
+        run_with_validate_max_n_steps(block_context.versioned_constants.validate_max_n_steps)
+            .unwrap();
+    let validate_steps = tx_execution_info.validate_call_info.unwrap().vm_resources.n_steps;
+    let execute_steps = tx_execution_info.execute_call_info.unwrap().vm_resources.n_steps;
+    assert!(execute_steps > validate_steps);
+
+    // Exactly at the limit; the execution stage uses more steps than the validation limit.
+    let tx_execution_info = run_with_validate_max_n_steps(validate_steps as u32).unwrap();
+    assert!(!tx_execution_info.is_reverted());
+
+    // Below the limit; the transaction is rejected rather than reverted.
+    assert_matches!(
+        run_with_validate_max_n_steps(validate_steps as u32 - 1).unwrap_err(),
+        TransactionExecutionError::ValidateTransactionError(_)
+    );
+}
+
+/// Tests that the memory of the calls of a transaction is limited: a deep recursion, which
+/// allocates much memory in few steps, is reverted once it exceeds the limit, while a transfer of
+/// the fee token is unaffected.
+#[rstest]
+#[case::recursion(false)]
+#[case::transfer(true)]
+fn test_max_n_memory_cells(
+    #[case] is_transfer: bool,
+    max_fee: Fee,
+    #[from(create_test_init_data)] init_data: TestInitData,
+) {
+    let TestInitData {
+        mut state,
+        account_address,
+        contract_address,
+        mut nonce_manager,
+        mut block_context,
+    } = init_data;
+    block_context.versioned_constants_mut().max_n_memory_cells = Some(20000);
+    let execute_calldata = if is_transfer {
+        let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+        calldata![
+            *fee_token_address.0.key(),                      // Contract address.
+            selector_from_name(TRANSFER_ENTRY_POINT_NAME).0, // EP selector.
+            stark_felt!(3_u8),                               // Calldata length.
+            *contract_address.0.key(),                       // Calldata: recipient.
+            stark_felt!(1_u8),                               // Calldata: amount (low).
+            stark_felt!(0_u8)                                // Calldata: amount (high).
+        ]
+    } else {
+        calldata![
+            *contract_address.0.key(), // Contract address.
+            selector!("recurse").0,    // EP selector.
+            stark_felt!(1_u8),         // Calldata length.
+            stark_felt!(10000_u16)     // Calldata: depth.
+        ]
+    };
+    let account_tx =
+        account_invoke_tx(execute_calldata, account_address, &mut nonce_manager, max_fee);
+    let tx_execution_info = account_tx.execute(&mut state, &block_context, true, true).unwrap();
+
+    if is_transfer {
+        assert!(!tx_execution_info.is_reverted());
+    } else {
+        assert!(tx_execution_info
+            .revert_error
+            .unwrap()
+            .contains("Exceeded the maximal number of memory cells per transaction"));
+    }
+}
+
+/// Tests that a `DeployAccount` transaction pays its fee from the deployed address, after the
+/// constructor and validation ran, and that it is rejected if the address is not funded.
+#[rstest]
+#[case::pre_funded(BALANCE)]
+#[case::unfunded(0)]
+fn test_deploy_account_fee_payer(
+    #[case] initial_balance: u128,
+    max_fee: Fee,
+    block_context: BlockContext,
+) {
+    let mut init_data = create_test_init_data_with_balance(block_context, (initial_balance, 0));
+    let deploy_account_tx =
+        init_data.deploy_account_tx(DeployAccountTxArgs { max_fee, ..Default::default() });
+    let deployed_account_address = deploy_account_tx.contract_address;
+    init_data.fund(deployed_account_address);
+    let (state, block_context) = (&mut init_data.state, &init_data.block_context);
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let deployed_account_balance_key =
+        get_storage_var_address("ERC20_balances", &[*deployed_account_address.0.key()]).unwrap();
+
+    let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
+    let result = account_tx.execute(state, block_context, true, true);
+
+    if initial_balance == 0 {
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::TransactionFeeError(
+                TransactionFeeError::MaxFeeExceedsBalance { balance_low, .. }
+            )
+            if balance_low == stark_felt!(0_u8)
+        );
+        assert_eq!(state.get_nonce_at(deployed_account_address).unwrap(), Nonce::default());
+        assert_eq!(
+            state.get_class_hash_at(deployed_account_address).unwrap(),
+            ClassHash::default()
+        );
+        return;
+    }
+
+    let tx_execution_info = result.unwrap();
+    assert!(tx_execution_info.execute_call_info.is_some());
+    assert!(tx_execution_info.validate_call_info.is_some());
+    let fee_transfer_call_info = tx_execution_info.fee_transfer_call_info.unwrap();
+    assert_eq!(fee_transfer_call_info.call.caller_address, deployed_account_address);
+    assert_eq!(
+        state.get_storage_at(fee_token_address, deployed_account_balance_key).unwrap(),
+        stark_felt!(initial_balance - tx_execution_info.receipt.fee.0)
+    );
+}
+
+/// Tests that if the payer's balance was spent after the pre-execution balance check (e.g., by the
+/// constructor of a `DeployAccount` transaction), the fee transfer is rejected with a precise
+/// error.
+#[rstest]
+fn test_actual_fee_exceeds_balance(
+    max_fee: Fee,
+    block_context: BlockContext,
+    #[from(create_state)] mut state: CachedState<DictStateReader>,
+) {
+    let deploy_account_tx = deploy_account_tx(
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        max_fee,
+        None,
+        None,
+        &mut NonceManager::default(),
+    );
+    // The state after the constructor ran and spent the funding, but for one unit.
+    let deployed_account_address = deploy_contract(
+        &mut state,
+        deploy_account_tx.class_hash(),
+        deploy_account_tx.contract_address_salt(),
+        deploy_account_tx.constructor_calldata(),
+    );
+    assert_eq!(deployed_account_address, deploy_account_tx.contract_address);
+    fund_account(&mut state, deployed_account_address, 1, &block_context);
+
+    let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
+    let actual_fee = Fee(2);
+    assert_matches!(
+        account_tx.handle_fee(&mut state, &block_context, actual_fee, true).unwrap_err(),
+        TransactionExecutionError::TransactionFeeError(
+            TransactionFeeError::ActualFeeExceedsBalance { actual_fee: fee, balance_low, balance_high }
+        )
+        if fee == actual_fee
+            && balance_low == stark_felt!(1_u8)
+            && balance_high == stark_felt!(0_u8)
+    );
+}
+
+/// An invoke that transfers away all of its sender's balance but for some dust (so that the
+/// sender cannot pay the actual fee) is rejected under the strict fee charge policy, and is charged
+/// the dust under the capped one.
+#[rstest]
+#[case::strict(FeeChargePolicy::Strict)]
+#[case::cap_at_balance(FeeChargePolicy::CapAtBalance)]
+fn test_fee_charge_policy(block_context: BlockContext, #[case] fee_charge_policy: FeeChargePolicy) {
+    let block_context = BlockContext { fee_charge_policy, ..block_context };
+    let fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let dust = 10_u128;
+    let execute_calldata = calldata![
+        *fee_token_address.0.key(),                      // Contract address.
+        selector_from_name(TRANSFER_ENTRY_POINT_NAME).0, // EP selector.
+        stark_felt!(3_u8),                               // Calldata length.
+        stark_felt!(TEST_CONTRACT_ADDRESS),              // Calldata: recipient.
+        stark_felt!(BALANCE - dust),                     // Calldata: amount (low).
+        stark_felt!(0_u8)                                // Calldata: amount (high).
+    ];
+    let account_tx = account_invoke_tx(
+        execute_calldata,
+        account_address,
+        &mut NonceManager::default(),
+        Fee(MAX_FEE),
+    );
+    let state = &mut create_state_with_trivial_validation_account();
+    let result = account_tx.execute(state, &block_context, true, true);
+
+    match fee_charge_policy {
+        FeeChargePolicy::Strict => assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::TransactionFeeError(
+                TransactionFeeError::ActualFeeExceedsBalance { balance_low, .. }
+            )
+            if balance_low == stark_felt!(dust)
+        ),
+        FeeChargePolicy::CapAtBalance => {
+            let tx_execution_info = result.unwrap();
+            assert!(!tx_execution_info.is_reverted());
+            assert!(tx_execution_info.receipt.fee > Fee(dust));
+            assert_eq!(tx_execution_info.receipt.collected_fee, Fee(dust));
+            assert_eq!(
+                state.get_fee_token_balance(&account_address, &fee_token_address).unwrap(),
+                (stark_felt!(0_u8), stark_felt!(0_u8))
+            );
+        }
+    }
+}
+
+/// With a zero sequencer address, the fee is transferred to the zero address (which the fee token
+/// rejects), burnt, or not charged, by the block's policy; burning debits the sender's balance
+/// alone.
+#[rstest]
+#[case::transfer(ZeroSequencerFeePolicy::Transfer)]
+#[case::burn(ZeroSequencerFeePolicy::Burn)]
+#[case::skip(ZeroSequencerFeePolicy::Skip)]
+fn test_zero_sequencer_fee_policy(
+    block_context: BlockContext,
+    #[case] zero_sequencer_fee_policy: ZeroSequencerFeePolicy,
+) {
+    let block_context = BlockContext {
+        sequencer_address: ContractAddress::default(),
+        zero_sequencer_fee_policy,
+        ..block_context
+    };
+    let mut init_data = create_test_init_data_with_balance(block_context, (BALANCE, 0));
+    let fee_token_address = init_data.block_context.fee_token_addresses.eth_fee_token_address;
+    let account_address = init_data.cairo0_account_address;
+    let account_tx = init_data.invoke_tx(InvokeTxArgs {
+        calldata: calldata![
+            *fee_token_address.0.key(), // Contract address.
+            selector!("balanceOf").0,   // EP selector.
+            stark_felt!(1_u8),          // Calldata length.
+            *account_address.0.key()    // Calldata: account.
+        ],
+        ..Default::default()
+    });
+    let state = &mut init_data.state;
+    let result = account_tx.execute(state, &init_data.block_context, true, true);
+
+    if zero_sequencer_fee_policy == ZeroSequencerFeePolicy::Transfer {
+        // The fee token rejects transfers to the zero address.
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::EntryPointExecutionError(
+                EntryPointExecutionError::VirtualMachineExecutionErrorWithTrace { .. }
+            )
+        );
+        return;
+    }
+
+    let tx_execution_info = result.unwrap();
+    let fee = tx_execution_info.receipt.fee;
+    assert!(fee > Fee(0));
+    assert_eq!(tx_execution_info.fee_transfer_call_info, None);
+    let fee_token_storage_updates =
+        state.to_state_diff().storage_updates.get(&fee_token_address).cloned().unwrap_or_default();
+    if zero_sequencer_fee_policy == ZeroSequencerFeePolicy::Burn {
+        assert_eq!(tx_execution_info.receipt.collected_fee, fee);
+        let (low_key, _) = get_erc20_balance_var_addresses(&account_address).unwrap();
+        assert_eq!(
+            fee_token_storage_updates,
+            IndexMap::from([(low_key, stark_felt!(BALANCE - fee.0))])
+        );
+    } else {
+        assert_eq!(tx_execution_info.receipt.collected_fee, Fee(0));
+        assert_eq!(fee_token_storage_updates, IndexMap::new());
+    }
+}
+
+/// A fee token without the canonical `transfer` entry point is charged through the one the block
+/// context configures.
+#[rstest]
+#[case::canonical_interface(None)]
+#[case::legacy_interface(Some("transfer_legacy"))]
+fn test_fee_transfer_interface(block_context: BlockContext, #[case] selector_name: Option<&str>) {
+    let fee_transfer_interface = match selector_name {
+        Some(selector_name) => FeeTransferInterface {
+            selector_name: selector_name.to_string(),
+            calldata_layout: FeeTransferCalldataLayout::RecipientAmount,
+        },
+        None => FeeTransferInterface::default(),
+    };
+    let block_context = BlockContext { fee_transfer_interface, ..block_context };
+    let mut init_data = create_test_init_data_with_balance(block_context, (BALANCE, 0));
+    let fee_token_address = init_data.block_context.fee_token_addresses.eth_fee_token_address;
+    let legacy_token_class_hash = class_hash!("0x1e2c");
+    init_data
+        .state
+        .set_contract_class(&legacy_token_class_hash, legacy_transfer_erc20_class())
+        .unwrap();
+    init_data.state.set_class_hash_at(fee_token_address, legacy_token_class_hash).unwrap();
+    let account_address = init_data.cairo0_account_address;
+    let account_tx = init_data.invoke_tx(InvokeTxArgs {
+        calldata: calldata![
+            *fee_token_address.0.key(), // Contract address.
+            selector!("balanceOf").0,   // EP selector.
+            stark_felt!(1_u8),          // Calldata length.
+            *account_address.0.key()    // Calldata: account.
+        ],
+        ..Default::default()
+    });
+    let state = &mut init_data.state;
+    let block_context = &init_data.block_context;
+    let result = account_tx.execute(state, block_context, true, true);
+
+    let Some(selector_name) = selector_name else {
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::EntryPointExecutionError(
+                EntryPointExecutionError::PreExecutionError(
+                    PreExecutionError::EntryPointNotFound(selector)
+                )
+            )
+            if selector == selector_from_name(TRANSFER_ENTRY_POINT_NAME)
+        );
+        return;
+    };
+
+    let tx_execution_info = result.unwrap();
+    let fee = tx_execution_info.receipt.fee;
+    let fee_transfer_call_info = tx_execution_info.fee_transfer_call_info.unwrap();
+    assert_eq!(fee_transfer_call_info.call.entry_point_selector, selector_from_name(selector_name));
+    assert_eq!(
+        state.get_fee_token_balance(&account_address, &fee_token_address).unwrap(),
+        (stark_felt!(BALANCE - fee.0), stark_felt!(0_u8))
+    );
+    assert_eq!(
+        state.get_fee_token_balance(&block_context.sequencer_address, &fee_token_address).unwrap(),
+        (stark_felt!(fee.0), stark_felt!(0_u8))
+    );
+}
+
+/// A state reader whose storage reads of the given contract are slow.
+struct SlowStorageStateReader {
+    state: DictStateReader,
+    slow_contract_address: ContractAddress,
+    delay: Duration,
+}
+
+impl StateReader for SlowStorageStateReader {
+    fn get_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<StarkFelt> {
+        if contract_address == self.slow_contract_address {
+            thread::sleep(self.delay);
+        }
+        self.state.get_storage_at(contract_address, key)
+    }
+
+    fn get_nonce_at(&mut self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        self.state.get_nonce_at(contract_address)
+    }
+
+    fn get_class_hash_at(&mut self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        self.state.get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_contract_class(
+        &mut self,
+        class_hash: &ClassHash,
+    ) -> StateResult<ContractClass> {
+        self.state.get_compiled_contract_class(class_hash)
+    }
+
+    fn get_compiled_class_hash(&mut self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.state.get_compiled_class_hash(class_hash)
+    }
+}
+
+/// Tests that a transaction whose execution exceeds its deadline is rejected, even if it is within
+/// its step limit; here, the execution is slowed down by a slow syscall rather than by its steps.
+#[rstest]
+#[case::no_deadline(None)]
+#[case::deadline_not_exceeded(Some(Duration::from_secs(60)))]
+#[case::deadline_exceeded(Some(Duration::from_millis(50)))]
+fn test_execution_deadline(
+    #[case] max_execution_time: Option<Duration>,
+    max_fee: Fee,
+    block_context: BlockContext,
+) {
+    let block_context = BlockContext { max_execution_time, ..block_context };
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let mut state = CachedState::from(SlowStorageStateReader {
+        state: create_state_with_trivial_validation_account().state,
+        slow_contract_address: contract_address,
+        delay: Duration::from_millis(100),
+    });
+
+    // The storage write reads the previous value, which takes longer than the deadline; the
+    // following storage read aborts the execution.
+    let execute_calldata = calldata![
+        *contract_address.0.key(),              // Contract address.
+        selector!("test_storage_read_write").0, // EP selector.
+        stark_felt!(2_u8),                      // Calldata length.
+        stark_felt!(15_u8),                     // Calldata: address.
+        stark_felt!(3_u8)                       // Calldata: value.
+    ];
+    let account_tx =
+        account_invoke_tx(execute_calldata, account_address, &mut NonceManager::default(), max_fee);
+    let result = account_tx.execute(&mut state, &block_context, true, true);
+
+    if max_execution_time == Some(Duration::from_millis(50)) {
+        assert_matches!(result.unwrap_err(), TransactionExecutionError::ExecutionDeadlineExceeded);
+        // Rejected: no nonce bump or fee charge.
+        assert_eq!(state.get_nonce_at(account_address).unwrap(), Nonce::default());
+        assert_eq!(
+            state
+                .get_fee_token_balance(
+                    &account_address,
+                    &block_context.fee_token_addresses.eth_fee_token_address
+                )
+                .unwrap(),
+            (stark_felt!(BALANCE), stark_felt!(0_u8))
+        );
+    } else {
+        assert!(!result.unwrap().is_reverted());
+    }
+}
+
+/// A clock advancing by a minute on every reading.
+#[derive(Debug, Default)]
+struct SteppingClock {
+    n_readings: AtomicU64,
+}
+
+impl Clock for SteppingClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs(60 * self.n_readings.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Tests that the deadline is measured by the block context clock, rather than by the system
+/// clock.
+#[rstest]
+fn test_execution_deadline_with_custom_clock(max_fee: Fee, block_context: BlockContext) {
+    let clock = Arc::new(SteppingClock::default());
+    let block_context = BlockContext {
+        max_execution_time: Some(Duration::from_secs(30)),
+        clock: clock.clone(),
+        ..block_context
+    };
+    let mut state = create_state_with_trivial_validation_account();
+
+    // The deadline is exceeded at the first syscall.
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS),     // Contract address.
+        selector!("test_storage_read_write").0, // EP selector.
+        stark_felt!(2_u8),                      // Calldata length.
+        stark_felt!(15_u8),                     // Calldata: address.
+        stark_felt!(3_u8)                       // Calldata: value.
+    ];
+    let account_invoke_tx = account_invoke_tx(
+        execute_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default(),
+        max_fee,
+    );
+    assert_matches!(
+        account_invoke_tx.execute(&mut state, &block_context, true, true).unwrap_err(),
+        TransactionExecutionError::ExecutionDeadlineExceeded
+    );
+    assert!(clock.n_readings.load(Ordering::Relaxed) > 1);
+}
+
+/// Tests that an execution failing on a state read rejects the transaction, rather than reverting
+/// it.
+#[rstest]
+fn test_state_read_error_in_execution(max_fee: Fee, block_context: BlockContext) {
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let mut state = CachedState::from(FailingStorageReader {
+        state_reader: create_state_with_trivial_validation_account().state,
+        failing_contract_address: contract_address,
+    });
+
+    let execute_calldata = calldata![
+        *contract_address.0.key(),              // Contract address.
+        selector!("test_storage_read_write").0, // EP selector.
+        stark_felt!(2_u8),                      // Calldata length.
+        stark_felt!(15_u8),                     // Calldata: address.
+        stark_felt!(3_u8)                       // Calldata: value.
+    ];
+    let account_tx = account_invoke_tx(
+        execute_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default(),
+        max_fee,
+    );
+    assert_matches!(
+        account_tx.execute(&mut state, &block_context, true, true).unwrap_err(),
+        TransactionExecutionError::ExecutionError(EntryPointExecutionError::StateError(
+            StateError::StateReadError(_)
+        ))
+    );
+}
+
+#[rstest]
+#[case::at_the_limits(0, 0)]
+#[case::signature_too_long(1, 0)]
+#[case::calldata_too_long(0, 1)]
+fn test_max_signature_and_calldata_length(
+    #[case] excess_signature_length: usize,
+    #[case] excess_calldata_length: usize,
+    max_fee: Fee,
+    block_context: BlockContext,
+    #[from(create_state)] state: CachedState<DictStateReader>,
+) {
+    let TestInitData { mut state, account_address, contract_address, mut nonce_manager, .. } =
+        create_test_init_data(max_fee, block_context.clone(), state);
+    let signature = TransactionSignature(vec![stark_felt!(1_u8), stark_felt!(2_u8)]);
+    let execute_calldata = calldata![
+        *contract_address.0.key(),    // Contract address.
+        selector!("return_result").0, // EP selector.
+        stark_felt!(1_u8),            // Calldata length.
+        stark_felt!(2_u8)             // Calldata: num.
+    ];
+    let mut block_context = block_context;
+    let versioned_constants = block_context.versioned_constants_mut();
+    versioned_constants.max_signature_length = signature.0.len() - excess_signature_length;
+    versioned_constants.max_calldata_length = execute_calldata.0.len() - excess_calldata_length;
+    let tx = InvokeTransactionV1 {
+        nonce: nonce_manager.next(account_address),
+        ..invoke_tx(execute_calldata, account_address, max_fee, Some(signature))
+    };
+    let account_tx = AccountTransaction::Invoke(tx.into());
+
+    let result = account_tx.execute(&mut state, &block_context, true, true);
+    if excess_signature_length > 0 {
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::SignatureTooLong { length: 2, max_length: 1 }
+        );
+    } else if excess_calldata_length > 0 {
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::CalldataTooLong { length: 4, max_length: 3 }
+        );
+    } else {
+        assert!(!result.unwrap().is_reverted());
+    }
+}
+
+#[rstest]
+fn test_kzg_da_gas_vector(
+    max_fee: Fee,
+    block_context: BlockContext,
+    #[from(create_state)] state: CachedState<DictStateReader>,
+) {
+    let TestInitData { mut state, account_address, contract_address, mut nonce_manager, .. } =
+        create_test_init_data(max_fee, block_context.clone(), state);
+    let tx = InvokeTransactionV1 {
+        nonce: nonce_manager.next(account_address),
+        ..invoke_tx(
+            calldata![
+                *contract_address.0.key(),    // Contract address.
+                selector!("return_result").0, // EP selector.
+                stark_felt!(1_u8),            // Calldata length.
+                stark_felt!(2_u8)             // Calldata: num.
+            ],
+            account_address,
+            max_fee,
+            None,
+        )
+    };
+    let mut execute_with_kzg_da = |use_kzg_da: bool| {
+        let block_context = BlockContext { use_kzg_da, ..block_context.clone() };
+        let account_tx = AccountTransaction::Invoke(tx.clone().into());
+        // Execute both versions on the same state.
+        let mut transactional_state = CachedState::create_transactional(&mut state);
+        let execution_info =
+            account_tx.execute(&mut transactional_state, &block_context, true, true).unwrap();
+        transactional_state.abort();
+        execution_info
+    };
+    let calldata_da_info = execute_with_kzg_da(false);
+    let kzg_da_info = execute_with_kzg_da(true);
+
+    // 1 modified contract, 1 storage update (sender balance); 4 felts, paid for either in L1 gas
+    // (612 per felt) or in data gas (32 per felt). Under KZG DA, the OS also computes the
+    // commitment, in 113 more steps (of 1 L1 gas each, in the test weights).
+    let n_da_felts = 2 + 2;
+    let kzg_commitment_gas = 113;
+    assert_eq!(calldata_da_info.receipt.gas.l1_data_gas, 0);
+    assert_eq!(
+        kzg_da_info.receipt.gas,
+        GasVector {
+            l1_gas: calldata_da_info.receipt.gas.l1_gas - n_da_felts * 612 + kzg_commitment_gas,
+            l1_data_gas: n_da_felts * 32,
+        }
+    );
+    let gas_prices = &block_context.gas_prices;
+    assert_eq!(
+        kzg_da_info.receipt.fee.0,
+        calldata_da_info.receipt.fee.0
+            - (n_da_felts * 612 - kzg_commitment_gas) as u128 * gas_prices.eth_l1_gas_price
+            + (n_da_felts * 32) as u128 * gas_prices.eth_l1_data_gas_price
+    );
+}
+
+#[rstest]
+fn test_event_emission_cost(max_fee: Fee, block_context: BlockContext) {
+    let mut state = create_state_with_trivial_validation_account();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    // The events are emitted by an inner (Cairo 1) call of the account.
+    state
+        .set_contract_class(
+            &class_hash!(TEST_CLASS_HASH),
+            ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH).into(),
+        )
+        .unwrap();
+    let (n_keys, n_data) = (10_u8, 50_u8);
+    let execute_calldata = Calldata(
+        [
+            vec![
+                *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+                selector!("test_emit_event").0,                    // EP selector.
+                stark_felt!(n_keys + n_data + 2),                  // Calldata length.
+                stark_felt!(n_keys),
+            ],
+            vec![stark_felt!(1_u8); n_keys.into()],
+            vec![stark_felt!(n_data)],
+            vec![stark_felt!(2_u8); n_data.into()],
+        ]
+        .concat()
+        .into(),
+    );
+    let tx =
+        account_invoke_tx(execute_calldata, account_address, &mut NonceManager::default(), max_fee);
+
+    let mut execute_with_event_costs = |event_key_gas_cost: usize, event_data_gas_cost: usize| {
+        let mut block_context = block_context.clone();
+        let versioned_constants = block_context.versioned_constants_mut();
+        versioned_constants.event_key_gas_cost = event_key_gas_cost;
+        versioned_constants.event_data_gas_cost = event_data_gas_cost;
+        // Execute both versions on the same state.
+        let mut transactional_state = CachedState::create_transactional(&mut state);
+        let execution_info =
+            tx.clone().execute(&mut transactional_state, &block_context, true, true).unwrap();
+        transactional_state.abort();
+        execution_info
+    };
+    // Events are free prior to 0.13.1.
+    let free_events_info = execute_with_event_costs(0, 0);
+    let charged_events_info = execute_with_event_costs(200, 100);
+
+    assert!(!charged_events_info.is_reverted());
+    let expected_events_gas_cost = usize::from(n_keys) * 200 + usize::from(n_data) * 100;
+    assert_eq!(
+        charged_events_info.receipt.gas.l1_gas,
+        free_events_info.receipt.gas.l1_gas + expected_events_gas_cost
+    );
+    assert_eq!(
+        charged_events_info.receipt.fee.0,
+        free_events_info.receipt.fee.0
+            + expected_events_gas_cost as u128 * block_context.gas_prices.eth_l1_gas_price
+    );
+}
+
+#[rstest]
+fn test_signature_length_for_fee(max_fee: Fee, block_context: BlockContext) {
+    let mut state = create_state_with_trivial_validation_account();
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+    let execute_calldata = calldata![
+        *contract_address!(TEST_CONTRACT_ADDRESS).0.key(), // Contract address.
+        selector!("return_result").0,                      // EP selector.
+        stark_felt!(1_u8),                                 // Calldata length.
+        stark_felt!(2_u8)                                  // Calldata: num.
+    ];
+    let signature = TransactionSignature(vec![stark_felt!(1_u8), stark_felt!(2_u8)]);
+    let tx = InvokeTransactionV1 {
+        nonce: NonceManager::default().next(account_address),
+        ..invoke_tx(execute_calldata, account_address, max_fee, None)
+    };
+
+    let mut execute = |signature: TransactionSignature,
+                       validate: bool,
+                       signature_length_for_fee: Option<usize>| {
+        let account_tx =
+            AccountTransaction::Invoke(InvokeTransactionV1 { signature, ..tx.clone() }.into());
+        // Execute all versions on the same state.
+        let mut transactional_state = CachedState::create_transactional(&mut state);
+        let execution_info = account_tx
+            .execute_with_signature_length_for_fee(
+                &mut transactional_state,
+                &block_context,
+                true,
+                validate,
+                signature_length_for_fee,
+            )
+            .unwrap();
+        transactional_state.abort();
+        execution_info
+    };
+    // Estimates are executed without validation.
+    let signed_info = execute(signature.clone(), false, None);
+    let estimate_info = execute(TransactionSignature::default(), false, Some(2));
+    let unsigned_info = execute(TransactionSignature::default(), false, None);
+    // Under validation, the declared length is ignored; the signature itself is validated.
+    let validated_info = execute(signature.clone(), true, None);
+    let validated_with_length_info = execute(signature, true, Some(0));
+
+    assert!(!signed_info.is_reverted());
+    assert_eq!(estimate_info.receipt.fee, signed_info.receipt.fee);
+    assert_eq!(estimate_info.receipt.gas, signed_info.receipt.gas);
+    // The signature is charged for as transaction data, per felt.
+    let signature_gas_cost = 2 * block_context.versioned_constants.tx_data_felt_gas_cost;
+    assert!(signature_gas_cost > 0);
+    assert_eq!(
+        estimate_info.receipt.fee.0,
+        unsigned_info.receipt.fee.0
+            + signature_gas_cost as u128 * block_context.gas_prices.eth_l1_gas_price
     );
+    assert_eq!(validated_with_length_info.receipt.fee, validated_info.receipt.fee);
 }
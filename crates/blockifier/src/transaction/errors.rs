@@ -1,3 +1,5 @@
+use std::fmt;
+
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
 use starknet_api::transaction::{Fee, TransactionVersion};
@@ -5,11 +7,19 @@ use starknet_api::StarknetApiError;
 use thiserror::Error;
 
 use crate::execution::entry_point::Retdata;
-use crate::execution::errors::EntryPointExecutionError;
+use crate::execution::errors::{EntryPointExecutionError, PostExecutionError, PreExecutionError};
 use crate::state::errors::StateError;
+use crate::transaction::constants;
+use crate::transaction::transaction_types::TransactionType;
+
+#[cfg(test)]
+#[path = "errors_test.rs"]
+mod test;
 
 #[derive(Debug, Error)]
 pub enum TransactionExecutionError {
+    #[error("Calldata length ({length}) exceeds the maximum ({max_length}).")]
+    CalldataTooLong { length: usize, max_length: usize },
     #[error("Cairo resource names must be contained in fee cost dict.")]
     CairoResourcesNotContainedInFeeCosts,
     #[error(
@@ -17,28 +27,28 @@ pub enum TransactionExecutionError {
          version {cairo_version:?}."
     )]
     ContractClassVersionMismatch { declare_version: TransactionVersion, cairo_version: u64 },
+    #[error("Contract class bytecode size ({size}) exceeds the maximum ({max}).")]
+    ContractBytecodeSizeTooLarge { size: usize, max: usize },
     #[error("Contract constructor execution has failed.")]
     ContractConstructorExecutionFailed(#[source] EntryPointExecutionError),
     #[error("Class with hash {class_hash:?} is already declared.")]
     DeclareTransactionError { class_hash: ClassHash },
     #[error(transparent)]
     EntryPointExecutionError(#[from] EntryPointExecutionError),
+    #[error("Transaction execution was aborted; its deadline was exceeded.")]
+    ExecutionDeadlineExceeded,
     #[error("Transaction execution has failed.")]
     ExecutionError(#[source] EntryPointExecutionError),
-    #[error("Actual fee ({actual_fee:?}) exceeded max fee ({max_fee:?}).")]
-    FeeTransferError { max_fee: Fee, actual_fee: Fee },
-    #[error("Actual fee ({actual_fee:?}) exceeded paid fee on L1 ({paid_fee:?}).")]
-    InsufficientL1Fee { paid_fee: Fee, actual_fee: Fee },
     #[error(
         "Invalid transaction nonce of contract at address {address:?}. Expected: \
          {expected_nonce:?}; got: {actual_nonce:?}."
     )]
     InvalidNonce { address: ContractAddress, expected_nonce: Nonce, actual_nonce: Nonce },
     #[error(
-        "Invalid order number for {object}. Order: {order} exceeds the maximum order limit: \
-         {max_order}."
+        "Invalid order number for L2-to-L1 message. Order: {order} exceeds the maximum order \
+         limit: {max_order}."
     )]
-    InvalidOrder { object: String, order: usize, max_order: usize },
+    InvalidL2ToL1MessageOrder { order: usize, max_order: usize },
     #[error("The `validate` entry point should return `VALID`. Got {actual:?}.")]
     InvalidValidateReturnData { actual: Retdata },
     #[error(
@@ -46,18 +56,189 @@ pub enum TransactionExecutionError {
          {allowed_versions:?}."
     )]
     InvalidVersion { version: TransactionVersion, allowed_versions: Vec<TransactionVersion> },
-    #[error("Max fee ({max_fee:?}) exceeds balance (Uint256({balance_low:?}, {balance_high:?})).")]
-    MaxFeeExceedsBalance { max_fee: Fee, balance_low: StarkFelt, balance_high: StarkFelt },
-    #[error("Max fee ({max_fee:?}) is too low. Minimum fee: {min_fee:?}.")]
-    MaxFeeTooLow { min_fee: Fee, max_fee: Fee },
+    #[error("L1 handler calldata must start with the L1 sender address (`from_address`).")]
+    MissingL1HandlerSenderAddress,
+    #[error("Signature length ({length}) exceeds the maximum ({max_length}).")]
+    SignatureTooLong { length: usize, max_length: usize },
     #[error(transparent)]
     StarknetApiError(#[from] StarknetApiError),
     #[error(transparent)]
     StateError(#[from] StateError),
+    #[error(transparent)]
+    TransactionFeeError(#[from] TransactionFeeError),
     #[error("Calling other contracts during '{entry_point_kind}' execution is forbidden.")]
-    UnauthorizedInnerCall { entry_point_kind: String },
-    #[error("Unexpected holes in the {object} order. No object with the order: {order}.")]
-    UnexpectedHoles { object: String, order: usize },
+    UnauthorizedInnerCall { entry_point_kind: RestrictedEntryPointKind },
+    #[error("Unexpected holes in the L2-to-L1 message order. No object with the order: {order}.")]
+    UnexpectedL2ToL1MessageOrderHole { order: usize },
+    #[error("Sender account at address {address:?} is not deployed.")]
+    UninitializedSenderAccount { address: ContractAddress },
     #[error("Transaction validation has failed.")]
     ValidateTransactionError(#[source] EntryPointExecutionError),
 }
+
+impl TransactionExecutionError {
+    /// Returns the Starknet error code of the rejection reason.
+    // No wildcard arms: every new variant must be assigned a code.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::CalldataTooLong { .. } => ErrorCode::CalldataTooLong,
+            Self::CairoResourcesNotContainedInFeeCosts
+            | Self::InvalidL2ToL1MessageOrder { .. }
+            | Self::MissingL1HandlerSenderAddress
+            | Self::StarknetApiError(_)
+            | Self::UnexpectedL2ToL1MessageOrderHole { .. } => ErrorCode::UnexpectedFailure,
+            Self::ContractBytecodeSizeTooLarge { .. } => ErrorCode::ContractBytecodeSizeTooLarge,
+            Self::ContractClassVersionMismatch { .. } => ErrorCode::InvalidContractClassVersion,
+            Self::ContractConstructorExecutionFailed(_) => ErrorCode::TransactionFailed,
+            Self::DeclareTransactionError { .. } => ErrorCode::ClassAlreadyDeclared,
+            // Raised, unwrapped, only by the fee transfer.
+            Self::EntryPointExecutionError(_) => ErrorCode::FeeTransferFailure,
+            Self::ExecutionDeadlineExceeded => ErrorCode::TransactionLimitExceeded,
+            Self::ExecutionError(error) => match error {
+                EntryPointExecutionError::ExecutionDeadlineExceeded
+                | EntryPointExecutionError::PostExecutionError(
+                    PostExecutionError::MemoryLimitExceeded { .. },
+                ) => ErrorCode::TransactionLimitExceeded,
+                EntryPointExecutionError::PreExecutionError(
+                    PreExecutionError::EntryPointNotFound(_),
+                ) => ErrorCode::EntryPointNotFound,
+                EntryPointExecutionError::PreExecutionError(
+                    PreExecutionError::UninitializedStorageAddress(_),
+                ) => ErrorCode::UninitializedContract,
+                _ => ErrorCode::TransactionFailed,
+            },
+            Self::InvalidNonce { .. } => ErrorCode::InvalidTransactionNonce,
+            Self::InvalidValidateReturnData { .. }
+            | Self::UnauthorizedInnerCall { .. }
+            | Self::ValidateTransactionError(_) => ErrorCode::ValidateFailure,
+            Self::InvalidVersion { .. } => ErrorCode::InvalidTransactionVersion,
+            Self::SignatureTooLong { .. } => ErrorCode::SignatureTooLong,
+            Self::StateError(error) => match error {
+                StateError::UndeclaredClassHash(_) => ErrorCode::UndeclaredClass,
+                _ => ErrorCode::UnexpectedFailure,
+            },
+            Self::TransactionFeeError(error) => error.error_code(),
+            Self::UninitializedSenderAccount { .. } => ErrorCode::UninitializedContract,
+        }
+    }
+}
+
+/// An entry point that may not call other contracts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RestrictedEntryPointKind {
+    AccountConstructor,
+    Validate,
+}
+
+impl fmt::Display for RestrictedEntryPointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AccountConstructor => write!(f, "an account constructor"),
+            Self::Validate => write!(f, "{}", constants::VALIDATE_ENTRY_POINT_NAME),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TransactionFeeError {
+    #[error(
+        "Actual fee ({actual_fee:?}) exceeds balance (Uint256({balance_low:?}, {balance_high:?}))."
+    )]
+    ActualFeeExceedsBalance { actual_fee: Fee, balance_low: StarkFelt, balance_high: StarkFelt },
+    #[error("Actual fee ({actual_fee:?}) exceeded max fee ({max_fee:?}).")]
+    FeeTransferError { max_fee: Fee, actual_fee: Fee },
+    #[error("Balance overflow: the balance does not fit in 256 bits.")]
+    BalanceOverflow,
+    #[error("Fee transfer failed; it returned {retdata:?}, rather than TRUE.")]
+    FeeTransferFailed { retdata: Retdata },
+    #[error("Fee overflow: the fee does not fit in 128 bits.")]
+    FeeOverflow,
+    #[error("Gas amount overflow in the fee calculation.")]
+    GasAmountOverflow,
+    #[error("Actual fee ({actual_fee:?}) exceeded paid fee on L1 ({paid_fee:?}).")]
+    InsufficientL1Fee { paid_fee: Fee, actual_fee: Fee },
+    #[error("Uint256 limb {limb:?} does not fit in 128 bits.")]
+    InvalidUint256Limb { limb: StarkFelt },
+    #[error("Max fee ({max_fee:?}) exceeds balance (Uint256({balance_low:?}, {balance_high:?})).")]
+    MaxFeeExceedsBalance { max_fee: Fee, balance_low: StarkFelt, balance_high: StarkFelt },
+    #[error("Max fee ({max_fee:?}) is too low. Minimum fee: {min_fee:?}.")]
+    MaxFeeTooLow { min_fee: Fee, max_fee: Fee },
+    #[error("The fee transfer did not read the sequencer balance where expected.")]
+    UnexpectedFeeTransferReads,
+}
+
+impl TransactionFeeError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::ActualFeeExceedsBalance { .. } | Self::MaxFeeExceedsBalance { .. } => {
+                ErrorCode::InsufficientAccountBalance
+            }
+            Self::FeeTransferError { .. }
+            | Self::InsufficientL1Fee { .. }
+            | Self::MaxFeeTooLow { .. } => ErrorCode::InsufficientMaxFee,
+            Self::BalanceOverflow
+            | Self::FeeOverflow
+            | Self::GasAmountOverflow
+            | Self::InvalidUint256Limb { .. }
+            | Self::UnexpectedFeeTransferReads => ErrorCode::UnexpectedFailure,
+            Self::FeeTransferFailed { .. } => ErrorCode::FeeTransferFailure,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TransactionTraceError {
+    #[error("The execution info of the {tx_type:?} transaction has no constructor call info.")]
+    MissingConstructorCallInfo { tx_type: TransactionType },
+    #[error("The execution info of the {tx_type:?} transaction has no execute call info.")]
+    MissingExecuteCallInfo { tx_type: TransactionType },
+    #[error("The call info of a call to contract {contract_address:?} has no class hash.")]
+    MissingClassHash { contract_address: ContractAddress },
+}
+
+/// The Starknet error codes a transaction may be rejected with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorCode {
+    CalldataTooLong,
+    ClassAlreadyDeclared,
+    ContractBytecodeSizeTooLarge,
+    EntryPointNotFound,
+    FeeTransferFailure,
+    InsufficientAccountBalance,
+    InsufficientMaxFee,
+    InvalidContractClassVersion,
+    InvalidTransactionNonce,
+    InvalidTransactionVersion,
+    SignatureTooLong,
+    TransactionFailed,
+    TransactionLimitExceeded,
+    UndeclaredClass,
+    UnexpectedFailure,
+    UninitializedContract,
+    ValidateFailure,
+}
+
+impl ErrorCode {
+    /// Returns the code as reported by the gateway.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CalldataTooLong => "CALLDATA_TOO_LONG",
+            Self::ClassAlreadyDeclared => "CLASS_ALREADY_DECLARED",
+            Self::ContractBytecodeSizeTooLarge => "CONTRACT_BYTECODE_SIZE_TOO_LARGE",
+            Self::EntryPointNotFound => "ENTRY_POINT_NOT_FOUND_IN_CONTRACT",
+            Self::FeeTransferFailure => "FEE_TRANSFER_FAILURE",
+            Self::InsufficientAccountBalance => "INSUFFICIENT_ACCOUNT_BALANCE",
+            Self::InsufficientMaxFee => "INSUFFICIENT_MAX_FEE",
+            Self::InvalidContractClassVersion => "INVALID_CONTRACT_CLASS_VERSION",
+            Self::InvalidTransactionNonce => "INVALID_TRANSACTION_NONCE",
+            Self::InvalidTransactionVersion => "INVALID_TRANSACTION_VERSION",
+            Self::SignatureTooLong => "SIGNATURE_TOO_LONG",
+            Self::TransactionFailed => "TRANSACTION_FAILED",
+            Self::TransactionLimitExceeded => "TRANSACTION_LIMIT_EXCEEDED",
+            Self::UndeclaredClass => "UNDECLARED_CLASS",
+            Self::UnexpectedFailure => "UNEXPECTED_FAILURE",
+            Self::UninitializedContract => "UNINITIALIZED_CONTRACT",
+            Self::ValidateFailure => "VALIDATE_FAILURE",
+        }
+    }
+}
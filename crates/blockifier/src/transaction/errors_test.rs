@@ -0,0 +1,162 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce};
+use starknet_api::hash::StarkFelt;
+use starknet_api::transaction::{Fee, TransactionVersion};
+
+use crate::execution::entry_point::{CallExecution, CallInfo, OrderedL2ToL1Message, Retdata};
+use crate::execution::errors::{EntryPointExecutionError, PreExecutionError};
+use crate::state::errors::StateError;
+use crate::transaction::errors::{ErrorCode, TransactionExecutionError, TransactionFeeError};
+
+fn execution_failed() -> EntryPointExecutionError {
+    EntryPointExecutionError::ExecutionFailed { error_data: vec![] }
+}
+
+#[rstest]
+#[case::balance_overflow(TransactionFeeError::BalanceOverflow.into(), "UNEXPECTED_FAILURE")]
+#[case::calldata_too_long(
+    TransactionExecutionError::CalldataTooLong { length: 2, max_length: 1 },
+    "CALLDATA_TOO_LONG"
+)]
+#[case::class_already_declared(
+    TransactionExecutionError::DeclareTransactionError { class_hash: ClassHash::default() },
+    "CLASS_ALREADY_DECLARED"
+)]
+#[case::contract_bytecode_size_too_large(
+    TransactionExecutionError::ContractBytecodeSizeTooLarge { size: 2, max: 1 },
+    "CONTRACT_BYTECODE_SIZE_TOO_LARGE"
+)]
+#[case::entry_point_not_found(
+    TransactionExecutionError::ExecutionError(PreExecutionError::EntryPointNotFound(
+        EntryPointSelector::default()
+    ).into()),
+    "ENTRY_POINT_NOT_FOUND_IN_CONTRACT"
+)]
+#[case::fee_transfer_failure(
+    TransactionExecutionError::EntryPointExecutionError(execution_failed()),
+    "FEE_TRANSFER_FAILURE"
+)]
+#[case::fee_overflow(TransactionFeeError::FeeOverflow.into(), "UNEXPECTED_FAILURE")]
+#[case::fee_transfer_returned_false(
+    TransactionFeeError::FeeTransferFailed { retdata: Retdata::default() }.into(),
+    "FEE_TRANSFER_FAILURE"
+)]
+#[case::gas_amount_overflow(
+    TransactionFeeError::GasAmountOverflow.into(),
+    "UNEXPECTED_FAILURE"
+)]
+#[case::insufficient_account_balance(
+    TransactionFeeError::MaxFeeExceedsBalance {
+        max_fee: Fee(2),
+        balance_low: StarkFelt::from(1_u8),
+        balance_high: StarkFelt::from(0_u8),
+    }.into(),
+    "INSUFFICIENT_ACCOUNT_BALANCE"
+)]
+#[case::insufficient_max_fee(
+    TransactionFeeError::MaxFeeTooLow { min_fee: Fee(2), max_fee: Fee(1) }.into(),
+    "INSUFFICIENT_MAX_FEE"
+)]
+#[case::invalid_contract_class_version(
+    TransactionExecutionError::ContractClassVersionMismatch {
+        declare_version: TransactionVersion(StarkFelt::from(2_u8)),
+        cairo_version: 1,
+    },
+    "INVALID_CONTRACT_CLASS_VERSION"
+)]
+#[case::invalid_uint256_limb(
+    TransactionFeeError::InvalidUint256Limb { limb: StarkFelt::from(u128::MAX) }.into(),
+    "UNEXPECTED_FAILURE"
+)]
+#[case::invalid_transaction_nonce(
+    TransactionExecutionError::InvalidNonce {
+        address: ContractAddress::default(),
+        expected_nonce: Nonce::default(),
+        actual_nonce: Nonce(StarkFelt::from(1_u8)),
+    },
+    "INVALID_TRANSACTION_NONCE"
+)]
+#[case::invalid_transaction_version(
+    TransactionExecutionError::InvalidVersion {
+        version: TransactionVersion(StarkFelt::from(3_u8)),
+        allowed_versions: vec![],
+    },
+    "INVALID_TRANSACTION_VERSION"
+)]
+#[case::missing_l1_handler_sender_address(
+    TransactionExecutionError::MissingL1HandlerSenderAddress,
+    "UNEXPECTED_FAILURE"
+)]
+#[case::signature_too_long(
+    TransactionExecutionError::SignatureTooLong { length: 2, max_length: 1 },
+    "SIGNATURE_TOO_LONG"
+)]
+#[case::transaction_failed(
+    TransactionExecutionError::ExecutionError(execution_failed()),
+    "TRANSACTION_FAILED"
+)]
+#[case::transaction_limit_exceeded(
+    TransactionExecutionError::ExecutionDeadlineExceeded,
+    "TRANSACTION_LIMIT_EXCEEDED"
+)]
+#[case::undeclared_class(
+    StateError::UndeclaredClassHash(ClassHash::default()).into(),
+    "UNDECLARED_CLASS"
+)]
+#[case::unexpected_failure(
+    TransactionExecutionError::CairoResourcesNotContainedInFeeCosts,
+    "UNEXPECTED_FAILURE"
+)]
+#[case::unexpected_fee_transfer_reads(
+    TransactionFeeError::UnexpectedFeeTransferReads.into(),
+    "UNEXPECTED_FAILURE"
+)]
+#[case::uninitialized_contract(
+    TransactionExecutionError::UninitializedSenderAccount { address: ContractAddress::default() },
+    "UNINITIALIZED_CONTRACT"
+)]
+#[case::validate_failure(
+    TransactionExecutionError::InvalidValidateReturnData { actual: Retdata::default() },
+    "VALIDATE_FAILURE"
+)]
+fn test_error_code(#[case] error: TransactionExecutionError, #[case] expected_code: &str) {
+    assert_eq!(error.error_code().as_str(), expected_code);
+}
+
+#[test]
+fn test_nested_validation_error_code() {
+    // Any failure inside `validate` is a validation failure, regardless of its cause.
+    let error = TransactionExecutionError::ValidateTransactionError(
+        PreExecutionError::EntryPointNotFound(EntryPointSelector::default()).into(),
+    );
+    assert_eq!(error.error_code(), ErrorCode::ValidateFailure);
+}
+
+fn call_info_with_message_orders(orders: &[usize]) -> CallInfo {
+    let l2_to_l1_messages =
+        orders.iter().map(|&order| OrderedL2ToL1Message { order, ..Default::default() }).collect();
+    CallInfo {
+        execution: CallExecution { l2_to_l1_messages, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_l2_to_l1_message_order_errors() {
+    let call_info = call_info_with_message_orders(&[1, 0]);
+    assert_eq!(call_info.get_sorted_l2_to_l1_payloads_length().unwrap(), vec![0, 0]);
+
+    let call_info = call_info_with_message_orders(&[0, 2]);
+    assert_matches!(
+        call_info.get_sorted_l2_to_l1_payloads_length(),
+        Err(TransactionExecutionError::InvalidL2ToL1MessageOrder { order: 2, max_order: 2 })
+    );
+
+    let call_info = call_info_with_message_orders(&[1, 1]);
+    assert_matches!(
+        call_info.get_sorted_l2_to_l1_payloads_length(),
+        Err(TransactionExecutionError::UnexpectedL2ToL1MessageOrderHole { order: 0 })
+    );
+}
@@ -1,14 +1,23 @@
 use std::collections::{HashMap, HashSet};
 
 use itertools::concat;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
 use starknet_api::stark_felt;
-use starknet_api::transaction::{Fee, TransactionHash, TransactionSignature, TransactionVersion};
+use starknet_api::transaction::{
+    Fee, TransactionExecutionStatus, TransactionHash, TransactionSignature, TransactionVersion,
+};
 
-use crate::execution::entry_point::CallInfo;
+use crate::block_context::{BlockContext, GasPrices};
+use crate::execution::entry_point::{CallInfo, EventWithAddress, MessageToL1WithAddress};
+use crate::fee::transaction_resources::TransactionResources;
 use crate::transaction::errors::TransactionExecutionError;
 
+#[cfg(test)]
+#[path = "objects_test.rs"]
+mod test;
+
 pub type TransactionExecutionResult<T> = Result<T, TransactionExecutionError>;
 
 /// Contains the account information of the transaction (outermost call).
@@ -26,10 +35,34 @@ impl AccountTransactionContext {
     pub fn is_v0(&self) -> bool {
         self.version == TransactionVersion(stark_felt!(0_u8))
     }
+
+    /// Returns the token the transaction fee is paid in; transactions prior to version 3 pay in
+    /// ETH.
+    pub fn fee_type(&self) -> FeeType {
+        if self.version < TransactionVersion(stark_felt!(3_u8)) {
+            FeeType::Eth
+        } else {
+            FeeType::Strk
+        }
+    }
+
+    /// Returns whether the transaction pays fee; it does not if its max fee is zero, or if fee is
+    /// disabled in the block (all gas prices are zero, e.g., on devnets). Such transactions skip
+    /// the balance checks and the fee transfer, but their actual resources are still reported (at
+    /// a zero fee), and the block step limits still apply.
+    pub fn enforce_fee(&self, block_context: &BlockContext) -> bool {
+        self.max_fee != Fee(0) && !block_context.gas_prices.are_zero(&self.fee_type())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FeeType {
+    Strk,
+    Eth,
 }
 
 /// Contains the information gathered by the execution of a transaction.
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct TransactionExecutionInfo {
     /// Transaction validation call info; [None] for `L1Handler`.
     pub validate_call_info: Option<CallInfo>,
@@ -37,11 +70,11 @@ pub struct TransactionExecutionInfo {
     pub execute_call_info: Option<CallInfo>,
     /// Fee transfer call info; [None] for `L1Handler`.
     pub fee_transfer_call_info: Option<CallInfo>,
-    /// The actual fee that was charged (in Wei).
-    pub actual_fee: Fee,
-    /// Actual execution resources the transaction is charged for,
-    /// including L1 gas and additional OS resources estimation.
-    pub actual_resources: ResourcesMapping,
+    /// The fee charged, and the gas consumed.
+    pub receipt: TransactionReceipt,
+    /// The resources consumed by the transaction, including additional OS resources estimation
+    /// and the steps of reverted executions.
+    pub actual_resources: TransactionResources,
     /// Error string for reverted transactions; [None] if transaction execution was successful.
     // TODO(Dori, 1/8/2023): If the `Eq` and `PartialEq` traits are removed, or implemented on all
     //   internal structs in this enum, this field should be `Option<TransactionExecutionError>`.
@@ -59,6 +92,37 @@ impl TransactionExecutionInfo {
         call_infos.into_iter().flatten().collect()
     }
 
+    /// Returns the events emitted by the transaction, in the order of emission: those of the
+    /// validation, then the execution, then the fee transfer; events of failed calls are
+    /// excluded.
+    pub fn all_events(&self) -> Vec<EventWithAddress> {
+        concat(self.non_optional_call_infos().into_iter().map(CallInfo::get_sorted_events))
+    }
+
+    /// Returns the L2 to L1 messages sent by the transaction, in the order they were sent (as
+    /// `all_events`).
+    pub fn all_messages(&self) -> Vec<MessageToL1WithAddress> {
+        concat(self.non_optional_call_infos().into_iter().map(CallInfo::get_sorted_messages))
+    }
+
+    /// Returns the number of events in `all_events`.
+    pub fn n_events(&self) -> usize {
+        self.non_optional_call_infos()
+            .into_iter()
+            .flat_map(CallInfo::iter_non_failed)
+            .map(|call| call.execution.events.len())
+            .sum()
+    }
+
+    /// Returns the number of messages in `all_messages`.
+    pub fn n_messages(&self) -> usize {
+        self.non_optional_call_infos()
+            .into_iter()
+            .flat_map(CallInfo::iter_non_failed)
+            .map(|call| call.execution.l2_to_l1_messages.len())
+            .sum()
+    }
+
     /// Returns the set of class hashes that were executed during this transaction execution.
     pub fn get_executed_class_hashes(&self) -> HashSet<ClassHash> {
         concat(
@@ -68,11 +132,129 @@ impl TransactionExecutionInfo {
         )
     }
 
+    /// Returns the execution info in the layout of the Python `TransactionExecutionInfo` (as
+    /// reported by `native_blockifier`), with felts as decimal strings; the resources are in the
+    /// flat form of `TransactionResources::to_resources_mapping`.
+    pub fn to_py_compatible_json(
+        &self,
+        block_context: &BlockContext,
+    ) -> TransactionExecutionResult<serde_json::Value> {
+        let actual_resources = self.actual_resources.to_resources_mapping(block_context)?.0;
+        let mut actual_resources: Vec<_> = actual_resources.into_iter().collect();
+        actual_resources.sort();
+
+        Ok(serde_json::json!({
+            "validate_call_info":
+                self.validate_call_info.as_ref().map(CallInfo::to_py_compatible_json),
+            "execute_call_info":
+                self.execute_call_info.as_ref().map(CallInfo::to_py_compatible_json),
+            "fee_transfer_call_info":
+                self.fee_transfer_call_info.as_ref().map(CallInfo::to_py_compatible_json),
+            "actual_fee": self.receipt.fee.0,
+            "actual_resources": serde_json::Map::from_iter(
+                actual_resources.into_iter().map(|(resource, usage)| (resource, usage.into()))
+            ),
+            "revert_error": self.revert_error,
+        }))
+    }
+
     pub fn is_reverted(&self) -> bool {
         self.revert_error.is_some()
     }
+
+    pub fn execution_status(&self) -> TransactionExecutionStatus {
+        if self.is_reverted() {
+            TransactionExecutionStatus::Reverted
+        } else {
+            TransactionExecutionStatus::Succeeded
+        }
+    }
+}
+
+/// A `TransactionExecutionInfo` to persist, tagged by the version of its format; felts serialize
+/// as hex strings in human-readable formats (e.g., JSON), and as bytes in the others (e.g.,
+/// bincode).
+/// On a change in the format, the previous version is kept as a variant (holding a copy of the
+/// previous structs), so that persisted infos remain readable.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum VersionedTransactionExecutionInfo {
+    V0(TransactionExecutionInfo),
+}
+
+impl From<TransactionExecutionInfo> for VersionedTransactionExecutionInfo {
+    fn from(tx_execution_info: TransactionExecutionInfo) -> Self {
+        Self::V0(tx_execution_info)
+    }
+}
+
+impl From<VersionedTransactionExecutionInfo> for TransactionExecutionInfo {
+    fn from(versioned_tx_execution_info: VersionedTransactionExecutionInfo) -> Self {
+        match versioned_tx_execution_info {
+            VersionedTransactionExecutionInfo::V0(tx_execution_info) => tx_execution_info,
+        }
+    }
+}
+
+/// The fee charged for a transaction, and the gas it consumed.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionReceipt {
+    /// The actual fee that was charged (in Wei or Fri); lower than the cost of `gas` if capped by
+    /// the max fee (e.g., for reverted transactions).
+    pub fee: Fee,
+    /// The fee actually transferred from the sender; lower than `fee` only if the sender could not
+    /// pay it in full, under `FeeChargePolicy::CapAtBalance`. Zero if the fee was not charged.
+    pub collected_fee: Fee,
+    /// The L1 gas and data gas consumed by the transaction.
+    pub gas: GasVector,
+    /// The part of `gas` consumed by publishing the state diff (data availability).
+    pub da_gas: GasVector,
+}
+
+/// Amounts of L1 gas and L1 data (blob) gas; each is priced separately.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GasVector {
+    pub l1_gas: usize,
+    pub l1_data_gas: usize,
+}
+
+impl GasVector {
+    /// Returns the sum of the vectors, or [None] on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            l1_gas: self.l1_gas.checked_add(rhs.l1_gas)?,
+            l1_data_gas: self.l1_data_gas.checked_add(rhs.l1_data_gas)?,
+        })
+    }
+
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self {
+            l1_gas: self.l1_gas.saturating_add(rhs.l1_gas),
+            l1_data_gas: self.l1_data_gas.saturating_add(rhs.l1_data_gas),
+        }
+    }
+
+    /// Returns the vector multiplied by the given factor, or [None] on overflow.
+    pub fn checked_scalar_mul(self, factor: usize) -> Option<Self> {
+        Some(Self {
+            l1_gas: self.l1_gas.checked_mul(factor)?,
+            l1_data_gas: self.l1_data_gas.checked_mul(factor)?,
+        })
+    }
+
+    /// Returns the cost of the vector by the gas prices of the given fee type, or [None] on
+    /// overflow.
+    pub fn cost(&self, gas_prices: &GasPrices, fee_type: &FeeType) -> Option<Fee> {
+        let l1_gas_cost = u128::try_from(self.l1_gas)
+            .ok()?
+            .checked_mul(gas_prices.get_gas_price_by_fee_type(fee_type))?;
+        let l1_data_gas_cost = u128::try_from(self.l1_data_gas)
+            .ok()?
+            .checked_mul(gas_prices.get_data_gas_price_by_fee_type(fee_type))?;
+
+        Some(Fee(l1_gas_cost.checked_add(l1_data_gas_cost)?))
+    }
 }
 
 /// A mapping from a transaction execution resource to its actual usage.
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ResourcesMapping(pub HashMap<String, usize>);
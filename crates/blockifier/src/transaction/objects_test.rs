@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use assert_matches::assert_matches;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{
+    Calldata, EthAddress, EventContent, EventData, EventKey, Fee, L2ToL1Payload,
+};
+use starknet_api::{class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::selector_from_name;
+use crate::block_context::{BlockContext, GasPrices};
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::execution::entry_point::{
+    CallEntryPoint, CallExecution, CallInfo, EventWithAddress, MessageToL1, MessageToL1WithAddress,
+    OrderedEvent, OrderedL2ToL1Message, Retdata,
+};
+use crate::fee::fee_utils::{get_fee_by_gas_vector, get_saturated_fee_by_gas_vector};
+use crate::fee::transaction_resources::TransactionResources;
+use crate::retdata;
+use crate::state::cached_state::StateChangesCount;
+use crate::test_utils::{
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS_2,
+    TEST_ERC20_CONTRACT_ADDRESS,
+};
+use crate::transaction::errors::{TransactionExecutionError, TransactionFeeError};
+use crate::transaction::objects::{
+    FeeType, GasVector, ResourcesMapping, TransactionExecutionInfo, TransactionReceipt,
+    VersionedTransactionExecutionInfo,
+};
+
+fn gas_vector(l1_gas: usize, l1_data_gas: usize) -> GasVector {
+    GasVector { l1_gas, l1_data_gas }
+}
+
+#[rstest]
+#[case::no_overflow(gas_vector(1, 2), gas_vector(3, 4), Some(gas_vector(4, 6)))]
+#[case::at_max(gas_vector(usize::MAX - 1, 0), gas_vector(1, 0), Some(gas_vector(usize::MAX, 0)))]
+#[case::l1_gas_overflow(gas_vector(usize::MAX, 0), gas_vector(1, 0), None)]
+#[case::l1_data_gas_overflow(gas_vector(0, usize::MAX / 2 + 1), gas_vector(0, usize::MAX / 2 + 1), None)]
+fn test_checked_add(
+    #[case] lhs: GasVector,
+    #[case] rhs: GasVector,
+    #[case] expected_sum: Option<GasVector>,
+) {
+    assert_eq!(lhs.checked_add(rhs), expected_sum);
+    assert_eq!(
+        lhs.saturating_add(rhs),
+        expected_sum.unwrap_or(gas_vector(
+            lhs.l1_gas.saturating_add(rhs.l1_gas),
+            lhs.l1_data_gas.saturating_add(rhs.l1_data_gas)
+        ))
+    );
+}
+
+#[test]
+fn test_saturating_add() {
+    assert_eq!(
+        gas_vector(usize::MAX, 1).saturating_add(gas_vector(usize::MAX, 1)),
+        gas_vector(usize::MAX, 2)
+    );
+}
+
+#[rstest]
+#[case::no_overflow(gas_vector(2, 3), 5, Some(gas_vector(10, 15)))]
+#[case::zero_factor(gas_vector(usize::MAX, usize::MAX), 0, Some(gas_vector(0, 0)))]
+#[case::overflow(gas_vector(1, usize::MAX / 2 + 1), 2, None)]
+fn test_checked_scalar_mul(
+    #[case] vector: GasVector,
+    #[case] factor: usize,
+    #[case] expected_product: Option<GasVector>,
+) {
+    assert_eq!(vector.checked_scalar_mul(factor), expected_product);
+}
+
+#[rstest]
+#[case::no_overflow(gas_vector(10, 20), 3, Some(Fee(10 * 3 + 20 * 3)))]
+#[case::max_price_overflow(gas_vector(2, 0), u128::MAX, None)]
+#[case::max_gas_and_price_overflow(gas_vector(u64::MAX as usize, u64::MAX as usize), u128::MAX, None)]
+#[case::sum_overflow(gas_vector(1, 1), u128::MAX, None)]
+#[case::product_overflow(gas_vector(3, 0), u128::MAX / 2, None)]
+fn test_cost(
+    #[case] vector: GasVector,
+    #[case] gas_price: u128,
+    #[case] expected_cost: Option<Fee>,
+) {
+    let gas_prices = GasPrices::new(gas_price, gas_price, gas_price, gas_price, false).unwrap();
+    for fee_type in [FeeType::Eth, FeeType::Strk] {
+        assert_eq!(vector.cost(&gas_prices, &fee_type), expected_cost);
+    }
+}
+
+/// A fee that does not fit in 128 bits is rejected, rather than wrapping; unless it is bounded by
+/// the max fee, in which case it saturates.
+#[test]
+fn test_fee_overflow_is_an_error() {
+    let price = u128::MAX / 2;
+    let block_context = BlockContext {
+        gas_prices: GasPrices::new(price, price, price, price, false).unwrap(),
+        ..BlockContext::create_for_account_testing()
+    };
+    let error =
+        get_fee_by_gas_vector(&block_context, gas_vector(3, 0), &FeeType::Strk).unwrap_err();
+    assert_matches!(
+        error,
+        TransactionExecutionError::TransactionFeeError(TransactionFeeError::FeeOverflow)
+    );
+
+    assert_eq!(
+        get_saturated_fee_by_gas_vector(&block_context, gas_vector(3, 0), &FeeType::Strk),
+        Fee(u128::MAX)
+    );
+    assert_eq!(
+        get_saturated_fee_by_gas_vector(&block_context, gas_vector(2, 0), &FeeType::Strk),
+        Fee(price * 2)
+    );
+}
+
+fn call_info(
+    storage_address: &str,
+    entry_point_name: &str,
+    execution: CallExecution,
+    inner_calls: Vec<CallInfo>,
+) -> CallInfo {
+    CallInfo {
+        call: CallEntryPoint {
+            class_hash: Some(class_hash!(TEST_CLASS_HASH)),
+            entry_point_selector: selector_from_name(entry_point_name),
+            calldata: Calldata(Arc::new(vec![stark_felt!(7_u8), stark_felt!("0x1234")])),
+            storage_address: contract_address!(storage_address),
+            initial_gas: 1000000,
+            ..Default::default()
+        },
+        execution,
+        vm_resources: VmExecutionResources {
+            n_steps: 120,
+            n_memory_holes: 3,
+            builtin_instance_counter: HashMap::from([("range_check_builtin".to_string(), 4)]),
+        },
+        inner_calls,
+        gas_metered: true,
+        storage_read_values: vec![stark_felt!(99_u8)],
+        accessed_storage_keys: HashSet::from([StorageKey(patricia_key!(15_u8))]),
+        syscall_counter: HashMap::from([(DeprecatedSyscallSelector::StorageRead, 1)]),
+    }
+}
+
+/// An execution whose top call succeeded after one of its inner calls (itself calling another
+/// contract) failed.
+fn reverted_call_trace_tx_execution_info() -> TransactionExecutionInfo {
+    let failed_inner_call = call_info(
+        TEST_CONTRACT_ADDRESS_2,
+        "fail",
+        CallExecution {
+            retdata: retdata![stark_felt!("0x6661696c")],
+            failed: true,
+            gas_consumed: 500,
+            ..Default::default()
+        },
+        vec![call_info(
+            TEST_CONTRACT_ADDRESS,
+            "test_storage_read",
+            CallExecution::default(),
+            vec![],
+        )],
+    );
+    let execute_call_info = call_info(
+        TEST_CONTRACT_ADDRESS,
+        "test_call_contract",
+        CallExecution {
+            retdata: retdata![stark_felt!(1_u8)],
+            events: vec![OrderedEvent {
+                order: 0,
+                event: EventContent {
+                    keys: vec![EventKey(stark_felt!(2_u8))],
+                    data: EventData(vec![stark_felt!(3_u8)]),
+                },
+            }],
+            l2_to_l1_messages: vec![OrderedL2ToL1Message {
+                order: 0,
+                message: MessageToL1 {
+                    to_address: EthAddress::try_from(stark_felt!(4_u8)).unwrap(),
+                    payload: L2ToL1Payload(vec![stark_felt!(5_u8)]),
+                },
+            }],
+            failed: false,
+            gas_consumed: 2000,
+        },
+        vec![failed_inner_call],
+    );
+
+    TransactionExecutionInfo {
+        validate_call_info: None,
+        execute_call_info: Some(execute_call_info),
+        fee_transfer_call_info: None,
+        receipt: TransactionReceipt {
+            fee: Fee(1000),
+            collected_fee: Fee(1000),
+            gas: GasVector { l1_gas: 10, l1_data_gas: 1 },
+            da_gas: GasVector { l1_gas: 2, l1_data_gas: 1 },
+        },
+        actual_resources: TransactionResources {
+            vm_resources: ResourcesMapping(HashMap::from([("n_steps".to_string(), 240)])),
+            calldata_length: 3,
+            signature_length: 2,
+            state_changes_count: StateChangesCount {
+                n_storage_updates: 1,
+                n_class_hash_updates: 0,
+                n_compiled_class_hash_updates: 0,
+                n_modified_contracts: 1,
+            },
+            l2_to_l1_payloads_length: vec![1],
+            l1_handler_payload_size: None,
+            n_event_keys: 1,
+            n_event_data_felts: 1,
+            l2_gas: 2000,
+        },
+        revert_error: Some("Execution failed.".to_string()),
+    }
+}
+
+#[test]
+fn test_tx_execution_info_json_round_trip() {
+    let versioned_info =
+        VersionedTransactionExecutionInfo::from(reverted_call_trace_tx_execution_info());
+    let json = serde_json::to_value(&versioned_info).unwrap();
+
+    // Felts are hex strings.
+    let retdata = &json["V0"]["execute_call_info"]["inner_calls"][0]["execution"]["retdata"];
+    assert_eq!(retdata, &serde_json::json!(["0x6661696c"]));
+
+    let deserialized_info: VersionedTransactionExecutionInfo =
+        serde_json::from_value(json).unwrap();
+    assert_eq!(
+        TransactionExecutionInfo::from(deserialized_info),
+        reverted_call_trace_tx_execution_info()
+    );
+}
+
+#[test]
+fn test_tx_execution_info_bincode_round_trip() {
+    let versioned_info =
+        VersionedTransactionExecutionInfo::from(reverted_call_trace_tx_execution_info());
+    let config = bincode::config::standard();
+    let bytes = bincode::serde::encode_to_vec(&versioned_info, config).unwrap();
+    assert!(bytes.len() < serde_json::to_vec(&versioned_info).unwrap().len());
+
+    let (deserialized_info, n_read_bytes): (VersionedTransactionExecutionInfo, usize) =
+        bincode::serde::decode_from_slice(&bytes, config).unwrap();
+    assert_eq!(n_read_bytes, bytes.len());
+    assert_eq!(
+        TransactionExecutionInfo::from(deserialized_info),
+        reverted_call_trace_tx_execution_info()
+    );
+}
+
+fn ordered_event(order: usize, key: u8) -> OrderedEvent {
+    OrderedEvent {
+        order,
+        event: EventContent { keys: vec![EventKey(stark_felt!(key))], data: EventData(vec![]) },
+    }
+}
+
+fn ordered_message(order: usize, to_address: u8) -> OrderedL2ToL1Message {
+    OrderedL2ToL1Message {
+        order,
+        message: MessageToL1 {
+            to_address: EthAddress::try_from(stark_felt!(to_address)).unwrap(),
+            payload: L2ToL1Payload(vec![stark_felt!(to_address)]),
+        },
+    }
+}
+
+#[test]
+fn test_all_events_and_messages() {
+    let validate_call_info = call_info(
+        TEST_ACCOUNT_CONTRACT_ADDRESS,
+        "__validate__",
+        CallExecution { events: vec![ordered_event(0, 1)], ..Default::default() },
+        vec![],
+    );
+    // The top call emits events before and after its inner calls: the first emits an event and
+    // sends a message, and the second fails (its event is discarded).
+    let inner_call = call_info(
+        TEST_CONTRACT_ADDRESS_2,
+        "test_emit_event",
+        CallExecution {
+            events: vec![ordered_event(1, 3)],
+            l2_to_l1_messages: vec![ordered_message(0, 7)],
+            ..Default::default()
+        },
+        vec![],
+    );
+    let failed_inner_call = call_info(
+        TEST_CONTRACT_ADDRESS_2,
+        "fail",
+        CallExecution { events: vec![ordered_event(2, 4)], failed: true, ..Default::default() },
+        vec![],
+    );
+    let execute_call_info = call_info(
+        TEST_CONTRACT_ADDRESS,
+        "test_call_contract",
+        CallExecution {
+            events: vec![ordered_event(0, 2), ordered_event(3, 5)],
+            l2_to_l1_messages: vec![ordered_message(1, 8)],
+            ..Default::default()
+        },
+        vec![inner_call, failed_inner_call],
+    );
+    let fee_transfer_call_info = call_info(
+        TEST_ERC20_CONTRACT_ADDRESS,
+        "transfer",
+        CallExecution { events: vec![ordered_event(0, 6)], ..Default::default() },
+        vec![],
+    );
+    let tx_execution_info = TransactionExecutionInfo {
+        validate_call_info: Some(validate_call_info),
+        execute_call_info: Some(execute_call_info),
+        fee_transfer_call_info: Some(fee_transfer_call_info),
+        ..Default::default()
+    };
+
+    let event = |from_address: &str, key: u8| EventWithAddress {
+        from_address: contract_address!(from_address),
+        content: ordered_event(0, key).event,
+    };
+    assert_eq!(
+        tx_execution_info.all_events(),
+        vec![
+            event(TEST_ACCOUNT_CONTRACT_ADDRESS, 1),
+            event(TEST_CONTRACT_ADDRESS, 2),
+            event(TEST_CONTRACT_ADDRESS_2, 3),
+            event(TEST_CONTRACT_ADDRESS, 5),
+            // The fee transfer event is last.
+            event(TEST_ERC20_CONTRACT_ADDRESS, 6),
+        ]
+    );
+    assert_eq!(tx_execution_info.n_events(), 5);
+
+    let message = |from_address: &str, to_address: u8| MessageToL1WithAddress {
+        from_address: contract_address!(from_address),
+        message: ordered_message(0, to_address).message,
+    };
+    assert_eq!(
+        tx_execution_info.all_messages(),
+        vec![message(TEST_CONTRACT_ADDRESS_2, 7), message(TEST_CONTRACT_ADDRESS, 8)]
+    );
+    assert_eq!(tx_execution_info.n_messages(), 2);
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
@@ -10,19 +12,25 @@ use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_f
 
 use crate::abi::abi_utils::{get_storage_var_address, selector_from_name};
 use crate::block_context::BlockContext;
-use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::execution::contract_class::{
+    ContractClass, ContractClassV0, ContractClassV0Inner, ContractClassV1,
+};
+use crate::selector;
 use crate::state::cached_state::CachedState;
 use crate::test_utils::{
     invoke_tx, test_erc20_account_balance_key, test_erc20_faulty_account_balance_key,
     DictStateReader, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH, ACCOUNT_CONTRACT_CAIRO1_PATH,
-    BALANCE, ERC20_CONTRACT_PATH, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH,
-    TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS, TEST_CONTRACT_CAIRO0_PATH,
-    TEST_ERC20_CONTRACT_CLASS_HASH, TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS,
-    TEST_FAULTY_ACCOUNT_CONTRACT_CAIRO0_PATH, TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH,
+    BALANCE, ERC20_CONTRACT_PATH, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS,
+    TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
+    TEST_CONTRACT_CAIRO0_PATH, TEST_ERC20_CONTRACT_CLASS_HASH,
+    TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS, TEST_FAULTY_ACCOUNT_CONTRACT_CAIRO0_PATH,
+    TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH,
 };
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::constants;
+use crate::transaction::constants::TRANSFER_ENTRY_POINT_NAME;
 use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
+use crate::transaction::transaction_execution::Transaction;
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transactions::{
     DeclareTransaction, ExecutableTransaction, InvokeTransaction,
@@ -65,16 +73,22 @@ pub fn create_account_tx_test_state(
     // A random address that is unlikely to equal the result of the calculation of a contract
     // address.
     let test_account_address = contract_address!(account_address);
-    let test_erc20_address = block_context.fee_token_address;
+    let test_erc20_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let test_strk_erc20_address = block_context.fee_token_addresses.strk_fee_token_address;
     let address_to_class_hash = HashMap::from([
         (test_contract_address, test_contract_class_hash),
         (test_account_address, test_account_class_hash),
         (test_erc20_address, test_erc20_class_hash),
+        (test_strk_erc20_address, test_erc20_class_hash),
     ]);
     let minter_var_address = get_storage_var_address("permitted_minter", &[])
         .expect("Failed to get permitted_minter storage address.");
     let storage_view = HashMap::from([
         ((test_erc20_address, erc20_account_balance_key), stark_felt!(initial_account_balance)),
+        (
+            (test_strk_erc20_address, erc20_account_balance_key),
+            stark_felt!(initial_account_balance),
+        ),
         // Give the account mint permission.
         ((test_erc20_address, minter_var_address), *test_account_address.0.key()),
     ]);
@@ -164,7 +178,7 @@ pub fn create_account_tx_for_validate_test(
             AccountTransaction::DeployAccount(deploy_account_tx)
         }
         TransactionType::InvokeFunction => {
-            let entry_point_selector = selector_from_name("foo");
+            let entry_point_selector = selector!("foo");
             let execute_calldata = calldata![
                 stark_felt!(TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS), // Contract address.
                 entry_point_selector.0,                            // EP selector.
@@ -194,6 +208,29 @@ pub fn account_invoke_tx(
     )
 }
 
+/// Returns an invoke of the test contract, writing 99 at the given storage key through the given
+/// entry point (e.g., `test_storage_read_write`, or `write_and_revert`).
+pub fn storage_write_tx(
+    entry_point_name: &str,
+    key: u8,
+    account_address: ContractAddress,
+    nonce_manager: &mut NonceManager,
+) -> Transaction {
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS),     // Contract address.
+        selector_from_name(entry_point_name).0, // EP selector.
+        stark_felt!(2_u8),                      // Calldata length.
+        stark_felt!(key),                       // Calldata: address.
+        stark_felt!(99_u8)                      // Calldata: value.
+    ];
+    Transaction::AccountTransaction(account_invoke_tx(
+        execute_calldata,
+        account_address,
+        nonce_manager,
+        Fee(MAX_FEE),
+    ))
+}
+
 pub fn run_invoke_tx(
     execute_calldata: Calldata,
     state: &mut CachedState<DictStateReader>,
@@ -209,3 +246,18 @@ pub fn run_invoke_tx(
         true,
     )
 }
+
+/// Returns the ERC20 class, with its `transfer` entry point renamed to `transfer_legacy`.
+pub fn legacy_transfer_erc20_class() -> ContractClass {
+    let erc20_class = ContractClassV0::from_file(ERC20_CONTRACT_PATH);
+    let mut entry_points_by_type = erc20_class.entry_points_by_type.clone();
+    let external_entry_points = entry_points_by_type.get_mut(&EntryPointType::External).unwrap();
+    for entry_point in external_entry_points {
+        if entry_point.selector == selector_from_name(TRANSFER_ENTRY_POINT_NAME) {
+            entry_point.selector = selector!("transfer_legacy");
+        }
+    }
+
+    let erc20_class = ContractClassV0Inner::new(erc20_class.program.clone(), entry_points_by_type);
+    ContractClassV0(Arc::new(erc20_class)).into()
+}
@@ -0,0 +1,484 @@
+use std::collections::{HashMap, HashSet};
+
+use cairo_vm::vm::runners::builtin_runner::{
+    BITWISE_BUILTIN_NAME, EC_OP_BUILTIN_NAME, HASH_BUILTIN_NAME, KECCAK_BUILTIN_NAME,
+    POSEIDON_BUILTIN_NAME, RANGE_CHECK_BUILTIN_NAME, SEGMENT_ARENA_BUILTIN_NAME,
+    SIGNATURE_BUILTIN_NAME,
+};
+use serde::Serialize;
+use starknet_api::core::{
+    ClassHash, CompiledClassHash, ContractAddress, EntryPointSelector, Nonce,
+};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Calldata, EthAddress, EventKey};
+
+use crate::abi::constants;
+use crate::execution::entry_point::{CallInfo, CallType};
+use crate::state::cached_state::CommitmentStateDiff;
+use crate::transaction::errors::TransactionTraceError;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::transaction_types::TransactionType;
+
+#[cfg(test)]
+#[path = "trace_test.rs"]
+pub mod test;
+
+pub type TransactionTraceResult<T> = Result<T, TransactionTraceError>;
+
+/// The trace of a transaction, shaped as the `TRANSACTION_TRACE` of the Starknet JSON-RPC spec
+/// (v0.7), as returned by `starknet_traceTransaction` and `starknet_simulateTransactions`.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum TransactionTrace {
+    #[serde(rename = "DECLARE")]
+    Declare(DeclareTransactionTrace),
+    #[serde(rename = "DEPLOY_ACCOUNT")]
+    DeployAccount(DeployAccountTransactionTrace),
+    #[serde(rename = "INVOKE")]
+    Invoke(InvokeTransactionTrace),
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler(L1HandlerTransactionTrace),
+}
+
+impl TransactionTrace {
+    /// Returns the trace of a transaction of the given type, given its execution info and state
+    /// diff.
+    /// Classes declared by Cairo 0 (deprecated) declarations are not part of the state diff; the
+    /// caller is to add them to `StateDiff::deprecated_declared_classes`.
+    pub fn new(
+        tx_execution_info: &TransactionExecutionInfo,
+        tx_type: TransactionType,
+        state_diff: &CommitmentStateDiff,
+    ) -> TransactionTraceResult<Self> {
+        let validate_invocation = tx_execution_info
+            .validate_call_info
+            .as_ref()
+            .map(FunctionInvocation::try_from)
+            .transpose()?;
+        let fee_transfer_invocation = tx_execution_info
+            .fee_transfer_call_info
+            .as_ref()
+            .map(FunctionInvocation::try_from)
+            .transpose()?;
+        let state_diff = StateDiff::new(state_diff, &deployed_contracts(tx_execution_info));
+        let execution_resources = TransactionExecutionResources::from(tx_execution_info);
+
+        Ok(match tx_type {
+            TransactionType::Declare => Self::Declare(DeclareTransactionTrace {
+                validate_invocation,
+                fee_transfer_invocation,
+                state_diff,
+                execution_resources,
+            }),
+            TransactionType::DeployAccount => {
+                let constructor_call_info = tx_execution_info
+                    .execute_call_info
+                    .as_ref()
+                    .ok_or(TransactionTraceError::MissingConstructorCallInfo { tx_type })?;
+                Self::DeployAccount(DeployAccountTransactionTrace {
+                    validate_invocation,
+                    constructor_invocation: constructor_call_info.try_into()?,
+                    fee_transfer_invocation,
+                    state_diff,
+                    execution_resources,
+                })
+            }
+            TransactionType::InvokeFunction => {
+                let execute_invocation =
+                    match (&tx_execution_info.execute_call_info, &tx_execution_info.revert_error) {
+                        (_, Some(revert_error)) => {
+                            ExecuteInvocation::Reverted(RevertedInvocation {
+                                revert_reason: revert_error.clone(),
+                            })
+                        }
+                        (Some(execute_call_info), None) => {
+                            ExecuteInvocation::Succeeded(execute_call_info.try_into()?)
+                        }
+                        (None, None) => {
+                            return Err(TransactionTraceError::MissingExecuteCallInfo { tx_type });
+                        }
+                    };
+                Self::Invoke(InvokeTransactionTrace {
+                    validate_invocation,
+                    execute_invocation,
+                    fee_transfer_invocation,
+                    state_diff,
+                    execution_resources,
+                })
+            }
+            TransactionType::L1Handler => {
+                let function_call_info = tx_execution_info
+                    .execute_call_info
+                    .as_ref()
+                    .ok_or(TransactionTraceError::MissingExecuteCallInfo { tx_type })?;
+                Self::L1Handler(L1HandlerTransactionTrace {
+                    function_invocation: function_call_info.try_into()?,
+                    state_diff,
+                    execution_resources,
+                })
+            }
+        })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DeclareTransactionTrace {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    pub state_diff: StateDiff,
+    pub execution_resources: TransactionExecutionResources,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DeployAccountTransactionTrace {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation>,
+    pub constructor_invocation: FunctionInvocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    pub state_diff: StateDiff,
+    pub execution_resources: TransactionExecutionResources,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct InvokeTransactionTrace {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation>,
+    pub execute_invocation: ExecuteInvocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    pub state_diff: StateDiff,
+    pub execution_resources: TransactionExecutionResources,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct L1HandlerTransactionTrace {
+    pub function_invocation: FunctionInvocation,
+    pub state_diff: StateDiff,
+    pub execution_resources: TransactionExecutionResources,
+}
+
+/// The execution of an invoke transaction; its reason only, if reverted.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ExecuteInvocation {
+    Succeeded(FunctionInvocation),
+    Reverted(RevertedInvocation),
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct RevertedInvocation {
+    pub revert_reason: String,
+}
+
+/// A call, along with the calls it made.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct FunctionInvocation {
+    pub contract_address: ContractAddress,
+    pub entry_point_selector: EntryPointSelector,
+    pub calldata: Calldata,
+    pub caller_address: ContractAddress,
+    pub class_hash: ClassHash,
+    pub entry_point_type: EntryPointType,
+    pub call_type: InvocationCallType,
+    pub result: Vec<StarkFelt>,
+    pub calls: Vec<FunctionInvocation>,
+    pub events: Vec<InvocationEvent>,
+    pub messages: Vec<InvocationMessage>,
+    /// Including the resources of the inner calls.
+    pub execution_resources: ComputationResources,
+}
+
+impl TryFrom<&CallInfo> for FunctionInvocation {
+    type Error = TransactionTraceError;
+
+    fn try_from(call_info: &CallInfo) -> TransactionTraceResult<Self> {
+        let call = &call_info.call;
+        let execution = &call_info.execution;
+        let vm_resources = &call_info.vm_resources;
+
+        Ok(Self {
+            contract_address: call.storage_address,
+            entry_point_selector: call.entry_point_selector,
+            calldata: call.calldata.clone(),
+            caller_address: call.caller_address,
+            class_hash: call.class_hash.ok_or(TransactionTraceError::MissingClassHash {
+                contract_address: call.storage_address,
+            })?,
+            entry_point_type: call.entry_point_type,
+            call_type: call.call_type.into(),
+            result: execution.retdata.0.clone(),
+            calls: call_info
+                .inner_calls
+                .iter()
+                .map(Self::try_from)
+                .collect::<TransactionTraceResult<_>>()?,
+            events: execution
+                .events
+                .iter()
+                .map(|ordered_event| InvocationEvent {
+                    order: ordered_event.order,
+                    keys: ordered_event.event.keys.clone(),
+                    data: ordered_event.event.data.0.clone(),
+                })
+                .collect(),
+            messages: execution
+                .l2_to_l1_messages
+                .iter()
+                .map(|ordered_message| InvocationMessage {
+                    order: ordered_message.order,
+                    from_address: call.storage_address,
+                    to_address: ordered_message.message.to_address,
+                    payload: ordered_message.message.payload.0.clone(),
+                })
+                .collect(),
+            execution_resources: ComputationResources::new(
+                vm_resources.n_steps,
+                vm_resources.n_memory_holes,
+                &vm_resources.builtin_instance_counter,
+            ),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum InvocationCallType {
+    #[serde(rename = "CALL")]
+    Call,
+    #[serde(rename = "LIBRARY_CALL")]
+    LibraryCall,
+}
+
+impl From<CallType> for InvocationCallType {
+    fn from(call_type: CallType) -> Self {
+        match call_type {
+            CallType::Call => Self::Call,
+            // Library calls run the code of another class in the context of the caller.
+            CallType::Delegate => Self::LibraryCall,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct InvocationEvent {
+    pub order: usize,
+    pub keys: Vec<EventKey>,
+    pub data: Vec<StarkFelt>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct InvocationMessage {
+    pub order: usize,
+    pub from_address: ContractAddress,
+    pub to_address: EthAddress,
+    pub payload: Vec<StarkFelt>,
+}
+
+/// Cairo VM resources; unused builtins (and memory holes) are omitted.
+#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ComputationResources {
+    pub steps: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_holes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_check_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pedersen_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poseidon_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ec_op_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecdsa_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitwise_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keccak_builtin_applications: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment_arena_builtin: Option<usize>,
+}
+
+impl ComputationResources {
+    pub fn new(
+        n_steps: usize,
+        n_memory_holes: usize,
+        builtin_instance_counter: &HashMap<String, usize>,
+    ) -> Self {
+        let builtin_count = |builtin_name: &str| {
+            builtin_instance_counter.get(builtin_name).copied().filter(|&count| count > 0)
+        };
+
+        Self {
+            steps: n_steps,
+            memory_holes: Some(n_memory_holes).filter(|&n_memory_holes| n_memory_holes > 0),
+            range_check_builtin_applications: builtin_count(RANGE_CHECK_BUILTIN_NAME),
+            pedersen_builtin_applications: builtin_count(HASH_BUILTIN_NAME),
+            poseidon_builtin_applications: builtin_count(POSEIDON_BUILTIN_NAME),
+            ec_op_builtin_applications: builtin_count(EC_OP_BUILTIN_NAME),
+            ecdsa_builtin_applications: builtin_count(SIGNATURE_BUILTIN_NAME),
+            bitwise_builtin_applications: builtin_count(BITWISE_BUILTIN_NAME),
+            keccak_builtin_applications: builtin_count(KECCAK_BUILTIN_NAME),
+            segment_arena_builtin: builtin_count(SEGMENT_ARENA_BUILTIN_NAME),
+        }
+    }
+}
+
+/// The resources consumed by a transaction: its Cairo VM resources (including the OS overhead, and
+/// with memory holes counted as steps), and the gas consumed by data availability.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct TransactionExecutionResources {
+    #[serde(flatten)]
+    pub computation_resources: ComputationResources,
+    pub data_availability: DataAvailabilityResources,
+}
+
+impl From<&TransactionExecutionInfo> for TransactionExecutionResources {
+    fn from(tx_execution_info: &TransactionExecutionInfo) -> Self {
+        let vm_resources = &tx_execution_info.actual_resources.vm_resources.0;
+        let n_steps = vm_resources.get(constants::N_STEPS_RESOURCE).copied().unwrap_or_default();
+        let da_gas = tx_execution_info.receipt.da_gas;
+
+        Self {
+            computation_resources: ComputationResources::new(n_steps, 0, vm_resources),
+            data_availability: DataAvailabilityResources {
+                l1_gas: da_gas.l1_gas,
+                l1_data_gas: da_gas.l1_data_gas,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DataAvailabilityResources {
+    pub l1_gas: usize,
+    pub l1_data_gas: usize,
+}
+
+/// A state diff, shaped as the `STATE_DIFF` of the spec; entries are sorted (by address, then by
+/// key).
+#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+pub struct StateDiff {
+    pub storage_diffs: Vec<ContractStorageDiff>,
+    pub deprecated_declared_classes: Vec<ClassHash>,
+    pub declared_classes: Vec<DeclaredClass>,
+    pub deployed_contracts: Vec<DeployedContract>,
+    pub replaced_classes: Vec<ReplacedClass>,
+    pub nonces: Vec<ContractNonce>,
+}
+
+impl StateDiff {
+    /// Returns the given state diff, where the class hash updates of the given contracts are
+    /// deployments, and the others are class replacements.
+    pub fn new(
+        state_diff: &CommitmentStateDiff,
+        deployed_contracts: &HashSet<ContractAddress>,
+    ) -> Self {
+        let mut storage_diffs: Vec<_> = state_diff
+            .storage_updates
+            .iter()
+            .map(|(&address, storage_updates)| {
+                let mut storage_entries: Vec<_> = storage_updates
+                    .iter()
+                    .map(|(&key, &value)| StorageEntry { key, value })
+                    .collect();
+                storage_entries.sort_by_key(|storage_entry| storage_entry.key);
+                ContractStorageDiff { address, storage_entries }
+            })
+            .collect();
+        storage_diffs.sort_by_key(|storage_diff| storage_diff.address);
+
+        let mut declared_classes: Vec<_> = state_diff
+            .class_hash_to_compiled_class_hash
+            .iter()
+            .map(|(&class_hash, &compiled_class_hash)| DeclaredClass {
+                class_hash,
+                compiled_class_hash,
+            })
+            .collect();
+        declared_classes.sort_by_key(|declared_class| declared_class.class_hash);
+
+        let mut class_hash_updates: Vec<_> = state_diff.address_to_class_hash.iter().collect();
+        class_hash_updates.sort();
+        let (deployed_class_hash_updates, replaced_class_hash_updates): (Vec<_>, Vec<_>) =
+            class_hash_updates
+                .into_iter()
+                .partition(|(address, _)| deployed_contracts.contains(address));
+
+        let mut nonces: Vec<_> = state_diff
+            .address_to_nonce
+            .iter()
+            .map(|(&contract_address, &nonce)| ContractNonce { contract_address, nonce })
+            .collect();
+        nonces.sort_by_key(|contract_nonce| contract_nonce.contract_address);
+
+        Self {
+            storage_diffs,
+            deprecated_declared_classes: vec![],
+            declared_classes,
+            deployed_contracts: deployed_class_hash_updates
+                .into_iter()
+                .map(|(&address, &class_hash)| DeployedContract { address, class_hash })
+                .collect(),
+            replaced_classes: replaced_class_hash_updates
+                .into_iter()
+                .map(|(&contract_address, &class_hash)| ReplacedClass {
+                    contract_address,
+                    class_hash,
+                })
+                .collect(),
+            nonces,
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct ContractStorageDiff {
+    pub address: ContractAddress,
+    pub storage_entries: Vec<StorageEntry>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct StorageEntry {
+    pub key: StorageKey,
+    pub value: StarkFelt,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DeclaredClass {
+    pub class_hash: ClassHash,
+    pub compiled_class_hash: CompiledClassHash,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DeployedContract {
+    pub address: ContractAddress,
+    pub class_hash: ClassHash,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct ReplacedClass {
+    pub contract_address: ContractAddress,
+    pub class_hash: ClassHash,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct ContractNonce {
+    pub contract_address: ContractAddress,
+    pub nonce: Nonce,
+}
+
+/// Returns the addresses of the contracts deployed by the transaction; i.e., whose constructor it
+/// ran (every deployment runs one, even for classes without a constructor).
+fn deployed_contracts(tx_execution_info: &TransactionExecutionInfo) -> HashSet<ContractAddress> {
+    tx_execution_info
+        .non_optional_call_infos()
+        .into_iter()
+        .flat_map(|call_info| call_info.into_iter())
+        .filter(|call_info| call_info.call.entry_point_type == EntryPointType::Constructor)
+        .map(|call_info| call_info.call.storage_address)
+        .collect()
+}
@@ -0,0 +1,201 @@
+use std::fs;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    Calldata, DeclareTransactionV2, Fee, TransactionHash, TransactionVersion,
+};
+use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
+
+use crate::abi::abi_utils::get_erc20_balance_var_addresses;
+use crate::block_context::BlockContext;
+use crate::blockifier::bouncer::BouncerConfig;
+use crate::blockifier::transaction_executor::TransactionExecutor;
+use crate::execution::contract_class::{ContractClass, ContractClassV1};
+use crate::execution::entry_point::{CallEntryPoint, CallInfo};
+use crate::selector;
+use crate::state::cached_state::CachedState;
+use crate::state::state_api::State;
+use crate::test_utils::{
+    create_test_state, deploy_account_tx, DictStateReader, NonceManager, BALANCE, MAX_FEE,
+    TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH,
+    TEST_CONTRACT_ADDRESS, TEST_EMPTY_CONTRACT_CAIRO1_PATH, TEST_EMPTY_CONTRACT_CLASS_HASH,
+};
+use crate::transaction::account_transaction::AccountTransaction;
+use crate::transaction::errors::TransactionTraceError;
+use crate::transaction::objects::TransactionExecutionInfo;
+use crate::transaction::test_utils::{
+    create_state_with_trivial_validation_account, storage_write_tx,
+};
+use crate::transaction::trace::TransactionTrace;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transaction_types::TransactionType;
+use crate::transaction::transactions::{DeclareTransaction, L1HandlerTransaction};
+
+const TRACES_DIR: &str = "./resources/testing/traces";
+
+fn declare_tx() -> Transaction {
+    let declare_tx = DeclareTransactionV2 {
+        max_fee: Fee(MAX_FEE),
+        class_hash: class_hash!(TEST_EMPTY_CONTRACT_CLASS_HASH),
+        sender_address: contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        ..Default::default()
+    };
+    let contract_class =
+        ContractClass::V1(ContractClassV1::from_file(TEST_EMPTY_CONTRACT_CAIRO1_PATH));
+    Transaction::AccountTransaction(AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V2(declare_tx),
+            TransactionHash::default(),
+            contract_class,
+        )
+        .unwrap(),
+    ))
+}
+
+fn deploy_account_tx_for_trace() -> Transaction {
+    Transaction::AccountTransaction(AccountTransaction::DeployAccount(deploy_account_tx(
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        Fee(MAX_FEE),
+        None,
+        None,
+        &mut NonceManager::default(),
+    )))
+}
+
+fn l1_handler_tx() -> Transaction {
+    Transaction::L1HandlerTransaction(L1HandlerTransaction {
+        tx: starknet_api::transaction::L1HandlerTransaction {
+            version: TransactionVersion::default(),
+            nonce: Nonce(stark_felt!(3_u8)),
+            contract_address: contract_address!(TEST_CONTRACT_ADDRESS),
+            entry_point_selector: selector!("l1_handle"),
+            calldata: calldata![stark_felt!(0x123_u16), stark_felt!(7_u8)],
+        },
+        tx_hash: TransactionHash::default(),
+        paid_fee_on_l1: Fee(1),
+    })
+}
+
+/// A state where the account deployed by `deploy_account_tx_for_trace` is funded.
+fn create_state_with_funded_deployed_account() -> CachedState<DictStateReader> {
+    let Transaction::AccountTransaction(AccountTransaction::DeployAccount(deploy_account_tx)) =
+        deploy_account_tx_for_trace()
+    else {
+        unreachable!();
+    };
+    let (balance_key, _) =
+        get_erc20_balance_var_addresses(&deploy_account_tx.contract_address).unwrap();
+    let fee_token_address =
+        BlockContext::create_for_account_testing().fee_token_addresses.eth_fee_token_address;
+    let mut state = create_state_with_trivial_validation_account();
+    state.set_storage_at(fee_token_address, balance_key, stark_felt!(BALANCE));
+    state
+}
+
+#[rstest]
+#[case::invoke(
+    create_state_with_trivial_validation_account(),
+    storage_write_tx(
+        "test_storage_read_write",
+        1,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default()
+    ),
+    TransactionType::InvokeFunction,
+    "invoke"
+)]
+#[case::reverted_invoke(
+    create_state_with_trivial_validation_account(),
+    storage_write_tx(
+        "write_and_revert",
+        1,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default()
+    ),
+    TransactionType::InvokeFunction,
+    "reverted_invoke"
+)]
+#[case::declare(
+    create_state_with_trivial_validation_account(),
+    declare_tx(),
+    TransactionType::Declare,
+    "declare"
+)]
+#[case::deploy_account(
+    create_state_with_funded_deployed_account(),
+    deploy_account_tx_for_trace(),
+    TransactionType::DeployAccount,
+    "deploy_account"
+)]
+#[case::l1_handler(create_test_state(), l1_handler_tx(), TransactionType::L1Handler, "l1_handler")]
+fn test_transaction_trace(
+    #[case] state: CachedState<DictStateReader>,
+    #[case] tx: Transaction,
+    #[case] tx_type: TransactionType,
+    #[case] trace_name: &str,
+) {
+    let mut executor = TransactionExecutor::new(
+        state,
+        BlockContext::create_for_account_testing(),
+        BouncerConfig::max(),
+        true,
+    )
+    .unwrap();
+    let (tx_execution_info, tx_state_diff) = executor.execute(tx, true).unwrap();
+    assert_eq!(tx_execution_info.is_reverted(), trace_name == "reverted_invoke");
+
+    let trace = TransactionTrace::new(&tx_execution_info, tx_type, &tx_state_diff).unwrap();
+    let trace_path = format!("{TRACES_DIR}/{trace_name}.json");
+    let expected_trace: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(trace_path).unwrap()).unwrap();
+    assert_eq!(serde_json::to_value(&trace).unwrap(), expected_trace);
+}
+
+#[test]
+fn test_missing_call_info() {
+    let tx_execution_info = TransactionExecutionInfo::default();
+    let state_diff = CachedState::from(DictStateReader::default()).to_state_diff();
+
+    assert_matches!(
+        TransactionTrace::new(&tx_execution_info, TransactionType::DeployAccount, &state_diff),
+        Err(TransactionTraceError::MissingConstructorCallInfo {
+            tx_type: TransactionType::DeployAccount
+        })
+    );
+    assert_matches!(
+        TransactionTrace::new(&tx_execution_info, TransactionType::InvokeFunction, &state_diff),
+        Err(TransactionTraceError::MissingExecuteCallInfo {
+            tx_type: TransactionType::InvokeFunction
+        })
+    );
+}
+
+#[test]
+fn test_missing_class_hash() {
+    let class_hash = Some(class_hash!(TEST_CLASS_HASH));
+    let call_info = |storage_address, class_hash, inner_calls| CallInfo {
+        call: CallEntryPoint { storage_address, class_hash, ..Default::default() },
+        inner_calls,
+        ..Default::default()
+    };
+    // The class hash is set on execution; it is missing from an inner call of a built call info.
+    let tx_execution_info = TransactionExecutionInfo {
+        execute_call_info: Some(call_info(
+            contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            class_hash,
+            vec![call_info(contract_address!(TEST_CONTRACT_ADDRESS), None, vec![])],
+        )),
+        ..Default::default()
+    };
+    let state_diff = CachedState::from(DictStateReader::default()).to_state_diff();
+
+    assert_matches!(
+        TransactionTrace::new(&tx_execution_info, TransactionType::InvokeFunction, &state_diff),
+        Err(TransactionTraceError::MissingClassHash { contract_address })
+            if contract_address == contract_address!(TEST_CONTRACT_ADDRESS)
+    );
+}
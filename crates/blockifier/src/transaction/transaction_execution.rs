@@ -7,22 +7,27 @@ use crate::abi::constants as abi_constants;
 use crate::block_context::BlockContext;
 use crate::execution::contract_class::ContractClass;
 use crate::execution::entry_point::{EntryPointExecutionContext, ExecutionResources};
-use crate::fee::fee_utils::calculate_tx_fee;
+use crate::fee::fee_utils::get_fee_by_gas_vector;
 use crate::state::cached_state::{StateChangesCount, TransactionalState};
 use crate::state::state_api::StateReader;
 use crate::transaction::account_transaction::AccountTransaction;
-use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::errors::TransactionFeeError;
 use crate::transaction::objects::{
     AccountTransactionContext, TransactionExecutionInfo, TransactionExecutionResult,
+    TransactionReceipt,
 };
 use crate::transaction::transaction_types::TransactionType;
-use crate::transaction::transaction_utils::{calculate_l1_gas_usage, calculate_tx_resources};
+use crate::transaction::transaction_utils::calculate_tx_resources;
 use crate::transaction::transactions::{
     DeclareTransaction, DeployAccountTransaction, Executable, ExecutableTransaction,
     InvokeTransaction, L1HandlerTransaction,
 };
 
-#[derive(Debug)]
+#[cfg(test)]
+#[path = "transaction_execution_test.rs"]
+mod test;
+
+#[derive(Clone, Debug)]
 pub enum Transaction {
     AccountTransaction(AccountTransaction),
     L1HandlerTransaction(L1HandlerTransaction),
@@ -33,6 +38,13 @@ impl Transaction {
     pub fn initial_gas() -> u64 {
         abi_constants::INITIAL_GAS_COST - abi_constants::TRANSACTION_GAS_COST
     }
+
+    pub fn tx_hash(&self) -> TransactionHash {
+        match self {
+            Self::AccountTransaction(account_tx) => account_tx.tx_hash(),
+            Self::L1HandlerTransaction(l1_handler) => l1_handler.tx_hash,
+        }
+    }
 }
 
 impl Transaction {
@@ -94,6 +106,7 @@ impl<S: StateReader> ExecutableTransaction<S> for L1HandlerTransaction {
         _validate: bool,
     ) -> TransactionExecutionResult<TransactionExecutionInfo> {
         let tx = &self.tx;
+        let l1_handler_payload_size = Some(self.payload_size()?);
         let tx_context = AccountTransactionContext {
             transaction_hash: self.tx_hash,
             max_fee: Fee::default(),
@@ -103,43 +116,57 @@ impl<S: StateReader> ExecutableTransaction<S> for L1HandlerTransaction {
             sender_address: tx.contract_address,
         };
         let mut resources = ExecutionResources::default();
-        let mut context = EntryPointExecutionContext::new_invoke(block_context, &tx_context);
+        // The L1 nonce is exposed to the contract only; there is no account nonce to handle.
+        let mut context = EntryPointExecutionContext::new_l1_handler(block_context, &tx_context)?;
         let mut remaining_gas = Transaction::initial_gas();
         let execute_call_info =
             self.run_execute(state, &mut resources, &mut context, &mut remaining_gas)?;
 
         let call_infos =
             if let Some(call_info) = execute_call_info.as_ref() { vec![call_info] } else { vec![] };
-        // The calldata includes the "from" field, which is not a part of the payload.
-        let l1_handler_payload_size = Some(tx.calldata.0.len() - 1);
-        let state_changes =
-            state.get_actual_state_changes_for_fee_charge(block_context.fee_token_address, None)?;
-        let l1_gas_usage = calculate_l1_gas_usage(
+        let fee_type = tx_context.fee_type();
+        let state_changes = state.get_actual_state_changes_for_fee_charge(
+            block_context.fee_token_address(&fee_type),
+            None,
+        )?;
+        let tx_resources = calculate_tx_resources(
             &call_infos,
+            resources,
+            TransactionType::L1Handler,
+            tx.calldata.0.len(),
+            0,
             StateChangesCount::from(&state_changes),
             l1_handler_payload_size,
+            block_context,
         )?;
-        let actual_resources =
-            calculate_tx_resources(resources, l1_gas_usage, TransactionType::L1Handler)?;
-        let actual_fee = calculate_tx_fee(&actual_resources, &context.block_context)?;
+        let gas_vector = tx_resources.to_gas_vector(block_context)?;
+        let actual_fee = get_fee_by_gas_vector(block_context, gas_vector, &fee_type)?;
         let paid_fee = self.paid_fee_on_l1;
         // For now, assert only that any amount of fee was paid.
         // The error message still indicates the required fee.
         if paid_fee == Fee(0) {
-            return Err(TransactionExecutionError::InsufficientL1Fee { paid_fee, actual_fee });
+            return Err(TransactionFeeError::InsufficientL1Fee { paid_fee, actual_fee }.into());
         }
 
         Ok(TransactionExecutionInfo {
             validate_call_info: None,
             execute_call_info,
             fee_transfer_call_info: None,
-            actual_fee: Fee::default(),
-            actual_resources,
+            // Paid for on L1.
+            receipt: TransactionReceipt {
+                fee: Fee::default(),
+                collected_fee: Fee::default(),
+                gas: gas_vector,
+                da_gas: tx_resources.da_gas_vector(block_context),
+            },
+            actual_resources: tx_resources,
             revert_error: None,
         })
     }
 }
 
+/// Account transactions are charged (and validated) according to the given flags; L1 handler
+/// transactions are paid for on L1, and are never charged nor validated.
 impl<S: StateReader> ExecutableTransaction<S> for Transaction {
     fn execute_raw(
         self,
@@ -0,0 +1,85 @@
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, Nonce, PatriciaKey};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{
+    Calldata, Fee, InvokeTransaction, L1HandlerTransaction, Transaction as StarknetApiTransaction,
+    TransactionHash, TransactionVersion,
+};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+
+use crate::block_context::BlockContext;
+use crate::execution::entry_point::Retdata;
+use crate::retdata;
+use crate::selector;
+use crate::test_utils::{
+    create_test_state, invoke_tx, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS,
+};
+use crate::transaction::test_utils::create_state_with_trivial_validation_account;
+use crate::transaction::transaction_execution::Transaction;
+use crate::transaction::transactions::ExecutableTransaction;
+
+#[rstest]
+fn test_execute_account_transaction(#[values(true, false)] charge_fee: bool) {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
+        selector!("return_result").0,       // EP selector.
+        stark_felt!(1_u8),                  // Calldata length.
+        stark_felt!(2_u8)                   // Calldata: num.
+    ];
+    let tx = invoke_tx(
+        execute_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        Fee(MAX_FEE),
+        None,
+    );
+    let tx = Transaction::from_api(
+        StarknetApiTransaction::Invoke(InvokeTransaction::V1(tx)),
+        TransactionHash::default(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let tx_execution_info = tx.execute(state, block_context, charge_fee, true).unwrap();
+    assert!(!tx_execution_info.is_reverted());
+    assert!(tx_execution_info.validate_call_info.is_some());
+    // The actual fee is computed either way, but only transferred if charged.
+    assert!(tx_execution_info.receipt.fee > Fee(0));
+    assert_eq!(tx_execution_info.fee_transfer_call_info.is_some(), charge_fee);
+}
+
+#[rstest]
+fn test_execute_l1_handler_transaction(#[values(true, false)] charge_fee: bool) {
+    let state = &mut create_test_state();
+    let block_context = &BlockContext::create_for_account_testing();
+    let arg = stark_felt!(0x7_u8);
+    let tx = L1HandlerTransaction {
+        version: TransactionVersion::default(),
+        nonce: Nonce::default(),
+        contract_address: contract_address!(TEST_CONTRACT_ADDRESS),
+        entry_point_selector: selector!("l1_handle"),
+        calldata: calldata![stark_felt!(0x123_u16), arg],
+    };
+    let tx = Transaction::from_api(
+        StarknetApiTransaction::L1Handler(tx),
+        TransactionHash::default(),
+        None,
+        Some(Fee(1)),
+        None,
+    )
+    .unwrap();
+
+    // Paid for on L1; never charged, regardless of the flag.
+    let tx_execution_info = tx.execute(state, block_context, charge_fee, true).unwrap();
+    let execute_call_info = tx_execution_info.execute_call_info.unwrap();
+    assert_eq!(execute_call_info.call.entry_point_type, EntryPointType::L1Handler);
+    assert_eq!(execute_call_info.execution.retdata, retdata![arg]);
+    assert_eq!(tx_execution_info.validate_call_info, None);
+    assert_eq!(tx_execution_info.fee_transfer_call_info, None);
+    assert_eq!(tx_execution_info.receipt.fee, Fee(0));
+}
@@ -3,17 +3,18 @@ use std::collections::HashMap;
 use cairo_vm::vm::runners::builtin_runner::SEGMENT_ARENA_BUILTIN_NAME;
 
 use crate::abi::constants;
+use crate::block_context::BlockContext;
 use crate::execution::entry_point::{CallInfo, ExecutionResources};
-use crate::fee::gas_usage::calculate_tx_gas_usage;
 use crate::fee::os_usage::get_additional_os_resources;
+use crate::fee::transaction_resources::TransactionResources;
 use crate::state::cached_state::StateChangesCount;
-use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::errors::{RestrictedEntryPointKind, TransactionExecutionError};
 use crate::transaction::objects::{ResourcesMapping, TransactionExecutionResult};
 use crate::transaction::transaction_types::TransactionType;
 
 pub fn verify_no_calls_to_other_contracts(
     call_info: &CallInfo,
-    entry_point_kind: String,
+    entry_point_kind: RestrictedEntryPointKind,
 ) -> TransactionExecutionResult<()> {
     let invoked_contract_address = call_info.call.storage_address;
     if call_info
@@ -26,36 +27,69 @@ pub fn verify_no_calls_to_other_contracts(
     Ok(())
 }
 
-pub fn calculate_l1_gas_usage(
+/// Calculates the total resources needed to include the transaction in a StarkNet block as
+/// most-recent (recent w.r.t. application on the given state); i.e., Cairo VM execution resources
+/// (including the OS overhead), the consumed L2 gas, the calldata and signature, and the messages,
+/// events and state changes of the transaction.
+/// Calls that failed are not part of the given call trees, and their events are not charged.
+#[allow(clippy::too_many_arguments)]
+pub fn calculate_tx_resources(
     call_infos: &[&CallInfo],
+    execution_resources: ExecutionResources,
+    tx_type: TransactionType,
+    calldata_length: usize,
+    signature_length: usize,
     state_changes_count: StateChangesCount,
     l1_handler_payload_size: Option<usize>,
-) -> TransactionExecutionResult<usize> {
+    block_context: &BlockContext,
+) -> TransactionExecutionResult<TransactionResources> {
     let mut l2_to_l1_payloads_length = vec![];
+    let (mut n_event_keys, mut n_event_data_felts, mut l2_gas) = (0, 0, 0);
     for call_info in call_infos {
         l2_to_l1_payloads_length.extend(call_info.get_sorted_l2_to_l1_payloads_length()?);
+        l2_gas += call_info.l2_gas_consumed() as usize;
+        for call in call_info.into_iter() {
+            for ordered_event in &call.execution.events {
+                n_event_keys += ordered_event.event.keys.len();
+                n_event_data_felts += ordered_event.event.data.0.len();
+            }
+        }
     }
 
-    let l1_gas_usage = calculate_tx_gas_usage(
-        &l2_to_l1_payloads_length,
+    Ok(TransactionResources {
+        vm_resources: calculate_tx_vm_resources(
+            execution_resources,
+            tx_type,
+            calldata_length,
+            block_context,
+        )?,
+        calldata_length,
+        signature_length,
         state_changes_count,
+        l2_to_l1_payloads_length,
         l1_handler_payload_size,
-    );
-
-    Ok(l1_gas_usage)
+        n_event_keys,
+        n_event_data_felts,
+        l2_gas,
+    })
 }
 
-/// Calculates the total resources needed to include the transaction in a StarkNet block as
-/// most-recent (recent w.r.t. application on the given state).
-/// I.e., Cairo VM execution resources.
-pub fn calculate_tx_resources(
+/// Calculates the Cairo VM resources needed to run the transaction, including the OS overhead.
+pub fn calculate_tx_vm_resources(
     execution_resources: ExecutionResources,
-    l1_gas_usage: usize,
     tx_type: TransactionType,
+    calldata_length: usize,
+    block_context: &BlockContext,
 ) -> TransactionExecutionResult<ResourcesMapping> {
     // Add additional Cairo resources needed for the OS to run the transaction.
     let total_vm_usage = &execution_resources.vm_resources
-        + &get_additional_os_resources(execution_resources.syscall_counter, tx_type)?;
+        + &get_additional_os_resources(
+            &block_context.versioned_constants.os_resources,
+            &execution_resources.syscall_counter,
+            tx_type,
+            calldata_length,
+            block_context.use_kzg_da,
+        )?;
     let mut total_vm_usage = total_vm_usage.filter_unused_builtins();
     // The segment arena" builtin is not part of SHARP (not in any proof layout).
     // Each instance requires approximately 10 steps in the OS.
@@ -66,13 +100,13 @@ pub fn calculate_tx_resources(
             .remove(SEGMENT_ARENA_BUILTIN_NAME)
             .unwrap_or_default();
 
-    let mut tx_resources = HashMap::from([
-        (constants::GAS_USAGE.to_string(), l1_gas_usage),
-        (constants::N_STEPS_RESOURCE.to_string(), n_steps + total_vm_usage.n_memory_holes),
-    ]);
-    tx_resources.extend(total_vm_usage.builtin_instance_counter);
+    let mut vm_resources = HashMap::from([(
+        constants::N_STEPS_RESOURCE.to_string(),
+        n_steps + total_vm_usage.n_memory_holes,
+    )]);
+    vm_resources.extend(total_vm_usage.builtin_instance_counter);
 
-    Ok(ResourcesMapping(tx_resources))
+    Ok(ResourcesMapping(vm_resources))
 }
 
 pub fn update_remaining_gas(remaining_gas: &mut u64, call_info: &CallInfo) {
@@ -1,24 +1,24 @@
 use std::sync::Arc;
 
-use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::transaction::{
     Calldata, ContractAddressSalt, Fee, TransactionHash, TransactionSignature, TransactionVersion,
 };
 
-use crate::abi::abi_utils::selector_from_name;
 use crate::block_context::BlockContext;
-use crate::execution::contract_class::ContractClass;
+use crate::execution::contract_class::{ClassInfo, ContractClass};
 use crate::execution::entry_point::{
     CallEntryPoint, CallInfo, CallType, ConstructorContext, EntryPointExecutionContext,
     ExecutionResources,
 };
 use crate::execution::execution_utils::execute_deployment;
+use crate::selector;
 use crate::state::cached_state::{CachedState, TransactionalState};
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader};
-use crate::transaction::constants;
-use crate::transaction::errors::TransactionExecutionError;
+use crate::transaction::constants::EXECUTE_ENTRY_POINT_NAME;
+use crate::transaction::errors::{RestrictedEntryPointKind, TransactionExecutionError};
 use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
 use crate::transaction::transaction_utils::{
     update_remaining_gas, verify_no_calls_to_other_contracts,
@@ -94,11 +94,13 @@ pub trait Executable<S: State> {
     ) -> TransactionExecutionResult<Option<CallInfo>>;
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DeclareTransaction {
     tx: starknet_api::transaction::DeclareTransaction,
     tx_hash: TransactionHash,
-    contract_class: ContractClass,
+    // If not given, the class is read from the state upon its first invocation.
+    contract_class: Option<ContractClass>,
+    class_info: ClassInfo,
 }
 
 impl DeclareTransaction {
@@ -107,48 +109,39 @@ impl DeclareTransaction {
         tx_hash: TransactionHash,
         contract_class: ContractClass,
     ) -> TransactionExecutionResult<Self> {
-        let declare_version = declare_tx.version();
-        match declare_tx {
-            starknet_api::transaction::DeclareTransaction::V0(tx) => {
-                let ContractClass::V0(contract_class) = contract_class else {
-                    return Err(TransactionExecutionError::ContractClassVersionMismatch {
-                        declare_version,
-                        cairo_version: 0,
-                    });
-                };
-                Ok(Self {
-                    tx: starknet_api::transaction::DeclareTransaction::V0(tx),
-                    tx_hash,
-                    contract_class: contract_class.into(),
-                })
-            }
-            starknet_api::transaction::DeclareTransaction::V1(tx) => {
-                let ContractClass::V0(contract_class) = contract_class else {
-                    return Err(TransactionExecutionError::ContractClassVersionMismatch {
-                        declare_version,
-                        cairo_version: 0,
-                    });
-                };
-                Ok(Self {
-                    tx: starknet_api::transaction::DeclareTransaction::V1(tx),
-                    tx_hash,
-                    contract_class: contract_class.into(),
-                })
-            }
-            starknet_api::transaction::DeclareTransaction::V2(tx) => {
-                let ContractClass::V1(contract_class) = contract_class else {
-                    return Err(TransactionExecutionError::ContractClassVersionMismatch {
-                        declare_version,
-                        cairo_version: 1,
-                    });
-                };
-                Ok(Self {
-                    tx: starknet_api::transaction::DeclareTransaction::V2(tx),
-                    tx_hash,
-                    contract_class: contract_class.into(),
-                })
-            }
+        let class_info = ClassInfo::from(&contract_class);
+        Self::create(declare_tx, tx_hash, Some(contract_class), class_info)
+    }
+
+    /// Creates a declaration of a class that is not at hand; its compiled class is not set in the
+    /// state, and must be supplied by the state reader when the class is invoked.
+    pub fn new_with_class_info(
+        declare_tx: starknet_api::transaction::DeclareTransaction,
+        tx_hash: TransactionHash,
+        class_info: ClassInfo,
+    ) -> TransactionExecutionResult<Self> {
+        Self::create(declare_tx, tx_hash, None, class_info)
+    }
+
+    fn create(
+        declare_tx: starknet_api::transaction::DeclareTransaction,
+        tx_hash: TransactionHash,
+        contract_class: Option<ContractClass>,
+        class_info: ClassInfo,
+    ) -> TransactionExecutionResult<Self> {
+        let cairo_version = match declare_tx {
+            starknet_api::transaction::DeclareTransaction::V0(_)
+            | starknet_api::transaction::DeclareTransaction::V1(_) => 0,
+            starknet_api::transaction::DeclareTransaction::V2(_) => 1,
+        };
+        if class_info.cairo_version != cairo_version {
+            return Err(TransactionExecutionError::ContractClassVersionMismatch {
+                declare_version: declare_tx.version(),
+                cairo_version,
+            });
         }
+
+        Ok(Self { tx: declare_tx, tx_hash, contract_class, class_info })
     }
 
     pub fn tx(&self) -> &starknet_api::transaction::DeclareTransaction {
@@ -159,10 +152,14 @@ impl DeclareTransaction {
         self.tx_hash
     }
 
-    pub fn contract_class(&self) -> ContractClass {
+    pub fn contract_class(&self) -> Option<ContractClass> {
         self.contract_class.clone()
     }
 
+    pub fn class_info(&self) -> ClassInfo {
+        self.class_info
+    }
+
     implement_inner_tx_getter_calls!((class_hash, ClassHash), (max_fee, Fee));
 }
 
@@ -171,7 +168,7 @@ impl<S: State> Executable<S> for DeclareTransaction {
         &self,
         state: &mut S,
         _resources: &mut ExecutionResources,
-        _context: &mut EntryPointExecutionContext,
+        context: &mut EntryPointExecutionContext,
         _remaining_gas: &mut u64,
     ) -> TransactionExecutionResult<Option<CallInfo>> {
         let class_hash = self.class_hash();
@@ -180,29 +177,58 @@ impl<S: State> Executable<S> for DeclareTransaction {
             // No class commitment, so no need to check if the class is already declared.
             starknet_api::transaction::DeclareTransaction::V0(_)
             | starknet_api::transaction::DeclareTransaction::V1(_) => {
-                state.set_contract_class(&class_hash, self.contract_class.clone())?;
+                let size = self.class_info.bytecode_length;
+                let max = context.block_context.versioned_constants.declare_tx_max_bytecode_size;
+                if size > max {
+                    return Err(TransactionExecutionError::ContractBytecodeSizeTooLarge {
+                        size,
+                        max,
+                    });
+                }
+                self.set_contract_class(state)?;
                 Ok(None)
             }
             starknet_api::transaction::DeclareTransaction::V2(tx) => {
-                match state.get_compiled_contract_class(&class_hash) {
-                    Err(StateError::UndeclaredClassHash(_)) => {
-                        // Class is undeclared; declare it.
-                        state.set_contract_class(&class_hash, self.contract_class.clone())?;
-                        state.set_compiled_class_hash(class_hash, tx.compiled_class_hash)?;
-                        Ok(None)
-                    }
-                    Err(error) => Err(error).map_err(TransactionExecutionError::from),
-                    Ok(_) => {
-                        // Class is already declared, cannot redeclare
-                        // (i.e., make sure the leaf is uninitialized).
-                        Err(TransactionExecutionError::DeclareTransactionError { class_hash })
-                    }
+                if self.is_declared(state)? {
+                    // Class is already declared, cannot redeclare
+                    // (i.e., make sure the leaf is uninitialized).
+                    return Err(TransactionExecutionError::DeclareTransactionError { class_hash });
                 }
+
+                // Class is undeclared; declare it.
+                self.set_contract_class(state)?;
+                state.set_compiled_class_hash(class_hash, tx.compiled_class_hash)?;
+                Ok(None)
             }
         }
     }
 }
 
+impl DeclareTransaction {
+    fn is_declared(&self, state: &mut dyn State) -> TransactionExecutionResult<bool> {
+        let class_hash = self.class_hash();
+        if self.contract_class.is_none() {
+            // Reading the compiled class would fetch it.
+            let compiled_class_hash = state.get_compiled_class_hash(class_hash)?;
+            return Ok(compiled_class_hash != CompiledClassHash::default());
+        }
+
+        match state.get_compiled_contract_class(&class_hash) {
+            Ok(_) => Ok(true),
+            Err(StateError::UndeclaredClassHash(_)) => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn set_contract_class(&self, state: &mut dyn State) -> TransactionExecutionResult<()> {
+        if let Some(contract_class) = &self.contract_class {
+            state.set_contract_class(&self.class_hash(), contract_class.clone())?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DeployAccountTransaction {
     pub tx: starknet_api::transaction::DeployAccountTransaction,
@@ -247,7 +273,10 @@ impl<S: State> Executable<S> for DeployAccountTransaction {
         let call_info = deployment_result
             .map_err(TransactionExecutionError::ContractConstructorExecutionFailed)?;
         update_remaining_gas(remaining_gas, &call_info);
-        verify_no_calls_to_other_contracts(&call_info, String::from("an account constructor"))?;
+        verify_no_calls_to_other_contracts(
+            &call_info,
+            RestrictedEntryPointKind::AccountConstructor,
+        )?;
 
         Ok(Some(call_info))
     }
@@ -278,7 +307,7 @@ impl<S: State> Executable<S> for InvokeTransaction {
         let entry_point_selector = match &self.tx {
             starknet_api::transaction::InvokeTransaction::V0(tx) => tx.entry_point_selector,
             starknet_api::transaction::InvokeTransaction::V1(_) => {
-                selector_from_name(constants::EXECUTE_ENTRY_POINT_NAME)
+                selector!(EXECUTE_ENTRY_POINT_NAME)
             }
         };
         let storage_address = context.account_tx_context.sender_address;
@@ -303,13 +332,26 @@ impl<S: State> Executable<S> for InvokeTransaction {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct L1HandlerTransaction {
     pub tx: starknet_api::transaction::L1HandlerTransaction,
     pub tx_hash: TransactionHash,
     pub paid_fee_on_l1: Fee,
 }
 
+impl L1HandlerTransaction {
+    /// Returns the size of the message payload: the calldata, excluding its first element, the L1
+    /// sender address (`from_address`).
+    pub fn payload_size(&self) -> TransactionExecutionResult<usize> {
+        self.tx
+            .calldata
+            .0
+            .len()
+            .checked_sub(1)
+            .ok_or(TransactionExecutionError::MissingL1HandlerSenderAddress)
+    }
+}
+
 impl<S: State> Executable<S> for L1HandlerTransaction {
     fn run_execute(
         &self,
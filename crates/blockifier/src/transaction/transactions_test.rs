@@ -6,13 +6,13 @@ use cairo_vm::vm::runners::builtin_runner::{HASH_BUILTIN_NAME, RANGE_CHECK_BUILT
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
 use itertools::concat;
 use pretty_assertions::assert_eq;
-use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
 use starknet_api::deprecated_contract_class::EntryPointType;
 use starknet_api::hash::{StarkFelt, StarkHash};
 use starknet_api::state::StorageKey;
 use starknet_api::transaction::{
     Calldata, DeclareTransactionV0V1, DeclareTransactionV2, EventContent, EventData, EventKey, Fee,
-    InvokeTransactionV1, TransactionHash, TransactionSignature,
+    InvokeTransactionV1, TransactionHash, TransactionSignature, TransactionVersion,
 };
 use starknet_api::{calldata, class_hash, contract_address, patricia_key, stark_felt};
 use test_case::test_case;
@@ -22,38 +22,54 @@ use crate::abi::abi_utils::{
 };
 use crate::abi::constants as abi_constants;
 use crate::block_context::BlockContext;
-use crate::execution::contract_class::{ContractClass, ContractClassV0, ContractClassV1};
+use crate::blockifier::bouncer::BouncerWeights;
+use crate::execution::contract_class::{
+    ClassInfo, ContractClass, ContractClassV0, ContractClassV1,
+};
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
 use crate::execution::entry_point::{
     CallEntryPoint, CallExecution, CallInfo, CallType, OrderedEvent, Retdata,
 };
 use crate::execution::errors::EntryPointExecutionError;
-use crate::fee::fee_utils::calculate_tx_fee;
-use crate::fee::gas_usage::{calculate_tx_gas_usage, estimate_minimal_fee};
+use crate::fee::fee_utils::get_fee_by_gas_vector;
+use crate::fee::gas_usage::{
+    calculate_tx_gas_usage, estimate_minimal_fee, get_events_gas_cost, get_tx_data_gas_cost,
+};
+use crate::fee::transaction_resources::TransactionResources;
 use crate::retdata;
+use crate::selector;
 use crate::state::cached_state::{CachedState, StateChangesCount};
 use crate::state::errors::StateError;
 use crate::state::state_api::{State, StateReader};
 use crate::test_utils::{
-    test_erc20_account_balance_key, test_erc20_sequencer_balance_key, DictStateReader,
-    NonceManager, BALANCE, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS,
+    assert_constructor_semantics, create_test_state, fund_account, get_raw_contract_class,
+    test_erc20_account_balance_key, test_erc20_sequencer_balance_key, trivial_external_entry_point,
+    DictStateReader, NonceManager, ACCOUNT_CONTRACT_CAIRO0_PATH, ACCOUNT_CONTRACT_CAIRO1_PATH,
+    BALANCE, DEFAULT_GAS_PRICE, DEFAULT_STRK_GAS_PRICE, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS,
     TEST_ACCOUNT_CONTRACT_CLASS_HASH, TEST_CLASS_HASH, TEST_CONTRACT_ADDRESS,
-    TEST_EMPTY_CONTRACT_CAIRO0_PATH, TEST_EMPTY_CONTRACT_CAIRO1_PATH,
-    TEST_EMPTY_CONTRACT_CLASS_HASH, TEST_ERC20_CONTRACT_ADDRESS, TEST_ERC20_CONTRACT_CLASS_HASH,
+    TEST_CONTRACT_CAIRO0_PATH, TEST_CONTRACT_CAIRO1_PATH, TEST_EMPTY_CONTRACT_CAIRO0_PATH,
+    TEST_EMPTY_CONTRACT_CAIRO1_PATH, TEST_EMPTY_CONTRACT_CLASS_HASH, TEST_ERC20_CONTRACT_ADDRESS,
+    TEST_ERC20_CONTRACT_CLASS_HASH, TEST_ERC20_STRK_CONTRACT_ADDRESS,
     TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS, TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH,
 };
 use crate::transaction::account_transaction::AccountTransaction;
 use crate::transaction::constants;
-use crate::transaction::errors::TransactionExecutionError;
-use crate::transaction::objects::{ResourcesMapping, TransactionExecutionInfo};
+use crate::transaction::errors::{
+    RestrictedEntryPointKind, TransactionExecutionError, TransactionFeeError,
+};
+use crate::transaction::objects::{
+    AccountTransactionContext, FeeType, GasVector, ResourcesMapping, TransactionExecutionInfo,
+    TransactionReceipt,
+};
 use crate::transaction::test_utils::{
-    create_account_tx_for_validate_test, create_state_with_cairo1_account,
-    create_state_with_falliable_validation_account, create_state_with_trivial_validation_account,
-    CALL_CONTRACT, INVALID, VALID,
+    create_account_tx_for_validate_test, create_account_tx_test_state,
+    create_state_with_cairo1_account, create_state_with_falliable_validation_account,
+    create_state_with_trivial_validation_account, CALL_CONTRACT, INVALID, VALID,
 };
 use crate::transaction::transaction_execution::Transaction;
 use crate::transaction::transaction_types::TransactionType;
 use crate::transaction::transactions::{
-    DeclareTransaction, DeployAccountTransaction, ExecutableTransaction,
+    DeclareTransaction, DeployAccountTransaction, ExecutableTransaction, L1HandlerTransaction,
 };
 
 enum CairoVersion {
@@ -78,6 +94,7 @@ fn expected_validate_call_info(
     storage_address: ContractAddress,
     cairo_version: CairoVersion,
 ) -> Option<CallInfo> {
+    let gas_metered = matches!(cairo_version, CairoVersion::Cairo1);
     let retdata = match cairo_version {
         CairoVersion::Cairo0 => Retdata::default(),
         CairoVersion::Cairo1 => retdata!(stark_felt!(constants::VALIDATE_RETDATA)),
@@ -133,6 +150,7 @@ fn expected_validate_call_info(
         // The account contract we use for testing has trivial `validate` functions.
         vm_resources,
         execution: CallExecution { retdata, gas_consumed, ..Default::default() },
+        gas_metered,
         ..Default::default()
     })
 }
@@ -149,7 +167,7 @@ fn expected_fee_transfer_call_info(
     let lsb_expected_amount = stark_felt!(actual_fee.0);
     // The most significant 128 bits of the expected amount transferred.
     let msb_expected_amount = stark_felt!(0_u8);
-    let storage_address = block_context.fee_token_address;
+    let storage_address = block_context.fee_token_addresses.eth_fee_token_address;
     let expected_fee_transfer_call = CallEntryPoint {
         class_hash: Some(expected_fee_token_class_hash),
         code_address: None,
@@ -210,6 +228,12 @@ fn expected_fee_transfer_call_info(
             sequencer_balance_key_low,
             sequencer_balance_key_high,
         ]),
+        syscall_counter: HashMap::from([
+            (DeprecatedSyscallSelector::StorageRead, 4),
+            (DeprecatedSyscallSelector::GetCallerAddress, 1),
+            (DeprecatedSyscallSelector::EmitEvent, 1),
+            (DeprecatedSyscallSelector::StorageWrite, 4),
+        ]),
         ..Default::default()
     })
 }
@@ -221,20 +245,27 @@ fn validate_final_balances(
     erc20_account_balance_key: StorageKey,
     expected_account_balance: u128,
 ) {
-    let account_balance =
-        state.get_storage_at(block_context.fee_token_address, erc20_account_balance_key).unwrap();
+    let account_balance = state
+        .get_storage_at(
+            block_context.fee_token_addresses.eth_fee_token_address,
+            erc20_account_balance_key,
+        )
+        .unwrap();
     assert_eq!(account_balance, stark_felt!(expected_account_balance));
 
     assert_eq!(
         state
-            .get_storage_at(block_context.fee_token_address, test_erc20_sequencer_balance_key())
+            .get_storage_at(
+                block_context.fee_token_addresses.eth_fee_token_address,
+                test_erc20_sequencer_balance_key()
+            )
             .unwrap(),
         stark_felt!(expected_sequencer_balance)
     );
 }
 
 fn invoke_tx() -> InvokeTransactionV1 {
-    let entry_point_selector = selector_from_name("return_result");
+    let entry_point_selector = selector!("return_result");
     let execute_calldata = calldata![
         stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
         entry_point_selector.0,             // EP selector.
@@ -253,8 +284,8 @@ fn invoke_tx() -> InvokeTransactionV1 {
 #[test_case(
     &mut create_state_with_trivial_validation_account(),
     ExpectedResultTestInvokeTx{
-        range_check: 101,
-        n_steps: 4135,
+        range_check: 89,
+        n_steps: 4859,
         vm_resources: VmExecutionResources {
             n_steps:  61,
             n_memory_holes:  0,
@@ -269,8 +300,8 @@ fn invoke_tx() -> InvokeTransactionV1 {
 #[test_case(
     &mut create_state_with_cairo1_account(),
     ExpectedResultTestInvokeTx{
-        range_check: 113,
-        n_steps: 4555,
+        range_check: 102,
+        n_steps: 5314,
         vm_resources: VmExecutionResources {
             n_steps: 283,
             n_memory_holes: 1,
@@ -294,6 +325,7 @@ fn test_invoke_tx(
     // Extract invoke transaction fields for testing, as it is consumed when creating an account
     // transaction.
     let calldata = Calldata(Arc::clone(&invoke_tx.calldata.0));
+    let calldata_length = calldata.0.len();
     let sender_address = invoke_tx.sender_address;
 
     let account_tx = AccountTransaction::Invoke(invoke_tx.into());
@@ -301,6 +333,7 @@ fn test_invoke_tx(
 
     // Build expected validate call info.
     let expected_account_class_hash = class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH);
+    let is_cairo1_account = matches!(cairo_version, CairoVersion::Cairo1);
     let expected_validate_call_info = expected_validate_call_info(
         expected_account_class_hash,
         constants::VALIDATE_ENTRY_POINT_NAME,
@@ -314,7 +347,7 @@ fn test_invoke_tx(
     let expected_return_result_calldata = vec![stark_felt!(2_u8)];
     let storage_address = contract_address!(TEST_CONTRACT_ADDRESS);
     let expected_return_result_call = CallEntryPoint {
-        entry_point_selector: selector_from_name("return_result"),
+        entry_point_selector: selector!("return_result"),
         class_hash: Some(class_hash!(TEST_CLASS_HASH)),
         code_address: Some(storage_address),
         entry_point_type: EntryPointType::External,
@@ -330,6 +363,12 @@ fn test_invoke_tx(
         ..expected_validate_call_info.as_ref().unwrap().call.clone()
     };
     let expected_return_result_retdata = Retdata(expected_return_result_calldata);
+    let mut expected_execute_syscall_counter =
+        HashMap::from([(DeprecatedSyscallSelector::CallContract, 1)]);
+    if is_cairo1_account {
+        // The Cairo 1 account validates its caller, which is read from the execution info.
+        expected_execute_syscall_counter.insert(DeprecatedSyscallSelector::GetExecutionInfo, 1);
+    }
     let expected_execute_call_info = Some(CallInfo {
         call: expected_execute_call,
         execution: CallExecution {
@@ -338,6 +377,8 @@ fn test_invoke_tx(
             ..Default::default()
         },
         vm_resources: expected_arguments.vm_resources,
+        gas_metered: is_cairo1_account,
+        syscall_counter: expected_execute_syscall_counter,
         inner_calls: vec![CallInfo {
             call: expected_return_result_call,
             execution: CallExecution::from_retdata(expected_return_result_retdata),
@@ -352,8 +393,12 @@ fn test_invoke_tx(
     });
 
     // Build expected fee transfer call info.
-    let expected_actual_fee =
-        calculate_tx_fee(&actual_execution_info.actual_resources, block_context).unwrap();
+    let expected_actual_fee = get_fee_by_gas_vector(
+        block_context,
+        actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+        &FeeType::Eth,
+    )
+    .unwrap();
     let expected_fee_transfer_call_info = expected_fee_transfer_call_info(
         block_context,
         sender_address,
@@ -368,18 +413,37 @@ fn test_invoke_tx(
         },
     );
 
+    let expected_l2_gas = [&expected_validate_call_info, &expected_execute_call_info]
+        .into_iter()
+        .flatten()
+        .map(|call_info| call_info.l2_gas_consumed() as usize)
+        .sum();
     let expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
         execute_call_info: expected_execute_call_info,
         fee_transfer_call_info: expected_fee_transfer_call_info,
-        actual_fee: expected_actual_fee,
-        actual_resources: ResourcesMapping(HashMap::from([
+        receipt: TransactionReceipt {
+            fee: expected_actual_fee,
+            collected_fee: expected_actual_fee,
+            gas: actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+            da_gas: GasVector { l1_gas: (2 + 2) * 612, l1_data_gas: 0 },
+        },
+        actual_resources: TransactionResources {
+            vm_resources: ResourcesMapping(HashMap::from([
+                (HASH_BUILTIN_NAME.to_string(), 20),
+                (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_arguments.range_check),
+                (abi_constants::N_STEPS_RESOURCE.to_string(), expected_arguments.n_steps),
+            ])),
+            calldata_length,
             // 1 modified contract, 1 storage update (sender balance).
-            (abi_constants::GAS_USAGE.to_string(), (2 + 2) * 612),
-            (HASH_BUILTIN_NAME.to_string(), 16),
-            (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_arguments.range_check),
-            (abi_constants::N_STEPS_RESOURCE.to_string(), expected_arguments.n_steps),
-        ])),
+            state_changes_count: StateChangesCount {
+                n_storage_updates: 1,
+                n_modified_contracts: 1,
+                ..Default::default()
+            },
+            l2_gas: expected_l2_gas,
+            ..Default::default()
+        },
         revert_error: None,
     };
 
@@ -414,7 +478,7 @@ fn test_state_get_fee_token_balance(state: &mut CachedState<DictStateReader>) {
     let recipient = stark_felt!(10_u8);
 
     // Mint some tokens.
-    let entry_point_selector = selector_from_name("permissionedMint");
+    let entry_point_selector = selector!("permissionedMint");
     let execute_calldata = calldata![
         stark_felt!(TEST_ERC20_CONTRACT_ADDRESS), // Contract address.
         entry_point_selector.0,                   // EP selector.
@@ -432,8 +496,12 @@ fn test_state_get_fee_token_balance(state: &mut CachedState<DictStateReader>) {
     AccountTransaction::Invoke(mint_tx.into()).execute(state, block_context, true, true).unwrap();
 
     // Get balance from state, and validate.
-    let (low, high) =
-        state.get_fee_token_balance(block_context, &contract_address!(recipient)).unwrap();
+    let (low, high) = state
+        .get_fee_token_balance(
+            &contract_address!(recipient),
+            &block_context.fee_token_addresses.eth_fee_token_address,
+        )
+        .unwrap();
 
     assert_eq!(low, mint_low);
     assert_eq!(high, mint_high);
@@ -449,7 +517,9 @@ fn assert_failure_if_max_fee_exceeds_balance(
     // Test error.
     assert_matches!(
         invalid_tx.execute(state, block_context, true, true).unwrap_err(),
-        TransactionExecutionError::MaxFeeExceedsBalance{ max_fee, .. }
+        TransactionExecutionError::TransactionFeeError(
+            TransactionFeeError::MaxFeeExceedsBalance{ max_fee, .. }
+        )
         if max_fee == sent_max_fee
     );
 }
@@ -480,19 +550,66 @@ fn test_max_fee_exceeds_balance(state: &mut CachedState<DictStateReader>) {
     assert_failure_if_max_fee_exceeds_balance(state, block_context, invalid_tx);
 
     // Declare.
-    let invalid_tx = AccountTransaction::Declare(DeclareTransaction {
-        tx: starknet_api::transaction::DeclareTransaction::V1(DeclareTransactionV0V1 {
-            max_fee: invalid_max_fee,
-            ..declare_tx(TEST_EMPTY_CONTRACT_CLASS_HASH, TEST_ACCOUNT_CONTRACT_ADDRESS, None)
-        }),
-        tx_hash: TransactionHash::default(),
-        contract_class: ContractClass::V0(ContractClassV0::from_file(
-            TEST_EMPTY_CONTRACT_CAIRO0_PATH,
-        )),
-    });
+    let invalid_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V1(DeclareTransactionV0V1 {
+                max_fee: invalid_max_fee,
+                ..declare_tx(TEST_EMPTY_CONTRACT_CLASS_HASH, TEST_ACCOUNT_CONTRACT_ADDRESS, None)
+            }),
+            TransactionHash::default(),
+            ContractClass::V0(ContractClassV0::from_file(TEST_EMPTY_CONTRACT_CAIRO0_PATH)),
+        )
+        .unwrap(),
+    );
     assert_failure_if_max_fee_exceeds_balance(state, block_context, invalid_tx);
 }
 
+/// Returns the Cairo1 account contract class, with its validation entry points returning the given
+/// felt instead of `VALID`.
+fn cairo1_account_with_validate_retdata(validate_retdata: &str) -> ContractClass {
+    let raw_contract_class = get_raw_contract_class(ACCOUNT_CONTRACT_CAIRO1_PATH).replace(
+        &format!("\"{}\"", constants::VALIDATE_RETDATA),
+        &format!("\"{validate_retdata}\""),
+    );
+    ContractClassV1::try_from_json_string(&raw_contract_class).unwrap().into()
+}
+
+#[test_case(
+    ContractClassV0::from_file(ACCOUNT_CONTRACT_CAIRO0_PATH).into(), None;
+    "Cairo0 account with empty return data")]
+#[test_case(
+    ContractClassV1::from_file(ACCOUNT_CONTRACT_CAIRO1_PATH).into(), None;
+    "Cairo1 account returning VALID")]
+#[test_case(
+    cairo1_account_with_validate_retdata("0x0"), Some(retdata![stark_felt!(0_u8)]);
+    "Cairo1 account returning zero")]
+fn test_validate_return_data(
+    account_class: ContractClass,
+    expected_invalid_retdata: Option<Retdata>,
+) {
+    let state = &mut create_account_tx_test_state(
+        account_class,
+        TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+        TEST_ACCOUNT_CONTRACT_ADDRESS,
+        test_erc20_account_balance_key(),
+        BALANCE,
+    );
+    let block_context = &BlockContext::create_for_account_testing();
+    let account_tx = AccountTransaction::Invoke(invoke_tx().into());
+    let execution_result = account_tx.execute(state, block_context, true, true);
+
+    match expected_invalid_retdata {
+        None => {
+            execution_result.unwrap();
+        }
+        Some(expected_retdata) => assert_matches!(
+            execution_result.unwrap_err(),
+            TransactionExecutionError::InvalidValidateReturnData { actual }
+            if actual == expected_retdata
+        ),
+    }
+}
+
 #[test_case(
     &mut create_state_with_trivial_validation_account();
     "With Cairo0 account")]
@@ -515,10 +632,20 @@ fn test_negative_invoke_tx_flows(state: &mut CachedState<DictStateReader>) {
     // Test error.
     assert_matches!(
         execution_error,
-        TransactionExecutionError::MaxFeeTooLow{ min_fee, max_fee }
+        TransactionExecutionError::TransactionFeeError(
+            TransactionFeeError::MaxFeeTooLow{ min_fee, max_fee }
+        )
         if max_fee == invalid_max_fee && min_fee == minimal_fee
     );
 
+    // Fee too low, but fee is not charged (e.g., in fee estimation); the check is skipped.
+    let invalid_tx = AccountTransaction::Invoke(
+        InvokeTransactionV1 { max_fee: invalid_max_fee, ..valid_invoke_tx.clone() }.into(),
+    );
+    invalid_tx
+        .execute(&mut CachedState::create_transactional(state), block_context, false, true)
+        .unwrap();
+
     // Insufficient fee.
     let invalid_max_fee = minimal_fee;
     let invalid_tx = AccountTransaction::Invoke(
@@ -530,7 +657,7 @@ fn test_negative_invoke_tx_flows(state: &mut CachedState<DictStateReader>) {
     // Test error.
     assert!(execution_error.starts_with("Insufficient max fee:"));
     // Test that fee was charged.
-    assert_eq!(execution_result.actual_fee, invalid_max_fee);
+    assert_eq!(execution_result.receipt.fee, invalid_max_fee);
 
     // Invalid nonce.
     // Use a fresh state to facilitate testing.
@@ -551,6 +678,44 @@ fn test_negative_invoke_tx_flows(state: &mut CachedState<DictStateReader>) {
     );
 }
 
+#[test]
+fn test_minimal_fee_boundary() {
+    let mut block_context = BlockContext::create_for_account_testing();
+    block_context.versioned_constants_mut().tx_data_felt_gas_cost = 100;
+    let signature = TransactionSignature(vec![stark_felt!(1_u8), stark_felt!(2_u8)]);
+    let invoke_tx = InvokeTransactionV1 { signature: signature.clone(), ..invoke_tx() };
+    let account_tx = AccountTransaction::Invoke(invoke_tx.clone().into());
+
+    // The calldata and signature felts are part of the minimal fee.
+    let minimal_fee = estimate_minimal_fee(&block_context, &account_tx).unwrap();
+    let data_felts_cost =
+        (invoke_tx.calldata.0.len() + signature.0.len()) as u128 * 100 * DEFAULT_GAS_PRICE;
+    block_context.versioned_constants_mut().tx_data_felt_gas_cost = 0;
+    assert_eq!(
+        estimate_minimal_fee(&block_context, &account_tx).unwrap(),
+        Fee(minimal_fee.0 - data_felts_cost)
+    );
+    block_context.versioned_constants_mut().tx_data_felt_gas_cost = 100;
+
+    // A max fee of exactly the minimal fee passes the check.
+    let state = &mut create_state_with_trivial_validation_account();
+    let tx = AccountTransaction::Invoke(
+        InvokeTransactionV1 { max_fee: minimal_fee, ..invoke_tx.clone() }.into(),
+    );
+    tx.execute(&mut CachedState::create_transactional(state), &block_context, true, true).unwrap();
+
+    // One below it does not.
+    let max_fee = Fee(minimal_fee.0 - 1);
+    let tx = AccountTransaction::Invoke(InvokeTransactionV1 { max_fee, ..invoke_tx }.into());
+    assert_matches!(
+        tx.execute(state, &block_context, true, true).unwrap_err(),
+        TransactionExecutionError::TransactionFeeError(
+            TransactionFeeError::MaxFeeTooLow { min_fee, max_fee: actual_max_fee }
+        )
+        if min_fee == minimal_fee && actual_max_fee == max_fee
+    );
+}
+
 fn declare_tx(
     class_hash: &str,
     sender_address: &str,
@@ -566,14 +731,14 @@ fn declare_tx(
 
 #[test_case(
     &mut create_state_with_trivial_validation_account(),
-    63, // range_check_builtin
-    2715, // n_steps
+    56, // range_check_builtin
+    3215, // n_steps
     CairoVersion::Cairo0;
     "With Cairo0 account")]
 #[test_case(
     &mut create_state_with_cairo1_account(),
-    65, // range_check_builtin
-    2753, // n_steps
+    58, // range_check_builtin
+    3253, // n_steps
     CairoVersion::Cairo1;
     "With Cairo1 account")]
 fn test_declare_tx(
@@ -593,11 +758,14 @@ fn test_declare_tx(
 
     let contract_class =
         ContractClass::V0(ContractClassV0::from_file(TEST_EMPTY_CONTRACT_CAIRO0_PATH));
-    let account_tx = AccountTransaction::Declare(DeclareTransaction {
-        tx: starknet_api::transaction::DeclareTransaction::V1(declare_tx),
-        tx_hash: TransactionHash::default(),
-        contract_class: contract_class.clone(),
-    });
+    let account_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V1(declare_tx),
+            TransactionHash::default(),
+            contract_class.clone(),
+        )
+        .unwrap(),
+    );
 
     // Check state before transaction application.
     assert_matches!(
@@ -621,8 +789,12 @@ fn test_declare_tx(
     );
 
     // Build expected fee transfer call info.
-    let expected_actual_fee =
-        calculate_tx_fee(&actual_execution_info.actual_resources, block_context).unwrap();
+    let expected_actual_fee = get_fee_by_gas_vector(
+        block_context,
+        actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+        &FeeType::Eth,
+    )
+    .unwrap();
     let expected_fee_transfer_call_info = expected_fee_transfer_call_info(
         block_context,
         expected_account_address,
@@ -637,19 +809,33 @@ fn test_declare_tx(
         },
     );
 
+    let expected_l2_gas = expected_validate_call_info.as_ref().unwrap().l2_gas_consumed() as usize;
     let expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
         execute_call_info: None,
         fee_transfer_call_info: expected_fee_transfer_call_info,
-        actual_fee: expected_actual_fee,
+        receipt: TransactionReceipt {
+            fee: expected_actual_fee,
+            collected_fee: expected_actual_fee,
+            gas: actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+            da_gas: GasVector { l1_gas: (2 + 2) * 612, l1_data_gas: 0 },
+        },
         revert_error: None,
-        actual_resources: ResourcesMapping(HashMap::from([
+        actual_resources: TransactionResources {
+            vm_resources: ResourcesMapping(HashMap::from([
+                (HASH_BUILTIN_NAME.to_string(), 16),
+                (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_range_check_builtin),
+                (abi_constants::N_STEPS_RESOURCE.to_string(), expected_n_steps_resource),
+            ])),
             // 1 modified contract, 1 storage update (sender balance).
-            (abi_constants::GAS_USAGE.to_string(), (2 + 2) * 612),
-            (HASH_BUILTIN_NAME.to_string(), 15),
-            (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_range_check_builtin),
-            (abi_constants::N_STEPS_RESOURCE.to_string(), expected_n_steps_resource),
-        ])),
+            state_changes_count: StateChangesCount {
+                n_storage_updates: 1,
+                n_modified_contracts: 1,
+                ..Default::default()
+            },
+            l2_gas: expected_l2_gas,
+            ..Default::default()
+        },
     };
 
     // Test execution info result.
@@ -675,6 +861,41 @@ fn test_declare_tx(
     assert_eq!(contract_class_from_state, contract_class);
 }
 
+#[test_case(0, true; "At the limit")]
+#[test_case(1, false; "Over the limit")]
+fn test_declare_tx_bytecode_size_limit(excess_bytecode_size: usize, expect_success: bool) {
+    let state = &mut create_state_with_trivial_validation_account();
+    let contract_class = ContractClass::V0(ContractClassV0::from_file(TEST_CONTRACT_CAIRO0_PATH));
+    let bytecode_size = contract_class.bytecode_length();
+    let max_bytecode_size = bytecode_size - excess_bytecode_size;
+    let mut block_context = BlockContext::create_for_account_testing();
+    block_context.versioned_constants_mut().declare_tx_max_bytecode_size = max_bytecode_size;
+    let block_context = &block_context;
+    let account_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V1(declare_tx(
+                TEST_CLASS_HASH,
+                TEST_ACCOUNT_CONTRACT_ADDRESS,
+                None,
+            )),
+            TransactionHash::default(),
+            contract_class,
+        )
+        .unwrap(),
+    );
+
+    let result = account_tx.execute(state, block_context, true, true);
+    if expect_success {
+        result.unwrap();
+    } else {
+        assert_matches!(
+            result.unwrap_err(),
+            TransactionExecutionError::ContractBytecodeSizeTooLarge { size, max }
+            if size == bytecode_size && max == max_bytecode_size
+        );
+    }
+}
+
 // TODO(Noa, 01/07/23): Consider unify the decalre tx tests.
 #[test]
 fn test_declare_tx_v2() {
@@ -691,11 +912,14 @@ fn test_declare_tx_v2() {
 
     let contract_class =
         ContractClass::V1(ContractClassV1::from_file(TEST_EMPTY_CONTRACT_CAIRO1_PATH));
-    let account_tx = AccountTransaction::Declare(DeclareTransaction {
-        tx: starknet_api::transaction::DeclareTransaction::V2(declare_tx),
-        tx_hash: TransactionHash::default(),
-        contract_class: contract_class.clone(),
-    });
+    let account_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V2(declare_tx),
+            TransactionHash::default(),
+            contract_class.clone(),
+        )
+        .unwrap(),
+    );
 
     // Check state before transaction application.
     assert_matches!(
@@ -708,22 +932,111 @@ fn test_declare_tx_v2() {
     let expected_actual_resources = ResourcesMapping(HashMap::from([
         // 1 modified contract, 1 storage update (sender balance) + 1 compiled_class_hash update.
         (abi_constants::GAS_USAGE.to_string(), (2 + 2 + 2) * 612),
-        (HASH_BUILTIN_NAME.to_string(), 15),
-        (RANGE_CHECK_BUILTIN_NAME.to_string(), 65),
-        (abi_constants::N_STEPS_RESOURCE.to_string(), 2753),
+        (abi_constants::BLOB_GAS_USAGE.to_string(), 0),
+        (HASH_BUILTIN_NAME.to_string(), 16),
+        (RANGE_CHECK_BUILTIN_NAME.to_string(), 58),
+        (abi_constants::N_STEPS_RESOURCE.to_string(), 3253),
     ]));
 
-    let expected_actual_fee =
-        calculate_tx_fee(&actual_execution_info.actual_resources, block_context).unwrap();
+    let expected_actual_fee = get_fee_by_gas_vector(
+        block_context,
+        actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+        &FeeType::Eth,
+    )
+    .unwrap();
 
-    assert_eq!(expected_actual_resources, actual_execution_info.actual_resources);
-    assert_eq!(expected_actual_fee, actual_execution_info.actual_fee);
+    assert_eq!(
+        expected_actual_resources,
+        actual_execution_info.actual_resources.to_resources_mapping(block_context).unwrap()
+    );
+    assert_eq!(expected_actual_fee, actual_execution_info.receipt.fee);
 
     // Verify class declaration.
     let contract_class_from_state = state.get_compiled_contract_class(&class_hash).unwrap();
     assert_eq!(contract_class_from_state, contract_class);
 }
 
+#[test]
+fn test_declare_tx_v2_with_class_info() {
+    let block_context = &BlockContext::create_for_account_testing();
+    let class_hash = class_hash!(TEST_EMPTY_CONTRACT_CLASS_HASH);
+    let compiled_class_hash = CompiledClassHash(stark_felt!(7_u8));
+    let declare_tx = |nonce: u8| {
+        starknet_api::transaction::DeclareTransaction::V2(DeclareTransactionV2 {
+            max_fee: Fee(MAX_FEE),
+            class_hash,
+            compiled_class_hash,
+            sender_address: contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+            nonce: Nonce(stark_felt!(nonce)),
+            ..Default::default()
+        })
+    };
+    let contract_class = ContractClass::V1(ContractClassV1::from_file(TEST_CONTRACT_CAIRO1_PATH));
+    let class_info = ClassInfo::from(&contract_class);
+
+    let full_declare_tx =
+        DeclareTransaction::new(declare_tx(0), TransactionHash::default(), contract_class.clone())
+            .unwrap();
+    let full_declare_execution_info = AccountTransaction::Declare(full_declare_tx)
+        .execute(&mut create_state_with_cairo1_account(), block_context, true, true)
+        .unwrap();
+
+    // Declare given the class info only; the compiled class is not at hand.
+    let state = &mut create_state_with_cairo1_account();
+    let declare_tx_with_class_info = DeclareTransaction::new_with_class_info(
+        declare_tx(0),
+        TransactionHash::default(),
+        class_info,
+    )
+    .unwrap();
+    let actual_execution_info = AccountTransaction::Declare(declare_tx_with_class_info)
+        .execute(state, block_context, true, true)
+        .unwrap();
+    assert_eq!(actual_execution_info.receipt, full_declare_execution_info.receipt);
+    assert_eq!(
+        actual_execution_info.actual_resources,
+        full_declare_execution_info.actual_resources
+    );
+    assert_eq!(state.get_compiled_class_hash(class_hash).unwrap(), compiled_class_hash);
+
+    // The class is read from the state reader upon its first invocation.
+    state.state.class_hash_to_class.insert(class_hash, contract_class);
+    let value = stark_felt!(18_u8);
+    let entry_point_call = CallEntryPoint {
+        class_hash: Some(class_hash),
+        entry_point_selector: selector!("test_storage_read_write"),
+        calldata: calldata![stark_felt!(1234_u16), value],
+        ..trivial_external_entry_point()
+    };
+    assert_eq!(
+        entry_point_call.execute_directly(state).unwrap().execution.retdata,
+        retdata![value]
+    );
+
+    // A redeclaration is detected by the compiled class hash.
+    let redeclare_tx = DeclareTransaction::new_with_class_info(
+        declare_tx(1),
+        TransactionHash::default(),
+        class_info,
+    )
+    .unwrap();
+    assert_matches!(
+        AccountTransaction::Declare(redeclare_tx).execute(state, block_context, true, true),
+        Err(TransactionExecutionError::DeclareTransactionError { class_hash: error_class_hash })
+        if error_class_hash == class_hash
+    );
+
+    // The class info must match the declaration version.
+    assert_matches!(
+        DeclareTransaction::new_with_class_info(
+            declare_tx(0),
+            TransactionHash::default(),
+            ClassInfo { cairo_version: 0, ..class_info }
+        ),
+        Err(TransactionExecutionError::ContractClassVersionMismatch { cairo_version: 1, .. })
+    );
+}
+
 fn deploy_account_tx(
     account_class_hash: &str,
     constructor_calldata: Option<Calldata>,
@@ -741,14 +1054,14 @@ fn deploy_account_tx(
 
 #[test_case(
     &mut create_state_with_trivial_validation_account(),
-    83, // range_check_builtin
-    3625, // n_steps
+    72, // range_check_builtin
+    4174, // n_steps
     CairoVersion::Cairo0;
     "With Cairo0 account")]
 #[test_case(
     &mut create_state_with_cairo1_account(),
-    85, // range_check_builtin
-    3681, // n_steps
+    74, // range_check_builtin
+    4230, // n_steps
     CairoVersion::Cairo1;
     "With Cairo1 account")]
 fn test_deploy_account_tx(
@@ -769,15 +1082,11 @@ fn test_deploy_account_tx(
     let constructor_calldata = deploy_account.constructor_calldata();
     let salt = deploy_account.contract_address_salt();
 
-    // Update the balance of the about to be deployed account contract in the erc20 contract, so it
-    // can pay for the transaction execution.
+    // Fund the about to be deployed account contract, so it can pay for the transaction
+    // execution.
+    fund_account(state, deployed_account_address, BALANCE, block_context);
     let deployed_account_balance_key =
         get_storage_var_address("ERC20_balances", &[*deployed_account_address.0.key()]).unwrap();
-    state.set_storage_at(
-        block_context.fee_token_address,
-        deployed_account_balance_key,
-        stark_felt!(BALANCE),
-    );
 
     let account_tx = AccountTransaction::DeployAccount(deploy_account);
     let actual_execution_info = account_tx.execute(state, block_context, true, true).unwrap();
@@ -811,8 +1120,12 @@ fn test_deploy_account_tx(
     });
 
     // Build expected fee transfer call info.
-    let expected_actual_fee =
-        calculate_tx_fee(&actual_execution_info.actual_resources, block_context).unwrap();
+    let expected_actual_fee = get_fee_by_gas_vector(
+        block_context,
+        actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+        &FeeType::Eth,
+    )
+    .unwrap();
     let expected_fee_transfer_call_info = expected_fee_transfer_call_info(
         block_context,
         deployed_account_address,
@@ -827,19 +1140,38 @@ fn test_deploy_account_tx(
         },
     );
 
+    let expected_l2_gas = [&expected_validate_call_info, &expected_execute_call_info]
+        .into_iter()
+        .flatten()
+        .map(|call_info| call_info.l2_gas_consumed() as usize)
+        .sum();
     let expected_execution_info = TransactionExecutionInfo {
         validate_call_info: expected_validate_call_info,
         execute_call_info: expected_execute_call_info,
         fee_transfer_call_info: expected_fee_transfer_call_info,
-        actual_fee: expected_actual_fee,
+        receipt: TransactionReceipt {
+            fee: expected_actual_fee,
+            collected_fee: expected_actual_fee,
+            gas: actual_execution_info.actual_resources.to_gas_vector(block_context).unwrap(),
+            da_gas: GasVector { l1_gas: (2 + 2 + 1) * 612, l1_data_gas: 0 },
+        },
         revert_error: None,
-        actual_resources: ResourcesMapping(HashMap::from([
+        actual_resources: TransactionResources {
+            vm_resources: ResourcesMapping(HashMap::from([
+                (HASH_BUILTIN_NAME.to_string(), 23),
+                (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_range_check_builtin),
+                (abi_constants::N_STEPS_RESOURCE.to_string(), expected_n_steps_resource),
+            ])),
             // 1 modified contract, 1 storage update (sender balance) + 1 class_hash update.
-            (abi_constants::GAS_USAGE.to_string(), (2 + 2 + 1) * 612),
-            (HASH_BUILTIN_NAME.to_string(), 23),
-            (RANGE_CHECK_BUILTIN_NAME.to_string(), expected_range_check_builtin),
-            (abi_constants::N_STEPS_RESOURCE.to_string(), expected_n_steps_resource),
-        ])),
+            state_changes_count: StateChangesCount {
+                n_storage_updates: 1,
+                n_class_hash_updates: 1,
+                n_modified_contracts: 1,
+                ..Default::default()
+            },
+            l2_gas: expected_l2_gas,
+            ..Default::default()
+        },
     };
 
     // Test execution info result.
@@ -878,6 +1210,76 @@ fn test_deploy_account_tx(
     );
 }
 
+#[test_case(
+    TEST_ACCOUNT_CONTRACT_CLASS_HASH, calldata![], VALID, None;
+    "No constructor: Positive flow")]
+#[test_case(
+    TEST_ACCOUNT_CONTRACT_CLASS_HASH,
+    calldata![stark_felt!(1_u8)],
+    VALID,
+    Some("Cannot pass calldata to a contract with no constructor.");
+    "No constructor: Negative flow: nonempty calldata")]
+#[test_case(
+    TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH,
+    calldata![stark_felt!(constants::FELT_TRUE)],
+    VALID,
+    None;
+    "With constructor: Positive flow")]
+#[test_case(
+    TEST_FAULTY_ACCOUNT_CONTRACT_CLASS_HASH,
+    calldata![stark_felt!(constants::FELT_TRUE)],
+    INVALID,
+    Some("An ASSERT_EQ instruction failed");
+    "With constructor: Negative flow: failing constructor")]
+fn test_deploy_account_constructor(
+    class_hash: &str,
+    constructor_calldata: Calldata,
+    // The scenario of the faulty account constructor (see `faulty_validate`).
+    scenario: u64,
+    expected_error: Option<&str>,
+) {
+    let state = &mut create_state_with_falliable_validation_account();
+    state
+        .set_contract_class(
+            &class_hash!(TEST_ACCOUNT_CONTRACT_CLASS_HASH),
+            ContractClassV0::from_file(ACCOUNT_CONTRACT_CAIRO0_PATH).into(),
+        )
+        .unwrap();
+    let deploy_account = crate::test_utils::deploy_account_tx(
+        class_hash,
+        Fee(0),
+        Some(constructor_calldata),
+        Some(TransactionSignature(vec![stark_felt!(scenario)])),
+        &mut NonceManager::default(),
+    );
+    let deployed_account_address = deploy_account.contract_address;
+    let account_tx = AccountTransaction::DeployAccount(deploy_account);
+    let result = account_tx.execute(state, &BlockContext::create_for_account_testing(), true, true);
+
+    if let Some(expected_error) = expected_error {
+        // A failing constructor rejects the transaction, rather than reverting it.
+        let TransactionExecutionError::ContractConstructorExecutionFailed(error) =
+            result.unwrap_err()
+        else {
+            panic!("Expected a constructor execution failure.");
+        };
+        assert!(error.to_string().contains(expected_error), "{error}");
+        assert_eq!(
+            state.get_class_hash_at(deployed_account_address).unwrap(),
+            ClassHash::default()
+        );
+        return;
+    }
+
+    assert_constructor_semantics(
+        &result.unwrap().execute_call_info.unwrap(),
+        deployed_account_address,
+        ContractAddress::default(),
+        class_hash!(class_hash),
+    );
+    assert_eq!(state.get_class_hash_at(deployed_account_address).unwrap(), class_hash!(class_hash));
+}
+
 #[test]
 fn test_validate_accounts_tx() {
     fn test_validate_account_tx(tx_type: TransactionType) {
@@ -924,8 +1326,12 @@ fn test_validate_accounts_tx() {
             &mut NonceManager::default(),
         );
         let error = account_tx.execute(state, block_context, true, true).unwrap_err();
-        assert_matches!(error, TransactionExecutionError::UnauthorizedInnerCall{entry_point_kind} if
-        entry_point_kind == constants::VALIDATE_ENTRY_POINT_NAME);
+        assert_matches!(
+            error,
+            TransactionExecutionError::UnauthorizedInnerCall {
+                entry_point_kind: RestrictedEntryPointKind::Validate
+            }
+        );
 
         // Verify that the contract does not call another contract in the constructor of deploy
         // account as well.
@@ -946,8 +1352,12 @@ fn test_validate_accounts_tx() {
             );
             let account_tx = AccountTransaction::DeployAccount(deploy_account_tx);
             let error = account_tx.execute(state, block_context, true, true).unwrap_err();
-            assert_matches!(error, TransactionExecutionError::UnauthorizedInnerCall{entry_point_kind} if
-        entry_point_kind == "an account constructor");
+            assert_matches!(
+                error,
+                TransactionExecutionError::UnauthorizedInnerCall {
+                    entry_point_kind: RestrictedEntryPointKind::AccountConstructor
+                }
+            );
         }
     }
 
@@ -962,6 +1372,21 @@ fn test_validate_accounts_tx() {
 fn test_calculate_tx_gas_usage() {
     let state = &mut create_state_with_trivial_validation_account();
     let block_context = &BlockContext::create_for_account_testing();
+    // The gas of the calldata, signature and events, on top of that of the state diff.
+    let tx_data_and_events_gas = |tx_resources: &TransactionResources| {
+        get_tx_data_gas_cost(
+            tx_resources.calldata_length,
+            tx_resources.signature_length,
+            block_context,
+        )
+        .l1_gas
+            + get_events_gas_cost(
+                tx_resources.n_event_keys,
+                tx_resources.n_event_data_felts,
+                block_context,
+            )
+            .l1_gas
+    };
 
     let invoke_tx = invoke_tx();
     let account_tx = AccountTransaction::Invoke(invoke_tx.into());
@@ -978,20 +1403,20 @@ fn test_calculate_tx_gas_usage() {
     let l1_gas_usage = calculate_tx_gas_usage(&[], state_changes_count, None);
 
     assert_eq!(
-        *tx_execution_info.actual_resources.0.get(abi_constants::GAS_USAGE).unwrap(),
-        l1_gas_usage
+        tx_execution_info.actual_resources.direct_gas_vector(block_context).unwrap().l1_gas,
+        l1_gas_usage + tx_data_and_events_gas(&tx_execution_info.actual_resources)
     );
 
     // A tx that changes the account and some other balance in execute.
     let entry_point_selector = selector_from_name(constants::TRANSFER_ENTRY_POINT_NAME);
     let some_other_account_address = stark_felt!(TEST_FAULTY_ACCOUNT_CONTRACT_ADDRESS);
     let execute_calldata = calldata![
-        *block_context.fee_token_address.0.key(), // Contract address.
-        entry_point_selector.0,                   // EP selector.
-        stark_felt!(3_u8),                        // Calldata length.
-        some_other_account_address,               // Calldata: recipient.
-        stark_felt!(2_u8),                        // Calldata: lsb amount.
-        stark_felt!(0_u8)                         // Calldata: msb amount.
+        *block_context.fee_token_addresses.eth_fee_token_address.0.key(), // Contract address.
+        entry_point_selector.0,                                           // EP selector.
+        stark_felt!(3_u8),                                                // Calldata length.
+        some_other_account_address,                                       // Calldata: recipient.
+        stark_felt!(2_u8),                                                // Calldata: lsb amount.
+        stark_felt!(0_u8)                                                 // Calldata: msb amount.
     ];
 
     let invoke_tx = crate::test_utils::invoke_tx(
@@ -1019,7 +1444,220 @@ fn test_calculate_tx_gas_usage() {
     let l1_gas_usage = calculate_tx_gas_usage(&[], state_changes_count, None);
 
     assert_eq!(
-        *tx_execution_info.actual_resources.0.get(abi_constants::GAS_USAGE).unwrap(),
-        l1_gas_usage
+        tx_execution_info.actual_resources.direct_gas_vector(block_context).unwrap().l1_gas,
+        l1_gas_usage + tx_data_and_events_gas(&tx_execution_info.actual_resources)
+    );
+}
+
+/// The same invoke publishes its state diff (one modified contract, one storage update) in calldata,
+/// paid in L1 gas, or in blobs, paid in data gas, by the DA mode of the block.
+#[test]
+fn test_da_gas_by_da_mode() {
+    let execution_by_da_mode = [false, true].map(|use_kzg_da| {
+        let state = &mut create_state_with_trivial_validation_account();
+        let block_context =
+            BlockContext { use_kzg_da, ..BlockContext::create_for_account_testing() };
+        let account_tx = AccountTransaction::Invoke(invoke_tx().into());
+        let tx_execution_info = account_tx.execute(state, &block_context, true, true).unwrap();
+        let bouncer_weights = BouncerWeights::from_tx_execution(
+            &tx_execution_info,
+            tx_execution_info.actual_resources.state_changes_count,
+            None,
+            &block_context,
+        )
+        .unwrap();
+        (tx_execution_info.receipt, bouncer_weights)
+    });
+    let [(calldata_receipt, calldata_weights), (blob_receipt, blob_weights)] = execution_by_da_mode;
+
+    let da_segment_length = 2 + 2;
+    // Publishing in blobs costs the OS 113 more steps (of 1 L1 gas each, in the test weights), to
+    // compute the KZG commitment.
+    let kzg_commitment_gas = 113;
+    assert_eq!(
+        calldata_receipt.da_gas,
+        GasVector { l1_gas: da_segment_length * 612, l1_data_gas: 0 }
     );
+    assert_eq!(blob_receipt.da_gas, GasVector { l1_gas: 0, l1_data_gas: da_segment_length * 32 });
+    assert_eq!(
+        blob_receipt.gas,
+        GasVector {
+            l1_gas: calldata_receipt.gas.l1_gas - da_segment_length * 612 + kzg_commitment_gas,
+            l1_data_gas: da_segment_length * 32
+        }
+    );
+
+    // The bouncer only counts the calldata DA as L1 gas; the state diff size counts either way.
+    assert_eq!(blob_weights.gas, calldata_weights.gas - da_segment_length * 612);
+    assert_eq!(blob_weights.state_diff_size, calldata_weights.state_diff_size);
+}
+
+#[test]
+fn test_fee_type_by_tx_version() {
+    let block_context = &BlockContext::create_for_account_testing();
+
+    for (version, expected_fee_type) in
+        [(0_u8, FeeType::Eth), (1_u8, FeeType::Eth), (2_u8, FeeType::Eth), (3_u8, FeeType::Strk)]
+    {
+        let account_tx_context = AccountTransactionContext {
+            version: TransactionVersion(stark_felt!(version)),
+            ..Default::default()
+        };
+        assert_eq!(account_tx_context.fee_type(), expected_fee_type);
+    }
+
+    assert_eq!(
+        block_context.fee_token_address(&FeeType::Eth),
+        contract_address!(TEST_ERC20_CONTRACT_ADDRESS)
+    );
+    assert_eq!(
+        block_context.fee_token_address(&FeeType::Strk),
+        contract_address!(TEST_ERC20_STRK_CONTRACT_ADDRESS)
+    );
+    assert_eq!(
+        block_context.gas_prices.get_gas_price_by_fee_type(&FeeType::Eth),
+        DEFAULT_GAS_PRICE
+    );
+    assert_eq!(
+        block_context.gas_prices.get_gas_price_by_fee_type(&FeeType::Strk),
+        DEFAULT_STRK_GAS_PRICE
+    );
+}
+
+/// Tests that the fee of a pre-v3 transaction is charged in ETH, and that the STRK token is not
+/// touched.
+#[test]
+fn test_fee_charged_in_eth_token() {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let eth_fee_token_address = block_context.fee_token_addresses.eth_fee_token_address;
+    let strk_fee_token_address = block_context.fee_token_addresses.strk_fee_token_address;
+    let account_address = contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS);
+
+    let account_tx = AccountTransaction::Invoke(invoke_tx().into());
+    let tx_execution_info = account_tx.execute(state, block_context, true, true).unwrap();
+    let actual_fee = tx_execution_info.receipt.fee.0;
+    assert_eq!(
+        tx_execution_info.fee_transfer_call_info.unwrap().call.storage_address,
+        eth_fee_token_address
+    );
+
+    // The account is debited and the sequencer is credited, only in ETH.
+    for (fee_token_address, expected_account_balance, expected_sequencer_balance) in [
+        (eth_fee_token_address, BALANCE - actual_fee, actual_fee),
+        (strk_fee_token_address, BALANCE, 0),
+    ] {
+        assert_eq!(
+            state.get_fee_token_balance(&account_address, &fee_token_address).unwrap(),
+            (stark_felt!(expected_account_balance), stark_felt!(0_u8))
+        );
+        assert_eq!(
+            state
+                .get_fee_token_balance(&block_context.sequencer_address, &fee_token_address)
+                .unwrap(),
+            (stark_felt!(expected_sequencer_balance), stark_felt!(0_u8))
+        );
+    }
+
+    let state_diff = state.to_state_diff();
+    assert!(state_diff.storage_updates.contains_key(&eth_fee_token_address));
+    assert!(!state_diff.storage_updates.contains_key(&strk_fee_token_address));
+}
+
+#[test]
+fn test_uninitialized_sender_account() {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let undeployed_address = "0x777";
+
+    let invoke_tx = AccountTransaction::Invoke(
+        InvokeTransactionV1 {
+            sender_address: contract_address!(undeployed_address),
+            ..invoke_tx()
+        }
+        .into(),
+    );
+    let declare_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V1(declare_tx(
+                TEST_EMPTY_CONTRACT_CLASS_HASH,
+                undeployed_address,
+                None,
+            )),
+            TransactionHash::default(),
+            ContractClass::V0(ContractClassV0::from_file(TEST_EMPTY_CONTRACT_CAIRO0_PATH)),
+        )
+        .unwrap(),
+    );
+
+    for account_tx in [invoke_tx, declare_tx] {
+        assert_matches!(
+            account_tx.execute(state, block_context, true, true).unwrap_err(),
+            TransactionExecutionError::UninitializedSenderAccount { address }
+            if address == contract_address!(undeployed_address)
+        );
+    }
+}
+
+/// Version 0 declarations bootstrap a system with no deployed accounts.
+#[test]
+fn test_declare_v0_from_undeployed_sender() {
+    let state = &mut create_state_with_trivial_validation_account();
+    let block_context = &BlockContext::create_for_account_testing();
+    let undeployed_address = "0x777";
+    let declare_tx = DeclareTransactionV0V1 {
+        max_fee: Fee(0),
+        ..declare_tx(TEST_EMPTY_CONTRACT_CLASS_HASH, undeployed_address, None)
+    };
+    let class_hash = declare_tx.class_hash;
+    let account_tx = AccountTransaction::Declare(
+        DeclareTransaction::new(
+            starknet_api::transaction::DeclareTransaction::V0(declare_tx),
+            TransactionHash::default(),
+            ContractClass::V0(ContractClassV0::from_file(TEST_EMPTY_CONTRACT_CAIRO0_PATH)),
+        )
+        .unwrap(),
+    );
+
+    let execution_info = account_tx.execute(state, block_context, true, true).unwrap();
+    assert!(!execution_info.is_reverted());
+    assert!(execution_info.validate_call_info.is_none());
+    assert!(execution_info.fee_transfer_call_info.is_none());
+    state.get_compiled_contract_class(&class_hash).unwrap();
+    assert_eq!(
+        state.get_nonce_at(contract_address!(undeployed_address)).unwrap(),
+        Nonce::default()
+    );
+}
+
+#[test]
+fn test_l1_handler() {
+    let state = &mut create_test_state();
+    let block_context = &BlockContext::create_for_account_testing();
+    let contract_address = contract_address!(TEST_CONTRACT_ADDRESS);
+    let from_address = stark_felt!(0x123_u16);
+    let arg = stark_felt!(0x7_u8);
+    let tx = L1HandlerTransaction {
+        tx: starknet_api::transaction::L1HandlerTransaction {
+            version: TransactionVersion::default(),
+            // The L1 nonce is unrelated to the nonce of the contract.
+            nonce: Nonce(stark_felt!(0x3_u8)),
+            contract_address,
+            entry_point_selector: selector!("l1_handle"),
+            calldata: calldata![from_address, arg],
+        },
+        tx_hash: TransactionHash::default(),
+        paid_fee_on_l1: Fee(1),
+    };
+
+    let tx_execution_info = tx.execute(state, block_context, true, true).unwrap();
+    let execute_call_info = tx_execution_info.execute_call_info.unwrap();
+    assert_eq!(execute_call_info.call.entry_point_type, EntryPointType::L1Handler);
+    assert_eq!(execute_call_info.execution.retdata, retdata![arg]);
+    assert_eq!(tx_execution_info.validate_call_info, None);
+    assert_eq!(tx_execution_info.fee_transfer_call_info, None);
+
+    // The nonce of the contract is not read nor incremented.
+    assert_eq!(state.get_nonce_at(contract_address).unwrap(), Nonce::default());
+    assert!(state.to_state_diff().address_to_nonce.is_empty());
 }
@@ -1,9 +1,31 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 #[cfg(test)]
 #[path = "utils_test.rs"]
 pub mod test;
 
+/// How a ratio is rounded to an integer.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Rounding {
+    /// Rounds up, as the OS does.
+    #[default]
+    Ceil,
+    /// Rounds down.
+    Floor,
+}
+
+/// Returns `numerator / denominator`, rounded by the given mode.
+pub fn u64_from_ratio(numerator: u64, denominator: u64, rounding: Rounding) -> u64 {
+    assert_ne!(denominator, 0, "The denominator of a ratio must be nonzero.");
+    let quotient = numerator / denominator;
+    match rounding {
+        Rounding::Ceil if numerator % denominator != 0 => quotient + 1,
+        Rounding::Ceil | Rounding::Floor => quotient,
+    }
+}
+
 /// Returns a `HashMap` containing key-value pairs from `a` that are not included in `b` (if
 /// a key appears in `b` with a different value, it will be part of the output).
 /// Usage: Get updated items from a mapping.
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
 use pretty_assertions::assert_eq;
+use rstest::rstest;
 
-use crate::utils::subtract_mappings;
+use crate::utils::{subtract_mappings, u64_from_ratio, Rounding};
 
 #[test]
 fn test_subtract_mappings() {
@@ -18,3 +19,19 @@ fn test_subtract_mappings() {
     let expected = HashMap::from([("red", 1), ("blue", 3)]);
     assert_eq!(expected, subtract_mappings(&map1, &map2));
 }
+
+#[rstest]
+#[case::exact(4, 2, 2, 2)]
+#[case::half(1, 2, 1, 0)]
+#[case::one_and_a_half(3, 2, 2, 1)]
+#[case::just_above(7, 3, 3, 2)]
+#[case::zero(0, 5, 0, 0)]
+fn test_u64_from_ratio(
+    #[case] numerator: u64,
+    #[case] denominator: u64,
+    #[case] expected_ceil: u64,
+    #[case] expected_floor: u64,
+) {
+    assert_eq!(u64_from_ratio(numerator, denominator, Rounding::Ceil), expected_ceil);
+    assert_eq!(u64_from_ratio(numerator, denominator, Rounding::Floor), expected_floor);
+}
@@ -0,0 +1,342 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use thiserror::Error;
+
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::fee::os_usage::OsResources;
+use crate::transaction::transaction_types::TransactionType;
+use crate::utils::Rounding;
+
+#[cfg(test)]
+#[path = "versioned_constants_test.rs"]
+pub mod test;
+
+#[ctor::ctor]
+static VERSIONED_CONSTANTS_V0_13_0: Arc<VersionedConstants> =
+    load(include_str!("../resources/versioned_constants_0_13_0.json"));
+#[ctor::ctor]
+static VERSIONED_CONSTANTS_V0_13_1: Arc<VersionedConstants> =
+    load(include_str!("../resources/versioned_constants_0_13_1.json"));
+#[ctor::ctor]
+static VERSIONED_CONSTANTS_V0_13_2: Arc<VersionedConstants> =
+    load(include_str!("../resources/versioned_constants_0_13_2.json"));
+#[ctor::ctor]
+static VERSIONED_CONSTANTS_V0_13_3: Arc<VersionedConstants> =
+    load(include_str!("../resources/versioned_constants_0_13_3.json"));
+
+fn load(versioned_constants_json: &str) -> Arc<VersionedConstants> {
+    Arc::new(
+        serde_json::from_str(versioned_constants_json)
+            .expect("Versioned constants JSON must be deserializable."),
+    )
+}
+
+/// A Starknet version whose constants are embedded in the blockifier; serialized as its name (e.g.,
+/// `0.13.1`).
+#[derive(Clone, Copy, Debug, Default, Deserialize, EnumIter, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "&'static str")]
+pub enum StarknetVersion {
+    V0_13_0,
+    V0_13_1,
+    V0_13_2,
+    #[default]
+    V0_13_3,
+}
+
+impl StarknetVersion {
+    pub const LATEST: Self = Self::V0_13_3;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V0_13_0 => "0.13.0",
+            Self::V0_13_1 => "0.13.1",
+            Self::V0_13_2 => "0.13.2",
+            Self::V0_13_3 => "0.13.3",
+        }
+    }
+}
+
+impl From<StarknetVersion> for &'static str {
+    fn from(version: StarknetVersion) -> Self {
+        version.as_str()
+    }
+}
+
+impl TryFrom<&str> for StarknetVersion {
+    type Error = VersionedConstantsError;
+
+    fn try_from(version: &str) -> VersionedConstantsResult<Self> {
+        match version {
+            "0.13.0" => Ok(Self::V0_13_0),
+            "0.13.1" => Ok(Self::V0_13_1),
+            "0.13.2" => Ok(Self::V0_13_2),
+            "0.13.3" => Ok(Self::V0_13_3),
+            _ => Err(VersionedConstantsError::UnknownStarknetVersion(version.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for StarknetVersion {
+    type Error = VersionedConstantsError;
+
+    fn try_from(version: String) -> VersionedConstantsResult<Self> {
+        Self::try_from(version.as_str())
+    }
+}
+
+/// The constants of a Starknet version: execution limits, gas costs and OS resources.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VersionedConstants {
+    // Limits.
+    pub invoke_tx_max_n_steps: u32,
+    pub validate_max_n_steps: u32,
+    pub max_recursion_depth: usize,
+    // Of declared Cairo 0 classes; in felts.
+    pub declare_tx_max_bytecode_size: usize,
+    pub max_signature_length: usize,
+    pub max_calldata_length: usize,
+    // Per-transaction limits, enforced by the respective syscalls; unlimited if unset (e.g., in
+    // versions predating them).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_n_events: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_n_l2_to_l1_messages: Option<usize>,
+    // Of distinct storage cells.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_n_storage_writes: Option<usize>,
+    // Of the Cairo VM memory of the calls, including memory holes; checked after each call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_n_memory_cells: Option<usize>,
+
+    // Gas costs.
+    // L1 gas charged per key / data felt of each emitted event; zero unless events are priced.
+    pub event_key_gas_cost: usize,
+    pub event_data_gas_cost: usize,
+    // L1 gas charged per calldata / signature felt of a transaction; zero unless they are priced.
+    pub tx_data_felt_gas_cost: usize,
+    // L1 gas charged per unit of L2 (Sierra) gas consumed by the calls of a transaction; zero
+    // unless L2 gas is priced.
+    pub l2_gas_cost: f64,
+    // How the L1 gas of resources with fractional weights (Cairo resources, L2 gas) is rounded; up,
+    // as the OS does, unless replaying versions that rounded down.
+    pub gas_rounding: Rounding,
+    // The L1 gas weight of each Cairo resource (steps and builtin instances); the VM resources of a
+    // transaction are charged by the heaviest one.
+    pub vm_resource_fee_cost: HashMap<String, f64>,
+    // The syscalls available in the version; all implemented syscalls, if unset. Others fail as
+    // unknown selectors, as they did in the version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_syscalls: Option<BTreeSet<DeprecatedSyscallSelector>>,
+    // The extra execution resources of transactions and syscalls in the OS.
+    pub os_resources: OsResources,
+}
+
+impl VersionedConstants {
+    /// Returns the constants of the given Starknet version.
+    pub fn get(version: StarknetVersion) -> &'static Self {
+        Self::shared(version)
+    }
+
+    pub fn latest() -> &'static Self {
+        Self::get(StarknetVersion::LATEST)
+    }
+
+    /// Returns the constants of the given Starknet version, to be shared by block contexts.
+    pub(crate) fn shared(version: StarknetVersion) -> &'static Arc<Self> {
+        match version {
+            StarknetVersion::V0_13_0 => &VERSIONED_CONSTANTS_V0_13_0,
+            StarknetVersion::V0_13_1 => &VERSIONED_CONSTANTS_V0_13_1,
+            StarknetVersion::V0_13_2 => &VERSIONED_CONSTANTS_V0_13_2,
+            StarknetVersion::V0_13_3 => &VERSIONED_CONSTANTS_V0_13_3,
+        }
+    }
+
+    /// Returns the constants of the `base` version, with the given overrides.
+    pub fn with_overrides(
+        base: StarknetVersion,
+        overrides: VersionedConstantsOverrides,
+    ) -> VersionedConstantsResult<Self> {
+        let mut versioned_constants = Self::get(base).clone();
+        if let Some(json_overrides) = &overrides.json_overrides {
+            let mut versioned_constants_json = versioned_constants.to_json();
+            merge_json(&mut versioned_constants_json, json_overrides);
+            versioned_constants = serde_json::from_value(versioned_constants_json)?;
+        }
+        overrides.apply_typed(&mut versioned_constants);
+        versioned_constants.os_resources.verify_complete()?;
+
+        Ok(versioned_constants)
+    }
+
+    pub fn is_syscall_allowed(&self, selector: DeprecatedSyscallSelector) -> bool {
+        self.allowed_syscalls
+            .as_ref()
+            .map_or(true, |allowed_syscalls| allowed_syscalls.contains(&selector))
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Versioned constants must be serializable.")
+    }
+}
+
+/// Overrides of the constants of a Starknet version; unset values are taken from the version.
+/// The commonly tuned values are typed; any other value (e.g., the OS resources of a syscall) can
+/// be overridden by a JSON merge patch of the version's constants, applied first.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct VersionedConstantsOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoke_tx_max_n_steps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_max_n_steps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_recursion_depth: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_signature_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_calldata_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_key_gas_cost: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_data_gas_cost: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_data_felt_gas_cost: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_gas_cost: Option<f64>,
+    // Merged deeply into the version's constants: objects are merged key by key, `null` removes a
+    // key, and any other value replaces the original one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_overrides: Option<serde_json::Value>,
+}
+
+impl VersionedConstantsOverrides {
+    /// Returns the overrides that turn the `base` constants into the given ones.
+    pub fn from_diff(base: &VersionedConstants, versioned_constants: &VersionedConstants) -> Self {
+        fn diff<T: PartialEq + Copy>(base: T, value: T) -> Option<T> {
+            (base != value).then_some(value)
+        }
+
+        let mut overrides = Self {
+            invoke_tx_max_n_steps: diff(
+                base.invoke_tx_max_n_steps,
+                versioned_constants.invoke_tx_max_n_steps,
+            ),
+            validate_max_n_steps: diff(
+                base.validate_max_n_steps,
+                versioned_constants.validate_max_n_steps,
+            ),
+            max_recursion_depth: diff(
+                base.max_recursion_depth,
+                versioned_constants.max_recursion_depth,
+            ),
+            max_signature_length: diff(
+                base.max_signature_length,
+                versioned_constants.max_signature_length,
+            ),
+            max_calldata_length: diff(
+                base.max_calldata_length,
+                versioned_constants.max_calldata_length,
+            ),
+            event_key_gas_cost: diff(
+                base.event_key_gas_cost,
+                versioned_constants.event_key_gas_cost,
+            ),
+            event_data_gas_cost: diff(
+                base.event_data_gas_cost,
+                versioned_constants.event_data_gas_cost,
+            ),
+            tx_data_felt_gas_cost: diff(
+                base.tx_data_felt_gas_cost,
+                versioned_constants.tx_data_felt_gas_cost,
+            ),
+            l2_gas_cost: diff(base.l2_gas_cost, versioned_constants.l2_gas_cost),
+            json_overrides: None,
+        };
+        // The remaining differences.
+        let mut typed_base = base.clone();
+        overrides.apply_typed(&mut typed_base);
+        overrides.json_overrides = json_diff(&typed_base.to_json(), &versioned_constants.to_json());
+
+        overrides
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    fn apply_typed(&self, versioned_constants: &mut VersionedConstants) {
+        fn apply<T: Copy>(value: &mut T, override_value: Option<T>) {
+            if let Some(override_value) = override_value {
+                *value = override_value;
+            }
+        }
+
+        apply(&mut versioned_constants.invoke_tx_max_n_steps, self.invoke_tx_max_n_steps);
+        apply(&mut versioned_constants.validate_max_n_steps, self.validate_max_n_steps);
+        apply(&mut versioned_constants.max_recursion_depth, self.max_recursion_depth);
+        apply(&mut versioned_constants.max_signature_length, self.max_signature_length);
+        apply(&mut versioned_constants.max_calldata_length, self.max_calldata_length);
+        apply(&mut versioned_constants.event_key_gas_cost, self.event_key_gas_cost);
+        apply(&mut versioned_constants.event_data_gas_cost, self.event_data_gas_cost);
+        apply(&mut versioned_constants.tx_data_felt_gas_cost, self.tx_data_felt_gas_cost);
+        apply(&mut versioned_constants.l2_gas_cost, self.l2_gas_cost);
+    }
+}
+
+/// Applies a JSON merge patch (RFC 7396).
+fn merge_json(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(Default::default());
+    }
+    let target = target.as_object_mut().expect("The target was just made an object.");
+    for (key, patch_value) in patch {
+        if patch_value.is_null() {
+            target.remove(key);
+        } else {
+            merge_json(target.entry(key).or_insert(serde_json::Value::Null), patch_value);
+        }
+    }
+}
+
+/// Returns the JSON merge patch that turns `base` into `value`; [None] if they are equal.
+fn json_diff(base: &serde_json::Value, value: &serde_json::Value) -> Option<serde_json::Value> {
+    if base == value {
+        return None;
+    }
+    let (serde_json::Value::Object(base), serde_json::Value::Object(value)) = (base, value) else {
+        return Some(value.clone());
+    };
+
+    let mut patch: serde_json::Map<String, serde_json::Value> = value
+        .iter()
+        .filter_map(|(key, value)| match base.get(key) {
+            Some(base_value) => json_diff(base_value, value).map(|patch| (key.clone(), patch)),
+            None => Some((key.clone(), value.clone())),
+        })
+        .collect();
+    for key in base.keys().filter(|key| !value.contains_key(*key)) {
+        patch.insert(key.clone(), serde_json::Value::Null);
+    }
+
+    Some(serde_json::Value::Object(patch))
+}
+
+#[derive(Debug, Error)]
+pub enum VersionedConstantsError {
+    #[error("Unknown Starknet version: {0}.")]
+    UnknownStarknetVersion(String),
+    #[error("Invalid versioned constants overrides: {0}")]
+    InvalidOverrides(#[from] serde_json::Error),
+    #[error("Syscall {selector:?} must have OS resources.")]
+    MissingSyscallOsResources { selector: DeprecatedSyscallSelector },
+    #[error("Transaction type {tx_type:?} must have OS resources.")]
+    MissingTxOsResources { tx_type: TransactionType },
+}
+
+pub type VersionedConstantsResult<T> = Result<T, VersionedConstantsError>;
@@ -0,0 +1,237 @@
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::transaction::{Calldata, Fee};
+use starknet_api::{calldata, contract_address, patricia_key, stark_felt};
+use strum::IntoEnumIterator;
+
+use crate::abi::constants;
+use crate::block_context::BlockContext;
+use crate::execution::deprecated_syscalls::hint_processor::SyscallCounter;
+use crate::execution::deprecated_syscalls::DeprecatedSyscallSelector;
+use crate::execution::entry_point::EntryPointExecutionContext;
+use crate::fee::os_usage::get_additional_os_resources;
+use crate::selector;
+use crate::test_utils::{
+    NonceManager, MAX_FEE, TEST_ACCOUNT_CONTRACT_ADDRESS, TEST_CONTRACT_ADDRESS,
+};
+use crate::transaction::test_utils::{
+    account_invoke_tx, create_state_with_trivial_validation_account,
+};
+use crate::transaction::transaction_types::TransactionType;
+use crate::transaction::transactions::ExecutableTransaction;
+use crate::versioned_constants::{
+    StarknetVersion, VersionedConstants, VersionedConstantsError, VersionedConstantsOverrides,
+};
+
+#[test]
+fn test_load_embedded_versions() {
+    for (version, version_str) in
+        StarknetVersion::iter().zip(["0.13.0", "0.13.1", "0.13.2", "0.13.3"])
+    {
+        assert_eq!(StarknetVersion::try_from(version_str).unwrap(), version);
+        let versioned_constants = VersionedConstants::get(version);
+        assert!(versioned_constants.invoke_tx_max_n_steps > 0);
+        assert!(versioned_constants.validate_max_n_steps > 0);
+    }
+    assert_eq!(
+        VersionedConstants::latest().invoke_tx_max_n_steps,
+        VersionedConstants::get(StarknetVersion::V0_13_3).invoke_tx_max_n_steps
+    );
+}
+
+#[test]
+fn test_unknown_version() {
+    assert_matches!(
+        StarknetVersion::try_from("0.12.3").unwrap_err(),
+        VersionedConstantsError::UnknownStarknetVersion(version) if version == "0.12.3"
+    );
+}
+
+#[test]
+fn test_versions_differ() {
+    let [v0_13_0, v0_13_1, v0_13_2, v0_13_3] = [
+        StarknetVersion::V0_13_0,
+        StarknetVersion::V0_13_1,
+        StarknetVersion::V0_13_2,
+        StarknetVersion::V0_13_3,
+    ]
+    .map(VersionedConstants::get);
+    assert_eq!(v0_13_0.invoke_tx_max_n_steps, 3_000_000);
+    assert_eq!(v0_13_1.invoke_tx_max_n_steps, 4_000_000);
+    // Events are priced as of 0.13.1, calldata and signatures as of 0.13.2, and L2 gas as of
+    // 0.13.3.
+    assert_eq!((v0_13_0.event_key_gas_cost, v0_13_1.event_key_gas_cost), (0, 2));
+    assert_eq!((v0_13_1.tx_data_felt_gas_cost, v0_13_2.tx_data_felt_gas_cost), (0, 1));
+    assert_eq!((v0_13_2.l2_gas_cost, v0_13_3.l2_gas_cost), (0.0, 0.000025));
+    // The OS resources of a transaction grow with its calldata as of 0.13.1.
+    let invoke_os_steps = |versioned_constants: &VersionedConstants, calldata_length| {
+        versioned_constants
+            .os_resources
+            .os_resources_for_tx(TransactionType::InvokeFunction, calldata_length, false)
+            .n_steps
+    };
+    assert_eq!([0, 10].map(|length| invoke_os_steps(v0_13_0, length)), [3363, 3363]);
+    assert_eq!([0, 10].map(|length| invoke_os_steps(v0_13_1, length)), [3763, 3843]);
+}
+
+/// The same invoke costs more under each version than under the previous one; from 0.13.0 to
+/// 0.13.1, its charged steps grow by the growth of the OS resources of it and of its syscalls.
+#[test]
+fn test_fee_by_version() {
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS),     // Contract address.
+        selector!("test_storage_read_write").0, // EP selector.
+        stark_felt!(2_u8),                      // Calldata length.
+        stark_felt!(1_u8),                      // Calldata: address.
+        stark_felt!(7_u8)                       // Calldata: value.
+    ];
+    let calldata_length = execute_calldata.0.len();
+    let execution_infos: Vec<_> = StarknetVersion::iter()
+        .map(|version| {
+            let block_context = BlockContext::create_for_account_testing_with_version(version);
+            let account_tx = account_invoke_tx(
+                execute_calldata.clone(),
+                contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+                &mut NonceManager::default(),
+                Fee(MAX_FEE),
+            );
+            let state = &mut create_state_with_trivial_validation_account();
+            account_tx.execute(state, &block_context, true, true).unwrap()
+        })
+        .collect();
+    for consecutive_versions in execution_infos.windows(2) {
+        assert!(consecutive_versions[1].receipt.fee > consecutive_versions[0].receipt.fee);
+    }
+
+    // The syscalls of the validation and the execution (the fee transfer is not charged for).
+    let tx_execution_info = &execution_infos[0];
+    let mut syscall_counter = SyscallCounter::new();
+    for call_info in [&tx_execution_info.validate_call_info, &tx_execution_info.execute_call_info]
+        .into_iter()
+        .flatten()
+        .flat_map(|call_info| call_info.into_iter())
+    {
+        for (selector, count) in &call_info.syscall_counter {
+            *syscall_counter.entry(*selector).or_default() += count;
+        }
+    }
+    let [(v0_13_0_steps, v0_13_0_os_steps), (v0_13_1_steps, v0_13_1_os_steps)] =
+        [StarknetVersion::V0_13_0, StarknetVersion::V0_13_1].map(|version| {
+            let os_steps = get_additional_os_resources(
+                &VersionedConstants::get(version).os_resources,
+                &syscall_counter,
+                TransactionType::InvokeFunction,
+                calldata_length,
+                false,
+            )
+            .unwrap()
+            .n_steps;
+            let steps = execution_infos[version as usize].actual_resources.vm_resources.0
+                [constants::N_STEPS_RESOURCE];
+            (steps, os_steps)
+        });
+    assert!(v0_13_1_os_steps > v0_13_0_os_steps);
+    assert_eq!(v0_13_1_steps - v0_13_0_steps, v0_13_1_os_steps - v0_13_0_os_steps);
+}
+
+/// The same transaction is granted the invoke step limit of the block's Starknet version.
+#[test]
+fn test_max_steps_by_version() {
+    let execute_calldata = calldata![
+        stark_felt!(TEST_CONTRACT_ADDRESS), // Contract address.
+        selector!("return_result").0,       // EP selector.
+        stark_felt!(1_u8),                  // Calldata length.
+        stark_felt!(2_u8)                   // Calldata: num.
+    ];
+    // Without a max fee, the steps are only bounded by the version.
+    let account_tx = account_invoke_tx(
+        execute_calldata,
+        contract_address!(TEST_ACCOUNT_CONTRACT_ADDRESS),
+        &mut NonceManager::default(),
+        Fee(0),
+    );
+    let account_tx_context = account_tx.get_account_transaction_context();
+
+    let max_steps_by_version =
+        [StarknetVersion::V0_13_0, StarknetVersion::V0_13_1].map(|version| {
+            let mut block_context = BlockContext::create_for_account_testing();
+            block_context.versioned_constants = VersionedConstants::shared(version).clone();
+            EntryPointExecutionContext::max_invoke_steps(&block_context, &account_tx_context)
+                .unwrap()
+        });
+    assert_eq!(max_steps_by_version, [3_000_000, 4_000_000]);
+}
+
+#[test]
+fn test_typed_override() {
+    let overrides =
+        VersionedConstantsOverrides { invoke_tx_max_n_steps: Some(5), ..Default::default() };
+    let versioned_constants =
+        VersionedConstants::with_overrides(StarknetVersion::V0_13_1, overrides.clone()).unwrap();
+
+    let base = VersionedConstants::get(StarknetVersion::V0_13_1);
+    let mut expected_json = base.to_json();
+    expected_json["invoke_tx_max_n_steps"] = json!(5);
+    assert_eq!(versioned_constants.to_json(), expected_json);
+    assert_eq!(VersionedConstantsOverrides::from_diff(base, &versioned_constants), overrides);
+}
+
+#[test]
+fn test_json_override() {
+    let overrides = VersionedConstantsOverrides {
+        json_overrides: Some(json!({
+            "os_resources": {"execute_syscalls": {"CallContract": {"n_steps": 1}}}
+        })),
+        ..Default::default()
+    };
+    let versioned_constants =
+        VersionedConstants::with_overrides(StarknetVersion::LATEST, overrides.clone()).unwrap();
+
+    // Only the overridden value changes; its siblings (including the other resources of the same
+    // syscall) are intact.
+    let base = VersionedConstants::latest();
+    let mut expected_json = base.to_json();
+    expected_json["os_resources"]["execute_syscalls"]["CallContract"]["n_steps"] = json!(1);
+    assert_eq!(versioned_constants.to_json(), expected_json);
+    assert_eq!(VersionedConstantsOverrides::from_diff(base, &versioned_constants), overrides);
+}
+
+#[test]
+fn test_invalid_json_override() {
+    let overrides = VersionedConstantsOverrides {
+        json_overrides: Some(json!({"max_recursion_depth": "deep"})),
+        ..Default::default()
+    };
+    assert_matches!(
+        VersionedConstants::with_overrides(StarknetVersion::LATEST, overrides).unwrap_err(),
+        VersionedConstantsError::InvalidOverrides(_)
+    );
+}
+
+#[test]
+fn test_json_override_removing_os_resources() {
+    let with_json_overrides = |json_overrides: serde_json::Value| {
+        let overrides = VersionedConstantsOverrides {
+            json_overrides: Some(json_overrides),
+            ..Default::default()
+        };
+        VersionedConstants::with_overrides(StarknetVersion::LATEST, overrides)
+    };
+
+    // A null in a merge patch removes the entry.
+    assert_matches!(
+        with_json_overrides(json!({"os_resources": {"execute_syscalls": {"CallContract": null}}}))
+            .unwrap_err(),
+        VersionedConstantsError::MissingSyscallOsResources {
+            selector: DeprecatedSyscallSelector::CallContract
+        }
+    );
+    assert_matches!(
+        with_json_overrides(json!({"os_resources": {"execute_txs_inner": {"L1Handler": null}}}))
+            .unwrap_err(),
+        VersionedConstantsError::MissingTxOsResources { tx_type: TransactionType::L1Handler }
+    );
+}
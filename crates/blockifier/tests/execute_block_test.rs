@@ -0,0 +1,44 @@
+use std::fs;
+use std::process::{Command, Output};
+
+use pretty_assertions::assert_eq;
+
+const FIXTURES_DIR: &str = "resources/testing/execute_block";
+const BLOCK_CONTEXT_FIXTURE_PATH: &str = "resources/testing/block_context.json";
+const FIX_COMMAND: &str = "FIX_EXECUTE_BLOCK_OUTPUT=1 cargo test -p blockifier --features testing \
+                           --test execute_block_test";
+
+fn execute_block(extra_args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_execute_block"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg(format!("{FIXTURES_DIR}/state.json"))
+        .arg(BLOCK_CONTEXT_FIXTURE_PATH)
+        .arg(format!("{FIXTURES_DIR}/txs.json"))
+        .args(extra_args)
+        .output()
+        .unwrap()
+}
+
+// The fixture block deploys an account, declares a class, transfers to the deployed account, and
+// has one rejected transaction.
+#[test]
+fn test_execute_block() {
+    let output = execute_block(&["--allow-rejections"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected_output_path =
+        format!("{}/{FIXTURES_DIR}/expected_output.json", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var("FIX_EXECUTE_BLOCK_OUTPUT").is_ok() {
+        fs::write(&expected_output_path, &stdout).unwrap();
+    }
+    let expected_output = fs::read_to_string(expected_output_path).unwrap();
+    assert_eq!(stdout, expected_output, "To fix the expected output, run: {FIX_COMMAND}.");
+}
+
+#[test]
+fn test_execute_block_with_rejections() {
+    let output = execute_block(&[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "1 transaction(s) rejected.\n");
+}
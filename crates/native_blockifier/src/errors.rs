@@ -1,6 +1,9 @@
+use blockifier::block_context::BlockContextError;
+use blockifier::block_execution::BlockExecutionError;
 use blockifier::state::errors::StateError;
 use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::transaction_types::TransactionType;
+use blockifier::versioned_constants::VersionedConstantsError;
 use cairo_vm::types::errors::program_errors::ProgramError;
 use pyo3::create_exception;
 use pyo3::exceptions::PyException;
@@ -57,6 +60,7 @@ macro_rules! native_blockifier_errors {
 }
 
 native_blockifier_errors!(
+    (BlockExecutionError, BlockExecutionError, PyBlockExecutionError),
     (NativeBlockifierInputError, NativeBlockifierInputError, PyNativeBlockifierInputError),
     (ProgramError, ProgramError, PyProgramError),
     (Pyo3Error, PyErr, PyPyo3Error),
@@ -69,6 +73,10 @@ native_blockifier_errors!(
 
 #[derive(Debug, Error)]
 pub enum NativeBlockifierInputError {
+    #[error(transparent)]
+    InvalidBlockContext(#[from] BlockContextError),
+    #[error(transparent)]
+    InvalidStarknetVersion(#[from] VersionedConstantsError),
     #[error(transparent)]
     ProgramError(#[from] ProgramError),
     #[error("Transaction of type {tx_type:?} is unsupported in version {version}.")]
@@ -1,13 +1,14 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use blockifier::block_context::BlockContext;
+use blockifier::block_context::{BlockContext, BlockContextBuilder, FeeTokenAddresses, GasPrices};
 use blockifier::state::cached_state::GlobalContractCache;
+use blockifier::versioned_constants::StarknetVersion;
 use pyo3::prelude::*;
 use starknet_api::block::{BlockNumber, BlockTimestamp};
 use starknet_api::core::{ChainId, ContractAddress};
 
-use crate::errors::NativeBlockifierResult;
+use crate::errors::{NativeBlockifierInputError, NativeBlockifierResult};
 use crate::py_state_diff::{PyBlockInfo, PyStateDiff};
 use crate::py_transaction_execution_info::{PyTransactionExecutionInfo, PyVmExecutionResources};
 use crate::py_utils::{int_to_chain_id, PyFelt};
@@ -221,6 +222,7 @@ pub struct PyOsConfig {
     #[pyo3(from_py_with = "int_to_chain_id")]
     pub chain_id: ChainId,
     pub fee_token_address: PyFelt,
+    pub strk_fee_token_address: PyFelt,
 }
 
 pub fn into_block_context(
@@ -230,18 +232,41 @@ pub fn into_block_context(
 ) -> NativeBlockifierResult<BlockContext> {
     let starknet_os_config = general_config.starknet_os_config.clone();
     let block_number = BlockNumber(block_info.block_number);
-    let block_context = BlockContext {
-        chain_id: starknet_os_config.chain_id,
-        block_number,
-        block_timestamp: BlockTimestamp(block_info.block_timestamp),
-        sequencer_address: ContractAddress::try_from(block_info.sequencer_address.0)?,
-        fee_token_address: ContractAddress::try_from(starknet_os_config.fee_token_address.0)?,
-        vm_resource_fee_cost: general_config.cairo_resource_fee_weights.clone(),
-        gas_price: block_info.gas_price,
-        invoke_tx_max_n_steps: general_config.invoke_tx_max_n_steps,
-        validate_max_n_steps: general_config.validate_max_n_steps,
-        max_recursion_depth,
+    let starknet_version = StarknetVersion::try_from(block_info.starknet_version.as_str())
+        .map_err(NativeBlockifierInputError::from)?;
+    let gas_prices = GasPrices::new(
+        block_info.gas_price,
+        block_info.strk_gas_price,
+        block_info.data_gas_price,
+        block_info.strk_data_gas_price,
+        // Old blocks carry no STRK gas prices, and blocks that publish their state diff in
+        // calldata carry no data gas prices.
+        true,
+    )
+    .map_err(NativeBlockifierInputError::from)?;
+    let fee_token_addresses = FeeTokenAddresses {
+        strk_fee_token_address: ContractAddress::try_from(
+            starknet_os_config.strk_fee_token_address.0,
+        )?,
+        eth_fee_token_address: ContractAddress::try_from(starknet_os_config.fee_token_address.0)?,
     };
+    let block_context = BlockContextBuilder::new(
+        starknet_os_config.chain_id.into(),
+        block_number,
+        BlockTimestamp(block_info.block_timestamp),
+        ContractAddress::try_from(block_info.sequencer_address.0)?,
+        gas_prices,
+        fee_token_addresses,
+    )
+    .allow_zero_gas_prices(true)
+    .require_positive_block_number(false)
+    .starknet_version(starknet_version)
+    .vm_resource_fee_cost(general_config.cairo_resource_fee_weights.as_ref().clone())
+    .use_kzg_da(block_info.use_kzg_da)
+    .max_n_steps(general_config.invoke_tx_max_n_steps, general_config.validate_max_n_steps)
+    .max_recursion_depth(max_recursion_depth)
+    .build()
+    .map_err(NativeBlockifierInputError::from)?;
 
     Ok(block_context)
 }
@@ -119,5 +119,11 @@ pub struct PyBlockInfo {
     pub block_number: u64,
     pub block_timestamp: u64,
     pub gas_price: u128,
+    pub strk_gas_price: u128,
+    pub data_gas_price: u128,
+    pub strk_data_gas_price: u128,
     pub sequencer_address: PyFelt,
+    pub use_kzg_da: bool,
+    // E.g., `0.13.1`; selects the versioned constants of the block.
+    pub starknet_version: String,
 }
@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
+use blockifier::block_context::BlockContext;
 use blockifier::execution::entry_point::{CallInfo, OrderedEvent, OrderedL2ToL1Message};
 use blockifier::transaction::objects::TransactionExecutionInfo;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources as VmExecutionResources;
 use pyo3::prelude::*;
 
+use crate::errors::NativeBlockifierResult;
 use crate::py_utils::{to_py_vec, PyFelt};
 
 #[pyclass]
@@ -24,17 +26,22 @@ pub struct PyTransactionExecutionInfo {
     pub revert_error: Option<String>,
 }
 
-impl From<TransactionExecutionInfo> for PyTransactionExecutionInfo {
-    // TODO(Gilad, 1/4/2023): Check that everything can't fail, recursively.
-    fn from(info: TransactionExecutionInfo) -> Self {
-        Self {
+impl PyTransactionExecutionInfo {
+    /// Reports the charged fee, and the resources consumed by the transaction in the flat
+    /// resources mapping form.
+    pub fn new(
+        info: TransactionExecutionInfo,
+        block_context: &BlockContext,
+    ) -> NativeBlockifierResult<Self> {
+        // TODO(Gilad, 1/4/2023): Check that everything can't fail, recursively.
+        Ok(Self {
             validate_call_info: info.validate_call_info.map(PyCallInfo::from),
             execute_call_info: info.execute_call_info.map(PyCallInfo::from),
             fee_transfer_call_info: info.fee_transfer_call_info.map(PyCallInfo::from),
-            actual_fee: info.actual_fee.0,
-            actual_resources: info.actual_resources.0,
+            actual_fee: info.receipt.fee.0,
+            actual_resources: info.actual_resources.to_resources_mapping(block_context)?.0,
             revert_error: info.revert_error,
-        }
+        })
     }
 }
 
@@ -46,6 +46,9 @@ impl TransactionExecutor {
         let reader = papyrus_storage.reader().clone();
 
         let block_context = into_block_context(general_config, block_info, max_recursion_depth)?;
+        // Transactions are always charged (see `execute`).
+        let enforce_fee = true;
+        block_context.validate(enforce_fee)?;
         let state = CachedState::new(
             PapyrusReader::new(reader, block_context.block_number),
             global_contract_cache,
@@ -79,13 +82,15 @@ impl TransactionExecutor {
             Ok(tx_execution_info) => {
                 tx_executed_class_hashes.extend(tx_execution_info.get_executed_class_hashes());
 
+                let py_tx_execution_info =
+                    PyTransactionExecutionInfo::new(tx_execution_info, &self.block_context)?;
                 let py_tx_execution_info = Python::with_gil(|py| {
                     // Allocate this instance on the Python heap.
                     // This is necessary in order to pass a reference to it to the callback
                     // (otherwise, if it were allocated on Rust's heap/stack, giving Python
                     // a reference to the objects will not
                     // work).
-                    Py::new(py, PyTransactionExecutionInfo::from(tx_execution_info))
+                    Py::new(py, py_tx_execution_info)
                         .expect("Should be able to allocate on Python heap")
                 });
 
@@ -150,9 +155,7 @@ impl TransactionExecutor {
         let old_block_number_and_hash = old_block_number_and_hash
             .map(|(block_number, block_hash)| (BlockNumber(block_number), BlockHash(block_hash.0)));
 
-        pre_process_block(&mut self.state, old_block_number_and_hash);
-
-        Ok(())
+        Ok(pre_process_block(&mut self.state, &self.block_context, old_block_number_and_hash)?)
     }
 }
 